@@ -6,16 +6,32 @@
 
 #include <pxr/usd/usd/stage.h>
 #include <pxr/usd/usd/primRange.h>
+#include <pxr/usd/usdGeom/basisCurves.h>
+#include <pxr/usd/usdGeom/camera.h>
+#include <pxr/usd/usdGeom/points.h>
 #include <pxr/usd/usdGeom/mesh.h>
 #include <pxr/usd/usdGeom/pointInstancer.h>
 #include <pxr/usd/usdGeom/xformCache.h>
+#include <pxr/usd/usdGeom/imageable.h>
 #include <pxr/usd/usdGeom/primvarsAPI.h>
+#include <pxr/usd/usdGeom/subset.h>
+#include <pxr/usd/usdLux/cylinderLight.h>
+#include <pxr/usd/usdLux/diskLight.h>
+#include <pxr/usd/usdLux/distantLight.h>
+#include <pxr/usd/usdLux/domeLight.h>
+#include <pxr/usd/usdLux/lightAPI.h>
+#include <pxr/usd/usdLux/rectLight.h>
+#include <pxr/usd/usdLux/shapingAPI.h>
+#include <pxr/usd/usdLux/sphereLight.h>
+#include <pxr/usd/usdLux/tokens.h>
 #include <pxr/usd/usdShade/material.h>
 #include <pxr/usd/usdShade/materialBindingAPI.h>
 #include <pxr/usd/usdShade/shader.h>
+#include <pxr/usd/usdShade/utils.h>
 #include <pxr/base/gf/matrix4f.h>
 #include <pxr/base/gf/vec2f.h>
 #include <pxr/base/gf/vec3f.h>
+#include <pxr/base/gf/vec4f.h>
 #include <pxr/base/gf/quath.h>
 #include <pxr/base/vt/array.h>
 
@@ -23,6 +39,8 @@
 #include <string>
 #include <memory>
 #include <iostream>
+#include <functional>
+#include <unordered_map>
 
 PXR_NAMESPACE_USING_DIRECTIVE
 
@@ -38,6 +56,19 @@ struct CachedMesh {
     std::vector<float> normals;
     std::vector<float> uvs;  // u,v pairs from primvars:st
     GfMatrix4d transform;
+    std::vector<int32_t> triangle_subsets;  // Per-triangle GeomSubset index (-1 = none)
+    std::vector<std::string> subset_material_paths;  // Bound material per GeomSubset
+    int32_t subdivision_scheme = 0;  // 0 = none, 1 = catmullClark, 2 = other
+    std::vector<uint32_t> face_vertex_counts;   // Control cage (subdivision meshes only)
+    std::vector<uint32_t> face_vertex_indices;
+    std::vector<uint32_t> crease_indices;
+    std::vector<uint32_t> crease_lengths;
+    std::vector<float> crease_sharpnesses;
+    std::vector<uint32_t> corner_indices;
+    std::vector<float> corner_sharpnesses;
+    float displacement_bound = -1.0f;  // RenderMan displacement bound (negative = unset)
+    bool visible = true;               // Not invisible and of default/render purpose
+    uint32_t ray_visibility = 0x1f;    // Ray types from primvars:bif:visibility:*
 };
 
 /// Cached instancer data for FFI transfer
@@ -47,6 +78,46 @@ struct CachedInstancer {
     std::vector<const char*> prototype_path_ptrs;  // For C API
     std::vector<float> transforms;
     std::vector<int32_t> proto_indices;
+    bool visible = true;
+    uint32_t ray_visibility = 0x1f;
+};
+
+/// Cached basis curves data for FFI transfer
+struct CachedCurves {
+    std::string path;
+    std::vector<float> points;
+    std::vector<uint32_t> curve_vertex_counts;
+    std::vector<float> widths;
+    int32_t curve_type = 1;  // 0 = linear, 1 = cubic
+    int32_t basis = 0;       // 0 = bezier, 1 = bspline, 2 = catmullRom
+    bool has_normals = false;
+    GfMatrix4d transform;
+    std::string material_path;
+};
+
+/// Cached points data for FFI transfer
+struct CachedPoints {
+    std::string path;
+    std::vector<float> points;
+    std::vector<float> widths;
+    GfMatrix4d transform;
+    std::string material_path;
+};
+
+/// Cached light data for FFI transfer
+struct CachedLight {
+    std::string path;
+    UsdBridgeLightData data;  // String fields are set on transfer
+    GfMatrix4d transform;
+    std::string texture_file;
+    std::string ies_file;
+};
+
+/// Cached camera data for FFI transfer
+struct CachedCamera {
+    std::string path;
+    UsdBridgeCameraData data;  // Path is set on transfer
+    GfMatrix4d transform;
 };
 
 /// Cached prim info for scene browser
@@ -60,6 +131,24 @@ struct CachedPrimInfo {
     std::vector<const char*> child_path_ptrs;  // For C API
 };
 
+/// Cached shader input for FFI transfer
+struct CachedShaderInput {
+    std::string name;
+    int kind;  // UsdBridgeShaderValueKind
+    float value[3];
+    std::string string_value;
+    int connected_node;  // -1 if unconnected
+    std::string connected_output;
+};
+
+/// Cached shader node (one prim in a material network)
+struct CachedShaderNode {
+    std::string path;
+    std::string shader_id;
+    std::vector<CachedShaderInput> inputs;
+    std::vector<UsdBridgeShaderInput> input_views;  // For C API
+};
+
 /// Cached material data for FFI transfer (UsdPreviewSurface or MaterialX)
 struct CachedMaterial {
     std::string path;
@@ -68,14 +157,23 @@ struct CachedMaterial {
     float roughness;
     float specular;
     float opacity;
+    float opacity_threshold;  // UsdPreviewSurface cutout threshold (0 = blend)
     float emissive_color[3];
     std::string diffuse_texture;
     std::string roughness_texture;
     std::string metallic_texture;
     std::string normal_texture;
     std::string emissive_texture;
+    std::string opacity_texture;
+    int opacity_texture_channel;  // Texture channel feeding opacity (0-3)
+    std::string displacement_texture;
+    int displacement_texture_channel;  // Texture channel feeding displacement (0-3)
+    float displacement_scale;  // Texture reader scale for the displacement channel
+    float displacement_bias;  // Texture reader bias for the displacement channel
+    bool vector_displacement;  // True if displacement is a vector3 input
     std::string material_path_for_mesh;  // Per-mesh material binding
     bool is_materialx;  // True if material is from MaterialX, false for UsdPreviewSurface
+    std::vector<CachedShaderNode> graph_nodes;  // MaterialX network, [0] = surface shader
 };
 
 /// Internal stage representation
@@ -83,6 +181,10 @@ struct UsdBridgeStage {
     UsdStageRefPtr stage;
     std::vector<CachedMesh> meshes;
     std::vector<CachedInstancer> instancers;
+    std::vector<CachedCurves> curves;
+    std::vector<CachedPoints> points;
+    std::vector<CachedLight> lights;
+    std::vector<CachedCamera> cameras;
     std::vector<CachedMaterial> materials;
     std::vector<std::string> mesh_material_paths;  // Material path per mesh
     std::vector<CachedPrimInfo> all_prims;  // All prims in traversal order
@@ -91,13 +193,30 @@ struct UsdBridgeStage {
     bool cached;
     bool prims_cached;
     bool materials_cached;
+    bool has_time;  // Set by usd_bridge_set_time
+    double time;
 
-    UsdBridgeStage() : cached(false), prims_cached(false), materials_cached(false) {}
+    UsdBridgeStage()
+        : cached(false), prims_cached(false), materials_cached(false), has_time(false), time(0.0) {}
+
+    /// Time to read points and topology at (earliest sample unless set)
+    UsdTimeCode points_time() const {
+        return has_time ? UsdTimeCode(time) : UsdTimeCode::EarliestTime();
+    }
+
+    /// Time to read transforms and instancers at (default values unless set)
+    UsdTimeCode transform_time() const {
+        return has_time ? UsdTimeCode(time) : UsdTimeCode::Default();
+    }
 
     ~UsdBridgeStage() {
         // Clear cached data to ensure proper cleanup
         meshes.clear();
         instancers.clear();
+        curves.clear();
+        points.clear();
+        lights.clear();
+        cameras.clear();
         materials.clear();
         mesh_material_paths.clear();
         all_prims.clear();
@@ -136,6 +255,42 @@ static void triangulate_mesh(
     }
 }
 
+/// Copy a non-negative int array attribute into a uint32 vector
+static void copy_indices(const VtArray<int>& values, std::vector<uint32_t>& out) {
+    out.reserve(values.size());
+    for (int v : values) {
+        out.push_back(static_cast<uint32_t>(v < 0 ? 0 : v));
+    }
+}
+
+/// Ray type names in `primvars:bif:visibility:<name>`, in bit order
+static const char* const RAY_VISIBILITY_NAMES[] = {
+    "camera", "shadow", "diffuse", "specular", "transmission"};
+
+/// Whether a prim is rendered: not invisible and of default or render purpose
+static bool is_rendered(const UsdPrim& prim) {
+    UsdGeomImageable imageable(prim);
+    if (imageable.ComputeVisibility() == UsdGeomTokens->invisible) return false;
+    TfToken purpose = imageable.ComputePurpose();
+    return purpose == UsdGeomTokens->default_ || purpose == UsdGeomTokens->render;
+}
+
+/// Ray visibility mask from (inherited) `primvars:bif:visibility:*` bools
+static uint32_t ray_visibility(const UsdPrim& prim) {
+    UsdGeomPrimvarsAPI primvars(prim);
+    uint32_t mask = 0x1f;
+    for (uint32_t bit = 0; bit < 5; ++bit) {
+        UsdGeomPrimvar primvar = primvars.FindPrimvarWithInheritance(
+            TfToken(std::string("bif:visibility:") + RAY_VISIBILITY_NAMES[bit]));
+        VtValue value;
+        if (primvar && primvar.Get(&value) && value.CanCast<int>() &&
+            VtValue::Cast<int>(value).UncheckedGet<int>() == 0) {
+            mask &= ~(1u << bit);
+        }
+    }
+    return mask;
+}
+
 /// Convert GfMatrix4d to column-major float array
 static void matrix_to_float16(const GfMatrix4d& mat, float* out) {
     GfMatrix4f matf(mat);
@@ -145,6 +300,108 @@ static void matrix_to_float16(const GfMatrix4d& mat, float* out) {
     }
 }
 
+/// Resolved path of an asset, or its authored path if it doesn't resolve
+static std::string asset_path_string(const SdfAssetPath& asset) {
+    return asset.GetResolvedPath().empty() ? asset.GetAssetPath() : asset.GetResolvedPath();
+}
+
+/// Read a UsdLux light prim; returns false if the prim is not a light
+static bool read_light(const UsdPrim& prim, UsdTimeCode time, CachedLight& out) {
+    UsdBridgeLightData& data = out.data;
+    data = UsdBridgeLightData{};
+    bool flag = false;
+    SdfAssetPath asset;
+
+    if (prim.IsA<UsdLuxSphereLight>()) {
+        UsdLuxSphereLight light(prim);
+        data.light_type = USD_BRIDGE_LIGHT_SPHERE;
+        light.GetRadiusAttr().Get(&data.radius, time);
+        light.GetTreatAsPointAttr().Get(&flag, time);
+        data.treat_as_point = flag;
+    } else if (prim.IsA<UsdLuxRectLight>()) {
+        UsdLuxRectLight light(prim);
+        data.light_type = USD_BRIDGE_LIGHT_RECT;
+        light.GetWidthAttr().Get(&data.width, time);
+        light.GetHeightAttr().Get(&data.height, time);
+        if (light.GetTextureFileAttr().Get(&asset, time)) {
+            out.texture_file = asset_path_string(asset);
+        }
+    } else if (prim.IsA<UsdLuxDiskLight>()) {
+        UsdLuxDiskLight light(prim);
+        data.light_type = USD_BRIDGE_LIGHT_DISK;
+        light.GetRadiusAttr().Get(&data.radius, time);
+    } else if (prim.IsA<UsdLuxCylinderLight>()) {
+        UsdLuxCylinderLight light(prim);
+        data.light_type = USD_BRIDGE_LIGHT_CYLINDER;
+        light.GetLengthAttr().Get(&data.length, time);
+        light.GetRadiusAttr().Get(&data.radius, time);
+        light.GetTreatAsLineAttr().Get(&flag, time);
+        data.treat_as_point = flag;
+    } else if (prim.IsA<UsdLuxDistantLight>()) {
+        UsdLuxDistantLight light(prim);
+        data.light_type = USD_BRIDGE_LIGHT_DISTANT;
+        light.GetAngleAttr().Get(&data.angle, time);
+    } else if (prim.IsA<UsdLuxDomeLight>()) {
+        UsdLuxDomeLight light(prim);
+        data.light_type = USD_BRIDGE_LIGHT_DOME;
+        if (light.GetTextureFileAttr().Get(&asset, time)) {
+            out.texture_file = asset_path_string(asset);
+        }
+        TfToken format;
+        light.GetTextureFormatAttr().Get(&format, time);
+        if (format == UsdLuxTokens->latlong) {
+            data.texture_format = 1;
+        } else if (format == UsdLuxTokens->mirroredBall) {
+            data.texture_format = 2;
+        } else if (format == UsdLuxTokens->angular) {
+            data.texture_format = 3;
+        } else if (format == UsdLuxTokens->cubeMapVerticalCross) {
+            data.texture_format = 4;
+        }
+    } else {
+        return false;
+    }
+
+    out.path = prim.GetPath().GetString();
+
+    // Fallbacks come from the schema when nothing is authored
+    UsdLuxLightAPI light(prim);
+    GfVec3f color(1.0f);
+    light.GetIntensityAttr().Get(&data.intensity, time);
+    light.GetExposureAttr().Get(&data.exposure, time);
+    light.GetColorAttr().Get(&color, time);
+    light.GetEnableColorTemperatureAttr().Get(&flag, time);
+    data.enable_color_temperature = flag;
+    light.GetColorTemperatureAttr().Get(&data.color_temperature, time);
+    flag = false;
+    light.GetNormalizeAttr().Get(&flag, time);
+    data.normalize = flag;
+    for (int i = 0; i < 3; ++i) {
+        data.color[i] = color[i];
+    }
+
+    if (prim.HasAPI<UsdLuxShapingAPI>()) {
+        UsdLuxShapingAPI shaping(prim);
+        GfVec3f tint(0.0f);
+        data.has_shaping = 1;
+        shaping.GetShapingConeAngleAttr().Get(&data.cone_angle, time);
+        shaping.GetShapingConeSoftnessAttr().Get(&data.cone_softness, time);
+        shaping.GetShapingFocusAttr().Get(&data.focus, time);
+        shaping.GetShapingFocusTintAttr().Get(&tint, time);
+        for (int i = 0; i < 3; ++i) {
+            data.focus_tint[i] = tint[i];
+        }
+        if (shaping.GetShapingIesFileAttr().Get(&asset, time)) {
+            out.ies_file = asset_path_string(asset);
+        }
+        shaping.GetShapingIesAngleScaleAttr().Get(&data.ies_angle_scale, time);
+        flag = false;
+        shaping.GetShapingIesNormalizeAttr().Get(&flag, time);
+        data.ies_normalize = flag;
+    }
+    return true;
+}
+
 /// Cache all prim info for scene browser
 static void cache_prim_data(UsdBridgeStage* bridge) {
     if (bridge->prims_cached) return;
@@ -189,7 +446,7 @@ static void cache_prim_data(UsdBridgeStage* bridge) {
 static void cache_stage_data(UsdBridgeStage* bridge) {
     if (bridge->cached) return;
 
-    UsdGeomXformCache xform_cache;
+    UsdGeomXformCache xform_cache(bridge->transform_time());
 
     // Traverse all prims
     for (const UsdPrim& prim : bridge->stage->Traverse()) {
@@ -199,9 +456,9 @@ static void cache_stage_data(UsdBridgeStage* bridge) {
             CachedMesh cached;
             cached.path = prim.GetPath().GetString();
 
-            // Get points at earliest authored time for animated geometry
+            // Get points at the set time (or earliest authored time)
             VtArray<GfVec3f> points;
-            UsdTimeCode timeCode = UsdTimeCode::EarliestTime();
+            UsdTimeCode timeCode = bridge->points_time();
             mesh.GetPointsAttr().Get(&points, timeCode);
             
             // Pre-allocate to exact size to minimize memory overhead
@@ -220,6 +477,73 @@ static void cache_stage_data(UsdBridgeStage* bridge) {
             mesh.GetFaceVertexIndicesAttr().Get(&face_vertex_indices, timeCode);
             triangulate_mesh(face_vertex_counts, face_vertex_indices, cached.indices);
 
+            // Get per-face material bindings from GeomSubsets (optional)
+            std::vector<UsdGeomSubset> subsets =
+                UsdShadeMaterialBindingAPI(prim).GetMaterialBindSubsets();
+            if (!subsets.empty()) {
+                std::vector<int32_t> face_subsets(face_vertex_counts.size(), -1);
+                for (size_t s = 0; s < subsets.size(); ++s) {
+                    VtIntArray face_indices;
+                    subsets[s].GetIndicesAttr().Get(&face_indices, timeCode);
+                    for (int face : face_indices) {
+                        if (face >= 0 && static_cast<size_t>(face) < face_subsets.size()) {
+                            face_subsets[face] = static_cast<int32_t>(s);
+                        }
+                    }
+
+                    UsdShadeMaterial bound =
+                        UsdShadeMaterialBindingAPI(subsets[s].GetPrim()).ComputeBoundMaterial();
+                    cached.subset_material_paths.push_back(
+                        bound ? bound.GetPath().GetString() : std::string());
+                }
+
+                // Expand face subsets to triangles (matches fan triangulation)
+                cached.triangle_subsets.reserve(cached.indices.size() / 3);
+                for (size_t face = 0; face < face_vertex_counts.size(); ++face) {
+                    for (int t = 0; t < face_vertex_counts[face] - 2; ++t) {
+                        cached.triangle_subsets.push_back(face_subsets[face]);
+                    }
+                }
+            }
+
+            // Keep the control cage for Catmull-Clark meshes. The schema's
+            // fallback scheme is catmullClark, but most exporters leave it
+            // unauthored on polygonal meshes, so only an authored value counts.
+            UsdAttribute scheme_attr = mesh.GetSubdivisionSchemeAttr();
+            TfToken scheme;
+            if (scheme_attr.HasAuthoredValue() && scheme_attr.Get(&scheme)) {
+                if (scheme == UsdGeomTokens->catmullClark) {
+                    cached.subdivision_scheme = 1;
+                } else if (scheme != UsdGeomTokens->none) {
+                    cached.subdivision_scheme = 2;
+                }
+            }
+            if (cached.subdivision_scheme == 1) {
+                copy_indices(face_vertex_counts, cached.face_vertex_counts);
+                copy_indices(face_vertex_indices, cached.face_vertex_indices);
+
+                VtIntArray crease_indices, crease_lengths, corner_indices;
+                VtFloatArray crease_sharpnesses, corner_sharpnesses;
+                mesh.GetCreaseIndicesAttr().Get(&crease_indices, timeCode);
+                mesh.GetCreaseLengthsAttr().Get(&crease_lengths, timeCode);
+                mesh.GetCreaseSharpnessesAttr().Get(&crease_sharpnesses, timeCode);
+                mesh.GetCornerIndicesAttr().Get(&corner_indices, timeCode);
+                mesh.GetCornerSharpnessesAttr().Get(&corner_sharpnesses, timeCode);
+                copy_indices(crease_indices, cached.crease_indices);
+                copy_indices(crease_lengths, cached.crease_lengths);
+                copy_indices(corner_indices, cached.corner_indices);
+                cached.crease_sharpnesses.assign(crease_sharpnesses.begin(), crease_sharpnesses.end());
+                cached.corner_sharpnesses.assign(corner_sharpnesses.begin(), corner_sharpnesses.end());
+            }
+
+            // RenderMan-style displacement bound (optional)
+            UsdGeomPrimvar bound_primvar = UsdGeomPrimvarsAPI(prim).GetPrimvar(
+                TfToken("ri:attributes:displacementbound:sphere"));
+            VtValue bound;
+            if (bound_primvar && bound_primvar.Get(&bound, timeCode) && bound.CanCast<float>()) {
+                cached.displacement_bound = VtValue::Cast<float>(bound).UncheckedGet<float>();
+            }
+
             // Get normals (optional)
             VtArray<GfVec3f> normals;
             if (mesh.GetNormalsAttr().Get(&normals, timeCode)) {
@@ -248,6 +572,9 @@ static void cache_stage_data(UsdBridgeStage* bridge) {
             // Get world transform
             cached.transform = xform_cache.GetLocalToWorldTransform(prim);
 
+            cached.visible = is_rendered(prim);
+            cached.ray_visibility = ray_visibility(prim);
+
             bridge->meshes.push_back(std::move(cached));
         }
 
@@ -270,15 +597,15 @@ static void cache_stage_data(UsdBridgeStage* bridge) {
 
             // Get proto indices
             VtArray<int> proto_indices;
-            instancer.GetProtoIndicesAttr().Get(&proto_indices);
+            instancer.GetProtoIndicesAttr().Get(&proto_indices, bridge->transform_time());
             cached.proto_indices.assign(proto_indices.begin(), proto_indices.end());
 
             // Compute instance transforms
             VtArray<GfMatrix4d> instance_transforms;
             if (instancer.ComputeInstanceTransformsAtTime(
                     &instance_transforms,
-                    UsdTimeCode::Default(),
-                    UsdTimeCode::Default())) {
+                    bridge->transform_time(),
+                    bridge->transform_time())) {
 
                 // Pre-allocate exact size for transforms
                 cached.transforms.reserve(instance_transforms.size() * 16);
@@ -292,8 +619,113 @@ static void cache_stage_data(UsdBridgeStage* bridge) {
                 cached.transforms.shrink_to_fit();
             }
 
+            cached.visible = is_rendered(prim);
+            cached.ray_visibility = ray_visibility(prim);
+
             bridge->instancers.push_back(std::move(cached));
         }
+
+        // Check for UsdGeomBasisCurves
+        if (prim.IsA<UsdGeomBasisCurves>()) {
+            UsdGeomBasisCurves curves(prim);
+            CachedCurves cached;
+            cached.path = prim.GetPath().GetString();
+            UsdTimeCode timeCode = bridge->points_time();
+
+            VtArray<GfVec3f> points;
+            curves.GetPointsAttr().Get(&points, timeCode);
+            cached.points.reserve(points.size() * 3);
+            for (const auto& p : points) {
+                cached.points.push_back(p[0]);
+                cached.points.push_back(p[1]);
+                cached.points.push_back(p[2]);
+            }
+
+            VtIntArray counts;
+            curves.GetCurveVertexCountsAttr().Get(&counts, timeCode);
+            copy_indices(counts, cached.curve_vertex_counts);
+
+            VtFloatArray widths;
+            if (curves.GetWidthsAttr().Get(&widths, timeCode)) {
+                cached.widths.assign(widths.begin(), widths.end());
+            }
+
+            TfToken type, basis;
+            curves.GetTypeAttr().Get(&type);
+            curves.GetBasisAttr().Get(&basis);
+            cached.curve_type = type == UsdGeomTokens->linear ? 0 : 1;
+            if (basis == UsdGeomTokens->bspline) {
+                cached.basis = 1;
+            } else if (basis == UsdGeomTokens->catmullRom) {
+                cached.basis = 2;
+            }
+            cached.has_normals = curves.GetNormalsAttr().HasAuthoredValue();
+
+            cached.transform = xform_cache.GetLocalToWorldTransform(prim);
+
+            UsdShadeMaterial bound = UsdShadeMaterialBindingAPI(prim).ComputeBoundMaterial();
+            if (bound) {
+                cached.material_path = bound.GetPath().GetString();
+            }
+
+            bridge->curves.push_back(std::move(cached));
+        }
+
+        // Check for UsdGeomPoints
+        if (prim.IsA<UsdGeomPoints>()) {
+            UsdGeomPoints points(prim);
+            CachedPoints cached;
+            cached.path = prim.GetPath().GetString();
+            UsdTimeCode timeCode = bridge->points_time();
+
+            VtArray<GfVec3f> positions;
+            points.GetPointsAttr().Get(&positions, timeCode);
+            cached.points.reserve(positions.size() * 3);
+            for (const auto& p : positions) {
+                cached.points.push_back(p[0]);
+                cached.points.push_back(p[1]);
+                cached.points.push_back(p[2]);
+            }
+
+            VtFloatArray widths;
+            if (points.GetWidthsAttr().Get(&widths, timeCode)) {
+                cached.widths.assign(widths.begin(), widths.end());
+            }
+
+            cached.transform = xform_cache.GetLocalToWorldTransform(prim);
+
+            UsdShadeMaterial bound = UsdShadeMaterialBindingAPI(prim).ComputeBoundMaterial();
+            if (bound) {
+                cached.material_path = bound.GetPath().GetString();
+            }
+
+            bridge->points.push_back(std::move(cached));
+        }
+
+        // Check for UsdLux lights (invisible lights are switched off)
+        CachedLight light;
+        if (read_light(prim, bridge->points_time(), light) && is_rendered(prim)) {
+            light.transform = xform_cache.GetLocalToWorldTransform(prim);
+            bridge->lights.push_back(std::move(light));
+        }
+
+        // Check for UsdGeomCamera
+        if (prim.IsA<UsdGeomCamera>()) {
+            UsdTimeCode timeCode = bridge->transform_time();
+            GfCamera camera = UsdGeomCamera(prim).GetCamera(timeCode);
+            CachedCamera cached;
+            cached.path = prim.GetPath().GetString();
+            cached.data = UsdBridgeCameraData{};
+            cached.data.focal_length = camera.GetFocalLength();
+            cached.data.horizontal_aperture = camera.GetHorizontalAperture();
+            cached.data.vertical_aperture = camera.GetVerticalAperture();
+            cached.data.clipping_range[0] = camera.GetClippingRange().GetMin();
+            cached.data.clipping_range[1] = camera.GetClippingRange().GetMax();
+            cached.data.f_stop = camera.GetFStop();
+            cached.data.focus_distance = camera.GetFocusDistance();
+            cached.transform = xform_cache.GetLocalToWorldTransform(prim);
+            bridge->cameras.push_back(std::move(cached));
+        }
     }
 
     bridge->cached = true;
@@ -336,6 +768,54 @@ static std::string get_texture_path(const UsdShadeInput& input) {
     return "";
 }
 
+/// Helper to get the texture channel a shader input reads (outputs:r/g/b/a)
+static int get_texture_channel(const UsdShadeInput& input) {
+    if (!input) return 0;
+
+    SdfPathVector connections;
+    input.GetRawConnectedSourcePaths(&connections);
+    for (const auto& conn_path : connections) {
+        const std::string output = conn_path.GetName();
+        if (output == "outputs:g") return 1;
+        if (output == "outputs:b") return 2;
+        if (output == "outputs:a") return 3;
+        return 0;
+    }
+    return 0;
+}
+
+/// Helper to read the scale and bias a UsdUVTexture applies to one channel
+static void get_texture_scale_bias(
+    const UsdShadeInput& input, int channel, float* out_scale, float* out_bias
+) {
+    if (!input) return;
+
+    SdfPathVector connections;
+    input.GetRawConnectedSourcePaths(&connections);
+    for (const auto& conn_path : connections) {
+        UsdPrim shader_prim = input.GetPrim().GetStage()->GetPrimAtPath(conn_path.GetPrimPath());
+        if (!shader_prim) continue;
+
+        UsdShadeShader shader(shader_prim);
+        if (!shader) continue;
+
+        TfToken shader_id;
+        shader.GetIdAttr().Get(&shader_id);
+        if (shader_id != TfToken("UsdUVTexture")) continue;
+
+        GfVec4f value;
+        UsdShadeInput scale_input = shader.GetInput(TfToken("scale"));
+        if (scale_input && scale_input.Get(&value)) {
+            *out_scale = value[channel];
+        }
+        UsdShadeInput bias_input = shader.GetInput(TfToken("bias"));
+        if (bias_input && bias_input.Get(&value)) {
+            *out_bias = value[channel];
+        }
+        return;
+    }
+}
+
 /// Helper to check if a shader ID is a MaterialX standard_surface
 static bool is_materialx_standard_surface(const TfToken& shader_id) {
     std::string id_str = shader_id.GetString();
@@ -383,6 +863,103 @@ static std::string get_materialx_texture_path(const UsdShadeInput& input) {
     return "";
 }
 
+/// Read a constant attribute value into a cached shader input
+static void read_shader_value(const UsdAttribute& attr, CachedShaderInput& out) {
+    VtValue value;
+    if (!attr.Get(&value) || value.IsEmpty()) return;
+
+    if (value.IsHolding<float>()) {
+        out.kind = USD_BRIDGE_SHADER_VALUE_FLOAT;
+        out.value[0] = value.UncheckedGet<float>();
+    } else if (value.IsHolding<double>()) {
+        out.kind = USD_BRIDGE_SHADER_VALUE_FLOAT;
+        out.value[0] = static_cast<float>(value.UncheckedGet<double>());
+    } else if (value.IsHolding<int>()) {
+        out.kind = USD_BRIDGE_SHADER_VALUE_FLOAT;
+        out.value[0] = static_cast<float>(value.UncheckedGet<int>());
+    } else if (value.IsHolding<bool>()) {
+        out.kind = USD_BRIDGE_SHADER_VALUE_FLOAT;
+        out.value[0] = value.UncheckedGet<bool>() ? 1.0f : 0.0f;
+    } else if (value.IsHolding<GfVec2f>()) {
+        const GfVec2f& v = value.UncheckedGet<GfVec2f>();
+        out.kind = USD_BRIDGE_SHADER_VALUE_VECTOR2;
+        out.value[0] = v[0];
+        out.value[1] = v[1];
+    } else if (value.IsHolding<GfVec3f>()) {
+        const GfVec3f& v = value.UncheckedGet<GfVec3f>();
+        out.kind = USD_BRIDGE_SHADER_VALUE_VECTOR3;
+        out.value[0] = v[0];
+        out.value[1] = v[1];
+        out.value[2] = v[2];
+    } else if (value.IsHolding<SdfAssetPath>()) {
+        const SdfAssetPath& asset = value.UncheckedGet<SdfAssetPath>();
+        out.kind = USD_BRIDGE_SHADER_VALUE_STRING;
+        out.string_value = asset.GetResolvedPath().empty()
+            ? asset.GetAssetPath()
+            : asset.GetResolvedPath();
+    } else if (value.IsHolding<std::string>()) {
+        out.kind = USD_BRIDGE_SHADER_VALUE_STRING;
+        out.string_value = value.UncheckedGet<std::string>();
+    } else if (value.IsHolding<TfToken>()) {
+        out.kind = USD_BRIDGE_SHADER_VALUE_STRING;
+        out.string_value = value.UncheckedGet<TfToken>().GetString();
+    }
+}
+
+/// Collect the shader network upstream of a surface shader.
+/// NodeGraph boundaries are resolved through GetValueProducingAttributes,
+/// so the result is a flat list of shader nodes with node 0 = surface.
+static void collect_shader_graph(const UsdShadeShader& surface,
+                                 std::vector<CachedShaderNode>& nodes) {
+    std::unordered_map<std::string, int> node_index;
+
+    std::function<int(const UsdShadeShader&)> visit = [&](const UsdShadeShader& shader) -> int {
+        std::string path = shader.GetPath().GetString();
+        auto found = node_index.find(path);
+        if (found != node_index.end()) return found->second;
+
+        int index = static_cast<int>(nodes.size());
+        node_index[path] = index;
+        nodes.emplace_back();
+
+        CachedShaderNode node;
+        node.path = path;
+        TfToken shader_id;
+        shader.GetIdAttr().Get(&shader_id);
+        node.shader_id = shader_id.GetString();
+
+        for (const UsdShadeInput& input : shader.GetInputs()) {
+            CachedShaderInput cached;
+            cached.name = input.GetBaseName().GetString();
+            cached.kind = USD_BRIDGE_SHADER_VALUE_NONE;
+            cached.value[0] = cached.value[1] = cached.value[2] = 0.0f;
+            cached.connected_node = -1;
+
+            UsdShadeAttributeVector sources = input.GetValueProducingAttributes();
+            if (!sources.empty()) {
+                const UsdAttribute& source = sources[0];
+                if (UsdShadeUtils::GetType(source.GetName()) == UsdShadeAttributeType::Output) {
+                    UsdShadeShader upstream(source.GetPrim());
+                    if (upstream) {
+                        cached.connected_output =
+                            UsdShadeUtils::GetBaseNameAndType(source.GetName()).first.GetString();
+                        cached.connected_node = visit(upstream);
+                    }
+                } else {
+                    read_shader_value(source, cached);
+                }
+            }
+
+            node.inputs.push_back(std::move(cached));
+        }
+
+        nodes[index] = std::move(node);
+        return index;
+    };
+
+    visit(surface);
+}
+
 /// Cache all material data from the stage
 static void cache_material_data(UsdBridgeStage* bridge) {
     if (bridge->materials_cached) return;
@@ -405,6 +982,12 @@ static void cache_material_data(UsdBridgeStage* bridge) {
         cached.roughness = 0.5f;
         cached.specular = 0.5f;
         cached.opacity = 1.0f;
+        cached.opacity_threshold = 0.0f;
+        cached.opacity_texture_channel = 0;
+        cached.displacement_texture_channel = 0;
+        cached.displacement_scale = 1.0f;
+        cached.displacement_bias = 0.0f;
+        cached.vector_displacement = false;
         cached.emissive_color[0] = 0.0f;
         cached.emissive_color[1] = 0.0f;
         cached.emissive_color[2] = 0.0f;
@@ -513,6 +1096,7 @@ static void cache_material_data(UsdBridgeStage* bridge) {
                 cached.normal_texture = get_materialx_texture_path(input);
             }
 
+            collect_shader_graph(mtlx_shader, cached.graph_nodes);
             bridge->materials.push_back(std::move(cached));
             continue;
         }
@@ -626,6 +1210,7 @@ static void cache_material_data(UsdBridgeStage* bridge) {
                 cached.normal_texture = get_materialx_texture_path(input);
             }
 
+            collect_shader_graph(shader, cached.graph_nodes);
             bridge->materials.push_back(std::move(cached));
             continue;
         }
@@ -674,10 +1259,18 @@ static void cache_material_data(UsdBridgeStage* bridge) {
             }
         }
 
-        // Opacity
+        // Opacity (optionally textured, e.g. foliage cards)
         input = shader.GetInput(TfToken("opacity"));
         if (input) {
             input.Get(&cached.opacity);
+            cached.opacity_texture = get_texture_path(input);
+            cached.opacity_texture_channel = get_texture_channel(input);
+        }
+
+        // Opacity threshold (> 0 turns opacity into a binary cutout)
+        input = shader.GetInput(TfToken("opacityThreshold"));
+        if (input) {
+            input.Get(&cached.opacity_threshold);
         }
 
         // Emissive color
@@ -698,9 +1291,50 @@ static void cache_material_data(UsdBridgeStage* bridge) {
             cached.normal_texture = get_texture_path(input);
         }
 
+        // Displacement (scalar height or vector3 offset, applied by Ivar at
+        // scene build time). A vector offset takes its scale and bias from
+        // the red channel.
+        input = shader.GetInput(TfToken("displacement"));
+        if (input) {
+            cached.displacement_texture = get_texture_path(input);
+            cached.displacement_texture_channel = get_texture_channel(input);
+            const SdfTupleDimensions dims = input.GetTypeName().GetDimensions();
+            cached.vector_displacement = dims.size == 1 && dims.d[0] == 3;
+            get_texture_scale_bias(
+                input,
+                cached.vector_displacement ? 0 : cached.displacement_texture_channel,
+                &cached.displacement_scale,
+                &cached.displacement_bias);
+        }
+
         bridge->materials.push_back(std::move(cached));
     }
 
+    // Build C API views of shader inputs now that the material list is final
+    // (moving CachedMaterial would invalidate the string pointers)
+    for (auto& mat : bridge->materials) {
+        for (auto& node : mat.graph_nodes) {
+            node.input_views.clear();
+            node.input_views.reserve(node.inputs.size());
+            for (const auto& input : node.inputs) {
+                UsdBridgeShaderInput view;
+                view.name = input.name.c_str();
+                view.kind = input.kind;
+                view.value[0] = input.value[0];
+                view.value[1] = input.value[1];
+                view.value[2] = input.value[2];
+                view.string_value = input.kind == USD_BRIDGE_SHADER_VALUE_STRING
+                    ? input.string_value.c_str()
+                    : nullptr;
+                view.connected_node = input.connected_node;
+                view.connected_output = input.connected_node >= 0
+                    ? input.connected_output.c_str()
+                    : nullptr;
+                node.input_views.push_back(view);
+            }
+        }
+    }
+
     // Also collect mesh-to-material bindings
     // Note: Material bindings can be inherited from parent prims
     bridge->mesh_material_paths.clear();
@@ -790,6 +1424,14 @@ void usd_bridge_clear_cache(UsdBridgeStage* stage) {
     stage->meshes.shrink_to_fit();
     stage->instancers.clear();
     stage->instancers.shrink_to_fit();
+    stage->curves.clear();
+    stage->curves.shrink_to_fit();
+    stage->points.clear();
+    stage->points.shrink_to_fit();
+    stage->lights.clear();
+    stage->lights.shrink_to_fit();
+    stage->cameras.clear();
+    stage->cameras.shrink_to_fit();
     stage->all_prims.clear();
     stage->all_prims.shrink_to_fit();
     stage->root_paths.clear();
@@ -800,6 +1442,41 @@ void usd_bridge_clear_cache(UsdBridgeStage* stage) {
     stage->prims_cached = false;
 }
 
+UsdBridgeError usd_bridge_set_time(UsdBridgeStage* stage, double time_code) {
+    if (!stage) {
+        return USD_BRIDGE_ERROR_NULL_POINTER;
+    }
+
+    stage->has_time = true;
+    stage->time = time_code;
+
+    // Geometry is read again at the new time
+    stage->meshes.clear();
+    stage->instancers.clear();
+    stage->curves.clear();
+    stage->points.clear();
+    stage->lights.clear();
+    stage->cameras.clear();
+    stage->cached = false;
+    return USD_BRIDGE_SUCCESS;
+}
+
+UsdBridgeError usd_bridge_get_time_codes(
+    const UsdBridgeStage* stage,
+    double* out_start,
+    double* out_end,
+    double* out_per_second
+) {
+    if (!stage || !out_start || !out_end || !out_per_second) {
+        return USD_BRIDGE_ERROR_NULL_POINTER;
+    }
+
+    *out_start = stage->stage->GetStartTimeCode();
+    *out_end = stage->stage->GetEndTimeCode();
+    *out_per_second = stage->stage->GetTimeCodesPerSecond();
+    return USD_BRIDGE_SUCCESS;
+}
+
 UsdBridgeError usd_bridge_get_mesh_count(
     const UsdBridgeStage* stage,
     size_t* out_count
@@ -854,6 +1531,31 @@ UsdBridgeError usd_bridge_get_mesh(
     out_data->normal_count = mesh.normals.size() / 3;
     out_data->uvs = mesh.uvs.empty() ? nullptr : mesh.uvs.data();
     out_data->uv_count = mesh.uvs.size() / 2;
+    out_data->triangle_subsets =
+        mesh.triangle_subsets.empty() ? nullptr : mesh.triangle_subsets.data();
+    out_data->triangle_subset_count = mesh.triangle_subsets.size();
+    out_data->subset_count = mesh.subset_material_paths.size();
+
+    // Subdivision cage and tags (empty vectors become NULL)
+    auto data_or_null = [](const auto& v) { return v.empty() ? nullptr : v.data(); };
+    out_data->subdivision_scheme = mesh.subdivision_scheme;
+    out_data->face_vertex_counts = data_or_null(mesh.face_vertex_counts);
+    out_data->face_count = mesh.face_vertex_counts.size();
+    out_data->face_vertex_indices = data_or_null(mesh.face_vertex_indices);
+    out_data->face_vertex_index_count = mesh.face_vertex_indices.size();
+    out_data->crease_indices = data_or_null(mesh.crease_indices);
+    out_data->crease_index_count = mesh.crease_indices.size();
+    out_data->crease_lengths = data_or_null(mesh.crease_lengths);
+    out_data->crease_length_count = mesh.crease_lengths.size();
+    out_data->crease_sharpnesses = data_or_null(mesh.crease_sharpnesses);
+    out_data->crease_sharpness_count = mesh.crease_sharpnesses.size();
+    out_data->corner_indices = data_or_null(mesh.corner_indices);
+    out_data->corner_index_count = mesh.corner_indices.size();
+    out_data->corner_sharpnesses = data_or_null(mesh.corner_sharpnesses);
+    out_data->corner_sharpness_count = mesh.corner_sharpnesses.size();
+    out_data->displacement_bound = mesh.displacement_bound;
+    out_data->visible = mesh.visible ? 1 : 0;
+    out_data->ray_visibility = mesh.ray_visibility;
 
     // Copy transform
     float mat_data[16];
@@ -887,6 +1589,177 @@ UsdBridgeError usd_bridge_get_instancer(
     out_data->transforms = instancer.transforms.data();
     out_data->instance_count = instancer.transforms.size() / 16;
     out_data->proto_indices = instancer.proto_indices.data();
+    out_data->visible = instancer.visible ? 1 : 0;
+    out_data->ray_visibility = instancer.ray_visibility;
+
+    return USD_BRIDGE_SUCCESS;
+}
+
+UsdBridgeError usd_bridge_get_curves_count(
+    const UsdBridgeStage* stage,
+    size_t* out_count
+) {
+    if (!stage || !out_count) {
+        return USD_BRIDGE_ERROR_NULL_POINTER;
+    }
+
+    cache_stage_data(const_cast<UsdBridgeStage*>(stage));
+    *out_count = stage->curves.size();
+    return USD_BRIDGE_SUCCESS;
+}
+
+UsdBridgeError usd_bridge_get_curves(
+    const UsdBridgeStage* stage,
+    size_t index,
+    UsdBridgeCurvesData* out_data
+) {
+    if (!stage || !out_data) {
+        return USD_BRIDGE_ERROR_NULL_POINTER;
+    }
+
+    cache_stage_data(const_cast<UsdBridgeStage*>(stage));
+
+    if (index >= stage->curves.size()) {
+        return USD_BRIDGE_ERROR_INVALID_PRIM;
+    }
+
+    const CachedCurves& curves = stage->curves[index];
+    out_data->path = curves.path.c_str();
+    out_data->points = curves.points.data();
+    out_data->point_count = curves.points.size() / 3;
+    out_data->curve_vertex_counts = curves.curve_vertex_counts.data();
+    out_data->curve_count = curves.curve_vertex_counts.size();
+    out_data->widths = curves.widths.empty() ? nullptr : curves.widths.data();
+    out_data->width_count = curves.widths.size();
+    out_data->curve_type = curves.curve_type;
+    out_data->basis = curves.basis;
+    out_data->has_normals = curves.has_normals ? 1 : 0;
+    out_data->material_path = curves.material_path.c_str();
+
+    float mat_data[16];
+    matrix_to_float16(curves.transform, mat_data);
+    for (int i = 0; i < 16; ++i) {
+        out_data->transform[i] = mat_data[i];
+    }
+
+    return USD_BRIDGE_SUCCESS;
+}
+
+UsdBridgeError usd_bridge_get_points_count(
+    const UsdBridgeStage* stage,
+    size_t* out_count
+) {
+    if (!stage || !out_count) {
+        return USD_BRIDGE_ERROR_NULL_POINTER;
+    }
+
+    cache_stage_data(const_cast<UsdBridgeStage*>(stage));
+    *out_count = stage->points.size();
+    return USD_BRIDGE_SUCCESS;
+}
+
+UsdBridgeError usd_bridge_get_points(
+    const UsdBridgeStage* stage,
+    size_t index,
+    UsdBridgePointsData* out_data
+) {
+    if (!stage || !out_data) {
+        return USD_BRIDGE_ERROR_NULL_POINTER;
+    }
+
+    cache_stage_data(const_cast<UsdBridgeStage*>(stage));
+
+    if (index >= stage->points.size()) {
+        return USD_BRIDGE_ERROR_INVALID_PRIM;
+    }
+
+    const CachedPoints& points = stage->points[index];
+    out_data->path = points.path.c_str();
+    out_data->points = points.points.data();
+    out_data->point_count = points.points.size() / 3;
+    out_data->widths = points.widths.empty() ? nullptr : points.widths.data();
+    out_data->width_count = points.widths.size();
+    out_data->material_path = points.material_path.c_str();
+
+    float mat_data[16];
+    matrix_to_float16(points.transform, mat_data);
+    for (int i = 0; i < 16; ++i) {
+        out_data->transform[i] = mat_data[i];
+    }
+
+    return USD_BRIDGE_SUCCESS;
+}
+
+UsdBridgeError usd_bridge_get_light_count(
+    const UsdBridgeStage* stage,
+    size_t* out_count
+) {
+    if (!stage || !out_count) {
+        return USD_BRIDGE_ERROR_NULL_POINTER;
+    }
+
+    cache_stage_data(const_cast<UsdBridgeStage*>(stage));
+    *out_count = stage->lights.size();
+    return USD_BRIDGE_SUCCESS;
+}
+
+UsdBridgeError usd_bridge_get_light(
+    const UsdBridgeStage* stage,
+    size_t index,
+    UsdBridgeLightData* out_data
+) {
+    if (!stage || !out_data) {
+        return USD_BRIDGE_ERROR_NULL_POINTER;
+    }
+
+    cache_stage_data(const_cast<UsdBridgeStage*>(stage));
+
+    if (index >= stage->lights.size()) {
+        return USD_BRIDGE_ERROR_INVALID_PRIM;
+    }
+
+    const CachedLight& light = stage->lights[index];
+    *out_data = light.data;
+    out_data->path = light.path.c_str();
+    out_data->texture_file = light.texture_file.c_str();
+    out_data->ies_file = light.ies_file.c_str();
+    matrix_to_float16(light.transform, out_data->transform);
+
+    return USD_BRIDGE_SUCCESS;
+}
+
+UsdBridgeError usd_bridge_get_camera_count(
+    const UsdBridgeStage* stage,
+    size_t* out_count
+) {
+    if (!stage || !out_count) {
+        return USD_BRIDGE_ERROR_NULL_POINTER;
+    }
+
+    cache_stage_data(const_cast<UsdBridgeStage*>(stage));
+    *out_count = stage->cameras.size();
+    return USD_BRIDGE_SUCCESS;
+}
+
+UsdBridgeError usd_bridge_get_camera(
+    const UsdBridgeStage* stage,
+    size_t index,
+    UsdBridgeCameraData* out_data
+) {
+    if (!stage || !out_data) {
+        return USD_BRIDGE_ERROR_NULL_POINTER;
+    }
+
+    cache_stage_data(const_cast<UsdBridgeStage*>(stage));
+
+    if (index >= stage->cameras.size()) {
+        return USD_BRIDGE_ERROR_INVALID_PRIM;
+    }
+
+    const CachedCamera& camera = stage->cameras[index];
+    *out_data = camera.data;
+    out_data->path = camera.path.c_str();
+    matrix_to_float16(camera.transform, out_data->transform);
 
     return USD_BRIDGE_SUCCESS;
 }
@@ -932,6 +1805,7 @@ UsdBridgeError usd_bridge_get_material(
     out_data->roughness = mat.roughness;
     out_data->specular = mat.specular;
     out_data->opacity = mat.opacity;
+    out_data->opacity_threshold = mat.opacity_threshold;
     out_data->emissive_color[0] = mat.emissive_color[0];
     out_data->emissive_color[1] = mat.emissive_color[1];
     out_data->emissive_color[2] = mat.emissive_color[2];
@@ -940,7 +1814,66 @@ UsdBridgeError usd_bridge_get_material(
     out_data->metallic_texture = mat.metallic_texture.empty() ? nullptr : mat.metallic_texture.c_str();
     out_data->normal_texture = mat.normal_texture.empty() ? nullptr : mat.normal_texture.c_str();
     out_data->emissive_texture = mat.emissive_texture.empty() ? nullptr : mat.emissive_texture.c_str();
+    out_data->opacity_texture = mat.opacity_texture.empty() ? nullptr : mat.opacity_texture.c_str();
+    out_data->opacity_texture_channel = mat.opacity_texture_channel;
     out_data->is_materialx = mat.is_materialx ? 1 : 0;
+    out_data->displacement_texture =
+        mat.displacement_texture.empty() ? nullptr : mat.displacement_texture.c_str();
+    out_data->displacement_texture_channel = mat.displacement_texture_channel;
+    out_data->displacement_scale = mat.displacement_scale;
+    out_data->displacement_bias = mat.displacement_bias;
+    out_data->vector_displacement = mat.vector_displacement ? 1 : 0;
+
+    return USD_BRIDGE_SUCCESS;
+}
+
+UsdBridgeError usd_bridge_get_material_node_count(
+    const UsdBridgeStage* stage,
+    size_t material_index,
+    size_t* out_count
+) {
+    if (!stage || !out_count) {
+        return USD_BRIDGE_ERROR_NULL_POINTER;
+    }
+
+    cache_stage_data(const_cast<UsdBridgeStage*>(stage));
+    cache_material_data(const_cast<UsdBridgeStage*>(stage));
+
+    if (material_index >= stage->materials.size()) {
+        return USD_BRIDGE_ERROR_INVALID_PRIM;
+    }
+
+    *out_count = stage->materials[material_index].graph_nodes.size();
+    return USD_BRIDGE_SUCCESS;
+}
+
+UsdBridgeError usd_bridge_get_material_node(
+    const UsdBridgeStage* stage,
+    size_t material_index,
+    size_t node_index,
+    UsdBridgeShaderNode* out_node
+) {
+    if (!stage || !out_node) {
+        return USD_BRIDGE_ERROR_NULL_POINTER;
+    }
+
+    cache_stage_data(const_cast<UsdBridgeStage*>(stage));
+    cache_material_data(const_cast<UsdBridgeStage*>(stage));
+
+    if (material_index >= stage->materials.size()) {
+        return USD_BRIDGE_ERROR_INVALID_PRIM;
+    }
+
+    const CachedMaterial& mat = stage->materials[material_index];
+    if (node_index >= mat.graph_nodes.size()) {
+        return USD_BRIDGE_ERROR_INVALID_PRIM;
+    }
+
+    const CachedShaderNode& node = mat.graph_nodes[node_index];
+    out_node->path = node.path.c_str();
+    out_node->shader_id = node.shader_id.c_str();
+    out_node->inputs = node.input_views.empty() ? nullptr : node.input_views.data();
+    out_node->input_count = node.input_views.size();
 
     return USD_BRIDGE_SUCCESS;
 }
@@ -965,6 +1898,31 @@ UsdBridgeError usd_bridge_get_mesh_material_path(
     return USD_BRIDGE_SUCCESS;
 }
 
+UsdBridgeError usd_bridge_get_mesh_subset_material_path(
+    const UsdBridgeStage* stage,
+    size_t mesh_index,
+    size_t subset_index,
+    const char** out_path
+) {
+    if (!stage || !out_path) {
+        return USD_BRIDGE_ERROR_NULL_POINTER;
+    }
+
+    cache_stage_data(const_cast<UsdBridgeStage*>(stage));
+
+    if (mesh_index >= stage->meshes.size()) {
+        return USD_BRIDGE_ERROR_INVALID_PRIM;
+    }
+
+    const CachedMesh& mesh = stage->meshes[mesh_index];
+    if (subset_index >= mesh.subset_material_paths.size()) {
+        return USD_BRIDGE_ERROR_INVALID_PRIM;
+    }
+
+    *out_path = mesh.subset_material_paths[subset_index].c_str();
+    return USD_BRIDGE_SUCCESS;
+}
+
 UsdBridgeError usd_bridge_export_stage(
     const UsdBridgeStage* stage,
     const char* path
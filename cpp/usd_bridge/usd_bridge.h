@@ -57,6 +57,30 @@ UsdBridgeError usd_bridge_open_stage(const char* path, UsdBridgeStage** out_stag
 /// @param stage Stage handle to close (safe to pass NULL)
 void usd_bridge_close_stage(UsdBridgeStage* stage);
 
+/// Read geometry, transforms and instancers at a time code from now on.
+/// Clears cached geometry so it is read again at the new time. Until this
+/// is called, points are read at their earliest time sample and transforms
+/// at their default values.
+///
+/// @param stage Stage handle
+/// @param time_code Time code to evaluate animated attributes at
+/// @return USD_BRIDGE_SUCCESS on success
+UsdBridgeError usd_bridge_set_time(UsdBridgeStage* stage, double time_code);
+
+/// Get the stage's playback range metadata.
+///
+/// @param stage Stage handle
+/// @param out_start Pointer to receive startTimeCode
+/// @param out_end Pointer to receive endTimeCode
+/// @param out_per_second Pointer to receive timeCodesPerSecond
+/// @return USD_BRIDGE_SUCCESS on success
+UsdBridgeError usd_bridge_get_time_codes(
+    const UsdBridgeStage* stage,
+    double* out_start,
+    double* out_end,
+    double* out_per_second
+);
+
 /// Clear cached mesh/instancer data to free memory while keeping stage open.
 /// Useful for memory management in long-running sessions.
 ///
@@ -114,6 +138,44 @@ typedef struct UsdBridgeMeshData {
 
     /// World transform (4x4 column-major matrix)
     float transform[16];
+
+    /// Per-triangle material GeomSubset index (optional, may be NULL; -1 = no subset)
+    const int32_t* triangle_subsets;
+    size_t triangle_subset_count;
+
+    /// Number of materialBind GeomSubsets on this mesh
+    size_t subset_count;
+
+    /// Authored subdivision scheme (0 = none, 1 = catmullClark, 2 = other)
+    int32_t subdivision_scheme;
+
+    /// Control cage topology (only set for subdivision meshes)
+    const uint32_t* face_vertex_counts;
+    size_t face_count;
+    const uint32_t* face_vertex_indices;
+    size_t face_vertex_index_count;
+
+    /// Subdivision creases and corners (optional, may be NULL)
+    const uint32_t* crease_indices;
+    size_t crease_index_count;
+    const uint32_t* crease_lengths;
+    size_t crease_length_count;
+    const float* crease_sharpnesses;
+    size_t crease_sharpness_count;
+    const uint32_t* corner_indices;
+    size_t corner_index_count;
+    const float* corner_sharpnesses;
+    size_t corner_sharpness_count;
+
+    /// Displacement bound in object space (negative = not authored)
+    float displacement_bound;
+
+    /// 1 if rendered (not invisible, default or render purpose), else 0
+    int32_t visible;
+
+    /// Ray types from primvars:bif:visibility:* (bits: 1 = camera,
+    /// 2 = shadow, 4 = diffuse, 8 = specular, 16 = transmission)
+    uint32_t ray_visibility;
 } UsdBridgeMeshData;
 
 /// Get mesh data by index.
@@ -148,6 +210,12 @@ typedef struct UsdBridgeInstancerData {
 
     /// Prototype index per instance
     const int32_t* proto_indices;
+
+    /// 1 if rendered (not invisible, default or render purpose), else 0
+    int32_t visible;
+
+    /// Ray types from primvars:bif:visibility:* (same bits as meshes)
+    uint32_t ray_visibility;
 } UsdBridgeInstancerData;
 
 /// Get point instancer data by index.
@@ -163,6 +231,248 @@ UsdBridgeError usd_bridge_get_instancer(
     UsdBridgeInstancerData* out_data
 );
 
+// ============================================================================
+// Curves Data Extraction
+// ============================================================================
+
+/// Basis curves data structure for FFI transfer (UsdGeomBasisCurves)
+typedef struct UsdBridgeCurvesData {
+    /// Prim path (e.g., "/World/Hair")
+    const char* path;
+
+    /// Control points (x, y, z triplets)
+    const float* points;
+    size_t point_count;
+
+    /// Number of control points per curve
+    const uint32_t* curve_vertex_counts;
+    size_t curve_count;
+
+    /// Authored widths (constant, uniform, vertex or varying; may be NULL)
+    const float* widths;
+    size_t width_count;
+
+    /// Curve type (0 = linear, 1 = cubic)
+    int32_t curve_type;
+
+    /// Cubic basis (0 = bezier, 1 = bspline, 2 = catmullRom)
+    int32_t basis;
+
+    /// Whether normals are authored (ribbon curves)
+    int32_t has_normals;
+
+    /// World transform (4x4 column-major matrix)
+    float transform[16];
+
+    /// Bound material path (empty string if none)
+    const char* material_path;
+} UsdBridgeCurvesData;
+
+/// Get the number of basis curves prims in the stage (UsdGeomBasisCurves).
+///
+/// @param stage Stage handle
+/// @param out_count Pointer to receive curves count
+/// @return USD_BRIDGE_SUCCESS on success
+UsdBridgeError usd_bridge_get_curves_count(
+    const UsdBridgeStage* stage,
+    size_t* out_count
+);
+
+/// Get basis curves data by index.
+/// The returned data is owned by the stage and valid until stage is closed.
+///
+/// @param stage Stage handle
+/// @param index Curves index (0 to curves_count-1)
+/// @param out_data Pointer to receive curves data
+/// @return USD_BRIDGE_SUCCESS on success
+UsdBridgeError usd_bridge_get_curves(
+    const UsdBridgeStage* stage,
+    size_t index,
+    UsdBridgeCurvesData* out_data
+);
+
+// ============================================================================
+// Points Data Extraction
+// ============================================================================
+
+/// Points data structure for FFI transfer (UsdGeomPoints)
+typedef struct UsdBridgePointsData {
+    /// Prim path (e.g., "/World/Particles")
+    const char* path;
+
+    /// Point centers (x, y, z triplets)
+    const float* points;
+    size_t point_count;
+
+    /// Authored widths (constant or per point; may be NULL)
+    const float* widths;
+    size_t width_count;
+
+    /// World transform (4x4 column-major matrix)
+    float transform[16];
+
+    /// Bound material path (empty string if none)
+    const char* material_path;
+} UsdBridgePointsData;
+
+/// Get the number of points prims in the stage (UsdGeomPoints).
+///
+/// @param stage Stage handle
+/// @param out_count Pointer to receive points count
+/// @return USD_BRIDGE_SUCCESS on success
+UsdBridgeError usd_bridge_get_points_count(
+    const UsdBridgeStage* stage,
+    size_t* out_count
+);
+
+/// Get points data by index.
+/// The returned data is owned by the stage and valid until stage is closed.
+///
+/// @param stage Stage handle
+/// @param index Points index (0 to points_count-1)
+/// @param out_data Pointer to receive points data
+/// @return USD_BRIDGE_SUCCESS on success
+UsdBridgeError usd_bridge_get_points(
+    const UsdBridgeStage* stage,
+    size_t index,
+    UsdBridgePointsData* out_data
+);
+
+// ============================================================================
+// Light Data Extraction (UsdLux)
+// ============================================================================
+
+/// Light types
+typedef enum UsdBridgeLightType {
+    USD_BRIDGE_LIGHT_SPHERE = 0,
+    USD_BRIDGE_LIGHT_RECT = 1,
+    USD_BRIDGE_LIGHT_DISK = 2,
+    USD_BRIDGE_LIGHT_CYLINDER = 3,
+    USD_BRIDGE_LIGHT_DISTANT = 4,
+    USD_BRIDGE_LIGHT_DOME = 5,
+} UsdBridgeLightType;
+
+/// Light data structure for FFI transfer (UsdLux lights)
+typedef struct UsdBridgeLightData {
+    /// Prim path (e.g., "/World/Lights/Key")
+    const char* path;
+
+    /// Light type (UsdBridgeLightType)
+    int32_t light_type;
+
+    /// World transform (4x4 column-major matrix)
+    float transform[16];
+
+    /// Common inputs
+    float intensity;
+    float exposure;
+    float color[3];
+    int32_t enable_color_temperature;
+    float color_temperature;
+    int32_t normalize;
+
+    /// Dimensions: radius (sphere, disk, cylinder), width and height (rect),
+    /// length (cylinder), angle in degrees (distant)
+    float radius;
+    float width;
+    float height;
+    float length;
+    float angle;
+
+    /// treatAsPoint (sphere) or treatAsLine (cylinder)
+    int32_t treat_as_point;
+
+    /// Dome texture format (0 = automatic, 1 = latlong, 2 = mirroredBall,
+    /// 3 = angular, 4 = cubeMapVerticalCross)
+    int32_t texture_format;
+
+    /// Texture file (rect and dome lights; empty string if none)
+    const char* texture_file;
+
+    /// Whether ShapingAPI is applied; the shaping inputs are valid if set
+    int32_t has_shaping;
+    float cone_angle;
+    float cone_softness;
+    float focus;
+    float focus_tint[3];
+
+    /// IES profile (empty string if none)
+    const char* ies_file;
+    float ies_angle_scale;
+    int32_t ies_normalize;
+} UsdBridgeLightData;
+
+/// Get the number of lights in the stage (visible UsdLux lights).
+///
+/// @param stage Stage handle
+/// @param out_count Pointer to receive light count
+/// @return USD_BRIDGE_SUCCESS on success
+UsdBridgeError usd_bridge_get_light_count(
+    const UsdBridgeStage* stage,
+    size_t* out_count
+);
+
+/// Get light data by index.
+/// The returned data is owned by the stage and valid until stage is closed.
+///
+/// @param stage Stage handle
+/// @param index Light index (0 to light_count-1)
+/// @param out_data Pointer to receive light data
+/// @return USD_BRIDGE_SUCCESS on success
+UsdBridgeError usd_bridge_get_light(
+    const UsdBridgeStage* stage,
+    size_t index,
+    UsdBridgeLightData* out_data
+);
+
+// ============================================================================
+// Camera Data Extraction (UsdGeomCamera)
+// ============================================================================
+
+/// Camera data structure for FFI transfer (UsdGeomCamera)
+typedef struct UsdBridgeCameraData {
+    /// Prim path (e.g., "/World/Cameras/Shot")
+    const char* path;
+
+    /// World transform (4x4 column-major matrix)
+    float transform[16];
+
+    /// Lens and film back, in tenths of a scene unit
+    float focal_length;
+    float horizontal_aperture;
+    float vertical_aperture;
+
+    /// Near and far clipping distances
+    float clipping_range[2];
+
+    /// f-number (0 = no depth of field) and focus distance
+    float f_stop;
+    float focus_distance;
+} UsdBridgeCameraData;
+
+/// Get the number of cameras in the stage.
+///
+/// @param stage Stage handle
+/// @param out_count Pointer to receive camera count
+/// @return USD_BRIDGE_SUCCESS on success
+UsdBridgeError usd_bridge_get_camera_count(
+    const UsdBridgeStage* stage,
+    size_t* out_count
+);
+
+/// Get camera data by index.
+/// The returned data is owned by the stage and valid until stage is closed.
+///
+/// @param stage Stage handle
+/// @param index Camera index (0 to camera_count-1)
+/// @param out_data Pointer to receive camera data
+/// @return USD_BRIDGE_SUCCESS on success
+UsdBridgeError usd_bridge_get_camera(
+    const UsdBridgeStage* stage,
+    size_t index,
+    UsdBridgeCameraData* out_data
+);
+
 // ============================================================================
 // Material Data Extraction (UsdPreviewSurface)
 // ============================================================================
@@ -187,6 +497,9 @@ typedef struct UsdBridgeMaterialData {
     /// Opacity (0=transparent, 1=opaque)
     float opacity;
 
+    /// Opacity threshold (>0 = binary cutout at this value, 0 = partial opacity)
+    float opacity_threshold;
+
     /// Emissive color (RGB)
     float emissive_color[3];
 
@@ -196,9 +509,24 @@ typedef struct UsdBridgeMaterialData {
     const char* metallic_texture;
     const char* normal_texture;
     const char* emissive_texture;
+    const char* opacity_texture;
+
+    /// Texture channel feeding opacity (0=r, 1=g, 2=b, 3=a)
+    int opacity_texture_channel;
 
     /// Material source type (1=MaterialX, 0=UsdPreviewSurface or default)
     int is_materialx;
+
+    /// Displacement texture path (NULL if not used) and the channel it is read from
+    const char* displacement_texture;
+    int displacement_texture_channel;
+
+    /// Texture reader scale and bias for the displacement channel (1 and 0 if unset)
+    float displacement_scale;
+    float displacement_bias;
+
+    /// Displacement input type (1=vector3 offset, 0=float height)
+    int vector_displacement;
 } UsdBridgeMaterialData;
 
 /// Get the number of materials in the stage.
@@ -224,6 +552,82 @@ UsdBridgeError usd_bridge_get_material(
     UsdBridgeMaterialData* out_data
 );
 
+// ============================================================================
+// Material Shading Graph (MaterialX node networks)
+// ============================================================================
+
+/// Value type of a shader input
+typedef enum UsdBridgeShaderValueKind {
+    USD_BRIDGE_SHADER_VALUE_NONE = 0,
+    USD_BRIDGE_SHADER_VALUE_FLOAT = 1,
+    USD_BRIDGE_SHADER_VALUE_VECTOR2 = 2,
+    USD_BRIDGE_SHADER_VALUE_VECTOR3 = 3,
+    USD_BRIDGE_SHADER_VALUE_STRING = 4,
+} UsdBridgeShaderValueKind;
+
+/// A single shader input (constant value or upstream connection)
+typedef struct UsdBridgeShaderInput {
+    /// Input base name (e.g., "base_color", "file")
+    const char* name;
+
+    /// Kind of constant value (NONE if the input is only connected)
+    int kind;
+
+    /// Numeric value (FLOAT uses [0], VECTOR2 uses [0..2], VECTOR3 uses [0..3])
+    float value[3];
+
+    /// String/asset value for STRING kind (NULL otherwise)
+    const char* string_value;
+
+    /// Index of the upstream node in the material's node list (-1 if unconnected)
+    int connected_node;
+
+    /// Name of the upstream output (e.g., "out"), NULL if unconnected
+    const char* connected_output;
+} UsdBridgeShaderInput;
+
+/// A shader node in a material's network
+typedef struct UsdBridgeShaderNode {
+    /// Shader prim path
+    const char* path;
+
+    /// info:id of the shader (e.g., "ND_image_color3")
+    const char* shader_id;
+
+    /// Inputs (owned by stage)
+    const UsdBridgeShaderInput* inputs;
+    size_t input_count;
+} UsdBridgeShaderNode;
+
+/// Get the number of shader nodes in a material's network.
+/// Node 0 is the surface shader. Returns 0 for materials without a
+/// MaterialX network.
+///
+/// @param stage Stage handle
+/// @param material_index Material index
+/// @param out_count Pointer to receive node count
+/// @return USD_BRIDGE_SUCCESS on success
+UsdBridgeError usd_bridge_get_material_node_count(
+    const UsdBridgeStage* stage,
+    size_t material_index,
+    size_t* out_count
+);
+
+/// Get a shader node from a material's network.
+/// The returned data is owned by the stage and valid until stage is closed.
+///
+/// @param stage Stage handle
+/// @param material_index Material index
+/// @param node_index Node index (0 to node_count-1)
+/// @param out_node Pointer to receive node data
+/// @return USD_BRIDGE_SUCCESS on success
+UsdBridgeError usd_bridge_get_material_node(
+    const UsdBridgeStage* stage,
+    size_t material_index,
+    size_t node_index,
+    UsdBridgeShaderNode* out_node
+);
+
 /// Get the material path bound to a mesh.
 /// Returns empty string if no material is bound.
 ///
@@ -237,6 +641,21 @@ UsdBridgeError usd_bridge_get_mesh_material_path(
     const char** out_path
 );
 
+/// Get the material path bound to one of a mesh's GeomSubsets.
+/// Returns empty string if the subset has no material binding.
+///
+/// @param stage Stage handle
+/// @param mesh_index Mesh index
+/// @param subset_index Subset index (0 to subset_count-1)
+/// @param out_path Pointer to receive material path (owned by stage)
+/// @return USD_BRIDGE_SUCCESS on success
+UsdBridgeError usd_bridge_get_mesh_subset_material_path(
+    const UsdBridgeStage* stage,
+    size_t mesh_index,
+    size_t subset_index,
+    const char** out_path
+);
+
 // ============================================================================
 // Export Functions
 // ============================================================================
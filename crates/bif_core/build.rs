@@ -84,6 +84,7 @@ fn main() {
     // USD core libraries (order matters for linking)
     let usd_libs = [
         "usd_usdShade", // For materials/shaders
+        "usd_usdLux",   // For lights
         "usd_usdGeom",
         "usd_usd",
         "usd_sdf",
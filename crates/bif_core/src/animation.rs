@@ -0,0 +1,262 @@
+//! Time-sampled animation.
+//!
+//! USD attributes can carry `timeSamples`: values keyed by time code. An
+//! [`AnimationCurve`] holds those keys and interpolates linearly between
+//! them, holding the first and last values outside their range as USD does.
+//! [`SceneAnimation`] collects the curves of a loaded scene and
+//! [`TimeCodes`] the stage's playback range.
+
+use std::collections::BTreeMap;
+
+use bif_math::{Mat4, Quat, TransformKey, Vec3};
+
+/// `timeCodesPerSecond` when the stage doesn't author it (the USD fallback).
+pub const DEFAULT_TIME_CODES_PER_SECOND: f64 = 24.0;
+
+/// A value that can be blended between time samples.
+pub trait Interpolate: Clone {
+    /// The value a fraction `t` of the way from `self` to `other`.
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Vec3 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.lerp(*other, t)
+    }
+}
+
+impl Interpolate for Quat {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.slerp(*other, t)
+    }
+}
+
+impl Interpolate for Mat4 {
+    /// Blends scale and translation linearly and slerps rotation, like
+    /// motion blur keys (see [`TransformKey`]).
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        TransformKey::from_matrix(self)
+            .lerp(&TransformKey::from_matrix(other), t)
+            .to_matrix()
+    }
+}
+
+impl<T: Interpolate> Interpolate for Vec<T> {
+    /// Blends element-wise. Arrays of different lengths (topology changes)
+    /// can't be blended, so `self` is held.
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        if self.len() != other.len() {
+            return self.clone();
+        }
+        self.iter()
+            .zip(other)
+            .map(|(a, b)| a.interpolate(b, t))
+            .collect()
+    }
+}
+
+/// Values keyed by time code, sorted by time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationCurve<T> {
+    keys: Vec<(f64, T)>,
+}
+
+impl<T: Interpolate> AnimationCurve<T> {
+    /// Curve through `keys` in any order; of two keys at the same time the
+    /// later one wins. Returns `None` without keys.
+    pub fn new(mut keys: Vec<(f64, T)>) -> Option<Self> {
+        // Stable, so duplicates keep their authored order
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut unique: Vec<(f64, T)> = Vec::with_capacity(keys.len());
+        for key in keys {
+            match unique.last_mut() {
+                Some(last) if last.0 == key.0 => *last = key,
+                _ => unique.push(key),
+            }
+        }
+        (!unique.is_empty()).then_some(Self { keys: unique })
+    }
+
+    /// A curve holding `value` at all times.
+    pub fn constant(value: T) -> Self {
+        Self {
+            keys: vec![(0.0, value)],
+        }
+    }
+
+    /// The keys, sorted by time.
+    pub fn keys(&self) -> &[(f64, T)] {
+        &self.keys
+    }
+
+    /// Times of the keys, in order.
+    pub fn times(&self) -> impl Iterator<Item = f64> + '_ {
+        self.keys.iter().map(|(time, _)| *time)
+    }
+
+    /// Value of the earliest key.
+    pub fn first(&self) -> &T {
+        &self.keys[0].1
+    }
+
+    /// Check if the value changes over time (more than one key).
+    pub fn is_animated(&self) -> bool {
+        self.keys.len() > 1
+    }
+
+    /// Value at `time`, interpolated between the keys around it.
+    pub fn evaluate(&self, time: f64) -> T {
+        let next = self.keys.partition_point(|(key_time, _)| *key_time <= time);
+        if next == 0 {
+            return self.keys[0].1.clone();
+        }
+        let (t0, v0) = &self.keys[next - 1];
+        match self.keys.get(next) {
+            Some((t1, v1)) => v0.interpolate(v1, ((time - t0) / (t1 - t0)) as f32),
+            None => v0.clone(),
+        }
+    }
+
+    /// Curve of `f` applied to every key.
+    pub fn map<U: Interpolate>(&self, f: impl Fn(&T) -> U) -> AnimationCurve<U> {
+        AnimationCurve {
+            keys: self
+                .keys
+                .iter()
+                .map(|(time, value)| (*time, f(value)))
+                .collect(),
+        }
+    }
+
+    /// Curve of `f` applied to both curves at every key time of either.
+    pub fn combine<U: Interpolate, V: Interpolate>(
+        &self,
+        other: &AnimationCurve<U>,
+        f: impl Fn(&T, &U) -> V,
+    ) -> AnimationCurve<V> {
+        let mut times: Vec<f64> = self.times().chain(other.times()).collect();
+        times.sort_by(f64::total_cmp);
+        times.dedup();
+        AnimationCurve {
+            keys: times
+                .into_iter()
+                .map(|time| (time, f(&self.evaluate(time), &other.evaluate(time))))
+                .collect(),
+        }
+    }
+}
+
+/// Playback range of a stage: `startTimeCode`, `endTimeCode` and
+/// `timeCodesPerSecond`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeCodes {
+    /// First time code of the animation
+    pub start: f64,
+
+    /// Last time code of the animation
+    pub end: f64,
+
+    /// Time codes per second of playback
+    pub per_second: f64,
+}
+
+impl Default for TimeCodes {
+    fn default() -> Self {
+        Self {
+            start: 0.0,
+            end: 0.0,
+            per_second: DEFAULT_TIME_CODES_PER_SECOND,
+        }
+    }
+}
+
+impl TimeCodes {
+    /// Check if the stage authors a range to play back.
+    pub fn is_animated(&self) -> bool {
+        self.end > self.start
+    }
+
+    /// Seconds from `start` to `time`.
+    pub fn seconds(&self, time: f64) -> f64 {
+        (time - self.start) / self.per_second
+    }
+}
+
+/// Animation curves of a loaded scene, keyed by the index of what they
+/// animate. Transform curves are in world space.
+#[derive(Clone, Debug, Default)]
+pub struct SceneAnimation {
+    /// Transforms of animated instances
+    pub instances: BTreeMap<usize, AnimationCurve<Mat4>>,
+
+    /// Vertex positions of deforming prototypes
+    pub prototypes: BTreeMap<usize, AnimationCurve<Vec<Vec3>>>,
+
+    /// Transforms of animated curve sets
+    pub curves: BTreeMap<usize, AnimationCurve<Mat4>>,
+
+    /// Transforms of animated point sets
+    pub points: BTreeMap<usize, AnimationCurve<Mat4>>,
+
+    /// Transforms of animated lights
+    pub lights: BTreeMap<usize, AnimationCurve<Mat4>>,
+
+    /// Transforms of animated cameras
+    pub cameras: BTreeMap<usize, AnimationCurve<Mat4>>,
+}
+
+impl SceneAnimation {
+    /// Check if nothing in the scene is animated.
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+            && self.prototypes.is_empty()
+            && self.curves.is_empty()
+            && self.points.is_empty()
+            && self.lights.is_empty()
+            && self.cameras.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_holds_and_interpolates() {
+        let curve = AnimationCurve::new(vec![(10.0, 4.0f32), (0.0, 0.0), (10.0, 2.0)]).unwrap();
+
+        // Sorted, with the later duplicate winning
+        assert_eq!(curve.keys(), &[(0.0, 0.0), (10.0, 2.0)]);
+        assert!(curve.is_animated());
+        assert_eq!(curve.evaluate(-5.0), 0.0);
+        assert_eq!(curve.evaluate(5.0), 1.0);
+        assert_eq!(curve.evaluate(10.0), 2.0);
+        assert_eq!(curve.evaluate(20.0), 2.0);
+        assert!(AnimationCurve::<f32>::new(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_combine_uses_all_key_times() {
+        let a = AnimationCurve::new(vec![(0.0, Vec3::ZERO), (2.0, Vec3::X * 2.0)]).unwrap();
+        let b = AnimationCurve::new(vec![(1.0, Vec3::Y), (3.0, Vec3::Y * 3.0)]).unwrap();
+        let sum = a.combine(&b, |a, b| *a + *b);
+
+        assert_eq!(sum.times().collect::<Vec<_>>(), vec![0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(sum.evaluate(1.0), Vec3::new(1.0, 1.0, 0.0));
+        assert_eq!(sum.evaluate(3.0), Vec3::new(2.0, 3.0, 0.0));
+    }
+
+    #[test]
+    fn test_mismatched_arrays_hold() {
+        let curve =
+            AnimationCurve::new(vec![(0.0, vec![Vec3::ZERO]), (1.0, vec![Vec3::X, Vec3::Y])])
+                .unwrap();
+        assert_eq!(curve.evaluate(0.5), vec![Vec3::ZERO]);
+    }
+}
@@ -0,0 +1,157 @@
+//! Cameras imported from UsdGeomCamera.
+//!
+//! A [`Camera`] keeps the physical lens and film back of a perspective
+//! `Camera` prim. As in USD, focal length and apertures are in tenths of a
+//! scene unit (millimeters for a centimeter scene), and the camera looks
+//! down its local -Z axis with +Y up. Defaults are the UsdGeom fallback
+//! values.
+
+use bif_math::{Mat4, Vec3};
+
+/// A camera in the scene (a UsdGeom `Camera` prim).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Camera {
+    /// Camera name (the USD prim path)
+    pub name: String,
+
+    /// Local-to-world transform
+    pub transform: Mat4,
+
+    /// Lens focal length in tenths of a scene unit
+    pub focal_length: f32,
+
+    /// Film back width in tenths of a scene unit
+    pub horizontal_aperture: f32,
+
+    /// Film back height in tenths of a scene unit
+    pub vertical_aperture: f32,
+
+    /// Near and far clipping distances
+    pub clipping_range: (f32, f32),
+
+    /// Lens f-number (0 = pinhole, no depth of field)
+    pub f_stop: f32,
+
+    /// Distance to the plane in focus
+    pub focus_distance: f32,
+}
+
+impl Camera {
+    /// Create a camera with UsdGeom fallback values at the origin.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            transform: Mat4::IDENTITY,
+            focal_length: 50.0,
+            horizontal_aperture: 20.955,
+            vertical_aperture: 15.2908,
+            clipping_range: (1.0, 1_000_000.0),
+            f_stop: 0.0,
+            focus_distance: 0.0,
+        }
+    }
+
+    /// Set the local-to-world transform.
+    pub fn with_transform(mut self, transform: Mat4) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Vertical field of view in degrees.
+    pub fn vertical_fov(&self) -> f32 {
+        fov_degrees(self.vertical_aperture, self.focal_length)
+    }
+
+    /// Horizontal field of view in degrees.
+    pub fn horizontal_fov(&self) -> f32 {
+        fov_degrees(self.horizontal_aperture, self.focal_length)
+    }
+
+    /// Film back width over height.
+    pub fn aspect_ratio(&self) -> f32 {
+        self.horizontal_aperture / self.vertical_aperture
+    }
+
+    /// Check if the lens has depth of field (a positive f-stop and focus
+    /// distance).
+    pub fn has_depth_of_field(&self) -> bool {
+        self.f_stop > 0.0 && self.focus_distance > 0.0
+    }
+
+    /// Diameter of the lens opening in scene units (0 without depth of
+    /// field).
+    pub fn lens_diameter(&self) -> f32 {
+        if self.has_depth_of_field() {
+            // Focal length is in tenths of a scene unit
+            self.focal_length / self.f_stop * 0.1
+        } else {
+            0.0
+        }
+    }
+
+    /// World-space position of the camera.
+    pub fn position(&self) -> Vec3 {
+        self.transform.w_axis.truncate()
+    }
+
+    /// World-space view direction (the local -Z axis).
+    pub fn direction(&self) -> Vec3 {
+        self.transform
+            .transform_vector3(Vec3::NEG_Z)
+            .normalize_or_zero()
+    }
+
+    /// World-space up direction (the local +Y axis).
+    pub fn up(&self) -> Vec3 {
+        self.transform
+            .transform_vector3(Vec3::Y)
+            .normalize_or_zero()
+    }
+
+    /// Point the camera looks at: at the focus distance when it has one,
+    /// otherwise one unit ahead.
+    pub fn target(&self) -> Vec3 {
+        let distance = if self.focus_distance > 0.0 {
+            self.focus_distance
+        } else {
+            1.0
+        };
+        self.position() + self.direction() * distance
+    }
+}
+
+/// Field of view in degrees of `aperture` behind a lens of `focal_length`.
+fn fov_degrees(aperture: f32, focal_length: f32) -> f32 {
+    (2.0 * (aperture / (2.0 * focal_length)).atan()).to_degrees()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_of_view() {
+        let mut camera = Camera::new("/cam");
+        camera.focal_length = 18.0;
+        camera.vertical_aperture = 36.0;
+        assert!((camera.vertical_fov() - 90.0).abs() < 1e-4);
+        assert!(camera.horizontal_fov() < camera.vertical_fov());
+
+        assert!(!camera.has_depth_of_field());
+        camera.f_stop = 2.0;
+        camera.focus_distance = 10.0;
+        assert!((camera.lens_diameter() - 0.9).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_orientation() {
+        let camera = Camera::new("/cam").with_transform(
+            Mat4::from_translation(Vec3::new(0.0, 5.0, 0.0))
+                * Mat4::from_rotation_x(-std::f32::consts::FRAC_PI_2),
+        );
+        assert_eq!(camera.position(), Vec3::new(0.0, 5.0, 0.0));
+        assert!((camera.direction() - Vec3::NEG_Y).length() < 1e-5);
+        assert!((camera.up() - Vec3::NEG_Z).length() < 1e-5);
+        assert!((camera.target() - Vec3::new(0.0, 4.0, 0.0)).length() < 1e-5);
+    }
+}
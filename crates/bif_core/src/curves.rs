@@ -0,0 +1,398 @@
+//! Curve geometry for hair, fur and grass.
+//!
+//! [`Curves`] mirrors `UsdGeomBasisCurves`: a batch of curves sharing one
+//! basis, each a run of control points with per-vertex widths. Renderers
+//! consume curves as cubic Bézier segments (see [`Curves::segments`]), so
+//! every basis is converted to Bézier control points here. Only
+//! non-periodic curves are supported.
+
+use bif_math::{Aabb, Vec3};
+
+/// Width used when `widths` is not authored (the USD fallback).
+pub const DEFAULT_CURVE_WIDTH: f32 = 1.0;
+
+/// Curve basis (USD `type` + `basis`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CurveBasis {
+    /// Polyline through the control points (`type = "linear"`)
+    #[default]
+    Linear,
+    /// Cubic Bézier, 3 points per segment plus a shared endpoint
+    Bezier,
+    /// Uniform cubic B-spline (approximating)
+    BSpline,
+    /// Catmull-Rom spline (interpolating)
+    CatmullRom,
+}
+
+impl CurveBasis {
+    /// Map USD `type` and `basis` tokens to a basis.
+    ///
+    /// Unknown cubic bases (e.g. `hermite`) fall back to B-spline.
+    pub fn from_usd(curve_type: &str, basis: &str) -> Self {
+        if curve_type == "linear" {
+            return Self::Linear;
+        }
+        match basis {
+            "bezier" => Self::Bezier,
+            "catmullRom" => Self::CatmullRom,
+            _ => Self::BSpline,
+        }
+    }
+
+    /// Number of segments of a curve with `vertex_count` control points.
+    pub fn segment_count(self, vertex_count: usize) -> usize {
+        match self {
+            Self::Linear => vertex_count.saturating_sub(1),
+            Self::Bezier => vertex_count.saturating_sub(1) / 3,
+            Self::BSpline | Self::CatmullRom => vertex_count.saturating_sub(3),
+        }
+    }
+}
+
+/// Cross-section of a curve.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CurveShape {
+    /// Tube with circular cross-section (hair, fur)
+    #[default]
+    Round,
+    /// Flat strip facing the viewer (grass blades, curves with normals)
+    Ribbon,
+}
+
+/// One cubic Bézier piece of a curve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CurveSegment {
+    /// Bézier control points
+    pub points: [Vec3; 4],
+    /// Width at the start and end of the segment
+    pub widths: [f32; 2],
+    /// Index of the curve this segment belongs to
+    pub curve: u32,
+    /// Range of the curve parameter (0 at the root, 1 at the tip) covered
+    pub u_range: [f32; 2],
+}
+
+impl CurveSegment {
+    /// Evaluate the segment at `t` in [0, 1].
+    pub fn point(&self, t: f32) -> Vec3 {
+        let [p0, p1, p2, p3] = self.points;
+        let s = 1.0 - t;
+        p0 * (s * s * s) + p1 * (3.0 * s * s * t) + p2 * (3.0 * s * t * t) + p3 * (t * t * t)
+    }
+
+    /// Width at `t`, interpolated linearly between the endpoints.
+    pub fn width(&self, t: f32) -> f32 {
+        self.widths[0] + (self.widths[1] - self.widths[0]) * t
+    }
+
+    /// Bounds of the control hull, padded by half the widest width.
+    pub fn bounds(&self) -> Aabb {
+        let [p0, p1, p2, p3] = self.points;
+        let radius = Vec3::splat(0.5 * self.widths[0].max(self.widths[1]));
+        let min = p0.min(p1).min(p2).min(p3) - radius;
+        let max = p0.max(p1).max(p2).max(p3) + radius;
+        Aabb::from_points(min, max)
+    }
+}
+
+/// A batch of curves with one basis and shape (a `UsdGeomBasisCurves`).
+#[derive(Clone, Debug)]
+pub struct Curves {
+    /// Control points of all curves, curve after curve
+    pub positions: Vec<Vec3>,
+
+    /// Width at each control point
+    pub widths: Vec<f32>,
+
+    /// Number of control points of each curve
+    pub vertex_counts: Vec<u32>,
+
+    /// Basis shared by all curves
+    pub basis: CurveBasis,
+
+    /// Cross-section shared by all curves
+    pub shape: CurveShape,
+
+    /// Axis-aligned bounding box, including widths
+    pub bounds: Aabb,
+}
+
+impl Curves {
+    /// Create curves from control points and authored widths.
+    ///
+    /// `widths` may hold one value (constant), one per curve (uniform), one
+    /// per control point (vertex), or one per segment endpoint (varying);
+    /// all are resolved to per-vertex widths. Empty or mismatched widths use
+    /// [`DEFAULT_CURVE_WIDTH`].
+    pub fn new(
+        positions: Vec<Vec3>,
+        vertex_counts: Vec<u32>,
+        widths: &[f32],
+        basis: CurveBasis,
+        shape: CurveShape,
+    ) -> Self {
+        let widths = Self::resolve_widths(&positions, &vertex_counts, widths, basis);
+        let bounds = Self::compute_bounds(&positions, &widths);
+        Self {
+            positions,
+            widths,
+            vertex_counts,
+            basis,
+            shape,
+            bounds,
+        }
+    }
+
+    fn resolve_widths(
+        positions: &[Vec3],
+        vertex_counts: &[u32],
+        widths: &[f32],
+        basis: CurveBasis,
+    ) -> Vec<f32> {
+        let vertex_total = positions.len();
+        let varying_total: usize = vertex_counts
+            .iter()
+            .map(|&n| basis.segment_count(n as usize) + 1)
+            .sum();
+
+        if widths.len() == vertex_total {
+            return widths.to_vec();
+        }
+        if widths.len() == 1 {
+            return vec![widths[0]; vertex_total];
+        }
+
+        let mut resolved = Vec::with_capacity(vertex_total);
+        let mut varying_start = 0;
+        for (curve, &count) in vertex_counts.iter().enumerate() {
+            let count = count as usize;
+            if widths.len() == vertex_counts.len() {
+                resolved.extend(std::iter::repeat_n(widths[curve], count));
+            } else if widths.len() == varying_total && !widths.is_empty() {
+                // Spread the per-segment-endpoint values along the curve
+                let varying = basis.segment_count(count) + 1;
+                let values = &widths[varying_start..varying_start + varying];
+                varying_start += varying;
+                resolved.extend((0..count).map(|i| {
+                    let x = i as f32 / (count.max(2) - 1) as f32 * (varying - 1) as f32;
+                    let j = (x.floor() as usize).min(varying.saturating_sub(2));
+                    match values {
+                        [single] => *single,
+                        _ => values[j] + (values[j + 1] - values[j]) * (x - j as f32),
+                    }
+                }));
+            } else {
+                if !widths.is_empty() && curve == 0 {
+                    log::warn!(
+                        "{} curve widths don't match {} curves / {} vertices, using default",
+                        widths.len(),
+                        vertex_counts.len(),
+                        vertex_total
+                    );
+                }
+                resolved.extend(std::iter::repeat_n(DEFAULT_CURVE_WIDTH, count));
+            }
+        }
+        resolved.resize(vertex_total, DEFAULT_CURVE_WIDTH);
+        resolved
+    }
+
+    fn compute_bounds(positions: &[Vec3], widths: &[f32]) -> Aabb {
+        if positions.is_empty() {
+            return Aabb::empty();
+        }
+
+        let mut min = Vec3::splat(f32::INFINITY);
+        let mut max = Vec3::splat(f32::NEG_INFINITY);
+        for (&p, &w) in positions.iter().zip(widths) {
+            min = min.min(p - Vec3::splat(0.5 * w));
+            max = max.max(p + Vec3::splat(0.5 * w));
+        }
+        Aabb::from_points(min, max)
+    }
+
+    /// Number of curves.
+    pub fn curve_count(&self) -> usize {
+        self.vertex_counts.len()
+    }
+
+    /// Total number of cubic segments over all curves.
+    pub fn segment_count(&self) -> usize {
+        self.vertex_counts
+            .iter()
+            .map(|&n| self.basis.segment_count(n as usize))
+            .sum()
+    }
+
+    /// Convert all curves to cubic Bézier segments.
+    ///
+    /// Each segment spans an equal share of its curve's parameter range.
+    /// Curves with too few control points for the basis are skipped.
+    pub fn segments(&self) -> Vec<CurveSegment> {
+        let mut segments = Vec::with_capacity(self.segment_count());
+        let mut start = 0;
+        for (curve, &count) in self.vertex_counts.iter().enumerate() {
+            let count = count as usize;
+            let end = (start + count).min(self.positions.len());
+            let p = &self.positions[start..end];
+            let w = &self.widths[start..end];
+            start = end;
+
+            let segment_count = self.basis.segment_count(p.len());
+            for i in 0..segment_count {
+                let (points, widths) = match self.basis {
+                    CurveBasis::Linear => {
+                        let (a, b) = (p[i], p[i + 1]);
+                        let third = (b - a) / 3.0;
+                        ([a, a + third, b - third, b], [w[i], w[i + 1]])
+                    }
+                    CurveBasis::Bezier => {
+                        let j = 3 * i;
+                        ([p[j], p[j + 1], p[j + 2], p[j + 3]], [w[j], w[j + 3]])
+                    }
+                    CurveBasis::BSpline => {
+                        let [p0, p1, p2, p3] = [p[i], p[i + 1], p[i + 2], p[i + 3]];
+                        (
+                            [
+                                (p0 + p1 * 4.0 + p2) / 6.0,
+                                (p1 * 2.0 + p2) / 3.0,
+                                (p1 + p2 * 2.0) / 3.0,
+                                (p1 + p2 * 4.0 + p3) / 6.0,
+                            ],
+                            [w[i + 1], w[i + 2]],
+                        )
+                    }
+                    CurveBasis::CatmullRom => {
+                        let [p0, p1, p2, p3] = [p[i], p[i + 1], p[i + 2], p[i + 3]];
+                        (
+                            [p1, p1 + (p2 - p0) / 6.0, p2 - (p3 - p1) / 6.0, p2],
+                            [w[i + 1], w[i + 2]],
+                        )
+                    }
+                };
+                let n = segment_count as f32;
+                segments.push(CurveSegment {
+                    points,
+                    widths,
+                    curve: curve as u32,
+                    u_range: [i as f32 / n, (i + 1) as f32 / n],
+                });
+            }
+        }
+        segments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(count: usize) -> Vec<Vec3> {
+        (0..count).map(|i| Vec3::new(i as f32, 0.0, 0.0)).collect()
+    }
+
+    #[test]
+    fn test_segment_counts() {
+        assert_eq!(CurveBasis::Linear.segment_count(4), 3);
+        assert_eq!(CurveBasis::Bezier.segment_count(7), 2);
+        assert_eq!(CurveBasis::BSpline.segment_count(6), 3);
+        assert_eq!(CurveBasis::CatmullRom.segment_count(2), 0);
+        assert_eq!(
+            CurveBasis::from_usd("cubic", "catmullRom"),
+            CurveBasis::CatmullRom
+        );
+        assert_eq!(CurveBasis::from_usd("linear", "bezier"), CurveBasis::Linear);
+    }
+
+    #[test]
+    fn test_catmull_rom_interpolates_control_points() {
+        let positions = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(3.0, 1.0, 0.0),
+            Vec3::new(4.0, 0.0, 0.0),
+        ];
+        let curves = Curves::new(
+            positions.clone(),
+            vec![5],
+            &[0.1],
+            CurveBasis::CatmullRom,
+            CurveShape::Round,
+        );
+        let segments = curves.segments();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].point(0.0), positions[1]);
+        assert_eq!(segments[0].point(1.0), positions[2]);
+        assert_eq!(segments[1].point(1.0), positions[3]);
+        assert_eq!(segments[1].u_range, [0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_bspline_segments_join() {
+        let positions = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 2.0, 0.0),
+            Vec3::new(2.0, -1.0, 1.0),
+            Vec3::new(3.0, 0.5, 0.0),
+            Vec3::new(4.0, 0.0, 2.0),
+        ];
+        let curves = Curves::new(
+            positions,
+            vec![5],
+            &[],
+            CurveBasis::BSpline,
+            CurveShape::Round,
+        );
+        let segments = curves.segments();
+        assert_eq!(segments.len(), 2);
+        assert!((segments[0].point(1.0) - segments[1].point(0.0)).length() < 1e-6);
+        assert_eq!(segments[0].widths, [DEFAULT_CURVE_WIDTH; 2]);
+    }
+
+    #[test]
+    fn test_width_interpolation_modes() {
+        let vertex = Curves::new(
+            line(3),
+            vec![3],
+            &[0.1, 0.2, 0.3],
+            CurveBasis::Linear,
+            CurveShape::Round,
+        );
+        assert_eq!(vertex.segments()[1].widths, [0.2, 0.3]);
+
+        let uniform = Curves::new(
+            line(6),
+            vec![3, 3],
+            &[0.5, 0.25],
+            CurveBasis::Linear,
+            CurveShape::Ribbon,
+        );
+        assert_eq!(uniform.widths, vec![0.5, 0.5, 0.5, 0.25, 0.25, 0.25]);
+
+        // Varying: one width per segment endpoint of a 5-vertex B-spline
+        let varying = Curves::new(
+            line(5),
+            vec![5],
+            &[0.0, 0.1, 0.4],
+            CurveBasis::BSpline,
+            CurveShape::Round,
+        );
+        assert!((varying.widths[2] - 0.1).abs() < 1e-6);
+        assert!((varying.widths[4] - 0.4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_bounds_include_width() {
+        let curves = Curves::new(
+            line(2),
+            vec![2],
+            &[1.0],
+            CurveBasis::Linear,
+            CurveShape::Round,
+        );
+        assert!((curves.bounds.y.min + 0.5).abs() < 1e-6);
+        assert!((curves.bounds.x.max - 1.5).abs() < 1e-6);
+    }
+}
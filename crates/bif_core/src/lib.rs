@@ -2,7 +2,10 @@
 //!
 //! This crate provides:
 //!
-//! - **Scene graph types**: `Scene`, `Prototype`, `Instance`, `Mesh`
+//! - **Scene graph types**: `Scene`, `Prototype`, `Instance`, `Mesh`, `Curves`, `Points`
+//! - **Animation**: time-sampled transforms and points (`AnimationCurve`)
+//! - **Lights**: UsdLux lights with their shaping and texture inputs (`Light`)
+//! - **Cameras**: UsdGeom cameras with their lens and film back (`Camera`)
 //! - **USD support**: All USD formats via C++ bridge (USDA, USD, USDC)
 //!
 //! # Example
@@ -18,13 +21,28 @@
 //!     scene.instance_count());
 //! ```
 
+pub mod animation;
+pub mod camera;
+pub mod curves;
+pub mod light;
 pub mod mesh;
+pub mod points;
 pub mod scene;
+pub mod shading;
 pub mod texture;
 pub mod usd;
 
 // Re-export commonly used types
+pub use animation::{AnimationCurve, SceneAnimation, TimeCodes};
+pub use camera::Camera;
+pub use curves::{CurveBasis, CurveShape, Curves};
+pub use light::{Light, LightKind, LightShaping};
 pub use mesh::Mesh;
-pub use scene::{Instance, Material, Prototype, Scene, Transform};
+pub use points::Points;
+pub use scene::{
+    CurveSet, Instance, Material, MaterialId, PointSet, Prototype, Scene, Transform, Visibility,
+    NO_MATERIAL,
+};
+pub use shading::{ShadingGraph, ShadingInput, ShadingNode, ShadingValue};
 pub use texture::{Texture, TextureCache, TextureError, TextureResult};
 pub use usd::{load_usd, load_usda, load_usda_from_string};
@@ -0,0 +1,301 @@
+//! Lights imported from UsdLux.
+//!
+//! A [`Light`] keeps the inputs shared by every UsdLux light (intensity,
+//! exposure, color, color temperature), the per-type dimensions in
+//! [`LightKind`], and the optional `ShapingAPI` inputs in [`LightShaping`].
+//! Defaults are the UsdLux fallback values. Lights face down their local -Z
+//! axis, as in USD.
+
+use bif_math::{Mat4, Vec3};
+
+/// Light type and its dimensions, with UsdLux fallback values as defaults.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LightKind {
+    /// `SphereLight` (fallback radius 0.5)
+    Sphere { radius: f32, treat_as_point: bool },
+
+    /// `RectLight` in the XY plane (fallback width 1, height 1)
+    Rect { width: f32, height: f32 },
+
+    /// `DiskLight` in the XY plane (fallback radius 0.5)
+    Disk { radius: f32 },
+
+    /// `CylinderLight` along the X axis (fallback length 1, radius 0.5)
+    Cylinder {
+        length: f32,
+        radius: f32,
+        treat_as_line: bool,
+    },
+
+    /// `DistantLight` with an angular diameter in degrees (fallback 0.53)
+    Distant { angle: f32 },
+
+    /// `DomeLight` lighting the scene from infinitely far away
+    Dome { texture_format: DomeTextureFormat },
+}
+
+impl LightKind {
+    /// Light for a UsdLux prim type name with fallback dimensions.
+    pub fn from_type_name(type_name: &str) -> Option<Self> {
+        match type_name {
+            "SphereLight" => Some(Self::Sphere {
+                radius: 0.5,
+                treat_as_point: false,
+            }),
+            "RectLight" => Some(Self::Rect {
+                width: 1.0,
+                height: 1.0,
+            }),
+            "DiskLight" => Some(Self::Disk { radius: 0.5 }),
+            "CylinderLight" => Some(Self::Cylinder {
+                length: 1.0,
+                radius: 0.5,
+                treat_as_line: false,
+            }),
+            "DistantLight" => Some(Self::Distant { angle: 0.53 }),
+            "DomeLight" => Some(Self::Dome {
+                texture_format: DomeTextureFormat::default(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Check if the light is infinitely far away (distant and dome lights).
+    pub fn is_infinite(&self) -> bool {
+        matches!(self, Self::Distant { .. } | Self::Dome { .. })
+    }
+}
+
+/// Parameterization of a dome light texture (`inputs:texture:format`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DomeTextureFormat {
+    /// Guess from the image (USD's fallback)
+    #[default]
+    Automatic,
+    /// Latitude-longitude (equirectangular) map
+    Latlong,
+    /// Photo of a mirrored ball
+    MirroredBall,
+    /// Angular map
+    Angular,
+    /// Vertical cross of cube faces
+    CubeMapVerticalCross,
+}
+
+impl DomeTextureFormat {
+    /// Parse a UsdLux `texture:format` token.
+    pub fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "automatic" => Some(Self::Automatic),
+            "latlong" => Some(Self::Latlong),
+            "mirroredBall" => Some(Self::MirroredBall),
+            "angular" => Some(Self::Angular),
+            "cubeMapVerticalCross" => Some(Self::CubeMapVerticalCross),
+            _ => None,
+        }
+    }
+}
+
+/// `ShapingAPI` inputs: spot cone, focus and IES profile.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LightShaping {
+    /// Half-angle of the spot cone in degrees (90 = unshaped)
+    pub cone_angle: f32,
+
+    /// Fraction of the cone that falls off softly (0-1)
+    pub cone_softness: f32,
+
+    /// Exponent narrowing the emission towards -Z
+    pub focus: f32,
+
+    /// Color of the light outside the focus
+    pub focus_tint: Vec3,
+
+    /// Path to an IES profile
+    pub ies_file: Option<String>,
+
+    /// Scale of the IES profile's angles (0 = unscaled)
+    pub ies_angle_scale: f32,
+
+    /// Normalize the IES profile to its peak
+    pub ies_normalize: bool,
+}
+
+impl Default for LightShaping {
+    fn default() -> Self {
+        Self {
+            cone_angle: 90.0,
+            cone_softness: 0.0,
+            focus: 0.0,
+            focus_tint: Vec3::ZERO,
+            ies_file: None,
+            ies_angle_scale: 0.0,
+            ies_normalize: false,
+        }
+    }
+}
+
+/// A light in the scene (a UsdLux light prim).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Light {
+    /// Light name (from USD prim path)
+    pub name: String,
+
+    /// Light type and dimensions
+    pub kind: LightKind,
+
+    /// Local-to-world transform
+    pub transform: Mat4,
+
+    /// Brightness multiplier
+    pub intensity: f32,
+
+    /// Brightness in stops (power of 2 multiplier)
+    pub exposure: f32,
+
+    /// Linear RGB color
+    pub color: Vec3,
+
+    /// Tint the color by `color_temperature`
+    pub enable_color_temperature: bool,
+
+    /// Blackbody temperature in Kelvin
+    pub color_temperature: f32,
+
+    /// Divide the power by the light's area, so resizing keeps it constant
+    pub normalize: bool,
+
+    /// Path to an emission texture (rect and dome lights)
+    pub texture: Option<String>,
+
+    /// Spot cone, focus and IES inputs, if `ShapingAPI` inputs are authored
+    pub shaping: Option<LightShaping>,
+}
+
+impl Light {
+    /// Create a light with UsdLux fallback inputs at the origin.
+    pub fn new(name: impl Into<String>, kind: LightKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            transform: Mat4::IDENTITY,
+            intensity: 1.0,
+            exposure: 0.0,
+            color: Vec3::ONE,
+            enable_color_temperature: false,
+            color_temperature: 6500.0,
+            normalize: false,
+            texture: None,
+            shaping: None,
+        }
+    }
+
+    /// Set the local-to-world transform.
+    pub fn with_transform(mut self, transform: Mat4) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Color tinted by the color temperature when it is enabled.
+    pub fn tint(&self) -> Vec3 {
+        if self.enable_color_temperature {
+            self.color * blackbody_color(self.color_temperature)
+        } else {
+            self.color
+        }
+    }
+
+    /// Emitted radiance: `tint * intensity * 2^exposure`.
+    ///
+    /// `normalize` is not applied; it depends on the light's world size.
+    pub fn radiance(&self) -> Vec3 {
+        self.tint() * self.intensity * self.exposure.exp2()
+    }
+
+    /// World-space position of the light's origin.
+    pub fn position(&self) -> Vec3 {
+        self.transform.w_axis.truncate()
+    }
+
+    /// World-space direction the light faces (its local -Z axis).
+    pub fn direction(&self) -> Vec3 {
+        self.transform
+            .transform_vector3(Vec3::NEG_Z)
+            .normalize_or_zero()
+    }
+}
+
+/// Linear Rec.709 color of a blackbody at `kelvin`, scaled to unit
+/// luminance.
+///
+/// Uses the Kim et al. fit of the Planckian locus, valid from 1667 K to
+/// 25000 K; temperatures outside are clamped.
+pub fn blackbody_color(kelvin: f32) -> Vec3 {
+    let t = kelvin.clamp(1667.0, 25000.0) as f64;
+    let (t2, t3) = (t * t, t * t * t);
+
+    // CIE 1931 chromaticity on the Planckian locus
+    let x = if t <= 4000.0 {
+        -0.266_123_9e9 / t3 - 0.234_358_9e6 / t2 + 0.877_695_6e3 / t + 0.179_910
+    } else {
+        -3.025_846_9e9 / t3 + 2.107_037_9e6 / t2 + 0.222_634_7e3 / t + 0.240_390
+    };
+    let (x2, x3) = (x * x, x * x * x);
+    let y = if t <= 2222.0 {
+        -1.106_381_4 * x3 - 1.348_110_20 * x2 + 2.185_558_32 * x - 0.202_196_83
+    } else if t <= 4000.0 {
+        -0.954_947_6 * x3 - 1.374_185_93 * x2 + 2.091_370_15 * x - 0.167_488_67
+    } else {
+        3.081_758_0 * x3 - 5.873_386_70 * x2 + 3.751_129_97 * x - 0.370_014_83
+    };
+
+    // XYZ at unit luminance, then linear Rec.709
+    let (big_x, big_z) = (x / y, (1.0 - x - y) / y);
+    let rgb = Vec3::new(
+        (3.240_454_2 * big_x - 1.537_138_5 - 0.498_531_4 * big_z) as f32,
+        (-0.969_266_0 * big_x + 1.876_010_8 + 0.041_556_0 * big_z) as f32,
+        (0.055_643_4 * big_x - 0.204_025_9 + 1.057_225_2 * big_z) as f32,
+    )
+    .max(Vec3::ZERO);
+
+    let luminance = rgb.dot(Vec3::new(0.2126, 0.7152, 0.0722));
+    rgb / luminance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blackbody_color() {
+        // D65 is close to white; candlelight is orange, sky is blue
+        let d65 = blackbody_color(6500.0);
+        assert!((d65 - Vec3::ONE).abs().max_element() < 0.05, "{d65}");
+
+        let warm = blackbody_color(2000.0);
+        assert!(warm.x > warm.y && warm.y > warm.z, "{warm}");
+        let cool = blackbody_color(12000.0);
+        assert!(cool.z > cool.x, "{cool}");
+
+        for rgb in [d65, warm, cool] {
+            let luminance = rgb.dot(Vec3::new(0.2126, 0.7152, 0.0722));
+            assert!((luminance - 1.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_radiance() {
+        let mut light = Light::new("Key", LightKind::from_type_name("RectLight").unwrap());
+        light.intensity = 3.0;
+        light.exposure = 2.0;
+        light.color = Vec3::new(1.0, 0.5, 0.25);
+        assert_eq!(light.radiance(), Vec3::new(12.0, 6.0, 3.0));
+
+        light.enable_color_temperature = true;
+        light.color_temperature = 3000.0;
+        assert!(light.radiance().z < 3.0);
+
+        let light = light.with_transform(Mat4::from_rotation_x(std::f32::consts::FRAC_PI_2));
+        assert!((light.direction() - Vec3::Y).length() < 1e-5);
+    }
+}
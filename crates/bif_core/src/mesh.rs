@@ -2,10 +2,24 @@
 //!
 //! This module provides a GPU-agnostic mesh representation that can be
 //! populated from various file formats (USD, OBJ, etc.) and converted
-//! to GPU vertex buffers by the viewport.
+//! to GPU vertex buffers by the viewport. Subdivision surfaces are refined
+//! into plain meshes by the [`subdivision`](SubdivisionMesh) support, and
+//! displaced meshes are tessellated with [`Mesh::displaced`].
 
 use bif_math::{Aabb, Vec3};
 
+mod displacement;
+mod subdivision;
+
+pub use displacement::{
+    DisplacementMap, TessellationSettings, DEFAULT_EDGE_LENGTH_FRACTION,
+    DEFAULT_MAX_TESSELLATION_LEVEL,
+};
+pub use subdivision::{
+    SubdivisionMesh, SubdivisionScheme, SubdivisionTags, DEFAULT_SUBDIVISION_LEVEL,
+    SHARPNESS_INFINITE,
+};
+
 /// A mesh consisting of vertex positions, optional normals, and triangle indices.
 ///
 /// This is the core geometry type used throughout BIF. It is intentionally
@@ -25,7 +39,13 @@ pub struct Mesh {
     /// Triangle indices (every 3 indices form a triangle)
     pub indices: Vec<u32>,
 
-    /// Axis-aligned bounding box
+    /// Further position keys for deformation blur (empty = static).
+    ///
+    /// `positions` is the key at shutter open; these follow it, evenly
+    /// spaced up to shutter close (see `bif_math::motion_segment`).
+    pub motion: Vec<Vec<Vec3>>,
+
+    /// Axis-aligned bounding box (over all position keys)
     pub bounds: Aabb,
 }
 
@@ -41,6 +61,7 @@ impl Mesh {
             normals,
             uvs: None,
             indices,
+            motion: Vec::new(),
             bounds,
         }
     }
@@ -58,10 +79,37 @@ impl Mesh {
             normals,
             uvs,
             indices,
+            motion: Vec::new(),
             bounds,
         }
     }
 
+    /// Add position keys after `positions` for deformation blur.
+    ///
+    /// Every key needs one position per vertex; if any doesn't match, the
+    /// mesh stays static.
+    pub fn with_motion(mut self, keys: Vec<Vec<Vec3>>) -> Self {
+        if let Some(key) = keys.iter().find(|k| k.len() != self.positions.len()) {
+            log::warn!(
+                "Motion key has {} positions for {} vertices, ignoring motion",
+                key.len(),
+                self.positions.len()
+            );
+            return self;
+        }
+
+        for key in &keys {
+            self.bounds = Aabb::surrounding(&self.bounds, &Self::compute_bounds(key));
+        }
+        self.motion = keys;
+        self
+    }
+
+    /// Check if the mesh has deformation keys.
+    pub fn is_deforming(&self) -> bool {
+        !self.motion.is_empty()
+    }
+
     /// Compute axis-aligned bounding box from positions.
     fn compute_bounds(positions: &[Vec3]) -> Aabb {
         if positions.is_empty() {
@@ -199,6 +247,31 @@ impl Mesh {
     /// // triangles[1] = [v0, v1, v2] for second triangle
     /// ```
     pub fn extract_triangle_vertices(&self) -> Vec<[Vec3; 3]> {
+        self.triangles_of(&self.positions)
+    }
+
+    /// Extract the triangles of each deformation key in [`Mesh::motion`].
+    ///
+    /// Triangles are in the same order as [`Mesh::extract_triangle_vertices`].
+    pub fn extract_motion_triangle_vertices(&self) -> Vec<Vec<[Vec3; 3]>> {
+        self.motion
+            .iter()
+            .map(|key| self.triangles_of(key))
+            .collect()
+    }
+
+    /// Extract the UVs of each triangle's corners (empty without UVs).
+    ///
+    /// Triangles are in the same order as [`Mesh::extract_triangle_vertices`].
+    pub fn extract_triangle_uvs(&self) -> Vec<[[f32; 2]; 3]> {
+        match &self.uvs {
+            Some(uvs) if uvs.len() == self.positions.len() => self.triangles_of(uvs),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Triangles of the mesh with per-vertex values `positions`.
+    fn triangles_of<T: Copy>(&self, positions: &[T]) -> Vec<[T; 3]> {
         let mut triangles = Vec::with_capacity(self.triangle_count());
 
         for chunk in self.indices.chunks(3) {
@@ -211,21 +284,18 @@ impl Mesh {
             let i2 = chunk[2] as usize;
 
             // Bounds check
-            if i0 >= self.positions.len()
-                || i1 >= self.positions.len()
-                || i2 >= self.positions.len()
-            {
+            if i0 >= positions.len() || i1 >= positions.len() || i2 >= positions.len() {
                 log::warn!(
                     "Invalid triangle indices: [{}, {}, {}], vertex count: {}",
                     i0,
                     i1,
                     i2,
-                    self.positions.len()
+                    positions.len()
                 );
                 continue;
             }
 
-            triangles.push([self.positions[i0], self.positions[i1], self.positions[i2]]);
+            triangles.push([positions[i0], positions[i1], positions[i2]]);
         }
 
         triangles
@@ -319,4 +389,21 @@ mod tests {
         assert_eq!(triangles[1][1], positions[3]);
         assert_eq!(triangles[1][2], positions[2]);
     }
+
+    #[test]
+    fn test_motion_keys() {
+        let positions = vec![Vec3::ZERO, Vec3::X, Vec3::Y];
+        let moved: Vec<Vec3> = positions.iter().map(|p| *p + Vec3::Z).collect();
+
+        let mesh = Mesh::new(positions.clone(), vec![0, 1, 2], None).with_motion(vec![moved]);
+        assert!(mesh.is_deforming());
+        assert!((mesh.bounds.z.max - 1.0).abs() < 1e-3);
+        let keys = mesh.extract_motion_triangle_vertices();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0][0][1], Vec3::new(1.0, 0.0, 1.0));
+
+        // Keys must match the vertex count
+        let mesh = Mesh::new(positions, vec![0, 1, 2], None).with_motion(vec![vec![Vec3::ZERO]]);
+        assert!(!mesh.is_deforming());
+    }
 }
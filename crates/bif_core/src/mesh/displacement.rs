@@ -0,0 +1,363 @@
+//! Render-time displacement of triangle meshes.
+//!
+//! Ivar displaces geometry when it builds a scene rather than perturbing
+//! normals while shading, so silhouettes and shadows change too. A mesh is
+//! first tessellated until its edges are shorter than a target length, then
+//! every vertex is moved by a texture lookup at its UV and normals are
+//! recomputed from the displaced surface.
+//!
+//! Tessellation splits edges, not triangles: an edge is split whenever it is
+//! longer than the target, and both triangles sharing it see the same
+//! midpoint, so the displaced mesh has no cracks.
+
+use std::collections::HashMap;
+
+use bif_math::Vec3;
+
+use super::Mesh;
+use crate::texture::Texture;
+
+/// Default edge length as a fraction of the mesh's bounding box diagonal.
+pub const DEFAULT_EDGE_LENGTH_FRACTION: f32 = 1.0 / 128.0;
+
+/// Default maximum number of edge-splitting passes.
+pub const DEFAULT_MAX_TESSELLATION_LEVEL: u32 = 6;
+
+/// How finely to tessellate a mesh before displacing it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TessellationSettings {
+    /// Target edge length in object space (`None` = a fraction of the
+    /// bounding box diagonal, see [`DEFAULT_EDGE_LENGTH_FRACTION`])
+    pub max_edge_length: Option<f32>,
+
+    /// Maximum number of splitting passes (each pass at most quadruples the
+    /// triangle count)
+    pub max_level: u32,
+}
+
+impl Default for TessellationSettings {
+    fn default() -> Self {
+        Self {
+            max_edge_length: None,
+            max_level: DEFAULT_MAX_TESSELLATION_LEVEL,
+        }
+    }
+}
+
+/// A displacement texture and how to apply it.
+#[derive(Clone, Copy, Debug)]
+pub struct DisplacementMap<'a> {
+    /// Texture sampled at each vertex UV
+    pub texture: &'a Texture,
+
+    /// Channel holding scalar height (0=r, 1=g, 2=b, 3=a)
+    pub channel: usize,
+
+    /// Multiplier applied to texture values
+    pub scale: f32,
+
+    /// Offset added to scaled texture values (to each component of vectors)
+    pub bias: f32,
+
+    /// Use the texture's RGB as an object-space offset instead of a height
+    /// along the vertex normal
+    pub vector: bool,
+
+    /// Maximum displacement distance (`None` = unbounded)
+    pub bound: Option<f32>,
+}
+
+impl DisplacementMap<'_> {
+    /// Object-space offset for a vertex with this UV and normal.
+    pub fn offset(&self, uv: [f32; 2], normal: Vec3) -> Vec3 {
+        let offset = if self.vector {
+            self.texture.sample(uv[0], uv[1]) * self.scale + self.bias
+        } else {
+            let height = match self.channel {
+                0..=2 => self.texture.sample(uv[0], uv[1])[self.channel],
+                _ => self.texture.sample_channel(uv[0], uv[1], self.channel),
+            };
+            normal * (height * self.scale + self.bias)
+        };
+
+        match self.bound {
+            Some(bound) => offset.clamp_length_max(bound),
+            None => offset,
+        }
+    }
+}
+
+impl Mesh {
+    /// Split edges longer than the target length until none remain or
+    /// `settings.max_level` passes have run.
+    ///
+    /// Positions, normals and UVs of new vertices are interpolated. Also
+    /// returns the source triangle each output triangle came from.
+    pub fn tessellated(&self, settings: &TessellationSettings) -> (Mesh, Vec<u32>) {
+        let max_edge_length = settings
+            .max_edge_length
+            .unwrap_or(self.size() * DEFAULT_EDGE_LENGTH_FRACTION);
+
+        let mut positions = self.positions.clone();
+        let mut normals = self
+            .normals
+            .clone()
+            .filter(|normals| normals.len() == positions.len());
+        let mut uvs = self.uvs.clone().filter(|uvs| uvs.len() == positions.len());
+        let mut triangles: Vec<[u32; 3]> = self
+            .indices
+            .chunks_exact(3)
+            .map(|tri| [tri[0], tri[1], tri[2]])
+            .collect();
+        let mut origins: Vec<u32> = (0..triangles.len() as u32).collect();
+
+        if max_edge_length > 0.0 {
+            let max_length_sq = max_edge_length * max_edge_length;
+            for _ in 0..settings.max_level {
+                let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+                let mut split = Vec::with_capacity(triangles.len());
+                let mut split_origins = Vec::with_capacity(triangles.len());
+
+                for (tri, &origin) in triangles.iter().zip(&origins) {
+                    let mut mids = [None; 3];
+                    for (i, mid) in mids.iter_mut().enumerate() {
+                        let (a, b) = (tri[i], tri[(i + 1) % 3]);
+                        let (pa, pb) = (positions[a as usize], positions[b as usize]);
+                        if pa.distance_squared(pb) <= max_length_sq {
+                            continue;
+                        }
+
+                        let key = (a.min(b), a.max(b));
+                        *mid = Some(*midpoints.entry(key).or_insert_with(|| {
+                            let (a, b) = (a as usize, b as usize);
+                            positions.push((pa + pb) * 0.5);
+                            if let Some(normals) = &mut normals {
+                                let n = (normals[a] + normals[b]).normalize_or_zero();
+                                normals.push(n);
+                            }
+                            if let Some(uvs) = &mut uvs {
+                                let (ua, ub) = (uvs[a], uvs[b]);
+                                uvs.push([(ua[0] + ub[0]) * 0.5, (ua[1] + ub[1]) * 0.5]);
+                            }
+                            positions.len() as u32 - 1
+                        }));
+                    }
+
+                    split_triangle(*tri, mids, &mut split);
+                    split_origins.resize(split.len(), origin);
+                }
+
+                let done = split.len() == triangles.len();
+                triangles = split;
+                origins = split_origins;
+                if done {
+                    break;
+                }
+            }
+        }
+
+        let indices = triangles.into_iter().flatten().collect();
+        (
+            Mesh::new_with_uvs(positions, indices, normals, uvs),
+            origins,
+        )
+    }
+
+    /// Tessellate and displace this mesh, recomputing its normals.
+    ///
+    /// Meshes without UVs are only tessellated, since there is nothing to
+    /// look the texture up with. Also returns the source triangle each
+    /// output triangle came from.
+    pub fn displaced(
+        &self,
+        map: &DisplacementMap,
+        settings: &TessellationSettings,
+    ) -> (Mesh, Vec<u32>) {
+        let (mut mesh, origins) = self.tessellated(settings);
+
+        let Some(uvs) = mesh.uvs.clone() else {
+            log::warn!(
+                "Mesh has no UVs, skipping displacement by {}",
+                map.texture.path
+            );
+            return (mesh, origins);
+        };
+
+        mesh.ensure_normals();
+        let normals = mesh.normals.take().unwrap_or_default();
+        for ((position, normal), uv) in mesh.positions.iter_mut().zip(&normals).zip(&uvs) {
+            *position += map.offset(*uv, *normal);
+        }
+
+        let mut displaced = Mesh::new_with_uvs(mesh.positions, mesh.indices, None, Some(uvs));
+        displaced.compute_normals();
+        (displaced, origins)
+    }
+}
+
+/// Split a triangle given the midpoints of its edges.
+///
+/// `mids[i]` is the midpoint of the edge from `tri[i]` to `tri[i + 1]`.
+/// Winding is preserved.
+fn split_triangle(tri: [u32; 3], mids: [Option<u32>; 3], out: &mut Vec<[u32; 3]>) {
+    let corner = |i: usize| tri[i % 3];
+    match mids {
+        [None, None, None] => out.push(tri),
+        [Some(ab), Some(bc), Some(ca)] => {
+            let [a, b, c] = tri;
+            out.extend([[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]);
+        }
+        _ => {
+            let split_count = mids.iter().flatten().count();
+            if split_count == 1 {
+                // Halve the triangle through the split edge's midpoint
+                let i = mids.iter().position(Option::is_some).unwrap_or(0);
+                let m = mids[i].unwrap_or_default();
+                let (a, b, c) = (corner(i), corner(i + 1), corner(i + 2));
+                out.extend([[a, m, c], [m, b, c]]);
+            } else {
+                // Cut off the corner between the two split edges, then split
+                // the remaining quad
+                let i = mids.iter().position(Option::is_none).unwrap_or(0);
+                let (a, b, c) = (corner(i), corner(i + 1), corner(i + 2));
+                let m_bc = mids[(i + 1) % 3].unwrap_or_default();
+                let m_ca = mids[(i + 2) % 3].unwrap_or_default();
+                out.extend([[m_bc, c, m_ca], [a, b, m_bc], [a, m_bc, m_ca]]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Unit square in the XY plane with UVs matching positions.
+    fn square() -> Mesh {
+        let positions = vec![Vec3::ZERO, Vec3::X, Vec3::new(1.0, 1.0, 0.0), Vec3::Y];
+        let uvs = vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        let normals = vec![Vec3::Z; 4];
+        Mesh::new_with_uvs(positions, vec![0, 1, 2, 0, 2, 3], Some(normals), Some(uvs))
+    }
+
+    fn area(mesh: &Mesh) -> f32 {
+        mesh.extract_triangle_vertices()
+            .iter()
+            .map(|[a, b, c]| (*b - *a).cross(*c - *a).length() * 0.5)
+            .sum()
+    }
+
+    #[test]
+    fn test_tessellate_to_edge_length() {
+        let settings = TessellationSettings {
+            max_edge_length: Some(0.3),
+            max_level: 8,
+        };
+        let (mesh, origins) = square().tessellated(&settings);
+
+        assert_eq!(origins.len(), mesh.triangle_count());
+        assert!(origins.iter().all(|&o| o < 2));
+        assert!((area(&mesh) - 1.0).abs() < 1e-4);
+        for [a, b, c] in mesh.extract_triangle_vertices() {
+            for (p, q) in [(a, b), (b, c), (c, a)] {
+                assert!(p.distance(q) <= 0.3 + 1e-5);
+            }
+        }
+
+        // Every edge is shared by at most two triangles, so there are no
+        // T-junctions along split edges
+        let mut edges: HashMap<(u32, u32), u32> = HashMap::new();
+        for tri in mesh.indices.chunks_exact(3) {
+            for i in 0..3 {
+                let (a, b) = (tri[i], tri[(i + 1) % 3]);
+                *edges.entry((a.min(b), a.max(b))).or_default() += 1;
+            }
+        }
+        let boundary_length: f32 = edges
+            .iter()
+            .filter(|(_, &count)| count == 1)
+            .map(|((a, b), _)| mesh.positions[*a as usize].distance(mesh.positions[*b as usize]))
+            .sum();
+        assert!((boundary_length - 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_tessellation_respects_max_level() {
+        let settings = TessellationSettings {
+            max_edge_length: Some(1e-6),
+            max_level: 2,
+        };
+        let (mesh, _) = square().tessellated(&settings);
+        assert_eq!(mesh.triangle_count(), 2 * 16);
+    }
+
+    #[test]
+    fn test_scalar_displacement_is_bounded() {
+        // Left half of the texture is flat, right half raised by 2
+        let pixels = vec![[0.0, 0.0, 0.0, 1.0], [2.0, 0.0, 0.0, 1.0]];
+        let texture = Texture::new(2, 1, pixels, "height");
+        let map = DisplacementMap {
+            texture: &texture,
+            channel: 0,
+            scale: 1.0,
+            bias: 0.0,
+            vector: false,
+            bound: Some(0.5),
+        };
+        let settings = TessellationSettings {
+            max_edge_length: Some(0.25),
+            max_level: 8,
+        };
+        let (mesh, _) = square().displaced(&map, &settings);
+
+        let mut bounded = 0;
+        for (p, uv) in mesh.positions.iter().zip(mesh.uvs.as_ref().unwrap()) {
+            let height = texture.sample(uv[0], uv[1]).x;
+            assert!((p.z - height.min(0.5)).abs() < 1e-4, "{p} at {uv:?}");
+            bounded += (height > 0.5) as usize;
+        }
+        assert!(bounded > 0);
+
+        // Normals follow the displaced surface, not the flat input
+        let normals = mesh.normals.as_ref().unwrap();
+        assert_eq!(normals.len(), mesh.positions.len());
+        assert!(normals.iter().any(|n| n.x.abs() > 0.1));
+    }
+
+    #[test]
+    fn test_vector_displacement() {
+        // Mid-gray at rest: (0.5, 0.5, 1) * 2 - 1 moves up by 1
+        let texture = Texture::solid_color(Vec3::new(0.5, 0.5, 1.0));
+        let map = DisplacementMap {
+            texture: &texture,
+            channel: 0,
+            scale: 2.0,
+            bias: -1.0,
+            vector: true,
+            bound: None,
+        };
+        let (mesh, _) = square().displaced(&map, &TessellationSettings::default());
+
+        assert!(mesh.positions.iter().all(|p| (p.z - 1.0).abs() < 1e-6));
+        assert!(mesh.bounds.z.min > 0.9);
+        assert!(mesh.bounds.x.min.abs() < 1e-6 && mesh.bounds.y.min.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mesh_without_uvs_is_not_displaced() {
+        let texture = Texture::solid_color(Vec3::ONE);
+        let map = DisplacementMap {
+            texture: &texture,
+            channel: 0,
+            scale: 1.0,
+            bias: 0.0,
+            vector: false,
+            bound: None,
+        };
+        let mut mesh = square();
+        mesh.uvs = None;
+
+        let (displaced, _) = mesh.displaced(&map, &TessellationSettings::default());
+        assert!(displaced.positions.iter().all(|p| p.z == 0.0));
+    }
+}
@@ -0,0 +1,598 @@
+//! Catmull-Clark subdivision surfaces.
+//!
+//! USD meshes with `subdivisionScheme = "catmullClark"` describe a control
+//! cage rather than the surface itself. [`SubdivisionMesh`] refines such a
+//! cage a fixed number of levels and triangulates the result, so the
+//! viewport and Ivar both see the smooth surface.
+//!
+//! Creases and corners follow USD's semi-sharp rules: an edge or vertex of
+//! sharpness `s` uses the sharp rule for `floor(s)` levels and a blend for
+//! the fractional remainder. Boundaries use USD's default
+//! `interpolateBoundary = "edgeAndCorner"`: boundary edges are infinitely
+//! sharp and vertices on a single face are corners.
+
+use std::collections::HashMap;
+
+use bif_math::Vec3;
+
+use super::Mesh;
+
+/// Refinement level used when loading subdivision meshes.
+pub const DEFAULT_SUBDIVISION_LEVEL: u32 = 2;
+
+/// Sharpness treated as infinitely sharp (USD's `SHARPNESS_INFINITE`).
+pub const SHARPNESS_INFINITE: f32 = 10.0;
+
+/// Subdivision scheme of a USD mesh.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SubdivisionScheme {
+    /// Polygonal mesh, triangulated as-is
+    #[default]
+    None,
+
+    /// Catmull-Clark surface, refined before triangulation
+    CatmullClark,
+}
+
+impl SubdivisionScheme {
+    /// Parse a USD `subdivisionScheme` token.
+    ///
+    /// Returns `None` for schemes we don't refine (`loop`, `bilinear`).
+    pub fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "none" => Some(Self::None),
+            "catmullClark" => Some(Self::CatmullClark),
+            _ => None,
+        }
+    }
+}
+
+/// Crease and corner tags, laid out like the USD attributes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SubdivisionTags {
+    /// Vertex chains along creased edges (`creaseIndices`)
+    pub crease_indices: Vec<u32>,
+
+    /// Number of vertices in each chain (`creaseLengths`)
+    pub crease_lengths: Vec<u32>,
+
+    /// One sharpness per chain, or one per edge (`creaseSharpnesses`)
+    pub crease_sharpnesses: Vec<f32>,
+
+    /// Sharp vertices (`cornerIndices`)
+    pub corner_indices: Vec<u32>,
+
+    /// Sharpness of each corner (`cornerSharpnesses`)
+    pub corner_sharpnesses: Vec<f32>,
+}
+
+impl SubdivisionTags {
+    /// Check if there are no creases or corners.
+    pub fn is_empty(&self) -> bool {
+        self.crease_indices.is_empty() && self.corner_indices.is_empty()
+    }
+}
+
+/// A polygonal control cage that can be refined with Catmull-Clark.
+#[derive(Clone, Debug, Default)]
+pub struct SubdivisionMesh {
+    /// Vertex positions
+    pub positions: Vec<Vec3>,
+
+    /// Per-vertex UVs, interpolated linearly
+    pub uvs: Option<Vec<[f32; 2]>>,
+
+    /// Number of vertices per face
+    pub face_vertex_counts: Vec<u32>,
+
+    /// Vertex indices for each face
+    pub face_vertex_indices: Vec<u32>,
+
+    /// Cage face each face was refined from
+    pub face_origins: Vec<u32>,
+
+    /// Sharpness of creased edges, keyed by (lower, higher) vertex index
+    creases: HashMap<(u32, u32), f32>,
+
+    /// Sharpness of corner vertices
+    corners: HashMap<u32, f32>,
+}
+
+/// Sorted key of the edge between two vertices.
+#[inline]
+fn edge_key(a: u32, b: u32) -> (u32, u32) {
+    (a.min(b), a.max(b))
+}
+
+/// Blend from the smooth to the sharp rule by a semi-sharp sharpness.
+#[inline]
+fn blend(smooth: Vec3, sharp: Vec3, sharpness: f32) -> Vec3 {
+    if sharpness >= 1.0 {
+        sharp
+    } else if sharpness <= 0.0 {
+        smooth
+    } else {
+        smooth.lerp(sharp, sharpness)
+    }
+}
+
+/// An edge of the cage with up to two adjacent faces.
+struct Edge {
+    vertices: [u32; 2],
+    faces: [u32; 2],
+    face_count: u32,
+}
+
+impl SubdivisionMesh {
+    /// Create a cage from USD-style polygon topology.
+    ///
+    /// Faces with fewer than three vertices or out-of-range indices are
+    /// dropped; `face_origins` keeps the original index of the others.
+    pub fn new(
+        positions: Vec<Vec3>,
+        face_vertex_counts: &[u32],
+        face_vertex_indices: &[u32],
+    ) -> Self {
+        let mut counts = Vec::with_capacity(face_vertex_counts.len());
+        let mut indices = Vec::with_capacity(face_vertex_indices.len());
+        let mut origins = Vec::with_capacity(face_vertex_counts.len());
+
+        let mut offset = 0usize;
+        for (face, &count) in face_vertex_counts.iter().enumerate() {
+            let Some(vertices) = face_vertex_indices.get(offset..offset + count as usize) else {
+                log::warn!("Subdivision cage face {} runs past the index array", face);
+                break;
+            };
+            offset += count as usize;
+
+            let in_range = vertices.iter().all(|&v| (v as usize) < positions.len());
+            if count < 3 || !in_range {
+                continue;
+            }
+            counts.push(count);
+            indices.extend_from_slice(vertices);
+            origins.push(face as u32);
+        }
+
+        Self {
+            positions,
+            uvs: None,
+            face_vertex_counts: counts,
+            face_vertex_indices: indices,
+            face_origins: origins,
+            creases: HashMap::new(),
+            corners: HashMap::new(),
+        }
+    }
+
+    /// Set per-vertex UVs (ignored unless there is one per position).
+    pub fn with_uvs(mut self, uvs: Option<Vec<[f32; 2]>>) -> Self {
+        self.uvs = uvs.filter(|uvs| uvs.len() == self.positions.len());
+        self
+    }
+
+    /// Add creases and corners.
+    pub fn with_tags(mut self, tags: &SubdivisionTags) -> Self {
+        // Sharpness is given per chain or per edge
+        let edge_count: usize = tags
+            .crease_lengths
+            .iter()
+            .map(|&len| len.saturating_sub(1) as usize)
+            .sum();
+        let per_edge = tags.crease_sharpnesses.len() == edge_count
+            && tags.crease_sharpnesses.len() != tags.crease_lengths.len();
+
+        let mut offset = 0usize;
+        let mut edge = 0usize;
+        for (chain, &length) in tags.crease_lengths.iter().enumerate() {
+            let Some(vertices) = tags.crease_indices.get(offset..offset + length as usize) else {
+                break;
+            };
+            offset += length as usize;
+
+            for pair in vertices.windows(2) {
+                let index = if per_edge { edge } else { chain };
+                edge += 1;
+                let sharpness = tags.crease_sharpnesses.get(index).copied().unwrap_or(0.0);
+                if sharpness > 0.0 {
+                    self.creases.insert(edge_key(pair[0], pair[1]), sharpness);
+                }
+            }
+        }
+
+        for (&vertex, &sharpness) in tags.corner_indices.iter().zip(&tags.corner_sharpnesses) {
+            if sharpness > 0.0 {
+                self.corners.insert(vertex, sharpness);
+            }
+        }
+
+        self
+    }
+
+    /// Number of faces.
+    pub fn face_count(&self) -> usize {
+        self.face_vertex_counts.len()
+    }
+
+    /// Sharpness of the edge between `a` and `b` (0 if smooth).
+    pub fn crease_sharpness(&self, a: u32, b: u32) -> f32 {
+        self.creases.get(&edge_key(a, b)).copied().unwrap_or(0.0)
+    }
+
+    /// Refine the cage `levels` times.
+    pub fn refined(&self, levels: u32) -> Self {
+        let mut mesh = self.clone();
+        for _ in 0..levels {
+            mesh = mesh.refine();
+        }
+        mesh
+    }
+
+    /// One level of Catmull-Clark refinement.
+    ///
+    /// Every n-sided face becomes n quads. New vertices are ordered as
+    /// vertex points (same indices as before), then edge points, then face
+    /// points.
+    pub fn refine(&self) -> Self {
+        let vertex_count = self.positions.len();
+        let offsets: Vec<usize> = self
+            .face_vertex_counts
+            .iter()
+            .scan(0usize, |offset, &count| {
+                let start = *offset;
+                *offset += count as usize;
+                Some(start)
+            })
+            .collect();
+        let face_vertices = |face: usize| {
+            let start = offsets[face];
+            &self.face_vertex_indices[start..start + self.face_vertex_counts[face] as usize]
+        };
+
+        // Edge table, and the edge leaving each face corner
+        let mut edge_ids: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut edges: Vec<Edge> = Vec::new();
+        let mut corner_edges = Vec::with_capacity(self.face_vertex_indices.len());
+        for face in 0..self.face_count() {
+            let vertices = face_vertices(face);
+            for (i, &a) in vertices.iter().enumerate() {
+                let b = vertices[(i + 1) % vertices.len()];
+                let id = *edge_ids.entry(edge_key(a, b)).or_insert_with(|| {
+                    edges.push(Edge {
+                        vertices: [a, b],
+                        faces: [0; 2],
+                        face_count: 0,
+                    });
+                    (edges.len() - 1) as u32
+                });
+                let edge = &mut edges[id as usize];
+                if edge.face_count < 2 {
+                    edge.faces[edge.face_count as usize] = face as u32;
+                }
+                edge.face_count += 1;
+                corner_edges.push(id);
+            }
+        }
+
+        // Boundary and non-manifold edges are infinitely sharp
+        let edge_sharpness: Vec<f32> = edges
+            .iter()
+            .map(|edge| {
+                if edge.face_count != 2 {
+                    f32::INFINITY
+                } else {
+                    self.crease_sharpness(edge.vertices[0], edge.vertices[1])
+                }
+            })
+            .collect();
+
+        let average = |values: &mut dyn Iterator<Item = Vec3>| {
+            let (sum, count) = values.fold((Vec3::ZERO, 0), |(s, n), v| (s + v, n + 1));
+            sum / count.max(1) as f32
+        };
+
+        // Face points: centroids of the faces
+        let face_points: Vec<Vec3> = (0..self.face_count())
+            .map(|face| {
+                average(
+                    &mut face_vertices(face)
+                        .iter()
+                        .map(|&v| self.positions[v as usize]),
+                )
+            })
+            .collect();
+
+        // Edge points: midpoints on sharp edges, smooth average otherwise
+        let edge_points: Vec<Vec3> = edges
+            .iter()
+            .zip(&edge_sharpness)
+            .map(|(edge, &sharpness)| {
+                let [a, b] = edge.vertices.map(|v| self.positions[v as usize]);
+                let midpoint = (a + b) * 0.5;
+                let [f0, f1] = edge.faces.map(|f| face_points[f as usize]);
+                blend((a + b + f0 + f1) * 0.25, midpoint, sharpness)
+            })
+            .collect();
+
+        // Per-vertex sums for the vertex rules
+        let mut face_sums = vec![(Vec3::ZERO, 0u32); vertex_count];
+        for (face, &face_point) in face_points.iter().enumerate() {
+            for &v in face_vertices(face) {
+                let sum = &mut face_sums[v as usize];
+                *sum = (sum.0 + face_point, sum.1 + 1);
+            }
+        }
+        let mut edge_sums = vec![(Vec3::ZERO, 0u32); vertex_count];
+        let mut sharp_sums = vec![(Vec3::ZERO, 0u32, 0.0f32); vertex_count];
+        for (edge, &sharpness) in edges.iter().zip(&edge_sharpness) {
+            let [a, b] = edge.vertices;
+            let midpoint = (self.positions[a as usize] + self.positions[b as usize]) * 0.5;
+            for (v, other) in [(a, b), (b, a)] {
+                let sum = &mut edge_sums[v as usize];
+                *sum = (sum.0 + midpoint, sum.1 + 1);
+                if sharpness > 0.0 {
+                    let sharp = &mut sharp_sums[v as usize];
+                    *sharp = (
+                        sharp.0 + self.positions[other as usize],
+                        sharp.1 + 1,
+                        sharp.2 + sharpness,
+                    );
+                }
+            }
+        }
+
+        let vertex_points = (0..vertex_count).map(|v| {
+            let position = self.positions[v];
+            let (face_sum, face_count) = face_sums[v];
+            let (edge_sum, valence) = edge_sums[v];
+            let (sharp_sum, sharp_count, sharpness_sum) = sharp_sums[v];
+            if valence == 0 {
+                return position;
+            }
+
+            // Smooth rule, only defined for interior vertices
+            let smooth = if face_count == valence {
+                let n = valence as f32;
+                let q = face_sum / face_count as f32;
+                let r = edge_sum / n;
+                (q + 2.0 * r + (n - 3.0) * position) / n
+            } else {
+                position
+            };
+
+            // Crease or corner rule, blended in by the average sharpness
+            let is_boundary_corner = face_count == 1 && sharp_count == 2;
+            let sharpness = sharpness_sum / sharp_count.max(1) as f32;
+            let point = if sharp_count > 2 || is_boundary_corner {
+                blend(smooth, position, sharpness)
+            } else if sharp_count == 2 {
+                blend(smooth, (sharp_sum + 6.0 * position) / 8.0, sharpness)
+            } else {
+                smooth
+            };
+
+            match self.corners.get(&(v as u32)) {
+                Some(&corner) => blend(point, position, corner),
+                None => point,
+            }
+        });
+
+        let mut positions = Vec::with_capacity(vertex_count + edges.len() + self.face_count());
+        positions.extend(vertex_points);
+        positions.extend(&edge_points);
+        positions.extend(&face_points);
+
+        // UVs are interpolated linearly
+        let uvs = self.uvs.as_ref().map(|uvs| {
+            let mut refined = uvs.clone();
+            refined.extend(edges.iter().map(|edge| {
+                let [a, b] = edge.vertices.map(|v| uvs[v as usize]);
+                [(a[0] + b[0]) * 0.5, (a[1] + b[1]) * 0.5]
+            }));
+            refined.extend((0..self.face_count()).map(|face| {
+                let vertices = face_vertices(face);
+                let n = vertices.len() as f32;
+                let sum = vertices.iter().fold([0.0, 0.0], |s, &v| {
+                    [s[0] + uvs[v as usize][0], s[1] + uvs[v as usize][1]]
+                });
+                [sum[0] / n, sum[1] / n]
+            }));
+            refined
+        });
+
+        // One quad per face corner: vertex, next edge, face, previous edge
+        let edge_base = vertex_count as u32;
+        let face_base = edge_base + edges.len() as u32;
+        let mut counts = Vec::with_capacity(self.face_vertex_indices.len());
+        let mut indices = Vec::with_capacity(self.face_vertex_indices.len() * 4);
+        let mut origins = Vec::with_capacity(self.face_vertex_indices.len());
+        for (face, (&start, &count)) in offsets.iter().zip(&self.face_vertex_counts).enumerate() {
+            let n = count as usize;
+            for i in 0..n {
+                let next_edge = corner_edges[start + i];
+                let previous_edge = corner_edges[start + (i + n - 1) % n];
+                counts.push(4);
+                indices.extend([
+                    self.face_vertex_indices[start + i],
+                    edge_base + next_edge,
+                    face_base + face as u32,
+                    edge_base + previous_edge,
+                ]);
+                origins.push(self.face_origins[face]);
+            }
+        }
+
+        // Sharpness decreases by one per level on both halves of an edge
+        let mut creases = HashMap::new();
+        for (id, edge) in edges.iter().enumerate() {
+            let sharpness = self.crease_sharpness(edge.vertices[0], edge.vertices[1]) - 1.0;
+            if sharpness > 0.0 {
+                let midpoint = edge_base + id as u32;
+                for v in edge.vertices {
+                    creases.insert(edge_key(v, midpoint), sharpness);
+                }
+            }
+        }
+        let corners = self
+            .corners
+            .iter()
+            .filter(|(_, &sharpness)| sharpness > 1.0)
+            .map(|(&v, &sharpness)| (v, sharpness - 1.0))
+            .collect();
+
+        Self {
+            positions,
+            uvs,
+            face_vertex_counts: counts,
+            face_vertex_indices: indices,
+            face_origins: origins,
+            creases,
+            corners,
+        }
+    }
+
+    /// Cage face of each triangle produced by [`Self::to_mesh`].
+    pub fn triangle_origins(&self) -> Vec<u32> {
+        self.face_vertex_counts
+            .iter()
+            .zip(&self.face_origins)
+            .flat_map(|(&count, &origin)| std::iter::repeat_n(origin, count as usize - 2))
+            .collect()
+    }
+
+    /// Fan-triangulate the faces into a mesh with smooth normals.
+    pub fn to_mesh(&self) -> Mesh {
+        let mut indices = Vec::with_capacity(self.face_vertex_indices.len() * 3 / 2);
+        let mut offset = 0usize;
+        for &count in &self.face_vertex_counts {
+            let face = &self.face_vertex_indices[offset..offset + count as usize];
+            for i in 1..face.len() - 1 {
+                indices.extend([face[0], face[i], face[i + 1]]);
+            }
+            offset += count as usize;
+        }
+
+        let mut mesh = Mesh::new_with_uvs(self.positions.clone(), indices, None, self.uvs.clone());
+        mesh.compute_normals();
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Unit cube cage centered on the origin.
+    fn cube() -> SubdivisionMesh {
+        let positions = vec![
+            Vec3::new(-0.5, -0.5, 0.5),
+            Vec3::new(0.5, -0.5, 0.5),
+            Vec3::new(-0.5, 0.5, 0.5),
+            Vec3::new(0.5, 0.5, 0.5),
+            Vec3::new(-0.5, 0.5, -0.5),
+            Vec3::new(0.5, 0.5, -0.5),
+            Vec3::new(-0.5, -0.5, -0.5),
+            Vec3::new(0.5, -0.5, -0.5),
+        ];
+        let indices = [
+            0, 1, 3, 2, 2, 3, 5, 4, 4, 5, 7, 6, 6, 7, 1, 0, 1, 7, 5, 3, 6, 0, 2, 4,
+        ];
+        SubdivisionMesh::new(positions, &[4; 6], &indices)
+    }
+
+    #[test]
+    fn test_refine_topology() {
+        let cube = cube();
+        let level1 = cube.refine();
+
+        // V + E + F vertices, one quad per face corner
+        assert_eq!(level1.positions.len(), 8 + 12 + 6);
+        assert_eq!(level1.face_count(), 24);
+        assert!(level1.face_vertex_counts.iter().all(|&c| c == 4));
+
+        let level2 = cube.refined(2);
+        assert_eq!(level2.face_count(), 96);
+        assert_eq!(level2.to_mesh().triangle_count(), 192);
+        assert_eq!(level2.triangle_origins().len(), 192);
+        assert!(level2.face_origins.iter().all(|&f| f < 6));
+    }
+
+    #[test]
+    fn test_smooth_cube_shrinks_towards_sphere() {
+        let refined = cube().refined(3);
+        let distances: Vec<f32> = refined.positions.iter().map(|p| p.length()).collect();
+        let (min, max) = distances
+            .iter()
+            .fold((f32::MAX, 0.0f32), |(lo, hi), &d| (lo.min(d), hi.max(d)));
+
+        // Corners are pulled in well inside the cage
+        assert!(max < 0.5, "max distance {max}");
+        assert!(max - min < 0.12, "spread {}", max - min);
+    }
+
+    #[test]
+    fn test_sharp_creases_keep_cube_edges() {
+        // Every edge of the cube tagged infinitely sharp
+        let chains: Vec<u32> = [
+            [0, 1],
+            [1, 3],
+            [3, 2],
+            [2, 0],
+            [4, 5],
+            [5, 7],
+            [7, 6],
+            [6, 4],
+            [0, 6],
+            [1, 7],
+            [2, 4],
+            [3, 5],
+        ]
+        .concat();
+        let tags = SubdivisionTags {
+            crease_indices: chains,
+            crease_lengths: vec![2; 12],
+            crease_sharpnesses: vec![SHARPNESS_INFINITE; 12],
+            ..Default::default()
+        };
+        let refined = cube().with_tags(&tags).refined(2);
+
+        // A fully creased cube keeps its shape
+        for p in &refined.positions {
+            let extent = p.abs().max_element();
+            assert!((extent - 0.5).abs() < 1e-5, "point {p} left the cube");
+        }
+    }
+
+    #[test]
+    fn test_open_quad_keeps_boundary_corners() {
+        let positions = vec![
+            Vec3::ZERO,
+            Vec3::X,
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::Y,
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(2.0, 1.0, 0.0),
+        ];
+        let grid = SubdivisionMesh::new(positions, &[4, 4], &[0, 1, 4, 3, 1, 2, 5, 4])
+            .with_uvs(Some(vec![[0.0, 0.0]; 6]));
+        let refined = grid.refined(2);
+
+        // edgeAndCorner: the four outer corners stay put
+        for (i, corner) in [0, 2, 3, 5].into_iter().enumerate() {
+            assert_eq!(
+                refined.positions[corner], grid.positions[corner],
+                "corner {i}"
+            );
+        }
+        assert_eq!(refined.uvs.as_ref().unwrap().len(), refined.positions.len());
+    }
+
+    #[test]
+    fn test_invalid_faces_are_dropped() {
+        let positions = vec![Vec3::ZERO, Vec3::X, Vec3::Y];
+        let cage = SubdivisionMesh::new(positions, &[2, 3, 3], &[0, 1, 0, 1, 2, 0, 1, 9]);
+        assert_eq!(cage.face_count(), 1);
+        assert_eq!(cage.face_origins, vec![1]);
+        assert_eq!(cage.refine().face_count(), 3);
+    }
+}
@@ -0,0 +1,122 @@
+//! Point clouds for particles.
+//!
+//! [`Points`] mirrors `UsdGeomPoints`: positions with a width (diameter)
+//! per point. Renderers draw each point as a sphere. Radii are stored once
+//! when every point shares the same width, so large particle caches cost
+//! little more than their positions.
+
+use bif_math::{Aabb, Vec3};
+
+/// Width used when `widths` is not authored (the USD fallback).
+pub const DEFAULT_POINT_WIDTH: f32 = 1.0;
+
+/// A point cloud (a `UsdGeomPoints`).
+#[derive(Clone, Debug)]
+pub struct Points {
+    /// Point centers
+    pub positions: Vec<Vec3>,
+
+    /// Radius of each point, or a single radius shared by all points
+    pub radii: Vec<f32>,
+
+    /// Axis-aligned bounding box, including radii
+    pub bounds: Aabb,
+}
+
+impl Points {
+    /// Create points from centers and authored widths (diameters).
+    ///
+    /// `widths` may hold one value (constant) or one per point. Empty or
+    /// mismatched widths use [`DEFAULT_POINT_WIDTH`].
+    pub fn new(positions: Vec<Vec3>, widths: &[f32]) -> Self {
+        let radii = if widths.len() == positions.len() && widths.len() > 1 {
+            widths.iter().map(|w| 0.5 * w).collect()
+        } else {
+            if widths.len() > 1 {
+                log::warn!(
+                    "{} point widths don't match {} points, using default",
+                    widths.len(),
+                    positions.len()
+                );
+            }
+            let width = match widths {
+                [width] => *width,
+                _ => DEFAULT_POINT_WIDTH,
+            };
+            vec![0.5 * width]
+        };
+        let bounds = Self::compute_bounds(&positions, &radii);
+        Self {
+            positions,
+            radii,
+            bounds,
+        }
+    }
+
+    fn compute_bounds(positions: &[Vec3], radii: &[f32]) -> Aabb {
+        if positions.is_empty() {
+            return Aabb::empty();
+        }
+
+        let mut min = Vec3::splat(f32::INFINITY);
+        let mut max = Vec3::splat(f32::NEG_INFINITY);
+        for (i, &p) in positions.iter().enumerate() {
+            let r = Vec3::splat(radii[i.min(radii.len() - 1)]);
+            min = min.min(p - r);
+            max = max.max(p + r);
+        }
+        Aabb::from_points(min, max)
+    }
+
+    /// Number of points.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// True if there are no points.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Radius of point `index`.
+    #[inline]
+    pub fn radius(&self, index: usize) -> f32 {
+        match self.radii.as_slice() {
+            [radius] => *radius,
+            radii => radii[index],
+        }
+    }
+
+    /// Bounds of point `index`.
+    pub fn point_bounds(&self, index: usize) -> Aabb {
+        let r = Vec3::splat(self.radius(index));
+        let p = self.positions[index];
+        Aabb::from_points(p - r, p + r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_width_is_stored_once() {
+        let positions = vec![Vec3::ZERO, Vec3::X, Vec3::new(0.0, 5.0, 0.0)];
+        let points = Points::new(positions, &[0.5]);
+        assert_eq!(points.radii, vec![0.25]);
+        assert_eq!(points.radius(2), 0.25);
+        assert_eq!(points.bounds.y.max, 5.25);
+        assert_eq!(points.bounds.x.min, -0.25);
+    }
+
+    #[test]
+    fn test_per_point_widths() {
+        let points = Points::new(vec![Vec3::ZERO, Vec3::X], &[1.0, 3.0]);
+        assert_eq!(points.radius(1), 1.5);
+        assert_eq!(points.bounds.x.max, 2.5);
+
+        // Mismatched widths fall back to the default
+        let points = Points::new(vec![Vec3::ZERO, Vec3::X, Vec3::Y], &[1.0, 3.0]);
+        assert_eq!(points.radius(1), 0.5 * DEFAULT_POINT_WIDTH);
+    }
+}
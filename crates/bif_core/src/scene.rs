@@ -5,9 +5,115 @@
 
 use std::sync::Arc;
 
-use bif_math::{Aabb, Mat4, Quat, Vec3};
+use bif_math::{motion_segment, Aabb, Mat4, Quat, Vec3};
 
+use crate::animation::{SceneAnimation, TimeCodes};
+use crate::camera::Camera;
+use crate::curves::Curves;
+use crate::light::Light;
 use crate::mesh::Mesh;
+use crate::points::Points;
+use crate::shading::ShadingGraph;
+
+/// Index into [`Scene::materials`], stored compactly for per-face/per-instance bindings.
+pub type MaterialId = u16;
+
+/// Sentinel [`MaterialId`] meaning "no binding here, fall through to the next level".
+pub const NO_MATERIAL: MaterialId = MaterialId::MAX;
+
+/// Ray types an object is visible to, as a bitmask.
+///
+/// Every traced ray carries exactly one of these flags, and an object is
+/// only hit by rays whose flag is in its mask. Clearing `CAMERA` hides an
+/// object from view while it still casts shadows; clearing `SPECULAR` keeps
+/// an emitter out of reflections while it still lights the scene.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Visibility(u8);
+
+impl Visibility {
+    /// Invisible to every ray
+    pub const NONE: Self = Self(0);
+    /// Primary rays from the camera
+    pub const CAMERA: Self = Self(1 << 0);
+    /// Shadow rays towards lights
+    pub const SHADOW: Self = Self(1 << 1);
+    /// Rays scattered by diffuse lobes
+    pub const DIFFUSE: Self = Self(1 << 2);
+    /// Rays reflected by glossy and mirror lobes
+    pub const SPECULAR: Self = Self(1 << 3);
+    /// Rays refracted through a surface
+    pub const TRANSMISSION: Self = Self(1 << 4);
+    /// Visible to every ray (the default)
+    pub const ALL: Self = Self(0b1_1111);
+
+    /// Ray types and their names in `primvars:bif:visibility:<name>`.
+    pub const NAMED: [(&'static str, Self); 5] = [
+        ("camera", Self::CAMERA),
+        ("shadow", Self::SHADOW),
+        ("diffuse", Self::DIFFUSE),
+        ("specular", Self::SPECULAR),
+        ("transmission", Self::TRANSMISSION),
+    ];
+
+    /// Mask from raw bits; unknown bits are dropped.
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits as u8 & Self::ALL.0)
+    }
+
+    /// Raw bits of the mask.
+    pub const fn bits(self) -> u32 {
+        self.0 as u32
+    }
+
+    /// True if every flag in `other` is set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// True if any flag in `other` is set.
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Set or clear the flags in `other`.
+    pub fn set(&mut self, other: Self, visible: bool) {
+        if visible {
+            self.0 |= other.0;
+        } else {
+            self.0 &= !other.0;
+        }
+    }
+
+    /// Ray-type flag for a name in [`Visibility::NAMED`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMED
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|&(_, flag)| flag)
+    }
+}
+
+impl Default for Visibility {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl std::ops::BitAnd for Visibility {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+}
+
+impl std::ops::BitOr for Visibility {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
 
 /// A PBR material definition based on UsdPreviewSurface.
 ///
@@ -33,6 +139,10 @@ pub struct Material {
     /// Opacity (0=transparent, 1=opaque)
     pub opacity: f32,
 
+    /// Opacity cutout threshold: above 0, opacity becomes a binary mask
+    /// (opaque where `opacity >= opacity_threshold`), as in UsdPreviewSurface
+    pub opacity_threshold: f32,
+
     /// Specular factor (for non-metallic surfaces)
     pub specular: f32,
 
@@ -50,6 +160,32 @@ pub struct Material {
 
     /// Path to emissive texture
     pub emissive_texture: Option<String>,
+
+    /// Path to opacity texture (e.g. a foliage card mask)
+    pub opacity_texture: Option<String>,
+
+    /// Texture channel read for opacity (0=r, 1=g, 2=b, 3=a)
+    pub opacity_texture_channel: usize,
+
+    /// Path to displacement texture (applied by Ivar at scene build time)
+    pub displacement_texture: Option<String>,
+
+    /// Texture channel read for scalar displacement (0=r, 1=g, 2=b, 3=a)
+    pub displacement_texture_channel: usize,
+
+    /// Multiplier applied to displacement texture values (object-space units)
+    pub displacement_scale: f32,
+
+    /// Offset added to scaled displacement texture values, like a
+    /// `UsdUVTexture` bias (e.g. -0.5 for mid-gray at rest)
+    pub displacement_bias: f32,
+
+    /// Treat the displacement texture's RGB as an object-space offset vector
+    /// instead of a height along the normal
+    pub vector_displacement: bool,
+
+    /// MaterialX shading network (constant fields above hold its fallback values)
+    pub shading_graph: Option<Arc<ShadingGraph>>,
 }
 
 impl Default for Material {
@@ -61,12 +197,21 @@ impl Default for Material {
             roughness: 0.5,
             emissive_color: Vec3::ZERO,
             opacity: 1.0,
+            opacity_threshold: 0.0,
             specular: 0.5,
             diffuse_texture: None,
             roughness_texture: None,
             metallic_texture: None,
             normal_texture: None,
             emissive_texture: None,
+            opacity_texture: None,
+            opacity_texture_channel: 0,
+            displacement_texture: None,
+            displacement_texture_channel: 0,
+            displacement_scale: 1.0,
+            displacement_bias: 0.0,
+            vector_displacement: false,
+            shading_graph: None,
         }
     }
 }
@@ -88,6 +233,19 @@ impl Material {
             || self.metallic_texture.is_some()
             || self.normal_texture.is_some()
             || self.emissive_texture.is_some()
+            || self.opacity_texture.is_some()
+            || self.displacement_texture.is_some()
+    }
+
+    /// Check if this material can be (partially) transparent.
+    pub fn has_opacity(&self) -> bool {
+        self.opacity < 1.0 || self.opacity_texture.is_some()
+    }
+
+    /// Check if this material displaces the surface it is bound to.
+    pub fn has_displacement(&self) -> bool {
+        self.displacement_texture.is_some()
+            && (self.displacement_scale != 0.0 || self.displacement_bias != 0.0)
     }
 
     /// Check if this material is emissive.
@@ -113,6 +271,20 @@ pub struct Prototype {
     /// Material (optional, defaults to grey)
     pub material: Option<Arc<Material>>,
 
+    /// Per-triangle material IDs from `GeomSubset` bindings.
+    ///
+    /// `NO_MATERIAL` entries (and `None`) fall back to the instance or
+    /// prototype material.
+    pub face_material_ids: Option<Arc<[MaterialId]>>,
+
+    /// Maximum displacement distance in object space (`None` = unbounded).
+    ///
+    /// Displaced positions are clamped to this distance from the surface.
+    pub displacement_bound: Option<f32>,
+
+    /// Ray types the prototype is visible to, for all of its instances
+    pub visibility: Visibility,
+
     /// Local bounding box (from mesh)
     pub bounds: Aabb,
 }
@@ -126,6 +298,9 @@ impl Prototype {
             name,
             mesh,
             material: None,
+            face_material_ids: None,
+            displacement_bound: None,
+            visibility: Visibility::ALL,
             bounds,
         }
     }
@@ -135,6 +310,34 @@ impl Prototype {
         self.material = Some(material);
         self
     }
+
+    /// Set per-triangle material IDs (one entry per mesh triangle).
+    pub fn with_face_materials(mut self, ids: Vec<MaterialId>) -> Self {
+        debug_assert_eq!(ids.len(), self.mesh.triangle_count());
+        self.face_material_ids = Some(ids.into());
+        self
+    }
+
+    /// Limit displacement to `bound` object-space units.
+    pub fn with_displacement_bound(mut self, bound: f32) -> Self {
+        self.displacement_bound = Some(bound.max(0.0));
+        self
+    }
+
+    /// Set the ray types this prototype is visible to.
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    /// Get the material ID bound to a triangle, if any.
+    #[inline]
+    pub fn face_material_id(&self, triangle: usize) -> Option<MaterialId> {
+        self.face_material_ids
+            .as_ref()
+            .and_then(|ids| ids.get(triangle).copied())
+            .filter(|&id| id != NO_MATERIAL)
+    }
 }
 
 /// Transform components that can be composed into a matrix.
@@ -187,6 +390,15 @@ impl Transform {
     pub fn to_matrix(&self) -> Mat4 {
         Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
     }
+
+    /// Interpolate towards `other`, slerping the rotation.
+    pub fn lerp(&self, other: &Transform, t: f32) -> Transform {
+        Transform {
+            translation: self.translation.lerp(other.translation, t),
+            rotation: self.rotation.slerp(other.rotation, t),
+            scale: self.scale.lerp(other.scale, t),
+        }
+    }
 }
 
 /// An instance of a prototype with a transform.
@@ -198,8 +410,20 @@ pub struct Instance {
     /// Index of the prototype this instance references
     pub prototype_id: usize,
 
-    /// Instance transform
+    /// Instance transform (at shutter open when the instance moves)
     pub transform: Transform,
+
+    /// Further transform keys for motion blur (empty = static).
+    ///
+    /// They follow `transform`, evenly spaced up to shutter close, and are
+    /// interpolated with slerp.
+    pub motion: Vec<Transform>,
+
+    /// Material override for this instance (None = use the prototype's)
+    pub material_id: Option<MaterialId>,
+
+    /// Ray types this instance is visible to, combined with the prototype's
+    pub visibility: Visibility,
 }
 
 impl Instance {
@@ -208,18 +432,137 @@ impl Instance {
         Self {
             prototype_id,
             transform,
+            motion: Vec::new(),
+            material_id: None,
+            visibility: Visibility::ALL,
         }
     }
 
+    /// Set a per-instance material override.
+    pub fn with_material(mut self, material_id: MaterialId) -> Self {
+        self.material_id = Some(material_id);
+        self
+    }
+
+    /// Restrict the ray types this instance is visible to.
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
     /// Create an instance with just a translation.
     pub fn with_translation(prototype_id: usize, translation: Vec3) -> Self {
         Self::new(prototype_id, Transform::from_translation(translation))
     }
 
+    /// Add transform keys after `transform` for motion blur.
+    pub fn with_motion(mut self, keys: Vec<Transform>) -> Self {
+        self.motion = keys;
+        self
+    }
+
+    /// Check if the instance has motion keys.
+    pub fn is_moving(&self) -> bool {
+        !self.motion.is_empty()
+    }
+
+    /// Interpolated transform at shutter `time` in [0, 1].
+    pub fn transform_at(&self, time: f32) -> Transform {
+        if self.motion.is_empty() {
+            return self.transform.clone();
+        }
+        let (segment, t) = motion_segment(time, self.motion.len() + 1);
+        let key = |i: usize| match i {
+            0 => &self.transform,
+            _ => &self.motion[i - 1],
+        };
+        key(segment).lerp(key(segment + 1), t)
+    }
+
     /// Get the 4x4 model matrix for this instance.
     pub fn model_matrix(&self) -> Mat4 {
         self.transform.to_matrix()
     }
+
+    /// Model matrices of every transform key, from shutter open to close.
+    pub fn model_matrices(&self) -> Vec<Mat4> {
+        std::iter::once(&self.transform)
+            .chain(&self.motion)
+            .map(Transform::to_matrix)
+            .collect()
+    }
+}
+
+/// A batch of curves placed in the scene (a `UsdGeomBasisCurves` prim).
+///
+/// Curves are not instanced: each set carries its own transform.
+#[derive(Clone, Debug)]
+pub struct CurveSet {
+    /// Curve set name (from USD prim path)
+    pub name: String,
+
+    /// Shared curve geometry
+    pub curves: Arc<Curves>,
+
+    /// Local-to-world transform
+    pub transform: Mat4,
+
+    /// Material (optional); shaded with a hair BSDF by Ivar
+    pub material: Option<Arc<Material>>,
+}
+
+impl CurveSet {
+    /// Create a curve set with no material.
+    pub fn new(name: impl Into<String>, curves: Arc<Curves>, transform: Mat4) -> Self {
+        Self {
+            name: name.into(),
+            curves,
+            transform,
+            material: None,
+        }
+    }
+
+    /// Set the material for this curve set.
+    pub fn with_material(mut self, material: Arc<Material>) -> Self {
+        self.material = Some(material);
+        self
+    }
+}
+
+/// A point cloud placed in the scene (a `UsdGeomPoints` prim).
+///
+/// Like curve sets, point sets are not instanced.
+#[derive(Clone, Debug)]
+pub struct PointSet {
+    /// Point set name (from USD prim path)
+    pub name: String,
+
+    /// Shared point geometry
+    pub points: Arc<Points>,
+
+    /// Local-to-world transform
+    pub transform: Mat4,
+
+    /// Material (optional)
+    pub material: Option<Arc<Material>>,
+}
+
+impl PointSet {
+    /// Create a point set with no material.
+    pub fn new(name: impl Into<String>, points: Arc<Points>, transform: Mat4) -> Self {
+        Self {
+            name: name.into(),
+            points,
+            transform,
+            material: None,
+        }
+    }
+
+    /// Set the material for this point set.
+    pub fn with_material(mut self, material: Arc<Material>) -> Self {
+        self.material = Some(material);
+        self
+    }
 }
 
 /// A complete scene containing prototypes, instances, and materials.
@@ -236,8 +579,26 @@ pub struct Scene {
     /// Materials used in the scene
     pub materials: Vec<Arc<Material>>,
 
+    /// Curve sets (hair, fur)
+    pub curves: Vec<CurveSet>,
+
+    /// Point sets (particles)
+    pub points: Vec<PointSet>,
+
+    /// Lights
+    pub lights: Vec<Light>,
+
+    /// Cameras
+    pub cameras: Vec<Camera>,
+
     /// Scene name (usually from filename)
     pub name: String,
+
+    /// Playback range of the stage
+    pub time_codes: TimeCodes,
+
+    /// Time-sampled transforms and points (see [`Scene::set_time`])
+    pub animation: SceneAnimation,
 }
 
 impl Scene {
@@ -262,6 +623,35 @@ impl Scene {
         self.instances.push(Instance::new(prototype_id, transform));
     }
 
+    /// Add a curve set and return its index.
+    pub fn add_curves(&mut self, curves: CurveSet) -> usize {
+        self.curves.push(curves);
+        self.curves.len() - 1
+    }
+
+    /// Add a point set and return its index.
+    pub fn add_points(&mut self, points: PointSet) -> usize {
+        self.points.push(points);
+        self.points.len() - 1
+    }
+
+    /// Add a light and return its index.
+    pub fn add_light(&mut self, light: Light) -> usize {
+        self.lights.push(light);
+        self.lights.len() - 1
+    }
+
+    /// Add a camera and return its index.
+    pub fn add_camera(&mut self, camera: Camera) -> usize {
+        self.cameras.push(camera);
+        self.cameras.len() - 1
+    }
+
+    /// Find a camera by name (its USD prim path).
+    pub fn find_camera(&self, name: &str) -> Option<&Camera> {
+        self.cameras.iter().find(|camera| camera.name == name)
+    }
+
     /// Add a material to the scene and return its ID.
     pub fn add_material(&mut self, material: Material) -> usize {
         let id = self.materials.len();
@@ -279,6 +669,141 @@ impl Scene {
         self.materials.len()
     }
 
+    /// Resolve the material for a triangle of an instance.
+    ///
+    /// Precedence follows USD binding strength: `GeomSubset` (per-face)
+    /// bindings win over the instance override, which wins over the
+    /// prototype's own material. Returns `None` if nothing is bound.
+    pub fn resolve_material(&self, instance: usize, triangle: usize) -> Option<&Arc<Material>> {
+        let instance = self.instances.get(instance)?;
+        let proto = self.prototypes.get(instance.prototype_id)?;
+
+        proto
+            .face_material_id(triangle)
+            .or(instance.material_id)
+            .and_then(|id| self.materials.get(id as usize))
+            .or(proto.material.as_ref())
+    }
+
+    /// Resolve the ray types an instance is visible to.
+    ///
+    /// An instance is visible to a ray type only if both it and its
+    /// prototype are; missing instances are invisible.
+    pub fn resolve_visibility(&self, instance: usize) -> Visibility {
+        self.instances
+            .get(instance)
+            .and_then(|instance| {
+                let proto = self.prototypes.get(instance.prototype_id)?;
+                Some(instance.visibility & proto.visibility)
+            })
+            .unwrap_or(Visibility::NONE)
+    }
+
+    /// Move the animated parts of the scene to `time` (a time code).
+    ///
+    /// Animated instances, curve sets, point sets, lights and cameras take
+    /// their transform at `time`. Deforming prototypes get a new mesh with
+    /// the positions at `time` and recomputed normals. Everything else is
+    /// left as loaded.
+    ///
+    /// Motion keys of animated instances and prototypes belong to the
+    /// previous frame and are dropped; call [`Self::sample_motion`] to
+    /// sample them again.
+    pub fn set_time(&mut self, time: f64) {
+        for (&index, curve) in &self.animation.instances {
+            if let Some(instance) = self.instances.get_mut(index) {
+                instance.transform = Transform::from_matrix(curve.evaluate(time));
+                instance.motion.clear();
+            }
+        }
+        for (&index, curve) in &self.animation.curves {
+            if let Some(set) = self.curves.get_mut(index) {
+                set.transform = curve.evaluate(time);
+            }
+        }
+        for (&index, curve) in &self.animation.points {
+            if let Some(set) = self.points.get_mut(index) {
+                set.transform = curve.evaluate(time);
+            }
+        }
+
+        for (&index, curve) in &self.animation.lights {
+            if let Some(light) = self.lights.get_mut(index) {
+                light.transform = curve.evaluate(time);
+            }
+        }
+        for (&index, curve) in &self.animation.cameras {
+            if let Some(camera) = self.cameras.get_mut(index) {
+                camera.transform = curve.evaluate(time);
+            }
+        }
+
+        for (&id, curve) in &self.animation.prototypes {
+            let Some(proto) = self.prototypes.get(id) else {
+                continue;
+            };
+            let positions = curve.evaluate(time);
+            if positions.len() != proto.mesh.positions.len() {
+                log::warn!(
+                    "Prototype {} has {} points at time {}, expected {}",
+                    proto.name,
+                    positions.len(),
+                    time,
+                    proto.mesh.positions.len()
+                );
+                continue;
+            }
+
+            let mut mesh = Mesh::new_with_uvs(
+                positions,
+                proto.mesh.indices.clone(),
+                None,
+                proto.mesh.uvs.clone(),
+            );
+            mesh.ensure_normals();
+            let mut proto = (**proto).clone();
+            proto.bounds = mesh.bounds;
+            proto.mesh = Arc::new(mesh);
+            self.prototypes[id] = Arc::new(proto);
+        }
+    }
+
+    /// Give animated instances and deforming prototypes motion keys over
+    /// the frame starting at `time`, for motion blur.
+    ///
+    /// The scene must be at `time` (loaded at it, or after
+    /// [`Self::set_time`]). Each moving instance and prototype gets a
+    /// second key one time code later, so a shutter of 0 to 1 spans the
+    /// whole frame; ones that don't move over the frame stay static.
+    pub fn sample_motion(&mut self, time: f64) {
+        let close = time + 1.0;
+        for (&index, curve) in &self.animation.instances {
+            if let Some(instance) = self.instances.get_mut(index) {
+                let matrix = curve.evaluate(close);
+                instance.motion = if matrix == curve.evaluate(time) {
+                    Vec::new()
+                } else {
+                    vec![Transform::from_matrix(matrix)]
+                };
+            }
+        }
+
+        for (&id, curve) in &self.animation.prototypes {
+            let Some(proto) = self.prototypes.get(id) else {
+                continue;
+            };
+            let positions = curve.evaluate(close);
+            if positions == proto.mesh.positions {
+                continue;
+            }
+            let mesh = (*proto.mesh).clone().with_motion(vec![positions]);
+            let mut proto = (**proto).clone();
+            proto.bounds = mesh.bounds;
+            proto.mesh = Arc::new(mesh);
+            self.prototypes[id] = Arc::new(proto);
+        }
+    }
+
     /// Get total triangle count across all instances.
     pub fn total_triangle_count(&self) -> usize {
         let mut count = 0;
@@ -300,33 +825,65 @@ impl Scene {
         self.prototypes.len()
     }
 
-    /// Compute the world-space bounding box of all instances.
+    /// Get total curve count across all curve sets.
+    pub fn total_curve_count(&self) -> usize {
+        self.curves.iter().map(|set| set.curves.curve_count()).sum()
+    }
+
+    /// Get total point count across all point sets.
+    pub fn total_point_count(&self) -> usize {
+        self.points.iter().map(|set| set.points.len()).sum()
+    }
+
+    /// Compute the world-space bounding box of all instances, curves and
+    /// points.
+    ///
+    /// Moving instances contribute their bounds at every motion key.
     pub fn world_bounds(&self) -> Aabb {
         let mut min = Vec3::splat(f32::INFINITY);
         let mut max = Vec3::splat(f32::NEG_INFINITY);
 
-        for instance in &self.instances {
-            if let Some(proto) = self.prototypes.get(instance.prototype_id) {
-                let matrix = instance.model_matrix();
-
-                // Transform all 8 corners of the prototype bounds
-                let b = &proto.bounds;
-                let corners = [
-                    Vec3::new(b.x.min, b.y.min, b.z.min),
-                    Vec3::new(b.x.max, b.y.min, b.z.min),
-                    Vec3::new(b.x.min, b.y.max, b.z.min),
-                    Vec3::new(b.x.max, b.y.max, b.z.min),
-                    Vec3::new(b.x.min, b.y.min, b.z.max),
-                    Vec3::new(b.x.max, b.y.min, b.z.max),
-                    Vec3::new(b.x.min, b.y.max, b.z.max),
-                    Vec3::new(b.x.max, b.y.max, b.z.max),
-                ];
-
-                for corner in corners {
-                    let world_pos = matrix.transform_point3(corner);
-                    min = min.min(world_pos);
-                    max = max.max(world_pos);
-                }
+        let placed = self
+            .instances
+            .iter()
+            .filter_map(|instance| {
+                let proto = self.prototypes.get(instance.prototype_id)?;
+                Some((instance, proto.bounds))
+            })
+            .flat_map(|(instance, bounds)| {
+                instance
+                    .model_matrices()
+                    .into_iter()
+                    .map(move |matrix| (matrix, bounds))
+            })
+            .chain(
+                self.curves
+                    .iter()
+                    .map(|set| (set.transform, set.curves.bounds)),
+            )
+            .chain(
+                self.points
+                    .iter()
+                    .map(|set| (set.transform, set.points.bounds)),
+            );
+
+        for (matrix, b) in placed {
+            // Transform all 8 corners of the local bounds
+            let corners = [
+                Vec3::new(b.x.min, b.y.min, b.z.min),
+                Vec3::new(b.x.max, b.y.min, b.z.min),
+                Vec3::new(b.x.min, b.y.max, b.z.min),
+                Vec3::new(b.x.max, b.y.max, b.z.min),
+                Vec3::new(b.x.min, b.y.min, b.z.max),
+                Vec3::new(b.x.max, b.y.min, b.z.max),
+                Vec3::new(b.x.min, b.y.max, b.z.max),
+                Vec3::new(b.x.max, b.y.max, b.z.max),
+            ];
+
+            for corner in corners {
+                let world_pos = matrix.transform_point3(corner);
+                min = min.min(world_pos);
+                max = max.max(world_pos);
             }
         }
 
@@ -366,6 +923,97 @@ mod tests {
         assert_eq!(scene.total_triangle_count(), 2);
     }
 
+    #[test]
+    fn test_resolve_material_precedence() {
+        let mut scene = Scene::new("test");
+        let mesh = Arc::new(Mesh::new(
+            vec![Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::ONE],
+            vec![0, 1, 2, 1, 3, 2],
+            None,
+        ));
+        let red = scene.add_material(Material::new("red", Vec3::X)) as MaterialId;
+        let green = scene.add_material(Material::new("green", Vec3::Y)) as MaterialId;
+        let blue = scene.add_material(Material::new("blue", Vec3::Z));
+
+        let proto = Prototype::new(0, "quad".to_string(), mesh)
+            .with_material(scene.materials[blue].clone())
+            .with_face_materials(vec![red, NO_MATERIAL]);
+        scene.prototypes.push(Arc::new(proto));
+        scene.add_instance(0, Transform::default());
+        scene
+            .instances
+            .push(Instance::new(0, Transform::default()).with_material(green));
+
+        let name = |i, t| scene.resolve_material(i, t).map(|m| m.name.as_str());
+        // Face binding beats everything
+        assert_eq!(name(0, 0), Some("red"));
+        assert_eq!(name(1, 0), Some("red"));
+        // Unbound face: instance override, then prototype material
+        assert_eq!(name(1, 1), Some("green"));
+        assert_eq!(name(0, 1), Some("blue"));
+        assert_eq!(name(2, 0), None);
+    }
+
+    #[test]
+    fn test_resolve_visibility() {
+        let mut scene = Scene::new("test");
+        let mesh = Arc::new(Mesh::new(
+            vec![Vec3::ZERO, Vec3::X, Vec3::Y],
+            vec![0, 1, 2],
+            None,
+        ));
+        let mut no_camera = Visibility::ALL;
+        no_camera.set(Visibility::CAMERA, false);
+        let proto = Prototype::new(0, "tri".to_string(), mesh).with_visibility(no_camera);
+        scene.prototypes.push(Arc::new(proto));
+        scene.add_instance(0, Transform::default());
+        scene.instances.push(
+            Instance::new(0, Transform::default())
+                .with_visibility(Visibility::CAMERA | Visibility::SHADOW),
+        );
+
+        assert_eq!(scene.resolve_visibility(0), no_camera);
+        assert!(!scene.resolve_visibility(0).intersects(Visibility::CAMERA));
+        // Instance and prototype masks combine
+        assert_eq!(scene.resolve_visibility(1), Visibility::SHADOW);
+        assert_eq!(scene.resolve_visibility(2), Visibility::NONE);
+        assert_eq!(
+            Visibility::from_name("specular"),
+            Some(Visibility::SPECULAR)
+        );
+        assert_eq!(Visibility::from_bits(0xff), Visibility::ALL);
+    }
+
+    #[test]
+    fn test_instance_motion() {
+        let mut scene = Scene::new("test");
+        let mesh = Arc::new(Mesh::new(
+            vec![Vec3::ZERO, Vec3::X, Vec3::Y],
+            vec![0, 1, 2],
+            None,
+        ));
+        let proto = scene.add_prototype(mesh, "tri".to_string());
+        let spin = Transform {
+            rotation: Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+            ..Transform::from_translation(Vec3::new(4.0, 0.0, 0.0))
+        };
+        let instance = Instance::new(proto, Transform::default()).with_motion(vec![spin]);
+        assert!(instance.is_moving());
+        scene.instances.push(instance.clone());
+
+        // Halfway: half the translation and half the turn
+        let mid = instance.transform_at(0.5);
+        assert!((mid.translation - Vec3::new(2.0, 0.0, 0.0)).length() < 1e-5);
+        let x = mid.rotation * Vec3::X;
+        assert!((x - Vec3::new(1.0, 1.0, 0.0).normalize()).length() < 1e-5);
+        assert_eq!(instance.model_matrices().len(), 2);
+
+        // Bounds cover both keys
+        let bounds = scene.world_bounds();
+        assert!(bounds.x.min.abs() < 1e-3 && (bounds.x.max - 4.0).abs() < 1e-3);
+        assert!((bounds.y.max - 1.0).abs() < 1e-3);
+    }
+
     #[test]
     fn test_transform_matrix_roundtrip() {
         let transform = Transform {
@@ -380,4 +1028,49 @@ mod tests {
         assert!((recovered.translation - transform.translation).length() < 0.001);
         assert!((recovered.scale - transform.scale).length() < 0.001);
     }
+
+    #[test]
+    fn test_set_time_resamples_motion() {
+        use crate::animation::AnimationCurve;
+
+        let mut scene = Scene::new("test");
+        let mesh = Arc::new(Mesh::new(
+            vec![Vec3::ZERO, Vec3::X, Vec3::Y],
+            vec![0, 1, 2],
+            None,
+        ));
+        let proto = scene.add_prototype(mesh, "tri".to_string());
+        scene.add_instance(proto, Transform::default());
+        let slide = |x: f32| Mat4::from_translation(Vec3::new(x, 0.0, 0.0));
+        let stretch = |x: f32| vec![Vec3::ZERO, Vec3::X * x, Vec3::Y];
+        scene.animation.instances.insert(
+            0,
+            AnimationCurve::new(vec![(0.0, slide(0.0)), (10.0, slide(10.0))]).unwrap(),
+        );
+        scene.animation.prototypes.insert(
+            proto,
+            AnimationCurve::new(vec![(0.0, stretch(1.0)), (10.0, stretch(11.0))]).unwrap(),
+        );
+
+        scene.set_time(2.0);
+        scene.sample_motion(2.0);
+        assert!((scene.instances[0].motion[0].translation.x - 3.0).abs() < 1e-5);
+        assert!((scene.prototypes[proto].mesh.motion[0][1].x - 4.0).abs() < 1e-5);
+
+        // Keys from the previous frame don't survive a frame change
+        scene.set_time(5.0);
+        assert!(!scene.instances[0].is_moving());
+        assert!(scene.prototypes[proto].mesh.motion.is_empty());
+
+        scene.sample_motion(5.0);
+        assert!((scene.instances[0].transform.translation.x - 5.0).abs() < 1e-5);
+        assert!((scene.instances[0].motion[0].translation.x - 6.0).abs() < 1e-5);
+        assert!((scene.prototypes[proto].mesh.motion[0][1].x - 7.0).abs() < 1e-5);
+
+        // Past the last key nothing moves
+        scene.set_time(10.0);
+        scene.sample_motion(10.0);
+        assert!(!scene.instances[0].is_moving());
+        assert!(scene.prototypes[proto].mesh.motion.is_empty());
+    }
 }
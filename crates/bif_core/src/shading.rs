@@ -0,0 +1,344 @@
+//! Shading node graphs (MaterialX-style).
+//!
+//! A renderer-agnostic description of a shader network: a flat list of
+//! nodes whose inputs are either constant values or connections to the
+//! output of another node. The C++ bridge fills this in for MaterialX
+//! materials; renderers decide which node categories they can evaluate.
+
+use bif_math::Vec3;
+
+/// A constant value bound to a shader input.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShadingValue {
+    /// Scalar input (`float`)
+    Float(f32),
+    /// Two-component input (`vector2`, typically a texture coordinate)
+    Vector2([f32; 2]),
+    /// Three-component input (`color3`, `vector3`)
+    Vector3(Vec3),
+    /// Four-component input (`color4`, `float4`, e.g. a texture's scale)
+    Vector4([f32; 4]),
+    /// String or asset path input (`filename`, `string`)
+    String(String),
+}
+
+impl ShadingValue {
+    /// Interpret the value as a scalar (vectors are averaged).
+    pub fn as_float(&self) -> Option<f32> {
+        match self {
+            Self::Float(f) => Some(*f),
+            Self::Vector2(v) => Some((v[0] + v[1]) * 0.5),
+            Self::Vector3(v) => Some((v.x + v.y + v.z) / 3.0),
+            Self::Vector4(v) => Some(v.iter().sum::<f32>() * 0.25),
+            Self::String(_) => None,
+        }
+    }
+
+    /// Interpret the value as a three-component vector (scalars are splatted).
+    pub fn as_vec3(&self) -> Option<Vec3> {
+        match self {
+            Self::Float(f) => Some(Vec3::splat(*f)),
+            Self::Vector2(v) => Some(Vec3::new(v[0], v[1], 0.0)),
+            Self::Vector3(v) => Some(*v),
+            Self::Vector4(v) => Some(Vec3::new(v[0], v[1], v[2])),
+            Self::String(_) => None,
+        }
+    }
+
+    /// Get one component, e.g. the one matching a texture channel
+    /// (scalars have it in every component).
+    pub fn component(&self, index: usize) -> Option<f32> {
+        match self {
+            Self::Float(f) => Some(*f),
+            Self::Vector2(v) => v.get(index).copied(),
+            Self::Vector3(v) => v.to_array().get(index).copied(),
+            Self::Vector4(v) => v.get(index).copied(),
+            Self::String(_) => None,
+        }
+    }
+
+    /// Get the string payload, if any.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// A shader input: either a constant or a connection to another node.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShadingInput {
+    /// Constant authored value
+    Value(ShadingValue),
+    /// Connection to `graph.nodes[node]`, reading the named output
+    Connection { node: usize, output: String },
+}
+
+/// A single node in a shading graph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShadingNode {
+    /// Shader prim path (e.g., "/Looks/Mat/image1")
+    pub name: String,
+
+    /// Shader identifier (e.g., "ND_image_color3", "ND_standard_surface_surfaceshader")
+    pub shader_id: String,
+
+    /// Named inputs, in authored order
+    pub inputs: Vec<(String, ShadingInput)>,
+}
+
+impl ShadingNode {
+    /// Create a node with no inputs.
+    pub fn new(name: impl Into<String>, shader_id: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            shader_id: shader_id.into(),
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Builder method to add a constant input.
+    pub fn with_value(mut self, name: impl Into<String>, value: ShadingValue) -> Self {
+        self.inputs.push((name.into(), ShadingInput::Value(value)));
+        self
+    }
+
+    /// Builder method to connect an input to another node's output.
+    pub fn with_connection(
+        mut self,
+        name: impl Into<String>,
+        node: usize,
+        output: impl Into<String>,
+    ) -> Self {
+        self.inputs.push((
+            name.into(),
+            ShadingInput::Connection {
+                node,
+                output: output.into(),
+            },
+        ));
+        self
+    }
+
+    /// Look up an input by name.
+    pub fn input(&self, name: &str) -> Option<&ShadingInput> {
+        self.inputs.iter().find(|(n, _)| n == name).map(|(_, i)| i)
+    }
+
+    /// Node category with the `ND_` prefix and type suffix stripped.
+    ///
+    /// `ND_image_color3` → `image`, `ND_standard_surface_surfaceshader` →
+    /// `standard_surface`, `ND_multiply_color3FA` → `multiply`.
+    pub fn category(&self) -> &str {
+        let id = self
+            .shader_id
+            .strip_prefix("ND_")
+            .unwrap_or(&self.shader_id);
+        const SUFFIXES: [&str; 10] = [
+            "_surfaceshader",
+            "_color3",
+            "_color4",
+            "_vector2",
+            "_vector3",
+            "_vector4",
+            "_float",
+            "_integer",
+            "_boolean",
+            "_filename",
+        ];
+        for suffix in SUFFIXES {
+            if let Some(pos) = id.find(suffix) {
+                return &id[..pos];
+            }
+        }
+        id
+    }
+}
+
+/// A shading network with a designated surface shader node.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShadingGraph {
+    /// All nodes reachable from the surface shader
+    pub nodes: Vec<ShadingNode>,
+
+    /// Index of the surface shader node (e.g., standard_surface)
+    pub surface: usize,
+}
+
+impl ShadingGraph {
+    /// Create an empty graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a node and return its index.
+    pub fn add_node(&mut self, node: ShadingNode) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// Get the surface shader node, if the graph has one.
+    pub fn surface_node(&self) -> Option<&ShadingNode> {
+        self.nodes.get(self.surface)
+    }
+
+    /// Collect every `file` path referenced by image nodes.
+    pub fn texture_paths(&self) -> Vec<&str> {
+        self.nodes
+            .iter()
+            .filter(|n| n.category().contains("image"))
+            .filter_map(|n| match n.input("file") {
+                Some(ShadingInput::Value(v)) => v.as_str(),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Check that every connection points at an existing node and that the
+    /// surface shader's network has no cycle.
+    pub fn is_valid(&self) -> bool {
+        self.surface < self.nodes.len()
+            && self.nodes.iter().all(|n| {
+                n.inputs.iter().all(|(_, i)| match i {
+                    ShadingInput::Connection { node, .. } => *node < self.nodes.len(),
+                    ShadingInput::Value(_) => true,
+                })
+            })
+            && self.evaluation_order().is_some()
+    }
+
+    /// Nodes the surface shader depends on, each after the nodes feeding
+    /// it, with the surface shader last.
+    ///
+    /// Returns `None` if a connection is dangling or forms a cycle.
+    pub fn evaluation_order(&self) -> Option<Vec<usize>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Visit {
+            New,
+            Active,
+            Done,
+        }
+
+        let mut visits = vec![Visit::New; self.nodes.len()];
+        *visits.get_mut(self.surface)? = Visit::Active;
+        let mut order = Vec::new();
+
+        // Depth-first: (node, index of its next input to follow)
+        let mut stack = vec![(self.surface, 0)];
+        while let Some(top) = stack.last_mut() {
+            let (node, input) = *top;
+            top.1 += 1;
+            match self.nodes[node].inputs.get(input) {
+                None => {
+                    visits[node] = Visit::Done;
+                    order.push(node);
+                    stack.pop();
+                }
+                Some((_, ShadingInput::Connection { node: upstream, .. })) => {
+                    match *visits.get(*upstream)? {
+                        Visit::New => {
+                            visits[*upstream] = Visit::Active;
+                            stack.push((*upstream, 0));
+                        }
+                        Visit::Active => return None,
+                        Visit::Done => {}
+                    }
+                }
+                Some((_, ShadingInput::Value(_))) => {}
+            }
+        }
+
+        Some(order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_category_strips_prefix_and_type() {
+        assert_eq!(ShadingNode::new("a", "ND_image_color3").category(), "image");
+        assert_eq!(
+            ShadingNode::new("b", "ND_standard_surface_surfaceshader").category(),
+            "standard_surface"
+        );
+        assert_eq!(
+            ShadingNode::new("c", "ND_multiply_color3FA").category(),
+            "multiply"
+        );
+        assert_eq!(
+            ShadingNode::new("d", "ND_ramplr_float").category(),
+            "ramplr"
+        );
+        assert_eq!(ShadingNode::new("e", "normalmap").category(), "normalmap");
+    }
+
+    #[test]
+    fn test_value_conversions() {
+        assert_eq!(ShadingValue::Float(0.5).as_vec3(), Some(Vec3::splat(0.5)));
+        assert_eq!(
+            ShadingValue::Vector3(Vec3::new(0.0, 0.3, 0.6)).as_float(),
+            Some(0.3)
+        );
+        assert_eq!(ShadingValue::String("a.png".into()).as_float(), None);
+
+        let scale = ShadingValue::Vector4([1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(scale.as_vec3(), Some(Vec3::new(1.0, 2.0, 3.0)));
+        assert_eq!(scale.component(3), Some(4.0));
+        assert_eq!(ShadingValue::Float(0.5).component(2), Some(0.5));
+    }
+
+    #[test]
+    fn test_graph_texture_paths_and_validity() {
+        let mut graph = ShadingGraph::new();
+        let img = graph.add_node(
+            ShadingNode::new("img", "ND_image_color3")
+                .with_value("file", ShadingValue::String("albedo.png".into())),
+        );
+        graph.surface = graph.add_node(
+            ShadingNode::new("surf", "ND_standard_surface_surfaceshader").with_connection(
+                "base_color",
+                img,
+                "out",
+            ),
+        );
+
+        assert!(graph.is_valid());
+        assert_eq!(graph.texture_paths(), vec!["albedo.png"]);
+        assert_eq!(graph.surface_node().unwrap().name, "surf");
+
+        graph.nodes[1] = graph.nodes[1].clone().with_connection("normal", 7, "out");
+        assert!(!graph.is_valid());
+    }
+
+    #[test]
+    fn test_evaluation_order_and_cycles() {
+        // A texture feeding two inputs of a multiply is ordered once
+        let mut graph = ShadingGraph::new();
+        let img = graph.add_node(ShadingNode::new("img", "ND_image_color3"));
+        let tint = graph.add_node(
+            ShadingNode::new("tint", "ND_multiply_color3")
+                .with_connection("in1", img, "out")
+                .with_connection("in2", img, "out"),
+        );
+        graph.add_node(ShadingNode::new("unused", "ND_constant_color3"));
+        graph.surface = graph.add_node(
+            ShadingNode::new("surf", "ND_standard_surface_surfaceshader")
+                .with_connection("base_color", tint, "out")
+                .with_connection("specular_color", img, "out"),
+        );
+        assert_eq!(
+            graph.evaluation_order(),
+            Some(vec![img, tint, graph.surface])
+        );
+
+        // img <- tint <- img
+        graph.nodes[img] = graph.nodes[img]
+            .clone()
+            .with_connection("texcoord", tint, "out");
+        assert_eq!(graph.evaluation_order(), None);
+        assert!(!graph.is_valid());
+    }
+}
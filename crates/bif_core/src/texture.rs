@@ -137,11 +137,16 @@ impl Texture {
 
 /// Cache for loaded textures.
 ///
-/// Textures are loaded on-demand and cached for reuse.
+/// Textures are loaded on-demand and cached for reuse. Color textures are
+/// sRGB decoded; non-color data (masks, weights, roughness, displacement)
+/// is loaded with [`TextureCache::load_linear`] and cached separately.
 pub struct TextureCache {
-    /// Cached textures by file path
+    /// Cached color textures by file path
     textures: HashMap<String, Arc<Texture>>,
 
+    /// Cached non-color textures by file path
+    linear_textures: HashMap<String, Arc<Texture>>,
+
     /// Base directory for resolving relative paths
     base_dir: Option<PathBuf>,
 }
@@ -151,6 +156,7 @@ impl TextureCache {
     pub fn new() -> Self {
         Self {
             textures: HashMap::new(),
+            linear_textures: HashMap::new(),
             base_dir: None,
         }
     }
@@ -159,6 +165,7 @@ impl TextureCache {
     pub fn with_base_dir(base_dir: impl Into<PathBuf>) -> Self {
         Self {
             textures: HashMap::new(),
+            linear_textures: HashMap::new(),
             base_dir: Some(base_dir.into()),
         }
     }
@@ -168,10 +175,21 @@ impl TextureCache {
         self.base_dir = Some(base_dir.into());
     }
 
-    /// Load a texture from file, using cache if available.
+    /// Load a color texture from file, using cache if available.
     pub fn load(&mut self, path: &str) -> TextureResult<Arc<Texture>> {
+        self.load_encoded(path, true)
+    }
+
+    /// Load a non-color texture (mask, weight, roughness, displacement)
+    /// from file without sRGB decoding, using cache if available.
+    pub fn load_linear(&mut self, path: &str) -> TextureResult<Arc<Texture>> {
+        self.load_encoded(path, false)
+    }
+
+    /// Load a texture, sRGB decoding its color channels if `srgb` is set.
+    fn load_encoded(&mut self, path: &str, srgb: bool) -> TextureResult<Arc<Texture>> {
         // Check cache first
-        if let Some(texture) = self.textures.get(path) {
+        if let Some(texture) = self.cache(srgb).get(path) {
             return Ok(texture.clone());
         }
 
@@ -179,11 +197,11 @@ impl TextureCache {
         let full_path = self.resolve_path(path);
 
         // Load the texture
-        let texture = load_texture_file(&full_path)?;
+        let texture = load_texture_file(&full_path, srgb)?;
         let texture = Arc::new(texture);
 
         // Cache it
-        self.textures.insert(path.to_string(), texture.clone());
+        self.cache(srgb).insert(path.to_string(), texture.clone());
 
         log::debug!(
             "Loaded texture: {} ({}x{}, {:.1} KB)",
@@ -196,6 +214,11 @@ impl TextureCache {
         Ok(texture)
     }
 
+    /// Add an already-loaded texture under `path` (replaces any cached entry).
+    pub fn insert(&mut self, path: impl Into<String>, texture: Arc<Texture>) {
+        self.textures.insert(path.into(), texture);
+    }
+
     /// Get a cached texture without loading.
     pub fn get(&self, path: &str) -> Option<Arc<Texture>> {
         self.textures.get(path).cloned()
@@ -208,22 +231,36 @@ impl TextureCache {
 
     /// Get the number of cached textures.
     pub fn len(&self) -> usize {
-        self.textures.len()
+        self.textures.len() + self.linear_textures.len()
     }
 
     /// Check if cache is empty.
     pub fn is_empty(&self) -> bool {
-        self.textures.is_empty()
+        self.textures.is_empty() && self.linear_textures.is_empty()
     }
 
     /// Clear all cached textures.
     pub fn clear(&mut self) {
         self.textures.clear();
+        self.linear_textures.clear();
     }
 
     /// Get total memory usage of cached textures.
     pub fn total_size_bytes(&self) -> usize {
-        self.textures.values().map(|t| t.size_bytes()).sum()
+        self.textures
+            .values()
+            .chain(self.linear_textures.values())
+            .map(|t| t.size_bytes())
+            .sum()
+    }
+
+    /// Cache of color (`srgb`) or non-color textures.
+    fn cache(&mut self, srgb: bool) -> &mut HashMap<String, Arc<Texture>> {
+        if srgb {
+            &mut self.textures
+        } else {
+            &mut self.linear_textures
+        }
     }
 
     /// Resolve a path relative to the base directory.
@@ -246,8 +283,8 @@ impl Default for TextureCache {
     }
 }
 
-/// Load a texture from a file path.
-fn load_texture_file(path: &Path) -> TextureResult<Texture> {
+/// Load a texture from a file path, sRGB decoding RGB if `srgb` is set.
+fn load_texture_file(path: &Path, srgb: bool) -> TextureResult<Texture> {
     // Load image using the image crate
     let img = image::open(path).map_err(|e| {
         TextureError::LoadError(format!("Failed to open {}: {}", path.display(), e))
@@ -258,13 +295,18 @@ fn load_texture_file(path: &Path) -> TextureResult<Texture> {
     let (width, height) = rgba.dimensions();
 
     // Convert to linear float RGBA
+    let decode = if srgb {
+        srgb_to_linear
+    } else {
+        |value: u8| value as f32 / 255.0
+    };
     let pixels: Vec<[f32; 4]> = rgba
         .pixels()
         .map(|p| {
             [
-                srgb_to_linear(p[0]),
-                srgb_to_linear(p[1]),
-                srgb_to_linear(p[2]),
+                decode(p[0]),
+                decode(p[1]),
+                decode(p[2]),
                 p[3] as f32 / 255.0, // Alpha is linear
             ]
         })
@@ -311,6 +353,22 @@ mod tests {
         assert_eq!(cache.len(), 0);
     }
 
+    #[test]
+    fn test_load_linear_skips_srgb_decode() {
+        let path = std::env::temp_dir().join("bif_texture_linear.png");
+        image::GrayImage::from_pixel(1, 1, image::Luma([102]))
+            .save(&path)
+            .unwrap();
+        let path = path.to_string_lossy();
+
+        let mut cache = TextureCache::new();
+        let color = cache.load(&path).unwrap();
+        let linear = cache.load_linear(&path).unwrap();
+        assert!((color.sample_channel(0.5, 0.5, 0) - srgb_to_linear(102)).abs() < 1e-6);
+        assert!((linear.sample_channel(0.5, 0.5, 0) - 0.4).abs() < 1e-6);
+        assert_eq!(cache.len(), 2);
+    }
+
     #[test]
     fn test_srgb_to_linear() {
         // Black stays black
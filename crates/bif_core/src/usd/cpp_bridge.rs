@@ -21,6 +21,16 @@ use std::ptr;
 use bif_math::{Mat4, Vec3};
 use thiserror::Error;
 
+use crate::animation::TimeCodes;
+use crate::camera::Camera;
+use crate::curves::{CurveBasis, CurveShape, Curves};
+use crate::light::{DomeTextureFormat, Light, LightKind, LightShaping};
+use crate::mesh::{SubdivisionScheme, SubdivisionTags};
+use crate::points::Points;
+use crate::scene::Visibility;
+use crate::shading::{ShadingGraph, ShadingInput, ShadingNode, ShadingValue};
+use crate::usd::types::UsdVisibility;
+
 // ============================================================================
 // FFI Declarations
 // ============================================================================
@@ -58,6 +68,27 @@ struct UsdBridgeMeshDataRaw {
     uvs: *const f32,
     uv_count: usize,
     transform: [f32; 16],
+    triangle_subsets: *const i32,
+    triangle_subset_count: usize,
+    subset_count: usize,
+    subdivision_scheme: i32,
+    face_vertex_counts: *const u32,
+    face_count: usize,
+    face_vertex_indices: *const u32,
+    face_vertex_index_count: usize,
+    crease_indices: *const u32,
+    crease_index_count: usize,
+    crease_lengths: *const u32,
+    crease_length_count: usize,
+    crease_sharpnesses: *const f32,
+    crease_sharpness_count: usize,
+    corner_indices: *const u32,
+    corner_index_count: usize,
+    corner_sharpnesses: *const f32,
+    corner_sharpness_count: usize,
+    displacement_bound: f32,
+    visible: i32,
+    ray_visibility: u32,
 }
 
 /// Instancer data from C API
@@ -69,6 +100,80 @@ struct UsdBridgeInstancerDataRaw {
     transforms: *const f32,
     instance_count: usize,
     proto_indices: *const i32,
+    visible: i32,
+    ray_visibility: u32,
+}
+
+/// Basis curves data from C API
+#[repr(C)]
+struct UsdBridgeCurvesDataRaw {
+    path: *const std::ffi::c_char,
+    points: *const f32,
+    point_count: usize,
+    curve_vertex_counts: *const u32,
+    curve_count: usize,
+    widths: *const f32,
+    width_count: usize,
+    curve_type: i32,
+    basis: i32,
+    has_normals: i32,
+    transform: [f32; 16],
+    material_path: *const std::ffi::c_char,
+}
+
+/// Points data from C API
+#[repr(C)]
+struct UsdBridgePointsDataRaw {
+    path: *const std::ffi::c_char,
+    points: *const f32,
+    point_count: usize,
+    widths: *const f32,
+    width_count: usize,
+    transform: [f32; 16],
+    material_path: *const std::ffi::c_char,
+}
+
+/// Light data from C API
+#[repr(C)]
+struct UsdBridgeLightDataRaw {
+    path: *const std::ffi::c_char,
+    light_type: i32,
+    transform: [f32; 16],
+    intensity: f32,
+    exposure: f32,
+    color: [f32; 3],
+    enable_color_temperature: i32,
+    color_temperature: f32,
+    normalize: i32,
+    radius: f32,
+    width: f32,
+    height: f32,
+    length: f32,
+    angle: f32,
+    treat_as_point: i32,
+    texture_format: i32,
+    texture_file: *const std::ffi::c_char,
+    has_shaping: i32,
+    cone_angle: f32,
+    cone_softness: f32,
+    focus: f32,
+    focus_tint: [f32; 3],
+    ies_file: *const std::ffi::c_char,
+    ies_angle_scale: f32,
+    ies_normalize: i32,
+}
+
+/// Camera data from C API
+#[repr(C)]
+struct UsdBridgeCameraDataRaw {
+    path: *const std::ffi::c_char,
+    transform: [f32; 16],
+    focal_length: f32,
+    horizontal_aperture: f32,
+    vertical_aperture: f32,
+    clipping_range: [f32; 2],
+    f_stop: f32,
+    focus_distance: f32,
 }
 
 /// Prim info from C API (for scene browser)
@@ -90,15 +195,49 @@ struct UsdBridgeMaterialDataRaw {
     roughness: f32,
     specular: f32,
     opacity: f32,
+    opacity_threshold: f32,
     emissive_color: [f32; 3],
     diffuse_texture: *const std::ffi::c_char,
     roughness_texture: *const std::ffi::c_char,
     metallic_texture: *const std::ffi::c_char,
     normal_texture: *const std::ffi::c_char,
     emissive_texture: *const std::ffi::c_char,
+    opacity_texture: *const std::ffi::c_char,
+    opacity_texture_channel: i32,
     is_materialx: i32,
+    displacement_texture: *const std::ffi::c_char,
+    displacement_texture_channel: i32,
+    displacement_scale: f32,
+    displacement_bias: f32,
+    vector_displacement: i32,
+}
+
+/// Shader input from C API (constant value or upstream connection)
+#[repr(C)]
+struct UsdBridgeShaderInputRaw {
+    name: *const std::ffi::c_char,
+    kind: i32,
+    value: [f32; 3],
+    string_value: *const std::ffi::c_char,
+    connected_node: i32,
+    connected_output: *const std::ffi::c_char,
+}
+
+/// Shader node from C API
+#[repr(C)]
+struct UsdBridgeShaderNodeRaw {
+    path: *const std::ffi::c_char,
+    shader_id: *const std::ffi::c_char,
+    inputs: *const UsdBridgeShaderInputRaw,
+    input_count: usize,
 }
 
+// Values of UsdBridgeShaderValueKind
+const SHADER_VALUE_FLOAT: i32 = 1;
+const SHADER_VALUE_VECTOR2: i32 = 2;
+const SHADER_VALUE_VECTOR3: i32 = 3;
+const SHADER_VALUE_STRING: i32 = 4;
+
 #[link(name = "usd_bridge")]
 extern "C" {
     fn usd_bridge_error_message(error: UsdBridgeErrorCode) -> *const std::ffi::c_char;
@@ -110,6 +249,15 @@ extern "C" {
 
     fn usd_bridge_close_stage(stage: *mut UsdBridgeStageRaw);
 
+    fn usd_bridge_set_time(stage: *mut UsdBridgeStageRaw, time_code: f64) -> UsdBridgeErrorCode;
+
+    fn usd_bridge_get_time_codes(
+        stage: *const UsdBridgeStageRaw,
+        out_start: *mut f64,
+        out_end: *mut f64,
+        out_per_second: *mut f64,
+    ) -> UsdBridgeErrorCode;
+
     fn usd_bridge_get_mesh_count(
         stage: *const UsdBridgeStageRaw,
         out_count: *mut usize,
@@ -132,6 +280,50 @@ extern "C" {
         out_data: *mut UsdBridgeInstancerDataRaw,
     ) -> UsdBridgeErrorCode;
 
+    fn usd_bridge_get_curves_count(
+        stage: *const UsdBridgeStageRaw,
+        out_count: *mut usize,
+    ) -> UsdBridgeErrorCode;
+
+    fn usd_bridge_get_curves(
+        stage: *const UsdBridgeStageRaw,
+        index: usize,
+        out_data: *mut UsdBridgeCurvesDataRaw,
+    ) -> UsdBridgeErrorCode;
+
+    fn usd_bridge_get_points_count(
+        stage: *const UsdBridgeStageRaw,
+        out_count: *mut usize,
+    ) -> UsdBridgeErrorCode;
+
+    fn usd_bridge_get_points(
+        stage: *const UsdBridgeStageRaw,
+        index: usize,
+        out_data: *mut UsdBridgePointsDataRaw,
+    ) -> UsdBridgeErrorCode;
+
+    fn usd_bridge_get_light_count(
+        stage: *const UsdBridgeStageRaw,
+        out_count: *mut usize,
+    ) -> UsdBridgeErrorCode;
+
+    fn usd_bridge_get_light(
+        stage: *const UsdBridgeStageRaw,
+        index: usize,
+        out_data: *mut UsdBridgeLightDataRaw,
+    ) -> UsdBridgeErrorCode;
+
+    fn usd_bridge_get_camera_count(
+        stage: *const UsdBridgeStageRaw,
+        out_count: *mut usize,
+    ) -> UsdBridgeErrorCode;
+
+    fn usd_bridge_get_camera(
+        stage: *const UsdBridgeStageRaw,
+        index: usize,
+        out_data: *mut UsdBridgeCameraDataRaw,
+    ) -> UsdBridgeErrorCode;
+
     fn usd_bridge_export_stage(
         stage: *const UsdBridgeStageRaw,
         path: *const std::ffi::c_char,
@@ -196,6 +388,26 @@ extern "C" {
         mesh_index: usize,
         out_path: *mut *const std::ffi::c_char,
     ) -> UsdBridgeErrorCode;
+
+    fn usd_bridge_get_mesh_subset_material_path(
+        stage: *const UsdBridgeStageRaw,
+        mesh_index: usize,
+        subset_index: usize,
+        out_path: *mut *const std::ffi::c_char,
+    ) -> UsdBridgeErrorCode;
+
+    fn usd_bridge_get_material_node_count(
+        stage: *const UsdBridgeStageRaw,
+        material_index: usize,
+        out_count: *mut usize,
+    ) -> UsdBridgeErrorCode;
+
+    fn usd_bridge_get_material_node(
+        stage: *const UsdBridgeStageRaw,
+        material_index: usize,
+        node_index: usize,
+        out_node: *mut UsdBridgeShaderNodeRaw,
+    ) -> UsdBridgeErrorCode;
 }
 
 // ============================================================================
@@ -277,6 +489,30 @@ pub struct UsdMeshData {
 
     /// World transform matrix
     pub transform: Mat4,
+
+    /// Per-triangle material GeomSubset index (None = no subsets, -1 = not in a subset)
+    pub triangle_subsets: Option<Vec<i32>>,
+
+    /// Material bound to each GeomSubset (None = subset has no binding)
+    pub subset_material_paths: Vec<Option<String>>,
+
+    /// Authored subdivision scheme (`None` unless explicitly catmullClark)
+    pub subdivision_scheme: SubdivisionScheme,
+
+    /// Polygon sizes of the control cage (subdivision meshes only)
+    pub face_vertex_counts: Vec<u32>,
+
+    /// Polygon vertex indices of the control cage (subdivision meshes only)
+    pub face_vertex_indices: Vec<u32>,
+
+    /// Creases and corners of the control cage
+    pub subdivision_tags: SubdivisionTags,
+
+    /// Displacement bound from `primvars:ri:attributes:displacementbound:sphere`
+    pub displacement_bound: Option<f32>,
+
+    /// Computed visibility and purpose, and ray visibility primvars
+    pub visibility: UsdVisibility,
 }
 
 /// Point instancer data extracted from USD.
@@ -293,6 +529,76 @@ pub struct UsdInstancerData {
 
     /// Prototype index for each instance
     pub proto_indices: Vec<i32>,
+
+    /// Computed visibility and purpose, and ray visibility primvars
+    pub visibility: UsdVisibility,
+}
+
+/// Basis curves data extracted from USD.
+#[derive(Clone, Debug)]
+pub struct UsdCurvesData {
+    /// Prim path in the USD hierarchy
+    pub path: String,
+
+    /// Control points (local space)
+    pub points: Vec<Vec3>,
+
+    /// Number of control points per curve
+    pub curve_vertex_counts: Vec<u32>,
+
+    /// Authored widths (may be empty)
+    pub widths: Vec<f32>,
+
+    /// Curve basis from the `type` and `basis` attributes
+    pub basis: CurveBasis,
+
+    /// Ribbon if normals are authored, round otherwise
+    pub shape: CurveShape,
+
+    /// World transform matrix
+    pub transform: Mat4,
+
+    /// Bound material path, if any
+    pub material_path: Option<String>,
+}
+
+impl UsdCurvesData {
+    /// Convert to scene curve geometry.
+    pub fn to_curves(&self) -> Curves {
+        Curves::new(
+            self.points.clone(),
+            self.curve_vertex_counts.clone(),
+            &self.widths,
+            self.basis,
+            self.shape,
+        )
+    }
+}
+
+/// Points data extracted from USD.
+#[derive(Clone, Debug)]
+pub struct UsdPointsData {
+    /// Prim path in the USD hierarchy
+    pub path: String,
+
+    /// Point centers (local space)
+    pub points: Vec<Vec3>,
+
+    /// Authored widths (may be empty)
+    pub widths: Vec<f32>,
+
+    /// World transform matrix
+    pub transform: Mat4,
+
+    /// Bound material path, if any
+    pub material_path: Option<String>,
+}
+
+impl UsdPointsData {
+    /// Convert to scene point geometry.
+    pub fn to_points(&self) -> Points {
+        Points::new(self.points.clone(), &self.widths)
+    }
 }
 
 /// Prim info for scene hierarchy browsing.
@@ -335,6 +641,9 @@ pub struct UsdMaterialData {
     /// Opacity (0=transparent, 1=opaque)
     pub opacity: f32,
 
+    /// Opacity cutout threshold (0 = partial opacity)
+    pub opacity_threshold: f32,
+
     /// Emissive color (RGB)
     pub emissive_color: Vec3,
 
@@ -353,8 +662,32 @@ pub struct UsdMaterialData {
     /// Path to emissive texture (if any)
     pub emissive_texture: Option<String>,
 
+    /// Path to opacity texture (if any)
+    pub opacity_texture: Option<String>,
+
+    /// Texture channel feeding opacity (0=r, 1=g, 2=b, 3=a)
+    pub opacity_texture_channel: usize,
+
+    /// Path to displacement texture (if any)
+    pub displacement_texture: Option<String>,
+
+    /// Texture channel feeding displacement (0=r, 1=g, 2=b, 3=a)
+    pub displacement_texture_channel: usize,
+
+    /// Displacement texture reader scale for the displacement channel
+    pub displacement_scale: f32,
+
+    /// Displacement texture reader bias for the displacement channel
+    pub displacement_bias: f32,
+
+    /// True if the displacement input is a vector3 offset rather than a height
+    pub vector_displacement: bool,
+
     /// True if material is from MaterialX, false for UsdPreviewSurface
     pub is_materialx: bool,
+
+    /// MaterialX node network feeding the surface shader (MaterialX only)
+    pub shading_graph: Option<ShadingGraph>,
 }
 
 // ============================================================================
@@ -404,6 +737,40 @@ impl UsdStage {
         Ok(Self { raw })
     }
 
+    /// Read geometry, transforms and instancers at time code `time` from
+    /// now on, dropping data already read.
+    ///
+    /// Without a time, animated points are read at their earliest sample
+    /// and transforms at their default values.
+    pub fn set_time(&mut self, time: f64) -> UsdBridgeResult<()> {
+        let result = unsafe { usd_bridge_set_time(self.raw, time) };
+
+        if result != UsdBridgeErrorCode::Success {
+            return Err(result.into());
+        }
+
+        Ok(())
+    }
+
+    /// Get the stage's playback range.
+    pub fn time_codes(&self) -> UsdBridgeResult<TimeCodes> {
+        let mut time_codes = TimeCodes::default();
+        let result = unsafe {
+            usd_bridge_get_time_codes(
+                self.raw,
+                &mut time_codes.start,
+                &mut time_codes.end,
+                &mut time_codes.per_second,
+            )
+        };
+
+        if result != UsdBridgeErrorCode::Success {
+            return Err(result.into());
+        }
+
+        Ok(time_codes)
+    }
+
     /// Get the number of mesh prims in the stage.
     pub fn mesh_count(&self) -> UsdBridgeResult<usize> {
         let mut count: usize = 0;
@@ -428,6 +795,54 @@ impl UsdStage {
         Ok(count)
     }
 
+    /// Get the number of basis curves prims in the stage.
+    pub fn curves_count(&self) -> UsdBridgeResult<usize> {
+        let mut count: usize = 0;
+        let result = unsafe { usd_bridge_get_curves_count(self.raw, &mut count) };
+
+        if result != UsdBridgeErrorCode::Success {
+            return Err(result.into());
+        }
+
+        Ok(count)
+    }
+
+    /// Get the number of points prims in the stage.
+    pub fn points_count(&self) -> UsdBridgeResult<usize> {
+        let mut count: usize = 0;
+        let result = unsafe { usd_bridge_get_points_count(self.raw, &mut count) };
+
+        if result != UsdBridgeErrorCode::Success {
+            return Err(result.into());
+        }
+
+        Ok(count)
+    }
+
+    /// Get the number of (visible) lights in the stage.
+    pub fn light_count(&self) -> UsdBridgeResult<usize> {
+        let mut count: usize = 0;
+        let result = unsafe { usd_bridge_get_light_count(self.raw, &mut count) };
+
+        if result != UsdBridgeErrorCode::Success {
+            return Err(result.into());
+        }
+
+        Ok(count)
+    }
+
+    /// Get the number of cameras in the stage.
+    pub fn camera_count(&self) -> UsdBridgeResult<usize> {
+        let mut count: usize = 0;
+        let result = unsafe { usd_bridge_get_camera_count(self.raw, &mut count) };
+
+        if result != UsdBridgeErrorCode::Success {
+            return Err(result.into());
+        }
+
+        Ok(count)
+    }
+
     /// Get mesh data by index.
     pub fn get_mesh(&self, index: usize) -> UsdBridgeResult<UsdMeshData> {
         let mut raw_data = UsdBridgeMeshDataRaw {
@@ -441,6 +856,27 @@ impl UsdStage {
             uvs: ptr::null(),
             uv_count: 0,
             transform: [0.0; 16],
+            triangle_subsets: ptr::null(),
+            triangle_subset_count: 0,
+            subset_count: 0,
+            subdivision_scheme: 0,
+            face_vertex_counts: ptr::null(),
+            face_count: 0,
+            face_vertex_indices: ptr::null(),
+            face_vertex_index_count: 0,
+            crease_indices: ptr::null(),
+            crease_index_count: 0,
+            crease_lengths: ptr::null(),
+            crease_length_count: 0,
+            crease_sharpnesses: ptr::null(),
+            crease_sharpness_count: 0,
+            corner_indices: ptr::null(),
+            corner_index_count: 0,
+            corner_sharpnesses: ptr::null(),
+            corner_sharpness_count: 0,
+            displacement_bound: -1.0,
+            visible: 1,
+            ray_visibility: Visibility::ALL.bits(),
         };
 
         let result = unsafe { usd_bridge_get_mesh(self.raw, index, &mut raw_data) };
@@ -519,6 +955,62 @@ impl UsdStage {
         // Convert transform (column-major f32[16] to Mat4)
         let transform = Mat4::from_cols_array(&raw_data.transform);
 
+        // Convert GeomSubset material bindings (optional)
+        let triangle_subsets = unsafe {
+            if raw_data.triangle_subsets.is_null() || raw_data.triangle_subset_count == 0 {
+                None
+            } else {
+                Some(
+                    std::slice::from_raw_parts(
+                        raw_data.triangle_subsets,
+                        raw_data.triangle_subset_count,
+                    )
+                    .to_vec(),
+                )
+            }
+        };
+        let subset_material_paths = (0..raw_data.subset_count)
+            .map(|subset| self.get_mesh_subset_material_path(index, subset))
+            .collect::<UsdBridgeResult<Vec<_>>>()?;
+
+        // Subdivision control cage and tags (only filled for catmullClark)
+        let subdivision_scheme = if raw_data.subdivision_scheme == 1 {
+            SubdivisionScheme::CatmullClark
+        } else {
+            SubdivisionScheme::None
+        };
+        let (face_vertex_counts, face_vertex_indices, subdivision_tags) = unsafe {
+            (
+                copy_array(raw_data.face_vertex_counts, raw_data.face_count),
+                copy_array(
+                    raw_data.face_vertex_indices,
+                    raw_data.face_vertex_index_count,
+                ),
+                SubdivisionTags {
+                    crease_indices: copy_array(
+                        raw_data.crease_indices,
+                        raw_data.crease_index_count,
+                    ),
+                    crease_lengths: copy_array(
+                        raw_data.crease_lengths,
+                        raw_data.crease_length_count,
+                    ),
+                    crease_sharpnesses: copy_array(
+                        raw_data.crease_sharpnesses,
+                        raw_data.crease_sharpness_count,
+                    ),
+                    corner_indices: copy_array(
+                        raw_data.corner_indices,
+                        raw_data.corner_index_count,
+                    ),
+                    corner_sharpnesses: copy_array(
+                        raw_data.corner_sharpnesses,
+                        raw_data.corner_sharpness_count,
+                    ),
+                },
+            )
+        };
+
         Ok(UsdMeshData {
             path,
             vertices,
@@ -526,9 +1018,59 @@ impl UsdStage {
             normals,
             uvs,
             transform,
+            triangle_subsets,
+            subset_material_paths,
+            subdivision_scheme,
+            face_vertex_counts,
+            face_vertex_indices,
+            subdivision_tags,
+            displacement_bound: (raw_data.displacement_bound >= 0.0)
+                .then_some(raw_data.displacement_bound),
+            visibility: UsdVisibility {
+                visible: raw_data.visible != 0,
+                rays: Visibility::from_bits(raw_data.ray_visibility),
+            },
         })
     }
 
+    /// Get the material path bound to one of a mesh's GeomSubsets.
+    pub fn get_mesh_subset_material_path(
+        &self,
+        mesh_index: usize,
+        subset_index: usize,
+    ) -> UsdBridgeResult<Option<String>> {
+        let mut path_ptr: *const std::ffi::c_char = ptr::null();
+        let result = unsafe {
+            usd_bridge_get_mesh_subset_material_path(
+                self.raw,
+                mesh_index,
+                subset_index,
+                &mut path_ptr,
+            )
+        };
+
+        if result != UsdBridgeErrorCode::Success {
+            return Err(match result {
+                UsdBridgeErrorCode::InvalidPrim => UsdBridgeError::InvalidPrim(format!(
+                    "mesh {} subset {}",
+                    mesh_index, subset_index
+                )),
+                other => other.into(),
+            });
+        }
+
+        if path_ptr.is_null() {
+            return Ok(None);
+        }
+
+        let path = unsafe { CStr::from_ptr(path_ptr).to_string_lossy().into_owned() };
+        if path.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(path))
+        }
+    }
+
     /// Get instancer data by index.
     pub fn get_instancer(&self, index: usize) -> UsdBridgeResult<UsdInstancerData> {
         let mut raw_data = UsdBridgeInstancerDataRaw {
@@ -538,6 +1080,8 @@ impl UsdStage {
             transforms: ptr::null(),
             instance_count: 0,
             proto_indices: ptr::null(),
+            visible: 1,
+            ray_visibility: Visibility::ALL.bits(),
         };
 
         let result = unsafe { usd_bridge_get_instancer(self.raw, index, &mut raw_data) };
@@ -611,6 +1155,277 @@ impl UsdStage {
             prototype_paths,
             transforms,
             proto_indices,
+            visibility: UsdVisibility {
+                visible: raw_data.visible != 0,
+                rays: Visibility::from_bits(raw_data.ray_visibility),
+            },
+        })
+    }
+
+    /// Get basis curves data by index.
+    pub fn get_curves(&self, index: usize) -> UsdBridgeResult<UsdCurvesData> {
+        let mut raw_data = UsdBridgeCurvesDataRaw {
+            path: ptr::null(),
+            points: ptr::null(),
+            point_count: 0,
+            curve_vertex_counts: ptr::null(),
+            curve_count: 0,
+            widths: ptr::null(),
+            width_count: 0,
+            curve_type: 1,
+            basis: 0,
+            has_normals: 0,
+            transform: [0.0; 16],
+            material_path: ptr::null(),
+        };
+
+        let result = unsafe { usd_bridge_get_curves(self.raw, index, &mut raw_data) };
+
+        if result != UsdBridgeErrorCode::Success {
+            return Err(match result {
+                UsdBridgeErrorCode::InvalidPrim => {
+                    UsdBridgeError::InvalidPrim(format!("curves index {}", index))
+                }
+                other => other.into(),
+            });
+        }
+
+        let c_string = |ptr: *const std::ffi::c_char| unsafe {
+            if ptr.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(ptr).to_string_lossy().into_owned()
+            }
+        };
+
+        let points = unsafe { copy_array(raw_data.points, raw_data.point_count * 3) }
+            .chunks_exact(3)
+            .map(|chunk| Vec3::new(chunk[0], chunk[1], chunk[2]))
+            .collect();
+
+        let basis = match (raw_data.curve_type, raw_data.basis) {
+            (0, _) => CurveBasis::Linear,
+            (_, 1) => CurveBasis::BSpline,
+            (_, 2) => CurveBasis::CatmullRom,
+            _ => CurveBasis::Bezier,
+        };
+        let shape = if raw_data.has_normals != 0 {
+            CurveShape::Ribbon
+        } else {
+            CurveShape::Round
+        };
+        let material_path = Some(c_string(raw_data.material_path)).filter(|p| !p.is_empty());
+
+        Ok(UsdCurvesData {
+            path: c_string(raw_data.path),
+            points,
+            curve_vertex_counts: unsafe {
+                copy_array(raw_data.curve_vertex_counts, raw_data.curve_count)
+            },
+            widths: unsafe { copy_array(raw_data.widths, raw_data.width_count) },
+            basis,
+            shape,
+            transform: Mat4::from_cols_array(&raw_data.transform),
+            material_path,
+        })
+    }
+
+    /// Get points data by index.
+    pub fn get_points(&self, index: usize) -> UsdBridgeResult<UsdPointsData> {
+        let mut raw_data = UsdBridgePointsDataRaw {
+            path: ptr::null(),
+            points: ptr::null(),
+            point_count: 0,
+            widths: ptr::null(),
+            width_count: 0,
+            transform: [0.0; 16],
+            material_path: ptr::null(),
+        };
+
+        let result = unsafe { usd_bridge_get_points(self.raw, index, &mut raw_data) };
+
+        if result != UsdBridgeErrorCode::Success {
+            return Err(match result {
+                UsdBridgeErrorCode::InvalidPrim => {
+                    UsdBridgeError::InvalidPrim(format!("points index {}", index))
+                }
+                other => other.into(),
+            });
+        }
+
+        let c_string = |ptr: *const std::ffi::c_char| unsafe {
+            if ptr.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(ptr).to_string_lossy().into_owned()
+            }
+        };
+
+        let points = unsafe { copy_array(raw_data.points, raw_data.point_count * 3) }
+            .chunks_exact(3)
+            .map(|chunk| Vec3::new(chunk[0], chunk[1], chunk[2]))
+            .collect();
+        let material_path = Some(c_string(raw_data.material_path)).filter(|p| !p.is_empty());
+
+        Ok(UsdPointsData {
+            path: c_string(raw_data.path),
+            points,
+            widths: unsafe { copy_array(raw_data.widths, raw_data.width_count) },
+            transform: Mat4::from_cols_array(&raw_data.transform),
+            material_path,
+        })
+    }
+
+    /// Get a light by index, named by its prim path and in world space.
+    pub fn get_light(&self, index: usize) -> UsdBridgeResult<Light> {
+        let mut raw_data = UsdBridgeLightDataRaw {
+            path: ptr::null(),
+            light_type: 0,
+            transform: [0.0; 16],
+            intensity: 0.0,
+            exposure: 0.0,
+            color: [0.0; 3],
+            enable_color_temperature: 0,
+            color_temperature: 0.0,
+            normalize: 0,
+            radius: 0.0,
+            width: 0.0,
+            height: 0.0,
+            length: 0.0,
+            angle: 0.0,
+            treat_as_point: 0,
+            texture_format: 0,
+            texture_file: ptr::null(),
+            has_shaping: 0,
+            cone_angle: 0.0,
+            cone_softness: 0.0,
+            focus: 0.0,
+            focus_tint: [0.0; 3],
+            ies_file: ptr::null(),
+            ies_angle_scale: 0.0,
+            ies_normalize: 0,
+        };
+
+        let result = unsafe { usd_bridge_get_light(self.raw, index, &mut raw_data) };
+
+        if result != UsdBridgeErrorCode::Success {
+            return Err(match result {
+                UsdBridgeErrorCode::InvalidPrim => {
+                    UsdBridgeError::InvalidPrim(format!("light index {}", index))
+                }
+                other => other.into(),
+            });
+        }
+
+        let c_string = |ptr: *const std::ffi::c_char| unsafe {
+            if ptr.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(ptr).to_string_lossy().into_owned()
+            }
+        };
+        let path = c_string(raw_data.path);
+
+        let kind = match raw_data.light_type {
+            0 => LightKind::Sphere {
+                radius: raw_data.radius,
+                treat_as_point: raw_data.treat_as_point != 0,
+            },
+            1 => LightKind::Rect {
+                width: raw_data.width,
+                height: raw_data.height,
+            },
+            2 => LightKind::Disk {
+                radius: raw_data.radius,
+            },
+            3 => LightKind::Cylinder {
+                length: raw_data.length,
+                radius: raw_data.radius,
+                treat_as_line: raw_data.treat_as_point != 0,
+            },
+            4 => LightKind::Distant {
+                angle: raw_data.angle,
+            },
+            5 => LightKind::Dome {
+                texture_format: match raw_data.texture_format {
+                    1 => DomeTextureFormat::Latlong,
+                    2 => DomeTextureFormat::MirroredBall,
+                    3 => DomeTextureFormat::Angular,
+                    4 => DomeTextureFormat::CubeMapVerticalCross,
+                    _ => DomeTextureFormat::Automatic,
+                },
+            },
+            other => {
+                return Err(UsdBridgeError::InvalidPrim(format!(
+                    "{} has unknown light type {}",
+                    path, other
+                )))
+            }
+        };
+
+        let shaping = (raw_data.has_shaping != 0).then(|| LightShaping {
+            cone_angle: raw_data.cone_angle,
+            cone_softness: raw_data.cone_softness,
+            focus: raw_data.focus,
+            focus_tint: Vec3::from_array(raw_data.focus_tint),
+            ies_file: Some(c_string(raw_data.ies_file)).filter(|p| !p.is_empty()),
+            ies_angle_scale: raw_data.ies_angle_scale,
+            ies_normalize: raw_data.ies_normalize != 0,
+        });
+
+        Ok(Light {
+            intensity: raw_data.intensity,
+            exposure: raw_data.exposure,
+            color: Vec3::from_array(raw_data.color),
+            enable_color_temperature: raw_data.enable_color_temperature != 0,
+            color_temperature: raw_data.color_temperature,
+            normalize: raw_data.normalize != 0,
+            texture: Some(c_string(raw_data.texture_file)).filter(|p| !p.is_empty()),
+            shaping,
+            ..Light::new(path, kind).with_transform(Mat4::from_cols_array(&raw_data.transform))
+        })
+    }
+
+    /// Get a camera by index, named by its prim path and in world space.
+    pub fn get_camera(&self, index: usize) -> UsdBridgeResult<Camera> {
+        let mut raw_data = UsdBridgeCameraDataRaw {
+            path: ptr::null(),
+            transform: [0.0; 16],
+            focal_length: 0.0,
+            horizontal_aperture: 0.0,
+            vertical_aperture: 0.0,
+            clipping_range: [0.0; 2],
+            f_stop: 0.0,
+            focus_distance: 0.0,
+        };
+
+        let result = unsafe { usd_bridge_get_camera(self.raw, index, &mut raw_data) };
+
+        if result != UsdBridgeErrorCode::Success {
+            return Err(match result {
+                UsdBridgeErrorCode::InvalidPrim => {
+                    UsdBridgeError::InvalidPrim(format!("camera index {}", index))
+                }
+                other => other.into(),
+            });
+        }
+
+        let path = if raw_data.path.is_null() {
+            String::new()
+        } else {
+            unsafe { CStr::from_ptr(raw_data.path) }
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        Ok(Camera {
+            focal_length: raw_data.focal_length,
+            horizontal_aperture: raw_data.horizontal_aperture,
+            vertical_aperture: raw_data.vertical_aperture,
+            clipping_range: (raw_data.clipping_range[0], raw_data.clipping_range[1]),
+            f_stop: raw_data.f_stop,
+            focus_distance: raw_data.focus_distance,
+            ..Camera::new(path).with_transform(Mat4::from_cols_array(&raw_data.transform))
         })
     }
 
@@ -634,6 +1449,46 @@ impl UsdStage {
         Ok(instancers)
     }
 
+    /// Get all basis curves in the stage.
+    pub fn curves(&self) -> UsdBridgeResult<Vec<UsdCurvesData>> {
+        let count = self.curves_count()?;
+        let mut curves = Vec::with_capacity(count);
+        for i in 0..count {
+            curves.push(self.get_curves(i)?);
+        }
+        Ok(curves)
+    }
+
+    /// Get all points prims in the stage.
+    pub fn points(&self) -> UsdBridgeResult<Vec<UsdPointsData>> {
+        let count = self.points_count()?;
+        let mut points = Vec::with_capacity(count);
+        for i in 0..count {
+            points.push(self.get_points(i)?);
+        }
+        Ok(points)
+    }
+
+    /// Get all (visible) lights in the stage.
+    pub fn lights(&self) -> UsdBridgeResult<Vec<Light>> {
+        let count = self.light_count()?;
+        let mut lights = Vec::with_capacity(count);
+        for i in 0..count {
+            lights.push(self.get_light(i)?);
+        }
+        Ok(lights)
+    }
+
+    /// Get all cameras in the stage.
+    pub fn cameras(&self) -> UsdBridgeResult<Vec<Camera>> {
+        let count = self.camera_count()?;
+        let mut cameras = Vec::with_capacity(count);
+        for i in 0..count {
+            cameras.push(self.get_camera(i)?);
+        }
+        Ok(cameras)
+    }
+
     /// Get the number of materials in the stage.
     pub fn material_count(&self) -> UsdBridgeResult<usize> {
         let mut count: usize = 0;
@@ -655,13 +1510,21 @@ impl UsdStage {
             roughness: 0.5,
             specular: 0.5,
             opacity: 1.0,
+            opacity_threshold: 0.0,
             emissive_color: [0.0, 0.0, 0.0],
             diffuse_texture: ptr::null(),
             roughness_texture: ptr::null(),
             metallic_texture: ptr::null(),
             normal_texture: ptr::null(),
             emissive_texture: ptr::null(),
+            opacity_texture: ptr::null(),
+            opacity_texture_channel: 0,
             is_materialx: 0,
+            displacement_texture: ptr::null(),
+            displacement_texture_channel: 0,
+            displacement_scale: 1.0,
+            displacement_bias: 0.0,
+            vector_displacement: 0,
         };
 
         let result = unsafe { usd_bridge_get_material(self.raw, index, &mut raw_data) };
@@ -709,6 +1572,7 @@ impl UsdStage {
             roughness: raw_data.roughness,
             specular: raw_data.specular,
             opacity: raw_data.opacity,
+            opacity_threshold: raw_data.opacity_threshold,
             emissive_color: Vec3::new(
                 raw_data.emissive_color[0],
                 raw_data.emissive_color[1],
@@ -719,10 +1583,106 @@ impl UsdStage {
             metallic_texture: texture_path(raw_data.metallic_texture),
             normal_texture: texture_path(raw_data.normal_texture),
             emissive_texture: texture_path(raw_data.emissive_texture),
+            opacity_texture: texture_path(raw_data.opacity_texture),
+            opacity_texture_channel: raw_data.opacity_texture_channel.clamp(0, 3) as usize,
+            displacement_texture: texture_path(raw_data.displacement_texture),
+            displacement_texture_channel: raw_data.displacement_texture_channel.clamp(0, 3)
+                as usize,
+            displacement_scale: raw_data.displacement_scale,
+            displacement_bias: raw_data.displacement_bias,
+            vector_displacement: raw_data.vector_displacement != 0,
             is_materialx: raw_data.is_materialx != 0,
+            shading_graph: if raw_data.is_materialx != 0 {
+                self.material_graph(index)?
+            } else {
+                None
+            },
         })
     }
 
+    /// Get the MaterialX node network of a material.
+    ///
+    /// Node 0 of the returned graph is the surface shader. Returns `None` for
+    /// materials without a network (e.g., UsdPreviewSurface).
+    pub fn material_graph(&self, index: usize) -> UsdBridgeResult<Option<ShadingGraph>> {
+        let mut count: usize = 0;
+        let result = unsafe { usd_bridge_get_material_node_count(self.raw, index, &mut count) };
+
+        if result != UsdBridgeErrorCode::Success {
+            return Err(match result {
+                UsdBridgeErrorCode::InvalidPrim => {
+                    UsdBridgeError::InvalidPrim(format!("material index {}", index))
+                }
+                other => other.into(),
+            });
+        }
+
+        if count == 0 {
+            return Ok(None);
+        }
+
+        let c_string = |ptr: *const std::ffi::c_char| -> String {
+            if ptr.is_null() {
+                String::new()
+            } else {
+                unsafe { CStr::from_ptr(ptr).to_string_lossy().into_owned() }
+            }
+        };
+
+        let mut graph = ShadingGraph::new();
+        for node_index in 0..count {
+            let mut raw_node = UsdBridgeShaderNodeRaw {
+                path: ptr::null(),
+                shader_id: ptr::null(),
+                inputs: ptr::null(),
+                input_count: 0,
+            };
+
+            let result =
+                unsafe { usd_bridge_get_material_node(self.raw, index, node_index, &mut raw_node) };
+            if result != UsdBridgeErrorCode::Success {
+                return Err(result.into());
+            }
+
+            let mut node = ShadingNode::new(c_string(raw_node.path), c_string(raw_node.shader_id));
+
+            let raw_inputs = if raw_node.inputs.is_null() || raw_node.input_count == 0 {
+                &[][..]
+            } else {
+                unsafe { std::slice::from_raw_parts(raw_node.inputs, raw_node.input_count) }
+            };
+
+            for raw_input in raw_inputs {
+                let name = c_string(raw_input.name);
+                if raw_input.connected_node >= 0 {
+                    node.inputs.push((
+                        name,
+                        ShadingInput::Connection {
+                            node: raw_input.connected_node as usize,
+                            output: c_string(raw_input.connected_output),
+                        },
+                    ));
+                    continue;
+                }
+
+                let [x, y, z] = raw_input.value;
+                let value = match raw_input.kind {
+                    SHADER_VALUE_FLOAT => ShadingValue::Float(x),
+                    SHADER_VALUE_VECTOR2 => ShadingValue::Vector2([x, y]),
+                    SHADER_VALUE_VECTOR3 => ShadingValue::Vector3(Vec3::new(x, y, z)),
+                    SHADER_VALUE_STRING => ShadingValue::String(c_string(raw_input.string_value)),
+                    _ => continue,
+                };
+                node.inputs.push((name, ShadingInput::Value(value)));
+            }
+
+            graph.add_node(node);
+        }
+
+        graph.surface = 0;
+        Ok(Some(graph))
+    }
+
     /// Get the material path bound to a mesh.
     pub fn get_mesh_material_path(&self, mesh_index: usize) -> UsdBridgeResult<Option<String>> {
         let mut path_ptr: *const std::ffi::c_char = ptr::null();
@@ -963,6 +1923,18 @@ impl Drop for UsdStage {
     }
 }
 
+/// Copy an optional C array into a Vec (NULL or empty gives an empty Vec).
+///
+/// # Safety
+/// `data` must be NULL or point to at least `count` valid elements.
+unsafe fn copy_array<T: Copy>(data: *const T, count: usize) -> Vec<T> {
+    if data.is_null() || count == 0 {
+        Vec::new()
+    } else {
+        std::slice::from_raw_parts(data, count).to_vec()
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -12,14 +12,24 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use bif_math::Mat4;
+use bif_math::{Mat4, Vec3};
 use thiserror::Error;
 
-use crate::mesh::Mesh;
-use crate::scene::{Scene, Transform};
-use crate::usd::cpp_bridge::{UsdBridgeError, UsdStage};
-use crate::usd::parser::{parse_usda, ParseError};
-use crate::usd::types::{UsdMesh, UsdPointInstancer, UsdPrim, UsdReference, UsdXform};
+use crate::animation::{AnimationCurve, Interpolate};
+use crate::mesh::{
+    Mesh, SubdivisionMesh, SubdivisionScheme, SubdivisionTags, DEFAULT_SUBDIVISION_LEVEL,
+};
+use crate::scene::{
+    CurveSet, Instance, Material, MaterialId, PointSet, Prototype, Scene, Transform, Visibility,
+    NO_MATERIAL,
+};
+use crate::shading::ShadingValue;
+use crate::usd::cpp_bridge::{UsdBridgeError, UsdInstancerData, UsdMeshData, UsdStage};
+use crate::usd::parser::{ParseError, UsdaParser};
+use crate::usd::types::{
+    UsdBasisCurves, UsdCamera, UsdLight, UsdMaterial, UsdMesh, UsdPointInstancer, UsdPoints,
+    UsdPrim, UsdReference, UsdShader, UsdShape, UsdXform,
+};
 
 /// Errors that can occur during USD loading.
 #[derive(Error, Debug)]
@@ -43,6 +53,32 @@ pub enum LoadError {
 /// Result type for loading operations.
 pub type LoadResult<T> = Result<T, LoadError>;
 
+/// Options controlling how USD geometry is converted.
+#[derive(Clone, Debug)]
+pub struct LoadOptions {
+    /// Catmull-Clark refinement level for `subdivisionScheme = "catmullClark"`
+    /// meshes (0 keeps the control cage)
+    pub subdivision_level: u32,
+
+    /// Time code to evaluate animated attributes at (`None` reads the
+    /// authored values, or the first time sample of attributes without one)
+    pub time: Option<f64>,
+
+    /// Also sample animated instances and meshes one time code after
+    /// `time`, as motion keys for motion blur (see [`Scene::sample_motion`])
+    pub motion_blur: bool,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            subdivision_level: DEFAULT_SUBDIVISION_LEVEL,
+            time: None,
+            motion_blur: false,
+        }
+    }
+}
+
 /// Load a USD file and return a BIF Scene.
 ///
 /// This function supports all USD formats via the C++ bridge:
@@ -79,6 +115,16 @@ pub fn load_usd<P: AsRef<Path>>(path: P) -> LoadResult<Scene> {
 /// println!("Loaded {} prims", stage.prim_count());
 /// ```
 pub fn load_usd_with_stage<P: AsRef<Path>>(path: P) -> LoadResult<(Scene, UsdStage)> {
+    load_usd_with_options(path, &LoadOptions::default())
+}
+
+/// Load a USD file with explicit [`LoadOptions`].
+///
+/// Returns the stage as well, like [`load_usd_with_stage`].
+pub fn load_usd_with_options<P: AsRef<Path>>(
+    path: P,
+    options: &LoadOptions,
+) -> LoadResult<(Scene, UsdStage)> {
     let path = path.as_ref();
     let name = path
         .file_stem()
@@ -86,63 +132,18 @@ pub fn load_usd_with_stage<P: AsRef<Path>>(path: P) -> LoadResult<(Scene, UsdSta
         .unwrap_or("unnamed");
 
     // Open stage via C++ bridge
-    let stage = UsdStage::open(path)?;
+    let mut stage = UsdStage::open(path)?;
+    if let Some(time) = options.time {
+        stage.set_time(time)?;
+    }
 
     let mut scene = Scene::new(name);
+    scene.time_codes = stage.time_codes()?;
     let mut prototype_map: HashMap<String, usize> = HashMap::new();
 
-    // Mesh deduplication: (vertex_count, index_count, first_vertex_hash) -> proto_id
+    // Mesh deduplication: MeshKey -> proto_id
     // This handles referenced meshes that appear multiple times with different transforms
-    let mut mesh_dedup: HashMap<(usize, usize, u64), usize> = HashMap::new();
-
-    // Load all meshes as prototypes (with deduplication)
-    let meshes = stage.meshes()?;
-    for mesh_data in &meshes {
-        let vertices = mesh_data.vertices.clone();
-        let indices = mesh_data.indices.clone();
-        let normals = mesh_data.normals.clone();
-        let uvs = mesh_data.uvs.clone();
-
-        // Create a hash key based on mesh geometry
-        // Use vertex count, index count, and hash of first few vertices
-        let vertex_hash = if vertices.len() >= 3 {
-            // Hash first 3 vertices (as Vec3)
-            let bits: u64 = vertices
-                .iter()
-                .take(3)
-                .enumerate()
-                .map(|(i, v)| {
-                    let x = v.x.to_bits() as u64;
-                    let y = v.y.to_bits() as u64;
-                    let z = v.z.to_bits() as u64;
-                    (x ^ y.rotate_left(21) ^ z.rotate_left(42)).wrapping_mul(i as u64 + 1)
-                })
-                .fold(0, |acc, x| acc ^ x);
-            bits
-        } else {
-            0
-        };
-        let dedup_key = (vertices.len(), indices.len(), vertex_hash);
-
-        let proto_id = if let Some(&existing_id) = mesh_dedup.get(&dedup_key) {
-            // Mesh already exists, reuse prototype
-            existing_id
-        } else {
-            // New unique mesh, create prototype
-            let mut mesh = Mesh::new_with_uvs(vertices, indices, normals, uvs);
-            mesh.ensure_normals();
-
-            let mesh_arc = Arc::new(mesh);
-            let proto_id = scene.add_prototype(mesh_arc, mesh_data.path.clone());
-            mesh_dedup.insert(dedup_key, proto_id);
-            prototype_map.insert(mesh_data.path.clone(), proto_id);
-            proto_id
-        };
-
-        // Add an instance with this mesh's world transform
-        let transform = Transform::from_matrix(mesh_data.transform);
-        scene.add_instance(proto_id, transform);
-    }
+    let mut mesh_dedup: HashMap<MeshKey, usize> = HashMap::new();
 
     // Load materials
     let usd_materials = stage.materials().unwrap_or_default();
@@ -153,11 +154,12 @@ pub fn load_usd_with_stage<P: AsRef<Path>>(path: P) -> LoadResult<(Scene, UsdSta
         if mat_data.is_materialx {
             materialx_count += 1;
             log::debug!(
-                "MaterialX material: {} (diffuse={:?}, metallic={:.2}, roughness={:.2})",
+                "MaterialX material: {} (diffuse={:?}, metallic={:.2}, roughness={:.2}, nodes={})",
                 mat_data.path,
                 mat_data.diffuse_color,
                 mat_data.metallic,
-                mat_data.roughness
+                mat_data.roughness,
+                mat_data.shading_graph.as_ref().map_or(0, |g| g.nodes.len())
             );
         }
         let material = crate::scene::Material {
@@ -167,12 +169,21 @@ pub fn load_usd_with_stage<P: AsRef<Path>>(path: P) -> LoadResult<(Scene, UsdSta
             roughness: mat_data.roughness,
             emissive_color: mat_data.emissive_color,
             opacity: mat_data.opacity,
+            opacity_threshold: mat_data.opacity_threshold,
             specular: mat_data.specular,
             diffuse_texture: mat_data.diffuse_texture.clone(),
             roughness_texture: mat_data.roughness_texture.clone(),
             metallic_texture: mat_data.metallic_texture.clone(),
             normal_texture: mat_data.normal_texture.clone(),
             emissive_texture: mat_data.emissive_texture.clone(),
+            opacity_texture: mat_data.opacity_texture.clone(),
+            opacity_texture_channel: mat_data.opacity_texture_channel,
+            displacement_texture: mat_data.displacement_texture.clone(),
+            displacement_texture_channel: mat_data.displacement_texture_channel,
+            displacement_scale: mat_data.displacement_scale,
+            displacement_bias: mat_data.displacement_bias,
+            vector_displacement: mat_data.vector_displacement,
+            shading_graph: mat_data.shading_graph.clone().map(Arc::new),
         };
         let mat_id = scene.add_material(material);
         material_map.insert(mat_data.path.clone(), mat_id);
@@ -182,24 +193,111 @@ pub fn load_usd_with_stage<P: AsRef<Path>>(path: P) -> LoadResult<(Scene, UsdSta
         log::info!("Loaded {} MaterialX materials", materialx_count);
     }
 
-    // Bind materials to prototypes via mesh material paths
-    for (mesh_idx, mesh_data) in meshes.iter().enumerate() {
-        if let Ok(Some(mat_path)) = stage.get_mesh_material_path(mesh_idx) {
-            if let Some(&mat_id) = material_map.get(&mat_path) {
-                // Find the prototype for this mesh and bind the material
-                if let Some(&proto_id) = prototype_map.get(&mesh_data.path) {
-                    if let Some(proto) = scene.prototypes.get(proto_id) {
-                        let mut updated_proto: crate::scene::Prototype = (**proto).clone();
-                        let mat = &scene.materials[mat_id];
-                        log::debug!(
-                            "Binding {} to prototype {} (diffuse={:?})",
-                            mat.name, proto_id, mat.diffuse_color
-                        );
-                        updated_proto.material = Some(mat.clone());
-                        scene.prototypes[proto_id] = Arc::new(updated_proto);
-                    }
+    // Load all meshes as prototypes (with deduplication)
+    let meshes = stage.meshes()?;
+    for mesh_data in &meshes {
+        let vertices = mesh_data.vertices.clone();
+        let indices = mesh_data.indices.clone();
+        let normals = mesh_data.normals.clone();
+        let uvs = mesh_data.uvs.clone();
+        let face_ids = face_material_ids(mesh_data, &material_map);
+        let dedup_key = MeshKey::new(mesh_data, face_ids.as_deref());
+
+        let proto_id = if let Some(&existing_id) = mesh_dedup.get(&dedup_key) {
+            // Mesh already exists, reuse prototype
+            existing_id
+        } else {
+            // New unique mesh, create prototype (refining subdivision cages)
+            let (mut mesh, triangle_origins) =
+                if mesh_data.subdivision_scheme == SubdivisionScheme::CatmullClark {
+                    let (mesh, origins) = subdivide(
+                        vertices,
+                        uvs,
+                        &mesh_data.face_vertex_counts,
+                        &mesh_data.face_vertex_indices,
+                        &mesh_data.subdivision_tags,
+                        options.subdivision_level,
+                    );
+                    (mesh, Some(origins))
+                } else {
+                    (Mesh::new_with_uvs(vertices, indices, normals, uvs), None)
+                };
+            mesh.ensure_normals();
+
+            let mesh_arc = Arc::new(mesh);
+            let proto_id = scene.add_prototype(mesh_arc, mesh_data.path.clone());
+
+            // Per-face materials from GeomSubset bindings
+            if let Some(mut face_ids) = face_ids {
+                if let Some(origins) = &triangle_origins {
+                    face_ids = refined_face_ids(&face_ids, &mesh_data.face_vertex_counts, origins);
                 }
+                let proto = (*scene.prototypes[proto_id])
+                    .clone()
+                    .with_face_materials(face_ids);
+                scene.prototypes[proto_id] = Arc::new(proto);
+            }
+
+            if let Some(bound) = mesh_data.displacement_bound {
+                let proto = (*scene.prototypes[proto_id])
+                    .clone()
+                    .with_displacement_bound(bound);
+                scene.prototypes[proto_id] = Arc::new(proto);
+            }
+
+            if mesh_data.visibility.rays != Visibility::ALL {
+                let proto = (*scene.prototypes[proto_id])
+                    .clone()
+                    .with_visibility(mesh_data.visibility.rays);
+                scene.prototypes[proto_id] = Arc::new(proto);
+            }
+
+            mesh_dedup.insert(dedup_key, proto_id);
+            prototype_map.insert(mesh_data.path.clone(), proto_id);
+            proto_id
+        };
+
+        // Add an instance with this mesh's world transform. Hidden meshes
+        // keep an (invisible) instance so instance indices match mesh indices.
+        let transform = Transform::from_matrix(mesh_data.transform);
+        let visibility = if mesh_data.visibility.visible {
+            Visibility::ALL
+        } else {
+            Visibility::NONE
+        };
+        scene
+            .instances
+            .push(Instance::new(proto_id, transform).with_visibility(visibility));
+    }
+
+    // Bind materials to prototypes via mesh material paths.
+    // Instances were added one per mesh above, so mesh_idx is also the instance index.
+    for (mesh_idx, mesh_data) in meshes.iter().enumerate() {
+        let Ok(Some(mat_path)) = stage.get_mesh_material_path(mesh_idx) else {
+            continue;
+        };
+        let Some(&mat_id) = material_map.get(&mat_path) else {
+            continue;
+        };
+
+        if let Some(&proto_id) = prototype_map.get(&mesh_data.path) {
+            // This mesh created the prototype: bind the material to it
+            if let Some(proto) = scene.prototypes.get(proto_id) {
+                let mut updated_proto: crate::scene::Prototype = (**proto).clone();
+                let mat = &scene.materials[mat_id];
+                log::debug!(
+                    "Binding {} to prototype {} (diffuse={:?})",
+                    mat.name,
+                    proto_id,
+                    mat.diffuse_color
+                );
+                updated_proto.material = Some(mat.clone());
+                scene.prototypes[proto_id] = Arc::new(updated_proto);
             }
+        } else if let Ok(id) = MaterialId::try_from(mat_id) {
+            // Deduplicated mesh sharing another mesh's prototype: bind per instance
+            log::debug!("Binding {} to instance {}", mat_path, mesh_idx);
+            scene.instances[mesh_idx].material_id = Some(id);
         }
     }
 
@@ -238,17 +336,274 @@ pub fn load_usd_with_stage<P: AsRef<Path>>(path: P) -> LoadResult<(Scene, UsdSta
                 .copied()
                 .unwrap_or(0);
 
-            scene.add_instance(proto_id, Transform::from_matrix(*transform));
+            scene.instances.push(
+                Instance::new(proto_id, Transform::from_matrix(*transform))
+                    .with_visibility(instancer_data.visibility.mask()),
+            );
+        }
+    }
+
+    // Load basis curves (hair, fur) with their bound materials
+    for curves_data in stage.curves()? {
+        let mut set = CurveSet::new(
+            curves_data.path.clone(),
+            Arc::new(curves_data.to_curves()),
+            curves_data.transform,
+        );
+        let material = curves_data
+            .material_path
+            .as_ref()
+            .and_then(|path| material_map.get(path));
+        if let Some(&mat_id) = material {
+            set = set.with_material(scene.materials[mat_id].clone());
+        }
+        scene.add_curves(set);
+    }
+
+    // Load point clouds (particles) with their bound materials
+    for points_data in stage.points()? {
+        let mut set = PointSet::new(
+            points_data.path.clone(),
+            Arc::new(points_data.to_points()),
+            points_data.transform,
+        );
+        let material = points_data
+            .material_path
+            .as_ref()
+            .and_then(|path| material_map.get(path));
+        if let Some(&mat_id) = material {
+            set = set.with_material(scene.materials[mat_id].clone());
         }
+        scene.add_points(set);
+    }
+
+    for light in stage.lights()? {
+        scene.add_light(light);
+    }
+    if !scene.lights.is_empty() {
+        log::info!("Loaded {} lights", scene.lights.len());
+    }
+
+    for camera in stage.cameras()? {
+        scene.add_camera(camera);
+    }
+    if !scene.cameras.is_empty() {
+        log::info!("Loaded {} cameras", scene.cameras.len());
     }
 
-    if scene.prototypes.is_empty() {
+    // The stage keeps no animation curves, so read the keys at shutter
+    // close from the stage itself
+    if let (true, Some(time)) = (options.motion_blur, options.time) {
+        stage.set_time(time + 1.0)?;
+        add_shutter_close_keys(&mut scene, &stage, &meshes, &instancers, &prototype_map)?;
+        stage.set_time(time)?;
+    }
+
+    if scene.prototypes.is_empty() && scene.curves.is_empty() && scene.points.is_empty() {
         return Err(LoadError::NoGeometry);
     }
 
     Ok((scene, stage))
 }
 
+/// Add motion keys to the instances and prototypes of `scene` that moved
+/// between the load time and the current time of `stage`.
+///
+/// `meshes` and `instancers` were read from the stage at the load time,
+/// and instances are in load order: one per mesh, then the instancers'
+/// points.
+fn add_shutter_close_keys(
+    scene: &mut Scene,
+    stage: &UsdStage,
+    meshes: &[UsdMeshData],
+    instancers: &[UsdInstancerData],
+    prototype_map: &HashMap<String, usize>,
+) -> LoadResult<()> {
+    let mut instance = 0;
+    for (open, close) in meshes.iter().zip(stage.meshes()?) {
+        if close.transform != open.transform {
+            scene.instances[instance].motion = vec![Transform::from_matrix(close.transform)];
+        }
+        instance += 1;
+
+        // Deformation of the prototype this mesh created; refined cages
+        // would need refining again
+        let Some(&proto_id) = prototype_map.get(&open.path) else {
+            continue;
+        };
+        let proto = &scene.prototypes[proto_id];
+        if close.vertices == open.vertices
+            || open.subdivision_scheme == SubdivisionScheme::CatmullClark
+            || close.vertices.len() != proto.mesh.positions.len()
+        {
+            continue;
+        }
+        let mesh = (*proto.mesh).clone().with_motion(vec![close.vertices]);
+        let mut proto = (**proto).clone();
+        proto.bounds = mesh.bounds;
+        proto.mesh = Arc::new(mesh);
+        scene.prototypes[proto_id] = Arc::new(proto);
+    }
+
+    // Instancers without resolvable prototypes added no instances
+    for (open, close) in instancers.iter().zip(stage.instancers()?) {
+        if !open
+            .prototype_paths
+            .iter()
+            .any(|path| prototype_map.contains_key(path))
+        {
+            continue;
+        }
+        if close.transforms.len() == open.transforms.len() {
+            let instances = &mut scene.instances[instance..];
+            let transforms = open.transforms.iter().zip(&close.transforms);
+            for (instance, (open, close)) in instances.iter_mut().zip(transforms) {
+                if close != open {
+                    instance.motion = vec![Transform::from_matrix(*close)];
+                }
+            }
+        }
+        instance += open.transforms.len();
+    }
+    Ok(())
+}
+
+/// Key under which identical stage meshes share one prototype.
+///
+/// Besides the geometry, everything the prototype holds for all of its
+/// instances is part of the key: per-face bindings, subdivision scheme,
+/// displacement bound and ray visibility.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct MeshKey {
+    vertex_count: usize,
+    index_count: usize,
+    vertex_hash: u64,
+    face_materials: Option<Vec<MaterialId>>,
+    subdivision_scheme: SubdivisionScheme,
+    displacement_bound: Option<u32>,
+    rays: Visibility,
+}
+
+impl MeshKey {
+    /// Key of `mesh_data` with per-face materials `face_ids`.
+    fn new(mesh_data: &UsdMeshData, face_ids: Option<&[MaterialId]>) -> Self {
+        // Hash of the first few vertices
+        let vertex_hash = if mesh_data.vertices.len() >= 3 {
+            // Hash first 3 vertices (as Vec3)
+            mesh_data
+                .vertices
+                .iter()
+                .take(3)
+                .enumerate()
+                .map(|(i, v)| {
+                    let x = v.x.to_bits() as u64;
+                    let y = v.y.to_bits() as u64;
+                    let z = v.z.to_bits() as u64;
+                    (x ^ y.rotate_left(21) ^ z.rotate_left(42)).wrapping_mul(i as u64 + 1)
+                })
+                .fold(0, |acc, x| acc ^ x)
+        } else {
+            0
+        };
+
+        Self {
+            vertex_count: mesh_data.vertices.len(),
+            index_count: mesh_data.indices.len(),
+            vertex_hash,
+            face_materials: face_ids.map(<[MaterialId]>::to_vec),
+            subdivision_scheme: mesh_data.subdivision_scheme,
+            displacement_bound: mesh_data.displacement_bound.map(f32::to_bits),
+            rays: mesh_data.visibility.rays,
+        }
+    }
+}
+
+/// Build per-triangle material IDs from a mesh's GeomSubset bindings.
+///
+/// Returns `None` if the mesh has no subsets or none of them resolve to a
+/// loaded material. Triangles outside any bound subset get `NO_MATERIAL`.
+fn face_material_ids(
+    mesh_data: &UsdMeshData,
+    material_map: &HashMap<String, usize>,
+) -> Option<Vec<MaterialId>> {
+    let triangle_subsets = mesh_data.triangle_subsets.as_ref()?;
+
+    let subset_ids: Vec<MaterialId> = mesh_data
+        .subset_material_paths
+        .iter()
+        .map(|path| {
+            path.as_ref()
+                .and_then(|p| material_map.get(p))
+                .and_then(|&id| MaterialId::try_from(id).ok())
+                .unwrap_or(NO_MATERIAL)
+        })
+        .collect();
+
+    if subset_ids.iter().all(|&id| id == NO_MATERIAL) {
+        return None;
+    }
+
+    Some(
+        triangle_subsets
+            .iter()
+            .map(|&subset| {
+                usize::try_from(subset)
+                    .ok()
+                    .and_then(|s| subset_ids.get(s).copied())
+                    .unwrap_or(NO_MATERIAL)
+            })
+            .collect(),
+    )
+}
+
+/// Refine a Catmull-Clark cage into a triangle mesh.
+///
+/// Also returns the cage face each refined triangle came from.
+fn subdivide(
+    positions: Vec<Vec3>,
+    uvs: Option<Vec<[f32; 2]>>,
+    face_vertex_counts: &[u32],
+    face_vertex_indices: &[u32],
+    tags: &SubdivisionTags,
+    level: u32,
+) -> (Mesh, Vec<u32>) {
+    let refined = SubdivisionMesh::new(positions, face_vertex_counts, face_vertex_indices)
+        .with_uvs(uvs)
+        .with_tags(tags)
+        .refined(level);
+    (refined.to_mesh(), refined.triangle_origins())
+}
+
+/// Map per-triangle material IDs of the triangulated cage onto refined
+/// triangles.
+///
+/// GeomSubsets select whole faces, so the first triangle of each cage face
+/// carries that face's material.
+fn refined_face_ids(
+    cage_ids: &[MaterialId],
+    face_vertex_counts: &[u32],
+    triangle_origins: &[u32],
+) -> Vec<MaterialId> {
+    let first_triangles: Vec<usize> = face_vertex_counts
+        .iter()
+        .scan(0usize, |next, &count| {
+            let first = *next;
+            *next += count.saturating_sub(2) as usize;
+            Some(first)
+        })
+        .collect();
+
+    triangle_origins
+        .iter()
+        .map(|&face| {
+            first_triangles
+                .get(face as usize)
+                .and_then(|&tri| cage_ids.get(tri).copied())
+                .unwrap_or(NO_MATERIAL)
+        })
+        .collect()
+}
+
 /// Load a USDA file using the pure Rust parser (legacy).
 ///
 /// For new code, prefer `load_usd()` which uses the C++ bridge
@@ -262,10 +617,15 @@ pub fn load_usd_with_stage<P: AsRef<Path>>(path: P) -> LoadResult<(Scene, UsdSta
 /// let scene = load_usda("scene.usda")?;
 /// ```
 pub fn load_usda<P: AsRef<Path>>(path: P) -> LoadResult<Scene> {
+    load_usda_with_options(path, &LoadOptions::default())
+}
+
+/// Load a USDA file with the pure Rust parser and explicit [`LoadOptions`].
+pub fn load_usda_with_options<P: AsRef<Path>>(path: P, options: &LoadOptions) -> LoadResult<Scene> {
     let path = path.as_ref();
     let content = std::fs::read_to_string(path)?;
     let base_dir = path.parent().map(|p| p.to_path_buf());
-    load_usda_from_string(&content, path.to_string_lossy().as_ref(), base_dir)
+    load_usda_string_with_options(&content, path.to_string_lossy().as_ref(), base_dir, options)
 }
 
 /// Load USDA from a string (useful for testing).
@@ -274,36 +634,357 @@ pub fn load_usda_from_string(
     name: &str,
     base_dir: Option<PathBuf>,
 ) -> LoadResult<Scene> {
-    let prims = parse_usda(content)?;
+    load_usda_string_with_options(content, name, base_dir, &LoadOptions::default())
+}
+
+fn load_usda_string_with_options(
+    content: &str,
+    name: &str,
+    base_dir: Option<PathBuf>,
+    options: &LoadOptions,
+) -> LoadResult<Scene> {
+    let mut parser = UsdaParser::new(content);
+    let prims = parser.parse()?;
+
+    let mut builder = SceneBuilder::new(name, base_dir, options.clone());
+    builder.scene.time_codes = parser.time_codes();
 
-    let mut builder = SceneBuilder::new(name, base_dir);
+    // Materials first, so bindings resolve wherever they are defined
+    builder.add_materials(&prims);
 
     for prim in prims {
         builder.process_prim(&prim, Mat4::IDENTITY)?;
     }
 
-    builder.finish()
+    let mut scene = builder.finish()?;
+    if let (true, Some(time)) = (options.motion_blur, options.time) {
+        scene.sample_motion(time);
+    }
+    Ok(scene)
 }
 
+/// A prim: the layer it is defined in (see `SceneBuilder::layer`) and its
+/// path there.
+type PrimKey = (String, String);
+
 /// Internal builder for constructing a Scene from USD prims.
 struct SceneBuilder {
     scene: Scene,
-    /// Map from USD prim path to prototype ID
-    prototype_map: HashMap<String, usize>,
-    /// Base directory for resolving relative references
+    /// Map from USD prim (layer and path) to prototype ID
+    prototype_map: HashMap<PrimKey, usize>,
+    /// Map from USD material (layer and path) to material ID
+    material_map: HashMap<PrimKey, usize>,
+    /// Layer being read: the root's name or a referenced file's path.
+    /// Prim paths are only unique within a layer.
+    layer: String,
+    /// Directory of the layer being read, for resolving relative asset paths
     base_dir: Option<PathBuf>,
     /// Cache of loaded reference files to avoid re-loading
     reference_cache: HashMap<String, Vec<UsdPrim>>,
+    /// Geometry conversion options
+    options: LoadOptions,
+    /// Visibility inherited from the enclosing prims
+    visibility: Visibility,
+    /// World transform samples of the enclosing prims (`None` if static)
+    transform_samples: Option<AnimationCurve<Mat4>>,
+    /// Material bound on the nearest enclosing prim with a binding
+    material_binding: Option<PrimKey>,
 }
 
 impl SceneBuilder {
-    fn new(name: &str, base_dir: Option<PathBuf>) -> Self {
+    fn new(name: &str, base_dir: Option<PathBuf>, options: LoadOptions) -> Self {
         Self {
             scene: Scene::new(name),
             prototype_map: HashMap::new(),
+            material_map: HashMap::new(),
+            layer: name.to_string(),
             base_dir,
             reference_cache: HashMap::new(),
+            options,
+            visibility: Visibility::ALL,
+            transform_samples: None,
+            material_binding: None,
+        }
+    }
+
+    /// Add the materials defined anywhere under `prims` in the current
+    /// layer. Materials already added (from an earlier reference to the same
+    /// file) are kept.
+    fn add_materials(&mut self, prims: &[UsdPrim]) {
+        for prim in prims {
+            match prim {
+                UsdPrim::Material(usd_material) => {
+                    let key = self.prim_key(&usd_material.path);
+                    if self.material_map.contains_key(&key) {
+                        continue;
+                    }
+                    let material = self.convert_material(usd_material);
+                    let id = self.scene.add_material(material);
+                    self.material_map.insert(key, id);
+                }
+                UsdPrim::Xform(xform) => self.add_materials(&xform.children),
+                UsdPrim::PointInstancer(instancer) => self.add_materials(&instancer.children),
+                UsdPrim::Reference(reference) => self.add_materials(&reference.children),
+                _ => {}
+            }
+        }
+    }
+
+    /// Convert a UsdPreviewSurface material, reading the same inputs as the
+    /// C++ bridge. Other surface shaders give a default material.
+    fn convert_material(&self, usd_material: &UsdMaterial) -> Material {
+        let mut material = Material {
+            name: usd_material.path.clone(),
+            ..Default::default()
+        };
+
+        let Some(surface) = usd_material
+            .surface_shader()
+            .filter(|shader| shader.id == "UsdPreviewSurface")
+        else {
+            log::warn!(
+                "Material {} has no UsdPreviewSurface, using defaults",
+                usd_material.path
+            );
+            return material;
+        };
+
+        // File, channel and shader of the UsdUVTexture connected to an input
+        let texture = |name: &str| -> Option<(String, usize, &UsdShader)> {
+            let connection = surface.input(name)?.connection.as_ref()?;
+            let shader = usd_material
+                .shader(&connection.prim_path)
+                .filter(|shader| shader.id == "UsdUVTexture")?;
+            let file = shader.value("file")?.as_str()?;
+            let file = match &self.base_dir {
+                Some(base_dir) => base_dir.join(file).to_string_lossy().into_owned(),
+                None => file.to_string(),
+            };
+            let channel = match connection.output.as_str() {
+                "g" => 1,
+                "b" => 2,
+                "a" => 3,
+                _ => 0,
+            };
+            Some((file, channel, shader))
+        };
+        let float = |name: &str| surface.value(name).and_then(ShadingValue::as_float);
+        let color = |name: &str| surface.value(name).and_then(ShadingValue::as_vec3);
+
+        if let Some(diffuse) = color("diffuseColor") {
+            material.diffuse_color = diffuse;
         }
+        if let Some(metallic) = float("metallic") {
+            material.metallic = metallic;
+        }
+        if let Some(roughness) = float("roughness") {
+            material.roughness = roughness;
+        }
+        // Specular (ior in UsdPreviewSurface, but we use specular for simplicity)
+        if let Some(specular) = float("specularColor") {
+            material.specular = specular;
+        }
+        if let Some(opacity) = float("opacity") {
+            material.opacity = opacity;
+        }
+        if let Some(threshold) = float("opacityThreshold") {
+            material.opacity_threshold = threshold;
+        }
+        if let Some(emissive) = color("emissiveColor") {
+            material.emissive_color = emissive;
+        }
+
+        material.diffuse_texture = texture("diffuseColor").map(|(file, ..)| file);
+        material.metallic_texture = texture("metallic").map(|(file, ..)| file);
+        material.roughness_texture = texture("roughness").map(|(file, ..)| file);
+        material.emissive_texture = texture("emissiveColor").map(|(file, ..)| file);
+        material.normal_texture = texture("normal").map(|(file, ..)| file);
+        if let Some((file, channel, _)) = texture("opacity") {
+            material.opacity_texture = Some(file);
+            material.opacity_texture_channel = channel;
+        }
+        if let Some((file, channel, reader)) = texture("displacement") {
+            material.displacement_texture = Some(file);
+            material.displacement_texture_channel = channel;
+
+            // A vector input offsets by the texture's RGB; its scale and
+            // bias come from the red channel
+            material.vector_displacement = surface
+                .input("displacement")
+                .is_some_and(|input| is_vector_type(&input.type_name));
+            let component = if material.vector_displacement {
+                0
+            } else {
+                channel
+            };
+            let reader = |name: &str| reader.value(name)?.component(component);
+            material.displacement_scale = reader("scale").unwrap_or(1.0);
+            material.displacement_bias = reader("bias").unwrap_or(0.0);
+        }
+
+        material
+    }
+
+    /// Key of the prim at `path` in the current layer.
+    fn prim_key(&self, path: &str) -> PrimKey {
+        (self.layer.clone(), path.to_string())
+    }
+
+    /// Material bound to a prim: its own `binding` (in the current layer),
+    /// else the inherited one.
+    fn bound_material(&self, binding: Option<&String>) -> Option<Arc<Material>> {
+        let key = match binding {
+            Some(path) => self.prim_key(path),
+            None => self.material_binding.clone()?,
+        };
+        match self.material_map.get(&key) {
+            Some(&id) => Some(self.scene.materials[id].clone()),
+            None => {
+                log::warn!("Could not resolve material binding: {} in {}", key.1, key.0);
+                None
+            }
+        }
+    }
+
+    /// Make `binding` (if authored) the material inherited by the
+    /// descendants. Returns the previously inherited one to restore.
+    fn inherit_material(&mut self, binding: Option<&String>) -> Option<PrimKey> {
+        match binding {
+            Some(path) => {
+                let key = self.prim_key(path);
+                self.material_binding.replace(key)
+            }
+            None => self.material_binding.clone(),
+        }
+    }
+
+    /// Add a prototype visible to `visibility` and return its ID.
+    ///
+    /// It gets the material bound with `binding` or inherited.
+    fn add_prototype(
+        &mut self,
+        mesh: Arc<Mesh>,
+        name: String,
+        visibility: Visibility,
+        binding: Option<&String>,
+    ) -> usize {
+        let id = self.scene.prototypes.len();
+        let mut prototype = Prototype::new(id, name, mesh).with_visibility(visibility);
+        if let Some(material) = self.bound_material(binding) {
+            prototype = prototype.with_material(material);
+        }
+        self.scene.prototypes.push(Arc::new(prototype));
+        id
+    }
+
+    /// Give a mesh prototype per-face materials from the mesh's
+    /// `materialBind` GeomSubsets. `triangle_faces` is the face each
+    /// prototype triangle came from.
+    fn add_face_materials(&mut self, proto_id: usize, usd_mesh: &UsdMesh, triangle_faces: &[u32]) {
+        let mut face_ids = vec![NO_MATERIAL; usd_mesh.face_vertex_counts.len()];
+        let mut bound = false;
+        for subset in &usd_mesh.subsets {
+            if subset.family_name != "materialBind" {
+                continue;
+            }
+            let Some(path) = &subset.material_binding else {
+                continue;
+            };
+            let Some(id) = self
+                .material_map
+                .get(&self.prim_key(path))
+                .and_then(|&id| MaterialId::try_from(id).ok())
+            else {
+                log::warn!("Could not resolve material binding: {}", path);
+                continue;
+            };
+            for &face in &subset.indices {
+                if let Some(face_id) = face_ids.get_mut(face as usize) {
+                    *face_id = id;
+                    bound = true;
+                }
+            }
+        }
+        if !bound {
+            return;
+        }
+
+        let ids = triangle_faces
+            .iter()
+            .map(|&face| face_ids.get(face as usize).copied().unwrap_or(NO_MATERIAL))
+            .collect();
+        let proto = (*self.scene.prototypes[proto_id])
+            .clone()
+            .with_face_materials(ids);
+        self.scene.prototypes[proto_id] = Arc::new(proto);
+    }
+
+    /// Add an instance, hidden from the rays its ancestors are hidden from.
+    ///
+    /// `samples` are its world transform samples if it is animated.
+    fn add_instance(
+        &mut self,
+        proto_id: usize,
+        transform: Mat4,
+        samples: Option<AnimationCurve<Mat4>>,
+        visibility: Visibility,
+    ) {
+        if let Some(samples) = samples {
+            self.scene
+                .animation
+                .instances
+                .insert(self.scene.instances.len(), samples);
+        }
+        self.scene.instances.push(
+            Instance::new(proto_id, Transform::from_matrix(transform))
+                .with_visibility(self.visibility & visibility),
+        );
+    }
+
+    /// Value of an attribute at the load time: its samples at
+    /// [`LoadOptions::time`] if it is animated, else the authored `value`.
+    fn at_load_time<T: Interpolate>(&self, value: &T, samples: Option<&AnimationCurve<T>>) -> T {
+        match (samples, self.options.time) {
+            (Some(samples), Some(time)) => samples.evaluate(time),
+            _ => value.clone(),
+        }
+    }
+
+    /// World transform of a prim under `parent_transform` at the load time,
+    /// with its world transform samples if it or an ancestor is animated.
+    fn world_transform(
+        &self,
+        parent_transform: Mat4,
+        local: Mat4,
+        local_samples: Option<&AnimationCurve<Mat4>>,
+    ) -> (Mat4, Option<AnimationCurve<Mat4>>) {
+        let local = self.at_load_time(&local, local_samples);
+        let samples = product_samples(
+            parent_transform,
+            self.transform_samples.as_ref(),
+            local,
+            local_samples,
+        );
+        (parent_transform * local, samples)
+    }
+
+    /// Keep the point samples of a deforming mesh prototype.
+    fn add_points_samples(&mut self, proto_id: usize, usd_mesh: &UsdMesh) {
+        let Some(samples) = &usd_mesh.points_samples else {
+            return;
+        };
+        if usd_mesh.subdivision_scheme == SubdivisionScheme::CatmullClark {
+            // The animation would need the cage refined at every time
+            log::warn!(
+                "Animated subdivision mesh {} is only refined at the load time",
+                usd_mesh.path
+            );
+            return;
+        }
+        self.scene
+            .animation
+            .prototypes
+            .insert(proto_id, samples.clone());
     }
 
     /// Process a USD prim recursively.
@@ -314,81 +995,269 @@ impl SceneBuilder {
             UsdPrim::PointInstancer(instancer) => {
                 self.process_point_instancer(instancer, parent_transform)
             }
+            UsdPrim::Shape(shape) => self.process_shape(shape, parent_transform),
+            UsdPrim::BasisCurves(curves) => self.process_basis_curves(curves, parent_transform),
+            UsdPrim::Points(points) => self.process_points(points, parent_transform),
+            UsdPrim::Light(light) => self.process_light(light, parent_transform),
+            UsdPrim::Camera(camera) => self.process_camera(camera, parent_transform),
             UsdPrim::Reference(reference) => self.process_reference(reference, parent_transform),
+            // Materials are added up front; shaders and subsets belong to
+            // their parent prims
+            UsdPrim::Material(_) | UsdPrim::Shader(_) | UsdPrim::GeomSubset(_) => Ok(()),
             UsdPrim::Unknown(_) => Ok(()), // Skip unknown prims
         }
     }
 
     /// Process an Xform (transform) prim.
     fn process_xform(&mut self, xform: &UsdXform, parent_transform: Mat4) -> LoadResult<()> {
-        let world_transform = parent_transform * xform.transform;
+        let (world_transform, samples) = self.world_transform(
+            parent_transform,
+            xform.transform,
+            xform.transform_samples.as_ref(),
+        );
 
-        // Process children with accumulated transform
-        for child in &xform.children {
-            self.process_prim(child, world_transform)?;
-        }
+        // Process children with accumulated transform, visibility and material
+        let inherited = self.visibility;
+        self.visibility = inherited & xform.visibility.mask();
+        let inherited_samples = std::mem::replace(&mut self.transform_samples, samples);
+        let inherited_material = self.inherit_material(xform.material_binding.as_ref());
+        let result = xform
+            .children
+            .iter()
+            .try_for_each(|child| self.process_prim(child, world_transform));
+        self.visibility = inherited;
+        self.transform_samples = inherited_samples;
+        self.material_binding = inherited_material;
 
-        Ok(())
+        result
     }
 
     /// Process a Mesh prim.
     fn process_mesh(&mut self, usd_mesh: &UsdMesh, parent_transform: Mat4) -> LoadResult<()> {
-        let world_transform = parent_transform * usd_mesh.transform;
-
-        // Convert USD mesh to BIF mesh
-        let mut mesh = self.convert_mesh(usd_mesh)?;
-
-        // Ensure normals exist - compute if not provided in USD
-        mesh.ensure_normals();
-
-        let mesh = Arc::new(mesh);
+        let (world_transform, samples) = self.world_transform(
+            parent_transform,
+            usd_mesh.transform,
+            usd_mesh.transform_samples.as_ref(),
+        );
 
         // Check if we already have this prototype
-        let proto_id = if let Some(&id) = self.prototype_map.get(&usd_mesh.path) {
+        let proto_id = if let Some(&id) = self.prototype_map.get(&self.prim_key(&usd_mesh.path)) {
             id
         } else {
-            let id = self.scene.add_prototype(mesh, usd_mesh.name.clone());
-            self.prototype_map.insert(usd_mesh.path.clone(), id);
+            // Convert USD mesh to BIF mesh
+            let (mut mesh, triangle_faces) = self.convert_mesh(usd_mesh)?;
+
+            // Ensure normals exist - compute if not provided in USD
+            mesh.ensure_normals();
+
+            let id = self.add_prototype(
+                Arc::new(mesh),
+                usd_mesh.name.clone(),
+                usd_mesh.visibility.rays,
+                usd_mesh.material_binding.as_ref(),
+            );
+            self.prototype_map.insert(self.prim_key(&usd_mesh.path), id);
+            self.add_face_materials(id, usd_mesh, &triangle_faces);
+            self.add_points_samples(id, usd_mesh);
             id
         };
 
         // Add an instance with the accumulated transform
-        self.scene
-            .add_instance(proto_id, Transform::from_matrix(world_transform));
+        self.add_instance(
+            proto_id,
+            world_transform,
+            samples,
+            usd_mesh.visibility.mask(),
+        );
 
         Ok(())
     }
 
+    /// Process an intrinsic shape prim as a tessellated mesh prototype.
+    fn process_shape(&mut self, shape: &UsdShape, parent_transform: Mat4) -> LoadResult<()> {
+        let (world_transform, samples) = self.world_transform(
+            parent_transform,
+            shape.transform,
+            shape.transform_samples.as_ref(),
+        );
+
+        let proto_id = if let Some(&id) = self.prototype_map.get(&self.prim_key(&shape.path)) {
+            id
+        } else {
+            let mesh = Arc::new(shape.to_mesh());
+            let id = self.add_prototype(
+                mesh,
+                shape.name.clone(),
+                shape.visibility.rays,
+                shape.material_binding.as_ref(),
+            );
+            self.prototype_map.insert(self.prim_key(&shape.path), id);
+            id
+        };
+
+        self.add_instance(proto_id, world_transform, samples, shape.visibility.mask());
+
+        Ok(())
+    }
+
+    /// Process a BasisCurves prim as a curve set.
+    fn process_basis_curves(
+        &mut self,
+        curves: &UsdBasisCurves,
+        parent_transform: Mat4,
+    ) -> LoadResult<()> {
+        let (world_transform, samples) = self.world_transform(
+            parent_transform,
+            curves.transform,
+            curves.transform_samples.as_ref(),
+        );
+        let mut set = CurveSet::new(
+            curves.name.clone(),
+            Arc::new(curves.to_curves()),
+            world_transform,
+        );
+        if let Some(material) = self.bound_material(curves.material_binding.as_ref()) {
+            set = set.with_material(material);
+        }
+        let index = self.scene.add_curves(set);
+        if let Some(samples) = samples {
+            self.scene.animation.curves.insert(index, samples);
+        }
+        Ok(())
+    }
+
+    /// Process a Points prim as a point set.
+    fn process_points(&mut self, points: &UsdPoints, parent_transform: Mat4) -> LoadResult<()> {
+        let (world_transform, samples) = self.world_transform(
+            parent_transform,
+            points.transform,
+            points.transform_samples.as_ref(),
+        );
+        let mut set = PointSet::new(
+            points.name.clone(),
+            Arc::new(points.to_points()),
+            world_transform,
+        );
+        if let Some(material) = self.bound_material(points.material_binding.as_ref()) {
+            set = set.with_material(material);
+        }
+        let index = self.scene.add_points(set);
+        if let Some(samples) = samples {
+            self.scene.animation.points.insert(index, samples);
+        }
+        Ok(())
+    }
+
+    /// Process a UsdLux light prim; invisible lights are switched off.
+    fn process_light(&mut self, usd_light: &UsdLight, parent_transform: Mat4) -> LoadResult<()> {
+        if self.visibility & usd_light.visibility.mask() == Visibility::NONE {
+            return Ok(());
+        }
+
+        let (world_transform, samples) = self.world_transform(
+            parent_transform,
+            usd_light.light.transform,
+            usd_light.transform_samples.as_ref(),
+        );
+        let mut light = usd_light.light.clone().with_transform(world_transform);
+
+        // Resolve asset paths relative to the base directory
+        let resolve = |path: &mut String| {
+            if let Some(base_dir) = &self.base_dir {
+                *path = base_dir.join(&*path).to_string_lossy().into_owned();
+            }
+        };
+        if let Some(texture) = &mut light.texture {
+            resolve(texture);
+        }
+        if let Some(ies_file) = light.shaping.as_mut().and_then(|s| s.ies_file.as_mut()) {
+            resolve(ies_file);
+        }
+
+        let index = self.scene.add_light(light);
+        if let Some(samples) = samples {
+            self.scene.animation.lights.insert(index, samples);
+        }
+        Ok(())
+    }
+
+    /// Process a UsdGeom camera prim.
+    fn process_camera(&mut self, usd_camera: &UsdCamera, parent_transform: Mat4) -> LoadResult<()> {
+        let (world_transform, samples) = self.world_transform(
+            parent_transform,
+            usd_camera.camera.transform,
+            usd_camera.transform_samples.as_ref(),
+        );
+        let camera = usd_camera.camera.clone().with_transform(world_transform);
+
+        let index = self.scene.add_camera(camera);
+        if let Some(samples) = samples {
+            self.scene.animation.cameras.insert(index, samples);
+        }
+        Ok(())
+    }
+
     /// Process a PointInstancer prim.
     fn process_point_instancer(
         &mut self,
         instancer: &UsdPointInstancer,
         parent_transform: Mat4,
     ) -> LoadResult<()> {
-        let world_transform = parent_transform * instancer.transform;
+        let (world_transform, world_samples) = self.world_transform(
+            parent_transform,
+            instancer.transform,
+            instancer.transform_samples.as_ref(),
+        );
 
-        // First, collect inline prototype definitions
+        // First, collect inline prototype definitions (which inherit the
+        // instancer's material)
         let mut inline_prototypes: Vec<usize> = Vec::new();
+        let inherited_material = self.inherit_material(instancer.material_binding.as_ref());
 
         for child in &instancer.children {
-            if let UsdPrim::Mesh(mesh) = child {
-                let mut bif_mesh = self.convert_mesh(mesh)?;
-                bif_mesh.ensure_normals();
-
-                let name = mesh.name.clone();
-                let mesh_arc = Arc::new(bif_mesh);
-                let id = self.scene.add_prototype(mesh_arc, name.clone());
-                self.prototype_map.insert(mesh.path.clone(), id);
-                inline_prototypes.push(id);
+            // Prototypes are only drawn through the instancer, so their
+            // `visibility` is ignored; ray visibility primvars still apply
+            let (bif_mesh, name, path, visibility, binding, usd_mesh) = match child {
+                UsdPrim::Mesh(mesh) => {
+                    let (mut bif_mesh, triangle_faces) = self.convert_mesh(mesh)?;
+                    bif_mesh.ensure_normals();
+                    (
+                        bif_mesh,
+                        &mesh.name,
+                        &mesh.path,
+                        mesh.visibility.rays,
+                        &mesh.material_binding,
+                        Some((mesh, triangle_faces)),
+                    )
+                }
+                UsdPrim::Shape(shape) => (
+                    shape.to_mesh(),
+                    &shape.name,
+                    &shape.path,
+                    shape.visibility.rays,
+                    &shape.material_binding,
+                    None,
+                ),
+                _ => continue,
+            };
+
+            let mesh_arc = Arc::new(bif_mesh);
+            let id = self.add_prototype(mesh_arc, name.clone(), visibility, binding.as_ref());
+            self.prototype_map.insert(self.prim_key(path), id);
+            if let Some((usd_mesh, triangle_faces)) = usd_mesh {
+                self.add_face_materials(id, usd_mesh, &triangle_faces);
+                self.add_points_samples(id, usd_mesh);
             }
+            inline_prototypes.push(id);
         }
+        self.material_binding = inherited_material;
 
         // If no inline prototypes, try to resolve prototype paths
         // For now, we only support inline prototypes
         if inline_prototypes.is_empty() && !instancer.prototypes.is_empty() {
             // Try to find prototypes by path
             for proto_path in &instancer.prototypes {
-                if let Some(&id) = self.prototype_map.get(proto_path) {
+                if let Some(&id) = self.prototype_map.get(&self.prim_key(proto_path)) {
                     inline_prototypes.push(id);
                 } else {
                     log::warn!("Could not resolve prototype path: {}", proto_path);
@@ -396,8 +1265,16 @@ impl SceneBuilder {
             }
         }
 
+        // Instance transforms at the load time and at every sample time
+        let matrices = instancer.instance_matrices_at(self.options.time);
+        let frames: Vec<(f64, Vec<Mat4>)> = instancer
+            .sample_times()
+            .into_iter()
+            .map(|time| (time, instancer.instance_matrices_at(Some(time))))
+            .collect();
+
         // Create instances
-        for i in 0..instancer.positions.len() {
+        for (i, &instance_matrix) in matrices.iter().enumerate() {
             let proto_idx = instancer.proto_indices.get(i).copied().unwrap_or(0) as usize;
 
             // Get the prototype ID (from inline prototypes or fallback to first)
@@ -408,11 +1285,21 @@ impl SceneBuilder {
                 .unwrap_or(0);
 
             // Build instance transform
-            let instance_matrix = instancer.instance_matrix(i);
             let final_matrix = world_transform * instance_matrix;
+            let instance_samples = AnimationCurve::new(
+                frames
+                    .iter()
+                    .filter_map(|(time, frame)| Some((*time, *frame.get(i)?)))
+                    .collect(),
+            );
+            let samples = product_samples(
+                world_transform,
+                world_samples.as_ref(),
+                instance_matrix,
+                instance_samples.as_ref(),
+            );
 
-            self.scene
-                .add_instance(proto_id, Transform::from_matrix(final_matrix));
+            self.add_instance(proto_id, final_matrix, samples, instancer.visibility.mask());
         }
 
         Ok(())
@@ -424,9 +1311,31 @@ impl SceneBuilder {
         reference: &UsdReference,
         parent_transform: Mat4,
     ) -> LoadResult<()> {
-        let world_transform = parent_transform * reference.transform;
+        let (world_transform, samples) = self.world_transform(
+            parent_transform,
+            reference.transform,
+            reference.transform_samples.as_ref(),
+        );
+        let inherited_samples = std::mem::replace(&mut self.transform_samples, samples);
+        let inherited_material = self.inherit_material(reference.material_binding.as_ref());
+        let result = self.process_reference_content(reference, world_transform);
+        self.transform_samples = inherited_samples;
+        self.material_binding = inherited_material;
+
+        result
+    }
 
-        // Resolve the asset path relative to the base directory
+    /// Load the prims of a reference under its world transform.
+    ///
+    /// The referenced file becomes the current layer while its prims are
+    /// read, so its material paths and assets resolve within it; the
+    /// reference's own child overrides belong to the referencing layer.
+    fn process_reference_content(
+        &mut self,
+        reference: &UsdReference,
+        world_transform: Mat4,
+    ) -> LoadResult<()> {
+        // Resolve the asset path relative to the referencing layer
         let asset_path = if let Some(base_dir) = &self.base_dir {
             base_dir.join(&reference.asset_path)
         } else {
@@ -447,31 +1356,52 @@ impl SceneBuilder {
             })?;
 
             let prims = crate::usd::parser::parse_usda(&content)?;
-            self.reference_cache.insert(cache_key, prims.clone());
+            self.reference_cache
+                .insert(cache_key.clone(), prims.clone());
             prims
         };
 
-        // Find the target prim (if specified) or process all root prims
+        let layer = std::mem::replace(&mut self.layer, cache_key);
+        let base_dir = std::mem::replace(
+            &mut self.base_dir,
+            asset_path.parent().map(Path::to_path_buf),
+        );
+        let result = self.process_layer_prims(reference, &prims, world_transform);
+        self.layer = layer;
+        self.base_dir = base_dir;
+        result?;
+
+        // Process any child overrides
+        for child in &reference.children {
+            self.process_prim(child, world_transform)?;
+        }
+
+        Ok(())
+    }
+
+    /// Process the prims of a referenced layer: its target prim, if the
+    /// reference names one, or all of its root prims.
+    fn process_layer_prims(
+        &mut self,
+        reference: &UsdReference,
+        prims: &[UsdPrim],
+        world_transform: Mat4,
+    ) -> LoadResult<()> {
+        self.add_materials(prims);
+
         if let Some(target_path) = &reference.target_prim_path {
             // Find the specific prim by path
-            for prim in &prims {
+            for prim in prims {
                 if self.prim_matches_path(prim, target_path) {
                     self.process_prim(prim, world_transform)?;
                     break;
                 }
             }
         } else {
-            // Process all root prims from the referenced file
-            for prim in &prims {
+            for prim in prims {
                 self.process_prim(prim, world_transform)?;
             }
         }
-
-        // Process any child overrides
-        for child in &reference.children {
-            self.process_prim(child, world_transform)?;
-        }
-
         Ok(())
     }
 
@@ -481,6 +1411,14 @@ impl SceneBuilder {
             UsdPrim::Xform(x) => &x.path,
             UsdPrim::Mesh(m) => &m.path,
             UsdPrim::PointInstancer(p) => &p.path,
+            UsdPrim::Shape(s) => &s.path,
+            UsdPrim::BasisCurves(c) => &c.path,
+            UsdPrim::Points(p) => &p.path,
+            UsdPrim::Light(l) => &l.path,
+            UsdPrim::Camera(c) => &c.path,
+            UsdPrim::Material(m) => &m.path,
+            UsdPrim::Shader(s) => &s.path,
+            UsdPrim::GeomSubset(s) => &s.path,
             UsdPrim::Reference(r) => &r.path,
             UsdPrim::Unknown(_) => return false,
         };
@@ -490,19 +1428,63 @@ impl SceneBuilder {
     }
 
     /// Convert a USD mesh to a BIF mesh.
-    fn convert_mesh(&self, usd_mesh: &UsdMesh) -> LoadResult<Mesh> {
+    ///
+    /// Also returns the face each triangle came from. Animated points are
+    /// taken at the load time.
+    fn convert_mesh(&self, usd_mesh: &UsdMesh) -> LoadResult<(Mesh, Vec<u32>)> {
+        let points = self.at_load_time(&usd_mesh.points, usd_mesh.points_samples.as_ref());
+
+        if usd_mesh.subdivision_scheme == SubdivisionScheme::CatmullClark {
+            let counts: Vec<u32> = usd_mesh
+                .face_vertex_counts
+                .iter()
+                .map(|&c| c.max(0) as u32)
+                .collect();
+            let mut indices: Vec<u32> = usd_mesh
+                .face_vertex_indices
+                .iter()
+                .map(|&i| i as u32)
+                .collect();
+
+            // Reverse each face so the refined surface has CCW winding
+            if usd_mesh.left_handed {
+                let mut offset = 0usize;
+                for &count in &counts {
+                    if let Some(face) = indices.get_mut(offset..offset + count as usize) {
+                        face.reverse();
+                    }
+                    offset += count as usize;
+                }
+            }
+
+            return Ok(subdivide(
+                points,
+                None,
+                &counts,
+                &indices,
+                &usd_mesh.subdivision_tags,
+                self.options.subdivision_level,
+            ));
+        }
+
         // Triangulate the mesh
         let indices = usd_mesh.triangulate();
 
         // Convert normals if present
         let normals = usd_mesh.normals.clone();
 
-        Ok(Mesh::new(usd_mesh.points.clone(), indices, normals))
+        Ok((
+            Mesh::new(points, indices, normals),
+            usd_mesh.triangle_faces(),
+        ))
     }
 
     /// Finish building and return the Scene.
     fn finish(self) -> LoadResult<Scene> {
-        if self.scene.prototypes.is_empty() {
+        if self.scene.prototypes.is_empty()
+            && self.scene.curves.is_empty()
+            && self.scene.points.is_empty()
+        {
             return Err(LoadError::NoGeometry);
         }
 
@@ -510,9 +1492,32 @@ impl SceneBuilder {
     }
 }
 
+/// Check if a declared USD value type has three components (`vector3f`,
+/// `float3`, `color3f`, ...).
+fn is_vector_type(type_name: &str) -> bool {
+    type_name.trim_end_matches(['f', 'd', 'h']).ends_with('3')
+}
+
+/// Samples of `parent * local` when either of them is animated.
+fn product_samples(
+    parent: Mat4,
+    parent_samples: Option<&AnimationCurve<Mat4>>,
+    local: Mat4,
+    local_samples: Option<&AnimationCurve<Mat4>>,
+) -> Option<AnimationCurve<Mat4>> {
+    match (parent_samples, local_samples) {
+        (None, None) => None,
+        (Some(parent), None) => Some(parent.map(|matrix| *matrix * local)),
+        (None, Some(local)) => Some(local.map(|matrix| parent * *matrix)),
+        (Some(parent), Some(local)) => Some(parent.combine(local, |a, b| *a * *b)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::light::LightKind;
+    use crate::usd::types::UsdVisibility;
 
     #[test]
     fn test_load_simple_mesh() {
@@ -600,6 +1605,689 @@ def Xform "World" {
         assert!((origin.x - 10.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_load_shapes() {
+        let usda = r#"
+def Xform "World" {
+    double3 xformOp:translate = (0, 5, 0)
+
+    def Sphere "Ball" {
+        double radius = 2
+    }
+    def PointInstancer "Posts" {
+        int[] protoIndices = [0, 0]
+        point3f[] positions = [(0, 0, 0), (4, 0, 0)]
+
+        def Cone "Post" {
+            uniform token axis = "Y"
+        }
+    }
+}
+"#;
+
+        let scene = load_usda_from_string(usda, "test", None).unwrap();
+
+        assert_eq!(scene.prototype_count(), 2);
+        assert_eq!(scene.instance_count(), 3);
+        assert!(scene.prototypes.iter().all(|p| p.mesh.uvs.is_some()));
+
+        let ball = &scene.prototypes[0].mesh;
+        assert!((ball.bounds.max_point().x - 2.0).abs() < 0.01);
+        let origin = scene.instances[0]
+            .model_matrix()
+            .transform_point3(bif_math::Vec3::ZERO);
+        assert!((origin.y - 5.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_load_visibility() {
+        let usda = r#"
+def Xform "World" {
+    def Mesh "Blocker" {
+        bool primvars:bif:visibility:camera = 0
+        point3f[] points = [(0, 0, 0), (1, 0, 0), (0.5, 1, 0)]
+        int[] faceVertexCounts = [3]
+        int[] faceVertexIndices = [0, 1, 2]
+    }
+    def Xform "Guides" {
+        uniform token purpose = "guide"
+
+        def Sphere "Marker" {
+        }
+    }
+    def PointInstancer "Lights" {
+        int[] protoIndices = [0, 0]
+        point3f[] positions = [(0, 0, 0), (2, 0, 0)]
+        bool primvars:bif:visibility:specular = false
+
+        def Cube "Panel" {
+            token visibility = "invisible"
+        }
+    }
+}
+"#;
+
+        let scene = load_usda_from_string(usda, "test", None).unwrap();
+        assert_eq!(scene.instance_count(), 4);
+
+        // Hidden from the camera, still casts shadows
+        let blocker = scene.resolve_visibility(0);
+        assert!(!blocker.intersects(Visibility::CAMERA));
+        assert!(blocker.contains(Visibility::SHADOW | Visibility::DIFFUSE));
+
+        // Guide purpose hides the subtree from every ray
+        assert_eq!(scene.resolve_visibility(1), Visibility::NONE);
+
+        // Instancer primvars apply to its instances; the inline prototype's
+        // own visibility doesn't hide them
+        for instance in 2..4 {
+            let mask = scene.resolve_visibility(instance);
+            assert!(mask.contains(Visibility::CAMERA));
+            assert!(!mask.intersects(Visibility::SPECULAR));
+        }
+    }
+
+    #[test]
+    fn test_load_curves_only() {
+        let usda = r#"
+def Xform "Groom" {
+    double3 xformOp:translate = (0, 2, 0)
+    uniform token[] xformOpOrder = ["xformOp:translate"]
+
+    def BasisCurves "Strands" {
+        uniform token type = "cubic"
+        uniform token basis = "bspline"
+        int[] curveVertexCounts = [4]
+        point3f[] points = [(0, 0, 0), (0, 1, 0), (0, 2, 0), (0, 3, 0)]
+        float[] widths = [0.1, 0.1, 0.05, 0.01]
+    }
+}
+"#;
+
+        let scene = load_usda_from_string(usda, "test", None).unwrap();
+
+        assert_eq!(scene.prototype_count(), 0);
+        assert_eq!(scene.curves.len(), 1);
+        assert_eq!(scene.total_curve_count(), 1);
+        let bounds = scene.world_bounds();
+        assert!((bounds.y.max - 5.005).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_load_points_only() {
+        let usda = r#"
+def Points "Particles" {
+    point3f[] points = [(0, 0, 0), (0, 4, 0)]
+    float[] widths = [0.5]
+    double3 xformOp:translate = (1, 0, 0)
+    uniform token[] xformOpOrder = ["xformOp:translate"]
+}
+"#;
+
+        let scene = load_usda_from_string(usda, "test", None).unwrap();
+
+        assert_eq!(scene.prototype_count(), 0);
+        assert_eq!(scene.total_point_count(), 2);
+        let bounds = scene.world_bounds();
+        assert!((bounds.y.max - 4.25).abs() < 0.001);
+        assert!((bounds.x.min - 0.75).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_load_subdivision_mesh() {
+        let usda = r#"
+def Mesh "Cube" {
+    point3f[] points = [(-1, -1, 1), (1, -1, 1), (-1, 1, 1), (1, 1, 1), (-1, 1, -1), (1, 1, -1), (-1, -1, -1), (1, -1, -1)]
+    int[] faceVertexCounts = [4, 4, 4, 4, 4, 4]
+    int[] faceVertexIndices = [0, 1, 3, 2, 2, 3, 5, 4, 4, 5, 7, 6, 6, 7, 1, 0, 1, 7, 5, 3, 6, 0, 2, 4]
+    uniform token subdivisionScheme = "catmullClark"
+}
+"#;
+
+        // Default level: 6 quads -> 96 quads -> 192 triangles, pulled inside the cage
+        let scene = load_usda_from_string(usda, "test", None).unwrap();
+        let mesh = &scene.prototypes[0].mesh;
+        assert_eq!(mesh.triangle_count(), 192);
+        assert!(mesh.bounds.max_point().x < 0.99);
+        assert!(mesh.normals.is_some());
+
+        // Level 0 keeps the cage
+        let options = LoadOptions {
+            subdivision_level: 0,
+            ..Default::default()
+        };
+        let scene = load_usda_string_with_options(usda, "test", None, &options).unwrap();
+        let mesh = &scene.prototypes[0].mesh;
+        assert_eq!(mesh.triangle_count(), 12);
+        assert!((mesh.bounds.max_point().x - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_refined_face_ids() {
+        // A quad, a dropped line face and a pentagon: cage triangles 0..1 and 2..4
+        let cage_ids = [5, 5, 7, 7, 7];
+        let counts = [4, 2, 5];
+        let origins = [0, 0, 0, 0, 2, 2, 2];
+
+        assert_eq!(
+            refined_face_ids(&cage_ids, &counts, &origins),
+            vec![5, 5, 5, 5, 7, 7, 7]
+        );
+    }
+
+    #[test]
+    fn test_load_lights() {
+        let usda = r#"#usda 1.0
+def Xform "World" {
+    def Mesh "Floor" {
+        point3f[] points = [(0, 0, 0), (1, 0, 0), (0, 0, 1)]
+        int[] faceVertexCounts = [3]
+        int[] faceVertexIndices = [0, 1, 2]
+    }
+
+    def Xform "Rig" {
+        double3 xformOp:translate.timeSamples = { 0: (0, 2, 0), 10: (0, 4, 0) }
+
+        def DistantLight "Sun" {
+            float inputs:angle = 1
+            float inputs:intensity = 3
+        }
+    }
+
+    def DomeLight "Sky" {
+        asset inputs:texture:file = @sky.exr@
+    }
+
+    def DiskLight "Off" {
+        token visibility = "invisible"
+    }
+}
+"#;
+
+        let mut scene =
+            load_usda_string_with_options(usda, "test", None, &LoadOptions::default()).unwrap();
+        assert_eq!(scene.lights.len(), 2);
+
+        let sun = &scene.lights[0];
+        assert_eq!(sun.name, "Sun");
+        assert_eq!(sun.kind, LightKind::Distant { angle: 1.0 });
+        assert_eq!(sun.radiance(), Vec3::splat(3.0));
+        assert_eq!(sun.position(), Vec3::new(0.0, 2.0, 0.0));
+        assert_eq!(scene.lights[1].texture.as_deref(), Some("sky.exr"));
+
+        scene.set_time(5.0);
+        assert_eq!(scene.lights[0].position(), Vec3::new(0.0, 3.0, 0.0));
+    }
+
+    #[test]
+    fn test_load_cameras() {
+        let usda = r#"#usda 1.0
+def Xform "World" {
+    def Mesh "Floor" {
+        point3f[] points = [(0, 0, 0), (1, 0, 0), (0, 0, 1)]
+        int[] faceVertexCounts = [3]
+        int[] faceVertexIndices = [0, 1, 2]
+    }
+
+    def Xform "Crane" {
+        double3 xformOp:translate.timeSamples = { 0: (0, 0, 10), 10: (0, 0, 20) }
+        uniform token[] xformOpOrder = ["xformOp:translate"]
+
+        def Camera "Shot" {
+            float focalLength = 35
+            double3 xformOp:translate = (0, 2, 0)
+            uniform token[] xformOpOrder = ["xformOp:translate"]
+        }
+    }
+
+    def Camera "Top" {
+        float xformOp:rotateX = -90
+        uniform token[] xformOpOrder = ["xformOp:rotateX"]
+    }
+}
+"#;
+
+        let mut scene =
+            load_usda_string_with_options(usda, "test", None, &LoadOptions::default()).unwrap();
+        assert_eq!(scene.cameras.len(), 2);
+
+        let shot = scene.find_camera("/World/Crane/Shot").unwrap();
+        assert_eq!(shot.focal_length, 35.0);
+        assert_eq!(shot.position(), Vec3::new(0.0, 2.0, 10.0));
+        let top = scene.find_camera("/World/Top").unwrap();
+        assert!((top.direction() - Vec3::NEG_Y).length() < 1e-5);
+        assert!(scene.find_camera("/World/Missing").is_none());
+
+        scene.set_time(5.0);
+        assert_eq!(scene.cameras[0].position(), Vec3::new(0.0, 2.0, 15.0));
+    }
+
+    #[test]
+    fn test_load_materials() {
+        let usda = r#"#usda 1.0
+def Xform "World" {
+    rel material:binding = </World/Looks/Leaf>
+
+    def Scope "Looks" {
+        def Material "Leaf" {
+            token outputs:surface.connect = </World/Looks/Leaf/Surface.outputs:surface>
+
+            def Shader "Surface" {
+                uniform token info:id = "UsdPreviewSurface"
+                color3f inputs:diffuseColor.connect = </World/Looks/Leaf/Tex.outputs:rgb>
+                float inputs:opacity.connect = </World/Looks/Leaf/Tex.outputs:a>
+                float inputs:opacityThreshold = 0.5
+                color3f inputs:specularColor = (0.2, 0.4, 0.6)
+            }
+
+            def Shader "Tex" {
+                uniform token info:id = "UsdUVTexture"
+                asset inputs:file = @leaf.png@
+            }
+        }
+
+        def Material "Red" {
+            token outputs:surface.connect = </World/Looks/Red/Surface.outputs:surface>
+
+            def Shader "Surface" {
+                uniform token info:id = "UsdPreviewSurface"
+                color3f inputs:diffuseColor = (1, 0, 0)
+                float inputs:metallic = 1
+            }
+        }
+    }
+
+    def Mesh "Card" {
+        point3f[] points = [(0, 0, 0), (1, 0, 0), (1, 1, 0), (0, 1, 0), (2, 0, 0)]
+        int[] faceVertexCounts = [4, 3]
+        int[] faceVertexIndices = [0, 1, 2, 3, 1, 4, 2]
+
+        def GeomSubset "Stem" {
+            uniform token familyName = "materialBind"
+            int[] indices = [1]
+            rel material:binding = </World/Looks/Red>
+        }
+    }
+
+    def Cube "Box" {
+        rel material:binding = </World/Looks/Red>
+    }
+
+    def BasisCurves "Hair" {
+        int[] curveVertexCounts = [4]
+        point3f[] points = [(0, 0, 0), (0, 1, 0), (0, 2, 0), (0, 3, 0)]
+    }
+}
+"#;
+
+        let scene = load_usda_string_with_options(
+            usda,
+            "test",
+            Some(PathBuf::from("textures")),
+            &LoadOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(scene.material_count(), 2);
+
+        let leaf = &scene.materials[0];
+        assert_eq!(leaf.name, "/World/Looks/Leaf");
+        let texture = Path::new("textures").join("leaf.png");
+        assert_eq!(
+            leaf.diffuse_texture.as_deref(),
+            Some(texture.to_string_lossy().as_ref())
+        );
+        assert_eq!(leaf.opacity_texture, leaf.diffuse_texture);
+        assert_eq!(leaf.opacity_texture_channel, 3);
+        assert_eq!(leaf.opacity_threshold, 0.5);
+        assert!((leaf.specular - 0.4).abs() < 1e-6);
+        let red = &scene.materials[1];
+        assert_eq!(red.diffuse_color, Vec3::X);
+        assert_eq!(red.metallic, 1.0);
+
+        // The card inherits the world binding; its second face (the
+        // triangle after the quad's two) is bound through a GeomSubset
+        let card = &scene.prototypes[0];
+        assert_eq!(card.material.as_ref().unwrap().name, "/World/Looks/Leaf");
+        assert_eq!(
+            card.face_material_ids.as_deref(),
+            Some(&[NO_MATERIAL, NO_MATERIAL, 1][..])
+        );
+
+        let cube = &scene.prototypes[1];
+        assert_eq!(cube.material.as_ref().unwrap().name, "/World/Looks/Red");
+        let hair = &scene.curves[0];
+        assert_eq!(hair.material.as_ref().unwrap().name, "/World/Looks/Leaf");
+    }
+
+    #[test]
+    fn test_load_displacement() {
+        let usda = r#"#usda 1.0
+def Scope "Looks" {
+    def Material "Bumps" {
+        token outputs:surface.connect = </Looks/Bumps/Surface.outputs:surface>
+
+        def Shader "Surface" {
+            uniform token info:id = "UsdPreviewSurface"
+            float inputs:displacement.connect = </Looks/Bumps/Height.outputs:g>
+        }
+
+        def Shader "Height" {
+            uniform token info:id = "UsdUVTexture"
+            asset inputs:file = @height.png@
+            float4 inputs:scale = (1, 0.5, 1, 1)
+            float4 inputs:bias = (0, -0.25, 0, 0)
+        }
+    }
+
+    def Material "Folds" {
+        token outputs:surface.connect = </Looks/Folds/Surface.outputs:surface>
+
+        def Shader "Surface" {
+            uniform token info:id = "UsdPreviewSurface"
+            vector3f inputs:displacement.connect = </Looks/Folds/Offset.outputs:rgb>
+        }
+
+        def Shader "Offset" {
+            uniform token info:id = "UsdUVTexture"
+            asset inputs:file = @offset.exr@
+            float4 inputs:scale = (2, 2, 2, 1)
+            float4 inputs:bias = (-1, -1, -1, 0)
+        }
+    }
+}
+
+def Mesh "Ground" {
+    rel material:binding = </Looks/Bumps>
+    point3f[] points = [(0, 0, 0), (1, 0, 0), (1, 1, 0)]
+    int[] faceVertexCounts = [3]
+    int[] faceVertexIndices = [0, 1, 2]
+}
+"#;
+
+        let scene = load_usda_from_string(usda, "test", None).unwrap();
+        assert_eq!(scene.material_count(), 2);
+
+        // A float input reads the scale and bias of its channel
+        let bumps = &scene.materials[0];
+        assert_eq!(bumps.displacement_texture_channel, 1);
+        assert!(!bumps.vector_displacement);
+        assert_eq!(bumps.displacement_scale, 0.5);
+        assert_eq!(bumps.displacement_bias, -0.25);
+        assert!(bumps.has_displacement());
+
+        // A vector3 input offsets by the texture's RGB
+        let folds = &scene.materials[1];
+        assert!(folds.vector_displacement);
+        assert_eq!(folds.displacement_scale, 2.0);
+        assert_eq!(folds.displacement_bias, -1.0);
+    }
+
+    #[test]
+    fn test_materials_are_scoped_to_their_layer() {
+        // Two referenced files each define /Looks/Wood with its own texture
+        let dir = std::env::temp_dir().join("bif_usd_material_layers");
+        let layer = |red: f32| {
+            format!(
+                r#"#usda 1.0
+def Xform "Asset" {{
+    def Scope "Looks" {{
+        def Material "Wood" {{
+            token outputs:surface.connect = </Asset/Looks/Wood/Surface.outputs:surface>
+
+            def Shader "Surface" {{
+                uniform token info:id = "UsdPreviewSurface"
+                color3f inputs:diffuseColor = ({red}, 0, 0)
+                float inputs:roughness.connect = </Asset/Looks/Wood/Tex.outputs:r>
+            }}
+
+            def Shader "Tex" {{
+                uniform token info:id = "UsdUVTexture"
+                asset inputs:file = @wood.png@
+            }}
+        }}
+    }}
+
+    def Mesh "Plank" {{
+        rel material:binding = </Asset/Looks/Wood>
+        point3f[] points = [(0, 0, 0), (1, 0, 0), (0, 1, 0)]
+        int[] faceVertexCounts = [3]
+        int[] faceVertexIndices = [0, 1, 2]
+    }}
+}}
+"#
+            )
+        };
+        for (sub, red) in [("a", 1.0), ("b", 0.5)] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+            std::fs::write(dir.join(sub).join("wood.usda"), layer(red)).unwrap();
+        }
+        let root = r#"#usda 1.0
+def Xform "A" (
+    references = @a/wood.usda@
+) {
+}
+
+def Xform "B" (
+    references = @b/wood.usda@
+) {
+}
+"#;
+        std::fs::write(dir.join("root.usda"), root).unwrap();
+
+        let scene = load_usda(dir.join("root.usda")).unwrap();
+        assert_eq!(scene.material_count(), 2);
+        assert_eq!(scene.prototypes.len(), 2);
+        for (prototype, (sub, red)) in scene.prototypes.iter().zip([("a", 1.0), ("b", 0.5)]) {
+            let material = prototype.material.as_ref().unwrap();
+            assert_eq!(material.diffuse_color, Vec3::new(red, 0.0, 0.0));
+            let texture = dir.join(sub).join("wood.png");
+            assert_eq!(
+                material.roughness_texture.as_deref(),
+                Some(texture.to_string_lossy().as_ref())
+            );
+        }
+    }
+
+    #[test]
+    fn test_load_animation() {
+        let usda = r#"#usda 1.0
+(
+    startTimeCode = 0
+    endTimeCode = 10
+)
+
+def Xform "Rig" {
+    double3 xformOp:translate.timeSamples = { 0: (0, 0, 0), 10: (10, 0, 0) }
+
+    def Mesh "Static" {
+        point3f[] points = [(0, 0, 0), (1, 0, 0), (0, 1, 0)]
+        int[] faceVertexCounts = [3]
+        int[] faceVertexIndices = [0, 1, 2]
+        double3 xformOp:translate = (0, 5, 0)
+    }
+
+    def PointInstancer "Swarm" {
+        point3f[] positions.timeSamples = { 0: [(0, 0, 0)], 10: [(0, 0, 10)] }
+        int[] protoIndices = [0]
+
+        def Mesh "Wobble" {
+            point3f[] points.timeSamples = {
+                0: [(0, 0, 0), (1, 0, 0), (0, 1, 0)],
+                10: [(0, 0, 0), (3, 0, 0), (0, 1, 0)],
+            }
+            int[] faceVertexCounts = [3]
+            int[] faceVertexIndices = [0, 1, 2]
+        }
+    }
+}
+"#;
+
+        let options = LoadOptions {
+            time: Some(5.0),
+            ..Default::default()
+        };
+        let mut scene = load_usda_string_with_options(usda, "test", None, &options).unwrap();
+        assert_eq!(scene.time_codes.end, 10.0);
+        assert_eq!(scene.animation.instances.len(), 2);
+        assert_eq!(scene.animation.prototypes.len(), 1);
+
+        // Loaded at time 5: the parent's motion reaches the child mesh
+        let origin = |scene: &Scene, i: usize| scene.instances[i].model_matrix().w_axis.truncate();
+        assert!((origin(&scene, 0) - Vec3::new(5.0, 5.0, 0.0)).length() < 1e-4);
+        assert!((origin(&scene, 1) - Vec3::new(5.0, 0.0, 5.0)).length() < 1e-4);
+        assert_eq!(
+            scene.prototypes[1].mesh.positions[1],
+            Vec3::new(2.0, 0.0, 0.0)
+        );
+
+        scene.set_time(10.0);
+        assert!((origin(&scene, 0) - Vec3::new(10.0, 5.0, 0.0)).length() < 1e-4);
+        assert!((origin(&scene, 1) - Vec3::new(10.0, 0.0, 10.0)).length() < 1e-4);
+        let mesh = &scene.prototypes[1].mesh;
+        assert_eq!(mesh.positions[1], Vec3::new(3.0, 0.0, 0.0));
+        assert_eq!(scene.prototypes[1].bounds.max_point().x, 3.0);
+        assert!(mesh.has_normals());
+    }
+
+    #[test]
+    fn test_load_motion_keys() {
+        let usda = r#"#usda 1.0
+def Xform "Rig" {
+    double3 xformOp:translate.timeSamples = { 0: (0, 0, 0), 10: (10, 0, 0) }
+
+    def Mesh "Wobble" {
+        point3f[] points.timeSamples = {
+            0: [(0, 0, 0), (1, 0, 0), (0, 1, 0)],
+            10: [(0, 0, 0), (3, 0, 0), (0, 1, 0)],
+        }
+        int[] faceVertexCounts = [3]
+        int[] faceVertexIndices = [0, 1, 2]
+    }
+}
+
+def Mesh "Static" {
+    point3f[] points = [(0, 0, 0), (1, 0, 0), (0, 0, 1)]
+    int[] faceVertexCounts = [3]
+    int[] faceVertexIndices = [0, 1, 2]
+}
+"#;
+
+        let options = LoadOptions {
+            time: Some(5.0),
+            motion_blur: true,
+            ..Default::default()
+        };
+        let scene = load_usda_string_with_options(usda, "test", None, &options).unwrap();
+
+        // Keys at the frame and one time code later
+        let rig = &scene.instances[0];
+        assert_eq!(rig.transform.translation, Vec3::new(5.0, 0.0, 0.0));
+        assert_eq!(rig.motion.len(), 1);
+        assert!((rig.motion[0].translation - Vec3::new(6.0, 0.0, 0.0)).length() < 1e-5);
+        let mesh = &scene.prototypes[0].mesh;
+        assert_eq!(mesh.positions[1], Vec3::new(2.0, 0.0, 0.0));
+        assert_eq!(mesh.motion.len(), 1);
+        assert!((mesh.motion[0][1] - Vec3::new(2.2, 0.0, 0.0)).length() < 1e-5);
+        assert_eq!(scene.prototypes[0].bounds.max_point().x, 2.2);
+
+        assert!(!scene.instances[1].is_moving());
+        assert!(scene.prototypes[1].mesh.motion.is_empty());
+
+        // Without motion blur the frame stays static
+        let options = LoadOptions {
+            motion_blur: false,
+            ..options
+        };
+        let scene = load_usda_string_with_options(usda, "test", None, &options).unwrap();
+        assert!(!scene.instances[0].is_moving());
+        assert!(scene.prototypes[0].mesh.motion.is_empty());
+    }
+
+    #[test]
+    fn test_face_material_ids_from_subsets() {
+        let mesh_data = UsdMeshData {
+            path: "/Root/Mesh".to_string(),
+            vertices: vec![],
+            indices: vec![],
+            normals: None,
+            uvs: None,
+            transform: Mat4::IDENTITY,
+            triangle_subsets: Some(vec![0, 0, -1, 1, 2]),
+            subset_material_paths: vec![
+                Some("/Looks/Red".to_string()),
+                Some("/Looks/Missing".to_string()),
+                None,
+            ],
+            subdivision_scheme: SubdivisionScheme::None,
+            face_vertex_counts: vec![],
+            face_vertex_indices: vec![],
+            subdivision_tags: SubdivisionTags::default(),
+            displacement_bound: None,
+            visibility: UsdVisibility::default(),
+        };
+        let material_map = HashMap::from([("/Looks/Red".to_string(), 3)]);
+
+        let ids = face_material_ids(&mesh_data, &material_map).unwrap();
+        assert_eq!(ids, vec![3, 3, NO_MATERIAL, NO_MATERIAL, NO_MATERIAL]);
+
+        // No resolvable bindings -> no per-face table
+        assert!(face_material_ids(&mesh_data, &HashMap::new()).is_none());
+    }
+
+    #[test]
+    fn test_mesh_key_keeps_prototype_data_apart() {
+        let quad = UsdMeshData {
+            path: "/Root/A".to_string(),
+            vertices: vec![Vec3::ZERO, Vec3::X, Vec3::new(1.0, 1.0, 0.0), Vec3::Y],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            normals: None,
+            uvs: None,
+            transform: Mat4::IDENTITY,
+            triangle_subsets: Some(vec![0, 1]),
+            subset_material_paths: vec![Some("/Looks/Red".to_string()), None],
+            subdivision_scheme: SubdivisionScheme::None,
+            face_vertex_counts: vec![4],
+            face_vertex_indices: vec![0, 1, 2, 3],
+            subdivision_tags: SubdivisionTags::default(),
+            displacement_bound: None,
+            visibility: UsdVisibility::default(),
+        };
+        let material_map = HashMap::from([
+            ("/Looks/Red".to_string(), 0),
+            ("/Looks/Blue".to_string(), 1),
+        ]);
+        let key = |mesh: &UsdMeshData| {
+            MeshKey::new(mesh, face_material_ids(mesh, &material_map).as_deref())
+        };
+
+        // Same points elsewhere: one prototype
+        let moved = UsdMeshData {
+            path: "/Root/B".to_string(),
+            transform: Mat4::from_translation(Vec3::X),
+            ..quad.clone()
+        };
+        assert_eq!(key(&quad), key(&moved));
+
+        // Same points bound differently per face: two prototypes
+        let rebound = UsdMeshData {
+            subset_material_paths: vec![Some("/Looks/Blue".to_string()), None],
+            ..quad.clone()
+        };
+        assert_ne!(key(&quad), key(&rebound));
+
+        let subdivided = UsdMeshData {
+            subdivision_scheme: SubdivisionScheme::CatmullClark,
+            ..quad.clone()
+        };
+        assert_ne!(key(&quad), key(&subdivided));
+
+        let displaced = UsdMeshData {
+            displacement_bound: Some(0.5),
+            ..quad.clone()
+        };
+        assert_ne!(key(&quad), key(&displaced));
+    }
+
     // ========================================================================
     // Integration tests for C++ bridge (require USD to be installed)
     // Run with: cargo test --package bif_core -- --ignored
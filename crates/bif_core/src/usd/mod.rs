@@ -6,18 +6,45 @@
 //! ## Supported USD Features (Milestone 13)
 //!
 //! - `UsdGeomMesh`: Triangle meshes with positions, normals, indices
+//! - Catmull-Clark subdivision (`subdivisionScheme = "catmullClark"`) with
+//!   creases and corners, refined to [`LoadOptions::subdivision_level`]
 //! - `UsdGeomPointInstancer`: Instanced geometry with transforms
 //! - `Xform`: Transform hierarchies with xformOps
+//! - `UsdGeomBasisCurves`: linear, Bézier, B-spline and Catmull-Rom curves
+//!   with per-vertex widths, as round tubes or ribbons (authored `normals`)
+//! - `UsdGeomPoints`: particle clouds with constant or per-point widths
+//! - Intrinsic shapes (`Cube`, `Sphere`, `Cylinder`, `Cone`, `Plane`) in the
+//!   pure-Rust USDA parser, tessellated into mesh prototypes
 //! - **File references**: `@path/to/file.usda@</Prim>` syntax
 //! - **Binary format**: `.usdc` files (via C++ bridge)
 //! - **Auto-detect format**: `.usd` files
+//! - `UsdShade` materials: UsdPreviewSurface constants and `UsdUVTexture`
+//!   connections, bound with `material:binding` on prims and `GeomSubset`s;
+//!   MaterialX `standard_surface` node graphs via the C++ bridge (see
+//!   [`crate::shading`])
+//! - `UsdLux` lights (`SphereLight`, `RectLight`, `DiskLight`,
+//!   `CylinderLight`, `DistantLight`, `DomeLight`) with intensity, exposure,
+//!   color, color temperature, `ShapingAPI` and texture inputs, in both the
+//!   C++ bridge and the USDA parser (see [`crate::light`])
+//! - `UsdGeomCamera`: focal length, apertures, clipping range, f-stop and
+//!   focus distance, in both the C++ bridge and the USDA parser (see
+//!   [`crate::camera`])
+//! - **Time samples**: any frame can be loaded with [`LoadOptions::time`],
+//!   and the stage's `startTimeCode`, `endTimeCode` and `timeCodesPerSecond`
+//!   land in [`Scene::time_codes`](crate::scene::Scene::time_codes). The
+//!   pure-Rust USDA parser also keeps animated xformOps, mesh points and
+//!   instancer positions, orientations and scales as animation curves (see
+//!   [`crate::animation`] and [`Scene::set_time`](crate::scene::Scene::set_time))
+//! - **Motion blur**: with [`LoadOptions::motion_blur`], instances and
+//!   meshes that move over the loaded frame get a second transform or
+//!   position key one time code later, in both loaders
 //!
 //! ## Not Yet Supported
 //!
-//! - Materials and textures (`UsdShade`)
-//! - Lights (`UsdLux`)
-//! - Cameras (`UsdGeomCamera`)
-//! - Animation / time samples
+//! - MaterialX node graphs in the pure-Rust USDA parser
+//! - `UsdLux` light filters, portals and mesh lights
+//! - Orthographic cameras and aperture offsets
+//! - Animation curves through the C++ bridge (it loads one time per stage)
 //! - Payloads and variants
 //!
 //! # Example
@@ -35,9 +62,12 @@
 pub mod cpp_bridge;
 mod loader;
 mod parser;
+mod shapes;
 mod types;
 
-pub use cpp_bridge::{UsdBridgeError, UsdInstancerData, UsdMeshData, UsdStage};
+pub use cpp_bridge::{
+    UsdBridgeError, UsdCurvesData, UsdInstancerData, UsdMeshData, UsdPointsData, UsdStage,
+};
 pub use loader::*;
 pub use parser::*;
 pub use types::*;
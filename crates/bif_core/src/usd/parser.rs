@@ -8,25 +8,44 @@
 //! - `def Xform "Name" { ... }`
 //! - `def Mesh "Name" { ... }`
 //! - `def PointInstancer "Name" { ... }`
+//! - UsdLux `SphereLight`, `RectLight`, `DiskLight`, `CylinderLight`,
+//!   `DistantLight` and `DomeLight` with their `inputs:*` (including
+//!   `shaping:*` and `texture:*`)
 //! - `float3[] points = [...]`
 //! - `int[] faceVertexCounts = [...]`
 //! - `int[] faceVertexIndices = [...]`
 //! - `normal3f[] normals = [...]`
+//! - `uniform token subdivisionScheme`, `creaseIndices`, `creaseLengths`,
+//!   `creaseSharpnesses`, `cornerIndices`, `cornerSharpnesses`
 //! - `float3[] positions = [...]` (for PointInstancer)
 //! - `quath[] orientations = [...]`
 //! - `float3[] scales = [...]`
 //! - `int[] protoIndices = [...]`
 //! - `rel prototypes = [...]`
 //! - `xformOp:translate`, `xformOp:rotateXYZ`, `xformOp:scale`
+//! - `token visibility`, `uniform token purpose`, `bool primvars:bif:visibility:*`
+//! - `.timeSamples = { time: value, ... }` on xformOps, mesh `points` and
+//!   instancer `positions`, `orientations` and `scales`
+//! - UsdShade `Material`, `NodeGraph` and `Shader` prims with `info:id`,
+//!   `inputs:*` values and `.connect` sources
+//! - `rel material:binding` and `GeomSubset` children of meshes
+//! - Stage `startTimeCode`, `endTimeCode` and `timeCodesPerSecond`
 
 // TODO: Consider nom/pest for robustness if grammar complexity grows
 
 use std::collections::VecDeque;
+use std::str::FromStr;
 
 use bif_math::{Mat4, Quat, Vec3};
 use thiserror::Error;
 
 use super::types::*;
+use crate::animation::{AnimationCurve, Interpolate, TimeCodes};
+use crate::camera::Camera;
+use crate::light::{DomeTextureFormat, Light, LightKind};
+use crate::mesh::SubdivisionScheme;
+use crate::scene::Visibility;
+use crate::shading::ShadingValue;
 
 /// Errors that can occur during USDA parsing.
 #[derive(Error, Debug)]
@@ -50,27 +69,58 @@ pub enum ParseError {
 /// Result type for parsing operations.
 pub type ParseResult<T> = Result<T, ParseError>;
 
+/// Suffix of time-sampled attribute names (`points.timeSamples`).
+const TIME_SAMPLES: &str = ".timeSamples";
+
 /// USDA file parser.
 pub struct UsdaParser {
     lines: VecDeque<(usize, String)>,
     current_line: usize,
+    time_codes: TimeCodes,
 }
 
 impl UsdaParser {
     /// Create a new parser from file contents.
     pub fn new(content: &str) -> Self {
-        let lines: VecDeque<_> = content
-            .lines()
-            .enumerate()
-            .map(|(i, s)| (i + 1, s.to_string()))
-            .collect();
+        // Join multi-line timeSamples blocks onto their first line, so prim
+        // content loops see each block as a single attribute
+        let mut lines = VecDeque::new();
+        let mut block: Option<(usize, String, i32)> = None;
+        for (i, line) in content.lines().enumerate() {
+            let depth = brace_depth(line);
+            match block.take() {
+                Some((num, mut joined, open)) => {
+                    joined.push(' ');
+                    joined.push_str(line.trim());
+                    if open + depth > 0 {
+                        block = Some((num, joined, open + depth));
+                    } else {
+                        lines.push_back((num, joined));
+                    }
+                }
+                None if line.contains(TIME_SAMPLES) && depth > 0 => {
+                    block = Some((i + 1, line.to_string(), depth));
+                }
+                None => lines.push_back((i + 1, line.to_string())),
+            }
+        }
+        if let Some((num, joined, _)) = block {
+            lines.push_back((num, joined));
+        }
 
         Self {
             lines,
             current_line: 0,
+            time_codes: TimeCodes::default(),
         }
     }
 
+    /// Stage playback range from the header metadata (valid after
+    /// [`UsdaParser::parse`]).
+    pub fn time_codes(&self) -> TimeCodes {
+        self.time_codes
+    }
+
     /// Parse the USDA content and return a list of root prims.
     pub fn parse(&mut self) -> ParseResult<Vec<UsdPrim>> {
         let mut prims = Vec::new();
@@ -88,12 +138,14 @@ impl UsdaParser {
                 if trimmed.ends_with(')') {
                     in_header_metadata = false;
                 }
+                parse_time_codes(trimmed, &mut self.time_codes);
                 self.lines.pop_front();
             } else if in_header_metadata {
                 // Inside header metadata, consume until we find closing paren
                 if trimmed.ends_with(')') || trimmed == ")" {
                     in_header_metadata = false;
                 }
+                parse_time_codes(trimmed, &mut self.time_codes);
                 self.lines.pop_front();
             } else {
                 break;
@@ -254,7 +306,32 @@ impl UsdaParser {
             "PointInstancer" => self
                 .parse_point_instancer_content(&path, name, start_line)
                 .map(|p| Some(UsdPrim::PointInstancer(p))),
-            "Scope" => {
+            "Cube" | "Sphere" | "Cylinder" | "Cone" | "Plane" => self
+                .parse_shape_content(&path, name, prim_type, start_line)
+                .map(|s| Some(UsdPrim::Shape(s))),
+            "BasisCurves" => self
+                .parse_basis_curves_content(&path, name, start_line)
+                .map(|c| Some(UsdPrim::BasisCurves(c))),
+            "Points" => self
+                .parse_points_content(&path, name, start_line)
+                .map(|p| Some(UsdPrim::Points(p))),
+            "SphereLight" | "RectLight" | "DiskLight" | "CylinderLight" | "DistantLight"
+            | "DomeLight" => self
+                .parse_light_content(&path, name, prim_type, start_line)
+                .map(|l| Some(UsdPrim::Light(l))),
+            "Camera" => self
+                .parse_camera_content(&path, start_line)
+                .map(|c| Some(UsdPrim::Camera(c))),
+            "Material" => self
+                .parse_material_content(&path, name, start_line)
+                .map(|m| Some(UsdPrim::Material(m))),
+            "Shader" => self
+                .parse_shader_content(&path, start_line)
+                .map(|s| Some(UsdPrim::Shader(s))),
+            "GeomSubset" => self
+                .parse_geom_subset_content(&path, start_line)
+                .map(|s| Some(UsdPrim::GeomSubset(s))),
+            "Scope" | "NodeGraph" => {
                 // Scope is like Xform but without transform
                 self.parse_xform_content(&path, name, start_line)
                     .map(|x| Some(UsdPrim::Xform(x)))
@@ -273,7 +350,9 @@ impl UsdaParser {
         // Look ahead for opening paren
         if let Some((_, line)) = self.lines.front() {
             let trimmed = line.trim();
-            if trimmed.starts_with('(') || trimmed.ends_with('(') {
+            // Only standalone parentheses: a child `def Type "Name" (` line
+            // opens the child's metadata, not ours
+            if trimmed.starts_with('(') {
                 // Collect all metadata lines
                 let mut metadata_lines = Vec::new();
                 let mut depth = 1;
@@ -380,10 +459,12 @@ impl UsdaParser {
             asset_path,
             target_prim_path: target_prim,
             transform: Mat4::IDENTITY,
+            transform_samples: None,
+            material_binding: None,
             children: Vec::new(),
         };
 
-        let mut xform_ops = Vec::new();
+        let mut xform_ops = XformOps::default();
 
         loop {
             let (line_num, line) = match self.lines.pop_front() {
@@ -402,8 +483,11 @@ impl UsdaParser {
             }
 
             // Parse xformOps (transform overrides)
-            if let Some(op) = self.parse_xform_op(trimmed)? {
-                xform_ops.push(op);
+            if self.parse_xform_attribute(trimmed, &mut xform_ops)? {
+                continue;
+            }
+
+            if parse_material_binding(trimmed, &mut reference.material_binding) {
                 continue;
             }
 
@@ -417,7 +501,8 @@ impl UsdaParser {
             }
         }
 
-        reference.transform = compose_xform_ops(&xform_ops);
+        reference.transform = xform_ops.transform();
+        reference.transform_samples = xform_ops.samples();
 
         Ok(reference)
     }
@@ -439,6 +524,8 @@ impl UsdaParser {
             asset_path,
             target_prim_path: target_prim,
             transform: Mat4::IDENTITY,
+            transform_samples: None,
+            material_binding: None,
             children: Vec::new(),
         };
 
@@ -459,8 +546,7 @@ impl UsdaParser {
         let mut xform = UsdXform {
             path: path.to_string(),
             name: name.to_string(),
-            transform: Mat4::IDENTITY,
-            children: Vec::new(),
+            ..Default::default()
         };
 
         let xform_ops = self.parse_inline_xform_ops(inline_content)?;
@@ -543,11 +629,10 @@ impl UsdaParser {
         let mut xform = UsdXform {
             path: path.to_string(),
             name: name.to_string(),
-            transform: Mat4::IDENTITY,
-            children: Vec::new(),
+            ..Default::default()
         };
 
-        let mut xform_ops = Vec::new();
+        let mut xform_ops = XformOps::default();
 
         loop {
             let (line_num, line) = match self.lines.pop_front() {
@@ -575,14 +660,22 @@ impl UsdaParser {
             }
 
             // Parse xformOps
-            if let Some(op) = self.parse_xform_op(trimmed)? {
-                xform_ops.push(op);
+            if self.parse_xform_attribute(trimmed, &mut xform_ops)? {
+                continue;
+            }
+
+            if parse_visibility(trimmed, &mut xform.visibility) {
+                continue;
+            }
+
+            if parse_material_binding(trimmed, &mut xform.material_binding) {
                 continue;
             }
         }
 
         // Compose xformOps into final transform
-        xform.transform = compose_xform_ops(&xform_ops);
+        xform.transform = xform_ops.transform();
+        xform.transform_samples = xform_ops.samples();
 
         Ok(xform)
     }
@@ -600,10 +693,10 @@ impl UsdaParser {
             ..Default::default()
         };
 
-        let mut xform_ops = Vec::new();
+        let mut xform_ops = XformOps::default();
 
         loop {
-            let (_, line) = match self.lines.pop_front() {
+            let (line_num, line) = match self.lines.pop_front() {
                 Some(x) => x,
                 None => return Err(ParseError::UnclosedBlock(start_line)),
             };
@@ -618,9 +711,32 @@ impl UsdaParser {
                 break;
             }
 
+            // Keep GeomSubset children; other child prims are dropped
+            if trimmed.starts_with("def ") {
+                self.lines.push_front((line_num, line));
+                if let Some(UsdPrim::GeomSubset(subset)) = self.parse_prim(path)? {
+                    mesh.subsets.push(subset);
+                }
+                continue;
+            }
+
             // Parse xformOps
-            if let Some(op) = self.parse_xform_op(trimmed)? {
-                xform_ops.push(op);
+            if self.parse_xform_attribute(trimmed, &mut xform_ops)? {
+                continue;
+            }
+
+            if parse_visibility(trimmed, &mut mesh.visibility) {
+                continue;
+            }
+
+            if parse_material_binding(trimmed, &mut mesh.material_binding) {
+                continue;
+            }
+
+            // Parse animated points (the checks below would take the
+            // first sample as the value)
+            if attribute_name(trimmed) == Some("points.timeSamples") {
+                mesh.points_samples = self.parse_samples(trimmed, Self::parse_vec3_array)?;
                 continue;
             }
 
@@ -637,13 +753,13 @@ impl UsdaParser {
 
             // Parse face vertex counts
             if trimmed.contains("faceVertexCounts") {
-                mesh.face_vertex_counts = self.parse_int_array(trimmed)?;
+                mesh.face_vertex_counts = self.parse_number_array(trimmed)?;
                 continue;
             }
 
             // Parse face vertex indices
             if trimmed.contains("faceVertexIndices") {
-                mesh.face_vertex_indices = self.parse_int_array(trimmed)?;
+                mesh.face_vertex_indices = self.parse_number_array(trimmed)?;
                 continue;
             }
 
@@ -658,6 +774,45 @@ impl UsdaParser {
                 continue;
             }
 
+            // Parse subdivision scheme and tags
+            match attribute_name(trimmed) {
+                Some("subdivisionScheme") => {
+                    let token = trimmed
+                        .split_once('=')
+                        .map_or("", |(_, value)| value.trim().trim_matches('"'));
+                    match SubdivisionScheme::from_token(token) {
+                        Some(scheme) => mesh.subdivision_scheme = scheme,
+                        None => log::warn!(
+                            "Unsupported subdivision scheme {:?} on {}, using the cage",
+                            token,
+                            mesh.path
+                        ),
+                    }
+                    continue;
+                }
+                Some("creaseIndices") => {
+                    mesh.subdivision_tags.crease_indices = self.parse_number_array(trimmed)?;
+                    continue;
+                }
+                Some("creaseLengths") => {
+                    mesh.subdivision_tags.crease_lengths = self.parse_number_array(trimmed)?;
+                    continue;
+                }
+                Some("creaseSharpnesses") => {
+                    mesh.subdivision_tags.crease_sharpnesses = self.parse_number_array(trimmed)?;
+                    continue;
+                }
+                Some("cornerIndices") => {
+                    mesh.subdivision_tags.corner_indices = self.parse_number_array(trimmed)?;
+                    continue;
+                }
+                Some("cornerSharpnesses") => {
+                    mesh.subdivision_tags.corner_sharpnesses = self.parse_number_array(trimmed)?;
+                    continue;
+                }
+                _ => {}
+            }
+
             // Parse orientation (winding order)
             if trimmed.contains("orientation") {
                 if trimmed.contains("\"leftHanded\"") {
@@ -668,28 +823,45 @@ impl UsdaParser {
             }
         }
 
-        mesh.transform = compose_xform_ops(&xform_ops);
+        mesh.transform = xform_ops.transform();
+        mesh.transform_samples = xform_ops.samples();
+
+        // Without an authored value the first sample stands in
+        if let (true, Some(samples)) = (mesh.points.is_empty(), &mesh.points_samples) {
+            mesh.points = samples.first().clone();
+        }
 
         Ok(mesh)
     }
 
-    /// Parse PointInstancer content.
-    fn parse_point_instancer_content(
+    /// Parse the content of an intrinsic shape prim.
+    ///
+    /// Dimensions not authored keep USD's fallback values.
+    fn parse_shape_content(
         &mut self,
         path: &str,
         name: &str,
+        prim_type: &str,
         start_line: usize,
-    ) -> ParseResult<UsdPointInstancer> {
-        let mut instancer = UsdPointInstancer {
+    ) -> ParseResult<UsdShape> {
+        let mut shape = UsdShape {
             path: path.to_string(),
             name: name.to_string(),
-            ..Default::default()
+            kind: UsdShapeKind::from_type_name(prim_type).ok_or_else(|| ParseError::Parse {
+                line: start_line,
+                message: format!("Not a shape prim type: {}", prim_type),
+            })?,
+            axis: UsdAxis::default(),
+            transform: Mat4::IDENTITY,
+            transform_samples: None,
+            visibility: UsdVisibility::default(),
+            material_binding: None,
         };
 
-        let mut xform_ops = Vec::new();
+        let mut xform_ops = XformOps::default();
 
         loop {
-            let (line_num, line) = match self.lines.pop_front() {
+            let (_, line) = match self.lines.pop_front() {
                 Some(x) => x,
                 None => return Err(ParseError::UnclosedBlock(start_line)),
             };
@@ -705,179 +877,890 @@ impl UsdaParser {
             }
 
             // Parse xformOps
-            if let Some(op) = self.parse_xform_op(trimmed)? {
-                xform_ops.push(op);
-                continue;
-            }
-
-            // Parse positions
-            if (trimmed.contains("point3f[]") || trimmed.contains("float3[]"))
-                && trimmed.contains("positions")
-            {
-                instancer.positions = self.parse_vec3_array(trimmed)?;
-                continue;
-            }
-
-            // Parse protoIndices
-            if trimmed.contains("protoIndices") {
-                instancer.proto_indices = self.parse_int_array(trimmed)?;
+            if self.parse_xform_attribute(trimmed, &mut xform_ops)? {
                 continue;
             }
 
-            // Parse orientations (quaternions)
-            if trimmed.contains("orientations") {
-                instancer.orientations = Some(self.parse_quat_array(trimmed)?);
+            if parse_visibility(trimmed, &mut shape.visibility) {
                 continue;
             }
 
-            // Parse scales
-            if (trimmed.contains("float3[]") || trimmed.contains("point3f[]"))
-                && trimmed.contains("scales")
-            {
-                instancer.scales = Some(self.parse_vec3_array(trimmed)?);
+            if parse_material_binding(trimmed, &mut shape.material_binding) {
                 continue;
             }
 
-            // Parse prototypes relationship
-            if trimmed.contains("rel prototypes") {
-                instancer.prototypes = self.parse_rel_array(trimmed)?;
+            let Some(attribute) = attribute_name(trimmed) else {
                 continue;
-            }
+            };
 
-            // Check for child prim (inline prototypes)
-            if trimmed.starts_with("def ") {
-                self.lines.push_front((line_num, line));
-                if let Some(child) = self.parse_prim(path)? {
-                    instancer.children.push(child);
+            if attribute == "axis" {
+                let token = trimmed
+                    .split_once('=')
+                    .map_or("", |(_, value)| value.trim().trim_matches('"'));
+                match UsdAxis::from_token(token) {
+                    Some(axis) => shape.axis = axis,
+                    None => log::warn!("Invalid axis {:?} on {}", token, shape.path),
                 }
                 continue;
             }
+
+            let value = match (&mut shape.kind, attribute) {
+                (UsdShapeKind::Cube { size }, "size") => size,
+                (UsdShapeKind::Sphere { radius }, "radius")
+                | (UsdShapeKind::Cylinder { radius, .. }, "radius")
+                | (UsdShapeKind::Cone { radius, .. }, "radius") => radius,
+                (UsdShapeKind::Cylinder { height, .. }, "height")
+                | (UsdShapeKind::Cone { height, .. }, "height") => height,
+                (UsdShapeKind::Plane { width, .. }, "width") => width,
+                (UsdShapeKind::Plane { length, .. }, "length") => length,
+                _ => continue,
+            };
+            *value = self.parse_inline_float(trimmed)?;
         }
 
-        instancer.transform = compose_xform_ops(&xform_ops);
+        shape.transform = xform_ops.transform();
+        shape.transform_samples = xform_ops.samples();
 
-        Ok(instancer)
+        Ok(shape)
     }
 
-    /// Parse a single xformOp attribute.
-    fn parse_xform_op(&self, line: &str) -> ParseResult<Option<XformOp>> {
-        // Skip xformOpOrder - it just lists the order of ops, not actual values
-        if line.contains("xformOpOrder") {
-            return Ok(None);
-        }
+    /// Parse BasisCurves content.
+    fn parse_basis_curves_content(
+        &mut self,
+        path: &str,
+        name: &str,
+        start_line: usize,
+    ) -> ParseResult<UsdBasisCurves> {
+        let mut curves = UsdBasisCurves {
+            path: path.to_string(),
+            name: name.to_string(),
+            // USD fallbacks
+            curve_type: "cubic".to_string(),
+            basis: "bezier".to_string(),
+            ..Default::default()
+        };
 
-        // xformOp:translate = (1, 2, 3)
-        if line.contains("xformOp:translate") && line.contains('=') && line.contains('(') {
-            let vec = self.parse_inline_vec3(line)?;
-            return Ok(Some(XformOp::Translate(vec)));
-        }
+        let mut xform_ops = XformOps::default();
 
-        // xformOp:rotateX = 90
-        if line.contains("xformOp:rotateX")
-            && line.contains('=')
-            && !line.contains("xformOp:rotateXYZ")
-        {
-            let val = self.parse_inline_float(line)?;
-            return Ok(Some(XformOp::RotateX(val)));
-        }
+        loop {
+            let (_, line) = match self.lines.pop_front() {
+                Some(x) => x,
+                None => return Err(ParseError::UnclosedBlock(start_line)),
+            };
 
-        // xformOp:rotateY = 90
-        if line.contains("xformOp:rotateY") && line.contains('=') {
-            let val = self.parse_inline_float(line)?;
-            return Ok(Some(XformOp::RotateY(val)));
-        }
+            let trimmed = line.trim();
 
-        // xformOp:rotateZ = 90
-        if line.contains("xformOp:rotateZ") && line.contains('=') {
-            let val = self.parse_inline_float(line)?;
-            return Ok(Some(XformOp::RotateZ(val)));
-        }
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
 
-        // xformOp:rotateXYZ = (0, 45, 0)
-        if line.contains("xformOp:rotateXYZ") && line.contains('=') {
-            let vec = self.parse_inline_vec3(line)?;
-            return Ok(Some(XformOp::RotateXYZ(vec)));
-        }
+            if trimmed == "}" {
+                break;
+            }
 
-        // xformOp:scale = (1, 1, 1)
-        if line.contains("xformOp:scale") && line.contains('=') {
-            let vec = self.parse_inline_vec3(line)?;
-            return Ok(Some(XformOp::Scale(vec)));
+            // Parse xformOps
+            if self.parse_xform_attribute(trimmed, &mut xform_ops)? {
+                continue;
+            }
+
+            if parse_material_binding(trimmed, &mut curves.material_binding) {
+                continue;
+            }
+
+            let token = || {
+                trimmed
+                    .split_once('=')
+                    .map_or("", |(_, value)| value.trim().trim_matches('"'))
+                    .to_string()
+            };
+            match attribute_name(trimmed) {
+                Some("points") => curves.points = self.parse_vec3_array(trimmed)?,
+                Some("curveVertexCounts") => {
+                    curves.curve_vertex_counts = self.parse_number_array(trimmed)?
+                }
+                Some("widths") => curves.widths = self.parse_number_array(trimmed)?,
+                Some("normals") => {
+                    // Only the presence matters: ribbons face their normals
+                    self.parse_vec3_array(trimmed)?;
+                    curves.has_normals = true;
+                }
+                Some("type") => curves.curve_type = token(),
+                Some("basis") => curves.basis = token(),
+                _ => {}
+            }
         }
 
-        Ok(None)
+        curves.transform = xform_ops.transform();
+        curves.transform_samples = xform_ops.samples();
+
+        Ok(curves)
     }
 
-    /// Parse an inline Vec3 value like (1, 2, 3).
-    /// For lines with xformOp, finds the value after the xformOp's = sign.
-    fn parse_inline_vec3(&self, line: &str) -> ParseResult<Vec3> {
-        // Find the xformOp pattern and look for = after it
-        // This handles lines like: { double3 xformOp:translate = (0, 0, 0) }
-        // where there might be other ( ) pairs earlier in the line
-        let search_start = if let Some(xform_pos) = line.find("xformOp:") {
-            // Find the = after the xformOp
-            if let Some(eq_offset) = line[xform_pos..].find('=') {
-                xform_pos + eq_offset
-            } else {
-                0
-            }
-        } else {
-            line.find('=').unwrap_or_default()
+    /// Parse Points content.
+    fn parse_points_content(
+        &mut self,
+        path: &str,
+        name: &str,
+        start_line: usize,
+    ) -> ParseResult<UsdPoints> {
+        let mut points = UsdPoints {
+            path: path.to_string(),
+            name: name.to_string(),
+            ..Default::default()
         };
 
-        let search_str = &line[search_start..];
+        let mut xform_ops = XformOps::default();
 
-        // Find parentheses in the portion after the relevant =
-        let start = search_str.find('(').ok_or_else(|| ParseError::Parse {
-            line: self.current_line,
-            message: format!("Expected '(' in: {}", line),
-        })?;
+        loop {
+            let (_, line) = match self.lines.pop_front() {
+                Some(x) => x,
+                None => return Err(ParseError::UnclosedBlock(start_line)),
+            };
 
-        let end = search_str.find(')').ok_or_else(|| ParseError::Parse {
-            line: self.current_line,
-            message: format!("Expected ')' in: {}", line),
-        })?;
+            let trimmed = line.trim();
 
-        let inner = &search_str[start + 1..end];
-        let parts: Vec<&str> = inner.split(',').collect();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
 
-        if parts.len() != 3 {
-            return Err(ParseError::Parse {
-                line: self.current_line,
-                message: format!("Expected 3 components, got {}", parts.len()),
-            });
+            if trimmed == "}" {
+                break;
+            }
+
+            // Parse xformOps
+            if self.parse_xform_attribute(trimmed, &mut xform_ops)? {
+                continue;
+            }
+
+            if parse_material_binding(trimmed, &mut points.material_binding) {
+                continue;
+            }
+
+            match attribute_name(trimmed) {
+                Some("points") => points.points = self.parse_vec3_array(trimmed)?,
+                Some("widths") => points.widths = self.parse_number_array(trimmed)?,
+                _ => {}
+            }
         }
 
-        let x = parts[0]
-            .trim()
-            .parse::<f32>()
-            .map_err(|_| ParseError::InvalidNumber(parts[0].to_string()))?;
-        let y = parts[1]
-            .trim()
-            .parse::<f32>()
-            .map_err(|_| ParseError::InvalidNumber(parts[1].to_string()))?;
-        let z = parts[2]
-            .trim()
-            .parse::<f32>()
-            .map_err(|_| ParseError::InvalidNumber(parts[2].to_string()))?;
+        points.transform = xform_ops.transform();
+        points.transform_samples = xform_ops.samples();
 
-        Ok(Vec3::new(x, y, z))
+        Ok(points)
     }
 
-    /// Parse an inline float value.
-    fn parse_inline_float(&self, line: &str) -> ParseResult<f32> {
-        // Find = and parse what comes after
-        let eq_pos = line.find('=').ok_or_else(|| ParseError::Parse {
-            line: self.current_line,
-            message: "Expected '='".to_string(),
+    /// Parse the content of a UsdLux light prim.
+    ///
+    /// Inputs not authored keep UsdLux fallback values. Both `inputs:`
+    /// names and the unprefixed names of older USD versions are read.
+    fn parse_light_content(
+        &mut self,
+        path: &str,
+        name: &str,
+        prim_type: &str,
+        start_line: usize,
+    ) -> ParseResult<UsdLight> {
+        let kind = LightKind::from_type_name(prim_type).ok_or_else(|| ParseError::Parse {
+            line: start_line,
+            message: format!("Not a light prim type: {}", prim_type),
         })?;
+        let mut light = UsdLight {
+            path: path.to_string(),
+            light: Light::new(name, kind),
+            transform_samples: None,
+            visibility: UsdVisibility::default(),
+        };
 
-        let value_str = line[eq_pos + 1..].trim();
-        value_str
+        let mut xform_ops = XformOps::default();
+
+        loop {
+            let (line_num, line) = match self.lines.pop_front() {
+                Some(x) => x,
+                None => return Err(ParseError::UnclosedBlock(start_line)),
+            };
+
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if trimmed == "}" {
+                break;
+            }
+
+            // Child prims (light filters, portals) are parsed but dropped
+            if trimmed.starts_with("def ") {
+                self.lines.push_front((line_num, line));
+                self.parse_prim(path)?;
+                continue;
+            }
+
+            // Parse xformOps
+            if self.parse_xform_attribute(trimmed, &mut xform_ops)? {
+                continue;
+            }
+
+            if parse_visibility(trimmed, &mut light.visibility) {
+                continue;
+            }
+
+            let Some(attribute) = attribute_name(trimmed) else {
+                continue;
+            };
+            let attribute = attribute.strip_prefix("inputs:").unwrap_or(attribute);
+            let value = trimmed
+                .split_once('=')
+                .map_or("", |(_, value)| value.trim().trim_matches('"'));
+            let light = &mut light.light;
+
+            if let Some(input) = attribute.strip_prefix("shaping:") {
+                let shaping = light.shaping.get_or_insert_with(Default::default);
+                match input {
+                    "cone:angle" => shaping.cone_angle = self.parse_inline_float(trimmed)?,
+                    "cone:softness" => shaping.cone_softness = self.parse_inline_float(trimmed)?,
+                    "focus" => shaping.focus = self.parse_inline_float(trimmed)?,
+                    "focusTint" => shaping.focus_tint = self.parse_inline_vec3(trimmed)?,
+                    "ies:file" => shaping.ies_file = asset_path(value),
+                    "ies:angleScale" => {
+                        shaping.ies_angle_scale = self.parse_inline_float(trimmed)?
+                    }
+                    "ies:normalize" => shaping.ies_normalize = parse_bool(value),
+                    _ => {}
+                }
+                continue;
+            }
+
+            match (&mut light.kind, attribute) {
+                (_, "intensity") => light.intensity = self.parse_inline_float(trimmed)?,
+                (_, "exposure") => light.exposure = self.parse_inline_float(trimmed)?,
+                (_, "color") => light.color = self.parse_inline_vec3(trimmed)?,
+                (_, "enableColorTemperature") => light.enable_color_temperature = parse_bool(value),
+                (_, "colorTemperature") => {
+                    light.color_temperature = self.parse_inline_float(trimmed)?
+                }
+                (_, "normalize") => light.normalize = parse_bool(value),
+                (LightKind::Rect { .. } | LightKind::Dome { .. }, "texture:file") => {
+                    light.texture = asset_path(value)
+                }
+                (LightKind::Dome { texture_format }, "texture:format") => {
+                    match DomeTextureFormat::from_token(value) {
+                        Some(format) => *texture_format = format,
+                        None => log::warn!("Invalid texture format {:?} on {}", value, path),
+                    }
+                }
+                (LightKind::Sphere { radius, .. }, "radius")
+                | (LightKind::Disk { radius }, "radius")
+                | (LightKind::Cylinder { radius, .. }, "radius") => {
+                    *radius = self.parse_inline_float(trimmed)?
+                }
+                (LightKind::Rect { width, .. }, "width") => {
+                    *width = self.parse_inline_float(trimmed)?
+                }
+                (LightKind::Rect { height, .. }, "height") => {
+                    *height = self.parse_inline_float(trimmed)?
+                }
+                (LightKind::Cylinder { length, .. }, "length") => {
+                    *length = self.parse_inline_float(trimmed)?
+                }
+                (LightKind::Distant { angle }, "angle") => {
+                    *angle = self.parse_inline_float(trimmed)?
+                }
+                (LightKind::Sphere { treat_as_point, .. }, "treatAsPoint") => {
+                    *treat_as_point = parse_bool(value)
+                }
+                (LightKind::Cylinder { treat_as_line, .. }, "treatAsLine") => {
+                    *treat_as_line = parse_bool(value)
+                }
+                _ => {}
+            }
+        }
+
+        light.light.transform = xform_ops.transform();
+        light.transform_samples = xform_ops.samples();
+
+        Ok(light)
+    }
+
+    /// Parse the content of a UsdGeom camera prim.
+    ///
+    /// Attributes not authored keep UsdGeom fallback values. The camera is
+    /// named by its prim path, as cameras are selected by path.
+    fn parse_camera_content(&mut self, path: &str, start_line: usize) -> ParseResult<UsdCamera> {
+        let mut camera = UsdCamera {
+            path: path.to_string(),
+            camera: Camera::new(path),
+            transform_samples: None,
+        };
+
+        let mut xform_ops = XformOps::default();
+
+        loop {
+            let (line_num, line) = match self.lines.pop_front() {
+                Some(x) => x,
+                None => return Err(ParseError::UnclosedBlock(start_line)),
+            };
+
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if trimmed == "}" {
+                break;
+            }
+
+            // Child prims are parsed but dropped
+            if trimmed.starts_with("def ") {
+                self.lines.push_front((line_num, line));
+                self.parse_prim(path)?;
+                continue;
+            }
+
+            // Parse xformOps
+            if self.parse_xform_attribute(trimmed, &mut xform_ops)? {
+                continue;
+            }
+
+            let camera = &mut camera.camera;
+            match attribute_name(trimmed) {
+                Some("focalLength") => camera.focal_length = self.parse_inline_float(trimmed)?,
+                Some("horizontalAperture") => {
+                    camera.horizontal_aperture = self.parse_inline_float(trimmed)?
+                }
+                Some("verticalAperture") => {
+                    camera.vertical_aperture = self.parse_inline_float(trimmed)?
+                }
+                Some("clippingRange") => {
+                    camera.clipping_range = self.parse_inline_float2(trimmed)?
+                }
+                Some("fStop") => camera.f_stop = self.parse_inline_float(trimmed)?,
+                Some("focusDistance") => {
+                    camera.focus_distance = self.parse_inline_float(trimmed)?
+                }
+                Some("projection") if !trimmed.contains("\"perspective\"") => {
+                    log::warn!("Only perspective projection is supported on {}", path)
+                }
+                _ => {}
+            }
+        }
+
+        camera.camera.transform = xform_ops.transform();
+        camera.transform_samples = xform_ops.samples();
+
+        Ok(camera)
+    }
+
+    /// Parse the content of a UsdShade material.
+    ///
+    /// Shaders in nested node graphs are flattened into the material.
+    fn parse_material_content(
+        &mut self,
+        path: &str,
+        name: &str,
+        start_line: usize,
+    ) -> ParseResult<UsdMaterial> {
+        let mut material = UsdMaterial {
+            path: path.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        };
+
+        loop {
+            let (line_num, line) = match self.lines.pop_front() {
+                Some(x) => x,
+                None => return Err(ParseError::UnclosedBlock(start_line)),
+            };
+
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if trimmed == "}" {
+                break;
+            }
+
+            if trimmed.starts_with("def ") {
+                self.lines.push_front((line_num, line));
+                if let Some(child) = self.parse_prim(path)? {
+                    collect_shaders(child, &mut material.shaders);
+                }
+                continue;
+            }
+
+            if attribute_name(trimmed) == Some("outputs:surface.connect") {
+                material.surface = parse_connection(trimmed);
+            }
+        }
+
+        Ok(material)
+    }
+
+    /// Parse the content of a UsdShade shader.
+    fn parse_shader_content(&mut self, path: &str, start_line: usize) -> ParseResult<UsdShader> {
+        let mut shader = UsdShader {
+            path: path.to_string(),
+            ..Default::default()
+        };
+
+        loop {
+            let (line_num, line) = match self.lines.pop_front() {
+                Some(x) => x,
+                None => return Err(ParseError::UnclosedBlock(start_line)),
+            };
+
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if trimmed == "}" {
+                break;
+            }
+
+            // Child prims are parsed but dropped
+            if trimmed.starts_with("def ") {
+                self.lines.push_front((line_num, line));
+                self.parse_prim(path)?;
+                continue;
+            }
+
+            let Some(attribute) = attribute_name(trimmed) else {
+                continue;
+            };
+
+            if attribute == "info:id" {
+                shader.id = trimmed
+                    .split_once('=')
+                    .map_or("", |(_, value)| value.trim().trim_matches('"'))
+                    .to_string();
+                continue;
+            }
+
+            let Some(input) = attribute.strip_prefix("inputs:") else {
+                continue;
+            };
+            if let Some(input) = input.strip_suffix(".connect") {
+                let input = shader_input(&mut shader, input, declared_type(trimmed));
+                input.connection = parse_connection(trimmed);
+            } else if !input.contains('.') {
+                let value = self.parse_shader_value(trimmed)?;
+                shader_input(&mut shader, input, declared_type(trimmed)).value = value;
+            }
+        }
+
+        Ok(shader)
+    }
+
+    /// Parse the content of a GeomSubset.
+    fn parse_geom_subset_content(
+        &mut self,
+        path: &str,
+        start_line: usize,
+    ) -> ParseResult<UsdGeomSubset> {
+        let mut subset = UsdGeomSubset {
+            path: path.to_string(),
+            ..Default::default()
+        };
+
+        loop {
+            let (_, line) = match self.lines.pop_front() {
+                Some(x) => x,
+                None => return Err(ParseError::UnclosedBlock(start_line)),
+            };
+
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if trimmed == "}" {
+                break;
+            }
+
+            if parse_material_binding(trimmed, &mut subset.material_binding) {
+                continue;
+            }
+
+            match attribute_name(trimmed) {
+                Some("indices") => subset.indices = self.parse_number_array(trimmed)?,
+                Some("familyName") => {
+                    subset.family_name = trimmed
+                        .split_once('=')
+                        .map_or("", |(_, value)| value.trim().trim_matches('"'))
+                        .to_string()
+                }
+                _ => {}
+            }
+        }
+
+        Ok(subset)
+    }
+
+    /// Parse a shader input value by its declared type.
+    ///
+    /// Returns `None` for types without a [`ShadingValue`] (e.g. `float4`).
+    fn parse_shader_value(&self, line: &str) -> ParseResult<Option<ShadingValue>> {
+        let value = line.split_once('=').map_or("", |(_, value)| value);
+        let value = match declared_type(line) {
+            "float" | "double" | "half" | "int" => {
+                ShadingValue::Float(self.parse_inline_float(line)?)
+            }
+            "bool" => ShadingValue::Float(if parse_bool(value.trim()) { 1.0 } else { 0.0 }),
+            "float2" | "double2" | "half2" | "texCoord2f" => {
+                let (x, y) = self.parse_inline_float2(line)?;
+                ShadingValue::Vector2([x, y])
+            }
+            "float3" | "double3" | "half3" | "color3f" | "color3d" | "normal3f" | "vector3f"
+            | "point3f" => ShadingValue::Vector3(self.parse_inline_vec3(line)?),
+            "float4" | "double4" | "half4" | "color4f" | "color4d" => {
+                ShadingValue::Vector4(self.parse_inline_float4(line)?)
+            }
+            "asset" => match asset_path(value) {
+                Some(path) => ShadingValue::String(path),
+                None => return Ok(None),
+            },
+            "token" | "string" => ShadingValue::String(value.trim().trim_matches('"').to_string()),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(value))
+    }
+
+    /// Parse PointInstancer content.
+    fn parse_point_instancer_content(
+        &mut self,
+        path: &str,
+        name: &str,
+        start_line: usize,
+    ) -> ParseResult<UsdPointInstancer> {
+        let mut instancer = UsdPointInstancer {
+            path: path.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        };
+
+        let mut xform_ops = XformOps::default();
+
+        loop {
+            let (line_num, line) = match self.lines.pop_front() {
+                Some(x) => x,
+                None => return Err(ParseError::UnclosedBlock(start_line)),
+            };
+
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if trimmed == "}" {
+                break;
+            }
+
+            // Parse xformOps
+            if self.parse_xform_attribute(trimmed, &mut xform_ops)? {
+                continue;
+            }
+
+            if parse_visibility(trimmed, &mut instancer.visibility) {
+                continue;
+            }
+
+            if parse_material_binding(trimmed, &mut instancer.material_binding) {
+                continue;
+            }
+
+            // Parse animated instance arrays
+            match attribute_name(trimmed) {
+                Some("positions.timeSamples") => {
+                    instancer.positions_samples =
+                        self.parse_samples(trimmed, Self::parse_vec3_array)?;
+                    continue;
+                }
+                Some("orientations.timeSamples") => {
+                    instancer.orientations_samples =
+                        self.parse_samples(trimmed, Self::parse_quat_array)?;
+                    continue;
+                }
+                Some("scales.timeSamples") => {
+                    instancer.scales_samples =
+                        self.parse_samples(trimmed, Self::parse_vec3_array)?;
+                    continue;
+                }
+                _ => {}
+            }
+
+            // Parse positions
+            if (trimmed.contains("point3f[]") || trimmed.contains("float3[]"))
+                && trimmed.contains("positions")
+            {
+                instancer.positions = self.parse_vec3_array(trimmed)?;
+                continue;
+            }
+
+            // Parse protoIndices
+            if trimmed.contains("protoIndices") {
+                instancer.proto_indices = self.parse_number_array(trimmed)?;
+                continue;
+            }
+
+            // Parse orientations (quaternions)
+            if trimmed.contains("orientations") {
+                instancer.orientations = Some(self.parse_quat_array(trimmed)?);
+                continue;
+            }
+
+            // Parse scales
+            if (trimmed.contains("float3[]") || trimmed.contains("point3f[]"))
+                && trimmed.contains("scales")
+            {
+                instancer.scales = Some(self.parse_vec3_array(trimmed)?);
+                continue;
+            }
+
+            // Parse prototypes relationship
+            if trimmed.contains("rel prototypes") {
+                instancer.prototypes = self.parse_rel_array(trimmed)?;
+                continue;
+            }
+
+            // Check for child prim (inline prototypes)
+            if trimmed.starts_with("def ") {
+                self.lines.push_front((line_num, line));
+                if let Some(child) = self.parse_prim(path)? {
+                    instancer.children.push(child);
+                }
+                continue;
+            }
+        }
+
+        instancer.transform = xform_ops.transform();
+        instancer.transform_samples = xform_ops.samples();
+
+        // Without authored values the first samples stand in
+        if let (true, Some(samples)) =
+            (instancer.positions.is_empty(), &instancer.positions_samples)
+        {
+            instancer.positions = samples.first().clone();
+        }
+        if instancer.orientations.is_none() {
+            instancer.orientations = instancer
+                .orientations_samples
+                .as_ref()
+                .map(|samples| samples.first().clone());
+        }
+        if instancer.scales.is_none() {
+            instancer.scales = instancer
+                .scales_samples
+                .as_ref()
+                .map(|samples| samples.first().clone());
+        }
+
+        Ok(instancer)
+    }
+
+    /// Parse an xformOp authored as a value or as time samples into `ops`.
+    /// Returns false for other lines.
+    fn parse_xform_attribute(&self, line: &str, ops: &mut XformOps) -> ParseResult<bool> {
+        let name = attribute_name(line).unwrap_or_default();
+
+        if let Some(op_name) = name
+            .strip_suffix(TIME_SAMPLES)
+            .filter(|op_name| op_name.starts_with("xformOp:"))
+        {
+            let mut samples = Vec::new();
+            for (time, value) in time_samples(line) {
+                if let Some(op) = self.parse_xform_op(&format!("{} = {}", op_name, value))? {
+                    samples.push((time, op));
+                }
+            }
+            ops.entry(op_name).samples = AnimationCurve::new(samples);
+            return Ok(true);
+        }
+
+        let Some(op) = self.parse_xform_op(line)? else {
+            return Ok(false);
+        };
+        ops.entry(name).value = Some(op);
+        Ok(true)
+    }
+
+    /// Parse a single xformOp attribute.
+    fn parse_xform_op(&self, line: &str) -> ParseResult<Option<XformOp>> {
+        // Skip xformOpOrder - it just lists the order of ops, not actual values
+        if line.contains("xformOpOrder") {
+            return Ok(None);
+        }
+
+        // xformOp:translate = (1, 2, 3)
+        if line.contains("xformOp:translate") && line.contains('=') && line.contains('(') {
+            let vec = self.parse_inline_vec3(line)?;
+            return Ok(Some(XformOp::Translate(vec)));
+        }
+
+        // xformOp:rotateX = 90
+        if line.contains("xformOp:rotateX")
+            && line.contains('=')
+            && !line.contains("xformOp:rotateXYZ")
+        {
+            let val = self.parse_inline_float(line)?;
+            return Ok(Some(XformOp::RotateX(val)));
+        }
+
+        // xformOp:rotateY = 90
+        if line.contains("xformOp:rotateY") && line.contains('=') {
+            let val = self.parse_inline_float(line)?;
+            return Ok(Some(XformOp::RotateY(val)));
+        }
+
+        // xformOp:rotateZ = 90
+        if line.contains("xformOp:rotateZ") && line.contains('=') {
+            let val = self.parse_inline_float(line)?;
+            return Ok(Some(XformOp::RotateZ(val)));
+        }
+
+        // xformOp:rotateXYZ = (0, 45, 0)
+        if line.contains("xformOp:rotateXYZ") && line.contains('=') {
+            let vec = self.parse_inline_vec3(line)?;
+            return Ok(Some(XformOp::RotateXYZ(vec)));
+        }
+
+        // xformOp:scale = (1, 1, 1)
+        if line.contains("xformOp:scale") && line.contains('=') {
+            let vec = self.parse_inline_vec3(line)?;
+            return Ok(Some(XformOp::Scale(vec)));
+        }
+
+        Ok(None)
+    }
+
+    /// Parse an inline Vec3 value like (1, 2, 3).
+    /// For lines with xformOp, finds the value after the xformOp's = sign.
+    fn parse_inline_vec3(&self, line: &str) -> ParseResult<Vec3> {
+        // Find the xformOp pattern and look for = after it
+        // This handles lines like: { double3 xformOp:translate = (0, 0, 0) }
+        // where there might be other ( ) pairs earlier in the line
+        let search_start = if let Some(xform_pos) = line.find("xformOp:") {
+            // Find the = after the xformOp
+            if let Some(eq_offset) = line[xform_pos..].find('=') {
+                xform_pos + eq_offset
+            } else {
+                0
+            }
+        } else {
+            line.find('=').unwrap_or_default()
+        };
+
+        let search_str = &line[search_start..];
+
+        // Find parentheses in the portion after the relevant =
+        let start = search_str.find('(').ok_or_else(|| ParseError::Parse {
+            line: self.current_line,
+            message: format!("Expected '(' in: {}", line),
+        })?;
+
+        let end = search_str.find(')').ok_or_else(|| ParseError::Parse {
+            line: self.current_line,
+            message: format!("Expected ')' in: {}", line),
+        })?;
+
+        let inner = &search_str[start + 1..end];
+        let parts: Vec<&str> = inner.split(',').collect();
+
+        if parts.len() != 3 {
+            return Err(ParseError::Parse {
+                line: self.current_line,
+                message: format!("Expected 3 components, got {}", parts.len()),
+            });
+        }
+
+        let x = parts[0]
+            .trim()
+            .parse::<f32>()
+            .map_err(|_| ParseError::InvalidNumber(parts[0].to_string()))?;
+        let y = parts[1]
+            .trim()
+            .parse::<f32>()
+            .map_err(|_| ParseError::InvalidNumber(parts[1].to_string()))?;
+        let z = parts[2]
+            .trim()
+            .parse::<f32>()
+            .map_err(|_| ParseError::InvalidNumber(parts[2].to_string()))?;
+
+        Ok(Vec3::new(x, y, z))
+    }
+
+    /// Parse an inline float value.
+    fn parse_inline_float(&self, line: &str) -> ParseResult<f32> {
+        // Find = and parse what comes after
+        let eq_pos = line.find('=').ok_or_else(|| ParseError::Parse {
+            line: self.current_line,
+            message: "Expected '='".to_string(),
+        })?;
+
+        let value_str = line[eq_pos + 1..].trim();
+        value_str
             .parse::<f32>()
             .map_err(|_| ParseError::InvalidNumber(value_str.to_string()))
     }
 
+    /// Parse an inline float2 value like (1, 1000).
+    fn parse_inline_float2(&self, line: &str) -> ParseResult<(f32, f32)> {
+        let value = line.split_once('=').map_or("", |(_, value)| value.trim());
+        let inner = value
+            .strip_prefix('(')
+            .and_then(|value| value.strip_suffix(')'))
+            .ok_or_else(|| ParseError::Parse {
+                line: self.current_line,
+                message: format!("Expected (x, y) in: {}", line),
+            })?;
+
+        let parse = |part: &str| {
+            part.trim()
+                .parse::<f32>()
+                .map_err(|_| ParseError::InvalidNumber(part.to_string()))
+        };
+        match inner.split_once(',') {
+            Some((x, y)) => Ok((parse(x)?, parse(y)?)),
+            None => Err(ParseError::Parse {
+                line: self.current_line,
+                message: format!("Expected 2 components in: {}", line),
+            }),
+        }
+    }
+
+    /// Parse an inline 4-component value like `(1, 2, 3, 4)`.
+    fn parse_inline_float4(&self, line: &str) -> ParseResult<[f32; 4]> {
+        let value = line.split_once('=').map_or("", |(_, value)| value.trim());
+        let inner = value
+            .strip_prefix('(')
+            .and_then(|value| value.strip_suffix(')'))
+            .ok_or_else(|| ParseError::Parse {
+                line: self.current_line,
+                message: format!("Expected (x, y, z, w) in: {}", line),
+            })?;
+
+        let parts: Vec<&str> = inner.split(',').collect();
+        let [x, y, z, w] = parts[..] else {
+            return Err(ParseError::Parse {
+                line: self.current_line,
+                message: format!("Expected 4 components in: {}", line),
+            });
+        };
+        let parse = |part: &str| {
+            part.trim()
+                .parse::<f32>()
+                .map_err(|_| ParseError::InvalidNumber(part.to_string()))
+        };
+        Ok([parse(x)?, parse(y)?, parse(z)?, parse(w)?])
+    }
+
     /// Parse a Vec3 array like [(1, 2, 3), (4, 5, 6), ...].
     fn parse_vec3_array(&mut self, first_line: &str) -> ParseResult<Vec<Vec3>> {
         let mut result = Vec::new();
@@ -937,8 +1820,8 @@ impl UsdaParser {
         Ok(result)
     }
 
-    /// Parse an int array like [1, 2, 3, ...].
-    fn parse_int_array(&mut self, first_line: &str) -> ParseResult<Vec<i32>> {
+    /// Parse a number array like [1, 2, 3, ...].
+    fn parse_number_array<T: FromStr>(&mut self, first_line: &str) -> ParseResult<Vec<T>> {
         let mut content = String::new();
 
         // Find the = sign first, then look for [ after it
@@ -966,10 +1849,10 @@ impl UsdaParser {
         let end = content.find(']').unwrap_or(content.len());
         let inner = &content[start..end];
 
-        // Parse comma-separated integers
-        let result: Vec<i32> = inner
+        // Parse comma-separated numbers
+        let result: Vec<T> = inner
             .split(',')
-            .filter_map(|s| s.trim().parse::<i32>().ok())
+            .filter_map(|s| s.trim().parse::<T>().ok())
             .collect();
 
         Ok(result)
@@ -1076,6 +1959,263 @@ impl UsdaParser {
 
         Ok(result)
     }
+
+    /// Parse a `name.timeSamples = { time: value, ... }` line, reading each
+    /// value with `parse_value` (which gets it as `= value`).
+    fn parse_samples<T: Interpolate>(
+        &mut self,
+        line: &str,
+        parse_value: impl Fn(&mut Self, &str) -> ParseResult<T>,
+    ) -> ParseResult<Option<AnimationCurve<T>>> {
+        let mut keys = Vec::new();
+        for (time, value) in time_samples(line) {
+            keys.push((time, parse_value(self, &format!("= {}", value))?));
+        }
+        Ok(AnimationCurve::new(keys))
+    }
+}
+
+/// xformOps of a prim in authored order, each with its value and/or time
+/// samples.
+#[derive(Default)]
+struct XformOps {
+    ops: Vec<AuthoredXformOp>,
+}
+
+struct AuthoredXformOp {
+    name: String,
+    value: Option<XformOp>,
+    samples: Option<AnimationCurve<XformOp>>,
+}
+
+impl XformOps {
+    /// The op named `name`, added if not authored yet.
+    fn entry(&mut self, name: &str) -> &mut AuthoredXformOp {
+        let index = match self.ops.iter().position(|op| op.name == name) {
+            Some(index) => index,
+            None => {
+                self.ops.push(AuthoredXformOp {
+                    name: name.to_string(),
+                    value: None,
+                    samples: None,
+                });
+                self.ops.len() - 1
+            }
+        };
+        &mut self.ops[index]
+    }
+
+    /// Transform from the authored values (the first sample of ops with
+    /// only time samples).
+    fn transform(&self) -> Mat4 {
+        let ops: Vec<XformOp> = self
+            .ops
+            .iter()
+            .filter_map(|op| {
+                op.value
+                    .clone()
+                    .or_else(|| op.samples.as_ref().map(|samples| samples.first().clone()))
+            })
+            .collect();
+        compose_xform_ops(&ops)
+    }
+
+    /// Transform at every sample time of any op (`None` if none is animated).
+    fn samples(&self) -> Option<AnimationCurve<Mat4>> {
+        let mut times: Vec<f64> = self
+            .ops
+            .iter()
+            .filter_map(|op| op.samples.as_ref())
+            .flat_map(|samples| samples.times())
+            .collect();
+        times.sort_by(f64::total_cmp);
+        times.dedup();
+
+        let keys = times
+            .into_iter()
+            .map(|time| {
+                let ops: Vec<XformOp> = self
+                    .ops
+                    .iter()
+                    .filter_map(|op| match &op.samples {
+                        Some(samples) => Some(samples.evaluate(time)),
+                        None => op.value.clone(),
+                    })
+                    .collect();
+                (time, compose_xform_ops(&ops))
+            })
+            .collect();
+        AnimationCurve::new(keys)
+    }
+}
+
+/// Net number of braces opened on `line`.
+fn brace_depth(line: &str) -> i32 {
+    line.matches('{').count() as i32 - line.matches('}').count() as i32
+}
+
+/// Split a `name.timeSamples = { time: value, ... }` line into
+/// `(time, value)` pairs. Blocked samples (`None`) are skipped.
+fn time_samples(line: &str) -> Vec<(f64, &str)> {
+    let Some((_, block)) = line.split_once('=') else {
+        return Vec::new();
+    };
+    let block = block.trim().trim_start_matches('{').trim_end_matches('}');
+
+    // Split on the commas between samples, not those inside values
+    let mut entries = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in block.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                entries.push(&block[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    entries.push(&block[start..]);
+
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let (time, value) = entry.split_once(':')?;
+            let value = value.trim();
+            if value == "None" {
+                return None;
+            }
+            Some((time.trim().parse().ok()?, value))
+        })
+        .collect()
+}
+
+/// Apply `startTimeCode`, `endTimeCode` or `timeCodesPerSecond` stage
+/// metadata on `line` to `time_codes`.
+fn parse_time_codes(line: &str, time_codes: &mut TimeCodes) {
+    let line = line.trim_matches(|c| c == '(' || c == ')');
+    let Some((key, value)) = line.split_once('=') else {
+        return;
+    };
+    let Ok(value) = value.trim().parse::<f64>() else {
+        return;
+    };
+    match key.trim() {
+        "startTimeCode" => time_codes.start = value,
+        "endTimeCode" => time_codes.end = value,
+        "timeCodesPerSecond" => time_codes.per_second = value,
+        _ => {}
+    }
+}
+
+/// Name of the attribute authored on `line`, e.g. "radius" for
+/// `double radius = 0.5`.
+fn attribute_name(line: &str) -> Option<&str> {
+    let (declaration, _) = line.split_once('=')?;
+    declaration.split_whitespace().last()
+}
+
+/// Value type of an attribute line, like `color3f` in
+/// `color3f inputs:diffuseColor = (1, 0, 0)` (empty if there is none).
+fn declared_type(line: &str) -> &str {
+    let (declaration, _) = line.split_once('=').unwrap_or((line, ""));
+    let mut words = declaration.split_whitespace().rev();
+    words.next(); // The attribute name
+    words.next().unwrap_or_default()
+}
+
+/// Value of a `bool` attribute (`1`/`0` or `true`/`false`).
+fn parse_bool(value: &str) -> bool {
+    !matches!(value, "0" | "false")
+}
+
+/// Path of an `asset` value like `@./sky.exr@`; `None` if empty.
+fn asset_path(value: &str) -> Option<String> {
+    let path = value.trim().trim_start_matches('@').trim_end_matches('@');
+    (!path.is_empty()).then(|| path.to_string())
+}
+
+/// Source of a `.connect` attribute line like
+/// `color3f inputs:diffuseColor.connect = </Looks/Wood/Tex.outputs:rgb>`.
+fn parse_connection(line: &str) -> Option<UsdConnection> {
+    let (_, value) = line.split_once('=')?;
+    UsdConnection::parse(value)
+}
+
+/// Input `name` of a shader declared as `type_name`, added if it is not
+/// authored yet.
+fn shader_input<'a>(
+    shader: &'a mut UsdShader,
+    name: &str,
+    type_name: &str,
+) -> &'a mut UsdShaderInput {
+    let index = match shader.inputs.iter().position(|input| input.name == name) {
+        Some(index) => index,
+        None => {
+            shader.inputs.push(UsdShaderInput {
+                name: name.to_string(),
+                type_name: type_name.to_string(),
+                ..Default::default()
+            });
+            shader.inputs.len() - 1
+        }
+    };
+    &mut shader.inputs[index]
+}
+
+/// Add the shaders of a material child, looking inside node graphs.
+fn collect_shaders(prim: UsdPrim, shaders: &mut Vec<UsdShader>) {
+    match prim {
+        UsdPrim::Shader(shader) => shaders.push(shader),
+        UsdPrim::Xform(node_graph) => {
+            for child in node_graph.children {
+                collect_shaders(child, shaders);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Apply a `rel material:binding = </Looks/Wood>` line to `binding`.
+/// Returns false for other lines.
+fn parse_material_binding(line: &str, binding: &mut Option<String>) -> bool {
+    if !line.starts_with("rel ") || attribute_name(line) != Some("material:binding") {
+        return false;
+    }
+    *binding = line
+        .split_once('<')
+        .and_then(|(_, target)| target.split_once('>'))
+        .map(|(path, _)| path.to_string())
+        .filter(|path| !path.is_empty());
+    true
+}
+
+/// Apply a `visibility`, `purpose` or `primvars:bif:visibility:<ray>`
+/// attribute line to `visibility`. Returns false for other lines.
+fn parse_visibility(line: &str, visibility: &mut UsdVisibility) -> bool {
+    let Some(attribute) = attribute_name(line) else {
+        return false;
+    };
+    let value = line
+        .split_once('=')
+        .map_or("", |(_, value)| value.trim().trim_matches('"'));
+
+    match attribute {
+        "visibility" => visibility.visible = value != "invisible",
+        "purpose" => visibility.visible = !matches!(value, "guide" | "proxy"),
+        _ => {
+            let Some(flag) = attribute
+                .strip_prefix("primvars:bif:visibility:")
+                .and_then(Visibility::from_name)
+            else {
+                return false;
+            };
+            visibility.rays.set(flag, parse_bool(value));
+        }
+    }
+    true
 }
 
 /// Parse a USDA string and return the list of root prims.
@@ -1088,6 +2228,44 @@ pub fn parse_usda(content: &str) -> ParseResult<Vec<UsdPrim>> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_visibility() {
+        let usda = r#"
+def Xform "World" {
+    token visibility = "invisible"
+
+    def Mesh "Floor" {
+        uniform token purpose = "render"
+        bool primvars:bif:visibility:camera = 0
+        bool primvars:bif:visibility:transmission = false
+        bool primvars:bif:visibility:shadow = 1
+        point3f[] points = [(0, 0, 0), (1, 0, 0), (0, 0, 1)]
+        int[] faceVertexCounts = [3]
+        int[] faceVertexIndices = [0, 1, 2]
+    }
+}
+"#;
+
+        let prims = parse_usda(usda).unwrap();
+        if let UsdPrim::Xform(world) = &prims[0] {
+            assert!(!world.visibility.visible);
+            assert_eq!(world.visibility.mask(), Visibility::NONE);
+
+            if let UsdPrim::Mesh(floor) = &world.children[0] {
+                assert_eq!(floor.points.len(), 3);
+                assert!(floor.visibility.visible);
+                assert_eq!(
+                    floor.visibility.rays,
+                    Visibility::SHADOW | Visibility::DIFFUSE | Visibility::SPECULAR
+                );
+            } else {
+                panic!("Expected Mesh prim");
+            }
+        } else {
+            panic!("Expected Xform prim");
+        }
+    }
+
     #[test]
     fn test_parse_simple_mesh() {
         let usda = r#"
@@ -1111,6 +2289,48 @@ def Mesh "Cube" {
         }
     }
 
+    #[test]
+    fn test_parse_subdivision_tags() {
+        let usda = r#"
+def Mesh "Cage" {
+    point3f[] points = [(0, 0, 0), (1, 0, 0), (1, 1, 0), (0, 1, 0)]
+    int[] faceVertexCounts = [4]
+    int[] faceVertexIndices = [0, 1, 2, 3]
+    uniform token subdivisionScheme = "catmullClark"
+    int[] creaseIndices = [0, 1, 2]
+    int[] creaseLengths = [3]
+    float[] creaseSharpnesses = [2.5]
+    int[] cornerIndices = [3]
+    float[] cornerSharpnesses = [10]
+}
+def Mesh "Loop" {
+    point3f[] points = [(0, 0, 0), (1, 0, 0), (1, 1, 0)]
+    int[] faceVertexCounts = [3]
+    int[] faceVertexIndices = [0, 1, 2]
+    uniform token subdivisionScheme = "loop"
+}
+"#;
+
+        let prims = parse_usda(usda).unwrap();
+
+        let UsdPrim::Mesh(cage) = &prims[0] else {
+            panic!("Expected Mesh prim");
+        };
+        assert_eq!(cage.subdivision_scheme, SubdivisionScheme::CatmullClark);
+        let tags = &cage.subdivision_tags;
+        assert_eq!(tags.crease_indices, vec![0, 1, 2]);
+        assert_eq!(tags.crease_lengths, vec![3]);
+        assert_eq!(tags.crease_sharpnesses, vec![2.5]);
+        assert_eq!(tags.corner_indices, vec![3]);
+        assert_eq!(tags.corner_sharpnesses, vec![10.0]);
+
+        // Schemes we can't refine fall back to the cage
+        let UsdPrim::Mesh(other) = &prims[1] else {
+            panic!("Expected Mesh prim");
+        };
+        assert_eq!(other.subdivision_scheme, SubdivisionScheme::None);
+    }
+
     #[test]
     fn test_parse_xform_with_ops() {
         let usda = r#"
@@ -1134,6 +2354,126 @@ def Xform "Model" {
         }
     }
 
+    #[test]
+    fn test_parse_shapes() {
+        let usda = r#"
+def Xform "World" {
+    def Cylinder "Pillar" {
+        double radius = 0.25
+        double height = 3
+        uniform token axis = "Y"
+        float3[] extent = [(-0.25, -1.5, -0.25), (0.25, 1.5, 0.25)]
+        double3 xformOp:translate = (1, 0, 0)
+        uniform token[] xformOpOrder = ["xformOp:translate"]
+    }
+    def Cube "Box" {
+    }
+}
+"#;
+
+        let prims = parse_usda(usda).unwrap();
+        let UsdPrim::Xform(world) = &prims[0] else {
+            panic!("Expected Xform prim");
+        };
+
+        if let UsdPrim::Shape(pillar) = &world.children[0] {
+            assert_eq!(pillar.path, "/World/Pillar");
+            assert_eq!(
+                pillar.kind,
+                UsdShapeKind::Cylinder {
+                    radius: 0.25,
+                    height: 3.0
+                }
+            );
+            assert_eq!(pillar.axis, UsdAxis::Y);
+            let translated = pillar.transform.transform_point3(Vec3::ZERO);
+            assert!((translated - Vec3::X).length() < 0.001);
+        } else {
+            panic!("Expected Shape prim");
+        }
+
+        // Unauthored dimensions keep USD's fallbacks
+        if let UsdPrim::Shape(cube) = &world.children[1] {
+            assert_eq!(cube.kind, UsdShapeKind::Cube { size: 2.0 });
+        } else {
+            panic!("Expected Shape prim");
+        }
+    }
+
+    #[test]
+    fn test_parse_basis_curves() {
+        let usda = r#"
+def BasisCurves "Hair" {
+    uniform token type = "cubic"
+    uniform token basis = "catmullRom"
+    int[] curveVertexCounts = [4, 5]
+    point3f[] points = [(0, 0, 0), (0, 1, 0), (0, 2, 0), (0, 3, 0),
+        (1, 0, 0), (1, 1, 0), (1, 2, 0), (1, 3, 0), (1, 4, 0)]
+    float[] widths = [0.02] (
+        interpolation = "constant"
+    )
+    double3 xformOp:translate = (0, 0, 5)
+    uniform token[] xformOpOrder = ["xformOp:translate"]
+}
+def BasisCurves "Grass" {
+    uniform token type = "linear"
+    int[] curveVertexCounts = [2]
+    point3f[] points = [(0, 0, 0), (0, 1, 0)]
+    normal3f[] normals = [(0, 0, 1), (0, 0, 1)]
+}
+"#;
+
+        let prims = parse_usda(usda).unwrap();
+        let UsdPrim::BasisCurves(hair) = &prims[0] else {
+            panic!("Expected BasisCurves prim");
+        };
+        assert_eq!(hair.path, "/Hair");
+        assert_eq!(hair.curve_vertex_counts, vec![4, 5]);
+        assert_eq!(hair.points.len(), 9);
+        assert_eq!(hair.widths, vec![0.02]);
+        assert_eq!(hair.basis, "catmullRom");
+        assert!(!hair.has_normals);
+        let translated = hair.transform.transform_point3(Vec3::ZERO);
+        assert!((translated.z - 5.0).abs() < 0.001);
+
+        let curves = hair.to_curves();
+        assert_eq!(curves.basis, crate::curves::CurveBasis::CatmullRom);
+        assert_eq!(curves.segment_count(), 3);
+
+        let UsdPrim::BasisCurves(grass) = &prims[1] else {
+            panic!("Expected BasisCurves prim");
+        };
+        assert_eq!(grass.curve_type, "linear");
+        assert_eq!(grass.to_curves().shape, crate::curves::CurveShape::Ribbon);
+    }
+
+    #[test]
+    fn test_parse_points() {
+        let usda = r#"
+def Points "Particles" {
+    point3f[] points = [(0, 0, 0), (1, 0, 0), (2, 0, 0)]
+    float[] widths = [0.1, 0.2, 0.3] (
+        interpolation = "vertex"
+    )
+    double3 xformOp:translate = (0, 1, 0)
+    uniform token[] xformOpOrder = ["xformOp:translate"]
+}
+"#;
+
+        let prims = parse_usda(usda).unwrap();
+        let UsdPrim::Points(particles) = &prims[0] else {
+            panic!("Expected Points prim");
+        };
+        assert_eq!(particles.path, "/Particles");
+        assert_eq!(particles.points.len(), 3);
+        assert_eq!(particles.widths, vec![0.1, 0.2, 0.3]);
+        let translated = particles.transform.transform_point3(Vec3::ZERO);
+        assert!((translated.y - 1.0).abs() < 0.001);
+
+        let points = particles.to_points();
+        assert!((points.radius(2) - 0.15).abs() < 1e-6);
+    }
+
     #[test]
     fn test_parse_point_instancer() {
         let usda = r#"
@@ -1162,4 +2502,321 @@ def PointInstancer "Instances" {
             panic!("Expected PointInstancer prim");
         }
     }
+
+    #[test]
+    fn test_parse_lights() {
+        let usda = r#"
+def Xform "Lights" {
+    def RectLight "Key" (
+        prepend apiSchemas = ["ShapingAPI"]
+    )
+    {
+        float inputs:intensity = 20
+        float inputs:exposure = 1.5
+        color3f inputs:color = (1, 0.9, 0.8)
+        bool inputs:enableColorTemperature = 1
+        float inputs:colorTemperature = 4500
+        float inputs:width = 2
+        asset inputs:texture:file = @./gobo.png@
+        float inputs:shaping:cone:angle = 35
+        asset inputs:shaping:ies:file = @./spot.ies@
+        double3 xformOp:translate = (0, 5, 0)
+        uniform token[] xformOpOrder = ["xformOp:translate"]
+
+        def LightFilter "Blocker" {
+            float inputs:density = 1
+        }
+    }
+
+    def DomeLight "Sky" {
+        asset inputs:texture:file = @./sky.exr@
+        token inputs:texture:format = "latlong"
+    }
+
+    def SphereLight "Bulb" {
+        float intensity = 5
+        float radius = 0.1
+        bool treatAsPoint = true
+        token visibility = "invisible"
+    }
+}
+"#;
+
+        let prims = parse_usda(usda).unwrap();
+        let UsdPrim::Xform(lights) = &prims[0] else {
+            panic!("Expected Xform prim");
+        };
+        assert_eq!(lights.children.len(), 3);
+
+        let UsdPrim::Light(key) = &lights.children[0] else {
+            panic!("Expected Light prim");
+        };
+        assert_eq!(key.path, "/Lights/Key");
+        let light = &key.light;
+        assert_eq!(light.name, "Key");
+        assert_eq!(
+            light.kind,
+            LightKind::Rect {
+                width: 2.0,
+                height: 1.0
+            }
+        );
+        assert_eq!((light.intensity, light.exposure), (20.0, 1.5));
+        assert_eq!(light.color, Vec3::new(1.0, 0.9, 0.8));
+        assert!(light.enable_color_temperature);
+        assert_eq!(light.color_temperature, 4500.0);
+        assert_eq!(light.texture.as_deref(), Some("./gobo.png"));
+        let shaping = light.shaping.as_ref().unwrap();
+        assert_eq!(shaping.cone_angle, 35.0);
+        assert_eq!(shaping.cone_softness, 0.0);
+        assert_eq!(shaping.ies_file.as_deref(), Some("./spot.ies"));
+        assert_eq!(light.transform.w_axis.y, 5.0);
+
+        let UsdPrim::Light(sky) = &lights.children[1] else {
+            panic!("Expected Light prim");
+        };
+        assert_eq!(
+            sky.light.kind,
+            LightKind::Dome {
+                texture_format: DomeTextureFormat::Latlong
+            }
+        );
+        assert_eq!(sky.light.texture.as_deref(), Some("./sky.exr"));
+        assert!(sky.light.shaping.is_none());
+
+        // Inputs without the `inputs:` prefix are still read
+        let UsdPrim::Light(bulb) = &lights.children[2] else {
+            panic!("Expected Light prim");
+        };
+        assert_eq!(bulb.light.intensity, 5.0);
+        assert_eq!(
+            bulb.light.kind,
+            LightKind::Sphere {
+                radius: 0.1,
+                treat_as_point: true
+            }
+        );
+        assert!(!bulb.visibility.visible);
+    }
+
+    #[test]
+    fn test_parse_camera() {
+        let usda = r#"
+def Camera "Shot" {
+    float focalLength = 35
+    float horizontalAperture = 36
+    float verticalAperture = 24
+    float2 clippingRange = (0.1, 500)
+    float fStop = 2.8
+    float focusDistance = 12.5
+    double3 xformOp:translate = (0, 1, 10)
+    uniform token[] xformOpOrder = ["xformOp:translate"]
+}
+
+def Camera "Default" {
+}
+"#;
+
+        let prims = parse_usda(usda).unwrap();
+        let UsdPrim::Camera(shot) = &prims[0] else {
+            panic!("Expected Camera prim");
+        };
+        let camera = &shot.camera;
+        assert_eq!(camera.name, "/Shot");
+        assert_eq!(camera.focal_length, 35.0);
+        assert_eq!(
+            (camera.horizontal_aperture, camera.vertical_aperture),
+            (36.0, 24.0)
+        );
+        assert_eq!(camera.clipping_range, (0.1, 500.0));
+        assert_eq!((camera.f_stop, camera.focus_distance), (2.8, 12.5));
+        assert_eq!(camera.position(), Vec3::new(0.0, 1.0, 10.0));
+
+        // Unauthored attributes keep the UsdGeom fallbacks
+        let UsdPrim::Camera(default) = &prims[1] else {
+            panic!("Expected Camera prim");
+        };
+        assert_eq!(default.camera, Camera::new("/Default"));
+    }
+
+    #[test]
+    fn test_parse_material() {
+        let usda = r#"
+def Scope "Looks" {
+    def Material "Wood" {
+        token outputs:surface.connect = </Looks/Wood/Surface.outputs:surface>
+
+        def Shader "Surface" {
+            uniform token info:id = "UsdPreviewSurface"
+            color3f inputs:diffuseColor.connect = </Looks/Wood/Tex.outputs:rgb>
+            float inputs:roughness = 0.25
+            int inputs:useSpecularWorkflow = 0
+            token outputs:surface
+        }
+
+        def NodeGraph "Textures" {
+            def Shader "Mask" {
+                uniform token info:id = "UsdUVTexture"
+                asset inputs:file = @./mask.png@
+                float2 inputs:st = (0, 1)
+            }
+        }
+
+        def Shader "Tex" {
+            uniform token info:id = "UsdUVTexture"
+            asset inputs:file = @./wood.png@
+            token inputs:wrapS = "repeat"
+            float4 inputs:scale = (2, 2, 2, 1)
+            float3 outputs:rgb
+        }
+    }
+}
+
+def Mesh "Board" (
+    prepend apiSchemas = ["MaterialBindingAPI"]
+) {
+    point3f[] points = [(0, 0, 0), (1, 0, 0), (1, 1, 0), (0, 1, 0)]
+    int[] faceVertexCounts = [3, 3]
+    int[] faceVertexIndices = [0, 1, 2, 0, 2, 3]
+    rel material:binding = </Looks/Wood>
+
+    def GeomSubset "Knot" (
+        prepend apiSchemas = ["MaterialBindingAPI"]
+    ) {
+        uniform token elementType = "face"
+        uniform token familyName = "materialBind"
+        int[] indices = [1]
+        rel material:binding = </Looks/Knot>
+    }
+}
+"#;
+
+        let prims = parse_usda(usda).unwrap();
+        let UsdPrim::Xform(looks) = &prims[0] else {
+            panic!("Expected Scope prim");
+        };
+        let UsdPrim::Material(wood) = &looks.children[0] else {
+            panic!("Expected Material prim");
+        };
+        assert_eq!(wood.path, "/Looks/Wood");
+        assert_eq!(wood.shaders.len(), 3);
+        assert!(wood.shader("/Looks/Wood/Textures/Mask").is_some());
+
+        let surface = wood.surface_shader().unwrap();
+        assert_eq!(surface.id, "UsdPreviewSurface");
+        assert_eq!(surface.value("roughness"), Some(&ShadingValue::Float(0.25)));
+        assert_eq!(
+            surface.value("useSpecularWorkflow"),
+            Some(&ShadingValue::Float(0.0))
+        );
+        let diffuse = surface.input("diffuseColor").unwrap();
+        assert_eq!(diffuse.type_name, "color3f");
+        assert!(diffuse.value.is_none());
+        assert_eq!(
+            diffuse.connection,
+            Some(UsdConnection {
+                prim_path: "/Looks/Wood/Tex".to_string(),
+                output: "rgb".to_string(),
+            })
+        );
+
+        let texture = wood.shader("/Looks/Wood/Tex").unwrap();
+        assert_eq!(
+            texture.value("file"),
+            Some(&ShadingValue::String("./wood.png".to_string()))
+        );
+        assert_eq!(
+            texture.value("wrapS"),
+            Some(&ShadingValue::String("repeat".to_string()))
+        );
+        assert_eq!(
+            texture.value("scale"),
+            Some(&ShadingValue::Vector4([2.0, 2.0, 2.0, 1.0]))
+        );
+
+        let UsdPrim::Mesh(board) = &prims[1] else {
+            panic!("Expected Mesh prim");
+        };
+        assert_eq!(board.material_binding.as_deref(), Some("/Looks/Wood"));
+        assert_eq!(board.face_vertex_counts, vec![3, 3]);
+        assert_eq!(board.subsets.len(), 1);
+        let knot = &board.subsets[0];
+        assert_eq!(knot.path, "/Board/Knot");
+        assert_eq!(knot.family_name, "materialBind");
+        assert_eq!(knot.indices, vec![1]);
+        assert_eq!(knot.material_binding.as_deref(), Some("/Looks/Knot"));
+    }
+
+    #[test]
+    fn test_parse_time_samples() {
+        let usda = r#"#usda 1.0
+(
+    startTimeCode = 1
+    endTimeCode = 24
+    timeCodesPerSecond = 12
+)
+
+def Xform "Spinner" {
+    double3 xformOp:translate.timeSamples = {
+        1: (0, 0, 0),
+        24: (23, 0, 0),
+    }
+    float xformOp:rotateY = 90
+    uniform token[] xformOpOrder = ["xformOp:translate", "xformOp:rotateY"]
+
+    def Mesh "Blob" {
+        point3f[] points.timeSamples = {
+            1: [(0, 0, 0), (1, 0, 0),
+                (0, 1, 0)],
+            5: None,
+            11: [(0, 0, 0), (2, 0, 0), (0, 2, 0)],
+        }
+        int[] faceVertexCounts = [3]
+        int[] faceVertexIndices = [0, 1, 2]
+    }
+
+    def PointInstancer "Swarm" {
+        point3f[] positions.timeSamples = { 1: [(0, 0, 0)], 3: [(0, 4, 0)] }
+        quath[] orientations.timeSamples = { 1: [(0, 0, 0, 1)] }
+        int[] protoIndices = [0]
+    }
+}
+"#;
+
+        let mut parser = UsdaParser::new(usda);
+        let prims = parser.parse().unwrap();
+        let time_codes = parser.time_codes();
+        assert_eq!((time_codes.start, time_codes.end), (1.0, 24.0));
+        assert_eq!(time_codes.per_second, 12.0);
+
+        let UsdPrim::Xform(xform) = &prims[0] else {
+            panic!("Expected Xform prim");
+        };
+        // Static ops are composed into every sample
+        let samples = xform.transform_samples.as_ref().unwrap();
+        assert_eq!(samples.times().collect::<Vec<_>>(), vec![1.0, 24.0]);
+        let x = samples.evaluate(12.5).transform_point3(Vec3::Z);
+        assert!((x - Vec3::new(12.5, 0.0, 0.0)).length() < 1e-4, "{x}");
+        let x = xform.transform.transform_point3(Vec3::Z);
+        assert!((x - Vec3::X).length() < 1e-4, "{x}");
+
+        let UsdPrim::Mesh(mesh) = &xform.children[0] else {
+            panic!("Expected Mesh prim");
+        };
+        // The blocked sample is skipped; the first one stands in for points
+        let points = mesh.points_samples.as_ref().unwrap();
+        assert_eq!(points.keys().len(), 2);
+        assert_eq!(points.evaluate(6.0)[1], Vec3::new(1.5, 0.0, 0.0));
+        assert_eq!(mesh.points[1], Vec3::X);
+        assert_eq!(mesh.face_vertex_indices, vec![0, 1, 2]);
+
+        let UsdPrim::PointInstancer(instancer) = &xform.children[1] else {
+            panic!("Expected PointInstancer prim");
+        };
+        assert_eq!(instancer.positions, vec![Vec3::ZERO]);
+        assert_eq!(instancer.orientations, Some(vec![Quat::IDENTITY]));
+        assert_eq!(instancer.sample_times(), vec![1.0, 3.0]);
+        let matrices = instancer.instance_matrices_at(Some(2.0));
+        assert_eq!(matrices[0].w_axis.y, 2.0);
+    }
 }
@@ -0,0 +1,273 @@
+//! Tessellation of USD intrinsic shapes.
+//!
+//! `Cube`, `Sphere`, `Cylinder`, `Cone` and `Plane` prims are turned into
+//! triangle meshes with normals and UVs when loaded, so the viewport and
+//! Ivar see exactly the same surface as any other mesh prototype.
+
+use std::f32::consts::PI;
+
+use bif_math::Vec3;
+
+use crate::mesh::Mesh;
+use crate::usd::types::{UsdAxis, UsdShape, UsdShapeKind};
+
+/// Segments around the axis of round shapes.
+const SEGMENTS: u32 = 32;
+
+/// Latitude bands of a sphere.
+const RINGS: u32 = 16;
+
+impl UsdShape {
+    /// Tessellate the shape into a mesh in its local space.
+    pub fn to_mesh(&self) -> Mesh {
+        let mut builder = MeshBuilder::default();
+        let frame = axis_frame(self.axis);
+
+        match self.kind {
+            UsdShapeKind::Cube { size } => builder.cube(size * 0.5),
+            UsdShapeKind::Sphere { radius } => builder.sphere(radius, frame),
+            UsdShapeKind::Cylinder { radius, height } => {
+                builder.tube(radius, radius, height, frame);
+                builder.cap(radius, height * 0.5, frame);
+                builder.cap(radius, -height * 0.5, frame);
+            }
+            UsdShapeKind::Cone { radius, height } => {
+                builder.tube(radius, 0.0, height, frame);
+                builder.cap(radius, -height * 0.5, frame);
+            }
+            UsdShapeKind::Plane { width, length } => builder.plane(width, length, self.axis),
+        }
+
+        builder.finish()
+    }
+}
+
+/// Basis (a, b, axis) for shapes built around local +Z.
+///
+/// The three choices are cyclic permutations of XYZ, so the basis stays
+/// right-handed and triangle winding is preserved.
+fn axis_frame(axis: UsdAxis) -> [Vec3; 3] {
+    match axis {
+        UsdAxis::X => [Vec3::Y, Vec3::Z, Vec3::X],
+        UsdAxis::Y => [Vec3::Z, Vec3::X, Vec3::Y],
+        UsdAxis::Z => [Vec3::X, Vec3::Y, Vec3::Z],
+    }
+}
+
+/// Indexed triangles with per-vertex normals and UVs.
+#[derive(Default)]
+struct MeshBuilder {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+}
+
+impl MeshBuilder {
+    fn vertex(&mut self, position: Vec3, normal: Vec3, uv: [f32; 2]) -> u32 {
+        self.positions.push(position);
+        self.normals.push(normal);
+        self.uvs.push(uv);
+        (self.positions.len() - 1) as u32
+    }
+
+    /// Flat quad from four corners in order, wound to face `normal`.
+    fn quad(&mut self, corners: [Vec3; 4], normal: Vec3) {
+        const UVS: [[f32; 2]; 4] = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        let [a, b, c, d] = [0, 1, 2, 3].map(|i| self.vertex(corners[i], normal, UVS[i]));
+
+        let [c0, c1, c2, _] = corners;
+        if (c1 - c0).cross(c2 - c0).dot(normal) >= 0.0 {
+            self.indices.extend([a, b, c, a, c, d]);
+        } else {
+            self.indices.extend([a, c, b, a, d, c]);
+        }
+    }
+
+    fn cube(&mut self, half: f32) {
+        for axis in 0..3 {
+            let mut u = Vec3::ZERO;
+            let mut v = Vec3::ZERO;
+            let mut n = Vec3::ZERO;
+            u[(axis + 1) % 3] = half;
+            v[(axis + 2) % 3] = half;
+            n[axis] = 1.0;
+
+            for normal in [n, -n] {
+                let center = normal * half;
+                self.quad(
+                    [
+                        center - u - v,
+                        center + u - v,
+                        center + u + v,
+                        center - u + v,
+                    ],
+                    normal,
+                );
+            }
+        }
+    }
+
+    fn sphere(&mut self, radius: f32, [a, b, c]: [Vec3; 3]) {
+        let first = self.positions.len() as u32;
+        for ring in 0..=RINGS {
+            let theta = PI * ring as f32 / RINGS as f32;
+            let (r, z) = (theta.sin(), -theta.cos());
+            for segment in 0..=SEGMENTS {
+                let phi = 2.0 * PI * segment as f32 / SEGMENTS as f32;
+                let normal = r * phi.cos() * a + r * phi.sin() * b + z * c;
+                let uv = [segment as f32 / SEGMENTS as f32, ring as f32 / RINGS as f32];
+                self.vertex(radius * normal, normal, uv);
+            }
+        }
+
+        // Skip the degenerate halves of the quads touching the poles
+        let row = SEGMENTS + 1;
+        for ring in 0..RINGS {
+            for segment in 0..SEGMENTS {
+                let i0 = first + ring * row + segment;
+                let (i1, i2, i3) = (i0 + 1, i0 + row + 1, i0 + row);
+                if ring > 0 {
+                    self.indices.extend([i0, i1, i2]);
+                }
+                if ring < RINGS - 1 {
+                    self.indices.extend([i0, i2, i3]);
+                }
+            }
+        }
+    }
+
+    /// Side of a cylinder (equal radii) or cone (zero top radius).
+    fn tube(&mut self, bottom: f32, top: f32, height: f32, [a, b, c]: [Vec3; 3]) {
+        let first = self.positions.len() as u32;
+        let half = height * 0.5;
+        let slope = bottom - top;
+
+        for (ring, (radius, z)) in [(bottom, -half), (top, half)].into_iter().enumerate() {
+            for segment in 0..=SEGMENTS {
+                let phi = 2.0 * PI * segment as f32 / SEGMENTS as f32;
+                let radial = phi.cos() * a + phi.sin() * b;
+                let normal = (height * radial + slope * c).normalize_or_zero();
+                let uv = [segment as f32 / SEGMENTS as f32, ring as f32];
+                self.vertex(radius * radial + z * c, normal, uv);
+            }
+        }
+
+        let row = SEGMENTS + 1;
+        for segment in 0..SEGMENTS {
+            let i0 = first + segment;
+            let (i1, i2, i3) = (i0 + 1, i0 + row + 1, i0 + row);
+            self.indices.extend([i0, i1, i2]);
+            if top > 0.0 {
+                self.indices.extend([i0, i2, i3]);
+            }
+        }
+    }
+
+    /// Disk at `z` along the axis, facing away from the shape's center.
+    fn cap(&mut self, radius: f32, z: f32, [a, b, c]: [Vec3; 3]) {
+        let normal = if z >= 0.0 { c } else { -c };
+        let center = self.vertex(z * c, normal, [0.5, 0.5]);
+
+        for segment in 0..=SEGMENTS {
+            let phi = 2.0 * PI * segment as f32 / SEGMENTS as f32;
+            let (cos, sin) = (phi.cos(), phi.sin());
+            let uv = [0.5 + 0.5 * cos, 0.5 + 0.5 * sin];
+            self.vertex(radius * (cos * a + sin * b) + z * c, normal, uv);
+        }
+
+        for segment in 0..SEGMENTS {
+            let (i0, i1) = (center + 1 + segment, center + 2 + segment);
+            if z >= 0.0 {
+                self.indices.extend([center, i0, i1]);
+            } else {
+                self.indices.extend([center, i1, i0]);
+            }
+        }
+    }
+
+    /// USD's plane: width along X (Z for an X axis), length along Y (Z for
+    /// a Y axis).
+    fn plane(&mut self, width: f32, length: f32, axis: UsdAxis) {
+        let (w, l, normal) = match axis {
+            UsdAxis::X => (Vec3::Z, Vec3::Y, Vec3::X),
+            UsdAxis::Y => (Vec3::X, Vec3::Z, Vec3::Y),
+            UsdAxis::Z => (Vec3::X, Vec3::Y, Vec3::Z),
+        };
+        let (w, l) = (w * width * 0.5, l * length * 0.5);
+        self.quad([-w - l, w - l, w + l, -w + l], normal);
+    }
+
+    fn finish(self) -> Mesh {
+        Mesh::new_with_uvs(
+            self.positions,
+            self.indices,
+            Some(self.normals),
+            Some(self.uvs),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usd::types::UsdVisibility;
+    use bif_math::Mat4;
+
+    fn shape(kind: UsdShapeKind, axis: UsdAxis) -> UsdShape {
+        UsdShape {
+            path: "/Shape".into(),
+            name: "Shape".into(),
+            kind,
+            axis,
+            transform: Mat4::IDENTITY,
+            transform_samples: None,
+            visibility: UsdVisibility::default(),
+            material_binding: None,
+        }
+    }
+
+    /// Every triangle must face the same way as its vertex normals.
+    fn assert_outward(mesh: &Mesh) {
+        let normals = mesh.normals.as_ref().unwrap();
+        for tri in mesh.indices.chunks(3) {
+            let [p0, p1, p2] = [0, 1, 2].map(|i| mesh.positions[tri[i] as usize]);
+            let face = (p1 - p0).cross(p2 - p0);
+            assert!(face.length() > 0.0, "degenerate triangle {tri:?}");
+            let n = normals[tri[0] as usize] + normals[tri[1] as usize] + normals[tri[2] as usize];
+            assert!(face.dot(n) > 0.0, "triangle {tri:?} faces inwards");
+        }
+    }
+
+    #[test]
+    fn test_shapes_are_outward_facing() {
+        for axis in [UsdAxis::X, UsdAxis::Y, UsdAxis::Z] {
+            for type_name in ["Cube", "Sphere", "Cylinder", "Cone", "Plane"] {
+                let kind = UsdShapeKind::from_type_name(type_name).unwrap();
+                assert_outward(&shape(kind, axis).to_mesh());
+            }
+        }
+    }
+
+    #[test]
+    fn test_shape_bounds_follow_axis() {
+        let cylinder = UsdShapeKind::Cylinder {
+            radius: 0.5,
+            height: 4.0,
+        };
+        let bounds = shape(cylinder, UsdAxis::Y).to_mesh().bounds;
+        assert!((bounds.max_point() - Vec3::new(0.5, 2.0, 0.5)).length() < 1e-3);
+
+        let plane = UsdShapeKind::Plane {
+            width: 2.0,
+            length: 6.0,
+        };
+        let bounds = shape(plane, UsdAxis::X).to_mesh().bounds;
+        assert!((bounds.max_point().z - 1.0).abs() < 1e-3);
+        assert!((bounds.max_point().y - 3.0).abs() < 1e-3);
+
+        let cube = shape(UsdShapeKind::Cube { size: 3.0 }, UsdAxis::Z).to_mesh();
+        assert_eq!(cube.indices.len(), 36);
+        assert!((cube.bounds.min_point() - Vec3::splat(-1.5)).length() < 1e-3);
+    }
+}
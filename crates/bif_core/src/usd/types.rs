@@ -4,6 +4,15 @@
 
 use bif_math::{Mat4, Quat, Vec3};
 
+use crate::animation::{AnimationCurve, Interpolate};
+use crate::camera::Camera;
+use crate::curves::{CurveBasis, CurveShape, Curves};
+use crate::light::Light;
+use crate::mesh::{SubdivisionScheme, SubdivisionTags};
+use crate::points::Points;
+use crate::scene::Visibility;
+use crate::shading::ShadingValue;
+
 /// A parsed USD prim (generic container).
 #[derive(Clone, Debug)]
 pub enum UsdPrim {
@@ -16,6 +25,30 @@ pub enum UsdPrim {
     /// A point instancer
     PointInstancer(UsdPointInstancer),
 
+    /// An intrinsic shape (`Cube`, `Sphere`, `Cylinder`, `Cone`, `Plane`)
+    Shape(UsdShape),
+
+    /// Curves (hair, fur)
+    BasisCurves(UsdBasisCurves),
+
+    /// A point cloud (particles)
+    Points(UsdPoints),
+
+    /// A UsdLux light
+    Light(UsdLight),
+
+    /// A UsdGeom camera
+    Camera(UsdCamera),
+
+    /// A UsdShade material and its shaders
+    Material(UsdMaterial),
+
+    /// A UsdShade shader (only meaningful under a material)
+    Shader(UsdShader),
+
+    /// A face subset (only meaningful under a mesh)
+    GeomSubset(UsdGeomSubset),
+
     /// A reference to an external USD file
     Reference(UsdReference),
 
@@ -23,6 +56,40 @@ pub enum UsdPrim {
     Unknown(String),
 }
 
+/// Render visibility authored on an imageable prim.
+///
+/// `visibility` and `purpose` hide the prim and its descendants from every
+/// ray; `primvars:bif:visibility:<ray>` bools hide it from single ray types
+/// (see [`Visibility::NAMED`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UsdVisibility {
+    /// False for `visibility = "invisible"` or a `guide`/`proxy` purpose
+    pub visible: bool,
+
+    /// Ray types left on by the `bif:visibility` primvars
+    pub rays: Visibility,
+}
+
+impl Default for UsdVisibility {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            rays: Visibility::ALL,
+        }
+    }
+}
+
+impl UsdVisibility {
+    /// Combined mask: no rays at all if the prim is invisible.
+    pub fn mask(&self) -> Visibility {
+        if self.visible {
+            self.rays
+        } else {
+            Visibility::NONE
+        }
+    }
+}
+
 /// A USD Reference to an external file.
 /// Syntax: `references = @path/to/file.usda@</PrimPath>`
 #[derive(Clone, Debug, Default)]
@@ -42,6 +109,13 @@ pub struct UsdReference {
     /// Local transform applied to the reference
     pub transform: Mat4,
 
+    /// Time samples of `transform` (`None` unless an xformOp is animated)
+    pub transform_samples: Option<AnimationCurve<Mat4>>,
+
+    /// Path of the material bound with `material:binding`, inherited by the
+    /// referenced prims
+    pub material_binding: Option<String>,
+
     /// Child prims (overrides or additional content)
     pub children: Vec<UsdPrim>,
 }
@@ -58,6 +132,16 @@ pub struct UsdXform {
     /// Combined transform matrix from xformOps
     pub transform: Mat4,
 
+    /// Time samples of `transform` (`None` unless an xformOp is animated)
+    pub transform_samples: Option<AnimationCurve<Mat4>>,
+
+    /// Visibility, inherited by the children
+    pub visibility: UsdVisibility,
+
+    /// Path of the material bound with `material:binding`, inherited by the
+    /// children
+    pub material_binding: Option<String>,
+
     /// Child prims
     pub children: Vec<UsdPrim>,
 }
@@ -74,6 +158,9 @@ pub struct UsdMesh {
     /// Vertex positions
     pub points: Vec<Vec3>,
 
+    /// Time samples of `points` (`None` if not animated)
+    pub points_samples: Option<AnimationCurve<Vec<Vec3>>>,
+
     /// Number of vertices per face (for triangulation)
     pub face_vertex_counts: Vec<i32>,
 
@@ -86,8 +173,26 @@ pub struct UsdMesh {
     /// Local transform
     pub transform: Mat4,
 
+    /// Time samples of `transform` (`None` unless an xformOp is animated)
+    pub transform_samples: Option<AnimationCurve<Mat4>>,
+
     /// Whether the mesh uses left-handed winding (CW instead of CCW)
     pub left_handed: bool,
+
+    /// Authored subdivision scheme (`None` unless explicitly catmullClark)
+    pub subdivision_scheme: SubdivisionScheme,
+
+    /// Creases and corners of the subdivision cage
+    pub subdivision_tags: SubdivisionTags,
+
+    /// Visibility
+    pub visibility: UsdVisibility,
+
+    /// Path of the material bound with `material:binding`
+    pub material_binding: Option<String>,
+
+    /// `GeomSubset` children (per-face material bindings)
+    pub subsets: Vec<UsdGeomSubset>,
 }
 
 impl UsdMesh {
@@ -131,6 +236,338 @@ impl UsdMesh {
 
         indices
     }
+
+    /// Face each triangle of [`Self::triangulate`] comes from.
+    pub fn triangle_faces(&self) -> Vec<u32> {
+        self.face_vertex_counts
+            .iter()
+            .enumerate()
+            .flat_map(|(face, &count)| {
+                std::iter::repeat_n(face as u32, count.saturating_sub(2) as usize)
+            })
+            .collect()
+    }
+}
+
+/// A USD BasisCurves prim.
+#[derive(Clone, Debug, Default)]
+pub struct UsdBasisCurves {
+    /// Prim path
+    pub path: String,
+
+    /// Prim name
+    pub name: String,
+
+    /// Control points
+    pub points: Vec<Vec3>,
+
+    /// Number of control points per curve
+    pub curve_vertex_counts: Vec<i32>,
+
+    /// Authored widths (constant, uniform, vertex or varying)
+    pub widths: Vec<f32>,
+
+    /// `type` token ("linear" or "cubic")
+    pub curve_type: String,
+
+    /// `basis` token ("bezier", "bspline", "catmullRom")
+    pub basis: String,
+
+    /// Whether `normals` are authored, which makes the curves ribbons
+    pub has_normals: bool,
+
+    /// Local transform
+    pub transform: Mat4,
+
+    /// Time samples of `transform` (`None` unless an xformOp is animated)
+    pub transform_samples: Option<AnimationCurve<Mat4>>,
+
+    /// Path of the material bound with `material:binding`
+    pub material_binding: Option<String>,
+}
+
+impl UsdBasisCurves {
+    /// Convert to scene curve geometry.
+    pub fn to_curves(&self) -> Curves {
+        let shape = if self.has_normals {
+            CurveShape::Ribbon
+        } else {
+            CurveShape::Round
+        };
+        Curves::new(
+            self.points.clone(),
+            self.curve_vertex_counts
+                .iter()
+                .map(|&n| n.max(0) as u32)
+                .collect(),
+            &self.widths,
+            CurveBasis::from_usd(&self.curve_type, &self.basis),
+            shape,
+        )
+    }
+}
+
+/// A USD Points prim.
+#[derive(Clone, Debug, Default)]
+pub struct UsdPoints {
+    /// Prim path
+    pub path: String,
+
+    /// Prim name
+    pub name: String,
+
+    /// Point centers
+    pub points: Vec<Vec3>,
+
+    /// Authored widths (diameters; constant or per point)
+    pub widths: Vec<f32>,
+
+    /// Local transform
+    pub transform: Mat4,
+
+    /// Time samples of `transform` (`None` unless an xformOp is animated)
+    pub transform_samples: Option<AnimationCurve<Mat4>>,
+
+    /// Path of the material bound with `material:binding`
+    pub material_binding: Option<String>,
+}
+
+impl UsdPoints {
+    /// Convert to scene point geometry.
+    pub fn to_points(&self) -> Points {
+        Points::new(self.points.clone(), &self.widths)
+    }
+}
+
+/// Spine or normal axis of an intrinsic shape (USD `axis` token).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UsdAxis {
+    X,
+    Y,
+    /// USD's fallback for every shape with an `axis` attribute
+    #[default]
+    Z,
+}
+
+impl UsdAxis {
+    /// Parse a USD axis token ("X", "Y" or "Z").
+    pub fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "X" => Some(Self::X),
+            "Y" => Some(Self::Y),
+            "Z" => Some(Self::Z),
+            _ => None,
+        }
+    }
+}
+
+/// Dimensions of an intrinsic shape, with USD's fallback values as defaults.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UsdShapeKind {
+    /// Axis-aligned cube with edge length `size` (fallback 2)
+    Cube { size: f32 },
+
+    /// Sphere (fallback radius 1)
+    Sphere { radius: f32 },
+
+    /// Closed cylinder along the axis (fallback radius 1, height 2)
+    Cylinder { radius: f32, height: f32 },
+
+    /// Closed cone with its apex towards +axis (fallback radius 1, height 2)
+    Cone { radius: f32, height: f32 },
+
+    /// Flat rectangle facing the axis (fallback width 2, length 2)
+    Plane { width: f32, length: f32 },
+}
+
+impl UsdShapeKind {
+    /// Shape for a USD prim type name with fallback dimensions.
+    pub fn from_type_name(type_name: &str) -> Option<Self> {
+        match type_name {
+            "Cube" => Some(Self::Cube { size: 2.0 }),
+            "Sphere" => Some(Self::Sphere { radius: 1.0 }),
+            "Cylinder" => Some(Self::Cylinder {
+                radius: 1.0,
+                height: 2.0,
+            }),
+            "Cone" => Some(Self::Cone {
+                radius: 1.0,
+                height: 2.0,
+            }),
+            "Plane" => Some(Self::Plane {
+                width: 2.0,
+                length: 2.0,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// A USD intrinsic shape prim, centered on its local origin.
+#[derive(Clone, Debug)]
+pub struct UsdShape {
+    /// Prim path
+    pub path: String,
+
+    /// Prim name
+    pub name: String,
+
+    /// Shape type and dimensions
+    pub kind: UsdShapeKind,
+
+    /// Spine axis (cylinder, cone) or normal (plane); unused otherwise
+    pub axis: UsdAxis,
+
+    /// Local transform
+    pub transform: Mat4,
+
+    /// Time samples of `transform` (`None` unless an xformOp is animated)
+    pub transform_samples: Option<AnimationCurve<Mat4>>,
+
+    /// Visibility
+    pub visibility: UsdVisibility,
+
+    /// Path of the material bound with `material:binding`
+    pub material_binding: Option<String>,
+}
+
+/// A UsdLux light prim.
+#[derive(Clone, Debug)]
+pub struct UsdLight {
+    /// Prim path
+    pub path: String,
+
+    /// Light inputs; its transform is the prim's local transform
+    pub light: Light,
+
+    /// Time samples of the local transform (`None` unless an xformOp is
+    /// animated)
+    pub transform_samples: Option<AnimationCurve<Mat4>>,
+
+    /// Visibility (an invisible light is switched off)
+    pub visibility: UsdVisibility,
+}
+
+/// A UsdGeom camera prim.
+#[derive(Clone, Debug)]
+pub struct UsdCamera {
+    /// Prim path
+    pub path: String,
+
+    /// Lens and film back; its transform is the prim's local transform
+    pub camera: Camera,
+
+    /// Time samples of the local transform (`None` unless an xformOp is
+    /// animated)
+    pub transform_samples: Option<AnimationCurve<Mat4>>,
+}
+
+/// A `GeomSubset` of a mesh's faces.
+#[derive(Clone, Debug, Default)]
+pub struct UsdGeomSubset {
+    /// Prim path
+    pub path: String,
+
+    /// `familyName` token ("materialBind" for material bindings)
+    pub family_name: String,
+
+    /// Indices of the faces in the subset
+    pub indices: Vec<i32>,
+
+    /// Path of the material bound with `material:binding`
+    pub material_binding: Option<String>,
+}
+
+/// A UsdShade `Material` prim with the shaders defined under it.
+#[derive(Clone, Debug, Default)]
+pub struct UsdMaterial {
+    /// Prim path
+    pub path: String,
+
+    /// Prim name
+    pub name: String,
+
+    /// Source of `outputs:surface`
+    pub surface: Option<UsdConnection>,
+
+    /// Shader prims under the material, including those in node graphs
+    pub shaders: Vec<UsdShader>,
+}
+
+impl UsdMaterial {
+    /// Shader at a prim path.
+    pub fn shader(&self, path: &str) -> Option<&UsdShader> {
+        self.shaders.iter().find(|shader| shader.path == path)
+    }
+
+    /// Shader connected to `outputs:surface`.
+    pub fn surface_shader(&self) -> Option<&UsdShader> {
+        self.shader(&self.surface.as_ref()?.prim_path)
+    }
+}
+
+/// A UsdShade `Shader` prim.
+#[derive(Clone, Debug, Default)]
+pub struct UsdShader {
+    /// Prim path
+    pub path: String,
+
+    /// Shader identifier (`info:id`, e.g. "UsdPreviewSurface")
+    pub id: String,
+
+    /// Authored `inputs:*` attributes
+    pub inputs: Vec<UsdShaderInput>,
+}
+
+impl UsdShader {
+    /// Input by name (without the `inputs:` prefix).
+    pub fn input(&self, name: &str) -> Option<&UsdShaderInput> {
+        self.inputs.iter().find(|input| input.name == name)
+    }
+
+    /// Constant value of an input.
+    pub fn value(&self, name: &str) -> Option<&ShadingValue> {
+        self.input(name)?.value.as_ref()
+    }
+}
+
+/// An `inputs:*` attribute of a shader: a value, a connection or both.
+#[derive(Clone, Debug, Default)]
+pub struct UsdShaderInput {
+    /// Input name without the `inputs:` prefix
+    pub name: String,
+
+    /// Declared value type (`float`, `vector3f`, ...)
+    pub type_name: String,
+
+    /// Authored value
+    pub value: Option<ShadingValue>,
+
+    /// Source of the `.connect` attribute
+    pub connection: Option<UsdConnection>,
+}
+
+/// Target of a UsdShade connection, like `</Looks/Wood/Tex.outputs:rgb>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UsdConnection {
+    /// Path of the connected prim ("/Looks/Wood/Tex")
+    pub prim_path: String,
+
+    /// Output name without the `outputs:` prefix ("rgb")
+    pub output: String,
+}
+
+impl UsdConnection {
+    /// Parse a connection target like `</Looks/Wood/Tex.outputs:rgb>`.
+    pub fn parse(target: &str) -> Option<Self> {
+        let target = target.trim().strip_prefix('<')?.strip_suffix('>')?;
+        let (prim_path, attribute) = target.rsplit_once('.')?;
+        let output = attribute.strip_prefix("outputs:").unwrap_or(attribute);
+        Some(Self {
+            prim_path: prim_path.to_string(),
+            output: output.to_string(),
+        })
+    }
 }
 
 /// A USD PointInstancer prim.
@@ -154,12 +591,31 @@ pub struct UsdPointInstancer {
     /// Instance scales (uniform or per-axis)
     pub scales: Option<Vec<Vec3>>,
 
+    /// Time samples of `positions` (`None` if not animated)
+    pub positions_samples: Option<AnimationCurve<Vec<Vec3>>>,
+
+    /// Time samples of `orientations`
+    pub orientations_samples: Option<AnimationCurve<Vec<Quat>>>,
+
+    /// Time samples of `scales`
+    pub scales_samples: Option<AnimationCurve<Vec<Vec3>>>,
+
     /// Paths to prototype prims
     pub prototypes: Vec<String>,
 
     /// Local transform
     pub transform: Mat4,
 
+    /// Time samples of `transform` (`None` unless an xformOp is animated)
+    pub transform_samples: Option<AnimationCurve<Mat4>>,
+
+    /// Visibility, applied to every instance
+    pub visibility: UsdVisibility,
+
+    /// Path of the material bound with `material:binding`, inherited by the
+    /// inline prototypes
+    pub material_binding: Option<String>,
+
     /// Inline prototype definitions (children)
     pub children: Vec<UsdPrim>,
 }
@@ -167,35 +623,84 @@ pub struct UsdPointInstancer {
 impl UsdPointInstancer {
     /// Get the transform matrix for a specific instance.
     pub fn instance_matrix(&self, index: usize) -> Mat4 {
-        if index >= self.positions.len() {
-            return Mat4::IDENTITY;
-        }
+        instance_matrix(
+            &self.positions,
+            self.orientations.as_deref(),
+            self.scales.as_deref(),
+            index,
+        )
+    }
 
-        let translation = self.positions[index];
+    /// Get the number of instances.
+    pub fn instance_count(&self) -> usize {
+        self.positions.len()
+    }
 
-        let rotation = self
+    /// Transform matrices of all instances at time code `time`.
+    ///
+    /// Animated positions, orientations and scales are evaluated at `time`;
+    /// with `None` (or no samples) the authored values are used.
+    pub fn instance_matrices_at(&self, time: Option<f64>) -> Vec<Mat4> {
+        let positions = at_time(&self.positions, self.positions_samples.as_ref(), time);
+        let orientations = self
             .orientations
             .as_ref()
-            .and_then(|o| o.get(index))
-            .copied()
-            .unwrap_or(Quat::IDENTITY);
-
-        let scale = self
+            .map(|orientations| at_time(orientations, self.orientations_samples.as_ref(), time));
+        let scales = self
             .scales
             .as_ref()
-            .and_then(|s| s.get(index))
-            .copied()
-            .unwrap_or(Vec3::ONE);
+            .map(|scales| at_time(scales, self.scales_samples.as_ref(), time));
 
-        Mat4::from_scale_rotation_translation(scale, rotation, translation)
+        (0..positions.len())
+            .map(|i| instance_matrix(&positions, orientations.as_deref(), scales.as_deref(), i))
+            .collect()
     }
 
-    /// Get the number of instances.
-    pub fn instance_count(&self) -> usize {
-        self.positions.len()
+    /// Sorted times of all position, orientation and scale samples.
+    pub fn sample_times(&self) -> Vec<f64> {
+        let mut times: Vec<f64> = self
+            .positions_samples
+            .iter()
+            .flat_map(|c| c.times())
+            .chain(self.orientations_samples.iter().flat_map(|c| c.times()))
+            .chain(self.scales_samples.iter().flat_map(|c| c.times()))
+            .collect();
+        times.sort_by(f64::total_cmp);
+        times.dedup();
+        times
+    }
+}
+
+/// Value of `samples` at `time`, or `value` when either is missing.
+fn at_time<T: Interpolate>(value: &T, samples: Option<&AnimationCurve<T>>, time: Option<f64>) -> T {
+    match (samples, time) {
+        (Some(samples), Some(time)) => samples.evaluate(time),
+        _ => value.clone(),
     }
 }
 
+/// Transform of instance `index` from point instancer arrays.
+fn instance_matrix(
+    positions: &[Vec3],
+    orientations: Option<&[Quat]>,
+    scales: Option<&[Vec3]>,
+    index: usize,
+) -> Mat4 {
+    let Some(&translation) = positions.get(index) else {
+        return Mat4::IDENTITY;
+    };
+    let rotation = orientations
+        .and_then(|o| o.get(index))
+        .copied()
+        .unwrap_or(Quat::IDENTITY);
+    let scale = scales
+        .and_then(|s| s.get(index))
+        .copied()
+        .unwrap_or(Vec3::ONE);
+
+    Mat4::from_scale_rotation_translation(scale, rotation, translation)
+}
+
 /// Transform operation types found in USD xformOps.
 #[derive(Clone, Debug)]
 pub enum XformOp {
@@ -240,6 +745,26 @@ impl XformOp {
     }
 }
 
+impl Interpolate for XformOp {
+    /// Blends the values of two ops of the same kind; an op of another
+    /// kind can't be blended, so `self` is held.
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        match (self, other) {
+            (XformOp::Translate(a), XformOp::Translate(b)) => XformOp::Translate(a.lerp(*b, t)),
+            (XformOp::RotateX(a), XformOp::RotateX(b)) => XformOp::RotateX(a.interpolate(b, t)),
+            (XformOp::RotateY(a), XformOp::RotateY(b)) => XformOp::RotateY(a.interpolate(b, t)),
+            (XformOp::RotateZ(a), XformOp::RotateZ(b)) => XformOp::RotateZ(a.interpolate(b, t)),
+            // Euler angles blend per axis, as in USD
+            (XformOp::RotateXYZ(a), XformOp::RotateXYZ(b)) => XformOp::RotateXYZ(a.lerp(*b, t)),
+            (XformOp::Scale(a), XformOp::Scale(b)) => XformOp::Scale(a.lerp(*b, t)),
+            (XformOp::Transform(a), XformOp::Transform(b)) => {
+                XformOp::Transform(a.interpolate(b, t))
+            }
+            _ => self.clone(),
+        }
+    }
+}
+
 /// Combine a list of xformOps into a single matrix.
 pub fn compose_xform_ops(ops: &[XformOp]) -> Mat4 {
     let mut result = Mat4::IDENTITY;
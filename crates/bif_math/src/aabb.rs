@@ -1,5 +1,10 @@
 use crate::{Interval, Ray, Vec3};
 
+/// Growth applied to slab exit distances so that rounding can't make a
+/// ray miss a box it touches (1 + 2γ₃, as in pbrt).
+pub const SLAB_EXIT_SCALE: f32 = 1.0 + 2.0 * (3.0 * HALF_EPSILON / (1.0 - 3.0 * HALF_EPSILON));
+const HALF_EPSILON: f32 = f32::EPSILON * 0.5;
+
 /// Axis-Aligned Bounding Box for spatial acceleration structures (BVH).
 ///
 /// An AABB is defined by three intervals (one per axis) that bound a 3D volume.
@@ -80,7 +85,7 @@ impl Aabb {
             std::mem::swap(&mut t0, &mut t1);
         }
         ray_t.min = t0.max(ray_t.min);
-        ray_t.max = t1.min(ray_t.max);
+        ray_t.max = (t1 * SLAB_EXIT_SCALE).min(ray_t.max);
         if ray_t.max <= ray_t.min {
             return false;
         }
@@ -93,7 +98,7 @@ impl Aabb {
             std::mem::swap(&mut t0, &mut t1);
         }
         ray_t.min = t0.max(ray_t.min);
-        ray_t.max = t1.min(ray_t.max);
+        ray_t.max = (t1 * SLAB_EXIT_SCALE).min(ray_t.max);
         if ray_t.max <= ray_t.min {
             return false;
         }
@@ -106,7 +111,7 @@ impl Aabb {
             std::mem::swap(&mut t0, &mut t1);
         }
         ray_t.min = t0.max(ray_t.min);
-        ray_t.max = t1.min(ray_t.max);
+        ray_t.max = (t1 * SLAB_EXIT_SCALE).min(ray_t.max);
         if ray_t.max <= ray_t.min {
             return false;
         }
@@ -167,6 +172,15 @@ impl Aabb {
         self.centroid()
     }
 
+    /// Returns the surface area of the box (0 for empty boxes).
+    pub fn surface_area(&self) -> f32 {
+        let (dx, dy, dz) = (self.x.size(), self.y.size(), self.z.size());
+        if dx < 0.0 || dy < 0.0 || dz < 0.0 {
+            return 0.0;
+        }
+        2.0 * (dx * dy + dy * dz + dz * dx)
+    }
+
     /// Returns the minimum corner point of the bounding box.
     pub fn min_point(&self) -> Vec3 {
         Vec3::new(self.x.min, self.y.min, self.z.min)
@@ -244,6 +258,13 @@ mod tests {
         assert_eq!(centroid, Vec3::new(5.0, 5.0, 5.0));
     }
 
+    #[test]
+    fn test_aabb_surface_area() {
+        let aabb = Aabb::from_points(Vec3::ZERO, Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(aabb.surface_area(), 22.0);
+        assert_eq!(Aabb::EMPTY.surface_area(), 0.0);
+    }
+
     #[test]
     fn test_aabb_longest_axis() {
         let aabb_x = Aabb::from_points(Vec3::ZERO, Vec3::new(10.0, 1.0, 1.0));
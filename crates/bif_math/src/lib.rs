@@ -9,7 +9,7 @@ mod interval;
 pub use interval::Interval;
 
 mod aabb;
-pub use aabb::Aabb;
+pub use aabb::{Aabb, SLAB_EXIT_SCALE};
 
 mod camera;
 pub use camera::Camera;
@@ -20,6 +20,9 @@ pub use transform::Mat4Ext;
 mod frustum;
 pub use frustum::Frustum;
 
+mod motion;
+pub use motion::{motion_segment, MotionTransform, TransformKey};
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,168 @@
+// Motion blur keys
+//
+// Moving geometry carries several keys (transforms or vertex positions)
+// evenly spaced over the shutter interval, normalized to [0, 1]: the first
+// key is at time 0 and the last at time 1. Ray times use the same units, and
+// geometry is interpolated between the two keys around a ray's time.
+
+use crate::{Aabb, Mat4, Mat4Ext, Quat, Vec3};
+
+/// Samples per key segment when bounding a moving box.
+const BOUNDS_STEPS: usize = 8;
+
+/// Key segment containing `time` and the fraction of the way through it.
+///
+/// `key_count` keys span [0, 1]; times outside are clamped. With fewer than
+/// two keys the result is `(0, 0.0)`.
+#[inline]
+pub fn motion_segment(time: f32, key_count: usize) -> (usize, f32) {
+    if key_count < 2 {
+        return (0, 0.0);
+    }
+    let position = time.clamp(0.0, 1.0) * (key_count - 1) as f32;
+    let segment = (position as usize).min(key_count - 2);
+    (segment, position - segment as f32)
+}
+
+/// One transform key, decomposed for interpolation.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TransformKey {
+    pub scale: Vec3,
+    pub rotation: Quat,
+    pub translation: Vec3,
+}
+
+impl TransformKey {
+    /// Decompose a matrix (shear is lost).
+    pub fn from_matrix(matrix: &Mat4) -> Self {
+        let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
+        Self {
+            scale,
+            rotation,
+            translation,
+        }
+    }
+
+    /// Compose back into a matrix (scale, then rotate, then translate).
+    pub fn to_matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+
+    /// Interpolate towards `other`: linear scale and translation, slerped rotation.
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            scale: self.scale.lerp(other.scale, t),
+            rotation: self.rotation.slerp(other.rotation, t),
+            translation: self.translation.lerp(other.translation, t),
+        }
+    }
+}
+
+/// Transform keys of a moving object.
+///
+/// Rotations are slerped rather than interpolating matrices, so spinning
+/// objects keep their shape between keys instead of shrinking.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MotionTransform {
+    keys: Vec<TransformKey>,
+}
+
+impl MotionTransform {
+    /// Decompose one matrix per key.
+    pub fn new(matrices: &[Mat4]) -> Self {
+        assert!(
+            !matrices.is_empty(),
+            "MotionTransform needs at least one key"
+        );
+        Self {
+            keys: matrices.iter().map(TransformKey::from_matrix).collect(),
+        }
+    }
+
+    /// The decomposed keys, from shutter open to close.
+    pub fn keys(&self) -> &[TransformKey] {
+        &self.keys
+    }
+
+    /// Local-to-world matrix at `time`.
+    pub fn at(&self, time: f32) -> Mat4 {
+        let (segment, t) = motion_segment(time, self.keys.len());
+        match self.keys.get(segment + 1) {
+            Some(next) => self.keys[segment].lerp(next, t).to_matrix(),
+            None => self.keys[segment].to_matrix(),
+        }
+    }
+
+    /// Bounds of `aabb` over the whole motion.
+    ///
+    /// Each segment is sampled at a few times, and the samples are padded
+    /// by how far a rotating corner can bulge out of the chord between
+    /// them, so the result stays conservative.
+    pub fn transform_aabb(&self, aabb: &Aabb) -> Aabb {
+        let radius = aabb.min_point().abs().max(aabb.max_point().abs()).length();
+
+        let mut bounds = self.keys[0].to_matrix().transform_aabb(aabb);
+        for pair in self.keys.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            let step_angle = a.rotation.angle_between(b.rotation) / BOUNDS_STEPS as f32;
+            let scale = a.scale.abs().max(b.scale.abs()).max_element();
+            let pad = Vec3::splat(radius * scale * (1.0 - (0.5 * step_angle).cos()));
+
+            for step in 1..=BOUNDS_STEPS {
+                let key = a.lerp(b, step as f32 / BOUNDS_STEPS as f32);
+                let sample = key.to_matrix().transform_aabb(aabb);
+                let padded = Aabb::from_points(sample.min_point() - pad, sample.max_point() + pad);
+                bounds = Aabb::surrounding(&bounds, &padded);
+            }
+        }
+        bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_motion_segment() {
+        assert_eq!(motion_segment(0.7, 1), (0, 0.0));
+        assert_eq!(motion_segment(0.0, 2), (0, 0.0));
+        assert_eq!(motion_segment(1.0, 2), (0, 1.0));
+        assert_eq!(motion_segment(0.75, 3), (1, 0.5));
+        // Clamped to the shutter
+        assert_eq!(motion_segment(-1.0, 3), (0, 0.0));
+        assert_eq!(motion_segment(2.0, 3), (1, 1.0));
+    }
+
+    #[test]
+    fn test_slerped_rotation_keeps_scale() {
+        let motion = MotionTransform::new(&[Mat4::IDENTITY, Mat4::from_rotation_z(PI / 2.0)]);
+
+        // Halfway, X is rotated 45 degrees and keeps unit length
+        let x = motion.at(0.5).transform_point3(Vec3::X);
+        assert!((x.length() - 1.0).abs() < 1e-5);
+        assert!((x.x - x.y).abs() < 1e-5);
+        assert_eq!(motion.keys().len(), 2);
+    }
+
+    #[test]
+    fn test_motion_bounds_cover_the_path() {
+        let motion = MotionTransform::new(&[
+            Mat4::IDENTITY,
+            Mat4::from_rotation_z(PI / 2.0),
+            Mat4::from_translation(Vec3::new(5.0, 0.0, 0.0)) * Mat4::from_rotation_z(PI),
+        ]);
+        let unit = Aabb::from_points(Vec3::ZERO, Vec3::X);
+        let bounds = motion.transform_aabb(&unit);
+
+        for i in 0..=100 {
+            let p = motion.at(i as f32 / 100.0).transform_point3(Vec3::X);
+            assert!(
+                p.cmpge(bounds.min_point() - 1e-5).all()
+                    && p.cmple(bounds.max_point() + 1e-5).all(),
+                "{p} outside {bounds:?}"
+            );
+        }
+    }
+}
@@ -4,6 +4,14 @@
 // Install via: vcpkg install embree[geometry-triangle,geometry-instance]:x64-windows
 
 fn main() {
+    // Rebuild if this build script changes
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // Without the `embree` feature Ivar uses its pure-Rust BVHs only
+    if std::env::var_os("CARGO_FEATURE_EMBREE").is_none() {
+        return;
+    }
+
     // vcpkg integration handles the linking automatically on Windows
     // but we need to tell Cargo the library name
 
@@ -16,7 +24,4 @@ fn main() {
         let lib_path = format!("{}\\installed\\x64-windows\\lib", vcpkg_root);
         println!("cargo:rustc-link-search=native={}", lib_path);
     }
-
-    // Rebuild if this build script changes
-    println!("cargo:rerun-if-changed=build.rs");
 }
@@ -2,13 +2,51 @@
 //!
 //! Uses a binary tree structure for efficient ray-scene intersection testing.
 //! Ported from legacy Go raytracer with Rust-idiomatic enhancements.
+//!
+//! Trees are built with a binned surface area heuristic (SAH): each node is
+//! split at the bin boundary that minimizes the expected traversal cost,
+//! and large subtrees are built in parallel with rayon.
 
+use crate::hittable::packet_interval;
 use crate::{HitRecord, Hittable, Ray};
-use bif_math::{Aabb, Interval};
+use bif_math::{Aabb, Interval, Vec3};
+use std::fmt;
 
-/// Maximum primitives per leaf node before splitting.
+/// Default maximum primitives per leaf node.
 const LEAF_MAX_SIZE: usize = 4;
 
+/// Deepest leaf any builder makes (the root is depth 0). Nodes too deep to
+/// finish with SAH splits are split at the median, so flattened trees can be
+/// traversed with a fixed stack of this many entries.
+pub(crate) const MAX_DEPTH: usize = 64;
+
+/// BVH construction parameters.
+#[derive(Debug, Clone, Copy)]
+pub struct BvhBuildConfig {
+    /// Nodes with more primitives than this are always split
+    pub max_leaf_size: usize,
+    /// Number of centroid bins evaluated per axis
+    pub bin_count: usize,
+    /// Cost of visiting an interior node (relative to `intersection_cost`)
+    pub traversal_cost: f32,
+    /// Cost of intersecting one primitive
+    pub intersection_cost: f32,
+    /// Subtrees with at least this many primitives are built in parallel
+    pub parallel_threshold: usize,
+}
+
+impl Default for BvhBuildConfig {
+    fn default() -> Self {
+        Self {
+            max_leaf_size: LEAF_MAX_SIZE,
+            bin_count: 16,
+            traversal_cost: 0.125,
+            intersection_cost: 1.0,
+            parallel_threshold: 4096,
+        }
+    }
+}
+
 /// BVH node - either a branch with two children or a leaf with primitives.
 ///
 /// Using an enum allows for more cache-efficient traversal since
@@ -29,6 +67,164 @@ pub enum BvhNode {
     Empty,
 }
 
+/// Primitive with its bounds cached for construction.
+pub(crate) struct BuildItem<T> {
+    pub object: T,
+    pub bbox: Aabb,
+    pub centroid: Vec3,
+}
+
+impl<T> BuildItem<T> {
+    pub fn new(object: T, bbox: Aabb) -> Self {
+        Self {
+            object,
+            bbox,
+            centroid: bbox.centroid(),
+        }
+    }
+}
+
+/// Outcome of splitting a set of build items.
+pub(crate) enum Partition<T> {
+    /// Cheaper to intersect the items directly
+    Leaf(Vec<BuildItem<T>>),
+    /// Split into two children along `axis`
+    Split {
+        left: Vec<BuildItem<T>>,
+        right: Vec<BuildItem<T>>,
+        axis: usize,
+    },
+}
+
+/// Bounds of all items.
+pub(crate) fn items_bounds<T>(items: &[BuildItem<T>]) -> Aabb {
+    items
+        .iter()
+        .fold(Aabb::EMPTY, |acc, item| Aabb::surrounding(&acc, &item.bbox))
+}
+
+/// Decide how to split one node at `depth` with the binned SAH.
+///
+/// Shared by `BvhNode` and `MeshBvh` so both builders make the same trees.
+/// Once `depth` leaves only enough levels to halve the items down to one,
+/// the node is split at the median instead, keeping leaves within
+/// `MAX_DEPTH`.
+pub(crate) fn partition<T>(
+    items: Vec<BuildItem<T>>,
+    bounds: &Aabb,
+    config: &BvhBuildConfig,
+    depth: usize,
+) -> Partition<T> {
+    let n = items.len();
+    if n == 1 {
+        return Partition::Leaf(items);
+    }
+    if depth + n.next_power_of_two().trailing_zeros() as usize >= MAX_DEPTH {
+        return median_split(items, bounds);
+    }
+
+    match find_sah_split(&items, bounds, config) {
+        Some(split) => {
+            let leaf_cost = n as f32 * config.intersection_cost;
+            if n <= config.max_leaf_size && split.cost >= leaf_cost {
+                return Partition::Leaf(items);
+            }
+            let axis = split.binning.axis;
+            let (left, right) = items.into_iter().partition(|item| split.goes_left(item));
+            Partition::Split { left, right, axis }
+        }
+        // All centroids coincide: SAH can't separate them
+        None if n <= config.max_leaf_size => Partition::Leaf(items),
+        None => {
+            let mut left = items;
+            let right = left.split_off(n / 2);
+            let axis = bounds.longest_axis();
+            Partition::Split { left, right, axis }
+        }
+    }
+}
+
+/// Split at the median centroid along the longest axis of `bounds`.
+///
+/// The left half gets `n / 2` items, so each level halves the count.
+fn median_split<T>(mut items: Vec<BuildItem<T>>, bounds: &Aabb) -> Partition<T> {
+    let axis = bounds.longest_axis();
+    let mid = items.len() / 2;
+    items.select_nth_unstable_by(mid, |a, b| a.centroid[axis].total_cmp(&b.centroid[axis]));
+    let right = items.split_off(mid);
+    Partition::Split {
+        left: items,
+        right,
+        axis,
+    }
+}
+
+/// Find the cheapest bin boundary over all three axes.
+///
+/// Returns None if the centroids don't spread along any axis.
+fn find_sah_split<T>(
+    items: &[BuildItem<T>],
+    bounds: &Aabb,
+    config: &BvhBuildConfig,
+) -> Option<SahSplit> {
+    let (centroid_min, centroid_max) = items
+        .iter()
+        .fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(lo, hi), item| {
+            (lo.min(item.centroid), hi.max(item.centroid))
+        });
+    let parent_area = bounds.surface_area().max(f32::MIN_POSITIVE);
+    let bin_count = config.bin_count.max(2);
+
+    let mut best: Option<SahSplit> = None;
+    for axis in 0..3 {
+        let extent = Interval::new(centroid_min[axis], centroid_max[axis]);
+        if extent.size() <= 0.0 {
+            continue;
+        }
+
+        let mut bins = vec![(Aabb::EMPTY, 0usize); bin_count];
+        let binning = Binning::new(axis, extent, bin_count);
+        for item in items {
+            let bin = &mut bins[binning.bin(item.centroid)];
+            bin.0 = Aabb::surrounding(&bin.0, &item.bbox);
+            bin.1 += 1;
+        }
+
+        // Sweep from the right to get suffix areas and counts
+        let mut right_area = vec![0.0; bin_count];
+        let mut right_count = vec![0; bin_count];
+        let (mut bbox, mut count) = (Aabb::EMPTY, 0);
+        for i in (1..bin_count).rev() {
+            bbox = Aabb::surrounding(&bbox, &bins[i].0);
+            count += bins[i].1;
+            right_area[i] = bbox.surface_area();
+            right_count[i] = count;
+        }
+
+        // Sweep from the left, evaluating the split before bin i
+        let (mut bbox, mut count) = (Aabb::EMPTY, 0);
+        for i in 1..bin_count {
+            bbox = Aabb::surrounding(&bbox, &bins[i - 1].0);
+            count += bins[i - 1].1;
+            if count == 0 || right_count[i] == 0 {
+                continue;
+            }
+            let cost = config.traversal_cost
+                + config.intersection_cost
+                    * (bbox.surface_area() * count as f32 + right_area[i] * right_count[i] as f32)
+                    / parent_area;
+            if best.as_ref().is_none_or(|b| cost < b.cost) {
+                best = Some(SahSplit {
+                    binning,
+                    split_bin: i,
+                    cost,
+                });
+            }
+        }
+    }
+    best
+}
+
 /// Convert our Ray to bif_math::Ray for AABB intersection.
 #[inline]
 fn to_math_ray(ray: &Ray) -> bif_math::Ray {
@@ -38,77 +234,211 @@ fn to_math_ray(ray: &Ray) -> bif_math::Ray {
 impl BvhNode {
     /// Create a BVH from a list of hittable objects.
     pub fn new(objects: Vec<Box<dyn Hittable + Send + Sync>>) -> Self {
+        Self::with_config(objects, &BvhBuildConfig::default())
+    }
+
+    /// Create a BVH with explicit construction parameters.
+    pub fn with_config(
+        objects: Vec<Box<dyn Hittable + Send + Sync>>,
+        config: &BvhBuildConfig,
+    ) -> Self {
         if objects.is_empty() {
             return BvhNode::Empty;
         }
-        Self::build(objects)
+
+        let items = objects
+            .into_iter()
+            .map(|object| {
+                let bbox = object.bounding_box();
+                BuildItem::new(object, bbox)
+            })
+            .collect();
+        Self::build(items, config, 0)
+    }
+
+    /// Recursive binned SAH construction of a node at `depth`.
+    fn build(
+        items: Vec<BuildItem<Box<dyn Hittable + Send + Sync>>>,
+        config: &BvhBuildConfig,
+        depth: usize,
+    ) -> Self {
+        let bbox = items_bounds(&items);
+        let n = items.len();
+
+        let (left, right) = match partition(items, &bbox, config, depth) {
+            Partition::Leaf(items) => {
+                return BvhNode::Leaf {
+                    objects: items.into_iter().map(|item| item.object).collect(),
+                    bbox,
+                };
+            }
+            Partition::Split { left, right, .. } => (left, right),
+        };
+
+        let build = |items| Self::build(items, config, depth + 1);
+        let (left, right) = if n >= config.parallel_threshold {
+            rayon::join(|| build(left), || build(right))
+        } else {
+            (build(left), build(right))
+        };
+
+        BvhNode::Branch {
+            left: Box::new(left),
+            right: Box::new(right),
+            bbox,
+        }
     }
 
-    /// Recursive BVH construction.
+    /// Compute a quality report for this tree.
     ///
-    /// Simple median-split approach: sort objects by centroid on longest axis,
-    /// split in half, recurse.
-    fn build(mut objects: Vec<Box<dyn Hittable + Send + Sync>>) -> Self {
-        let n = objects.len();
+    /// The SAH cost uses the cost constants from `config` and is normalized
+    /// by the root's surface area, so it is comparable across builders.
+    pub fn stats(&self, config: &BvhBuildConfig) -> BvhStats {
+        let mut stats = BvhStats {
+            min_leaf_size: usize::MAX,
+            ..Default::default()
+        };
+        let root_area = self.bounding_box().surface_area();
+        self.accumulate_stats(config, root_area, 0, &mut stats);
+        if stats.leaf_count == 0 {
+            stats.min_leaf_size = 0;
+        }
+        stats
+    }
 
-        // Compute bounding box of all objects
-        let bounds = objects
-            .iter()
-            .map(|o| o.bounding_box())
-            .fold(objects[0].bounding_box(), |acc, b| {
-                Aabb::surrounding(&acc, &b)
-            });
+    fn accumulate_stats(
+        &self,
+        config: &BvhBuildConfig,
+        root_area: f32,
+        depth: usize,
+        stats: &mut BvhStats,
+    ) {
+        let relative_area = |bbox: &Aabb| {
+            if root_area > 0.0 {
+                bbox.surface_area() / root_area
+            } else {
+                1.0
+            }
+        };
 
-        // Create leaf for small sets
-        if n <= LEAF_MAX_SIZE {
-            return BvhNode::Leaf {
-                objects,
-                bbox: bounds,
-            };
+        match self {
+            BvhNode::Empty => {}
+            BvhNode::Leaf { objects, bbox } => {
+                stats.node_count += 1;
+                stats.leaf_count += 1;
+                stats.primitive_count += objects.len();
+                stats.max_depth = stats.max_depth.max(depth);
+                stats.total_leaf_depth += depth;
+                stats.min_leaf_size = stats.min_leaf_size.min(objects.len());
+                stats.max_leaf_size = stats.max_leaf_size.max(objects.len());
+                stats.sah_cost +=
+                    config.intersection_cost * objects.len() as f32 * relative_area(bbox);
+            }
+            BvhNode::Branch { left, right, bbox } => {
+                stats.node_count += 1;
+                stats.sah_cost += config.traversal_cost * relative_area(bbox);
+                left.accumulate_stats(config, root_area, depth + 1, stats);
+                right.accumulate_stats(config, root_area, depth + 1, stats);
+            }
         }
+    }
+}
 
-        // Compute centroid bounds to choose split axis
-        let centroid_bounds = objects.iter().fold(Aabb::EMPTY, |acc, obj| {
-            let c = obj.bounding_box().centroid();
-            Aabb::surrounding(&acc, &Aabb::from_points(c, c))
-        });
+/// Mapping of centroids to bins along one axis.
+#[derive(Clone, Copy)]
+struct Binning {
+    axis: usize,
+    min: f32,
+    scale: f32,
+    bin_count: usize,
+}
 
-        // Choose split axis based on centroid spread
-        let axis = centroid_bounds.longest_axis();
-
-        // Sort objects by centroid on chosen axis
-        objects.sort_unstable_by(|a, b| {
-            let a_centroid = a.bounding_box().centroid();
-            let b_centroid = b.bounding_box().centroid();
-            let a_val = match axis {
-                0 => a_centroid.x,
-                1 => a_centroid.y,
-                _ => a_centroid.z,
-            };
-            let b_val = match axis {
-                0 => b_centroid.x,
-                1 => b_centroid.y,
-                _ => b_centroid.z,
-            };
-            a_val
-                .partial_cmp(&b_val)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+impl Binning {
+    fn new(axis: usize, extent: Interval, bin_count: usize) -> Self {
+        Self {
+            axis,
+            min: extent.min,
+            scale: bin_count as f32 / extent.size(),
+            bin_count,
+        }
+    }
+
+    #[inline]
+    fn bin(&self, centroid: Vec3) -> usize {
+        let offset = (centroid[self.axis] - self.min) * self.scale;
+        (offset as usize).min(self.bin_count - 1)
+    }
+}
 
-        // Split at midpoint
-        let mid = n / 2;
-        let right_objects = objects.split_off(mid);
-        let left_objects = objects;
+/// Best split found by the binned SAH.
+struct SahSplit {
+    binning: Binning,
+    /// Items in bins below this index go left
+    split_bin: usize,
+    /// Expected cost relative to intersecting one primitive
+    cost: f32,
+}
 
-        // Recurse
-        let left = Self::build(left_objects);
-        let right = Self::build(right_objects);
+impl SahSplit {
+    #[inline]
+    fn goes_left<T>(&self, item: &BuildItem<T>) -> bool {
+        self.binning.bin(item.centroid) < self.split_bin
+    }
+}
 
-        BvhNode::Branch {
-            left: Box::new(left),
-            right: Box::new(right),
-            bbox: bounds,
+/// BVH quality report (see `BvhNode::stats`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BvhStats {
+    /// Interior and leaf nodes
+    pub node_count: usize,
+    /// Leaf nodes
+    pub leaf_count: usize,
+    /// Primitives referenced by leaves
+    pub primitive_count: usize,
+    /// Depth of the deepest leaf (root = 0)
+    pub max_depth: usize,
+    /// Sum of leaf depths (for the average)
+    pub total_leaf_depth: usize,
+    /// Fewest primitives in a leaf
+    pub min_leaf_size: usize,
+    /// Most primitives in a leaf
+    pub max_leaf_size: usize,
+    /// Expected cost of tracing a ray that hits the root bounds
+    pub sah_cost: f32,
+}
+
+impl BvhStats {
+    /// Average leaf depth.
+    pub fn average_leaf_depth(&self) -> f32 {
+        if self.leaf_count == 0 {
+            return 0.0;
+        }
+        self.total_leaf_depth as f32 / self.leaf_count as f32
+    }
+
+    /// Average number of primitives per leaf.
+    pub fn average_leaf_size(&self) -> f32 {
+        if self.leaf_count == 0 {
+            return 0.0;
         }
+        self.primitive_count as f32 / self.leaf_count as f32
+    }
+}
+
+impl fmt::Display for BvhStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SAH cost {:.2}, {} nodes, {} leaves, depth {} (avg {:.1}), leaf size {}..{} (avg {:.1})",
+            self.sah_cost,
+            self.node_count,
+            self.leaf_count,
+            self.max_depth,
+            self.average_leaf_depth(),
+            self.min_leaf_size,
+            self.max_leaf_size,
+            self.average_leaf_size()
+        )
     }
 }
 
@@ -153,6 +483,44 @@ impl Hittable for BvhNode {
         }
     }
 
+    fn hit_packet<'a>(
+        &'a self,
+        rays: &[Ray],
+        ray_t: Interval,
+        recs: &mut [HitRecord<'a>],
+        hits: &mut [bool],
+    ) {
+        let bbox = match self {
+            BvhNode::Empty => return,
+            BvhNode::Leaf { bbox, .. } | BvhNode::Branch { bbox, .. } => bbox,
+        };
+
+        // Descend if any ray of the packet enters the node
+        let entered = rays
+            .iter()
+            .zip(recs.iter())
+            .zip(hits.iter())
+            .any(|((ray, rec), &hit)| {
+                bbox.hit(&to_math_ray(ray), packet_interval(ray_t, rec, hit))
+            });
+        if !entered {
+            return;
+        }
+
+        match self {
+            BvhNode::Empty => {}
+            BvhNode::Leaf { objects, .. } => {
+                for obj in objects {
+                    obj.hit_packet(rays, ray_t, recs, hits);
+                }
+            }
+            BvhNode::Branch { left, right, .. } => {
+                left.hit_packet(rays, ray_t, recs, hits);
+                right.hit_packet(rays, ray_t, recs, hits);
+            }
+        }
+    }
+
     fn bounding_box(&self) -> Aabb {
         match self {
             BvhNode::Empty => Aabb::EMPTY,
@@ -221,4 +589,143 @@ mod tests {
         // Hit point should be near z = -4.5 (sphere at z=-5, radius 0.5)
         assert!((rec.p.z - (-4.5)).abs() < 0.01);
     }
+
+    #[test]
+    fn test_bvh_ray_passes_through_cutout() {
+        use crate::{ShadingGraphMaterial, Triangle};
+        use bif_core::TextureCache;
+
+        let transparent = bif_core::Material {
+            opacity: 0.0,
+            ..Default::default()
+        };
+        let quad_at = |z: f32, mat: &bif_core::Material| {
+            Box::new(Triangle::new(
+                Vec3::new(-1.0, -1.0, z),
+                Vec3::new(1.0, -1.0, z),
+                Vec3::new(0.0, 1.0, z),
+                ShadingGraphMaterial::new(mat, &mut TextureCache::new()),
+            )) as Box<dyn Hittable + Send + Sync>
+        };
+
+        // Masked card in front of an opaque one
+        let objects = vec![
+            quad_at(-1.0, &transparent),
+            quad_at(-3.0, &bif_core::Material::default()),
+        ];
+        let bvh = BvhNode::new(objects);
+
+        let ray = Ray::new(Vec3::ZERO, Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(bvh.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.t - 3.0).abs() < 1e-4, "t = {}", rec.t);
+    }
+
+    fn sphere_at(center: Vec3, radius: f32) -> Box<dyn Hittable + Send + Sync> {
+        Box::new(Sphere::new(
+            center,
+            radius,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        ))
+    }
+
+    #[test]
+    fn test_bvh_sah_isolates_large_object() {
+        // A large ground object next to a dense cluster: the median split
+        // would group the ground with half the cluster
+        let mut objects = vec![sphere_at(Vec3::new(0.0, -1000.0, 0.0), 999.0)];
+        for i in 0..64 {
+            let offset = Vec3::new((i % 8) as f32, (i / 8) as f32, 0.0) * 0.1;
+            objects.push(sphere_at(offset, 0.04));
+        }
+        let config = BvhBuildConfig::default();
+        let bvh = BvhNode::with_config(objects, &config);
+
+        let BvhNode::Branch { left, right, .. } = &bvh else {
+            panic!("expected a branch at the root");
+        };
+        let sizes = [left.stats(&config), right.stats(&config)].map(|s| s.primitive_count);
+        assert!(sizes.contains(&1), "ground not isolated: {sizes:?}");
+
+        // Hits still resolve to the closest object
+        let ray = Ray::new(Vec3::new(0.3, 0.3, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(bvh.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.p.z - 0.04).abs() < 1e-3, "z = {}", rec.p.z);
+    }
+
+    #[test]
+    fn test_bvh_stats() {
+        let objects: Vec<_> = (0..100)
+            .map(|i| sphere_at(Vec3::new(i as f32, 0.0, 0.0), 0.4))
+            .collect();
+        let config = BvhBuildConfig {
+            max_leaf_size: 2,
+            ..Default::default()
+        };
+        let stats = BvhNode::with_config(objects, &config).stats(&config);
+
+        assert_eq!(stats.primitive_count, 100);
+        assert_eq!(stats.node_count, 2 * stats.leaf_count - 1);
+        assert!(stats.min_leaf_size >= 1 && stats.max_leaf_size <= 2);
+        assert!(stats.max_depth >= 6);
+        assert!(stats.sah_cost > 0.0);
+        assert!(stats.to_string().starts_with("SAH cost"));
+    }
+
+    #[test]
+    fn test_bvh_depth_is_capped() {
+        // A subtree started 10 levels above the cap must fit in those levels,
+        // so its 256 objects switch from SAH to median splits on the way
+        let items = (0..256)
+            .map(|i| {
+                let sphere = sphere_at(Vec3::new(1.1f32.powi(i % 100), i as f32, 0.0), 0.3);
+                let bbox = sphere.bounding_box();
+                BuildItem::new(sphere, bbox)
+            })
+            .collect();
+        let config = BvhBuildConfig::default();
+        let bvh = BvhNode::build(items, &config, MAX_DEPTH - 10);
+        let stats = bvh.stats(&config);
+        assert_eq!(stats.primitive_count, 256);
+        assert!(stats.max_depth <= 10, "depth {}", stats.max_depth);
+
+        let ray = Ray::new(Vec3::new(1.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(bvh.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.t - 4.7).abs() < 1e-3, "t = {}", rec.t);
+    }
+
+    #[test]
+    fn test_bvh_coincident_centroids() {
+        // Identical objects can't be separated by SAH and must still build
+        let objects: Vec<_> = (0..10).map(|_| sphere_at(Vec3::ZERO, 1.0)).collect();
+        let bvh = BvhNode::new(objects);
+        let stats = bvh.stats(&BvhBuildConfig::default());
+        assert_eq!(stats.primitive_count, 10);
+        assert!(stats.max_leaf_size <= LEAF_MAX_SIZE);
+    }
+
+    #[test]
+    fn test_bvh_parallel_build_matches_serial() {
+        let objects = || -> Vec<_> {
+            (0..500)
+                .map(|i| {
+                    let (x, y) = ((i % 25) as f32, (i / 25) as f32);
+                    sphere_at(Vec3::new(x, y, -(x * y) % 7.0), 0.3)
+                })
+                .collect()
+        };
+        let serial = BvhBuildConfig {
+            parallel_threshold: usize::MAX,
+            ..Default::default()
+        };
+        let parallel = BvhBuildConfig {
+            parallel_threshold: 8,
+            ..Default::default()
+        };
+        let a = BvhNode::with_config(objects(), &serial);
+        let b = BvhNode::with_config(objects(), &parallel);
+        assert_eq!(a.stats(&serial), b.stats(&serial));
+    }
 }
@@ -23,6 +23,10 @@ pub struct Camera {
     defocus_angle: f32, // Variation angle of rays through each pixel
     focus_dist: f32,    // Distance from camera to plane of perfect focus
 
+    // Shutter interval in normalized motion time (keys span 0 to 1)
+    shutter_open: f32,
+    shutter_close: f32,
+
     // Background color
     pub background: Vec3,
 
@@ -53,6 +57,8 @@ impl Camera {
             vfov: 90.0,
             defocus_angle: 0.0,
             focus_dist: 1.0,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
             background: Vec3::ZERO,
             // Cached values (initialized to defaults)
             center: Vec3::ZERO,
@@ -98,6 +104,41 @@ impl Camera {
         self
     }
 
+    /// Look through a scene camera (a UsdGeom `Camera`).
+    ///
+    /// Takes its position, orientation, vertical field of view and depth of
+    /// field; resolution, quality and shutter are kept. The image aspect
+    /// comes from the resolution, so the horizontal aperture is ignored.
+    pub fn with_scene_camera(self, camera: &bif_core::Camera) -> Self {
+        let position = camera.position();
+        let (defocus_angle, focus_dist) = if camera.has_depth_of_field() {
+            let radius = camera.lens_diameter() * 0.5;
+            let angle = 2.0 * (radius / camera.focus_distance).atan();
+            (angle.to_degrees(), camera.focus_distance)
+        } else {
+            (0.0, 1.0)
+        };
+
+        self.with_position(position, position + camera.direction(), camera.up())
+            .with_lens(camera.vertical_fov(), defocus_angle, focus_dist)
+    }
+
+    /// Set the shutter interval for motion blur.
+    ///
+    /// Times are normalized so motion keys span 0 (first key) to 1 (last
+    /// key). Rays are spread uniformly over `[open, close]`; equal times
+    /// (the default, both 0) disable motion blur.
+    pub fn with_shutter(mut self, open: f32, close: f32) -> Self {
+        self.shutter_open = open;
+        self.shutter_close = close.max(open);
+        self
+    }
+
+    /// Shutter open and close times.
+    pub fn shutter(&self) -> (f32, f32) {
+        (self.shutter_open, self.shutter_close)
+    }
+
     /// Set background color.
     pub fn with_background(mut self, color: Vec3) -> Self {
         self.background = color;
@@ -155,7 +196,7 @@ impl Camera {
         };
 
         let ray_direction = pixel_sample - ray_origin;
-        let ray_time = gen_f32(rng);
+        let ray_time = self.shutter_open + (self.shutter_close - self.shutter_open) * gen_f32(rng);
 
         Ray::new(ray_origin, ray_direction, ray_time)
     }
@@ -235,4 +276,19 @@ mod tests {
         let ray = camera.get_ray(50, 50, &mut rng);
         assert!(ray.direction().z < 0.0);
     }
+
+    #[test]
+    fn test_shutter_times() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut camera = Camera::new().with_resolution(10, 10);
+        camera.initialize();
+        assert_eq!(camera.get_ray(5, 5, &mut rng).time(), 0.0);
+
+        let mut camera = camera.with_shutter(0.25, 0.75);
+        camera.initialize();
+        for _ in 0..100 {
+            let time = camera.get_ray(5, 5, &mut rng).time();
+            assert!((0.25..=0.75).contains(&time));
+        }
+    }
 }
@@ -0,0 +1,209 @@
+//! Axis-aligned box primitive for ray tracing.
+
+use crate::{
+    hittable::{alpha_test, HitRecord, Hittable},
+    ray::ray_point_error,
+    AreaLight, Material, Ray, SurfacePoint,
+};
+use bif_math::{Aabb, Interval, Vec3};
+
+/// A solid axis-aligned box.
+///
+/// Named `Cuboid` to stay clear of `std::boxed::Box`. Each face has its own
+/// [0, 1]² texture coordinates along the two other axes in cyclic order
+/// (y/z on the x faces, z/x on the y faces, x/y on the z faces).
+pub struct Cuboid<M: Material> {
+    min: Vec3,
+    max: Vec3,
+    /// Areas of the x, y and z faces
+    face_areas: [f32; 3],
+    material: M,
+    bbox: Aabb,
+}
+
+impl<M: Material> Cuboid<M> {
+    /// Create a box between two opposite corners.
+    pub fn new(a: Vec3, b: Vec3, material: M) -> Self {
+        let (min, max) = (a.min(b), a.max(b));
+        let size = max - min;
+
+        Self {
+            min,
+            max,
+            face_areas: [size.y * size.z, size.z * size.x, size.x * size.y],
+            material,
+            bbox: Aabb::from_points(min, max),
+        }
+    }
+
+    /// Texture coordinates of `p` on a face perpendicular to `axis`.
+    #[inline]
+    fn uv(&self, p: Vec3, axis: usize) -> (f32, f32) {
+        let coordinate = |i: usize| {
+            let extent = self.max[i] - self.min[i];
+            if extent > 0.0 {
+                (p[i] - self.min[i]) / extent
+            } else {
+                0.0
+            }
+        };
+        (coordinate((axis + 1) % 3), coordinate((axis + 2) % 3))
+    }
+}
+
+impl<M: Material + 'static> Hittable for Cuboid<M> {
+    fn hit<'a>(&'a self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord<'a>) -> bool {
+        let origin = ray.origin();
+        let direction = ray.direction();
+
+        // Slab test, remembering which axis bounds the entry and exit
+        let (mut t_near, mut near_axis) = (f32::NEG_INFINITY, 0);
+        let (mut t_far, mut far_axis) = (f32::INFINITY, 0);
+        for axis in 0..3 {
+            let inv = 1.0 / direction[axis];
+            let t0 = (self.min[axis] - origin[axis]) * inv;
+            let t1 = (self.max[axis] - origin[axis]) * inv;
+            let (near, far) = (t0.min(t1), t0.max(t1));
+            if near > t_near {
+                (t_near, near_axis) = (near, axis);
+            }
+            if far < t_far {
+                (t_far, far_axis) = (far, axis);
+            }
+        }
+        if t_near > t_far {
+            return false;
+        }
+
+        // Front faces point against the ray on entry and along it on exit
+        for (t, axis, sign) in [(t_near, near_axis, -1.0), (t_far, far_axis, 1.0)] {
+            if !ray_t.surrounds(t) {
+                continue;
+            }
+
+            let p = ray.at(t);
+            let (u, v) = self.uv(p, axis);
+            if !alpha_test(self.material.opacity(u, v), origin, direction, t) {
+                continue;
+            }
+
+            let mut outward_normal = Vec3::ZERO;
+            outward_normal[axis] = sign * direction[axis].signum();
+            rec.t = t;
+            rec.p = p;
+            rec.p_error = ray_point_error(ray, t);
+            rec.set_face_normal(ray, outward_normal);
+            (rec.u, rec.v) = (u, v);
+            rec.material = &self.material;
+            return true;
+        }
+
+        false
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+impl<M: Material + 'static> AreaLight for Cuboid<M> {
+    fn area(&self) -> f32 {
+        2.0 * self.face_areas.iter().sum::<f32>()
+    }
+
+    fn sample_area(&self, s: f32, t: f32) -> SurfacePoint {
+        // Pick one of the six faces by area, then reuse `s` within it
+        let mut remaining = s * self.area();
+        let mut face = 5;
+        for i in 0..6 {
+            let area = self.face_areas[i / 2];
+            if remaining < area || i == 5 {
+                face = i;
+                break;
+            }
+            remaining -= area;
+        }
+        let axis = face / 2;
+        let area = self.face_areas[axis];
+        let s = if area > 0.0 {
+            (remaining / area).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let mut p = Vec3::ZERO;
+        let mut normal = Vec3::ZERO;
+        let (ua, va) = ((axis + 1) % 3, (axis + 2) % 3);
+        p[ua] = self.min[ua] + s * (self.max[ua] - self.min[ua]);
+        p[va] = self.min[va] + t * (self.max[va] - self.min[va]);
+        if face % 2 == 0 {
+            (p[axis], normal[axis]) = (self.min[axis], -1.0);
+        } else {
+            (p[axis], normal[axis]) = (self.max[axis], 1.0);
+        }
+
+        SurfacePoint {
+            p,
+            normal,
+            u: s,
+            v: t,
+        }
+    }
+
+    fn material(&self) -> &dyn Material {
+        &self.material
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    fn cuboid() -> Cuboid<Lambertian> {
+        Cuboid::new(
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(-1.0, -2.0, -3.0),
+            Lambertian::new(Vec3::ONE),
+        )
+    }
+
+    #[test]
+    fn test_cuboid_hit_faces() {
+        let cuboid = cuboid();
+        let interval = Interval::new(0.001, f32::INFINITY);
+        let mut rec = HitRecord::default();
+
+        let ray = Ray::new_simple(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(cuboid.hit(&ray, interval, &mut rec));
+        assert!((rec.t - 4.0).abs() < 1e-5);
+        assert_eq!(rec.normal, Vec3::Z);
+        assert!(rec.front_face);
+        assert!((rec.u - 0.5).abs() < 1e-5 && (rec.v - 2.0 / 3.0).abs() < 1e-5);
+
+        // From inside, the exit face is hit from behind
+        let ray = Ray::new_simple(Vec3::ZERO, Vec3::new(-1.0, 0.0, 0.0));
+        assert!(cuboid.hit(&ray, interval, &mut rec));
+        assert!((rec.t - 1.0).abs() < 1e-5);
+        assert!(!rec.front_face);
+
+        let ray = Ray::new_simple(Vec3::new(2.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(!cuboid.hit(&ray, interval, &mut rec));
+    }
+
+    #[test]
+    fn test_cuboid_sampling_by_face_area() {
+        let cuboid = cuboid();
+        // Faces: x = 3 * 4, y = 4 * 2, z = 2 * 3
+        assert!((cuboid.area() - 52.0).abs() < 1e-4);
+
+        let point = cuboid.sample_area(0.0, 0.5);
+        assert_eq!(point.normal, Vec3::new(-1.0, 0.0, 0.0));
+        assert_eq!(point.p.x, -1.0);
+
+        let point = cuboid.sample_area(0.999, 0.5);
+        assert_eq!(point.normal, Vec3::Z);
+        assert_eq!(point.p.z, 1.0);
+        assert!(point.p.x >= -1.0 && point.p.x <= 1.0);
+    }
+}
@@ -0,0 +1,395 @@
+//! Curve primitive (hair, fur) for ray tracing.
+//!
+//! Curves are traced as cubic Bézier segments with the recursive
+//! subdivision test from PBRT: each segment is transformed into a frame
+//! where the ray runs along +z, split until it is nearly straight, and the
+//! ray is then tested against the swept width of the straight piece. Round
+//! curves bend the normal across the width so they shade like tubes;
+//! ribbons keep a flat normal facing the ray.
+
+use crate::{
+    hittable::{HitRecord, Hittable},
+    material::build_tangent_basis,
+    primitive_bvh::PrimitiveBvh,
+    ray::ray_point_error,
+    Material, Ray,
+};
+use bif_core::curves::{CurveSegment, CurveShape, Curves};
+use bif_math::{Aabb, Interval, Mat4, Vec3};
+use std::sync::Arc;
+
+/// Maximum number of Bézier splits per segment.
+const MAX_SUBDIVISION_DEPTH: i32 = 10;
+
+/// A set of curves in world space with their own BVH and material.
+///
+/// Shared between the pure-Rust worlds (as a top-level leaf) and
+/// `EmbreeScene` (which intersects the segments itself and uses
+/// [`CurveGeometry::fill_record`] for shading).
+pub struct CurveGeometry {
+    segments: Vec<CurveSegment>,
+    shape: CurveShape,
+    bvh: PrimitiveBvh,
+    material: Arc<dyn Material>,
+}
+
+/// Ray-aligned coordinate frame: the ray starts at the origin and runs
+/// along +z, with z measured in world units.
+struct RayFrame {
+    origin: Vec3,
+    x: Vec3,
+    y: Vec3,
+    z: Vec3,
+    /// Length of the ray direction, to convert z back to ray t
+    length: f32,
+}
+
+impl RayFrame {
+    fn new(ray: &Ray) -> Self {
+        let length = ray.direction().length();
+        let z = ray.direction() / length;
+        let (x, y) = build_tangent_basis(z);
+        Self {
+            origin: ray.origin(),
+            x,
+            y,
+            z,
+            length,
+        }
+    }
+
+    #[inline]
+    fn to_local(&self, p: Vec3) -> Vec3 {
+        let d = p - self.origin;
+        Vec3::new(d.dot(self.x), d.dot(self.y), d.dot(self.z))
+    }
+}
+
+impl CurveGeometry {
+    /// Place `curves` in the world with `transform`.
+    ///
+    /// Widths are scaled by the transform's average scale factor.
+    pub fn new(curves: &Curves, transform: Mat4, material: Arc<dyn Material>) -> Self {
+        let width_scale = transform.determinant().abs().cbrt();
+        let segments: Vec<CurveSegment> = curves
+            .segments()
+            .into_iter()
+            .map(|segment| CurveSegment {
+                points: segment.points.map(|p| transform.transform_point3(p)),
+                widths: segment.widths.map(|w| w * width_scale),
+                ..segment
+            })
+            .collect();
+
+        let bounds: Vec<Aabb> = segments.iter().map(CurveSegment::bounds).collect();
+        let bvh = PrimitiveBvh::new(&bounds);
+        log::debug!(
+            "Curves: {} curves, {} segments, {} nodes",
+            curves.curve_count(),
+            segments.len(),
+            bvh.node_count()
+        );
+
+        Self {
+            segments,
+            shape: curves.shape,
+            bvh,
+            material,
+        }
+    }
+
+    /// World-space Bézier segments.
+    pub fn segments(&self) -> &[CurveSegment] {
+        &self.segments
+    }
+
+    /// Cross-section shared by all segments.
+    pub fn shape(&self) -> CurveShape {
+        self.shape
+    }
+
+    /// Number of Bézier segments.
+    pub fn segment_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Intersect one segment, returning (t, segment parameter).
+    fn intersect_segment(
+        &self,
+        frame: &RayFrame,
+        segment: &CurveSegment,
+        ray_t: Interval,
+    ) -> Option<(f32, f32)> {
+        let cp = segment.points.map(|p| frame.to_local(p));
+
+        // Split until the pieces are flat relative to the curve width
+        let l0 = (0..2)
+            .map(|i| (cp[i] - 2.0 * cp[i + 1] + cp[i + 2]).abs().max_element())
+            .fold(0.0, f32::max);
+        let eps = segment.widths[0].max(segment.widths[1]) * 0.05;
+        let depth = if l0 > 0.0 && eps > 0.0 {
+            let r0 = (std::f32::consts::SQRT_2 * 6.0 * l0 / (8.0 * eps)).log2() as i32 / 2;
+            r0.clamp(0, MAX_SUBDIVISION_DEPTH)
+        } else {
+            0
+        };
+
+        let z_range = Interval::new(ray_t.min * frame.length, ray_t.max * frame.length);
+        let (z, w) = recursive_intersect(cp, segment.widths, [0.0, 1.0], depth, z_range)?;
+        Some((z / frame.length, w))
+    }
+
+    /// Fill `rec` for a hit on `segment` at parameter `w` and ray `t`.
+    ///
+    /// `rec.u` is the parameter along the whole curve (0 at the root),
+    /// `rec.v` the position across the width (0.5 on the spine) and
+    /// `rec.tangent` the curve direction.
+    pub fn fill_record<'a>(
+        &'a self,
+        ray: &Ray,
+        segment: usize,
+        w: f32,
+        t: f32,
+        rec: &mut HitRecord<'a>,
+    ) {
+        let segment = &self.segments[segment];
+        let p = ray.at(t);
+        let tangent = bezier_derivative(&segment.points, w)
+            .try_normalize()
+            .or_else(|| (segment.points[3] - segment.points[0]).try_normalize())
+            .unwrap_or(Vec3::X);
+
+        // Flat normal: towards the ray origin, perpendicular to the tangent
+        let wo = -ray.direction().normalize();
+        let facing = (wo - tangent * tangent.dot(wo))
+            .try_normalize()
+            .unwrap_or_else(|| build_tangent_basis(tangent).0);
+        let across = facing.cross(tangent);
+
+        let radius = 0.5 * segment.width(w);
+        let h = if radius > 0.0 {
+            ((p - segment.point(w)).dot(across) / radius).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let normal = match self.shape {
+            CurveShape::Round => facing * (1.0 - h * h).max(0.0).sqrt() + across * h,
+            CurveShape::Ribbon => facing,
+        };
+
+        rec.t = t;
+        rec.p = p;
+        rec.p_error = ray_point_error(ray, t);
+        rec.set_face_normal(ray, normal);
+        rec.instance = None;
+        let [u0, u1] = segment.u_range;
+        rec.u = u0 + (u1 - u0) * w;
+        rec.v = 0.5 * (h + 1.0);
+        rec.tangent = tangent;
+        rec.material = self.material.as_ref();
+    }
+}
+
+impl Hittable for CurveGeometry {
+    fn hit<'a>(&'a self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord<'a>) -> bool {
+        let frame = RayFrame::new(ray);
+        let hit = self.bvh.intersect(ray, ray_t, |segment, interval| {
+            self.intersect_segment(&frame, &self.segments[segment as usize], interval)
+        });
+        match hit {
+            Some((t, segment, w)) => {
+                self.fill_record(ray, segment as usize, w, t, rec);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bvh.bounding_box()
+    }
+}
+
+/// Derivative of a cubic Bézier curve.
+fn bezier_derivative(cp: &[Vec3; 4], t: f32) -> Vec3 {
+    let s = 1.0 - t;
+    3.0 * (s * s * (cp[1] - cp[0]) + 2.0 * s * t * (cp[2] - cp[1]) + t * t * (cp[3] - cp[2]))
+}
+
+/// Split a cubic Bézier curve at its midpoint.
+fn bezier_split(cp: &[Vec3; 4]) -> ([Vec3; 4], [Vec3; 4]) {
+    let p01 = (cp[0] + cp[1]) * 0.5;
+    let p12 = (cp[1] + cp[2]) * 0.5;
+    let p23 = (cp[2] + cp[3]) * 0.5;
+    let p012 = (p01 + p12) * 0.5;
+    let p123 = (p12 + p23) * 0.5;
+    let mid = (p012 + p123) * 0.5;
+    ([cp[0], p01, p012, mid], [mid, p123, p23, cp[3]])
+}
+
+/// Closest hit of the +z axis with a curve piece in ray space.
+///
+/// `widths` are the widths at the ends of the piece and `u` its parameter
+/// range within the segment. Returns (z, segment parameter).
+fn recursive_intersect(
+    cp: [Vec3; 4],
+    widths: [f32; 2],
+    u: [f32; 2],
+    depth: i32,
+    z_range: Interval,
+) -> Option<(f32, f32)> {
+    // Cull pieces whose padded hull misses the ray
+    let pad = Vec3::splat(0.5 * widths[0].max(widths[1]));
+    let min = cp[0].min(cp[1]).min(cp[2]).min(cp[3]) - pad;
+    let max = cp[0].max(cp[1]).max(cp[2]).max(cp[3]) + pad;
+    if min.x > 0.0 || max.x < 0.0 || min.y > 0.0 || max.y < 0.0 {
+        return None;
+    }
+    if max.z < z_range.min || min.z > z_range.max {
+        return None;
+    }
+
+    if depth > 0 {
+        let (left, right) = bezier_split(&cp);
+        let w_mid = 0.5 * (widths[0] + widths[1]);
+        let u_mid = 0.5 * (u[0] + u[1]);
+        let near = recursive_intersect(left, [widths[0], w_mid], [u[0], u_mid], depth - 1, z_range);
+        let z_max = near.map_or(z_range.max, |(z, _)| z);
+        let far = recursive_intersect(
+            right,
+            [w_mid, widths[1]],
+            [u_mid, u[1]],
+            depth - 1,
+            Interval::new(z_range.min, z_max),
+        );
+        return far.or(near);
+    }
+
+    // The ray must pass between the planes through the piece's end points
+    let edge = (cp[1].y - cp[0].y) * -cp[0].y + cp[0].x * (cp[0].x - cp[1].x);
+    if edge < 0.0 {
+        return None;
+    }
+    let edge = (cp[2].y - cp[3].y) * -cp[3].y + cp[3].x * (cp[3].x - cp[2].x);
+    if edge < 0.0 {
+        return None;
+    }
+
+    // Closest point to the ray on the straightened piece
+    let dx = cp[3].x - cp[0].x;
+    let dy = cp[3].y - cp[0].y;
+    let denom = dx * dx + dy * dy;
+    if denom == 0.0 {
+        return None;
+    }
+    let w = ((-cp[0].x * dx - cp[0].y * dy) / denom).clamp(0.0, 1.0);
+
+    let width = widths[0] + (widths[1] - widths[0]) * w;
+    let pc = CurveSegment {
+        points: cp,
+        widths,
+        curve: 0,
+        u_range: u,
+    }
+    .point(w);
+    if pc.x * pc.x + pc.y * pc.y > width * width * 0.25 {
+        return None;
+    }
+    if !z_range.surrounds(pc.z) {
+        return None;
+    }
+
+    Some((pc.z, u[0] + (u[1] - u[0]) * w))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, Lambertian};
+    use bif_core::curves::CurveBasis;
+
+    fn straight_hair(shape: CurveShape) -> CurveGeometry {
+        // Vertical curve of width 0.2 along +y at the origin
+        let positions = (0..4).map(|i| Vec3::new(0.0, i as f32, 0.0)).collect();
+        let curves = Curves::new(positions, vec![4], &[0.2], CurveBasis::Linear, shape);
+        CurveGeometry::new(
+            &curves,
+            Mat4::IDENTITY,
+            Arc::new(Lambertian::new(Color::ONE)),
+        )
+    }
+
+    #[test]
+    fn test_hit_round_curve() {
+        let curve = straight_hair(CurveShape::Round);
+        assert_eq!(curve.segment_count(), 3);
+
+        let ray = Ray::new(Vec3::new(0.05, 1.5, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(curve.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.t - 5.0).abs() < 1e-4, "t = {}", rec.t);
+        assert!((rec.u - 0.5).abs() < 1e-3);
+        // Half way to the edge of the tube: normal bends sideways
+        assert!((rec.v - 0.75).abs() < 1e-3 || (rec.v - 0.25).abs() < 1e-3);
+        assert!(rec.normal.x.abs() > 0.4 && rec.normal.z > 0.0);
+        assert!((rec.tangent - Vec3::Y).length() < 1e-4);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn test_miss_beyond_width() {
+        let curve = straight_hair(CurveShape::Ribbon);
+        let ray = Ray::new(Vec3::new(0.15, 1.5, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(!curve.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+
+        // Past the tip
+        let ray = Ray::new(Vec3::new(0.0, 3.2, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert!(!curve.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+    }
+
+    #[test]
+    fn test_ribbon_normal_faces_ray() {
+        let curve = straight_hair(CurveShape::Ribbon);
+        let ray = Ray::new(Vec3::new(3.0, 0.5, 0.05), Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(curve.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.normal - Vec3::X).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_curved_bspline_matches_curve_point() {
+        // Arc-like B-spline in the xy plane, hit from +z at a point on the curve
+        let positions = vec![
+            Vec3::new(-2.0, 0.0, 0.0),
+            Vec3::new(-1.0, 1.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+        ];
+        let curves = Curves::new(
+            positions,
+            vec![4],
+            &[0.05],
+            CurveBasis::BSpline,
+            CurveShape::Round,
+        );
+        let curve = CurveGeometry::new(
+            &curves,
+            Mat4::from_translation(Vec3::new(0.0, 0.0, -1.0)),
+            Arc::new(Lambertian::new(Color::ONE)),
+        );
+        let target = curve.segments()[0].point(0.3);
+
+        let ray = Ray::new(
+            Vec3::new(target.x, target.y, 4.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            0.0,
+        );
+        let mut rec = HitRecord::default();
+        assert!(curve.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.t - 5.0).abs() < 1e-3, "t = {}", rec.t);
+        assert!((rec.u - 0.3).abs() < 0.02, "u = {}", rec.u);
+        assert!((rec.v - 0.5).abs() < 0.05);
+    }
+}
@@ -0,0 +1,198 @@
+//! Cylinder primitive for ray tracing.
+
+use crate::{
+    hittable::{alpha_test, HitRecord, Hittable},
+    material::build_tangent_basis,
+    ray::ray_point_error,
+    AreaLight, Material, Ray, SurfacePoint,
+};
+use bif_math::{Aabb, Interval, Vec3};
+use std::f32::consts::PI;
+
+/// The open side of a cylinder, centered on `center` along `axis`.
+///
+/// The tube spans `height / 2` on either side of the center, like USD's
+/// `Cylinder`; add two `Disk`s for a closed cylinder. Texture coordinates
+/// are the angle around the axis (u) and the position along it (v).
+pub struct Cylinder<M: Material> {
+    center: Vec3,
+    axis: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    radius: f32,
+    height: f32,
+    material: M,
+    bbox: Aabb,
+}
+
+impl<M: Material> Cylinder<M> {
+    /// Create a cylinder of `radius` and `height` around `axis`.
+    pub fn new(center: Vec3, axis: Vec3, radius: f32, height: f32, material: M) -> Self {
+        let axis = axis.normalize();
+        let radius = radius.max(0.0);
+        let height = height.max(0.0);
+        let (tangent, bitangent) = build_tangent_basis(axis);
+
+        // Bounds of the two end circles
+        let half = axis * (height * 0.5);
+        let e = (Vec3::ONE - axis * axis).max(Vec3::ZERO);
+        let extent = radius * Vec3::new(e.x.sqrt(), e.y.sqrt(), e.z.sqrt());
+        let bbox = Aabb::surrounding(
+            &Aabb::from_points(center - half - extent, center - half + extent),
+            &Aabb::from_points(center + half - extent, center + half + extent),
+        );
+
+        Self {
+            center,
+            axis,
+            tangent,
+            bitangent,
+            radius,
+            height,
+            material,
+            bbox,
+        }
+    }
+
+    /// Texture coordinates for a point in the cylinder's local frame.
+    #[inline]
+    fn uv(&self, x: f32, y: f32, z: f32) -> (f32, f32) {
+        let phi = y.atan2(x);
+        let u = if phi < 0.0 {
+            phi / (2.0 * PI) + 1.0
+        } else {
+            phi / (2.0 * PI)
+        };
+        (u, z / self.height + 0.5)
+    }
+}
+
+impl<M: Material + 'static> Hittable for Cylinder<M> {
+    fn hit<'a>(&'a self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord<'a>) -> bool {
+        // Work in the local frame, where the axis is +Z
+        let o = ray.origin() - self.center;
+        let d = ray.direction();
+        let (ox, oy, oz) = (o.dot(self.tangent), o.dot(self.bitangent), o.dot(self.axis));
+        let (dx, dy, dz) = (d.dot(self.tangent), d.dot(self.bitangent), d.dot(self.axis));
+
+        let a = dx * dx + dy * dy;
+        if a <= 0.0 {
+            return false;
+        }
+        let h = ox * dx + oy * dy;
+        let c = ox * ox + oy * oy - self.radius * self.radius;
+        let discriminant = h * h - a * c;
+        if discriminant < 0.0 {
+            return false;
+        }
+        let sqrtd = discriminant.sqrt();
+
+        // Nearest root within the interval, the height and the alpha mask
+        for root in [(-h - sqrtd) / a, (-h + sqrtd) / a] {
+            if !ray_t.surrounds(root) {
+                continue;
+            }
+
+            let (x, y, z) = (ox + root * dx, oy + root * dy, oz + root * dz);
+            if z.abs() > self.height * 0.5 {
+                continue;
+            }
+
+            let (u, v) = self.uv(x, y, z);
+            if !alpha_test(self.material.opacity(u, v), ray.origin(), d, root) {
+                continue;
+            }
+
+            let outward_normal = (x * self.tangent + y * self.bitangent) / self.radius;
+            rec.t = root;
+            rec.p = ray.at(root);
+            rec.p_error = ray_point_error(ray, root);
+            rec.set_face_normal(ray, outward_normal);
+            (rec.u, rec.v) = (u, v);
+            rec.material = &self.material;
+            return true;
+        }
+
+        false
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+impl<M: Material + 'static> AreaLight for Cylinder<M> {
+    fn area(&self) -> f32 {
+        2.0 * PI * self.radius * self.height
+    }
+
+    fn sample_area(&self, s: f32, t: f32) -> SurfacePoint {
+        let phi = 2.0 * PI * s;
+        let normal = phi.cos() * self.tangent + phi.sin() * self.bitangent;
+        let z = (t - 0.5) * self.height;
+
+        SurfacePoint {
+            p: self.center + self.radius * normal + z * self.axis,
+            normal,
+            u: s,
+            v: t,
+        }
+    }
+
+    fn material(&self) -> &dyn Material {
+        &self.material
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    fn cylinder() -> Cylinder<Lambertian> {
+        Cylinder::new(Vec3::ZERO, Vec3::Y, 1.0, 2.0, Lambertian::new(Vec3::ONE))
+    }
+
+    #[test]
+    fn test_cylinder_hit_side() {
+        let cylinder = cylinder();
+        let interval = Interval::new(0.001, f32::INFINITY);
+        let mut rec = HitRecord::default();
+
+        let ray = Ray::new_simple(Vec3::new(0.0, 0.5, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(cylinder.hit(&ray, interval, &mut rec));
+        assert!((rec.t - 4.0).abs() < 1e-5);
+        assert!((rec.normal - Vec3::Z).length() < 1e-5);
+        assert!((rec.v - 0.75).abs() < 1e-5);
+
+        // Above the tube, and straight down its open end
+        let ray = Ray::new_simple(Vec3::new(0.0, 1.5, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(!cylinder.hit(&ray, interval, &mut rec));
+        let ray = Ray::new_simple(Vec3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(!cylinder.hit(&ray, interval, &mut rec));
+    }
+
+    #[test]
+    fn test_cylinder_hit_from_inside() {
+        let cylinder = cylinder();
+        let mut rec = HitRecord::default();
+        let ray = Ray::new_simple(Vec3::ZERO, Vec3::X);
+
+        assert!(cylinder.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.t - 1.0).abs() < 1e-5);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn test_cylinder_sample_uv_matches_hit() {
+        let cylinder = cylinder();
+        let point = cylinder.sample_area(0.3, 0.6);
+
+        // Shoot back at the sample from outside along its normal
+        let ray = Ray::new_simple(point.p + point.normal * 3.0, -point.normal);
+        let mut rec = HitRecord::default();
+        assert!(cylinder.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.u - point.u).abs() < 1e-4);
+        assert!((rec.v - point.v).abs() < 1e-4);
+    }
+}
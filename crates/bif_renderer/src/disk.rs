@@ -0,0 +1,164 @@
+//! Disk primitive for ray tracing.
+
+use crate::{
+    hittable::{alpha_test, HitRecord, Hittable},
+    material::build_tangent_basis,
+    ray::ray_point_error,
+    AreaLight, Material, Ray, SurfacePoint,
+};
+use bif_math::{Aabb, Interval, Vec3};
+use std::f32::consts::PI;
+
+/// A flat circular disk.
+///
+/// Texture coordinates map the disk's bounding square in its tangent frame
+/// to [0, 1]², so an image is projected onto the disk without distortion.
+pub struct Disk<M: Material> {
+    center: Vec3,
+    normal: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    radius: f32,
+    material: M,
+    bbox: Aabb,
+}
+
+impl<M: Material> Disk<M> {
+    /// Create a disk facing `normal`.
+    pub fn new(center: Vec3, normal: Vec3, radius: f32, material: M) -> Self {
+        let normal = normal.normalize();
+        let radius = radius.max(0.0);
+        let (tangent, bitangent) = build_tangent_basis(normal);
+
+        // Half-extent of the disk along each world axis
+        let e = (Vec3::ONE - normal * normal).max(Vec3::ZERO);
+        let extent = radius * Vec3::new(e.x.sqrt(), e.y.sqrt(), e.z.sqrt());
+        let bbox = Aabb::from_points(center - extent, center + extent);
+
+        Self {
+            center,
+            normal,
+            tangent,
+            bitangent,
+            radius,
+            material,
+            bbox,
+        }
+    }
+
+    /// Texture coordinates for a point in the disk's tangent frame.
+    #[inline]
+    fn uv(&self, x: f32, y: f32) -> (f32, f32) {
+        ((x / self.radius + 1.0) * 0.5, (y / self.radius + 1.0) * 0.5)
+    }
+}
+
+impl<M: Material + 'static> Hittable for Disk<M> {
+    fn hit<'a>(&'a self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord<'a>) -> bool {
+        let denom = self.normal.dot(ray.direction());
+        if denom.abs() < 1e-8 {
+            return false;
+        }
+
+        let t = self.normal.dot(self.center - ray.origin()) / denom;
+        if !ray_t.surrounds(t) {
+            return false;
+        }
+
+        let p = ray.at(t);
+        let local = p - self.center;
+        if local.length_squared() > self.radius * self.radius {
+            return false;
+        }
+
+        let (u, v) = self.uv(local.dot(self.tangent), local.dot(self.bitangent));
+        if !alpha_test(
+            self.material.opacity(u, v),
+            ray.origin(),
+            ray.direction(),
+            t,
+        ) {
+            return false;
+        }
+
+        rec.t = t;
+        rec.p = p;
+        rec.p_error = ray_point_error(ray, t);
+        rec.set_face_normal(ray, self.normal);
+        (rec.u, rec.v) = (u, v);
+        rec.material = &self.material;
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+impl<M: Material + 'static> AreaLight for Disk<M> {
+    fn area(&self) -> f32 {
+        PI * self.radius * self.radius
+    }
+
+    fn sample_area(&self, s: f32, t: f32) -> SurfacePoint {
+        let r = self.radius * s.sqrt();
+        let phi = 2.0 * PI * t;
+        let (x, y) = (r * phi.cos(), r * phi.sin());
+        let (u, v) = self.uv(x, y);
+
+        SurfacePoint {
+            p: self.center + x * self.tangent + y * self.bitangent,
+            normal: self.normal,
+            u,
+            v,
+        }
+    }
+
+    fn material(&self) -> &dyn Material {
+        &self.material
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    #[test]
+    fn test_disk_hit_and_miss() {
+        let disk = Disk::new(Vec3::ZERO, Vec3::Y, 1.0, Lambertian::new(Vec3::ONE));
+        let interval = Interval::new(0.001, f32::INFINITY);
+        let mut rec = HitRecord::default();
+
+        let down = Vec3::new(0.0, -1.0, 0.0);
+        assert!(disk.hit(
+            &Ray::new_simple(Vec3::new(0.0, 1.0, 0.0), down),
+            interval,
+            &mut rec
+        ));
+        assert!((rec.t - 1.0).abs() < 1e-5);
+        assert!((rec.u - 0.5).abs() < 1e-5 && (rec.v - 0.5).abs() < 1e-5);
+
+        // Inside the bounding square but outside the circle
+        let corner = Vec3::new(0.8, 1.0, 0.8);
+        assert!(!disk.hit(&Ray::new_simple(corner, down), interval, &mut rec));
+    }
+
+    #[test]
+    fn test_disk_bounds_are_tight() {
+        let disk = Disk::new(Vec3::ZERO, Vec3::Z, 2.0, Lambertian::new(Vec3::ONE));
+        let bbox = disk.bounding_box();
+        assert!((bbox.max_point().x - 2.0).abs() < 1e-5);
+        assert!(bbox.max_point().z < 0.01);
+    }
+
+    #[test]
+    fn test_disk_samples_lie_on_disk() {
+        let disk = Disk::new(Vec3::ONE, Vec3::X, 0.5, Lambertian::new(Vec3::ONE));
+        for &(s, t) in &[(0.0, 0.0), (0.3, 0.6), (0.99, 0.2)] {
+            let point = disk.sample_area(s, t);
+            assert!((point.p.x - 1.0).abs() < 1e-5);
+            assert!((point.p - Vec3::ONE).length() <= 0.5 + 1e-5);
+        }
+    }
+}
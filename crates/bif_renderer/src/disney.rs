@@ -3,12 +3,26 @@
 //! Based on the 2012 Disney paper "Physically Based Shading at Disney"
 //! and the 2015 extension for clearcoat and sheen.
 
-use crate::material::{cosine_weighted_hemisphere, gen_f32, reflect, Color, ScatterResult};
-use crate::{hittable::HitRecord, Material, Ray};
+use crate::material::{
+    cosine_weighted_hemisphere, gen_f32, reflect, Color, MaterialProperties, ScatterResult,
+};
+use crate::{hittable::HitRecord, Material, Ray, Subsurface};
+use bif_core::Visibility;
 use bif_math::Vec3;
 use rand::RngCore;
 use std::f32::consts::PI;
 
+/// How the Disney subsurface parameter is rendered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SubsurfaceMode {
+    /// Burley's diffuse-lobe approximation (no light transport under the surface)
+    #[default]
+    Approximate,
+
+    /// Volumetric random walk inside the mesh (see `Subsurface`)
+    RandomWalk,
+}
+
 /// Disney Principled BSDF material.
 ///
 /// A physically-based material with intuitive artist-friendly parameters.
@@ -45,9 +59,18 @@ pub struct DisneyBSDF {
     #[allow(dead_code)]
     pub clearcoat_gloss: f32,
 
-    /// Subsurface: blend to subsurface approximation
+    /// Subsurface: blend to subsurface scattering
     pub subsurface: f32,
 
+    /// How the subsurface blend is rendered
+    pub subsurface_mode: SubsurfaceMode,
+
+    /// Subsurface color (multiple-scattering albedo for random walk)
+    pub subsurface_color: Color,
+
+    /// Subsurface mean free path per channel (random walk only)
+    pub subsurface_radius: Color,
+
     /// Anisotropic: aspect ratio for anisotropic reflection
     /// TODO: Implement anisotropic GGX sampling
     #[allow(dead_code)]
@@ -67,6 +90,9 @@ impl Default for DisneyBSDF {
             clearcoat: 0.0,
             clearcoat_gloss: 1.0,
             subsurface: 0.0,
+            subsurface_mode: SubsurfaceMode::Approximate,
+            subsurface_color: Color::ONE,
+            subsurface_radius: Color::ONE,
             anisotropic: 0.0,
         }
     }
@@ -129,6 +155,15 @@ impl DisneyBSDF {
         self
     }
 
+    /// Builder method to enable random-walk subsurface scattering.
+    pub fn with_random_walk_subsurface(mut self, weight: f32, color: Color, radius: Color) -> Self {
+        self.subsurface = weight.clamp(0.0, 1.0);
+        self.subsurface_mode = SubsurfaceMode::RandomWalk;
+        self.subsurface_color = color;
+        self.subsurface_radius = radius;
+        self
+    }
+
     /// Builder method to set specular.
     pub fn with_specular(mut self, specular: f32) -> Self {
         self.specular = specular.clamp(0.0, 1.0);
@@ -158,6 +193,9 @@ impl From<&bif_core::Material> for DisneyBSDF {
             clearcoat: 0.0,
             clearcoat_gloss: 1.0,
             subsurface: 0.0,
+            subsurface_mode: SubsurfaceMode::Approximate,
+            subsurface_color: Color::ONE,
+            subsurface_radius: Color::ONE,
             anisotropic: 0.0,
         }
     }
@@ -173,43 +211,95 @@ impl Material for DisneyBSDF {
         let wo = -ray_in.direction().normalize();
         let n = rec.normal;
 
-        // Decide between diffuse and specular based on material parameters
-        let diffuse_weight = (1.0 - self.metallic) * (1.0 - self.specular * 0.5);
-        let specular_weight = 1.0 - diffuse_weight;
-
-        let do_diffuse =
-            gen_f32(rng) < diffuse_weight / (diffuse_weight + specular_weight);
-
-        if do_diffuse {
-            // Diffuse scattering (Burley diffuse approximation)
-            self.scatter_diffuse(wo, n, rec.p, ray_in.time(), rng)
+        // Pick a lobe to sample based on material parameters
+        let (wi, visibility) = if gen_f32(rng) < self.diffuse_probability() {
+            // Diffuse lobe (cosine-weighted)
+            (cosine_weighted_hemisphere(n, rng), Visibility::DIFFUSE)
         } else {
-            // Specular scattering (GGX microfacet)
-            self.scatter_specular(wo, n, rec.p, ray_in.time(), rng)
+            // Specular lobe (GGX microfacet normal)
+            (self.sample_specular(wo, n, rng), Visibility::SPECULAR)
+        };
+        let scattered = Ray::new(rec.p, wi, ray_in.time()).with_visibility(visibility);
+
+        // Weight by the full BSDF over the lobe mixture density, so the
+        // sample agrees with `bsdf` and `pdf` (and NEE's MIS weights)
+        let pdf = self.pdf(ray_in, rec, &scattered);
+        if pdf <= 0.0 {
+            return None;
+        }
+        let f = self.bsdf(ray_in, rec, &scattered);
+        if f == Color::ZERO {
+            return None;
         }
+
+        Some(ScatterResult {
+            attenuation: f / pdf,
+            scattered,
+            pdf,
+        })
     }
-}
 
-impl DisneyBSDF {
-    /// Scatter with diffuse (Burley) lobe.
-    fn scatter_diffuse(
-        &self,
-        wo: Vec3,
-        n: Vec3,
-        hit_point: Vec3,
-        time: f32,
-        rng: &mut dyn RngCore,
-    ) -> Option<ScatterResult> {
-        // Sample cosine-weighted hemisphere (PDF = cos(theta) / PI)
-        let wi = cosine_weighted_hemisphere(n, rng);
+    fn bsdf(&self, ray_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Color {
+        let wo = -ray_in.direction().normalize();
+        let wi = scattered.direction().normalize();
+        let n = rec.normal;
 
-        // Burley diffuse
-        let n_dot_l = n.dot(wi).max(0.0);
-        let n_dot_v = n.dot(wo).max(0.0);
+        let n_dot_l = n.dot(wi);
+        if n_dot_l <= 0.0 || n.dot(wo) <= 0.0 {
+            return Color::ZERO;
+        }
+
+        let diffuse = (1.0 - self.metallic) * self.diffuse_brdf(wo, wi, n);
+        (diffuse + self.specular_brdf(wo, wi, n)) * n_dot_l
+    }
+
+    fn pdf(&self, ray_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f32 {
+        let wo = -ray_in.direction().normalize();
+        let wi = scattered.direction().normalize();
+        let n = rec.normal;
 
+        let n_dot_l = n.dot(wi);
         if n_dot_l <= 0.0 {
+            return 0.0;
+        }
+
+        // Mixture of the two lobes sampled by `scatter`
+        let alpha = (self.roughness * self.roughness).max(0.001);
+        let h = (wo + wi).normalize();
+        let n_dot_h = n.dot(h).max(0.0);
+        let l_dot_h = wi.dot(h).max(0.0001);
+        let specular_pdf = ggx_d(n_dot_h, alpha) * n_dot_h / (4.0 * l_dot_h);
+
+        let p = self.diffuse_probability();
+        p * n_dot_l / PI + (1.0 - p) * specular_pdf
+    }
+
+    fn properties(&self) -> MaterialProperties {
+        MaterialProperties {
+            is_pure_specular: false,
+            is_emissive: false,
+            can_use_nee: true,
+        }
+    }
+
+    fn subsurface(&self, _u: f32, _v: f32) -> Option<Subsurface> {
+        if self.subsurface_mode != SubsurfaceMode::RandomWalk || self.subsurface <= 0.0 {
             return None;
         }
+        // The walk takes over that share of the (dielectric) diffuse lobe
+        Some(Subsurface::new(
+            self.subsurface * (1.0 - self.metallic),
+            self.subsurface_color,
+            self.subsurface_radius,
+        ))
+    }
+}
+
+impl DisneyBSDF {
+    /// Evaluate the diffuse (Burley + sheen) BRDF, without the cosine term.
+    fn diffuse_brdf(&self, wo: Vec3, wi: Vec3, n: Vec3) -> Color {
+        let n_dot_l = n.dot(wi).max(0.0);
+        let n_dot_v = n.dot(wo).max(0.0);
 
         // Fresnel-weighted diffuse (Burley 2012)
         let h = (wo + wi).normalize();
@@ -225,7 +315,11 @@ impl DisneyBSDF {
         let fss = lerp(1.0, fss90, fl) * lerp(1.0, fss90, fv);
         let ss = 1.25 * (fss * (1.0 / (n_dot_l + n_dot_v).max(0.001) - 0.5) + 0.5);
 
-        let diffuse = lerp(fd, ss, self.subsurface);
+        // The random walk replaces the subsurface share in the renderer
+        let diffuse = match self.subsurface_mode {
+            SubsurfaceMode::Approximate => lerp(fd, ss, self.subsurface),
+            SubsurfaceMode::RandomWalk => (1.0 - self.subsurface) * fd,
+        };
 
         // Sheen
         let sheen = if self.sheen > 0.0 {
@@ -240,72 +334,36 @@ impl DisneyBSDF {
             Color::ZERO
         };
 
-        let attenuation = self.base_color * diffuse / PI + sheen;
-        let scattered = Ray::new(hit_point, wi, time);
-
-        // Cosine-weighted hemisphere PDF
-        let pdf = (n_dot_l / PI).max(0.0001);
-
-        Some(ScatterResult {
-            attenuation,
-            scattered,
-            pdf,
-        })
+        self.base_color * diffuse / PI + sheen
     }
 
-    /// Scatter with specular (GGX) lobe.
-    fn scatter_specular(
-        &self,
-        wo: Vec3,
-        n: Vec3,
-        hit_point: Vec3,
-        time: f32,
-        rng: &mut dyn RngCore,
-    ) -> Option<ScatterResult> {
-        // Use GGX importance sampling
-        let alpha = self.roughness * self.roughness;
-        let alpha = alpha.max(0.001); // Prevent division by zero
-
-        // Sample GGX microfacet normal
+    /// Sample a direction from the specular (GGX) lobe.
+    fn sample_specular(&self, wo: Vec3, n: Vec3, rng: &mut dyn RngCore) -> Vec3 {
+        let alpha = (self.roughness * self.roughness).max(0.001);
         let h = sample_ggx(n, alpha, rng);
-        let wi = reflect(-wo, h);
+        reflect(-wo, h)
+    }
 
-        // Check if scattering direction is valid
-        let n_dot_l = n.dot(wi);
-        if n_dot_l <= 0.0 {
-            return None;
-        }
+    /// Evaluate the specular (GGX) BRDF, without the cosine term.
+    fn specular_brdf(&self, wo: Vec3, wi: Vec3, n: Vec3) -> Color {
+        let alpha = (self.roughness * self.roughness).max(0.001);
+        let h = (wo + wi).normalize();
 
-        let n_dot_v = n.dot(wo).max(0.0);
+        let n_dot_l = n.dot(wi).max(0.0);
+        let n_dot_v = n.dot(wo).max(0.001);
         let n_dot_h = n.dot(h).max(0.0);
         let l_dot_h = wi.dot(h).max(0.0);
 
-        // GGX distribution
         let d = ggx_d(n_dot_h, alpha);
-
-        // Schlick-GGX geometry (Smith)
         let g = smith_g_ggx(n_dot_l, n_dot_v, alpha);
+        let f = schlick_fresnel3(self.fresnel_0(), l_dot_h);
 
-        // Fresnel
-        let f0 = self.fresnel_0();
-        let f = schlick_fresnel3(f0, l_dot_h);
-
-        // Specular BRDF: D * G * F / (4 * NdotL * NdotV)
-        // With GGX importance sampling (PDF = D * NdotH / (4 * LdotH)):
-        // weight = BRDF / PDF = G * F * LdotH / (NdotH * NdotV)
-        let weight = (g * l_dot_h) / (n_dot_h * n_dot_v.max(0.001));
-
-        let attenuation = f * weight.max(0.0);
-        let scattered = Ray::new(hit_point, wi, time);
-
-        // GGX importance sampling PDF: D * n_dot_h / (4 * l_dot_h)
-        let pdf = (d * n_dot_h / (4.0 * l_dot_h)).max(0.0001);
+        f * (d * g / (4.0 * n_dot_l * n_dot_v).max(0.0001))
+    }
 
-        Some(ScatterResult {
-            attenuation,
-            scattered,
-            pdf,
-        })
+    /// Probability that `scatter` picks the diffuse lobe.
+    fn diffuse_probability(&self) -> f32 {
+        (1.0 - self.metallic) * (1.0 - self.specular * 0.5)
     }
 
     /// Compute F0 (Fresnel at normal incidence) based on material parameters.
@@ -463,4 +521,51 @@ mod tests {
         assert!((t.length() - 1.0).abs() < 0.001);
         assert!((b.length() - 1.0).abs() < 0.001);
     }
+
+    #[test]
+    fn test_bsdf_and_pdf_hemisphere() {
+        let mat = DisneyBSDF::plastic(Color::new(0.8, 0.2, 0.2), 0.4);
+        let rec = HitRecord {
+            normal: Vec3::Y,
+            ..Default::default()
+        };
+        let ray_in = Ray::new(Vec3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0), 0.0);
+
+        let above = Ray::new(Vec3::ZERO, Vec3::new(1.0, 1.0, 0.0), 0.0);
+        assert!(mat.bsdf(&ray_in, &rec, &above).min_element() > 0.0);
+        assert!(mat.pdf(&ray_in, &rec, &above) > 0.0);
+
+        let below = Ray::new(Vec3::ZERO, Vec3::new(1.0, -1.0, 0.0), 0.0);
+        assert_eq!(mat.bsdf(&ray_in, &rec, &below), Color::ZERO);
+        assert_eq!(mat.pdf(&ray_in, &rec, &below), 0.0);
+        assert!(mat.properties().can_use_nee);
+    }
+
+    #[test]
+    fn test_random_walk_replaces_diffuse_share() {
+        let rec = HitRecord {
+            normal: Vec3::Y,
+            ..Default::default()
+        };
+        let ray_in = Ray::new(Vec3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0), 0.0);
+        let out = Ray::new(Vec3::ZERO, Vec3::new(1.0, 2.0, 0.5), 0.0);
+
+        let surface = DisneyBSDF::plastic(Color::splat(0.8), 0.5).with_metallic(0.5);
+        let walk = |weight: f32| {
+            surface
+                .clone()
+                .with_random_walk_subsurface(weight, Color::ONE, Color::ONE)
+        };
+        let walked = walk(0.25);
+        let sss = walked.subsurface(0.0, 0.0).unwrap();
+        assert!((sss.weight - 0.125).abs() < 1e-6);
+
+        // The BSDF keeps the specular lobe and 3/4 of the diffuse lobe
+        let bsdf = |m: &DisneyBSDF| m.bsdf(&ray_in, &rec, &out);
+        let specular = bsdf(&walk(1.0));
+        let diffuse = bsdf(&surface) - specular;
+        assert!(diffuse.min_element() > 0.0);
+        let expected = specular + diffuse * 0.75;
+        assert!((walked.bsdf(&ray_in, &rec, &out) - expected).length() < 1e-5);
+    }
 }
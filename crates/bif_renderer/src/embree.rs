@@ -4,10 +4,13 @@
 //! Only includes the minimal API needed for instanced geometry rendering.
 
 use crate::{
-    hittable::{HitRecord, Hittable},
-    Material, Ray,
+    hittable::{alpha_test, HitRecord, Hittable},
+    ray::ray_point_error,
+    CurveGeometry, InstancedPrototype, InstancedWorld, Material, MaterialTable, PointGeometry, Ray,
+    TriangleUvs,
 };
-use bif_math::{Aabb, Interval, Mat4, Vec3};
+use bif_core::{CurveShape, MaterialId, Visibility};
+use bif_math::{Aabb, Interval, Mat4, MotionTransform, TransformKey, Vec3};
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
@@ -32,8 +35,11 @@ type RTCBuffer = *mut std::ffi::c_void;
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[allow(dead_code)]
 enum RTCGeometryType {
-    Triangle = 0,   // RTC_GEOMETRY_TYPE_TRIANGLE
-    Instance = 121, // RTC_GEOMETRY_TYPE_INSTANCE
+    Triangle = 0,          // RTC_GEOMETRY_TYPE_TRIANGLE
+    RoundBezierCurve = 24, // RTC_GEOMETRY_TYPE_ROUND_BEZIER_CURVE
+    FlatBezierCurve = 25,  // RTC_GEOMETRY_TYPE_FLAT_BEZIER_CURVE
+    SpherePoint = 50,      // RTC_GEOMETRY_TYPE_SPHERE_POINT
+    Instance = 121,        // RTC_GEOMETRY_TYPE_INSTANCE
 }
 
 // Embree buffer type
@@ -52,8 +58,10 @@ enum RTCBufferType {
 #[allow(dead_code)]
 enum RTCFormat {
     Undefined = 0,
+    UInt = 0x5001,                // RTC_FORMAT_UINT
     UInt3 = 0x5003,               // RTC_FORMAT_UINT = 0x5001, +2 for UINT3
     Float3 = 0x9003,              // RTC_FORMAT_FLOAT = 0x9001, +2 for FLOAT3
+    Float4 = 0x9004,              // Curve and point vertices: position + radius
     Float4x4ColumnMajor = 0x9244, // For transforms
 }
 
@@ -63,7 +71,9 @@ enum RTCFormat {
 #[allow(dead_code)]
 enum RTCSceneFlags {
     None = 0,
-    Robust = 1 << 0,
+    Dynamic = 1 << 0,
+    Compact = 1 << 1,
+    Robust = 1 << 2, // Watertight traversal, no cracks between triangles
 }
 
 // Embree build quality
@@ -74,6 +84,7 @@ enum RTCBuildQuality {
     Low = 0,
     Medium = 1,
     High = 2,
+    Refit = 3,
 }
 
 // Ray structure matching Embree's RTCRay
@@ -135,9 +146,78 @@ struct RTCBounds {
     align1: f32,
 }
 
+// Instance transform key interpolated with slerp (RTCQuaternionDecomposition)
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct RTCQuaternionDecomposition {
+    scale_x: f32,
+    scale_y: f32,
+    scale_z: f32,
+    skew_xy: f32,
+    skew_xz: f32,
+    skew_yz: f32,
+    shift_x: f32,
+    shift_y: f32,
+    shift_z: f32,
+    quaternion_r: f32,
+    quaternion_i: f32,
+    quaternion_j: f32,
+    quaternion_k: f32,
+    translation_x: f32,
+    translation_y: f32,
+    translation_z: f32,
+}
+
+impl From<&TransformKey> for RTCQuaternionDecomposition {
+    fn from(key: &TransformKey) -> Self {
+        Self {
+            scale_x: key.scale.x,
+            scale_y: key.scale.y,
+            scale_z: key.scale.z,
+            skew_xy: 0.0,
+            skew_xz: 0.0,
+            skew_yz: 0.0,
+            shift_x: 0.0,
+            shift_y: 0.0,
+            shift_z: 0.0,
+            quaternion_r: key.rotation.w,
+            quaternion_i: key.rotation.x,
+            quaternion_j: key.rotation.y,
+            quaternion_k: key.rotation.z,
+            translation_x: key.translation.x,
+            translation_y: key.translation.y,
+            translation_z: key.translation.z,
+        }
+    }
+}
+
 // Invalid geometry ID constant
 const RTC_INVALID_GEOMETRY_ID: u32 = 0xFFFFFFFF;
 
+// Arguments passed to filter callbacks (RTCFilterFunctionNArguments).
+// Ray and hit are SOA packets of N rays: field k of ray i is at [k * N + i].
+#[repr(C)]
+struct RTCFilterFunctionNArguments {
+    valid: *mut i32,
+    geometry_user_ptr: *mut std::ffi::c_void,
+    context: *mut std::ffi::c_void,
+    ray: *mut f32,
+    hit: *mut f32,
+    n: u32,
+}
+
+#[allow(non_camel_case_types)]
+type RTCFilterFunctionN = Option<unsafe extern "C" fn(args: *const RTCFilterFunctionNArguments)>;
+
+// Field offsets in RTCRayN / RTCHitN (in units of N)
+const RAYN_ORG: usize = 0;
+const RAYN_DIR: usize = 4;
+const RAYN_TFAR: usize = 8;
+const HITN_U: usize = 3;
+const HITN_V: usize = 4;
+const HITN_PRIM_ID: usize = 5;
+const HITN_INST_ID: usize = 7;
+
 // Embree C API functions
 #[link(name = "embree4")]
 extern "C" {
@@ -148,12 +228,16 @@ extern "C" {
     fn rtcNewScene(device: RTCDevice) -> RTCScene;
     fn rtcReleaseScene(scene: RTCScene);
     fn rtcCommitScene(scene: RTCScene);
+    fn rtcSetSceneFlags(scene: RTCScene, flags: RTCSceneFlags);
     fn rtcGetSceneBounds(scene: RTCScene, bounds: *mut RTCBounds);
 
     fn rtcNewGeometry(device: RTCDevice, geom_type: RTCGeometryType) -> RTCGeometry;
     fn rtcReleaseGeometry(geom: RTCGeometry);
     fn rtcCommitGeometry(geom: RTCGeometry);
     fn rtcAttachGeometry(scene: RTCScene, geom: RTCGeometry) -> u32;
+    fn rtcGetGeometry(scene: RTCScene, geom_id: u32) -> RTCGeometry;
+    fn rtcSetGeometryBuildQuality(geom: RTCGeometry, quality: RTCBuildQuality);
+    fn rtcUpdateGeometryBuffer(geom: RTCGeometry, buffer_type: u32, slot: u32);
 
     fn rtcSetSharedGeometryBuffer(
         geom: RTCGeometry,
@@ -174,6 +258,17 @@ extern "C" {
         xfm: *const f32,
     );
 
+    fn rtcSetGeometryTransformQuaternion(
+        geom: RTCGeometry,
+        time_step: u32,
+        qd: *const RTCQuaternionDecomposition,
+    );
+    fn rtcSetGeometryTimeStepCount(geom: RTCGeometry, time_step_count: u32);
+
+    fn rtcSetGeometryMask(geom: RTCGeometry, mask: u32);
+    fn rtcSetGeometryUserData(geom: RTCGeometry, ptr: *mut std::ffi::c_void);
+    fn rtcSetGeometryIntersectFilterFunction(geom: RTCGeometry, filter: RTCFilterFunctionN);
+
     #[allow(dead_code)]
     fn rtcSetGeometryVertexAttributeCount(geom: RTCGeometry, vertex_attribute_count: u32);
 
@@ -218,7 +313,8 @@ impl RTCRayHit {
                 time: ray.time(),
 
                 tfar: ray_t.max,
-                mask: 0xFFFFFFFF,
+                // Geometry masks are visibility masks, so the ray type selects
+                mask: ray.visibility().bits(),
                 id: 0,
                 flags: 0,
             },
@@ -236,18 +332,80 @@ impl RTCRayHit {
     }
 }
 
+/// Per-prototype data, also handed to the alpha filter as geometry user data.
+struct PrototypeData<M: Clone> {
+    /// Materials resolved by local instance index and triangle
+    materials: MaterialTable<M>,
+
+    /// Texture coordinates and tangents of the prototype triangles
+    uvs: TriangleUvs,
+
+    /// Top-level geometry ID of the prototype's first instance
+    first_instance: u32,
+
+    /// Number of triangles in the prototype
+    triangle_count: usize,
+}
+
+/// Intersection filter implementing opacity masks.
+///
+/// Rejects candidate hits that fail the stochastic alpha test for the
+/// material resolved at that triangle, so Embree keeps traversing and
+/// returns the next surface behind masked regions.
+unsafe extern "C" fn alpha_filter<M: Material + Clone + 'static>(
+    args: *const RTCFilterFunctionNArguments,
+) {
+    let args = &*args;
+    let prototype = &*(args.geometry_user_ptr as *const PrototypeData<M>);
+    let n = args.n as usize;
+    let ray = |field: usize, i: usize| *args.ray.add(field * n + i);
+    let hit_u32 = |field: usize, i: usize| *(args.hit as *const u32).add(field * n + i);
+
+    for i in 0..n {
+        let valid = args.valid.add(i);
+        if *valid == 0 {
+            continue;
+        }
+
+        let instance = hit_u32(HITN_INST_ID, i) - prototype.first_instance;
+        let material = prototype
+            .materials
+            .resolve(instance as usize, hit_u32(HITN_PRIM_ID, i) as usize);
+        let (u, v) = prototype.uvs.uv(
+            hit_u32(HITN_PRIM_ID, i),
+            *args.hit.add(HITN_U * n + i),
+            *args.hit.add(HITN_V * n + i),
+        );
+        let origin = Vec3::new(ray(RAYN_ORG, i), ray(RAYN_ORG + 1, i), ray(RAYN_ORG + 2, i));
+        let direction = Vec3::new(ray(RAYN_DIR, i), ray(RAYN_DIR + 1, i), ray(RAYN_DIR + 2, i));
+
+        if !alpha_test(material.opacity(u, v), origin, direction, ray(RAYN_TFAR, i)) {
+            *valid = 0;
+        }
+    }
+}
+
 // ============================================================================
 // EmbreeScene - Two-Level BVH for Instanced Geometry
 // ============================================================================
 
+/// Non-instanced geometry in the top-level scene, in geometry ID order
+enum TopLevelGeometry {
+    Curves(Arc<CurveGeometry>),
+    Points(Arc<PointGeometry>),
+}
+
 /// High-performance instanced geometry using Intel Embree.
 ///
 /// Uses two-level BVH:
 /// - Top level: Instance transforms (O(log I) where I = instance count)
-/// - Bottom level: Prototype mesh triangles (O(log P) where P = primitive count)
+/// - Bottom level: One sub-scene per prototype mesh (O(log P) where P = primitive count)
 ///
 /// Performance: O(log I + log P) vs O(I × log P) for instance-aware BVH
 ///
+/// Moving instances and deforming prototypes become multi-step motion
+/// blur geometry, interpolated by Embree at each ray's time.
+///
 /// # Example
 /// ```ignore
 /// let vertices = mesh.extract_triangle_vertices();
@@ -260,32 +418,48 @@ impl RTCRayHit {
 pub struct EmbreeScene<M: Material + Clone + 'static> {
     device: RTCDevice,
     scene: RTCScene,
-    prototype_scene: RTCScene, // Must stay alive while instances reference it!
-    material: Arc<M>,
+    prototype_scenes: Vec<RTCScene>, // Must stay alive while instances reference them!
+    // Boxed so the addresses handed to the alpha filter stay stable when the scene moves
+    prototypes: Vec<Box<PrototypeData<M>>>,
 
     // Keep vertex, index, and transform data alive (Embree holds pointers to this)
-    _vertex_data: Vec<f32>,
-    _index_data: Vec<u32>,
+    _vertex_data: Vec<Vec<f32>>,
+    _index_data: Vec<Vec<u32>>,
     _transform_data: Vec<[f32; 16]>,
 
+    // Curve and point sets, attached to the top-level scene after the instances
+    top_level: Vec<TopLevelGeometry>,
+    _curve_vertex_data: Vec<Vec<f32>>,
+    _curve_index_data: Vec<Vec<u32>>,
+    _point_vertex_data: Vec<Vec<f32>>,
+
     // For debugging/stats
     instance_count: usize,
     triangle_count: usize,
 }
 
 impl<M: Material + Clone + 'static> EmbreeScene<M> {
-    /// Try to create Embree scene, returns None if Embree unavailable.
-    pub fn try_new(vertices: &[[Vec3; 3]], transforms: Vec<Mat4>, material: M) -> Option<Self> {
-        // Check if Embree is available
+    /// Check whether the Embree library can be loaded.
+    pub fn is_available() -> bool {
         unsafe {
             let test_device = rtcNewDevice(std::ptr::null());
             if test_device.is_null() {
                 log::warn!("Embree not available - DLL not found or failed to load");
-                return None;
+                return false;
             }
             rtcReleaseDevice(test_device);
         }
-        Some(Self::new(vertices, transforms, material))
+        true
+    }
+
+    /// Try to create Embree scene, returns None if Embree unavailable.
+    pub fn try_new(vertices: &[[Vec3; 3]], transforms: Vec<Mat4>, material: M) -> Option<Self> {
+        Self::is_available().then(|| Self::new(vertices, transforms, material))
+    }
+
+    /// Try to create a multi-prototype scene, returns None if Embree unavailable.
+    pub fn try_from_prototypes(prototypes: Vec<InstancedPrototype<M>>) -> Option<Self> {
+        Self::is_available().then(|| Self::from_prototypes(prototypes))
     }
 
     /// Create Embree scene with instanced geometry.
@@ -293,11 +467,24 @@ impl<M: Material + Clone + 'static> EmbreeScene<M> {
     /// # Arguments
     /// * `vertices` - Triangle vertices as flat array of Vec3 triplets
     /// * `transforms` - Instance transforms (local-to-world matrices)
-    /// * `material` - Shared material for all instances
+    /// * `material` - Default material (see [`Self::with_materials`])
     ///
     /// # Safety
     /// Requires Embree 3 library to be installed and linkable.
     pub fn new(vertices: &[[Vec3; 3]], transforms: Vec<Mat4>, material: M) -> Self {
+        Self::from_prototypes(vec![InstancedPrototype::new(
+            vertices,
+            transforms,
+            MaterialTable::new(material),
+        )])
+    }
+
+    /// Create an Embree scene with several prototype meshes.
+    ///
+    /// Each prototype becomes its own Embree sub-scene, instanced by the
+    /// top-level scene once per transform. Instance indices in each
+    /// prototype's material table are local to that prototype.
+    pub fn from_prototypes(prototypes: Vec<InstancedPrototype<M>>) -> Self {
         unsafe {
             // 1. Create Embree device
             let device = rtcNewDevice(std::ptr::null());
@@ -311,198 +498,125 @@ impl<M: Material + Clone + 'static> EmbreeScene<M> {
                 panic!("Embree device error: {} - check Embree installation", err);
             }
 
-            // 2. Create scene for prototype mesh
-            let prototype_scene = rtcNewScene(device);
-            if prototype_scene.is_null() {
-                rtcReleaseDevice(device);
-                panic!("Failed to create Embree prototype scene");
-            }
-
-            // 3. Flatten triangles into separate vertex and index arrays
-            // Embree requires indexed triangle meshes
-            let mut vertex_data = Vec::with_capacity(vertices.len() * 9);
-            let mut index_data = Vec::with_capacity(vertices.len() * 3);
-
-            for (tri_idx, tri) in vertices.iter().enumerate() {
-                // Add 3 vertices
-                vertex_data.extend_from_slice(&[tri[0].x, tri[0].y, tri[0].z]);
-                vertex_data.extend_from_slice(&[tri[1].x, tri[1].y, tri[1].z]);
-                vertex_data.extend_from_slice(&[tri[2].x, tri[2].y, tri[2].z]);
-
-                // Add indices (each triangle uses 3 consecutive vertices)
-                let base_idx = (tri_idx * 3) as u32;
-                index_data.push(base_idx);
-                index_data.push(base_idx + 1);
-                index_data.push(base_idx + 2);
-            }
-
-            // Debug first triangle
-            if !vertices.is_empty() {
-                log::debug!(
-                    "First triangle: v0=({}, {}, {}), v1=({}, {}, {}), v2=({}, {}, {})",
-                    vertices[0][0].x,
-                    vertices[0][0].y,
-                    vertices[0][0].z,
-                    vertices[0][1].x,
-                    vertices[0][1].y,
-                    vertices[0][1].z,
-                    vertices[0][2].x,
-                    vertices[0][2].y,
-                    vertices[0][2].z
+            // 2. Build one sub-scene per prototype
+            let mut prototype_scenes = Vec::with_capacity(prototypes.len());
+            let mut prototype_data = Vec::with_capacity(prototypes.len());
+            let mut vertex_data = Vec::with_capacity(prototypes.len());
+            let mut index_data = Vec::with_capacity(prototypes.len());
+            let mut first_instance = 0;
+            for prototype in &prototypes {
+                let data = Box::new(PrototypeData {
+                    materials: prototype.materials.clone(),
+                    uvs: TriangleUvs::new(prototype.vertices, prototype.uvs),
+                    first_instance,
+                    triangle_count: prototype.vertices.len(),
+                });
+                let (scene, vertices, indices) = Self::build_prototype_scene(
+                    device,
+                    prototype.vertices,
+                    prototype.deformation,
+                    &data,
                 );
+                prototype_scenes.push(scene);
+                prototype_data.push(data);
+                vertex_data.push(vertices);
+                index_data.push(indices);
+                first_instance += prototype.transforms.len() as u32;
             }
 
-            // 4. Create triangle mesh geometry
-            let geom = rtcNewGeometry(device, RTCGeometryType::Triangle);
-            if geom.is_null() {
-                rtcReleaseScene(prototype_scene);
-                rtcReleaseDevice(device);
-                panic!("Failed to create Embree geometry");
-            }
-
-            log::info!(
-                "Setting up triangle geometry: {} triangles, {} vertices, {} indices",
-                vertices.len(),
-                vertex_data.len() / 3,
-                index_data.len()
-            );
-
-            // 5. Set vertex buffer
-            rtcSetSharedGeometryBuffer(
-                geom,
-                RTCBufferType::Vertex as u32,
-                0, // slot
-                RTCFormat::Float3 as u32,
-                vertex_data.as_ptr() as *const std::ffi::c_void,
-                0,                     // byte offset
-                12,                    // stride: 3 * f32 = 12 bytes per vertex
-                vertex_data.len() / 3, // vertex count
-            );
-
-            let err = rtcGetDeviceError(device);
-            if err != 0 {
-                panic!("Embree error after setting vertex buffer: {}", err);
-            }
-
-            // 6. Set index buffer
-            rtcSetSharedGeometryBuffer(
-                geom,
-                RTCBufferType::Index as u32,
-                0, // slot
-                RTCFormat::UInt3 as u32,
-                index_data.as_ptr() as *const std::ffi::c_void,
-                0,              // byte offset
-                12,             // stride: 3 * u32 = 12 bytes per triangle
-                vertices.len(), // triangle count
-            );
-
-            let err = rtcGetDeviceError(device);
-            if err != 0 {
-                panic!("Embree error after setting index buffer: {}", err);
-            }
-
-            rtcCommitGeometry(geom);
-            let geom_id = rtcAttachGeometry(prototype_scene, geom);
-            log::info!("Attached geometry to prototype scene: geom_id={}", geom_id);
-
-            // Check for Embree errors
-            let err = rtcGetDeviceError(device);
-            if err != 0 {
-                let err_msg = match err {
-                    1 => "RTC_ERROR_UNKNOWN",
-                    2 => "RTC_ERROR_INVALID_ARGUMENT",
-                    3 => "RTC_ERROR_INVALID_OPERATION",
-                    4 => "RTC_ERROR_OUT_OF_MEMORY",
-                    5 => "RTC_ERROR_UNSUPPORTED_CPU",
-                    6 => "RTC_ERROR_CANCELLED",
-                    _ => "UNKNOWN_ERROR",
-                };
-                panic!("Embree error after attaching geometry: {} ({})\nVertex count: {}, Triangle count: {}",
-                    err, err_msg, vertices.len() * 3, vertices.len());
-            }
-
-            log::info!("Geometry attached successfully, checking commit...");
-
-            rtcReleaseGeometry(geom);
-
-            // 6. Commit prototype scene
-            rtcCommitScene(prototype_scene);
-
-            // Debug: Check prototype scene bounds
-            let mut proto_bounds = RTCBounds::default();
-            rtcGetSceneBounds(prototype_scene, &mut proto_bounds);
-            log::debug!(
-                "Prototype scene bounds: ({}, {}, {}) to ({}, {}, {})",
-                proto_bounds.lower_x,
-                proto_bounds.lower_y,
-                proto_bounds.lower_z,
-                proto_bounds.upper_x,
-                proto_bounds.upper_y,
-                proto_bounds.upper_z
-            );
-
-            // 7. Create top-level scene with instances
+            // 3. Create top-level scene with instances
             let scene = rtcNewScene(device);
             if scene.is_null() {
-                rtcReleaseScene(prototype_scene);
+                for prototype_scene in prototype_scenes {
+                    rtcReleaseScene(prototype_scene);
+                }
                 rtcReleaseDevice(device);
                 panic!("Failed to create Embree top-level scene");
             }
-
-            // 8. Store transforms (Embree holds pointers, must keep alive)
-            let transform_data: Vec<[f32; 16]> =
-                transforms.iter().map(|t| t.to_cols_array()).collect();
-
-            // 9. Add instances
-            for (idx, transform_array) in transform_data.iter().enumerate() {
-                let inst_geom = rtcNewGeometry(device, RTCGeometryType::Instance);
-                if inst_geom.is_null() {
-                    log::warn!("Failed to create instance geometry");
-                    continue;
+            rtcSetSceneFlags(scene, RTCSceneFlags::Robust);
+
+            // 4. Store transforms (Embree holds pointers, must keep alive)
+            let transform_data: Vec<[f32; 16]> = prototypes
+                .iter()
+                .flat_map(|prototype| prototype.transforms.iter().map(|t| t.to_cols_array()))
+                .collect();
+
+            // 5. Add instances, prototype by prototype so each one's
+            // instances have consecutive geometry IDs
+            let mut transform_arrays = transform_data.iter();
+            for (prototype, &prototype_scene) in prototypes.iter().zip(&prototype_scenes) {
+                let instances = transform_arrays.by_ref().take(prototype.transforms.len());
+                for (instance, transform_array) in instances.enumerate() {
+                    let inst_geom = rtcNewGeometry(device, RTCGeometryType::Instance);
+                    if inst_geom.is_null() {
+                        panic!("Failed to create Embree instance geometry");
+                    }
+
+                    // Set instanced scene
+                    rtcSetGeometryInstancedScene(inst_geom, prototype_scene);
+
+                    let motion = prototype.instance_motion(instance);
+                    if motion.is_empty() {
+                        // Set transform (column-major Mat4)
+                        // From rtcore_common.h: RTC_FORMAT_FLOAT4X4_COLUMN_MAJOR = 0x9244
+                        rtcSetGeometryTransform(
+                            inst_geom,
+                            0, // time step
+                            RTCFormat::Float4x4ColumnMajor as u32,
+                            transform_array.as_ptr(),
+                        );
+                    } else {
+                        // One decomposed key per time step, so Embree slerps
+                        // rotations like `InstancedGeometryBVH`
+                        let matrices: Vec<Mat4> = std::iter::once(prototype.transforms[instance])
+                            .chain(motion.iter().copied())
+                            .collect();
+                        let keys = MotionTransform::new(&matrices);
+                        rtcSetGeometryTimeStepCount(inst_geom, keys.keys().len() as u32);
+                        for (step, key) in keys.keys().iter().enumerate() {
+                            let decomposition = RTCQuaternionDecomposition::from(key);
+                            rtcSetGeometryTransformQuaternion(
+                                inst_geom,
+                                step as u32,
+                                &decomposition,
+                            );
+                        }
+                    }
+                    rtcSetGeometryMask(inst_geom, prototype.instance_visibility(instance).bits());
+
+                    rtcCommitGeometry(inst_geom);
+                    rtcAttachGeometry(scene, inst_geom);
+                    rtcReleaseGeometry(inst_geom);
                 }
+            }
 
-                // Set instanced scene
-                rtcSetGeometryInstancedScene(inst_geom, prototype_scene);
-
-                // Set transform (column-major Mat4)
-                // From rtcore_common.h: RTC_FORMAT_FLOAT4X4_COLUMN_MAJOR = 0x9244
-                rtcSetGeometryTransform(
-                    inst_geom,
-                    0, // time step
-                    RTCFormat::Float4x4ColumnMajor as u32,
-                    transform_array.as_ptr(),
+            // Debug first transform
+            if let Some(t) = transform_data.first() {
+                log::debug!(
+                    "First transform:\n  [{}, {}, {}, {}]\n  [{}, {}, {}, {}]\n  [{}, {}, {}, {}]\n  [{}, {}, {}, {}]",
+                    t[0], t[1], t[2], t[3],
+                    t[4], t[5], t[6], t[7],
+                    t[8], t[9], t[10], t[11],
+                    t[12], t[13], t[14], t[15]
                 );
-
-                // Debug first transform
-                if idx == 0 {
-                    log::debug!(
-                        "First transform:\n  [{}, {}, {}, {}]\n  [{}, {}, {}, {}]\n  [{}, {}, {}, {}]\n  [{}, {}, {}, {}]",
-                        transform_array[0], transform_array[1], transform_array[2], transform_array[3],
-                        transform_array[4], transform_array[5], transform_array[6], transform_array[7],
-                        transform_array[8], transform_array[9], transform_array[10], transform_array[11],
-                        transform_array[12], transform_array[13], transform_array[14], transform_array[15]
-                    );
-                }
-
-                rtcCommitGeometry(inst_geom);
-                rtcAttachGeometry(scene, inst_geom);
-                rtcReleaseGeometry(inst_geom);
             }
 
-            // 10. Commit top-level scene
+            // 6. Commit top-level scene
             rtcCommitScene(scene);
 
-            // NOTE: Do NOT release prototype_scene here!
-            // Instances reference it and it must stay alive for the lifetime of EmbreeScene.
+            // NOTE: Do NOT release the prototype scenes here!
+            // Instances reference them and they must stay alive for the lifetime of EmbreeScene.
 
             // Get scene bounds for debugging
             let mut bounds = RTCBounds::default();
             rtcGetSceneBounds(scene, &mut bounds);
 
+            let triangle_count = prototypes.iter().map(|p| p.vertices.len()).sum();
             log::info!(
-                "Embree scene created: {} instances, {} triangles",
+                "Embree scene created: {} prototypes, {} instances, {} triangles",
+                prototypes.len(),
                 transform_data.len(),
-                vertices.len()
+                triangle_count
             );
             log::info!(
                 "Scene bounds: ({}, {}, {}) to ({}, {}, {})",
@@ -517,26 +631,441 @@ impl<M: Material + Clone + 'static> EmbreeScene<M> {
             Self {
                 device,
                 scene,
-                prototype_scene, // Keep alive for instances
-                material: Arc::new(material),
+                prototype_scenes, // Keep alive for instances
+                prototypes: prototype_data,
                 _vertex_data: vertex_data,
                 _index_data: index_data,
+                instance_count: transform_data.len(),
                 _transform_data: transform_data,
-                instance_count: transforms.len(),
-                triangle_count: vertices.len(),
+                top_level: Vec::new(),
+                _curve_vertex_data: Vec::new(),
+                _curve_index_data: Vec::new(),
+                _point_vertex_data: Vec::new(),
+                triangle_count,
+            }
+        }
+    }
+
+    /// Build and commit the Embree sub-scene for one prototype mesh.
+    ///
+    /// Deformation keys become further time steps of the triangle mesh.
+    /// Returns the scene with the vertex and index data it points into.
+    unsafe fn build_prototype_scene(
+        device: RTCDevice,
+        vertices: &[[Vec3; 3]],
+        deformation: &[Vec<[Vec3; 3]>],
+        data: &PrototypeData<M>,
+    ) -> (RTCScene, Vec<f32>, Vec<u32>) {
+        let prototype_scene = rtcNewScene(device);
+        if prototype_scene.is_null() {
+            rtcReleaseDevice(device);
+            panic!("Failed to create Embree prototype scene");
+        }
+        rtcSetSceneFlags(prototype_scene, RTCSceneFlags::Robust);
+
+        // Flatten triangles into separate vertex and index arrays
+        // Embree requires indexed triangle meshes
+        // Deformation keys follow the first one, with the same layout
+        let key_count = deformation.len() + 1;
+        let mut vertex_data = Vec::with_capacity(key_count * vertices.len() * 9);
+        let mut index_data = Vec::with_capacity(vertices.len() * 3);
+
+        for key in std::iter::once(vertices).chain(deformation.iter().map(Vec::as_slice)) {
+            for tri in key {
+                // Add 3 vertices
+                vertex_data.extend_from_slice(&[tri[0].x, tri[0].y, tri[0].z]);
+                vertex_data.extend_from_slice(&[tri[1].x, tri[1].y, tri[1].z]);
+                vertex_data.extend_from_slice(&[tri[2].x, tri[2].y, tri[2].z]);
+            }
+        }
+
+        for tri_idx in 0..vertices.len() {
+            // Add indices (each triangle uses 3 consecutive vertices)
+            let base_idx = (tri_idx * 3) as u32;
+            index_data.push(base_idx);
+            index_data.push(base_idx + 1);
+            index_data.push(base_idx + 2);
+        }
+
+        // An empty prototype still gets a (geometry-less) scene to instance
+        if vertices.is_empty() {
+            rtcCommitScene(prototype_scene);
+            return (prototype_scene, vertex_data, index_data);
+        }
+
+        // Create triangle mesh geometry
+        let geom = rtcNewGeometry(device, RTCGeometryType::Triangle);
+        if geom.is_null() {
+            rtcReleaseScene(prototype_scene);
+            rtcReleaseDevice(device);
+            panic!("Failed to create Embree geometry");
+        }
+
+        log::info!(
+            "Setting up triangle geometry: {} triangles, {} vertices, {} indices",
+            vertices.len(),
+            vertex_data.len() / 3,
+            index_data.len()
+        );
+
+        // Set vertex buffers, one slot per time step
+        let key_vertex_count = vertices.len() * 3;
+        rtcSetGeometryTimeStepCount(geom, key_count as u32);
+        for slot in 0..key_count {
+            rtcSetSharedGeometryBuffer(
+                geom,
+                RTCBufferType::Vertex as u32,
+                slot as u32,
+                RTCFormat::Float3 as u32,
+                vertex_data.as_ptr() as *const std::ffi::c_void,
+                slot * key_vertex_count * 12, // byte offset
+                12,                           // stride: 3 * f32 = 12 bytes per vertex
+                key_vertex_count,             // vertex count
+            );
+        }
+
+        let err = rtcGetDeviceError(device);
+        if err != 0 {
+            panic!("Embree error after setting vertex buffer: {}", err);
+        }
+
+        // Set index buffer
+        rtcSetSharedGeometryBuffer(
+            geom,
+            RTCBufferType::Index as u32,
+            0, // slot
+            RTCFormat::UInt3 as u32,
+            index_data.as_ptr() as *const std::ffi::c_void,
+            0,              // byte offset
+            12,             // stride: 3 * u32 = 12 bytes per triangle
+            vertices.len(), // triangle count
+        );
+
+        let err = rtcGetDeviceError(device);
+        if err != 0 {
+            panic!("Embree error after setting index buffer: {}", err);
+        }
+
+        // Opacity masks: filter candidate hits against the resolved material
+        rtcSetGeometryUserData(
+            geom,
+            data as *const PrototypeData<M> as *mut std::ffi::c_void,
+        );
+        rtcSetGeometryIntersectFilterFunction(geom, Some(alpha_filter::<M>));
+
+        rtcCommitGeometry(geom);
+        let geom_id = rtcAttachGeometry(prototype_scene, geom);
+        log::info!("Attached geometry to prototype scene: geom_id={}", geom_id);
+
+        // Check for Embree errors
+        let err = rtcGetDeviceError(device);
+        if err != 0 {
+            let err_msg = match err {
+                1 => "RTC_ERROR_UNKNOWN",
+                2 => "RTC_ERROR_INVALID_ARGUMENT",
+                3 => "RTC_ERROR_INVALID_OPERATION",
+                4 => "RTC_ERROR_OUT_OF_MEMORY",
+                5 => "RTC_ERROR_UNSUPPORTED_CPU",
+                6 => "RTC_ERROR_CANCELLED",
+                _ => "UNKNOWN_ERROR",
+            };
+            panic!("Embree error after attaching geometry: {} ({})\nVertex count: {}, Triangle count: {}",
+                err, err_msg, vertices.len() * 3, vertices.len());
+        }
+
+        rtcReleaseGeometry(geom);
+
+        // Commit prototype scene
+        rtcCommitScene(prototype_scene);
+
+        // Debug: Check prototype scene bounds
+        let mut proto_bounds = RTCBounds::default();
+        rtcGetSceneBounds(prototype_scene, &mut proto_bounds);
+        log::debug!(
+            "Prototype scene bounds: ({}, {}, {}) to ({}, {}, {})",
+            proto_bounds.lower_x,
+            proto_bounds.lower_y,
+            proto_bounds.lower_z,
+            proto_bounds.upper_x,
+            proto_bounds.upper_y,
+            proto_bounds.upper_z
+        );
+
+        (prototype_scene, vertex_data, index_data)
+    }
+
+    /// Assign materials per instance and per prototype triangle.
+    ///
+    /// `instance_ids` has one entry per instance and `face_ids` one per
+    /// triangle, both indexing into `materials`. Face bindings win over
+    /// instance bindings; `NO_MATERIAL` keeps the default material.
+    /// Applies to the first prototype (for single-prototype scenes).
+    pub fn with_materials(
+        self,
+        materials: Vec<M>,
+        instance_ids: Vec<MaterialId>,
+        face_ids: Option<Arc<[MaterialId]>>,
+    ) -> Self {
+        let instance_count = self
+            .prototypes
+            .get(1)
+            .map_or(self.instance_count, |p| p.first_instance as usize);
+        debug_assert!(instance_ids.is_empty() || instance_ids.len() == instance_count);
+        debug_assert!(face_ids
+            .as_ref()
+            .is_none_or(|ids| ids.len() == self.prototypes[0].triangle_count));
+
+        let mut table = self
+            .materials()
+            .clone()
+            .with_materials(materials)
+            .with_instance_ids(instance_ids);
+        if let Some(face_ids) = face_ids {
+            table = table.with_face_ids(face_ids);
+        }
+        self.with_material_table(table)
+    }
+
+    /// Add curve sets (hair, fur) as native Embree Bézier curves.
+    ///
+    /// Curves live in the top-level scene beside the instances, so their
+    /// geometry IDs follow the instance IDs.
+    pub fn with_curves(mut self, curves: Vec<Arc<CurveGeometry>>) -> Self {
+        if curves.is_empty() {
+            return self;
+        }
+
+        unsafe {
+            for set in &curves {
+                // Per control point: position and radius
+                let mut vertices = Vec::with_capacity(set.segment_count() * 16);
+                for segment in set.segments() {
+                    let [r0, r1] = segment.widths.map(|w| 0.5 * w);
+                    for (i, point) in segment.points.iter().enumerate() {
+                        let radius = r0 + (r1 - r0) * i as f32 / 3.0;
+                        vertices.extend_from_slice(&[point.x, point.y, point.z, radius]);
+                    }
+                }
+                let indices: Vec<u32> = (0..set.segment_count() as u32).map(|i| 4 * i).collect();
+
+                let geometry_type = match set.shape() {
+                    CurveShape::Round => RTCGeometryType::RoundBezierCurve,
+                    CurveShape::Ribbon => RTCGeometryType::FlatBezierCurve,
+                };
+                let geom = rtcNewGeometry(self.device, geometry_type);
+                if geom.is_null() {
+                    panic!("Failed to create Embree curve geometry");
+                }
+                rtcSetSharedGeometryBuffer(
+                    geom,
+                    RTCBufferType::Vertex as u32,
+                    0,
+                    RTCFormat::Float4 as u32,
+                    vertices.as_ptr() as *const std::ffi::c_void,
+                    0,
+                    16, // stride: x, y, z, radius
+                    vertices.len() / 4,
+                );
+                rtcSetSharedGeometryBuffer(
+                    geom,
+                    RTCBufferType::Index as u32,
+                    0,
+                    RTCFormat::UInt as u32,
+                    indices.as_ptr() as *const std::ffi::c_void,
+                    0,
+                    4, // first control point of each segment
+                    indices.len(),
+                );
+                rtcSetGeometryMask(geom, Visibility::ALL.bits());
+                rtcCommitGeometry(geom);
+                rtcAttachGeometry(self.scene, geom);
+                rtcReleaseGeometry(geom);
+
+                let err = rtcGetDeviceError(self.device);
+                if err != 0 {
+                    panic!("Embree error after adding curves: {}", err);
+                }
+
+                self._curve_vertex_data.push(vertices);
+                self._curve_index_data.push(indices);
+            }
+            rtcCommitScene(self.scene);
+        }
+
+        log::info!(
+            "Embree curves: {} sets, {} segments",
+            curves.len(),
+            curves.iter().map(|c| c.segment_count()).sum::<usize>()
+        );
+        self.top_level
+            .extend(curves.into_iter().map(TopLevelGeometry::Curves));
+        self
+    }
+
+    /// Add point sets (particles) as native Embree sphere points.
+    ///
+    /// Like curves, points live in the top-level scene after the instances.
+    pub fn with_points(mut self, points: Vec<Arc<PointGeometry>>) -> Self {
+        if points.is_empty() {
+            return self;
+        }
+
+        unsafe {
+            for set in &points {
+                let mut vertices = Vec::with_capacity(set.len() * 4);
+                for i in 0..set.len() {
+                    let center = set.center(i);
+                    vertices.extend_from_slice(&[center.x, center.y, center.z, set.radius(i)]);
+                }
+
+                let geom = rtcNewGeometry(self.device, RTCGeometryType::SpherePoint);
+                if geom.is_null() {
+                    panic!("Failed to create Embree point geometry");
+                }
+                rtcSetSharedGeometryBuffer(
+                    geom,
+                    RTCBufferType::Vertex as u32,
+                    0,
+                    RTCFormat::Float4 as u32,
+                    vertices.as_ptr() as *const std::ffi::c_void,
+                    0,
+                    16, // stride: x, y, z, radius
+                    set.len(),
+                );
+                rtcSetGeometryMask(geom, Visibility::ALL.bits());
+                rtcCommitGeometry(geom);
+                rtcAttachGeometry(self.scene, geom);
+                rtcReleaseGeometry(geom);
+
+                let err = rtcGetDeviceError(self.device);
+                if err != 0 {
+                    panic!("Embree error after adding points: {}", err);
+                }
+
+                self._point_vertex_data.push(vertices);
             }
+            rtcCommitScene(self.scene);
         }
+
+        log::info!(
+            "Embree points: {} sets, {} points",
+            points.len(),
+            points.iter().map(|p| p.len()).sum::<usize>()
+        );
+        self.top_level
+            .extend(points.into_iter().map(TopLevelGeometry::Points));
+        self
+    }
+
+    /// Replace the whole material table (default material included) of the
+    /// first prototype.
+    pub fn with_material_table(mut self, table: MaterialTable<M>) -> Self {
+        // Assign in place: the alpha filter holds a pointer to this allocation
+        self.prototypes[0].materials = table;
+        self
+    }
+
+    /// Get the material table used to resolve hits on the first prototype
+    pub fn materials(&self) -> &MaterialTable<M> {
+        &self.prototypes[0].materials
+    }
+
+    /// Get prototype count
+    pub fn prototype_count(&self) -> usize {
+        self.prototypes.len()
     }
 
-    /// Get instance count
+    /// Get instance count (over all prototypes)
     pub fn instance_count(&self) -> usize {
         self.instance_count
     }
 
-    /// Get triangle count
+    /// Get triangle count (over all prototypes, not instanced)
     pub fn triangle_count(&self) -> usize {
         self.triangle_count
     }
+
+    /// Find the prototype of a top-level instance and its local index.
+    #[inline]
+    fn prototype_of(&self, instance: u32) -> (&PrototypeData<M>, usize) {
+        let index = self
+            .prototypes
+            .partition_point(|p| p.first_instance <= instance)
+            .saturating_sub(1);
+        let prototype = &self.prototypes[index];
+        (prototype, (instance - prototype.first_instance) as usize)
+    }
+}
+
+impl<M: Material + Clone + 'static> InstancedWorld for EmbreeScene<M> {
+    fn set_transforms(&mut self, prototype: usize, transforms: &[Mat4]) {
+        let first = self.prototypes[prototype].first_instance as usize;
+        let end = self
+            .prototypes
+            .get(prototype + 1)
+            .map_or(self.instance_count, |p| p.first_instance as usize);
+        assert_eq!(
+            transforms.len(),
+            end - first,
+            "set_transforms can't change the instance count"
+        );
+
+        unsafe {
+            for (offset, transform) in transforms.iter().enumerate() {
+                let instance = first + offset;
+                self._transform_data[instance] = transform.to_cols_array();
+
+                // Instance geometry IDs are the global instance indices
+                let inst_geom = rtcGetGeometry(self.scene, instance as u32);
+                rtcSetGeometryTimeStepCount(inst_geom, 1);
+                rtcSetGeometryTransform(
+                    inst_geom,
+                    0,
+                    RTCFormat::Float4x4ColumnMajor as u32,
+                    self._transform_data[instance].as_ptr(),
+                );
+                rtcCommitGeometry(inst_geom);
+            }
+
+            // Only the top level is rebuilt; prototype scenes are reused
+            rtcCommitScene(self.scene);
+        }
+    }
+
+    fn refit_prototype(&mut self, prototype: usize, vertices: &[[Vec3; 3]]) {
+        assert_eq!(
+            vertices.len(),
+            self.prototypes[prototype].triangle_count,
+            "refit needs the triangle count the prototype was built with"
+        );
+        if vertices.is_empty() {
+            return;
+        }
+
+        // Same layout as in `build_prototype_scene`; Embree shares this
+        // buffer. Only the first key is written, the others are dropped.
+        self.prototypes[prototype].uvs.refit(vertices);
+        let vertex_data = &mut self._vertex_data[prototype];
+        for (dst, src) in vertex_data
+            .chunks_exact_mut(3)
+            .zip(vertices.iter().flatten())
+        {
+            dst.copy_from_slice(&src.to_array());
+        }
+
+        unsafe {
+            let prototype_scene = self.prototype_scenes[prototype];
+            let geom = rtcGetGeometry(prototype_scene, 0);
+            rtcSetGeometryBuildQuality(geom, RTCBuildQuality::Refit);
+            rtcSetGeometryTimeStepCount(geom, 1);
+            rtcUpdateGeometryBuffer(geom, RTCBufferType::Vertex as u32, 0);
+            rtcCommitGeometry(geom);
+            rtcCommitScene(prototype_scene);
+
+            // Instance bounds changed with the prototype
+            rtcCommitScene(self.scene);
+        }
+    }
 }
 
 impl<M: Material + Clone + 'static> Hittable for EmbreeScene<M> {
@@ -585,20 +1114,42 @@ impl<M: Material + Clone + 'static> Hittable for EmbreeScene<M> {
                 );
             }
 
+            // Curves and points are top-level geometry, not instanced
+            if rayhit.hit.inst_id[0] == RTC_INVALID_GEOMETRY_ID {
+                let prim_id = rayhit.hit.prim_id as usize;
+                match &self.top_level[rayhit.hit.geom_id as usize - self.instance_count] {
+                    TopLevelGeometry::Curves(curves) => {
+                        curves.fill_record(ray, prim_id, rayhit.hit.u, rayhit.ray.tfar, rec)
+                    }
+                    TopLevelGeometry::Points(points) => {
+                        points.fill_record(ray, prim_id, rayhit.ray.tfar, rec)
+                    }
+                }
+                return true;
+            }
+
             // 4. Fill HitRecord
             rec.t = rayhit.ray.tfar;
             rec.p = ray.at(rec.t);
+            rec.p_error = ray_point_error(ray, rec.t);
 
             // Embree returns geometric normal (not interpolated)
             let normal = Vec3::new(rayhit.hit.ng_x, rayhit.hit.ng_y, rayhit.hit.ng_z);
             rec.normal = normal.normalize();
 
-            // UV coordinates from barycentric
-            rec.u = rayhit.hit.u;
-            rec.v = rayhit.hit.v;
+            // Resolve UVs and material from instance and triangle IDs
+            let (prototype, instance) = self.prototype_of(rayhit.hit.inst_id[0]);
+            let prim_id = rayhit.hit.prim_id;
+            (rec.u, rec.v) = prototype.uvs.uv(prim_id, rayhit.hit.u, rayhit.hit.v);
 
-            // Shared material
-            rec.material = &*self.material;
+            // Tangent through the shutter-open transform of the instance
+            let transform =
+                Mat4::from_cols_array(&self._transform_data[rayhit.hit.inst_id[0] as usize]);
+            rec.tangent = transform
+                .transform_vector3(prototype.uvs.tangent(prim_id))
+                .normalize_or_zero();
+            rec.material = prototype.materials.resolve(instance, prim_id as usize);
+            rec.instance = Some(rayhit.hit.inst_id[0]);
 
             // Set front face
             rec.set_face_normal(ray, rec.normal);
@@ -624,7 +1175,10 @@ impl<M: Material + Clone + 'static> Drop for EmbreeScene<M> {
     fn drop(&mut self) {
         unsafe {
             rtcReleaseScene(self.scene);
-            rtcReleaseScene(self.prototype_scene); // Release prototype after top-level scene
+            // Release prototypes after the top-level scene
+            for &prototype_scene in &self.prototype_scenes {
+                rtcReleaseScene(prototype_scene);
+            }
             rtcReleaseDevice(self.device);
         }
     }
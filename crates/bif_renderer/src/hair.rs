@@ -0,0 +1,519 @@
+//! Hair scattering model.
+//!
+//! Port of the Chiang et al. 2016 hair BSDF ("A Practical and Controllable
+//! Hair and Fur Model for Production Path Tracing") as presented in PBRT-v3.
+//! Light is split into R, TT, TRT and a residual lobe; each lobe is the
+//! product of a longitudinal term (Mp), an attenuation term (Ap) and an
+//! azimuthal term (Np) evaluated at the hit's offset `h` across the fiber.
+//!
+//! The shading frame comes from the hit record: x runs along the fiber
+//! (`rec.tangent`), z faces the viewer and `rec.v` maps to h in [-1, 1].
+
+use crate::material::{build_tangent_basis, gen_f32, Color, MaterialProperties, ScatterResult};
+use crate::{hittable::HitRecord, Material, Ray};
+use bif_core::Visibility;
+use bif_math::Vec3;
+use rand::RngCore;
+use std::f32::consts::PI;
+
+/// Number of explicit lobes (R, TT, TRT); higher orders are lumped together.
+const P_MAX: usize = 3;
+
+/// Default index of refraction of the hair cortex.
+pub const DEFAULT_HAIR_ETA: f32 = 1.55;
+
+/// Default scale tilt in degrees.
+pub const DEFAULT_HAIR_ALPHA: f32 = 2.0;
+
+/// Chiang hair BSDF.
+#[derive(Debug, Clone)]
+pub struct HairBSDF {
+    /// Absorption coefficient inside the fiber (per unit diameter)
+    sigma_a: Color,
+    /// Index of refraction
+    eta: f32,
+    /// Longitudinal variance per lobe
+    v: [f32; P_MAX + 1],
+    /// Azimuthal logistic scale
+    s: f32,
+    /// Scale tilt terms for the R, TT and TRT lobes
+    sin_2k_alpha: [f32; 3],
+    cos_2k_alpha: [f32; 3],
+}
+
+impl HairBSDF {
+    /// Create a hair BSDF.
+    ///
+    /// - `sigma_a`: absorption coefficient (0 = white hair)
+    /// - `beta_m`: longitudinal roughness in [0, 1]
+    /// - `beta_n`: azimuthal roughness in [0, 1]
+    /// - `alpha`: scale tilt in degrees
+    pub fn new(sigma_a: Color, beta_m: f32, beta_n: f32, alpha: f32) -> Self {
+        let beta_m = beta_m.clamp(0.0, 1.0);
+        let beta_n = beta_n.clamp(0.0, 1.0);
+
+        let v0 = (0.726 * beta_m + 0.812 * beta_m.powi(2) + 3.7 * beta_m.powi(20)).powi(2);
+        let v = [v0, 0.25 * v0, 4.0 * v0, 4.0 * v0];
+
+        let s =
+            (PI / 8.0).sqrt() * (0.265 * beta_n + 1.194 * beta_n.powi(2) + 5.372 * beta_n.powi(22));
+
+        let mut sin_2k_alpha = [alpha.to_radians().sin(), 0.0, 0.0];
+        let mut cos_2k_alpha = [safe_sqrt(1.0 - sin_2k_alpha[0].powi(2)), 0.0, 0.0];
+        for i in 1..3 {
+            sin_2k_alpha[i] = 2.0 * cos_2k_alpha[i - 1] * sin_2k_alpha[i - 1];
+            cos_2k_alpha[i] = cos_2k_alpha[i - 1].powi(2) - sin_2k_alpha[i - 1].powi(2);
+        }
+
+        Self {
+            sigma_a,
+            eta: DEFAULT_HAIR_ETA,
+            v,
+            s,
+            sin_2k_alpha,
+            cos_2k_alpha,
+        }
+    }
+
+    /// Create a hair BSDF whose multiple-scattered color approximates `color`.
+    pub fn from_reflectance(color: Color, beta_m: f32, beta_n: f32) -> Self {
+        Self::new(
+            sigma_a_from_reflectance(color, beta_n),
+            beta_m,
+            beta_n,
+            DEFAULT_HAIR_ALPHA,
+        )
+    }
+
+    /// Create a hair BSDF from a scene material.
+    ///
+    /// The diffuse color sets the hair color and roughness drives both
+    /// longitudinal and azimuthal roughness.
+    pub fn from_material(material: &bif_core::Material) -> Self {
+        let roughness = material.roughness.clamp(0.05, 1.0);
+        Self::from_reflectance(material.diffuse_color, roughness, roughness)
+    }
+
+    /// Set the index of refraction.
+    pub fn with_eta(mut self, eta: f32) -> Self {
+        self.eta = eta;
+        self
+    }
+
+    /// World-to-local frame for a hit: (x along the fiber, y across, z facing `wo`).
+    fn frame(rec: &HitRecord, wo: Vec3) -> [Vec3; 3] {
+        let x = rec
+            .tangent
+            .try_normalize()
+            .unwrap_or_else(|| build_tangent_basis(rec.normal).0);
+        let z = (wo - x * x.dot(wo))
+            .try_normalize()
+            .or_else(|| (rec.normal - x * x.dot(rec.normal)).try_normalize())
+            .unwrap_or_else(|| build_tangent_basis(x).0);
+        [x, z.cross(x), z]
+    }
+
+    /// Tilted longitudinal angle of `wo` for lobe `p`, as (sin, |cos|).
+    fn tilt(&self, p: usize, sin_theta_o: f32, cos_theta_o: f32) -> (f32, f32) {
+        let (sin_op, cos_op) = match p {
+            0 => (
+                sin_theta_o * self.cos_2k_alpha[1] - cos_theta_o * self.sin_2k_alpha[1],
+                cos_theta_o * self.cos_2k_alpha[1] + sin_theta_o * self.sin_2k_alpha[1],
+            ),
+            1 => (
+                sin_theta_o * self.cos_2k_alpha[0] + cos_theta_o * self.sin_2k_alpha[0],
+                cos_theta_o * self.cos_2k_alpha[0] - sin_theta_o * self.sin_2k_alpha[0],
+            ),
+            2 => (
+                sin_theta_o * self.cos_2k_alpha[2] + cos_theta_o * self.sin_2k_alpha[2],
+                cos_theta_o * self.cos_2k_alpha[2] - sin_theta_o * self.sin_2k_alpha[2],
+            ),
+            _ => (sin_theta_o, cos_theta_o),
+        };
+        (sin_op, cos_op.abs())
+    }
+
+    /// Lobe attenuations and the shared geometry for a local `wo` at offset `h`.
+    fn lobes(&self, wo: Vec3, h: f32) -> Lobes {
+        let sin_theta_o = wo.x;
+        let cos_theta_o = safe_sqrt(1.0 - sin_theta_o * sin_theta_o);
+
+        // Refracted ray inside the fiber
+        let sin_theta_t = sin_theta_o / self.eta;
+        let cos_theta_t = safe_sqrt(1.0 - sin_theta_t * sin_theta_t);
+        let etap = (self.eta * self.eta - sin_theta_o * sin_theta_o).sqrt() / cos_theta_o;
+        let sin_gamma_t = h / etap;
+        let cos_gamma_t = safe_sqrt(1.0 - sin_gamma_t * sin_gamma_t);
+
+        let transmittance = (-self.sigma_a * (2.0 * cos_gamma_t / cos_theta_t)).exp();
+        let ap = ap(cos_theta_o, self.eta, h, transmittance);
+
+        // Lobe selection probabilities follow luminance
+        let weights = ap.map(luminance);
+        let total: f32 = weights.iter().sum();
+        let pdf = if total > 0.0 {
+            weights.map(|w| w / total)
+        } else {
+            [0.25; P_MAX + 1]
+        };
+
+        Lobes {
+            sin_theta_o,
+            cos_theta_o,
+            phi_o: wo.z.atan2(wo.y),
+            gamma_o: safe_asin(h),
+            gamma_t: safe_asin(sin_gamma_t),
+            ap,
+            pdf,
+        }
+    }
+
+    /// Evaluate f·|cos θi| and the sampling pdf for local directions.
+    fn evaluate(&self, lobes: &Lobes, wi: Vec3) -> (Color, f32) {
+        let sin_theta_i = wi.x;
+        let cos_theta_i = safe_sqrt(1.0 - sin_theta_i * sin_theta_i);
+        let phi = wi.z.atan2(wi.y) - lobes.phi_o;
+
+        let mut f = Color::ZERO;
+        let mut pdf = 0.0;
+        for p in 0..P_MAX {
+            let (sin_op, cos_op) = self.tilt(p, lobes.sin_theta_o, lobes.cos_theta_o);
+            let m = mp(cos_theta_i, cos_op, sin_theta_i, sin_op, self.v[p]);
+            let n = np(phi, p, self.s, lobes.gamma_o, lobes.gamma_t);
+            f += lobes.ap[p] * (m * n);
+            pdf += lobes.pdf[p] * m * n;
+        }
+
+        // Residual lobe is azimuthally uniform
+        let m = mp(
+            cos_theta_i,
+            lobes.cos_theta_o,
+            sin_theta_i,
+            lobes.sin_theta_o,
+            self.v[P_MAX],
+        );
+        f += lobes.ap[P_MAX] * (m / (2.0 * PI));
+        pdf += lobes.pdf[P_MAX] * m / (2.0 * PI);
+
+        (f, pdf)
+    }
+
+    /// Sample a local incident direction.
+    fn sample(&self, lobes: &Lobes, rng: &mut dyn RngCore) -> Vec3 {
+        // Pick a lobe
+        let mut u = gen_f32(rng);
+        let mut p = 0;
+        while p < P_MAX && u >= lobes.pdf[p] {
+            u -= lobes.pdf[p];
+            p += 1;
+        }
+
+        // Longitudinal angle around the tilted specular cone
+        let (sin_op, cos_op) = self.tilt(p, lobes.sin_theta_o, lobes.cos_theta_o);
+        let v = self.v[p];
+        let u_m = gen_f32(rng).max(1e-5);
+        let cos_theta = 1.0 + v * (u_m + (1.0 - u_m) * (-2.0 / v).exp()).ln();
+        let sin_theta = safe_sqrt(1.0 - cos_theta * cos_theta);
+        let cos_phi = (2.0 * PI * gen_f32(rng)).cos();
+        let sin_theta_i = -cos_theta * sin_op + sin_theta * cos_phi * cos_op;
+        let cos_theta_i = safe_sqrt(1.0 - sin_theta_i * sin_theta_i);
+
+        // Azimuth relative to the outgoing direction
+        let u_phi = gen_f32(rng);
+        let dphi = if p < P_MAX {
+            phi(p, lobes.gamma_o, lobes.gamma_t) + sample_trimmed_logistic(u_phi, self.s, -PI, PI)
+        } else {
+            2.0 * PI * u_phi
+        };
+        let phi_i = lobes.phi_o + dphi;
+
+        Vec3::new(
+            sin_theta_i,
+            cos_theta_i * phi_i.cos(),
+            cos_theta_i * phi_i.sin(),
+        )
+    }
+}
+
+/// Per-hit quantities shared by evaluation and sampling.
+struct Lobes {
+    sin_theta_o: f32,
+    cos_theta_o: f32,
+    phi_o: f32,
+    gamma_o: f32,
+    gamma_t: f32,
+    ap: [Color; P_MAX + 1],
+    pdf: [f32; P_MAX + 1],
+}
+
+impl Material for HairBSDF {
+    fn scatter(
+        &self,
+        ray_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterResult> {
+        let wo = -ray_in.direction().normalize();
+        let frame = Self::frame(rec, wo);
+        let lobes = self.lobes(to_local(&frame, wo), 2.0 * rec.v - 1.0);
+
+        let wi = self.sample(&lobes, rng);
+        let (f, pdf) = self.evaluate(&lobes, wi);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        let direction = frame[0] * wi.x + frame[1] * wi.y + frame[2] * wi.z;
+        Some(ScatterResult {
+            attenuation: f / pdf,
+            // Every hair lobe is glossy, including transmission through the fiber
+            scattered: Ray::new(rec.p, direction, ray_in.time())
+                .with_visibility(Visibility::SPECULAR),
+            pdf,
+        })
+    }
+
+    fn bsdf(&self, ray_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Color {
+        let wo = -ray_in.direction().normalize();
+        let frame = Self::frame(rec, wo);
+        let lobes = self.lobes(to_local(&frame, wo), 2.0 * rec.v - 1.0);
+        let wi = to_local(&frame, scattered.direction().normalize());
+        self.evaluate(&lobes, wi).0
+    }
+
+    fn pdf(&self, ray_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f32 {
+        let wo = -ray_in.direction().normalize();
+        let frame = Self::frame(rec, wo);
+        let lobes = self.lobes(to_local(&frame, wo), 2.0 * rec.v - 1.0);
+        let wi = to_local(&frame, scattered.direction().normalize());
+        self.evaluate(&lobes, wi).1
+    }
+
+    fn properties(&self) -> MaterialProperties {
+        MaterialProperties {
+            is_pure_specular: false,
+            is_emissive: false,
+            can_use_nee: true,
+        }
+    }
+}
+
+/// Absorption coefficient that gives roughly `color` after multiple scattering.
+pub fn sigma_a_from_reflectance(color: Color, beta_n: f32) -> Color {
+    let denom = 5.969 - 0.215 * beta_n + 2.532 * beta_n.powi(2) - 10.73 * beta_n.powi(3)
+        + 5.574 * beta_n.powi(4)
+        + 0.245 * beta_n.powi(5);
+    color
+        .clamp(Color::splat(1e-4), Color::ONE)
+        .to_array()
+        .map(|c| (c.ln() / denom).powi(2))
+        .into()
+}
+
+#[inline]
+fn to_local(frame: &[Vec3; 3], w: Vec3) -> Vec3 {
+    Vec3::new(w.dot(frame[0]), w.dot(frame[1]), w.dot(frame[2]))
+}
+
+#[inline]
+fn safe_sqrt(x: f32) -> f32 {
+    x.max(0.0).sqrt()
+}
+
+#[inline]
+fn safe_asin(x: f32) -> f32 {
+    x.clamp(-1.0, 1.0).asin()
+}
+
+#[inline]
+fn luminance(c: Color) -> f32 {
+    0.2126 * c.x + 0.7152 * c.y + 0.0722 * c.z
+}
+
+/// Modified Bessel function of the first kind, order zero.
+fn i0(x: f32) -> f32 {
+    let mut val = 0.0;
+    let mut x2i = 1.0;
+    let mut ifact = 1.0;
+    let mut i4 = 1.0;
+    for i in 0..10 {
+        if i > 1 {
+            ifact *= i as f32;
+        }
+        val += x2i / (i4 * ifact * ifact);
+        x2i *= x * x;
+        i4 *= 4.0;
+    }
+    val
+}
+
+fn log_i0(x: f32) -> f32 {
+    if x > 12.0 {
+        x + 0.5 * (-(2.0 * PI).ln() + (1.0 / x).ln() + 1.0 / (8.0 * x))
+    } else {
+        i0(x).ln()
+    }
+}
+
+/// Longitudinal scattering function.
+fn mp(cos_theta_i: f32, cos_theta_o: f32, sin_theta_i: f32, sin_theta_o: f32, v: f32) -> f32 {
+    let a = cos_theta_i * cos_theta_o / v;
+    let b = sin_theta_i * sin_theta_o / v;
+    if v <= 0.1 {
+        (log_i0(a) - b - 1.0 / v + std::f32::consts::LN_2 + (1.0 / (2.0 * v)).ln()).exp()
+    } else {
+        (-b).exp() * i0(a) / ((1.0 / v).sinh() * 2.0 * v)
+    }
+}
+
+/// Fresnel reflectance of a dielectric interface.
+fn fr_dielectric(cos_theta_i: f32, eta: f32) -> f32 {
+    let cos_theta_i = cos_theta_i.clamp(-1.0, 1.0);
+    let (eta_i, eta_t, cos_theta_i) = if cos_theta_i > 0.0 {
+        (1.0, eta, cos_theta_i)
+    } else {
+        (eta, 1.0, -cos_theta_i)
+    };
+
+    let sin_theta_t = eta_i / eta_t * safe_sqrt(1.0 - cos_theta_i * cos_theta_i);
+    if sin_theta_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_theta_t = safe_sqrt(1.0 - sin_theta_t * sin_theta_t);
+    let r_parl =
+        (eta_t * cos_theta_i - eta_i * cos_theta_t) / (eta_t * cos_theta_i + eta_i * cos_theta_t);
+    let r_perp =
+        (eta_i * cos_theta_i - eta_t * cos_theta_t) / (eta_i * cos_theta_i + eta_t * cos_theta_t);
+    0.5 * (r_parl * r_parl + r_perp * r_perp)
+}
+
+/// Attenuation of each lobe.
+fn ap(cos_theta_o: f32, eta: f32, h: f32, transmittance: Color) -> [Color; P_MAX + 1] {
+    let cos_gamma_o = safe_sqrt(1.0 - h * h);
+    let f = fr_dielectric(cos_theta_o * cos_gamma_o, eta);
+
+    let r = Color::splat(f);
+    let tt = (1.0 - f).powi(2) * transmittance;
+    let trt = tt * transmittance * f;
+    let rest = trt * f * transmittance / (Color::ONE - transmittance * f);
+    [r, tt, trt, rest]
+}
+
+/// Net azimuthal deflection of lobe `p`.
+#[inline]
+fn phi(p: usize, gamma_o: f32, gamma_t: f32) -> f32 {
+    let p = p as f32;
+    2.0 * p * gamma_t - 2.0 * gamma_o + p * PI
+}
+
+#[inline]
+fn logistic(x: f32, s: f32) -> f32 {
+    let x = x.abs();
+    (-x / s).exp() / (s * (1.0 + (-x / s).exp()).powi(2))
+}
+
+#[inline]
+fn logistic_cdf(x: f32, s: f32) -> f32 {
+    1.0 / (1.0 + (-x / s).exp())
+}
+
+#[inline]
+fn trimmed_logistic(x: f32, s: f32, a: f32, b: f32) -> f32 {
+    logistic(x, s) / (logistic_cdf(b, s) - logistic_cdf(a, s))
+}
+
+fn sample_trimmed_logistic(u: f32, s: f32, a: f32, b: f32) -> f32 {
+    let k = logistic_cdf(b, s) - logistic_cdf(a, s);
+    let x = -s * (1.0 / (u * k + logistic_cdf(a, s)) - 1.0).ln();
+    x.clamp(a, b)
+}
+
+/// Azimuthal scattering function.
+fn np(phi_diff: f32, p: usize, s: f32, gamma_o: f32, gamma_t: f32) -> f32 {
+    let mut dphi = phi_diff - phi(p, gamma_o, gamma_t);
+    // Remap to [-pi, pi]
+    while dphi > PI {
+        dphi -= 2.0 * PI;
+    }
+    while dphi < -PI {
+        dphi += 2.0 * PI;
+    }
+    trimmed_logistic(dphi, s, -PI, PI)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn uniform_sphere(rng: &mut StdRng) -> Vec3 {
+        let z = 1.0 - 2.0 * gen_f32(rng);
+        let r = safe_sqrt(1.0 - z * z);
+        let phi = 2.0 * PI * gen_f32(rng);
+        Vec3::new(r * phi.cos(), r * phi.sin(), z)
+    }
+
+    fn fiber_hit(v: f32) -> HitRecord<'static> {
+        HitRecord {
+            normal: Vec3::Z,
+            tangent: Vec3::X,
+            v,
+            front_face: true,
+            ..HitRecord::default()
+        }
+    }
+
+    #[test]
+    fn test_white_furnace() {
+        // Non-absorbing hair scatters all energy
+        let mut rng = StdRng::seed_from_u64(7);
+        let hair = HairBSDF::new(Color::ZERO, 0.3, 0.3, DEFAULT_HAIR_ALPHA);
+        let count = 100_000;
+        let mut sum = Color::ZERO;
+        for _ in 0..count {
+            let rec = fiber_hit(gen_f32(&mut rng));
+            let wo = uniform_sphere(&mut rng);
+            let wi = uniform_sphere(&mut rng);
+            let ray_in = Ray::new(wo, -wo, 0.0);
+            let scattered = Ray::new(Vec3::ZERO, wi, 0.0);
+            sum += hair.bsdf(&ray_in, &rec, &scattered) * (4.0 * PI);
+        }
+        let avg = sum / count as f32;
+        assert!((avg.x - 1.0).abs() < 0.05, "furnace = {avg}");
+    }
+
+    #[test]
+    fn test_sampling_matches_evaluation() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let hair = HairBSDF::from_reflectance(Color::new(0.6, 0.4, 0.2), 0.4, 0.5);
+        let mut sum = Color::ZERO;
+        let count = 20_000;
+        for _ in 0..count {
+            let rec = fiber_hit(gen_f32(&mut rng));
+            let wo = uniform_sphere(&mut rng);
+            let ray_in = Ray::new(wo, -wo, 0.0);
+            let result = hair.scatter(&ray_in, &rec, &mut rng).unwrap();
+
+            let pdf = hair.pdf(&ray_in, &rec, &result.scattered);
+            assert!((pdf - result.pdf).abs() <= 1e-3 * pdf.max(1.0));
+            let f = hair.bsdf(&ray_in, &rec, &result.scattered);
+            assert!((f / pdf - result.attenuation).abs().max_element() < 1e-3);
+            sum += result.attenuation;
+        }
+        // Absorbing hair loses energy but never gains it
+        let avg = sum / count as f32;
+        assert!(
+            avg.max_element() < 1.0 && avg.min_element() > 0.0,
+            "avg = {avg}"
+        );
+        assert!(avg.x > avg.y && avg.y > avg.z);
+    }
+
+    #[test]
+    fn test_sigma_a_from_reflectance() {
+        let sigma_a = sigma_a_from_reflectance(Color::new(0.9, 0.5, 0.1), 0.3);
+        assert!(sigma_a.x < sigma_a.y && sigma_a.y < sigma_a.z);
+        assert_eq!(sigma_a_from_reflectance(Color::ONE, 0.3), Color::ZERO);
+    }
+}
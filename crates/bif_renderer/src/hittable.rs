@@ -1,8 +1,10 @@
 //! Hittable trait and HitRecord for ray-object intersection.
 
-use crate::{Material, Ray, ScatterResult};
+use crate::{offset_ray_origin, Material, Ray, ScatterResult};
+use bif_core::Visibility;
 use bif_math::{Aabb, Interval, Vec3};
 use rand::RngCore;
+use std::sync::Arc;
 
 /// A dummy material used for HitRecord::default().
 /// Always absorbs light (returns None from scatter).
@@ -22,6 +24,8 @@ static DUMMY_MATERIAL: DummyMaterial = DummyMaterial;
 pub struct HitRecord<'a> {
     /// Point of intersection
     pub p: Vec3,
+    /// Conservative bound on the floating-point error of `p`, per axis
+    pub p_error: Vec3,
     /// Surface normal at intersection (always points against ray)
     pub normal: Vec3,
     /// Material at the intersection point
@@ -33,18 +37,26 @@ pub struct HitRecord<'a> {
     pub t: f32,
     /// Whether the ray hit the front face (outside) of the surface
     pub front_face: bool,
+    /// Unit direction of increasing `u`: the hair tangent on curves, dP/du
+    /// on instanced meshes with UVs; zero otherwise
+    pub tangent: Vec3,
+    /// Instance hit in a two-level world (None for other geometry)
+    pub instance: Option<u32>,
 }
 
 impl<'a> Default for HitRecord<'a> {
     fn default() -> Self {
         Self {
             p: Vec3::ZERO,
+            p_error: Vec3::ZERO,
             normal: Vec3::ZERO,
             material: &DUMMY_MATERIAL,
             u: 0.0,
             v: 0.0,
             t: 0.0,
             front_face: false,
+            tangent: Vec3::ZERO,
+            instance: None,
         }
     }
 }
@@ -65,6 +77,55 @@ impl<'a> HitRecord<'a> {
             -outward_normal
         };
     }
+
+    /// Ray leaving the hit point in `direction`.
+    ///
+    /// The origin is offset past `p_error` along the geometric normal, so the
+    /// ray can be traced from `t = 0` without re-hitting this surface.
+    pub fn spawn_ray(&self, direction: Vec3, time: f32) -> Ray {
+        let origin = offset_ray_origin(self.p, self.p_error, self.normal, direction);
+        Ray::new(origin, direction, time)
+    }
+
+    /// Shadow ray from the hit point towards `target`, which it reaches at
+    /// `t = 1`.
+    ///
+    /// Trace it over `(0, 1 - SHADOW_EPSILON)`.
+    pub fn spawn_ray_to(&self, target: Vec3, time: f32) -> Ray {
+        let origin = offset_ray_origin(self.p, self.p_error, self.normal, target - self.p);
+        Ray::new(origin, target - origin, time).with_visibility(Visibility::SHADOW)
+    }
+}
+
+/// Fraction of a shadow ray left untested at the light end, so that the
+/// light's own surface doesn't count as a blocker.
+pub const SHADOW_EPSILON: f32 = 1e-4;
+
+/// Stochastic alpha test for a candidate hit.
+///
+/// Opacity 1 always hits and 0 never does; in between the hit is kept with
+/// probability `opacity`. The random number is a hash of the ray and hit
+/// distance, so traversal needs no RNG and a given ray always makes the same
+/// decision, while jittered samples average out to partial coverage.
+#[inline]
+pub fn alpha_test(opacity: f32, origin: Vec3, direction: Vec3, t: f32) -> bool {
+    if opacity >= 1.0 {
+        return true;
+    }
+    if opacity <= 0.0 {
+        return false;
+    }
+
+    let components = origin.to_array().into_iter().chain(direction.to_array());
+    let mut h = 0x9e37_79b9u32;
+    for bits in components.chain([t]).map(f32::to_bits) {
+        // PCG-style mixing step per component
+        h = (h ^ bits).wrapping_mul(747_796_405);
+        h = h.wrapping_add(2_891_336_453);
+        h ^= h >> 16;
+    }
+    let xi = (h >> 8) as f32 * (1.0 / (1u32 << 24) as f32);
+    xi < opacity
 }
 
 /// Trait for objects that can be hit by rays.
@@ -74,10 +135,41 @@ pub trait Hittable: Send + Sync {
     /// Returns true if hit, and fills in the hit record.
     fn hit<'a>(&'a self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord<'a>) -> bool;
 
+    /// Test a packet of rays against this object.
+    ///
+    /// Sets `hits[i]` and fills `recs[i]` when ray `i` hits within `ray_t`;
+    /// records that already hold a hit are only replaced by closer ones.
+    /// Acceleration structures override this to share traversal work across
+    /// coherent rays; the default tests the rays one at a time.
+    fn hit_packet<'a>(
+        &'a self,
+        rays: &[Ray],
+        ray_t: Interval,
+        recs: &mut [HitRecord<'a>],
+        hits: &mut [bool],
+    ) {
+        for ((ray, rec), hit) in rays.iter().zip(recs.iter_mut()).zip(hits.iter_mut()) {
+            let interval = packet_interval(ray_t, rec, *hit);
+            if self.hit(ray, interval, rec) {
+                *hit = true;
+            }
+        }
+    }
+
     /// Get the axis-aligned bounding box of this object.
     fn bounding_box(&self) -> Aabb;
 }
 
+/// Interval still open for a packet ray, given its current record.
+#[inline]
+pub(crate) fn packet_interval(ray_t: Interval, rec: &HitRecord, hit: bool) -> Interval {
+    if hit {
+        Interval::new(ray_t.min, rec.t.min(ray_t.max))
+    } else {
+        ray_t
+    }
+}
+
 /// A list of hittable objects.
 pub struct HittableList {
     objects: Vec<Box<dyn Hittable>>,
@@ -138,7 +230,71 @@ impl Hittable for HittableList {
         hit_anything
     }
 
+    fn hit_packet<'a>(
+        &'a self,
+        rays: &[Ray],
+        ray_t: Interval,
+        recs: &mut [HitRecord<'a>],
+        hits: &mut [bool],
+    ) {
+        for object in &self.objects {
+            object.hit_packet(rays, ray_t, recs, hits);
+        }
+    }
+
     fn bounding_box(&self) -> Aabb {
         self.bbox
     }
 }
+
+/// Shared primitives, e.g. a quad that is both in the scene and an area
+/// light in a `LightList`.
+impl<T: Hittable + ?Sized> Hittable for Arc<T> {
+    fn hit<'a>(&'a self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord<'a>) -> bool {
+        (**self).hit(ray, ray_t, rec)
+    }
+
+    fn hit_packet<'a>(
+        &'a self,
+        rays: &[Ray],
+        ray_t: Interval,
+        recs: &mut [HitRecord<'a>],
+        hits: &mut [bool],
+    ) {
+        (**self).hit_packet(rays, ray_t, recs, hits)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        (**self).bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alpha_test_bounds_and_determinism() {
+        let origin = Vec3::new(0.1, 0.2, 0.3);
+        let dir = Vec3::new(0.0, 0.0, -1.0);
+        assert!(alpha_test(1.0, origin, dir, 2.0));
+        assert!(!alpha_test(0.0, origin, dir, 2.0));
+        assert_eq!(
+            alpha_test(0.5, origin, dir, 2.0),
+            alpha_test(0.5, origin, dir, 2.0)
+        );
+    }
+
+    #[test]
+    fn test_alpha_test_coverage() {
+        let dir = Vec3::new(0.0, 0.0, -1.0);
+        let kept = (0..10_000)
+            .filter(|i| {
+                let origin = Vec3::new(*i as f32 * 1e-3, 0.0, 0.0);
+                alpha_test(0.3, origin, dir, 1.0)
+            })
+            .count();
+        let fraction = kept as f32 / 10_000.0;
+        assert!((fraction - 0.3).abs() < 0.03, "fraction = {}", fraction);
+    }
+}
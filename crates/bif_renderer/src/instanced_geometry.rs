@@ -9,8 +9,10 @@
 
 use crate::{
     hittable::{HitRecord, Hittable},
-    BvhNode, Material, Ray,
+    ray::transform_point_with_error,
+    BvhNode, Material, MaterialTable, Ray,
 };
+use bif_core::MaterialId;
 use bif_math::{Aabb, Interval, Mat4, Mat4Ext};
 use std::sync::Arc;
 
@@ -48,9 +50,8 @@ pub struct InstancedGeometry<M: Material + Clone> {
     /// World-space bounding box for each instance (for culling)
     instance_bboxes: Vec<Aabb>,
 
-    /// Material for all instances
-    /// TODO: Per-instance materials via material ID array
-    material: M,
+    /// Materials, resolved per instance at hit time
+    materials: MaterialTable<M>,
 
     /// Cached world-space bounding box of all instances
     world_bbox: Aabb,
@@ -62,7 +63,7 @@ impl<M: Material + Clone + 'static> InstancedGeometry<M> {
     /// # Arguments
     /// * `local_primitives` - Geometry in local space (e.g., triangles at origin)
     /// * `transforms` - Local-to-world transforms for each instance
-    /// * `material` - Material shared by all instances (for now)
+    /// * `material` - Default material (see [`Self::with_instance_materials`])
     ///
     /// # Performance
     /// - BVH build: O(P log P) where P = number of primitives (e.g., 280K triangles)
@@ -99,11 +100,24 @@ impl<M: Material + Clone + 'static> InstancedGeometry<M> {
             transforms,
             inv_transforms,
             instance_bboxes,
-            material,
+            materials: MaterialTable::new(material),
             world_bbox,
         }
     }
 
+    /// Assign a material per instance.
+    ///
+    /// `ids` holds one index into `materials` per instance; `NO_MATERIAL`
+    /// entries keep the default material.
+    pub fn with_instance_materials(mut self, materials: Vec<M>, ids: Vec<MaterialId>) -> Self {
+        debug_assert_eq!(ids.len(), self.transforms.len());
+        self.materials = self
+            .materials
+            .with_materials(materials)
+            .with_instance_ids(ids);
+        self
+    }
+
     /// Get number of instances
     pub fn instance_count(&self) -> usize {
         self.transforms.len()
@@ -150,7 +164,8 @@ impl<M: Material + Clone + 'static> Hittable for InstancedGeometry<M> {
             if self.prototype_bvh.hit(&local_ray, interval, &mut local_rec) {
                 // Transform hit back to world space
                 rec.t = local_rec.t;
-                rec.p = transform.transform_point3(local_rec.p);
+                (rec.p, rec.p_error) =
+                    transform_point_with_error(transform, local_rec.p, local_rec.p_error);
 
                 // Transform normal to world space
                 // Note: For non-uniform scales, we'd need inverse-transpose,
@@ -159,7 +174,8 @@ impl<M: Material + Clone + 'static> Hittable for InstancedGeometry<M> {
 
                 rec.u = local_rec.u;
                 rec.v = local_rec.v;
-                rec.material = &self.material;
+                rec.material = self.materials.resolve_instance(i);
+                rec.instance = Some(i as u32);
                 rec.front_face = local_rec.front_face;
 
                 hit_anything = true;
@@ -350,4 +366,43 @@ mod tests {
 
         assert_eq!(instanced.instance_count(), 100);
     }
+
+    #[test]
+    fn test_per_instance_materials() {
+        use bif_core::NO_MATERIAL;
+
+        let tri = create_unit_triangle();
+        let local_primitives: Vec<Box<dyn Hittable + Send + Sync>> = vec![Box::new(tri)];
+
+        let transforms = vec![
+            Mat4::IDENTITY,
+            Mat4::from_translation(Vec3::new(5.0, 0.0, 0.0)),
+        ];
+
+        let instanced = InstancedGeometry::new(
+            local_primitives,
+            transforms,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        )
+        .with_instance_materials(
+            vec![Lambertian::new(Color::new(1.0, 0.0, 0.0))],
+            vec![NO_MATERIAL, 0],
+        );
+
+        let ray_t = Interval::new(0.001, f32::INFINITY);
+        let mut rng = rand::thread_rng();
+        let mut albedo_at = |x: f32| {
+            let ray = Ray::new(Vec3::new(x, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+            let mut rec = HitRecord::default();
+            assert!(instanced.hit(&ray, ray_t, &mut rec));
+            rec.material
+                .scatter(&ray, &rec, &mut rng)
+                .unwrap()
+                .attenuation
+        };
+
+        // Unbound instance keeps the default, bound instance uses its override
+        assert_eq!(albedo_at(0.5), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(albedo_at(5.5), Color::new(1.0, 0.0, 0.0));
+    }
 }
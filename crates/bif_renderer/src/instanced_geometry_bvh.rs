@@ -1,274 +1,908 @@
 //! Two-level BVH for massive instancing (10K+ instances).
 //!
-//! Builds top-level BVH over instance AABBs for logarithmic culling.
+//! Pure-Rust counterpart of `EmbreeScene`: a top-level BVH over instance
+//! bounds whose leaves transform rays into a shared prototype BVH. Used when
+//! the Embree library is unavailable. Curve and point sets are extra
+//! top-level leaves. Instances hidden from a ray's type are skipped before
+//! the ray enters their prototype. Moving instances are placed at each ray's
+//! time, and deforming prototypes interpolate their triangles (see
+//! `bif_math::motion_segment` for how keys map to times).
 
 use crate::{
-    hittable::{HitRecord, Hittable},
-    BvhNode, Material, Ray,
+    hittable::{alpha_test, packet_interval, HitRecord, Hittable},
+    ray::transform_point_with_error,
+    wide_bvh::MAX_PACKET_SIZE,
+    Bvh4, BvhBuildConfig, BvhNode, CurveGeometry, Material, MaterialTable, MeshHit, PointGeometry,
+    Ray, TriangleUvs,
 };
-use bif_math::{Aabb, Interval, Mat4, Mat4Ext, Vec3};
+use bif_core::Visibility;
+use bif_math::{Aabb, Interval, Mat4, Mat4Ext, MotionTransform, Vec3};
 use std::sync::Arc;
 
-/// Instance data for efficient traversal
-struct Instance {
+/// One instance of the shared prototype mesh (a leaf of the top-level BVH).
+struct InstanceNode<M: Material + Clone> {
+    prototype: Arc<Bvh4>,
+    materials: Arc<MaterialTable<M>>,
+    uvs: Arc<TriangleUvs>,
+    /// Index within the prototype's instances
+    instance: usize,
+    /// Index among all instances of the world (reported in hit records)
+    id: u32,
+    visibility: Visibility,
     transform: Mat4,
     inv_transform: Mat4,
-    world_bbox: Aabb,
+    /// Inverse transpose, for transforming normals
+    normal_transform: Mat4,
+    /// Transform keys of a moving instance (the matrices above are unused)
+    motion: Option<MotionTransform>,
+    bbox: Aabb,
 }
 
-/// Wrapper for instance to make it Hittable (for BVH construction)
-struct InstanceWrapper {
-    instance_id: usize,
-    bbox: Aabb,
+impl<M: Material + Clone> InstanceNode<M> {
+    /// Local-to-world transform at `time`.
+    #[inline]
+    fn transform_at(&self, time: f32) -> Mat4 {
+        match &self.motion {
+            Some(motion) => motion.at(time),
+            None => self.transform,
+        }
+    }
+
+    /// Transform a world ray into prototype space.
+    ///
+    /// The direction is not normalized, so local t equals world t.
+    fn local_ray(&self, ray: &Ray) -> Ray {
+        let inv_transform = match &self.motion {
+            Some(motion) => motion.at(ray.time()).inverse(),
+            None => self.inv_transform,
+        };
+        Ray::new(
+            inv_transform.transform_point3(ray.origin()),
+            inv_transform.transform_vector3(ray.direction()),
+            ray.time(),
+        )
+    }
+
+    /// Alpha test with this instance's material, like Embree's filter.
+    fn accept(&self, ray: &Ray, face: u32, u: f32, v: f32, t: f32) -> bool {
+        let material = self.materials.resolve(self.instance, face as usize);
+        let (u, v) = self.uvs.uv(face, u, v);
+        alpha_test(material.opacity(u, v), ray.origin(), ray.direction(), t)
+    }
+
+    fn fill_record<'a>(&'a self, ray: &Ray, hit: &MeshHit, rec: &mut HitRecord<'a>) {
+        let transform = self.transform_at(ray.time());
+        let normal_transform = match self.motion {
+            Some(_) => transform.inverse().transpose(),
+            None => self.normal_transform,
+        };
+        rec.t = hit.t;
+        (rec.p, rec.p_error) = transform_point_with_error(&transform, hit.p, hit.p_error);
+        let normal = normal_transform.transform_vector3(hit.normal).normalize();
+        rec.set_face_normal(ray, normal);
+        (rec.u, rec.v) = self.uvs.uv(hit.primitive, hit.u, hit.v);
+        rec.tangent = transform
+            .transform_vector3(self.uvs.tangent(hit.primitive))
+            .normalize_or_zero();
+        rec.instance = Some(self.id);
+        rec.material = self
+            .materials
+            .resolve(self.instance, hit.primitive as usize);
+    }
 }
 
-impl Hittable for InstanceWrapper {
-    fn hit<'a>(&'a self, _ray: &Ray, _ray_t: Interval, _rec: &mut HitRecord<'a>) -> bool {
-        // Not used - instances are tested via InstancedGeometryBVH
-        false
+impl<M: Material + Clone + 'static> Hittable for InstanceNode<M> {
+    fn hit<'a>(&'a self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord<'a>) -> bool {
+        if !ray.sees(self.visibility) {
+            return false;
+        }
+
+        let local_ray = self.local_ray(ray);
+        let hit = self
+            .prototype
+            .intersect(&local_ray, ray_t, |face, u, v, t| {
+                self.accept(ray, face, u, v, t)
+            });
+        match hit {
+            Some(hit) => {
+                self.fill_record(ray, &hit, rec);
+                true
+            }
+            None => false,
+        }
     }
-    
+
+    fn hit_packet<'a>(
+        &'a self,
+        rays: &[Ray],
+        ray_t: Interval,
+        recs: &mut [HitRecord<'a>],
+        hits: &mut [bool],
+    ) {
+        let chunks = rays
+            .chunks(MAX_PACKET_SIZE)
+            .zip(recs.chunks_mut(MAX_PACKET_SIZE));
+        for ((rays, recs), hits) in chunks.zip(hits.chunks_mut(MAX_PACKET_SIZE)) {
+            if !rays.iter().any(|ray| ray.sees(self.visibility)) {
+                continue;
+            }
+
+            // Rays that can't see this instance get an empty interval
+            let mut local_rays = [rays[0]; MAX_PACKET_SIZE];
+            let mut t_max = [ray_t.max; MAX_PACKET_SIZE];
+            for (i, ray) in rays.iter().enumerate() {
+                local_rays[i] = self.local_ray(ray);
+                t_max[i] = if ray.sees(self.visibility) {
+                    packet_interval(ray_t, &recs[i], hits[i]).max
+                } else {
+                    ray_t.min
+                };
+            }
+
+            let n = rays.len();
+            let mut found = [None; MAX_PACKET_SIZE];
+            self.prototype.intersect_packet(
+                &local_rays[..n],
+                ray_t.min,
+                &mut t_max[..n],
+                &mut found[..n],
+                |r, face, u, v, t| self.accept(&rays[r], face, u, v, t),
+            );
+
+            for (i, hit) in found[..n].iter().enumerate() {
+                if let Some(hit) = hit {
+                    self.fill_record(&rays[i], hit, &mut recs[i]);
+                    hits[i] = true;
+                }
+            }
+        }
+    }
+
     fn bounding_box(&self) -> Aabb {
         self.bbox
     }
 }
 
-/// Two-level BVH: top over instances, bottom per prototype
-pub struct InstancedGeometryBVH<M: Material + Clone> {
-    /// BVH of prototype mesh in local space
-    prototype_bvh: Arc<BvhNode>,
-    
-    /// All instances with transforms
-    instances: Vec<Instance>,
-    
-    /// Top-level BVH over instances (stores instance IDs)
-    instance_tree: BvhNode,
-    
-    /// Mapping from BVH leaves to instance IDs
-    instance_map: Vec<usize>,
-    
-    /// Material for all instances
-    material: M,
-    
-    /// World bbox
-    world_bbox: Aabb,
+/// One prototype mesh and its instances, as input to the two-level scenes.
+///
+/// Instance indices in `materials` and `visibility` are local to this
+/// prototype.
+#[derive(Clone)]
+pub struct InstancedPrototype<'a, M: Clone> {
+    /// Prototype triangles in local space
+    pub vertices: &'a [[Vec3; 3]],
+    /// UVs of each triangle's corners (empty = hits report barycentrics)
+    pub uvs: &'a [[[f32; 2]; 3]],
+    /// Instance transforms (local-to-world matrices)
+    pub transforms: Vec<Mat4>,
+    /// Material table resolved per instance and face
+    pub materials: MaterialTable<M>,
+    /// Ray types each instance is visible to (empty = all visible)
+    pub visibility: Vec<Visibility>,
+    /// Further transform keys of each instance after `transforms`
+    /// (empty = no instance moves)
+    pub motion: Vec<Vec<Mat4>>,
+    /// Further keys of the prototype triangles after `vertices`
+    /// (empty = not deforming)
+    pub deformation: &'a [Vec<[Vec3; 3]>],
 }
 
-impl<M: Material + Clone + 'static> InstancedGeometryBVH<M> {
+impl<'a, M: Clone> InstancedPrototype<'a, M> {
     pub fn new(
-        local_primitives: Vec<Box<dyn Hittable + Send + Sync>>,
+        vertices: &'a [[Vec3; 3]],
         transforms: Vec<Mat4>,
-        material: M,
+        materials: MaterialTable<M>,
     ) -> Self {
-        // Build prototype BVH
-        let prototype_bvh = Arc::new(BvhNode::new(local_primitives));
-        let local_bbox = prototype_bvh.bounding_box();
-        
-        // Create instances
-        let mut instances = Vec::with_capacity(transforms.len());
-        let mut world_bbox = Aabb::EMPTY;
-        let mut instance_wrappers: Vec<Box<dyn Hittable + Send + Sync>> = Vec::new();
-        let mut instance_map = Vec::new();
-        
-        for (id, transform) in transforms.into_iter().enumerate() {
-            let inv_transform = transform.inverse();
-            let inst_bbox = transform.transform_aabb(&local_bbox);
-            
-            instances.push(Instance {
-                transform,
-                inv_transform,
-                world_bbox: inst_bbox,
-            });
-            
-            world_bbox = Aabb::surrounding(&world_bbox, &inst_bbox);
-            
-            // Create wrapper for BVH
-            instance_wrappers.push(Box::new(InstanceWrapper {
-                instance_id: id,
-                bbox: inst_bbox,
-            }));
-            instance_map.push(id);
+        Self {
+            vertices,
+            uvs: &[],
+            transforms,
+            materials,
+            visibility: Vec::new(),
+            motion: Vec::new(),
+            deformation: &[],
         }
-        
-        // Build top-level BVH
-        let instance_tree = BvhNode::new(instance_wrappers);
-        
+    }
+
+    /// Set the UVs of each triangle's corners, for texturing and tangents.
+    pub fn with_uvs(mut self, uvs: &'a [[[f32; 2]; 3]]) -> Self {
+        self.uvs = uvs;
+        self
+    }
+
+    /// Set the ray types each instance is visible to.
+    pub fn with_visibility(mut self, visibility: Vec<Visibility>) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    /// Set further transform keys per instance for motion blur.
+    pub fn with_motion(mut self, motion: Vec<Vec<Mat4>>) -> Self {
+        self.motion = motion;
+        self
+    }
+
+    /// Set further triangle keys for deformation blur.
+    pub fn with_deformation(mut self, deformation: &'a [Vec<[Vec3; 3]>]) -> Self {
+        self.deformation = deformation;
+        self
+    }
+
+    /// Ray types instance `instance` is visible to.
+    #[inline]
+    pub fn instance_visibility(&self, instance: usize) -> Visibility {
+        instance_visibility(&self.visibility, instance)
+    }
+
+    /// Further transform keys of instance `instance` (empty if static).
+    #[inline]
+    pub fn instance_motion(&self, instance: usize) -> &[Mat4] {
+        instance_motion(&self.motion, instance)
+    }
+}
+
+/// Look up an instance in a per-instance visibility list (empty = all visible).
+#[inline]
+pub(crate) fn instance_visibility(visibility: &[Visibility], instance: usize) -> Visibility {
+    visibility.get(instance).copied().unwrap_or(Visibility::ALL)
+}
+
+/// Look up an instance in a per-instance motion list (empty = static).
+#[inline]
+fn instance_motion(motion: &[Vec<Mat4>], instance: usize) -> &[Mat4] {
+    motion.get(instance).map_or(&[], Vec::as_slice)
+}
+
+/// Two-level world that can be updated without a full rebuild.
+///
+/// Implemented by `EmbreeScene` and `InstancedGeometryBVH`. Prototypes are
+/// indexed in the order they were passed to `from_prototypes`.
+pub trait InstancedWorld: Hittable + Send + Sync {
+    /// Replace the transforms of a prototype's instances.
+    ///
+    /// The instance count must not change. Only the top level is rebuilt;
+    /// prototype BVHs are reused. Motion keys are dropped, leaving the
+    /// instances static.
+    fn set_transforms(&mut self, prototype: usize, transforms: &[Mat4]);
+
+    /// Move a prototype's triangles (same triangles in the same order).
+    ///
+    /// The prototype BVH is refit in place rather than rebuilt, for
+    /// deformations and animated frames. Deformation keys are dropped.
+    fn refit_prototype(&mut self, prototype: usize, vertices: &[[Vec3; 3]]);
+}
+
+/// Bottom level of one prototype, kept for incremental updates.
+struct PrototypeLevel<M: Material + Clone> {
+    bvh: Arc<Bvh4>,
+    materials: Arc<MaterialTable<M>>,
+    uvs: Arc<TriangleUvs>,
+    transforms: Vec<Mat4>,
+    visibility: Vec<Visibility>,
+    motion: Vec<Vec<Mat4>>,
+}
+
+/// Two-level BVH: top over instances, bottom per prototype.
+///
+/// Takes the same inputs as `EmbreeScene` so it can stand in for it.
+/// Each prototype is a 4-wide `Bvh4`; materials (including alpha tests)
+/// are resolved per instance and face like `EmbreeScene`.
+pub struct InstancedGeometryBVH<M: Material + Clone> {
+    /// Top-level BVH whose leaves are instances
+    instance_tree: BvhNode,
+
+    /// Per-prototype BVHs, materials and instance transforms
+    prototypes: Vec<PrototypeLevel<M>>,
+
+    /// Curve sets in world space, placed beside the instances
+    curves: Vec<Arc<CurveGeometry>>,
+
+    /// Point sets in world space, placed beside the instances
+    points: Vec<Arc<PointGeometry>>,
+
+    instance_count: usize,
+    triangle_count: usize,
+    world_bbox: Aabb,
+}
+
+impl<M: Material + Clone + 'static> InstancedGeometryBVH<M> {
+    /// Build the two-level BVH for a single prototype.
+    ///
+    /// # Arguments
+    /// * `vertices` - Prototype triangles in local space
+    /// * `transforms` - Instance transforms (local-to-world matrices)
+    /// * `materials` - Material table resolved per instance and face
+    pub fn new(vertices: &[[Vec3; 3]], transforms: Vec<Mat4>, materials: MaterialTable<M>) -> Self {
+        Self::from_prototypes(vec![InstancedPrototype::new(
+            vertices, transforms, materials,
+        )])
+    }
+
+    /// Build the two-level BVH over several prototypes.
+    pub fn from_prototypes(prototypes: Vec<InstancedPrototype<M>>) -> Self {
+        let triangle_count = prototypes.iter().map(|p| p.vertices.len()).sum();
+        let prototypes: Vec<PrototypeLevel<M>> = prototypes
+            .into_iter()
+            .map(|prototype| {
+                let bvh = Arc::new(Bvh4::with_motion(prototype.vertices, prototype.deformation));
+                log::debug!(
+                    "Prototype: {} triangles, {} nodes ({} KiB), {} instances",
+                    prototype.vertices.len(),
+                    bvh.node_count(),
+                    bvh.memory_usage() / 1024,
+                    prototype.transforms.len()
+                );
+                PrototypeLevel {
+                    bvh,
+                    materials: Arc::new(prototype.materials),
+                    uvs: Arc::new(TriangleUvs::new(prototype.vertices, prototype.uvs)),
+                    transforms: prototype.transforms,
+                    visibility: prototype.visibility,
+                    motion: prototype.motion,
+                }
+            })
+            .collect();
+
+        let mut instanced = Self {
+            instance_tree: BvhNode::Empty,
+            prototypes,
+            curves: Vec::new(),
+            points: Vec::new(),
+            instance_count: 0,
+            triangle_count,
+            world_bbox: Aabb::EMPTY,
+        };
+        instanced.build_instance_tree();
+
         log::info!(
-            "InstancedGeometryBVH: {} instances, top-level BVH depth ~{}",
-            instances.len(),
-            (instances.len() as f32).log2() as u32
+            "InstancedGeometryBVH: {} prototypes, {} instances, {} triangles",
+            instanced.prototypes.len(),
+            instanced.instance_count,
+            triangle_count
         );
-        
-        Self {
-            prototype_bvh,
-            instances,
-            instance_tree,
-            instance_map,
-            material,
-            world_bbox,
-        }
+        instanced
     }
-    
-    pub fn instance_count(&self) -> usize {
-        self.instances.len()
+
+    /// Add curve sets (hair, fur) to the top level.
+    pub fn with_curves(mut self, curves: Vec<Arc<CurveGeometry>>) -> Self {
+        if !curves.is_empty() {
+            self.curves = curves;
+            self.build_instance_tree();
+        }
+        self
     }
-    
-    /// Test ray against instances whose bboxes it hits
-    fn test_instances<'a>(
-        &'a self,
-        ray: &Ray,
-        ray_t: Interval,
-        node: &dyn Hittable,
-        rec: &mut HitRecord<'a>,
-        closest: &mut f32,
-    ) -> bool {
-        // Check bbox intersection
-        if !node.bounding_box().hit(ray, Interval::new(ray_t.min, *closest)) {
-            return false;
+
+    /// Add point sets (particles) to the top level.
+    pub fn with_points(mut self, points: Vec<Arc<PointGeometry>>) -> Self {
+        if !points.is_empty() {
+            self.points = points;
+            self.build_instance_tree();
         }
-        
-        // Check if this is a leaf (InstanceWrapper)
-        // Since we can't downcast easily, we'll use a different approach...
-        // Actually, let's just traverse the BVH normally and handle leaves specially
-        
-        let mut hit = false;
-        
-        // Try as BVH node first
-        if let Some(bvh_node) = unsafe { 
-            // This is a hack - in production we'd use proper trait objects
-            (node as *const dyn Hittable as *const BvhNode).as_ref() 
-        } {
-            // It's a BVH node - recurse
-            if let Some(left) = bvh_node.left() {
-                if self.test_instances(ray, ray_t, left.as_ref(), rec, closest) {
-                    hit = true;
-                }
-            }
-            if let Some(right) = bvh_node.right() {
-                if self.test_instances(ray, ray_t, right.as_ref(), rec, closest) {
-                    hit = true;
-                }
-            }
-        } else {
-            // It's a leaf - test the actual instance
-            // For simplicity, test all instances (we'll optimize later)
-            for instance in &self.instances {
-                if !instance.world_bbox.hit(ray, Interval::new(ray_t.min, *closest)) {
-                    continue;
-                }
-                
-                // Transform ray to local space
-                let local_origin = instance.inv_transform.transform_point3(ray.origin());
-                let local_dir = instance.inv_transform.transform_vector3(ray.direction()).normalize();
-                let local_ray = Ray::new(local_origin, local_dir, ray.time());
-                
-                let mut local_rec = HitRecord::default();
-                if self.prototype_bvh.hit(&local_ray, Interval::new(ray_t.min, *closest), &mut local_rec) {
-                    // Transform hit to world
-                    rec.t = local_rec.t;
-                    rec.p = instance.transform.transform_point3(local_rec.p);
-                    rec.normal = instance.transform.transform_vector3(local_rec.normal).normalize();
-                    rec.u = local_rec.u;
-                    rec.v = local_rec.v;
-                    rec.material = &self.material;
-                    rec.front_face = local_rec.front_face;
-                    
-                    *closest = rec.t;
-                    hit = true;
-                }
-            }
+        self
+    }
+
+    /// (Re)build the top-level BVH over all instances, curve and point sets.
+    fn build_instance_tree(&mut self) {
+        let mut instances: Vec<Box<dyn Hittable + Send + Sync>> = Vec::new();
+        for prototype in &self.prototypes {
+            let first_id = instances.len();
+            let bbox = prototype.bvh.bounding_box();
+            instances.extend(prototype.transforms.iter().enumerate().map(
+                |(instance, transform)| {
+                    let inv_transform = transform.inverse();
+                    let keys = instance_motion(&prototype.motion, instance);
+                    let motion = (!keys.is_empty()).then(|| {
+                        let matrices: Vec<Mat4> = std::iter::once(*transform)
+                            .chain(keys.iter().copied())
+                            .collect();
+                        MotionTransform::new(&matrices)
+                    });
+                    let bbox = match &motion {
+                        Some(motion) => motion.transform_aabb(&bbox),
+                        None => transform.transform_aabb(&bbox),
+                    };
+                    Box::new(InstanceNode {
+                        prototype: prototype.bvh.clone(),
+                        materials: prototype.materials.clone(),
+                        uvs: prototype.uvs.clone(),
+                        instance,
+                        id: (first_id + instance) as u32,
+                        visibility: instance_visibility(&prototype.visibility, instance),
+                        transform: *transform,
+                        inv_transform,
+                        normal_transform: inv_transform.transpose(),
+                        motion,
+                        bbox,
+                    }) as Box<dyn Hittable + Send + Sync>
+                },
+            ));
         }
-        
-        hit
+
+        self.instance_count = instances.len();
+        instances.extend(
+            self.curves
+                .iter()
+                .map(|curves| Box::new(curves.clone()) as Box<dyn Hittable + Send + Sync>),
+        );
+        instances.extend(
+            self.points
+                .iter()
+                .map(|points| Box::new(points.clone()) as Box<dyn Hittable + Send + Sync>),
+        );
+        self.instance_tree = BvhNode::new(instances);
+        self.world_bbox = self.instance_tree.bounding_box();
+        log::debug!(
+            "Instance BVH: {}",
+            self.instance_tree.stats(&BvhBuildConfig::default())
+        );
+    }
+
+    /// Get the material table used to resolve hits on the first prototype
+    pub fn materials(&self) -> &MaterialTable<M> {
+        &self.prototypes[0].materials
+    }
+
+    /// Get prototype count
+    pub fn prototype_count(&self) -> usize {
+        self.prototypes.len()
+    }
+
+    /// Get instance count (over all prototypes)
+    pub fn instance_count(&self) -> usize {
+        self.instance_count
+    }
+
+    /// Get triangle count (over all prototypes, not instanced)
+    pub fn triangle_count(&self) -> usize {
+        self.triangle_count
+    }
+
+    /// Get curve segment count (over all curve sets)
+    pub fn curve_segment_count(&self) -> usize {
+        self.curves.iter().map(|c| c.segment_count()).sum()
+    }
+
+    /// Get point count (over all point sets)
+    pub fn point_count(&self) -> usize {
+        self.points.iter().map(|p| p.len()).sum()
+    }
+}
+
+impl<M: Material + Clone + 'static> InstancedWorld for InstancedGeometryBVH<M> {
+    fn set_transforms(&mut self, prototype: usize, transforms: &[Mat4]) {
+        let level = &mut self.prototypes[prototype];
+        assert_eq!(
+            transforms.len(),
+            level.transforms.len(),
+            "set_transforms can't change the instance count"
+        );
+        level.transforms.copy_from_slice(transforms);
+        level.motion.clear();
+
+        // Prototype BVHs are shared as-is; only the top level is rebuilt
+        self.build_instance_tree();
+    }
+
+    fn refit_prototype(&mut self, prototype: usize, vertices: &[[Vec3; 3]]) {
+        // Drop the instance nodes first so the prototype BVH is not shared
+        self.instance_tree = BvhNode::Empty;
+        let level = &mut self.prototypes[prototype];
+        Arc::make_mut(&mut level.bvh).refit(vertices);
+        Arc::make_mut(&mut level.uvs).refit(vertices);
+
+        // Instance bounds depend on the prototype bounds
+        self.build_instance_tree();
     }
 }
 
 impl<M: Material + Clone + 'static> Hittable for InstancedGeometryBVH<M> {
     fn hit<'a>(&'a self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord<'a>) -> bool {
-        // Quick reject using world bbox
-        if !self.world_bbox.hit(ray, ray_t) {
-            return false;
-        }
-        
-        // For now, fall back to linear search until we fix the traversal
-        // This is still better than before because we have early bbox rejection
-        let mut hit_anything = false;
-        let mut closest = ray_t.max;
-        
-        for instance in &self.instances {
-            // Early bbox reject
-            if !instance.world_bbox.hit(ray, Interval::new(ray_t.min, closest)) {
-                continue;
-            }
-            
-            // Transform ray to local space
-            let local_origin = instance.inv_transform.transform_point3(ray.origin());
-            let local_direction = instance.inv_transform.transform_vector3(ray.direction()).normalize();
-            let local_ray = Ray::new(local_origin, local_direction, ray.time());
-            
-            // Test against prototype BVH
-            let interval = Interval::new(ray_t.min, closest);
-            let mut local_rec = HitRecord::default();
-            
-            if self.prototype_bvh.hit(&local_ray, interval, &mut local_rec) {
-                // Transform hit back to world space
-                rec.t = local_rec.t;
-                rec.p = instance.transform.transform_point3(local_rec.p);
-                rec.normal = instance.transform.transform_vector3(local_rec.normal).normalize();
-                rec.u = local_rec.u;
-                rec.v = local_rec.v;
-                rec.material = &self.material;
-                rec.front_face = local_rec.front_face;
-                
-                hit_anything = true;
-                closest = rec.t;
-            }
-        }
-        
-        hit_anything
+        self.instance_tree.hit(ray, ray_t, rec)
+    }
+
+    fn hit_packet<'a>(
+        &'a self,
+        rays: &[Ray],
+        ray_t: Interval,
+        recs: &mut [HitRecord<'a>],
+        hits: &mut [bool],
+    ) {
+        self.instance_tree.hit_packet(rays, ray_t, recs, hits);
     }
-    
+
     fn bounding_box(&self) -> Aabb {
         self.world_bbox
     }
 }
 
+/// Build the fastest available two-level instancing structure.
+///
+/// Uses `EmbreeScene` when built with the `embree` feature and the library
+/// can be loaded, and falls back to `InstancedGeometryBVH` otherwise, so Ivar
+/// always has geometry to trace.
+pub fn build_instanced_world<M: Material + Clone + 'static>(
+    vertices: &[[Vec3; 3]],
+    transforms: Vec<Mat4>,
+    materials: MaterialTable<M>,
+) -> Box<dyn InstancedWorld> {
+    build_prototype_world(vec![InstancedPrototype::new(
+        vertices, transforms, materials,
+    )])
+}
+
+/// Build the fastest available two-level structure over several prototypes.
+///
+/// Same backend choice as [`build_instanced_world`].
+pub fn build_prototype_world<M: Material + Clone + 'static>(
+    prototypes: Vec<InstancedPrototype<M>>,
+) -> Box<dyn InstancedWorld> {
+    build_scene_world(prototypes, Vec::new(), Vec::new())
+}
+
+/// Build the fastest available two-level structure with curve and point sets.
+///
+/// Same backend choice as [`build_instanced_world`]; Embree intersects the
+/// curves and points natively when it is used.
+pub fn build_scene_world<M: Material + Clone + 'static>(
+    prototypes: Vec<InstancedPrototype<M>>,
+    curves: Vec<Arc<CurveGeometry>>,
+    points: Vec<Arc<PointGeometry>>,
+) -> Box<dyn InstancedWorld> {
+    #[cfg(feature = "embree")]
+    if crate::EmbreeScene::<M>::is_available() {
+        log::info!("Using Embree for hardware-accelerated ray tracing");
+        return Box::new(
+            crate::EmbreeScene::from_prototypes(prototypes)
+                .with_curves(curves)
+                .with_points(points),
+        );
+    }
+
+    log::warn!("Embree not available - using pure-Rust two-level BVH");
+    Box::new(
+        InstancedGeometryBVH::from_prototypes(prototypes)
+            .with_curves(curves)
+            .with_points(points),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Color, Lambertian, Triangle};
-    
+    use crate::{Color, Lambertian};
+    use bif_core::NO_MATERIAL;
+
+    fn unit_triangle() -> [Vec3; 3] {
+        [Vec3::ZERO, Vec3::X, Vec3::Y]
+    }
+
     #[test]
     fn test_10k_instances() {
-        // Simple triangle
-        let tri = Triangle::new(
-            Vec3::new(0.0, 0.0, 0.0),
-            Vec3::new(1.0, 0.0, 0.0),
-            Vec3::new(0.0, 1.0, 0.0),
-            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
-        );
-        
-        let primitives: Vec<Box<dyn Hittable + Send + Sync>> = vec![Box::new(tri)];
-        
         // 10K instances in 100x100 grid
         let mut transforms = Vec::new();
         for x in 0..100 {
-            for z in 0..100 {
-                transforms.push(Mat4::from_translation(Vec3::new(x as f32 * 2.0, 0.0, z as f32 * 2.0)));
+            for y in 0..100 {
+                let offset = Vec3::new(x as f32 * 2.0, y as f32 * 2.0, 0.0);
+                transforms.push(Mat4::from_translation(offset));
             }
         }
-        
+
         let instanced = InstancedGeometryBVH::new(
-            primitives,
+            &[unit_triangle()],
             transforms,
-            Lambertian::new(Color::new(0.7, 0.7, 0.7)),
+            MaterialTable::new(Lambertian::new(Color::new(0.7, 0.7, 0.7))),
         );
-        
         assert_eq!(instanced.instance_count(), 10000);
-        
-        // Ray hitting origin instance
-        let ray = Ray::new(Vec3::new(0.5, 0.5, -1.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+
+        // Ray hitting the instance at (42, 84)
+        let ray = Ray::new(Vec3::new(42.25, 84.25, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(instanced.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.p - Vec3::new(42.25, 84.25, 0.0)).length() < 1e-4);
+
+        // Ray between instances misses
+        let ray = Ray::new(Vec3::new(1.5, 1.5, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(!instanced.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+    }
+
+    #[test]
+    fn test_scaled_instance_distance_and_normal() {
+        // Scaled instance in front of an unscaled one: t must stay in world units
+        let transforms = vec![
+            Mat4::from_translation(Vec3::new(0.0, 0.0, -10.0)),
+            Mat4::from_translation(Vec3::new(0.0, 0.0, -5.0)) * Mat4::from_scale(Vec3::splat(4.0)),
+        ];
+        let instanced = InstancedGeometryBVH::new(
+            &[unit_triangle()],
+            transforms,
+            MaterialTable::new(Lambertian::new(Color::ONE)),
+        );
+
+        let ray = Ray::new(Vec3::new(0.5, 0.5, 0.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
         let mut rec = HitRecord::default();
         assert!(instanced.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.t - 5.0).abs() < 1e-4, "t = {}", rec.t);
+        assert!((rec.normal - Vec3::Z).length() < 1e-4);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_instance_and_face_materials() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let green = Color::new(0.0, 1.0, 0.0);
+
+        // Two prototype faces side by side; face 1 is bound to green
+        let faces = [
+            unit_triangle(),
+            [
+                Vec3::new(2.0, 0.0, 0.0),
+                Vec3::new(3.0, 0.0, 0.0),
+                Vec3::new(2.0, 1.0, 0.0),
+            ],
+        ];
+        let table = MaterialTable::new(Lambertian::new(Color::ONE))
+            .with_materials(vec![Lambertian::new(red), Lambertian::new(green)])
+            .with_instance_ids(vec![NO_MATERIAL, 0])
+            .with_face_ids(vec![NO_MATERIAL, 1].into());
+        let transforms = vec![
+            Mat4::IDENTITY,
+            Mat4::from_translation(Vec3::new(0.0, 5.0, 0.0)),
+        ];
+        let instanced = InstancedGeometryBVH::new(&faces, transforms, table);
+
+        let albedo = |x: f32, y: f32| {
+            let ray = Ray::new(Vec3::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+            let mut rec = HitRecord::default();
+            assert!(instanced.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+            let out = Ray::new(rec.p, rec.normal, 0.0);
+            rec.material.bsdf(&ray, &rec, &out) * std::f32::consts::PI
+        };
+
+        // Instance 0: default, face binding wins on face 1
+        assert!((albedo(0.25, 0.25) - Color::ONE).length() < 1e-4);
+        assert!((albedo(2.25, 0.25) - green).length() < 1e-4);
+        // Instance 1: instance binding, face binding still wins on face 1
+        assert!((albedo(0.25, 5.25) - red).length() < 1e-4);
+        assert!((albedo(2.25, 5.25) - green).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_instance_alpha_override() {
+        use crate::ShadingGraphMaterial;
+        use bif_core::TextureCache;
+
+        let mut cache = TextureCache::new();
+        let opaque = ShadingGraphMaterial::new(&bif_core::Material::default(), &mut cache);
+        let cutout = ShadingGraphMaterial::new(
+            &bif_core::Material {
+                opacity: 0.0,
+                ..Default::default()
+            },
+            &mut cache,
+        );
+
+        // Instance 0 is bound to the cutout material and sits in front
+        let table = MaterialTable::new(opaque)
+            .with_materials(vec![cutout])
+            .with_instance_ids(vec![0, NO_MATERIAL]);
+        let transforms = vec![
+            Mat4::from_translation(Vec3::new(0.0, 0.0, -1.0)),
+            Mat4::from_translation(Vec3::new(0.0, 0.0, -3.0)),
+        ];
+        let instanced = InstancedGeometryBVH::new(&[unit_triangle()], transforms, table);
+
+        let ray = Ray::new(Vec3::new(0.25, 0.25, 0.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(instanced.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.t - 3.0).abs() < 1e-4, "t = {}", rec.t);
+    }
+
+    #[test]
+    fn test_multiple_prototypes() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let green = Color::new(0.0, 1.0, 0.0);
+
+        // A triangle and a quad, each with its own instances and materials
+        let triangle = [unit_triangle()];
+        let quad = [
+            unit_triangle(),
+            [Vec3::X, Vec3::new(1.0, 1.0, 0.0), Vec3::Y],
+        ];
+        let instanced = InstancedGeometryBVH::from_prototypes(vec![
+            InstancedPrototype::new(
+                &triangle,
+                vec![Mat4::IDENTITY],
+                MaterialTable::new(Lambertian::new(red)),
+            ),
+            InstancedPrototype::new(
+                &quad,
+                vec![
+                    Mat4::from_translation(Vec3::new(5.0, 0.0, 0.0)),
+                    Mat4::from_translation(Vec3::new(10.0, 0.0, 0.0)),
+                ],
+                MaterialTable::new(Lambertian::new(Color::ONE))
+                    .with_materials(vec![Lambertian::new(green)])
+                    .with_instance_ids(vec![NO_MATERIAL, 0]),
+            ),
+        ]);
+        assert_eq!(instanced.prototype_count(), 2);
+        assert_eq!(instanced.instance_count(), 3);
+        assert_eq!(instanced.triangle_count(), 3);
+
+        let albedo = |x: f32, y: f32| {
+            let ray = Ray::new(Vec3::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+            let mut rec = HitRecord::default();
+            instanced
+                .hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec)
+                .then(|| {
+                    let out = Ray::new(rec.p, rec.normal, 0.0);
+                    rec.material.bsdf(&ray, &rec, &out) * std::f32::consts::PI
+                })
+        };
+
+        // The triangle prototype has no upper-right half; the quad does
+        assert!(albedo(0.75, 0.75).is_none());
+        assert!((albedo(0.25, 0.25).unwrap() - red).length() < 1e-4);
+        assert!((albedo(5.75, 0.75).unwrap() - Color::ONE).length() < 1e-4);
+        // Instance indices are local to each prototype
+        assert!((albedo(10.75, 0.75).unwrap() - green).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_incremental_updates() {
+        let mut instanced = InstancedGeometryBVH::new(
+            &[unit_triangle()],
+            vec![
+                Mat4::IDENTITY,
+                Mat4::from_translation(Vec3::new(5.0, 0.0, 0.0)),
+            ],
+            MaterialTable::new(Lambertian::new(Color::ONE)),
+        );
+        let hit_t = |world: &InstancedGeometryBVH<Lambertian>, x: f32, y: f32| {
+            let ray = Ray::new(Vec3::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+            let mut rec = HitRecord::default();
+            world
+                .hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec)
+                .then_some(rec.t)
+        };
+        assert_eq!(hit_t(&instanced, 5.25, 0.25), Some(1.0));
+
+        // Move the second instance up and back
+        instanced.set_transforms(
+            0,
+            &[
+                Mat4::IDENTITY,
+                Mat4::from_translation(Vec3::new(5.0, 3.0, -1.0)),
+            ],
+        );
+        assert_eq!(hit_t(&instanced, 5.25, 0.25), None);
+        assert_eq!(hit_t(&instanced, 5.25, 3.25), Some(2.0));
+
+        // Deform the prototype: both instances follow
+        let lifted = [
+            Vec3::new(0.0, 0.0, 0.5),
+            Vec3::new(1.0, 0.0, 0.5),
+            Vec3::new(0.0, 1.0, 0.5),
+        ];
+        instanced.refit_prototype(0, &[lifted]);
+        assert_eq!(hit_t(&instanced, 0.25, 0.25), Some(0.5));
+        assert_eq!(hit_t(&instanced, 5.25, 3.25), Some(1.5));
+        assert!((instanced.bounding_box().z.max - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_packet_matches_single_rays() {
+        let transforms = (0..10)
+            .flat_map(|x| (0..10).map(move |y| (x, y)))
+            .map(|(x, y)| Mat4::from_translation(Vec3::new(x as f32 * 2.0, y as f32 * 2.0, 0.0)))
+            .collect();
+        let instanced = InstancedGeometryBVH::new(
+            &[unit_triangle()],
+            transforms,
+            MaterialTable::new(Lambertian::new(Color::ONE)),
+        );
+
+        // A coherent grid of rays, some between instances
+        let rays: Vec<Ray> = (0..80)
+            .map(|i| {
+                let origin = Vec3::new((i % 10) as f32 * 0.7, (i / 10) as f32 * 0.7, 1.0) + 0.05;
+                Ray::new(origin, Vec3::new(0.0, 0.0, -1.0), 0.0)
+            })
+            .collect();
+        let ray_t = Interval::new(0.001, f32::INFINITY);
+        let mut recs = vec![HitRecord::default(); rays.len()];
+        let mut hits = vec![false; rays.len()];
+        instanced.hit_packet(&rays, ray_t, &mut recs, &mut hits);
+
+        assert!(hits.iter().any(|&h| h) && hits.iter().any(|&h| !h));
+        for ((ray, rec), &hit) in rays.iter().zip(&recs).zip(&hits) {
+            let mut expected = HitRecord::default();
+            assert_eq!(instanced.hit(ray, ray_t, &mut expected), hit);
+            if hit {
+                assert!((expected.p - rec.p).length() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_motion_blur() {
+        // One instance slides 4 units along X over the shutter, two keys of
+        // a deforming prototype lift it from z=0 to z=1
+        let lifted = unit_triangle().map(|p| p + Vec3::Z);
+        let instanced = InstancedGeometryBVH::from_prototypes(vec![
+            InstancedPrototype::new(
+                &[unit_triangle()],
+                vec![Mat4::IDENTITY],
+                MaterialTable::new(Lambertian::new(Color::ONE)),
+            )
+            .with_motion(vec![vec![Mat4::from_translation(Vec3::new(4.0, 0.0, 0.0))]]),
+            InstancedPrototype::new(
+                &[unit_triangle()],
+                vec![Mat4::from_translation(Vec3::new(0.0, 5.0, 0.0))],
+                MaterialTable::new(Lambertian::new(Color::ONE)),
+            )
+            .with_deformation(&[vec![lifted]]),
+        ]);
+        assert!((instanced.bounding_box().x.max - 5.0).abs() < 1e-3);
+
+        let ray_t = Interval::new(0.001, f32::INFINITY);
+        let hit_t = |x: f32, y: f32, time: f32| {
+            let ray = Ray::new(Vec3::new(x, y, 2.0), -Vec3::Z, time);
+            let mut rec = HitRecord::default();
+            instanced.hit(&ray, ray_t, &mut rec).then_some(rec.t)
+        };
+
+        // The sliding triangle is where the ray time puts it
+        assert_eq!(hit_t(2.25, 0.25, 0.0), None);
+        assert_eq!(hit_t(2.25, 0.25, 0.5), Some(2.0));
+        assert_eq!(hit_t(4.25, 0.25, 1.0), Some(2.0));
+
+        // The deforming triangle rises with time
+        assert_eq!(hit_t(0.25, 5.25, 0.0), Some(2.0));
+        assert!((hit_t(0.25, 5.25, 0.5).unwrap() - 1.5).abs() < 1e-5);
+        assert!((hit_t(0.25, 5.25, 1.0).unwrap() - 1.0).abs() < 1e-5);
+
+        // Packets place each ray at its own time
+        let rays = [0.0, 0.5].map(|time| Ray::new(Vec3::new(2.25, 0.25, 2.0), -Vec3::Z, time));
+        let mut recs = vec![HitRecord::default(); rays.len()];
+        let mut hits = vec![false; rays.len()];
+        instanced.hit_packet(&rays, ray_t, &mut recs, &mut hits);
+        assert_eq!(hits, [false, true]);
+    }
+
+    #[test]
+    fn test_visibility_masks() {
+        // A camera-invisible blocker above a visible floor
+        let mut no_camera = Visibility::ALL;
+        no_camera.set(Visibility::CAMERA, false);
+        let instanced = InstancedGeometryBVH::from_prototypes(vec![InstancedPrototype::new(
+            &[unit_triangle()],
+            vec![Mat4::from_translation(Vec3::Z), Mat4::IDENTITY],
+            MaterialTable::new(Lambertian::new(Color::ONE)),
+        )
+        .with_visibility(vec![no_camera])]);
+
+        let ray_t = Interval::new(0.001, f32::INFINITY);
+        let down = |visibility| {
+            Ray::new(Vec3::new(0.25, 0.25, 5.0), -Vec3::Z, 0.0).with_visibility(visibility)
+        };
+        let hit_t = |ray: &Ray| {
+            let mut rec = HitRecord::default();
+            instanced.hit(ray, ray_t, &mut rec).then_some(rec.t)
+        };
+
+        // The camera sees through the blocker, everything else hits it
+        assert_eq!(hit_t(&down(Visibility::CAMERA)), Some(5.0));
+        assert_eq!(hit_t(&down(Visibility::SHADOW)), Some(4.0));
+        assert_eq!(hit_t(&down(Visibility::SPECULAR)), Some(4.0));
+
+        // Packets test each ray's type
+        let rays = [down(Visibility::CAMERA), down(Visibility::SHADOW)];
+        let mut recs = vec![HitRecord::default(); rays.len()];
+        let mut hits = vec![false; rays.len()];
+        instanced.hit_packet(&rays, ray_t, &mut recs, &mut hits);
+        assert_eq!(hits, [true, true]);
+        assert_eq!(recs[0].t, 5.0);
+        assert_eq!(recs[1].t, 4.0);
+    }
+}
@@ -8,31 +8,75 @@
 mod bucket;
 mod bvh;
 mod camera;
+mod cuboid;
+mod curves;
+mod cylinder;
+mod disk;
 pub mod disney;
+#[cfg(feature = "embree")]
 mod embree;
+mod hair;
 mod hittable;
 mod instanced_geometry;
+mod instanced_geometry_bvh;
+mod lights;
 mod material;
+mod material_table;
+mod mesh_bvh;
+mod plane;
+mod points;
+mod primitive_bvh;
+mod quad;
 mod ray;
 mod renderer;
+mod scene_builder;
+mod shading_graph;
 mod sphere;
+mod subsurface;
 mod triangle;
+mod triangle_uvs;
+mod wide_bvh;
 
 pub use bucket::{generate_buckets, render_bucket, Bucket, BucketResult, DEFAULT_BUCKET_SIZE};
-pub use bvh::BvhNode;
+pub use bvh::{BvhBuildConfig, BvhNode, BvhStats};
 pub use camera::Camera;
-pub use disney::DisneyBSDF;
+pub use cuboid::Cuboid;
+pub use curves::CurveGeometry;
+pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use disney::{DisneyBSDF, SubsurfaceMode};
+#[cfg(feature = "embree")]
 pub use embree::EmbreeScene;
-pub use hittable::{HitRecord, Hittable, HittableList};
+pub use hair::{sigma_a_from_reflectance, HairBSDF};
+pub use hittable::{alpha_test, HitRecord, Hittable, HittableList};
 pub use instanced_geometry::InstancedGeometry;
+pub use instanced_geometry_bvh::{
+    build_instanced_world, build_prototype_world, build_scene_world, InstancedGeometryBVH,
+    InstancedPrototype, InstancedWorld,
+};
+pub use lights::{
+    power_heuristic, AreaLight, DistantLight, EnvironmentLight, InstancedLights, LightList,
+    LightSample, Shaping, SurfacePoint, TriangleLight,
+};
 pub use material::{
     cosine_weighted_hemisphere, gen_f32, random_in_hemisphere, random_unit_vector, Color,
-    Dielectric, DiffuseLight, Lambertian, Material, Metal, ScatterResult,
+    Dielectric, DiffuseLight, Lambertian, LayeredMaterial, Material, MaterialProperties, Metal,
+    MixMaterial, MixWeight, ScatterResult,
 };
-pub use ray::Ray;
+pub use material_table::MaterialTable;
+pub use mesh_bvh::{MeshBvh, MeshHit};
+pub use plane::Plane;
+pub use points::PointGeometry;
+pub use quad::Quad;
+pub use ray::{offset_ray_origin, Ray};
 pub use renderer::{color_to_rgba, ray_color, render, render_pixel, ImageBuffer, RenderConfig};
+pub use scene_builder::{IvarScene, SceneBuilder};
+pub use shading_graph::{ShadedSurface, ShadingGraphMaterial};
 pub use sphere::Sphere;
+pub use subsurface::{Subsurface, SubsurfaceExit};
 pub use triangle::Triangle;
+pub use triangle_uvs::TriangleUvs;
+pub use wide_bvh::{Bvh4, Bvh8, WideBvh, MAX_PACKET_SIZE};
 
 /// Re-export Vec3 and common math types from bif_math
 pub use bif_math::{Aabb, Interval, Vec3};
@@ -0,0 +1,1514 @@
+//! Area lights for next event estimation.
+//!
+//! Emissive mesh triangles, analytic shapes and UsdLux lights are collected
+//! into a `LightList` and sampled proportionally to their emitted power, so
+//! that area lights are found directly instead of only when a path happens
+//! to hit them. An environment light (a UsdLux dome) is also seen by rays
+//! that escape the scene.
+
+use crate::instanced_geometry_bvh::instance_visibility;
+use crate::material::{build_tangent_basis, gen_f32, Color};
+use crate::{HitRecord, InstancedPrototype, Material, MaterialTable, Ray};
+use bif_core::{LightShaping, Texture, Visibility};
+use bif_math::{Mat4, Vec3};
+use rand::RngCore;
+use std::f32::consts::PI;
+use std::fmt;
+use std::sync::Arc;
+
+/// Barycentric points used to estimate the average emission of a triangle.
+const POWER_SAMPLES: [(f32, f32); 4] = [
+    (1.0 / 3.0, 1.0 / 3.0),
+    (1.0 / 6.0, 1.0 / 6.0),
+    (2.0 / 3.0, 1.0 / 6.0),
+    (1.0 / 6.0, 2.0 / 3.0),
+];
+
+/// A point on the surface of an area light.
+#[derive(Debug, Clone, Copy)]
+pub struct SurfacePoint {
+    /// World-space position
+    pub p: Vec3,
+
+    /// Geometric normal (either orientation; lights are sampled two-sided)
+    pub normal: Vec3,
+
+    /// Texture coordinates, as the material sees them at hit time
+    pub u: f32,
+    pub v: f32,
+}
+
+/// A finite emitting surface that can be sampled uniformly by area.
+///
+/// Implemented by `TriangleLight` and by the analytic primitives (`Quad`,
+/// `Disk`, `Cylinder`, `Cuboid`, `Sphere`), so any of them can be added to
+/// a `LightList`.
+pub trait AreaLight: Send + Sync {
+    /// Surface area in world units.
+    fn area(&self) -> f32;
+
+    /// Map `(s, t)` in [0, 1)² to a point on the surface.
+    ///
+    /// Uniformly distributed `(s, t)` must give points uniformly
+    /// distributed by area, so the area density is `1 / area()`.
+    fn sample_area(&self, s: f32, t: f32) -> SurfacePoint;
+
+    /// Material providing the emission.
+    fn material(&self) -> &dyn Material;
+
+    /// Average emitted luminance times area, used to weight light selection.
+    fn power(&self) -> f32 {
+        let emission: f32 = POWER_GRID
+            .iter()
+            .map(|&(s, t)| {
+                let point = self.sample_area(s, t);
+                luminance(self.material().emitted(point.u, point.v, point.p))
+            })
+            .sum();
+        emission / POWER_GRID.len() as f32 * self.area()
+    }
+}
+
+/// Stratified points used to estimate the average emission of a surface.
+const POWER_GRID: [(f32, f32); 4] = [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)];
+
+/// Corner UVs that make texture coordinates equal the barycentrics.
+const BARYCENTRIC_UVS: [[f32; 2]; 3] = [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]];
+
+/// An emissive triangle in world space.
+///
+/// Barycentric coordinates follow `Triangle`: a point is
+/// `(1 - u - v) * v0 + u * v1 + v * v2`. The material sees the corner UVs
+/// interpolated at that point, which default to the barycentrics.
+#[derive(Clone)]
+pub struct TriangleLight {
+    /// World-space vertices
+    pub vertices: [Vec3; 3],
+
+    /// Texture coordinates of each vertex
+    pub uvs: [[f32; 2]; 3],
+
+    /// Material providing the emission
+    pub material: Arc<dyn Material>,
+}
+
+impl TriangleLight {
+    /// Create a triangle light.
+    pub fn new(vertices: [Vec3; 3], material: Arc<dyn Material>) -> Self {
+        Self {
+            vertices,
+            uvs: BARYCENTRIC_UVS,
+            material,
+        }
+    }
+
+    /// Set the texture coordinates of each vertex (e.g. the mesh UVs).
+    pub fn with_uvs(mut self, uvs: [[f32; 2]; 3]) -> Self {
+        self.uvs = uvs;
+        self
+    }
+
+    /// Point at barycentric coordinates (u, v).
+    #[inline]
+    pub fn point(&self, u: f32, v: f32) -> Vec3 {
+        let [v0, v1, v2] = self.vertices;
+        v0 + u * (v1 - v0) + v * (v2 - v0)
+    }
+
+    /// Texture coordinates at barycentric coordinates (u, v).
+    #[inline]
+    pub fn uv(&self, u: f32, v: f32) -> (f32, f32) {
+        let [a, b, c] = self.uvs;
+        let w = 1.0 - u - v;
+        (
+            w * a[0] + u * b[0] + v * c[0],
+            w * a[1] + u * b[1] + v * c[1],
+        )
+    }
+}
+
+impl AreaLight for TriangleLight {
+    fn area(&self) -> f32 {
+        let [v0, v1, v2] = self.vertices;
+        0.5 * (v1 - v0).cross(v2 - v0).length()
+    }
+
+    fn sample_area(&self, s: f32, t: f32) -> SurfacePoint {
+        let su = s.sqrt();
+        let (u, v) = (su * (1.0 - t), su * t);
+        let [v0, v1, v2] = self.vertices;
+        let (tex_u, tex_v) = self.uv(u, v);
+        SurfacePoint {
+            p: self.point(u, v),
+            normal: (v1 - v0).cross(v2 - v0).normalize(),
+            u: tex_u,
+            v: tex_v,
+        }
+    }
+
+    fn material(&self) -> &dyn Material {
+        self.material.as_ref()
+    }
+
+    fn power(&self) -> f32 {
+        let emission: f32 = POWER_SAMPLES
+            .iter()
+            .map(|&(u, v)| {
+                let (tex_u, tex_v) = self.uv(u, v);
+                luminance(self.material.emitted(tex_u, tex_v, self.point(u, v)))
+            })
+            .sum();
+        emission / POWER_SAMPLES.len() as f32 * self.area()
+    }
+}
+
+/// A light infinitely far away (a UsdLux `DistantLight`), seen from every
+/// point under the same cone of directions.
+#[derive(Debug, Clone, Copy)]
+pub struct DistantLight {
+    /// Unit direction towards the light
+    to_light: Vec3,
+
+    /// `1 - cos` of the cone's half-angle (0 for a single direction)
+    one_minus_cos: f32,
+
+    /// Irradiance on a surface facing the light
+    irradiance: Color,
+
+    /// Radius of a sphere around the scene, to place shadow ray targets
+    /// outside it and estimate the light's power
+    scene_radius: f32,
+}
+
+impl DistantLight {
+    /// Create a light shining along `direction` from a cone `angle` radians
+    /// across. `irradiance` is what a surface facing the light receives.
+    pub fn new(direction: Vec3, angle: f32, irradiance: Color, scene_radius: f32) -> Self {
+        let half_sin = (0.25 * angle.clamp(0.0, 2.0 * PI)).sin();
+        Self {
+            to_light: -direction.normalize(),
+            one_minus_cos: 2.0 * half_sin * half_sin,
+            irradiance,
+            scene_radius: scene_radius.max(0.0),
+        }
+    }
+
+    /// Solid angle of a cone `angle` radians across, in steradians.
+    pub fn solid_angle(angle: f32) -> f32 {
+        let half_sin = (0.25 * angle.clamp(0.0, 2.0 * PI)).sin();
+        4.0 * PI * half_sin * half_sin
+    }
+
+    /// Power falling on the scene's bounding sphere.
+    fn power(&self) -> f32 {
+        luminance(self.irradiance) * PI * self.scene_radius * self.scene_radius
+    }
+
+    /// Sample a direction in the cone, uniformly by solid angle.
+    fn sample(&self, from: Vec3, probability: f32, s: f32, t: f32) -> Option<LightSample> {
+        let one_minus_cos = s * self.one_minus_cos;
+        let cos_theta = 1.0 - one_minus_cos;
+        let sin_theta = (one_minus_cos * (2.0 - one_minus_cos)).max(0.0).sqrt();
+        let phi = 2.0 * PI * t;
+        let (tangent, bitangent) = build_tangent_basis(self.to_light);
+        let direction =
+            cos_theta * self.to_light + sin_theta * (phi.cos() * tangent + phi.sin() * bitangent);
+
+        // A single direction carries all of the irradiance
+        let solid_angle = 2.0 * PI * self.one_minus_cos;
+        let (emission, pdf) = if solid_angle > 0.0 {
+            (self.irradiance / solid_angle, probability / solid_angle)
+        } else {
+            (self.irradiance, probability)
+        };
+
+        Some(LightSample {
+            p: from + direction * (2.0 * self.scene_radius).max(1.0),
+            emission,
+            pdf,
+            mis_pdf: f32::INFINITY,
+        })
+    }
+}
+
+/// Light from every direction infinitely far away (a UsdLux `DomeLight`),
+/// either constant or read from a latitude-longitude texture.
+///
+/// In the dome's local frame the texture's top row is +Y, and its left edge
+/// faces -Z with u = 0.25 facing +X, as in Hydra Storm. Textured domes are
+/// sampled proportionally to the luminance of their texels.
+#[derive(Clone)]
+pub struct EnvironmentLight {
+    /// Dome-to-world transform
+    to_world: Mat4,
+
+    /// World-to-dome transform
+    to_local: Mat4,
+
+    /// Radiance, multiplied with the texture if there is one
+    radiance: Color,
+
+    /// Latlong emission texture
+    texture: Option<Arc<Texture>>,
+
+    /// Sampling distribution over the texture (None for constant domes)
+    distribution: Option<Distribution2D>,
+
+    /// Average luminance over the sphere of directions
+    average: f32,
+
+    /// Radius of a sphere around the scene, to place shadow ray targets
+    /// outside it and estimate the light's power
+    scene_radius: f32,
+}
+
+impl EnvironmentLight {
+    /// Create a constant dome of `radiance`, oriented by `transform`.
+    pub fn new(transform: Mat4, radiance: Color, scene_radius: f32) -> Self {
+        Self {
+            to_world: transform,
+            to_local: transform.inverse(),
+            radiance,
+            texture: None,
+            distribution: None,
+            average: luminance(radiance),
+            scene_radius: scene_radius.max(0.0),
+        }
+    }
+
+    /// Read the emission from a latlong `texture`, scaled by the radiance.
+    pub fn with_texture(mut self, texture: Arc<Texture>) -> Self {
+        // One cell per texel, looked up like escaped rays and weighted by
+        // the solid angle of its row
+        let (width, height) = (texture.width.max(1), texture.height.max(1));
+        let mut rows = Vec::with_capacity(height as usize);
+        let (mut weighted, mut solid_angle) = (0.0, 0.0);
+        for row in 0..height {
+            let y = (row as f32 + 0.5) / height as f32;
+            let sin_theta = (y * PI).sin();
+            let cells: Vec<f32> = (0..width)
+                .map(|column| {
+                    let x = (column as f32 + 0.5) / width as f32;
+                    luminance(self.radiance * texture.sample(x, 1.0 - y)) * sin_theta
+                })
+                .collect();
+            weighted += cells.iter().sum::<f32>();
+            solid_angle += sin_theta * width as f32;
+            rows.push(cells);
+        }
+        self.average = if solid_angle > 0.0 {
+            weighted / solid_angle
+        } else {
+            0.0
+        };
+
+        // Bilinear lookups spill light into dark cells, so a small share of
+        // the average keeps every direction reachable
+        let floor = 0.01 * weighted / (width * height) as f32;
+        for cells in &mut rows {
+            for cell in cells.iter_mut() {
+                *cell += floor;
+            }
+        }
+        self.distribution = Some(Distribution2D::new(&rows));
+        self.texture = Some(texture);
+        self
+    }
+
+    /// Radiance arriving from world-space `direction`.
+    pub fn radiance(&self, direction: Vec3) -> Color {
+        match &self.texture {
+            Some(texture) => {
+                let (x, y) = latlong(self.local_direction(direction));
+                self.radiance * texture.sample(x, 1.0 - y)
+            }
+            None => self.radiance,
+        }
+    }
+
+    /// Solid-angle density of sampling world-space `direction`.
+    pub fn pdf(&self, direction: Vec3) -> f32 {
+        let Some(distribution) = &self.distribution else {
+            return 1.0 / (4.0 * PI);
+        };
+        let (x, y) = latlong(self.local_direction(direction));
+        let sin_theta = (y * PI).sin();
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+        distribution.pdf(x, y) / (2.0 * PI * PI * sin_theta)
+    }
+
+    /// Power falling on the scene's bounding sphere.
+    fn power(&self) -> f32 {
+        self.average * 4.0 * PI * PI * self.scene_radius * self.scene_radius
+    }
+
+    fn local_direction(&self, direction: Vec3) -> Vec3 {
+        self.to_local
+            .transform_vector3(direction)
+            .normalize_or_zero()
+    }
+
+    /// Sample a direction proportionally to the emitted luminance (uniformly
+    /// for constant domes).
+    fn sample(&self, from: Vec3, probability: f32, s: f32, t: f32) -> Option<LightSample> {
+        let local = match &self.distribution {
+            Some(distribution) => {
+                let (x, y) = distribution.sample(s, t);
+                latlong_direction(x, y)
+            }
+            None => {
+                let z = 1.0 - 2.0 * s;
+                let r = (1.0 - z * z).max(0.0).sqrt();
+                let phi = 2.0 * PI * t;
+                Vec3::new(r * phi.cos(), r * phi.sin(), z)
+            }
+        };
+        let direction = self.to_world.transform_vector3(local).normalize_or_zero();
+        let pdf = probability * self.pdf(direction);
+        if pdf <= 0.0 || !pdf.is_finite() {
+            return None;
+        }
+
+        // Escaped rays see the same radiance with the same density, so MIS
+        // uses the exact pdf
+        Some(LightSample {
+            p: from + direction * (2.0 * self.scene_radius).max(1.0),
+            emission: self.radiance(direction),
+            pdf,
+            mis_pdf: pdf,
+        })
+    }
+}
+
+/// Point in the unit square of a latlong map for a local direction, with y
+/// running down from +Y.
+fn latlong(direction: Vec3) -> (f32, f32) {
+    let x = (direction.z.atan2(direction.x) + 0.5 * PI) / (2.0 * PI);
+    let y = direction.y.clamp(-1.0, 1.0).acos() / PI;
+    (x.rem_euclid(1.0), y)
+}
+
+/// Local direction of a point in the unit square of a latlong map (inverse
+/// of `latlong`).
+fn latlong_direction(x: f32, y: f32) -> Vec3 {
+    let (sin_theta, cos_theta) = (y * PI).sin_cos();
+    let (sin_phi, cos_phi) = (2.0 * PI * x - 0.5 * PI).sin_cos();
+    Vec3::new(sin_theta * cos_phi, cos_theta, sin_theta * sin_phi)
+}
+
+/// Spot cone and focus of a UsdLux `ShapingAPI`, attenuating a hidden
+/// light's emission by direction. IES profiles aren't supported.
+#[derive(Debug, Clone, Copy)]
+pub struct Shaping {
+    /// Unit direction the light shines along
+    axis: Vec3,
+
+    /// Cosine of the cone's half-angle (-1 without a cone)
+    cos_cone: f32,
+
+    /// Cosine of the angle where the cone's soft edge starts
+    cos_inner: f32,
+
+    /// Exponent of the cosine falloff away from the axis
+    focus: f32,
+
+    /// Color the focus falls off to
+    focus_tint: Color,
+}
+
+impl Shaping {
+    /// Shape a light shining along `axis` with `ShapingAPI` inputs.
+    ///
+    /// Cones of 90 degrees or more leave the light unshaped. Softness
+    /// smooths the edge from `angle * (1 - softness)` out to `angle`.
+    pub fn new(axis: Vec3, shaping: &LightShaping) -> Self {
+        let (cos_cone, cos_inner) = if shaping.cone_angle < 90.0 {
+            let angle = shaping.cone_angle.max(0.0).to_radians();
+            let inner = angle * (1.0 - shaping.cone_softness.clamp(0.0, 1.0));
+            (angle.cos(), inner.cos())
+        } else {
+            (-1.0, -1.0)
+        };
+        Self {
+            axis: axis.normalize(),
+            cos_cone,
+            cos_inner,
+            focus: shaping.focus.max(0.0),
+            focus_tint: shaping.focus_tint,
+        }
+    }
+
+    /// Emission multiplier towards unit `direction`, away from the light.
+    ///
+    /// Focus scales the emission by `cos^focus` off the axis, blending
+    /// towards the focus tint.
+    pub fn attenuation(&self, direction: Vec3) -> Color {
+        let cos = self.axis.dot(direction);
+        let cone = if cos >= self.cos_inner {
+            1.0
+        } else if cos <= self.cos_cone {
+            0.0
+        } else {
+            let t = (cos - self.cos_cone) / (self.cos_inner - self.cos_cone);
+            t * t * (3.0 - 2.0 * t)
+        };
+        if self.focus <= 0.0 {
+            return Color::splat(cone);
+        }
+        let falloff = cos.max(0.0).powf(self.focus);
+        (self.focus_tint + (Color::ONE - self.focus_tint) * falloff) * cone
+    }
+
+    /// Approximate share of the light's power left by the cone (its share
+    /// of a cosine-weighted hemisphere), to weight light selection.
+    fn power_fraction(&self) -> f32 {
+        if self.cos_cone <= 0.0 {
+            1.0
+        } else {
+            1.0 - self.cos_cone * self.cos_cone
+        }
+    }
+}
+
+/// A point sampled on a light, as seen from a shading point.
+#[derive(Debug, Clone)]
+pub struct LightSample {
+    /// Sampled point on the light
+    pub p: Vec3,
+
+    /// Radiance emitted towards the shading point
+    pub emission: Color,
+
+    /// Probability density with respect to solid angle at the shading point
+    pub pdf: f32,
+
+    /// MIS density for the sample (see `LightList::mis_pdf`), infinite for
+    /// lights BSDF sampling can't hit
+    pub mis_pdf: f32,
+}
+
+/// Ray types a scattered path can take. Emitters hidden from any of them
+/// are left out of next event estimation, which lights every lobe at once.
+const BOUNCE_RAYS: Visibility = Visibility::from_bits(
+    Visibility::DIFFUSE.bits() | Visibility::SPECULAR.bits() | Visibility::TRANSMISSION.bits(),
+);
+
+/// A light in a `LightList`.
+#[derive(Clone)]
+enum ListedLight {
+    /// Emitting surface that is part of the world
+    Surface(Arc<dyn AreaLight>),
+
+    /// Emitting surface that isn't part of the world (a UsdLux light), so
+    /// only light samples find it. It emits on the side its normal faces,
+    /// shaped by direction if it has `ShapingAPI` inputs.
+    Hidden(Arc<dyn AreaLight>, Option<Shaping>),
+
+    /// Infinitely far light, also only found by light samples
+    Distant(DistantLight),
+
+    /// Light from every direction, also seen by escaped rays
+    Environment(Arc<EnvironmentLight>),
+}
+
+impl ListedLight {
+    fn power(&self) -> f32 {
+        match self {
+            Self::Surface(light) => light.power(),
+            Self::Hidden(light, shaping) => {
+                light.power() * shaping.map_or(1.0, |shaping| shaping.power_fraction())
+            }
+            Self::Distant(light) => light.power(),
+            Self::Environment(light) => light.power(),
+        }
+    }
+}
+
+/// Lights with a power-weighted sampling distribution.
+#[derive(Clone, Default)]
+pub struct LightList {
+    lights: Vec<ListedLight>,
+
+    /// Cumulative selection probabilities (last entry = 1)
+    cdf: Vec<f32>,
+
+    /// Sum of all light powers
+    total_power: f32,
+
+    /// Emissive instances left to BSDF sampling because their visibility
+    /// masks some bounce rays (sorted)
+    unsampled_instances: Vec<u32>,
+
+    /// Environment light and its selection probability
+    environment: Option<(Arc<EnvironmentLight>, f32)>,
+}
+
+impl LightList {
+    /// Create an empty light list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a light list from triangles, dropping ones that emit nothing.
+    pub fn from_triangles(triangles: impl IntoIterator<Item = TriangleLight>) -> Self {
+        Self::from_lights(
+            triangles
+                .into_iter()
+                .map(|triangle| Arc::new(triangle) as Arc<dyn AreaLight>),
+        )
+    }
+
+    /// Build a light list from any area lights, dropping ones that emit
+    /// nothing.
+    pub fn from_lights(area_lights: impl IntoIterator<Item = Arc<dyn AreaLight>>) -> Self {
+        Self::from_listed(area_lights.into_iter().map(ListedLight::Surface))
+    }
+
+    /// Build a light list, dropping lights that emit nothing.
+    fn from_listed(listed: impl IntoIterator<Item = ListedLight>) -> Self {
+        let mut lights = Vec::new();
+        let mut cdf = Vec::new();
+        let mut total_power = 0.0;
+
+        for light in listed {
+            let power = light.power();
+            if power <= 0.0 || !power.is_finite() {
+                continue;
+            }
+            total_power += power;
+            cdf.push(total_power);
+            lights.push(light);
+        }
+
+        for c in &mut cdf {
+            *c /= total_power;
+        }
+
+        let mut list = Self {
+            lights,
+            cdf,
+            total_power,
+            unsampled_instances: Vec::new(),
+            environment: None,
+        };
+        list.environment = list.lights.iter().enumerate().find_map(|(index, light)| {
+            let ListedLight::Environment(environment) = light else {
+                return None;
+            };
+            Some((environment.clone(), list.selection_probability(index)))
+        });
+        list
+    }
+
+    /// Collect the emissive triangles of an instanced mesh.
+    ///
+    /// `vertices` are the prototype triangles in local space and
+    /// `transforms` the instance matrices; materials are resolved per
+    /// instance and face exactly as at hit time. Only materials whose
+    /// properties report `is_emissive` are considered.
+    pub fn from_instances<M: Material + Clone + 'static>(
+        vertices: &[[Vec3; 3]],
+        transforms: &[Mat4],
+        materials: &MaterialTable<M>,
+    ) -> Self {
+        let emitters = EmissivePrototype::new(vertices, &[], transforms, materials, &[]);
+        InstancedLights {
+            prototypes: vec![emitters],
+            scene_lights: Vec::new(),
+        }
+        .light_list()
+    }
+
+    /// Collect the emissive triangles of every prototype's instances.
+    ///
+    /// Use [`InstancedLights`] instead to rebuild the list after instance
+    /// edits.
+    pub fn from_prototypes<M: Material + Clone + 'static>(
+        prototypes: &[InstancedPrototype<M>],
+    ) -> Self {
+        InstancedLights::from_prototypes(prototypes).light_list()
+    }
+
+    /// Number of lights.
+    pub fn len(&self) -> usize {
+        self.lights.len()
+    }
+
+    /// Check if there are no lights.
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty()
+    }
+
+    /// Total emitted power (luminance times area) of all lights.
+    ///
+    /// Distant and environment lights count the power falling on the scene.
+    pub fn total_power(&self) -> f32 {
+        self.total_power
+    }
+
+    /// Probability of selecting light `index`.
+    fn selection_probability(&self, index: usize) -> f32 {
+        let previous = if index == 0 { 0.0 } else { self.cdf[index - 1] };
+        self.cdf[index] - previous
+    }
+
+    /// Sample a point on a light as seen from `from`.
+    ///
+    /// Returns None if there are no lights or the sample is degenerate.
+    pub fn sample(&self, from: Vec3, rng: &mut dyn RngCore) -> Option<LightSample> {
+        if self.lights.is_empty() {
+            return None;
+        }
+
+        // Pick a light proportionally to its power
+        let xi = gen_f32(rng);
+        let index = self
+            .cdf
+            .partition_point(|&c| c <= xi)
+            .min(self.lights.len() - 1);
+        let probability = self.selection_probability(index);
+        let s = gen_f32(rng);
+        let t = gen_f32(rng);
+        let (light, hidden, shaping) = match &self.lights[index] {
+            ListedLight::Surface(light) => (light, false, None),
+            ListedLight::Hidden(light, shaping) => (light, true, *shaping),
+            ListedLight::Distant(light) => return light.sample(from, probability, s, t),
+            ListedLight::Environment(light) => return light.sample(from, probability, s, t),
+        };
+
+        // Uniform point on its surface
+        let point = light.sample_area(s, t);
+
+        let to_light = point.p - from;
+        let distance_squared = to_light.length_squared();
+        let cos_light = point.normal.dot(to_light.normalize());
+        let cos_light = if hidden { -cos_light } else { cos_light.abs() };
+        if cos_light <= 1e-6 || distance_squared <= 1e-12 {
+            return None;
+        }
+
+        let area_pdf = probability / light.area();
+        let mut emission = light.material().emitted(point.u, point.v, point.p);
+        if let Some(shaping) = shaping {
+            emission *= shaping.attenuation(-to_light.normalize());
+        }
+        let to_solid_angle = distance_squared / cos_light;
+
+        // Hidden lights are never hit, so light samples get the full weight
+        let mis_pdf = if hidden {
+            f32::INFINITY
+        } else {
+            self.mis_pdf(emission) * to_solid_angle
+        };
+
+        Some(LightSample {
+            p: point.p,
+            emission,
+            pdf: area_pdf * to_solid_angle,
+            mis_pdf,
+        })
+    }
+
+    /// MIS density (per unit area) for a light point emitting `emission`.
+    ///
+    /// This is luminance over total power: exact for lights with constant
+    /// emission, and an approximation for textured ones. The same function
+    /// is used for both strategies, so MIS weights still sum to one.
+    pub fn mis_pdf(&self, emission: Color) -> f32 {
+        if self.total_power <= 0.0 {
+            return 0.0;
+        }
+        luminance(emission) / self.total_power
+    }
+
+    /// MIS density (per unit solid angle) for a BSDF-sampled `ray` that hit
+    /// an emitter at `rec`.
+    ///
+    /// Zero for instances the list never samples, so BSDF sampling gets
+    /// their full weight.
+    pub fn mis_pdf_for_hit(&self, ray: &Ray, rec: &HitRecord, emission: Color) -> f32 {
+        if rec
+            .instance
+            .is_some_and(|id| self.unsampled_instances.binary_search(&id).is_ok())
+        {
+            return 0.0;
+        }
+        let direction = ray.direction();
+        let distance = rec.t * direction.length();
+        let cos_light = rec.normal.dot(direction.normalize()).abs();
+        if cos_light <= 1e-6 {
+            return 0.0;
+        }
+        self.mis_pdf(emission) * distance * distance / cos_light
+    }
+
+    /// Radiance of the environment light along an escaped ray's
+    /// `direction`, and the solid-angle density with which the list samples
+    /// it (for MIS).
+    ///
+    /// None without an environment light.
+    pub fn environment(&self, direction: Vec3) -> Option<(Color, f32)> {
+        let (light, probability) = self.environment.as_ref()?;
+        Some((
+            light.radiance(direction),
+            probability * light.pdf(direction),
+        ))
+    }
+}
+
+impl fmt::Debug for LightList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LightList")
+            .field("lights", &self.lights.len())
+            .field("total_power", &self.total_power)
+            .finish()
+    }
+}
+
+/// Power heuristic (beta = 2) for combining two sampling strategies.
+///
+/// An infinite `pdf` (a light only one strategy can find) gets full weight.
+#[inline]
+pub fn power_heuristic(pdf: f32, other_pdf: f32) -> f32 {
+    if pdf == f32::INFINITY {
+        return 1.0;
+    }
+    let a = pdf * pdf;
+    let b = other_pdf * other_pdf;
+    if a + b <= 0.0 {
+        return 0.0;
+    }
+    a / (a + b)
+}
+
+/// Emissive triangles of instanced prototypes, kept in local space so the
+/// light list can follow edits to an [`InstancedWorld`](crate::InstancedWorld).
+///
+/// Prototypes are indexed like the world built from the same
+/// `InstancedPrototype`s, and `set_transforms` / `refit_prototype` take the
+/// same arguments as their `InstancedWorld` counterparts. Instances are
+/// numbered like the world's hit records, so instances whose visibility
+/// masks some bounce rays can be left to BSDF sampling.
+///
+/// Lights outside the world (UsdLux lights) are kept alongside and added
+/// to every rebuilt list.
+#[derive(Clone, Default)]
+pub struct InstancedLights {
+    prototypes: Vec<EmissivePrototype>,
+    scene_lights: Vec<ListedLight>,
+}
+
+impl InstancedLights {
+    /// Collect the emissive triangles of every prototype's instances.
+    pub fn from_prototypes<M: Material + Clone + 'static>(
+        prototypes: &[InstancedPrototype<M>],
+    ) -> Self {
+        let prototypes = prototypes
+            .iter()
+            .map(|prototype| {
+                EmissivePrototype::new(
+                    prototype.vertices,
+                    prototype.uvs,
+                    &prototype.transforms,
+                    &prototype.materials,
+                    &prototype.visibility,
+                )
+            })
+            .collect();
+        Self {
+            prototypes,
+            scene_lights: Vec::new(),
+        }
+    }
+
+    /// Add an emitting surface that isn't part of the world, such as a
+    /// UsdLux rect or sphere light.
+    ///
+    /// It emits only on the side its normal faces, and since paths can't
+    /// hit it, only light samples find it.
+    pub fn with_hidden_light(mut self, light: Arc<dyn AreaLight>) -> Self {
+        self.scene_lights.push(ListedLight::Hidden(light, None));
+        self
+    }
+
+    /// Add a hidden light (see [`Self::with_hidden_light`]) whose emission
+    /// is shaped by direction.
+    pub fn with_shaped_light(mut self, light: Arc<dyn AreaLight>, shaping: Shaping) -> Self {
+        self.scene_lights
+            .push(ListedLight::Hidden(light, Some(shaping)));
+        self
+    }
+
+    /// Add a distant light.
+    pub fn with_distant_light(mut self, light: DistantLight) -> Self {
+        self.scene_lights.push(ListedLight::Distant(light));
+        self
+    }
+
+    /// Add the environment light, which escaped rays also see. Only one is
+    /// used; later ones are ignored.
+    pub fn with_environment_light(mut self, light: EnvironmentLight) -> Self {
+        if self.has_environment_light() {
+            log::warn!("Only one environment light is supported, ignoring another");
+        } else {
+            self.scene_lights
+                .push(ListedLight::Environment(Arc::new(light)));
+        }
+        self
+    }
+
+    /// Check if an environment light was added.
+    pub fn has_environment_light(&self) -> bool {
+        self.scene_lights
+            .iter()
+            .any(|light| matches!(light, ListedLight::Environment(_)))
+    }
+
+    /// Replace the transforms of a prototype's instances.
+    pub fn set_transforms(&mut self, prototype: usize, transforms: &[Mat4]) {
+        let prototype = &mut self.prototypes[prototype];
+        assert_eq!(
+            transforms.len(),
+            prototype.transforms.len(),
+            "set_transforms can't change the instance count"
+        );
+        prototype.transforms.copy_from_slice(transforms);
+    }
+
+    /// Move a prototype's triangles (same triangles in the same order).
+    pub fn refit_prototype(&mut self, prototype: usize, vertices: &[[Vec3; 3]]) {
+        for triangle in &mut self.prototypes[prototype].triangles {
+            triangle.vertices = vertices[triangle.face];
+        }
+    }
+
+    /// Build the light list for the current transforms and vertices.
+    pub fn light_list(&self) -> LightList {
+        let mut triangles = Vec::new();
+        let mut unsampled_instances = Vec::new();
+        let mut first_id = 0;
+        for prototype in &self.prototypes {
+            prototype.append_world(&mut triangles);
+            unsampled_instances.extend(
+                prototype
+                    .unsampled
+                    .iter()
+                    .map(|&instance| (first_id + instance) as u32),
+            );
+            first_id += prototype.transforms.len();
+        }
+        let triangles = triangles
+            .into_iter()
+            .map(|triangle| ListedLight::Surface(Arc::new(triangle)));
+        LightList {
+            unsampled_instances,
+            ..LightList::from_listed(triangles.chain(self.scene_lights.iter().cloned()))
+        }
+    }
+}
+
+/// Emissive triangles of one instanced mesh, in local space.
+#[derive(Clone, Default)]
+struct EmissivePrototype {
+    triangles: Vec<EmissiveTriangle>,
+    transforms: Vec<Mat4>,
+    /// Emissive instances hidden from some bounce rays (not sampled)
+    unsampled: Vec<usize>,
+}
+
+/// An emissive face of one instance.
+#[derive(Clone)]
+struct EmissiveTriangle {
+    instance: usize,
+    face: usize,
+    vertices: [Vec3; 3],
+    uvs: [[f32; 2]; 3],
+    material: Arc<dyn Material>,
+}
+
+impl EmissivePrototype {
+    /// Find the emissive faces of each instance of a mesh.
+    ///
+    /// `uvs` are the corner UVs of each triangle; without one per triangle,
+    /// emission is looked up at the barycentrics as on hits.
+    fn new<M: Material + Clone + 'static>(
+        vertices: &[[Vec3; 3]],
+        uvs: &[[[f32; 2]; 3]],
+        transforms: &[Mat4],
+        materials: &MaterialTable<M>,
+        visibility: &[Visibility],
+    ) -> Self {
+        let mut triangles = Vec::new();
+        let mut unsampled = Vec::new();
+        if materials.iter().any(|m| m.properties().is_emissive) {
+            // Share one allocation per material between all of its triangles
+            let materials = materials.map(|m| {
+                let emissive = m.properties().is_emissive;
+                emissive.then(|| Arc::new(m.clone()) as Arc<dyn Material>)
+            });
+
+            for instance in 0..transforms.len() {
+                // Light samples stand in for every lobe, so instances some
+                // bounces can't hit (e.g. kept out of reflections) are only
+                // found by BSDF sampling
+                if !instance_visibility(visibility, instance).contains(BOUNCE_RAYS) {
+                    unsampled.push(instance);
+                    continue;
+                }
+                for (face, triangle) in vertices.iter().enumerate() {
+                    let Some(material) = materials.resolve(instance, face) else {
+                        continue;
+                    };
+                    triangles.push(EmissiveTriangle {
+                        instance,
+                        face,
+                        vertices: *triangle,
+                        uvs: if uvs.len() == vertices.len() {
+                            uvs[face]
+                        } else {
+                            BARYCENTRIC_UVS
+                        },
+                        material: material.clone(),
+                    });
+                }
+            }
+        }
+
+        Self {
+            triangles,
+            transforms: transforms.to_vec(),
+            unsampled,
+        }
+    }
+
+    /// Append the triangles in world space to `triangles`.
+    fn append_world(&self, triangles: &mut Vec<TriangleLight>) {
+        for triangle in &self.triangles {
+            let transform = self.transforms[triangle.instance];
+            let world = triangle.vertices.map(|v| transform.transform_point3(v));
+            triangles
+                .push(TriangleLight::new(world, triangle.material.clone()).with_uvs(triangle.uvs));
+        }
+    }
+}
+
+/// Piecewise-constant density over [0, 1), sampled by inverting its CDF.
+#[derive(Debug, Clone)]
+struct Distribution1D {
+    /// Cumulative weights (first entry 0, last entry 1)
+    cdf: Vec<f32>,
+
+    /// Density of each cell
+    density: Vec<f32>,
+
+    /// Sum of the weights
+    total: f32,
+}
+
+impl Distribution1D {
+    /// Build from non-negative cell weights; all-zero weights give a
+    /// uniform density.
+    fn new(weights: &[f32]) -> Self {
+        let total: f32 = weights.iter().sum();
+        let cells = weights.len().max(1) as f32;
+        let weight = |w: f32| if total > 0.0 { w } else { 1.0 };
+        let sum = if total > 0.0 { total } else { cells };
+
+        let mut cdf = Vec::with_capacity(weights.len() + 1);
+        let mut running = 0.0;
+        cdf.push(0.0);
+        for &w in weights {
+            running += weight(w);
+            cdf.push(running / sum);
+        }
+        Self {
+            cdf,
+            density: weights.iter().map(|&w| weight(w) * cells / sum).collect(),
+            total,
+        }
+    }
+
+    /// Map `xi` in [0, 1) to a point in [0, 1) and the cell it falls in.
+    fn sample(&self, xi: f32) -> (f32, usize) {
+        let cells = self.density.len();
+        let cell = (self.cdf.partition_point(|&c| c <= xi) - 1).min(cells - 1);
+        let width = self.cdf[cell + 1] - self.cdf[cell];
+        let offset = if width > 0.0 {
+            ((xi - self.cdf[cell]) / width).clamp(0.0, 1.0)
+        } else {
+            0.5
+        };
+        let x = (cell as f32 + offset) / cells as f32;
+        (x.min(1.0 - f32::EPSILON), cell)
+    }
+
+    /// Cell containing `x` in [0, 1).
+    fn cell(&self, x: f32) -> usize {
+        ((x * self.density.len() as f32) as usize).min(self.density.len() - 1)
+    }
+}
+
+/// Piecewise-constant density over the unit square, from a grid of cell
+/// weights (rows along y).
+#[derive(Debug, Clone)]
+struct Distribution2D {
+    /// Density of picking each row
+    rows: Distribution1D,
+
+    /// Density along x within each row
+    columns: Vec<Distribution1D>,
+}
+
+impl Distribution2D {
+    fn new(weights: &[Vec<f32>]) -> Self {
+        let columns: Vec<Distribution1D> =
+            weights.iter().map(|row| Distribution1D::new(row)).collect();
+        let row_weights: Vec<f32> = columns.iter().map(|row| row.total).collect();
+        Self {
+            rows: Distribution1D::new(&row_weights),
+            columns,
+        }
+    }
+
+    /// Map `(s, t)` in [0, 1)² to a point distributed by the density.
+    fn sample(&self, s: f32, t: f32) -> (f32, f32) {
+        let (y, row) = self.rows.sample(s);
+        let (x, _) = self.columns[row].sample(t);
+        (x, y)
+    }
+
+    /// Density at `(x, y)`.
+    fn pdf(&self, x: f32, y: f32) -> f32 {
+        let row = self.rows.cell(y);
+        let columns = &self.columns[row];
+        self.rows.density[row] * columns.density[columns.cell(x)]
+    }
+}
+
+/// Luminance of a color (Rec. 709).
+#[inline]
+fn luminance(c: Color) -> f32 {
+    0.2126 * c.x + 0.7152 * c.y + 0.0722 * c.z
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DiffuseLight;
+    use bif_core::Texture;
+    use rand::SeedableRng;
+
+    fn quad_lights(emit: Color, z: f32) -> [TriangleLight; 2] {
+        let material: Arc<dyn Material> = Arc::new(DiffuseLight::new(emit));
+        let a = Vec3::new(-1.0, -1.0, z);
+        let b = Vec3::new(1.0, -1.0, z);
+        let c = Vec3::new(1.0, 1.0, z);
+        let d = Vec3::new(-1.0, 1.0, z);
+        [
+            TriangleLight::new([a, b, c], material.clone()),
+            TriangleLight::new([a, c, d], material),
+        ]
+    }
+
+    #[test]
+    fn test_power_weighted_selection() {
+        let bright = quad_lights(Color::splat(3.0), 1.0);
+        let dim = quad_lights(Color::splat(1.0), -1.0);
+        let dark = quad_lights(Color::ZERO, 0.0);
+        let lights = LightList::from_triangles(bright.into_iter().chain(dim).chain(dark));
+
+        // Non-emissive triangles are dropped
+        assert_eq!(lights.len(), 4);
+        assert!((lights.total_power() - 16.0).abs() < 1e-3);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let n = 4000;
+        let bright_hits = (0..n)
+            .filter_map(|_| lights.sample(Vec3::ZERO, &mut rng))
+            .filter(|s| s.p.z > 0.0)
+            .count();
+        let fraction = bright_hits as f32 / n as f32;
+        assert!((fraction - 0.75).abs() < 0.05, "fraction {fraction}");
+    }
+
+    #[test]
+    fn test_sample_pdf_matches_mis_pdf_for_constant_emission() {
+        let lights = LightList::from_triangles(quad_lights(Color::splat(2.0), 1.0));
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        for _ in 0..16 {
+            let sample = lights.sample(Vec3::ZERO, &mut rng).unwrap();
+            assert!((sample.pdf - sample.mis_pdf).abs() < 1e-3 * sample.pdf);
+            assert!(sample.p.x.abs() <= 1.0 && sample.p.y.abs() <= 1.0);
+        }
+    }
+
+    #[test]
+    fn test_textured_emission() {
+        // Dark at u = 0, bright from the middle texel on
+        let texture = Texture::new(
+            3,
+            1,
+            vec![
+                [0.0, 0.0, 0.0, 1.0],
+                [4.0, 4.0, 4.0, 1.0],
+                [4.0, 4.0, 4.0, 1.0],
+            ],
+            "emission.exr",
+        );
+        let light = DiffuseLight::textured(Arc::new(texture), Color::ONE);
+        assert_eq!(light.emitted(0.0, 0.0, Vec3::ZERO), Color::ZERO);
+        assert_eq!(light.emitted(0.9, 0.0, Vec3::ZERO), Color::splat(4.0));
+
+        let triangle = TriangleLight::new(
+            [Vec3::ZERO, Vec3::X, Vec3::Y],
+            Arc::new(light) as Arc<dyn Material>,
+        );
+        let lights = LightList::from_triangles([triangle]);
+        assert_eq!(lights.len(), 1);
+        assert!(lights.total_power() > 0.0);
+    }
+
+    #[test]
+    fn test_emission_at_mesh_uvs() {
+        // Dark texel at u = 0, bright from u = 0.5 on
+        let texture = Texture::new(
+            3,
+            1,
+            vec![
+                [0.0, 0.0, 0.0, 1.0],
+                [4.0, 4.0, 4.0, 1.0],
+                [4.0, 4.0, 4.0, 1.0],
+            ],
+            "emission.exr",
+        );
+        let light = DiffuseLight::textured(Arc::new(texture), Color::ONE);
+        let triangle = [Vec3::ZERO, Vec3::X, Vec3::Y];
+        let prototype = |uvs| {
+            InstancedPrototype::new(
+                std::slice::from_ref(&triangle),
+                vec![Mat4::IDENTITY],
+                MaterialTable::new(light.clone()),
+            )
+            .with_uvs(uvs)
+        };
+
+        // Mapped entirely onto the dark texel: no power despite the
+        // bright barycentrics
+        let dark = [[[0.0, 0.5]; 3]];
+        assert_eq!(LightList::from_prototypes(&[prototype(&dark)]).len(), 0);
+
+        let bright = [[[0.75, 0.5]; 3]];
+        let lights = LightList::from_prototypes(&[prototype(&bright)]);
+        assert!((lights.total_power() - 4.0 * 0.5).abs() < 1e-4);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(2);
+        let sample = lights.sample(Vec3::new(0.2, 0.2, 1.0), &mut rng).unwrap();
+        assert_eq!(sample.emission, Color::splat(4.0));
+    }
+
+    #[test]
+    fn test_from_instances_resolves_materials() {
+        let triangle = [Vec3::ZERO, Vec3::X, Vec3::Y];
+        let transforms = [
+            Mat4::IDENTITY,
+            Mat4::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+        ];
+
+        // Only the second instance is bound to the emissive material
+        let table = MaterialTable::new(crate::MixMaterial::new(
+            DiffuseLight::new(Color::ZERO),
+            DiffuseLight::new(Color::ONE),
+            0.0,
+        ))
+        .with_materials(vec![crate::MixMaterial::new(
+            DiffuseLight::new(Color::ZERO),
+            DiffuseLight::new(Color::ONE),
+            1.0,
+        )])
+        .with_instance_ids(vec![bif_core::NO_MATERIAL, 0]);
+
+        let lights = LightList::from_instances(&[triangle], &transforms, &table);
+        assert_eq!(lights.len(), 1);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(9);
+        let sample = lights.sample(Vec3::new(0.2, 0.2, 1.0), &mut rng).unwrap();
+        assert!((sample.p.z - 5.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_from_prototypes() {
+        let triangle = [Vec3::ZERO, Vec3::X, Vec3::Y];
+        let quad = [triangle, [Vec3::X, Vec3::new(1.0, 1.0, 0.0), Vec3::Y]];
+        let prototypes = [
+            InstancedPrototype::new(
+                &quad[..1],
+                vec![Mat4::IDENTITY],
+                MaterialTable::new(DiffuseLight::new(Color::ZERO)),
+            ),
+            InstancedPrototype::new(
+                &quad,
+                vec![Mat4::IDENTITY, Mat4::from_translation(Vec3::Z)],
+                MaterialTable::new(DiffuseLight::new(Color::ONE)),
+            ),
+        ];
+
+        // Only the second prototype is emissive: 2 faces x 2 instances
+        let lights = LightList::from_prototypes(&prototypes);
+        assert_eq!(lights.len(), 4);
+    }
+
+    #[test]
+    fn test_masked_instances_are_left_to_bsdf_sampling() {
+        let quad = quad_lights(Color::ONE, 0.0).map(|light| light.vertices);
+        let mut no_specular = Visibility::ALL;
+        no_specular.set(Visibility::SPECULAR, false);
+        let prototypes = [
+            InstancedPrototype::new(
+                &quad,
+                vec![Mat4::IDENTITY],
+                MaterialTable::new(DiffuseLight::new(Color::ZERO)),
+            ),
+            InstancedPrototype::new(
+                &quad,
+                vec![Mat4::IDENTITY, Mat4::from_translation(Vec3::Z)],
+                MaterialTable::new(DiffuseLight::new(Color::ONE)),
+            )
+            .with_visibility(vec![Visibility::ALL, no_specular]),
+        ];
+
+        // Instance 1 of the second prototype (world instance 2) isn't sampled
+        let lights = LightList::from_prototypes(&prototypes);
+        assert_eq!(lights.len(), 2);
+
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 2.0), Vec3::NEG_Z, 0.0);
+        let hit = |instance| HitRecord {
+            t: 1.0,
+            normal: Vec3::Z,
+            instance: Some(instance),
+            ..Default::default()
+        };
+        assert!(lights.mis_pdf_for_hit(&ray, &hit(1), Color::ONE) > 0.0);
+        assert_eq!(lights.mis_pdf_for_hit(&ray, &hit(2), Color::ONE), 0.0);
+    }
+
+    #[test]
+    fn test_instanced_lights_follow_edits() {
+        let quad = quad_lights(Color::ONE, 0.0).map(|light| light.vertices);
+        let prototypes = [InstancedPrototype::new(
+            &quad,
+            vec![Mat4::IDENTITY],
+            MaterialTable::new(DiffuseLight::new(Color::ONE)),
+        )];
+        let mut instanced = InstancedLights::from_prototypes(&prototypes);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+
+        instanced.set_transforms(0, &[Mat4::from_translation(Vec3::new(0.0, 0.0, 3.0))]);
+        let sample = instanced.light_list().sample(Vec3::ZERO, &mut rng).unwrap();
+        assert!((sample.p.z - 3.0).abs() < 1e-6);
+
+        // Doubling the quad in place quadruples its power
+        instanced.refit_prototype(0, &quad.map(|tri| tri.map(|v| v * 2.0)));
+        let lights = instanced.light_list();
+        assert!((lights.total_power() - 16.0).abs() < 1e-3);
+        let sample = lights.sample(Vec3::ZERO, &mut rng).unwrap();
+        assert!((sample.p.z - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_analytic_area_lights() {
+        // A quad light matches the same quad as two triangles
+        let quad = Arc::new(crate::Quad::new(
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            DiffuseLight::new(Color::splat(2.0)),
+        ));
+        let triangles = LightList::from_triangles(quad_lights(Color::splat(2.0), 1.0));
+        let lights = LightList::from_lights([quad as Arc<dyn AreaLight>]);
+        assert_eq!(lights.len(), 1);
+        assert!((lights.total_power() - triangles.total_power()).abs() < 1e-4);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        for _ in 0..16 {
+            let sample = lights.sample(Vec3::ZERO, &mut rng).unwrap();
+            assert!((sample.pdf - sample.mis_pdf).abs() < 1e-3 * sample.pdf);
+            assert!((sample.p.z - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_hidden_lights_emit_one_sided() {
+        // Facing down, with no world geometry for paths to hit
+        let quad = Arc::new(crate::Quad::new(
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            DiffuseLight::new(Color::ONE),
+        ));
+        let lights = InstancedLights::default()
+            .with_hidden_light(quad)
+            .light_list();
+        assert_eq!(lights.len(), 1);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(13);
+        let sample = lights.sample(Vec3::ZERO, &mut rng).unwrap();
+        assert_eq!(sample.mis_pdf, f32::INFINITY);
+        assert_eq!(power_heuristic(sample.mis_pdf, 1.0), 1.0);
+        assert!(lights.sample(Vec3::new(0.0, 0.0, 2.0), &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_distant_light() {
+        let irradiance = Color::splat(2.0);
+        let sun = DistantLight::new(Vec3::NEG_Z, 0.01, irradiance, 10.0);
+        let lights = InstancedLights::default()
+            .with_distant_light(sun)
+            .with_distant_light(DistantLight::new(Vec3::X, 0.0, Color::ZERO, 10.0))
+            .light_list();
+        assert_eq!(lights.len(), 1);
+        assert!((lights.total_power() - 2.0 * PI * 100.0).abs() < 1e-2);
+
+        // Samples come from above, outside the scene, and carry the irradiance
+        let solid_angle = DistantLight::solid_angle(0.01);
+        assert!((solid_angle - PI * 0.005 * 0.005).abs() < 1e-9);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(17);
+        for _ in 0..16 {
+            let sample = lights.sample(Vec3::ZERO, &mut rng).unwrap();
+            assert!(sample.p.z > 19.99 && sample.p.length() <= 20.0 + 1e-3);
+            assert!((sample.emission / sample.pdf - irradiance).length() < 1e-3);
+            assert!((sample.pdf - 1.0 / solid_angle).abs() < 1e-3 * sample.pdf);
+        }
+
+        // A single direction
+        let lights = InstancedLights::default()
+            .with_distant_light(DistantLight::new(Vec3::NEG_Z, 0.0, irradiance, 1.0))
+            .light_list();
+        let sample = lights.sample(Vec3::ZERO, &mut rng).unwrap();
+        assert_eq!(sample.p, Vec3::new(0.0, 0.0, 2.0));
+        assert_eq!((sample.emission, sample.pdf), (irradiance, 1.0));
+    }
+
+    #[test]
+    fn test_environment_light() {
+        // A constant dome is sampled uniformly over the sphere
+        let dome = EnvironmentLight::new(Mat4::IDENTITY, Color::splat(2.0), 1.0);
+        let lights = InstancedLights::default()
+            .with_environment_light(dome)
+            .light_list();
+        assert!((lights.total_power() - 2.0 * 4.0 * PI * PI).abs() < 1e-3);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(19);
+        let sample = lights.sample(Vec3::ZERO, &mut rng).unwrap();
+        assert!((sample.p.length() - 2.0).abs() < 1e-5);
+        assert_eq!(sample.emission, Color::splat(2.0));
+        assert!((sample.pdf - 1.0 / (4.0 * PI)).abs() < 1e-6);
+        assert_eq!(
+            lights.environment(sample.p),
+            Some((sample.emission, sample.pdf))
+        );
+
+        // Without one, escaped rays see nothing
+        assert!(LightList::new().environment(Vec3::Y).is_none());
+    }
+
+    #[test]
+    fn test_textured_environment_light() {
+        // Bright in the columns around +X, turned to face +Z
+        let width = 8;
+        let pixels = (0..width * 4)
+            .map(|i| match i % width {
+                1..=3 => [8.0, 8.0, 8.0, 1.0],
+                _ => [0.1, 0.1, 0.1, 1.0],
+            })
+            .collect();
+        let texture = Arc::new(Texture::new(width, 4, pixels, "dome.exr"));
+        let turn = Mat4::from_rotation_y(-0.5 * PI);
+        let dome = EnvironmentLight::new(turn, Color::ONE, 1.0).with_texture(texture);
+        assert!(dome.radiance(Vec3::Z).x > 7.0);
+        assert!(dome.radiance(Vec3::NEG_Z).x < 0.2);
+
+        // The density integrates to one over the sphere
+        let mut rng = rand::rngs::StdRng::seed_from_u64(23);
+        let n = 40_000;
+        let integral: f32 = (0..n)
+            .map(|_| {
+                let z = 1.0 - 2.0 * gen_f32(&mut rng);
+                let phi = 2.0 * PI * gen_f32(&mut rng);
+                let r = (1.0 - z * z).sqrt();
+                dome.pdf(Vec3::new(r * phi.cos(), r * phi.sin(), z)) * 4.0 * PI
+            })
+            .sum::<f32>()
+            / n as f32;
+        assert!((integral - 1.0).abs() < 0.03, "integral {integral}");
+
+        // Samples favor the bright side and match what escaped rays see
+        let lights = InstancedLights::default()
+            .with_environment_light(dome)
+            .light_list();
+        let samples: Vec<_> = (0..1000)
+            .filter_map(|_| lights.sample(Vec3::ZERO, &mut rng))
+            .collect();
+        let bright = samples.iter().filter(|s| s.emission.x > 1.0).count();
+        assert!(bright > 900, "{bright} of {} bright", samples.len());
+        for sample in &samples[..16] {
+            let (radiance, pdf) = lights.environment(sample.p).unwrap();
+            assert!((radiance - sample.emission).length() < 1e-4);
+            assert!((pdf - sample.pdf).abs() < 1e-3 * pdf);
+            assert_eq!(sample.mis_pdf, sample.pdf);
+        }
+    }
+
+    #[test]
+    fn test_shaped_lights() {
+        // A 30 degree cone softened over its outer half, focused along -Z
+        let shaping = LightShaping {
+            cone_angle: 30.0,
+            cone_softness: 0.5,
+            focus: 2.0,
+            focus_tint: Color::new(0.5, 0.0, 0.0),
+            ..Default::default()
+        };
+        let spot = Shaping::new(Vec3::NEG_Z, &shaping);
+        let off_axis = |degrees: f32| {
+            let (sin, cos) = degrees.to_radians().sin_cos();
+            spot.attenuation(Vec3::new(sin, 0.0, -cos))
+        };
+        assert_eq!(off_axis(0.0), Color::ONE);
+        let focused = off_axis(10.0);
+        let falloff = 10f32.to_radians().cos().powi(2);
+        assert!((focused.y - falloff).abs() < 1e-5);
+        assert!((focused.x - (0.5 + 0.5 * falloff)).abs() < 1e-5);
+        let edge = off_axis(22.5);
+        assert!(edge.y > 0.0 && edge.y < off_axis(15.0).y * 0.6);
+        assert_eq!(off_axis(31.0), Color::ZERO);
+
+        // Unshaped at the 90 degree default
+        let wide = Shaping::new(Vec3::NEG_Z, &LightShaping::default());
+        assert_eq!(wide.attenuation(Vec3::X), Color::ONE);
+
+        // Light samples outside the cone carry nothing
+        let quad = Arc::new(crate::Quad::new(
+            Vec3::new(-0.1, -0.1, 1.0),
+            Vec3::new(0.0, 0.2, 0.0),
+            Vec3::new(0.2, 0.0, 0.0),
+            DiffuseLight::new(Color::ONE),
+        ));
+        let lights = InstancedLights::default()
+            .with_shaped_light(quad, spot)
+            .light_list();
+        assert!((lights.total_power() - 0.04 * 0.25).abs() < 1e-5);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(29);
+        let below = lights.sample(Vec3::ZERO, &mut rng).unwrap();
+        assert!(below.emission.y > 0.9);
+        let aside = lights.sample(Vec3::new(2.0, 0.0, 0.0), &mut rng).unwrap();
+        assert_eq!(aside.emission, Color::ZERO);
+    }
+
+    #[test]
+    fn test_power_heuristic() {
+        assert_eq!(power_heuristic(f32::INFINITY, 1.0), 1.0);
+        assert_eq!(power_heuristic(1.0, 0.0), 1.0);
+        assert_eq!(power_heuristic(0.0, 0.0), 0.0);
+        assert!((power_heuristic(1.0, 1.0) - 0.5).abs() < 1e-6);
+    }
+}
@@ -1,9 +1,11 @@
 //! Material trait for surface scattering.
 
-use crate::{hittable::HitRecord, Ray};
+use crate::{hittable::HitRecord, Ray, Subsurface};
+use bif_core::{Texture, TextureCache, TextureResult, Visibility};
 use bif_math::Vec3;
 use rand::RngCore;
 use std::f32::consts::PI;
+use std::sync::Arc;
 
 /// Color type alias (RGB values typically 0-1)
 pub type Color = Vec3;
@@ -56,6 +58,66 @@ pub trait Material: Send + Sync {
     fn emitted(&self, _u: f32, _v: f32, _p: Vec3) -> Color {
         Color::ZERO
     }
+
+    /// Get the opacity (coverage) at the given UV coordinates.
+    ///
+    /// Used during traversal to let rays pass through masked regions
+    /// (see `alpha_test`). Most materials are fully opaque.
+    fn opacity(&self, _u: f32, _v: f32) -> f32 {
+        1.0
+    }
+
+    /// Get random-walk subsurface parameters at the given UV coordinates.
+    ///
+    /// Materials returning Some are entered by the renderer with a
+    /// `Subsurface::random_walk` for the given fraction of paths.
+    fn subsurface(&self, _u: f32, _v: f32) -> Option<Subsurface> {
+        None
+    }
+
+    /// Get hints about how the renderer may treat this material.
+    ///
+    /// Next event estimation is only performed for materials that report
+    /// `can_use_nee`, i.e. whose `bsdf()` and `pdf()` match `scatter()`.
+    fn properties(&self) -> MaterialProperties {
+        MaterialProperties::default()
+    }
+}
+
+/// Shared materials behave like the material they point to.
+impl<M: Material + ?Sized> Material for Arc<M> {
+    fn scatter(
+        &self,
+        ray_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterResult> {
+        (**self).scatter(ray_in, rec, rng)
+    }
+
+    fn bsdf(&self, ray_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Color {
+        (**self).bsdf(ray_in, rec, scattered)
+    }
+
+    fn pdf(&self, ray_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f32 {
+        (**self).pdf(ray_in, rec, scattered)
+    }
+
+    fn emitted(&self, u: f32, v: f32, p: Vec3) -> Color {
+        (**self).emitted(u, v, p)
+    }
+
+    fn opacity(&self, u: f32, v: f32) -> f32 {
+        (**self).opacity(u, v)
+    }
+
+    fn subsurface(&self, u: f32, v: f32) -> Option<Subsurface> {
+        (**self).subsurface(u, v)
+    }
+
+    fn properties(&self) -> MaterialProperties {
+        (**self).properties()
+    }
 }
 
 // =============================================================================
@@ -83,6 +145,17 @@ pub struct MaterialProperties {
     pub can_use_nee: bool,
 }
 
+impl MaterialProperties {
+    /// Properties of a blend of two materials.
+    pub fn blend(self, other: Self) -> Self {
+        Self {
+            is_pure_specular: self.is_pure_specular && other.is_pure_specular,
+            is_emissive: self.is_emissive || other.is_emissive,
+            can_use_nee: self.can_use_nee && other.can_use_nee,
+        }
+    }
+}
+
 /// Lambertian (diffuse) material.
 #[derive(Clone)]
 pub struct Lambertian {
@@ -94,15 +167,6 @@ impl Lambertian {
     pub fn new(albedo: Color) -> Self {
         Self { albedo }
     }
-
-    /// Get the material properties.
-    pub fn properties() -> MaterialProperties {
-        MaterialProperties {
-            is_pure_specular: false,
-            is_emissive: false,
-            can_use_nee: true,
-        }
-    }
 }
 
 impl Material for Lambertian {
@@ -120,7 +184,8 @@ impl Material for Lambertian {
             scatter_direction = rec.normal;
         }
 
-        let scattered = Ray::new(rec.p, scatter_direction, ray_in.time());
+        let scattered =
+            Ray::new(rec.p, scatter_direction, ray_in.time()).with_visibility(Visibility::DIFFUSE);
 
         // Cosine-weighted PDF: cos(theta) / pi
         let cos_theta = rec.normal.dot(scatter_direction.normalize()).max(0.0);
@@ -142,6 +207,14 @@ impl Material for Lambertian {
         let cos_theta = rec.normal.dot(scattered.direction().normalize()).max(0.0);
         (cos_theta / PI).max(0.0001)
     }
+
+    fn properties(&self) -> MaterialProperties {
+        MaterialProperties {
+            is_pure_specular: false,
+            is_emissive: false,
+            can_use_nee: true,
+        }
+    }
 }
 
 /// Metal (specular) material.
@@ -175,7 +248,8 @@ impl Material for Metal {
 
         // Only scatter if the reflected ray is in the same hemisphere as the normal
         if scattered_dir.dot(rec.normal) > 0.0 {
-            let scattered = Ray::new(rec.p, scattered_dir, ray_in.time());
+            let scattered =
+                Ray::new(rec.p, scattered_dir, ray_in.time()).with_visibility(Visibility::SPECULAR);
             // Perfect specular has delta PDF, use 1.0 as placeholder
             Some(ScatterResult {
                 attenuation: self.albedo,
@@ -186,6 +260,13 @@ impl Material for Metal {
             None
         }
     }
+
+    fn properties(&self) -> MaterialProperties {
+        MaterialProperties {
+            is_pure_specular: true,
+            ..Default::default()
+        }
+    }
 }
 
 /// Dielectric (glass) material.
@@ -230,14 +311,17 @@ impl Material for Dielectric {
         // Check for total internal reflection
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
 
-        let direction =
+        let (direction, visibility) =
             if cannot_refract || Self::reflectance(cos_theta, refraction_ratio) > gen_f32(rng) {
-                reflect(unit_direction, rec.normal)
+                (reflect(unit_direction, rec.normal), Visibility::SPECULAR)
             } else {
-                refract(unit_direction, rec.normal, refraction_ratio)
+                (
+                    refract(unit_direction, rec.normal, refraction_ratio),
+                    Visibility::TRANSMISSION,
+                )
             };
 
-        let scattered = Ray::new(rec.p, direction, ray_in.time());
+        let scattered = Ray::new(rec.p, direction, ray_in.time()).with_visibility(visibility);
         // Perfect specular/transmission has delta PDF, use 1.0 as placeholder
         Some(ScatterResult {
             attenuation,
@@ -245,17 +329,38 @@ impl Material for Dielectric {
             pdf: 1.0,
         })
     }
+
+    fn properties(&self) -> MaterialProperties {
+        MaterialProperties {
+            is_pure_specular: true,
+            ..Default::default()
+        }
+    }
 }
 
 /// Diffuse light emitter.
+#[derive(Clone)]
 pub struct DiffuseLight {
     emit: Color,
+    /// Optional emission texture, multiplied with `emit`
+    texture: Option<Arc<Texture>>,
 }
 
 impl DiffuseLight {
     /// Create a new diffuse light with the given emission color.
     pub fn new(emit: Color) -> Self {
-        Self { emit }
+        Self {
+            emit,
+            texture: None,
+        }
+    }
+
+    /// Create a diffuse light whose emission is read from a texture.
+    pub fn textured(texture: Arc<Texture>, scale: Color) -> Self {
+        Self {
+            emit: scale,
+            texture: Some(texture),
+        }
     }
 }
 
@@ -270,8 +375,262 @@ impl Material for DiffuseLight {
         None
     }
 
-    fn emitted(&self, _u: f32, _v: f32, _p: Vec3) -> Color {
-        self.emit
+    fn emitted(&self, u: f32, v: f32, _p: Vec3) -> Color {
+        match &self.texture {
+            Some(texture) => self.emit * texture.sample(u, v),
+            None => self.emit,
+        }
+    }
+
+    fn properties(&self) -> MaterialProperties {
+        MaterialProperties {
+            is_emissive: true,
+            ..Default::default()
+        }
+    }
+}
+
+// =============================================================================
+// Material mixing
+// =============================================================================
+
+/// Blend weight for mixed and layered materials.
+#[derive(Clone)]
+pub enum MixWeight {
+    /// Same weight everywhere
+    Constant(f32),
+    /// Weight read from one channel of a texture at the hit UV (e.g. a dirt mask)
+    Texture {
+        texture: Arc<Texture>,
+        channel: usize,
+    },
+}
+
+impl MixWeight {
+    /// Create a weight from the first channel of a mask texture.
+    pub fn texture(texture: Arc<Texture>) -> Self {
+        Self::Texture {
+            texture,
+            channel: 0,
+        }
+    }
+
+    /// Load a mask texture as a weight. Masks are non-color data, so the
+    /// file is read without sRGB decoding.
+    pub fn load(textures: &mut TextureCache, path: &str) -> TextureResult<Self> {
+        textures.load_linear(path).map(Self::texture)
+    }
+
+    /// Evaluate the weight at texture coordinates (u, v), clamped to [0, 1].
+    #[inline]
+    pub fn at(&self, u: f32, v: f32) -> f32 {
+        let w = match self {
+            Self::Constant(w) => *w,
+            Self::Texture { texture, channel } => texture.sample_channel(u, v, *channel),
+        };
+        w.clamp(0.0, 1.0)
+    }
+}
+
+impl From<f32> for MixWeight {
+    fn from(weight: f32) -> Self {
+        Self::Constant(weight)
+    }
+}
+
+/// Linear blend of two materials: `(1 - w) * a + w * b`.
+///
+/// `scatter()` picks one component stochastically with probability equal to
+/// its weight (one-sample estimator, like the Disney lobe selection), while
+/// `bsdf()` and `pdf()` return the weighted sum of both components so that
+/// MIS sees the same mixture that was sampled.
+#[derive(Clone)]
+pub struct MixMaterial<A: Material, B: Material> {
+    a: A,
+    b: B,
+    weight: MixWeight,
+}
+
+impl<A: Material, B: Material> MixMaterial<A, B> {
+    /// Mix `a` and `b`; `weight` is the amount of `b` (0 = all `a`, 1 = all `b`).
+    pub fn new(a: A, b: B, weight: impl Into<MixWeight>) -> Self {
+        Self {
+            a,
+            b,
+            weight: weight.into(),
+        }
+    }
+}
+
+impl<A: Material, B: Material> Material for MixMaterial<A, B> {
+    fn scatter(
+        &self,
+        ray_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterResult> {
+        let w = self.weight.at(rec.u, rec.v);
+        let (chosen, other, p): (&dyn Material, &dyn Material, f32) = if gen_f32(rng) < w {
+            (&self.b, &self.a, w)
+        } else {
+            (&self.a, &self.b, 1.0 - w)
+        };
+
+        let mut result = chosen.scatter(ray_in, rec, rng)?;
+        result.pdf = p * result.pdf + (1.0 - p) * other.pdf(ray_in, rec, &result.scattered);
+        Some(result)
+    }
+
+    fn bsdf(&self, ray_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Color {
+        let w = self.weight.at(rec.u, rec.v);
+        self.a
+            .bsdf(ray_in, rec, scattered)
+            .lerp(self.b.bsdf(ray_in, rec, scattered), w)
+    }
+
+    fn pdf(&self, ray_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f32 {
+        let w = self.weight.at(rec.u, rec.v);
+        (1.0 - w) * self.a.pdf(ray_in, rec, scattered) + w * self.b.pdf(ray_in, rec, scattered)
+    }
+
+    fn emitted(&self, u: f32, v: f32, p: Vec3) -> Color {
+        let w = self.weight.at(u, v);
+        self.a.emitted(u, v, p).lerp(self.b.emitted(u, v, p), w)
+    }
+
+    fn opacity(&self, u: f32, v: f32) -> f32 {
+        let w = self.weight.at(u, v);
+        (1.0 - w) * self.a.opacity(u, v) + w * self.b.opacity(u, v)
+    }
+
+    fn properties(&self) -> MaterialProperties {
+        self.a.properties().blend(self.b.properties())
+    }
+}
+
+/// Stack of materials over a base, each layer with its own coverage.
+///
+/// Layers are applied bottom to top: a layer with coverage `c` hides `c` of
+/// everything beneath it, so the effective weight of a layer is its coverage
+/// times the uncovered fraction of all layers above it. A dirt layer with a
+/// mask texture over a metal base is `LayeredMaterial::new(metal)
+/// .with_layer(dirt, MixWeight::texture(mask))`.
+#[derive(Clone)]
+pub struct LayeredMaterial {
+    base: Arc<dyn Material>,
+    /// Layers from bottom to top
+    layers: Vec<(Arc<dyn Material>, MixWeight)>,
+}
+
+impl LayeredMaterial {
+    /// Create a layered material with only a base.
+    pub fn new(base: impl Material + 'static) -> Self {
+        Self {
+            base: Arc::new(base),
+            layers: Vec::new(),
+        }
+    }
+
+    /// Add a layer on top of the current stack.
+    pub fn with_layer(
+        mut self,
+        layer: impl Material + 'static,
+        coverage: impl Into<MixWeight>,
+    ) -> Self {
+        self.layers.push((Arc::new(layer), coverage.into()));
+        self
+    }
+
+    /// Number of layers above the base.
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Effective weight of each component at (u, v), top layer first and
+    /// base last.
+    ///
+    /// Weights always sum to 1.
+    fn weights(&self, u: f32, v: f32) -> impl Iterator<Item = (&dyn Material, f32)> + '_ {
+        // The base covers whatever the layers above leave uncovered
+        let mut uncovered = 1.0;
+        self.layers
+            .iter()
+            .rev()
+            .map(move |(layer, coverage)| (layer.as_ref(), coverage.at(u, v)))
+            .chain(std::iter::once((self.base.as_ref(), 1.0)))
+            .map(move |(material, c)| {
+                let w = uncovered * c;
+                uncovered *= 1.0 - c;
+                (material, w)
+            })
+    }
+}
+
+impl Material for LayeredMaterial {
+    fn scatter(
+        &self,
+        ray_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterResult> {
+        // Pick a component proportionally to its weight, falling back to
+        // the base
+        let mut xi = gen_f32(rng);
+        let mut chosen = None;
+        for (i, (material, w)) in self.weights(rec.u, rec.v).enumerate() {
+            chosen = Some((i, material, w));
+            if xi < w {
+                break;
+            }
+            xi -= w;
+        }
+
+        let (chosen, material, w) = chosen?;
+        let mut result = material.scatter(ray_in, rec, rng)?;
+        let others: f32 = self
+            .weights(rec.u, rec.v)
+            .enumerate()
+            .filter(|(i, (_, w))| *i != chosen && *w > 0.0)
+            .map(|(_, (m, w))| w * m.pdf(ray_in, rec, &result.scattered))
+            .sum();
+        result.pdf = w * result.pdf + others;
+        Some(result)
+    }
+
+    fn bsdf(&self, ray_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Color {
+        self.weights(rec.u, rec.v)
+            .filter(|(_, w)| *w > 0.0)
+            .map(|(m, w)| w * m.bsdf(ray_in, rec, scattered))
+            .sum()
+    }
+
+    fn pdf(&self, ray_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f32 {
+        self.weights(rec.u, rec.v)
+            .filter(|(_, w)| *w > 0.0)
+            .map(|(m, w)| w * m.pdf(ray_in, rec, scattered))
+            .sum()
+    }
+
+    fn emitted(&self, u: f32, v: f32, p: Vec3) -> Color {
+        self.weights(u, v)
+            .filter(|(_, w)| *w > 0.0)
+            .map(|(m, w)| w * m.emitted(u, v, p))
+            .sum()
+    }
+
+    fn opacity(&self, u: f32, v: f32) -> f32 {
+        self.weights(u, v)
+            .filter(|(_, w)| *w > 0.0)
+            .map(|(m, w)| w * m.opacity(u, v))
+            .sum()
+    }
+
+    fn properties(&self) -> MaterialProperties {
+        self.layers
+            .iter()
+            .fold(self.base.properties(), |props, (layer, _)| {
+                props.blend(layer.properties())
+            })
     }
 }
 
@@ -344,7 +703,7 @@ pub fn cosine_weighted_hemisphere(normal: Vec3, rng: &mut dyn RngCore) -> Vec3 {
 }
 
 /// Build orthonormal tangent/bitangent from a normal.
-fn build_tangent_basis(n: Vec3) -> (Vec3, Vec3) {
+pub(crate) fn build_tangent_basis(n: Vec3) -> (Vec3, Vec3) {
     let sign = if n.z >= 0.0 { 1.0 } else { -1.0 };
     let a = -1.0 / (sign + n.z);
     let b = n.x * n.y * a;
@@ -354,3 +713,111 @@ fn build_tangent_basis(n: Vec3) -> (Vec3, Vec3) {
 
     (tangent, bitangent)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit_record(u: f32, v: f32) -> HitRecord<'static> {
+        HitRecord {
+            p: Vec3::ZERO,
+            normal: Vec3::Y,
+            u,
+            v,
+            t: 1.0,
+            front_face: true,
+            ..Default::default()
+        }
+    }
+
+    fn rays() -> (Ray, Ray) {
+        let ray_in = Ray::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let scattered = Ray::new(Vec3::ZERO, Vec3::new(0.3, 1.0, 0.0), 0.0);
+        (ray_in, scattered)
+    }
+
+    #[test]
+    fn test_mix_bsdf_and_pdf_are_weighted() {
+        let red = Lambertian::new(Color::new(1.0, 0.0, 0.0));
+        let blue = Lambertian::new(Color::new(0.0, 0.0, 1.0));
+        let mix = MixMaterial::new(red.clone(), blue.clone(), 0.25);
+        let rec = hit_record(0.5, 0.5);
+        let (ray_in, scattered) = rays();
+
+        let expected = red
+            .bsdf(&ray_in, &rec, &scattered)
+            .lerp(blue.bsdf(&ray_in, &rec, &scattered), 0.25);
+        assert!((mix.bsdf(&ray_in, &rec, &scattered) - expected).length() < 1e-6);
+
+        // Both lobes share a cosine PDF, so the mixture PDF is unchanged
+        let pdf = red.pdf(&ray_in, &rec, &scattered);
+        assert!((mix.pdf(&ray_in, &rec, &scattered) - pdf).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mix_scatter_selects_by_weight() {
+        let mix = MixMaterial::new(
+            Lambertian::new(Color::new(1.0, 0.0, 0.0)),
+            Lambertian::new(Color::new(0.0, 0.0, 1.0)),
+            0.25,
+        );
+        let rec = hit_record(0.5, 0.5);
+        let (ray_in, _) = rays();
+        let mut rng = rand::thread_rng();
+
+        let n = 4000;
+        let blue = (0..n)
+            .filter_map(|_| mix.scatter(&ray_in, &rec, &mut rng))
+            .filter(|r| r.attenuation.z > 0.5)
+            .count();
+        let fraction = blue as f32 / n as f32;
+        assert!((fraction - 0.25).abs() < 0.05, "blue fraction {}", fraction);
+    }
+
+    #[test]
+    fn test_mix_weight_from_texture() {
+        let mask = Arc::new(Texture::new(
+            3,
+            1,
+            vec![[0.0; 4], [1.0; 4], [1.0; 4]],
+            "<mask>",
+        ));
+        let weight = MixWeight::texture(mask);
+        assert_eq!(weight.at(0.0, 0.5), 0.0);
+        assert_eq!(weight.at(0.99, 0.5), 1.0);
+        assert_eq!(MixWeight::from(2.0).at(0.0, 0.0), 1.0);
+
+        let path = std::env::temp_dir().join("bif_mix_weight_mask.png");
+        image::GrayImage::from_pixel(1, 1, image::Luma([102]))
+            .save(&path)
+            .unwrap();
+        let weight = MixWeight::load(&mut TextureCache::new(), &path.to_string_lossy()).unwrap();
+        assert!((weight.at(0.5, 0.5) - 0.4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_layered_weights_sum_to_one() {
+        let layered = LayeredMaterial::new(Lambertian::new(Color::ONE))
+            .with_layer(Lambertian::new(Color::X), 0.5)
+            .with_layer(Lambertian::new(Color::Y), 0.5);
+        assert_eq!(layered.layer_count(), 2);
+
+        let weights: Vec<f32> = layered.weights(0.0, 0.0).map(|(_, w)| w).collect();
+        // Top layer covers half, middle layer half of the rest, base the remainder
+        assert_eq!(weights, vec![0.5, 0.25, 0.25]);
+
+        let rec = hit_record(0.0, 0.0);
+        let (ray_in, scattered) = rays();
+        let bsdf = layered.bsdf(&ray_in, &rec, &scattered);
+        let base = Lambertian::new(Color::new(0.5, 0.75, 0.25)).bsdf(&ray_in, &rec, &scattered);
+        assert!((bsdf - base).length() < 1e-6);
+    }
+
+    #[test]
+    fn test_layered_emission() {
+        let layered = LayeredMaterial::new(DiffuseLight::new(Color::ONE))
+            .with_layer(Lambertian::new(Color::ONE), 0.75);
+        let emitted = layered.emitted(0.0, 0.0, Vec3::ZERO);
+        assert!((emitted - Color::splat(0.25)).length() < 1e-6);
+    }
+}
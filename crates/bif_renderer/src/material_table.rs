@@ -0,0 +1,165 @@
+//! Per-instance and per-face material assignment.
+//!
+//! Accelerators store one compact `MaterialId` per instance and per
+//! prototype triangle instead of a material per primitive, and resolve the
+//! actual material at hit time.
+
+use bif_core::{MaterialId, NO_MATERIAL};
+use std::sync::Arc;
+
+/// Material palette plus the ID arrays that index into it.
+///
+/// Resolution follows `bif_core::Scene::resolve_material`: a face binding
+/// wins over the instance binding, which wins over the default material.
+/// `NO_MATERIAL` (or a missing entry) falls through to the next level.
+#[derive(Clone)]
+pub struct MaterialTable<M: Clone> {
+    /// Material used when nothing else is bound
+    default: M,
+
+    /// Materials referenced by ID (shared between tables)
+    materials: Arc<[M]>,
+
+    /// Material ID for each instance (empty = no per-instance bindings)
+    instance_ids: Vec<MaterialId>,
+
+    /// Material ID for each prototype triangle (None = no per-face bindings)
+    face_ids: Option<Arc<[MaterialId]>>,
+}
+
+impl<M: Clone> MaterialTable<M> {
+    /// Create a table where every hit resolves to `default`.
+    pub fn new(default: M) -> Self {
+        Self {
+            default,
+            materials: Arc::from([]),
+            instance_ids: Vec::new(),
+            face_ids: None,
+        }
+    }
+
+    /// Set the material palette that IDs index into.
+    ///
+    /// Pass an `Arc<[M]>` to share one palette between several tables.
+    pub fn with_materials(mut self, materials: impl Into<Arc<[M]>>) -> Self {
+        self.materials = materials.into();
+        self
+    }
+
+    /// Set one material ID per instance.
+    pub fn with_instance_ids(mut self, ids: Vec<MaterialId>) -> Self {
+        self.instance_ids = ids;
+        self
+    }
+
+    /// Set one material ID per prototype triangle.
+    pub fn with_face_ids(mut self, ids: Arc<[MaterialId]>) -> Self {
+        self.face_ids = Some(ids);
+        self
+    }
+
+    /// Convert every material in the table, keeping the ID arrays.
+    pub fn map<N: Clone>(&self, mut f: impl FnMut(&M) -> N) -> MaterialTable<N> {
+        MaterialTable {
+            default: f(&self.default),
+            materials: self.materials.iter().map(f).collect(),
+            instance_ids: self.instance_ids.clone(),
+            face_ids: self.face_ids.clone(),
+        }
+    }
+
+    /// Iterate over the default material followed by the palette.
+    pub fn iter(&self) -> impl Iterator<Item = &M> {
+        std::iter::once(&self.default).chain(self.materials.iter())
+    }
+
+    /// Number of materials in the palette (excluding the default).
+    pub fn material_count(&self) -> usize {
+        self.materials.len()
+    }
+
+    /// Material used when nothing else is bound.
+    pub fn default_material(&self) -> &M {
+        &self.default
+    }
+
+    /// Raw material ID bound to a prototype triangle (`NO_MATERIAL` if none).
+    pub fn face_id(&self, face: usize) -> MaterialId {
+        self.face_ids
+            .as_ref()
+            .and_then(|ids| ids.get(face).copied())
+            .unwrap_or(NO_MATERIAL)
+    }
+
+    /// Resolve the material for an instance, ignoring face bindings.
+    #[inline]
+    pub fn resolve_instance(&self, instance: usize) -> &M {
+        self.lookup(self.instance_ids.get(instance).copied())
+            .unwrap_or(&self.default)
+    }
+
+    /// Resolve the material for a triangle of an instance.
+    #[inline]
+    pub fn resolve(&self, instance: usize, face: usize) -> &M {
+        let face_id = self
+            .face_ids
+            .as_ref()
+            .and_then(|ids| ids.get(face).copied());
+
+        self.lookup(face_id)
+            .unwrap_or_else(|| self.resolve_instance(instance))
+    }
+
+    #[inline]
+    fn lookup(&self, id: Option<MaterialId>) -> Option<&M> {
+        id.filter(|&id| id != NO_MATERIAL)
+            .and_then(|id| self.materials.get(id as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_only() {
+        let table = MaterialTable::new("default");
+        assert_eq!(*table.resolve(0, 0), "default");
+        assert_eq!(*table.resolve_instance(7), "default");
+    }
+
+    #[test]
+    fn test_precedence() {
+        let table = MaterialTable::new("default")
+            .with_materials(vec!["red", "green"])
+            .with_instance_ids(vec![NO_MATERIAL, 1])
+            .with_face_ids(vec![0, NO_MATERIAL].into());
+
+        // Face binding beats the instance binding
+        assert_eq!(*table.resolve(1, 0), "red");
+        // Unbound face falls back to instance, then default
+        assert_eq!(*table.resolve(1, 1), "green");
+        assert_eq!(*table.resolve(0, 1), "default");
+        // Out-of-range instance / face / ID fall back too
+        assert_eq!(*table.resolve(5, 9), "default");
+    }
+
+    #[test]
+    fn test_invalid_id_falls_back() {
+        let table = MaterialTable::new("default")
+            .with_materials(vec!["red"])
+            .with_instance_ids(vec![3]);
+        assert_eq!(*table.resolve_instance(0), "default");
+    }
+
+    #[test]
+    fn test_map_keeps_bindings() {
+        let table = MaterialTable::new("default")
+            .with_materials(vec!["red"])
+            .with_instance_ids(vec![NO_MATERIAL, 0])
+            .map(|m| m.len());
+        assert_eq!(*table.resolve_instance(0), 7);
+        assert_eq!(*table.resolve_instance(1), 3);
+        assert_eq!(table.iter().copied().collect::<Vec<_>>(), vec![7, 3]);
+    }
+}
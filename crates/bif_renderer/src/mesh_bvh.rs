@@ -0,0 +1,626 @@
+//! Linearized BVH over a contiguous triangle array.
+//!
+//! `BvhNode` boxes every primitive as a `dyn Hittable`, which costs a heap
+//! allocation and a virtual call per triangle. `MeshBvh` stores the tree as
+//! an array of 32-byte nodes in depth-first order and the triangles as plain
+//! data, and traverses with a small fixed stack. Used for mesh prototypes.
+//! Deforming meshes keep one triangle array per motion key and are
+//! interpolated at each ray's time; their nodes bound every key.
+
+use crate::bvh::{items_bounds, partition, BuildItem, Partition, MAX_DEPTH};
+use crate::triangle::{intersect_triangle, triangle_point};
+use crate::{BvhBuildConfig, Ray};
+use bif_math::{motion_segment, Aabb, Interval, Vec3, SLAB_EXIT_SCALE};
+
+/// Traversal stack size (one entry per level of a tree of at most
+/// `MAX_DEPTH` levels).
+const STACK_SIZE: usize = MAX_DEPTH;
+
+/// Flattened BVH node (32 bytes).
+///
+/// Interior nodes store their first child right after themselves and the
+/// second child at `offset`. Leaves store `count` triangles from `offset`.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub(crate) struct FlatNode {
+    pub min: [f32; 3],
+    /// Second child (interior) or first triangle (leaf)
+    pub offset: u32,
+    pub max: [f32; 3],
+    /// Triangle count; 0 for interior nodes
+    pub count: u16,
+    /// Split axis of interior nodes, for front-to-back traversal
+    pub axis: u16,
+}
+
+const _: () = assert!(std::mem::size_of::<FlatNode>() == 32);
+
+impl FlatNode {
+    pub fn new(bbox: &Aabb, offset: u32, count: u16, axis: u16) -> Self {
+        Self {
+            min: [bbox.x.min, bbox.y.min, bbox.z.min],
+            offset,
+            max: [bbox.x.max, bbox.y.max, bbox.z.max],
+            count,
+            axis,
+        }
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        Aabb::from_points(Vec3::from(self.min), Vec3::from(self.max))
+    }
+
+    fn set_bounds(&mut self, bbox: &Aabb) {
+        self.min = [bbox.x.min, bbox.y.min, bbox.z.min];
+        self.max = [bbox.x.max, bbox.y.max, bbox.z.max];
+    }
+
+    /// Slab test against a ray with precomputed inverse direction.
+    #[inline]
+    pub fn hit(&self, origin: Vec3, inv_dir: Vec3, t_min: f32, t_max: f32) -> bool {
+        let t0 = (Vec3::from(self.min) - origin) * inv_dir;
+        let t1 = (Vec3::from(self.max) - origin) * inv_dir;
+        let near = t0.min(t1).max_element().max(t_min);
+        let far = (t0.max(t1).min_element() * SLAB_EXIT_SCALE).min(t_max);
+        near <= far
+    }
+}
+
+/// Triangle stored as its three vertices.
+///
+/// Vertices are kept as given (not as edges), so triangles sharing an edge
+/// see bit-identical coordinates and the watertight test can't crack.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PackedTriangle {
+    vertices: [Vec3; 3],
+}
+
+impl PackedTriangle {
+    fn new(vertices: [Vec3; 3]) -> Self {
+        Self { vertices }
+    }
+
+    /// Watertight intersection, returning (t, u, v).
+    #[inline]
+    pub fn intersect(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        ray_t: Interval,
+    ) -> Option<(f32, f32, f32)> {
+        intersect_triangle(origin, direction, self.vertices, ray_t)
+    }
+
+    /// Hit point at barycentrics `(u, v)` and its error bound.
+    pub fn point(&self, u: f32, v: f32) -> (Vec3, Vec3) {
+        triangle_point(self.vertices, u, v)
+    }
+
+    /// Unit face normal (USD uses CW winding, like `Triangle`).
+    pub fn normal(&self) -> Vec3 {
+        let [v0, v1, v2] = self.vertices;
+        (v2 - v0).cross(v1 - v0).normalize()
+    }
+
+    /// Bounds, padded like the boxes the BVH was built from.
+    pub fn bounds(&self) -> Aabb {
+        let [v0, v1, v2] = self.vertices;
+        Aabb::from_points(v0.min(v1).min(v2), v0.max(v1).max(v2))
+    }
+
+    /// Vertex-wise interpolation towards `other`.
+    #[inline]
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self::new(std::array::from_fn(|i| {
+            self.vertices[i].lerp(other.vertices[i], t)
+        }))
+    }
+}
+
+/// Triangle `index` at ray `time`, interpolated between deformation keys.
+///
+/// `motion` holds the keys after `triangles` (empty for a static mesh).
+#[inline]
+pub(crate) fn triangle_at(
+    triangles: &[PackedTriangle],
+    motion: &[Vec<PackedTriangle>],
+    index: usize,
+    time: f32,
+) -> PackedTriangle {
+    if motion.is_empty() {
+        return triangles[index];
+    }
+    let (segment, t) = motion_segment(time, motion.len() + 1);
+    let key = |k: usize| match k {
+        0 => &triangles[index],
+        _ => &motion[k - 1][index],
+    };
+    key(segment).lerp(key(segment + 1), t)
+}
+
+/// Replace packed triangles with moved `vertices` (indexed by primitive ID).
+pub(crate) fn refit_triangles(
+    triangles: &mut [PackedTriangle],
+    primitive_ids: &[u32],
+    vertices: &[[Vec3; 3]],
+) {
+    assert_eq!(
+        vertices.len(),
+        triangles.len(),
+        "refit needs the triangle count the BVH was built with"
+    );
+    for (triangle, &id) in triangles.iter_mut().zip(primitive_ids) {
+        *triangle = PackedTriangle::new(vertices[id as usize]);
+    }
+}
+
+/// Bounds of a run of packed triangles.
+pub(crate) fn triangles_bounds(triangles: &[PackedTriangle]) -> Aabb {
+    triangles.iter().fold(Aabb::EMPTY, |bbox, triangle| {
+        Aabb::surrounding(&bbox, &triangle.bounds())
+    })
+}
+
+/// Closest hit found by `MeshBvh::intersect`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshHit {
+    /// Ray parameter of the hit
+    pub t: f32,
+    /// Barycentric coordinates
+    pub u: f32,
+    pub v: f32,
+    /// Index of the triangle in the input array
+    pub primitive: u32,
+    /// Unit geometric normal (not flipped towards the ray)
+    pub normal: Vec3,
+    /// Hit point interpolated from the vertices
+    pub p: Vec3,
+    /// Bound on the floating-point error of `p`
+    pub p_error: Vec3,
+}
+
+/// Intermediate tree, built in parallel and then flattened.
+enum BuildNode {
+    Leaf {
+        bbox: Aabb,
+        triangles: Vec<u32>,
+    },
+    Branch {
+        bbox: Aabb,
+        axis: usize,
+        children: Box<[BuildNode; 2]>,
+    },
+}
+
+/// Linearized BVH over a triangle mesh.
+#[derive(Debug, Clone, Default)]
+pub struct MeshBvh {
+    pub(crate) nodes: Vec<FlatNode>,
+    /// Triangles in leaf order
+    pub(crate) triangles: Vec<PackedTriangle>,
+    /// Input index of each triangle in `triangles`
+    pub(crate) primitive_ids: Vec<u32>,
+    /// Further deformation keys, in the same order as `triangles`
+    pub(crate) motion: Vec<Vec<PackedTriangle>>,
+}
+
+impl MeshBvh {
+    /// Build a BVH over triangles given as vertex triples.
+    pub fn new(vertices: &[[Vec3; 3]]) -> Self {
+        Self::with_config(vertices, &BvhBuildConfig::default())
+    }
+
+    /// Build with explicit construction parameters.
+    pub fn with_config(vertices: &[[Vec3; 3]], config: &BvhBuildConfig) -> Self {
+        Self::with_motion_config(vertices, &[], config)
+    }
+
+    /// Build a BVH over a deforming mesh.
+    ///
+    /// `vertices` are the triangles at shutter open and each entry of
+    /// `motion` the same triangles at a later key (see
+    /// `bif_math::motion_segment`). Rays are intersected with the triangles
+    /// interpolated at their time.
+    pub fn with_motion(vertices: &[[Vec3; 3]], motion: &[Vec<[Vec3; 3]>]) -> Self {
+        Self::with_motion_config(vertices, motion, &BvhBuildConfig::default())
+    }
+
+    /// Build a deforming BVH with explicit construction parameters.
+    pub fn with_motion_config(
+        vertices: &[[Vec3; 3]],
+        motion: &[Vec<[Vec3; 3]>],
+        config: &BvhBuildConfig,
+    ) -> Self {
+        assert!(
+            motion.iter().all(|key| key.len() == vertices.len()),
+            "every motion key needs the same triangles"
+        );
+        if vertices.is_empty() {
+            return Self::default();
+        }
+
+        // Leaf sizes are stored in 16 bits
+        let config = BvhBuildConfig {
+            max_leaf_size: config.max_leaf_size.min(u16::MAX as usize),
+            ..*config
+        };
+
+        let items = vertices
+            .iter()
+            .enumerate()
+            .map(|(i, &[v0, v1, v2])| {
+                // Linear motion stays inside the bounds of its keys
+                let bbox = motion.iter().fold(
+                    Aabb::from_points(v0.min(v1).min(v2), v0.max(v1).max(v2)),
+                    |bbox, key| {
+                        let [v0, v1, v2] = key[i];
+                        let key_bbox = Aabb::from_points(v0.min(v1).min(v2), v0.max(v1).max(v2));
+                        Aabb::surrounding(&bbox, &key_bbox)
+                    },
+                );
+                BuildItem::new(i as u32, bbox)
+            })
+            .collect();
+        let root = Self::build(items, &config, 0);
+
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(2 * vertices.len() / config.max_leaf_size.max(1)),
+            triangles: Vec::with_capacity(vertices.len()),
+            primitive_ids: Vec::with_capacity(vertices.len()),
+            motion: vec![Vec::with_capacity(vertices.len()); motion.len()],
+        };
+        bvh.flatten(root, vertices, motion);
+        bvh
+    }
+
+    fn build(items: Vec<BuildItem<u32>>, config: &BvhBuildConfig, depth: usize) -> BuildNode {
+        let bbox = items_bounds(&items);
+        match partition(items, &bbox, config, depth) {
+            Partition::Leaf(items) => BuildNode::Leaf {
+                bbox,
+                triangles: items.into_iter().map(|item| item.object).collect(),
+            },
+            Partition::Split { left, right, axis } => {
+                Self::branch(left, right, axis, bbox, config, depth)
+            }
+        }
+    }
+
+    fn branch(
+        left: Vec<BuildItem<u32>>,
+        right: Vec<BuildItem<u32>>,
+        axis: usize,
+        bbox: Aabb,
+        config: &BvhBuildConfig,
+        depth: usize,
+    ) -> BuildNode {
+        let build = |items| Self::build(items, config, depth + 1);
+        let children = if left.len() + right.len() >= config.parallel_threshold {
+            rayon::join(|| build(left), || build(right))
+        } else {
+            (build(left), build(right))
+        };
+        BuildNode::Branch {
+            bbox,
+            axis,
+            children: Box::new([children.0, children.1]),
+        }
+    }
+
+    /// Append `node` and its subtree in depth-first order.
+    fn flatten(&mut self, node: BuildNode, vertices: &[[Vec3; 3]], motion: &[Vec<[Vec3; 3]>]) {
+        match node {
+            BuildNode::Leaf { bbox, triangles } => {
+                let first = self.triangles.len() as u32;
+                self.nodes
+                    .push(FlatNode::new(&bbox, first, triangles.len() as u16, 0));
+                for id in triangles {
+                    self.triangles
+                        .push(PackedTriangle::new(vertices[id as usize]));
+                    self.primitive_ids.push(id);
+                    for (packed, key) in self.motion.iter_mut().zip(motion) {
+                        packed.push(PackedTriangle::new(key[id as usize]));
+                    }
+                }
+            }
+            BuildNode::Branch {
+                bbox,
+                axis,
+                children,
+            } => {
+                let index = self.nodes.len();
+                self.nodes.push(FlatNode::new(&bbox, 0, 0, axis as u16));
+                let [left, right] = *children;
+                self.flatten(left, vertices, motion);
+                self.nodes[index].offset = self.nodes.len() as u32;
+                self.flatten(right, vertices, motion);
+            }
+        }
+    }
+
+    /// Move the triangles to new vertex positions, keeping the tree.
+    ///
+    /// `vertices` must have the same triangles, in the same order, as the
+    /// mesh the BVH was built from. Node bounds are recomputed bottom-up,
+    /// which is much cheaper than a rebuild but lets tree quality degrade
+    /// under large deformations. Deformation keys are dropped.
+    pub fn refit(&mut self, vertices: &[[Vec3; 3]]) {
+        refit_triangles(&mut self.triangles, &self.primitive_ids, vertices);
+        self.motion.clear();
+
+        // Children are stored after their parent, so refit in reverse
+        for index in (0..self.nodes.len()).rev() {
+            let node = self.nodes[index];
+            let bbox = if node.count > 0 {
+                let first = node.offset as usize;
+                triangles_bounds(&self.triangles[first..first + node.count as usize])
+            } else {
+                Aabb::surrounding(
+                    &self.nodes[index + 1].bounds(),
+                    &self.nodes[node.offset as usize].bounds(),
+                )
+            };
+            self.nodes[index].set_bounds(&bbox);
+        }
+    }
+
+    /// Find the closest triangle hit within `ray_t`.
+    ///
+    /// `accept(primitive, u, v, t)` is called for every candidate hit and can
+    /// reject it (e.g. for alpha tests); rejected hits don't occlude.
+    pub fn intersect(
+        &self,
+        ray: &Ray,
+        ray_t: Interval,
+        mut accept: impl FnMut(u32, f32, f32, f32) -> bool,
+    ) -> Option<MeshHit> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let origin = ray.origin();
+        let direction = ray.direction();
+        let inv_dir = direction.recip();
+        let negative = [direction.x < 0.0, direction.y < 0.0, direction.z < 0.0];
+
+        let mut closest = ray_t.max;
+        let mut best: Option<(u32, f32, f32)> = None;
+
+        let mut stack = [0u32; STACK_SIZE];
+        let mut stack_len = 0;
+        let mut index = 0;
+        loop {
+            let node = &self.nodes[index];
+            if node.hit(origin, inv_dir, ray_t.min, closest) {
+                if node.count > 0 {
+                    let first = node.offset as usize;
+                    for i in first..first + node.count as usize {
+                        let interval = Interval::new(ray_t.min, closest);
+                        let triangle = triangle_at(&self.triangles, &self.motion, i, ray.time());
+                        let Some((t, u, v)) = triangle.intersect(origin, direction, interval)
+                        else {
+                            continue;
+                        };
+                        if accept(self.primitive_ids[i], u, v, t) {
+                            closest = t;
+                            best = Some((i as u32, u, v));
+                        }
+                    }
+                } else {
+                    // Visit the child on the ray's side of the split first
+                    let (near, far) = if negative[node.axis as usize] {
+                        (node.offset as usize, index + 1)
+                    } else {
+                        (index + 1, node.offset as usize)
+                    };
+                    debug_assert!(stack_len < STACK_SIZE, "MeshBvh stack overflow");
+                    stack[stack_len] = far as u32;
+                    stack_len += 1;
+                    index = near;
+                    continue;
+                }
+            }
+
+            if stack_len == 0 {
+                break;
+            }
+            stack_len -= 1;
+            index = stack[stack_len] as usize;
+        }
+
+        best.map(|(i, u, v)| {
+            let triangle = triangle_at(&self.triangles, &self.motion, i as usize, ray.time());
+            let (p, p_error) = triangle.point(u, v);
+            MeshHit {
+                t: closest,
+                u,
+                v,
+                primitive: self.primitive_ids[i as usize],
+                normal: triangle.normal(),
+                p,
+                p_error,
+            }
+        })
+    }
+
+    /// Bounds of the whole mesh.
+    pub fn bounding_box(&self) -> Aabb {
+        self.nodes.first().map_or(Aabb::EMPTY, FlatNode::bounds)
+    }
+
+    /// Number of triangles.
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Number of flattened nodes.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Approximate heap memory used by the BVH, in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.nodes.len() * std::mem::size_of::<FlatNode>()
+            + (self.motion.len() + 1) * self.triangles.len() * std::mem::size_of::<PackedTriangle>()
+            + self.primitive_ids.len() * std::mem::size_of::<u32>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Color;
+    use crate::{BvhNode, HitRecord, Hittable, Lambertian, Triangle};
+    use rand::{Rng, SeedableRng};
+
+    fn random_triangles(count: usize, seed: u64) -> Vec<[Vec3; 3]> {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let mut point = || Vec3::new(rng.gen(), rng.gen(), rng.gen());
+        (0..count)
+            .map(|_| {
+                let center = point() * 10.0;
+                [center, center + point(), center + point()]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_node_size() {
+        assert_eq!(std::mem::size_of::<FlatNode>(), 32);
+    }
+
+    #[test]
+    fn test_matches_boxed_bvh() {
+        let triangles = random_triangles(2000, 3);
+        let mesh = MeshBvh::new(&triangles);
+        assert_eq!(mesh.triangle_count(), 2000);
+
+        let boxed = BvhNode::new(
+            triangles
+                .iter()
+                .map(|&[v0, v1, v2]| {
+                    Box::new(Triangle::new(v0, v1, v2, Lambertian::new(Color::ONE)))
+                        as Box<dyn Hittable + Send + Sync>
+                })
+                .collect(),
+        );
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let mut hits = 0;
+        for _ in 0..500 {
+            let origin = Vec3::new(rng.gen(), rng.gen(), rng.gen()) * 10.0 - Vec3::splat(5.0);
+            let target = Vec3::new(rng.gen(), rng.gen(), rng.gen()) * 10.0;
+            let ray = Ray::new(origin, target - origin, 0.0);
+            let range = Interval::new(0.001, f32::INFINITY);
+
+            let mut rec = HitRecord::default();
+            let expected = boxed.hit(&ray, range, &mut rec).then_some(rec.t);
+            let actual = mesh.intersect(&ray, range, |_, _, _, _| true).map(|h| h.t);
+            match (expected, actual) {
+                (Some(a), Some(b)) => {
+                    hits += 1;
+                    assert!((a - b).abs() < 1e-4, "{a} != {b}");
+                }
+                (None, None) => {}
+                _ => panic!("mismatch: {expected:?} vs {actual:?}"),
+            }
+        }
+        assert!(hits > 100);
+    }
+
+    #[test]
+    fn test_primitive_ids_and_filter() {
+        // Two parallel triangles; the front one is index 1
+        let triangles = [
+            [
+                Vec3::new(-1.0, -1.0, -3.0),
+                Vec3::new(1.0, -1.0, -3.0),
+                Vec3::new(0.0, 1.0, -3.0),
+            ],
+            [
+                Vec3::new(-1.0, -1.0, -1.0),
+                Vec3::new(1.0, -1.0, -1.0),
+                Vec3::new(0.0, 1.0, -1.0),
+            ],
+        ];
+        let mesh = MeshBvh::new(&triangles);
+        let ray = Ray::new(Vec3::ZERO, Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let range = Interval::new(0.001, f32::INFINITY);
+
+        let hit = mesh.intersect(&ray, range, |_, _, _, _| true).unwrap();
+        assert_eq!(hit.primitive, 1);
+        assert!((hit.t - 1.0).abs() < 1e-5);
+        assert!((hit.normal - Vec3::NEG_Z).length() < 1e-5);
+
+        // Rejecting the front triangle reveals the back one
+        let hit = mesh.intersect(&ray, range, |id, _, _, _| id != 1).unwrap();
+        assert_eq!(hit.primitive, 0);
+        assert!((hit.t - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_watertight_shared_edges() {
+        // Irregular grid of triangles; rays through its vertices and edges
+        // must hit some triangle at any scale
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        for scale in [1e-3f32, 1.0, 1e4] {
+            let n = 8;
+            let offset = Vec3::new(3.7, -1.3, 2.9) * scale;
+            let mut grid = vec![Vec3::ZERO; (n + 1) * (n + 1)];
+            for j in 0..=n {
+                for i in 0..=n {
+                    let jitter = Vec3::new(rng.gen(), rng.gen(), rng.gen()) * 0.3;
+                    let p = Vec3::new(i as f32, j as f32, 0.0) + jitter;
+                    grid[j * (n + 1) + i] = p * scale + offset;
+                }
+            }
+            let vertex = |i: usize, j: usize| grid[j * (n + 1) + i];
+            let mut triangles = Vec::new();
+            for j in 0..n {
+                for i in 0..n {
+                    let [a, b, c, d] = [
+                        vertex(i, j),
+                        vertex(i + 1, j),
+                        vertex(i + 1, j + 1),
+                        vertex(i, j + 1),
+                    ];
+                    triangles.push([a, b, c]);
+                    triangles.push([a, c, d]);
+                }
+            }
+            let mesh = MeshBvh::new(&triangles);
+
+            // Interior vertices and the midpoints of their edges
+            let mut targets = Vec::new();
+            for j in 1..n {
+                for i in 1..n {
+                    let v = vertex(i, j);
+                    targets.push(v);
+                    targets.push(0.5 * (v + vertex(i + 1, j)));
+                    targets.push(0.5 * (v + vertex(i, j + 1)));
+                    targets.push(0.5 * (v + vertex(i + 1, j + 1)));
+                }
+            }
+            for target in targets {
+                for _ in 0..4 {
+                    let dir = Vec3::new(rng.gen(), rng.gen(), rng.gen()) - Vec3::splat(0.5);
+                    let origin = target + (dir + Vec3::Z) * scale * 3.0;
+                    let ray = Ray::new(origin, target - origin, 0.0);
+                    let hit =
+                        mesh.intersect(&ray, Interval::new(0.0, f32::INFINITY), |_, _, _, _| true);
+                    assert!(hit.is_some(), "crack at scale {scale}: {target:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_mesh() {
+        let mesh = MeshBvh::new(&[]);
+        let ray = Ray::new(Vec3::ZERO, Vec3::X, 0.0);
+        assert!(mesh
+            .intersect(&ray, Interval::new(0.0, f32::INFINITY), |_, _, _, _| true)
+            .is_none());
+        assert_eq!(mesh.node_count(), 0);
+    }
+}
@@ -0,0 +1,98 @@
+//! Infinite plane primitive for ray tracing.
+
+use crate::{
+    hittable::{alpha_test, HitRecord, Hittable},
+    material::build_tangent_basis,
+    ray::ray_point_error,
+    Material, Ray,
+};
+use bif_math::{Aabb, Interval, Vec3};
+
+/// An infinite plane, e.g. a ground plane.
+///
+/// Its bounding box is unbounded, so add it to a `HittableList` next to
+/// the BVH rather than into one. Texture coordinates are distances from
+/// `point` along the plane's tangent frame, so textures repeat once per
+/// world unit; the plane has no finite area and can't be an area light.
+pub struct Plane<M: Material> {
+    point: Vec3,
+    normal: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    material: M,
+}
+
+impl<M: Material> Plane<M> {
+    /// Create a plane through `point` facing `normal`.
+    pub fn new(point: Vec3, normal: Vec3, material: M) -> Self {
+        let normal = normal.normalize();
+        let (tangent, bitangent) = build_tangent_basis(normal);
+        Self {
+            point,
+            normal,
+            tangent,
+            bitangent,
+            material,
+        }
+    }
+}
+
+impl<M: Material + 'static> Hittable for Plane<M> {
+    fn hit<'a>(&'a self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord<'a>) -> bool {
+        let denom = self.normal.dot(ray.direction());
+        if denom.abs() < 1e-8 {
+            return false;
+        }
+
+        let t = self.normal.dot(self.point - ray.origin()) / denom;
+        if !ray_t.surrounds(t) {
+            return false;
+        }
+
+        let p = ray.at(t);
+        let local = p - self.point;
+        let (u, v) = (local.dot(self.tangent), local.dot(self.bitangent));
+        if !alpha_test(
+            self.material.opacity(u, v),
+            ray.origin(),
+            ray.direction(),
+            t,
+        ) {
+            return false;
+        }
+
+        rec.t = t;
+        rec.p = p;
+        rec.p_error = ray_point_error(ray, t);
+        rec.set_face_normal(ray, self.normal);
+        (rec.u, rec.v) = (u, v);
+        rec.material = &self.material;
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::UNIVERSE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    #[test]
+    fn test_plane_hit_far_away() {
+        let plane = Plane::new(Vec3::ZERO, Vec3::Y, Lambertian::new(Vec3::ONE));
+        let interval = Interval::new(0.001, f32::INFINITY);
+        let mut rec = HitRecord::default();
+
+        let ray = Ray::new_simple(Vec3::new(1000.0, 2.0, -500.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(plane.hit(&ray, interval, &mut rec));
+        assert!((rec.t - 2.0).abs() < 1e-4);
+        assert!(rec.front_face);
+
+        // Parallel rays never hit
+        let ray = Ray::new_simple(Vec3::new(0.0, 1.0, 0.0), Vec3::X);
+        assert!(!plane.hit(&ray, interval, &mut rec));
+    }
+}
@@ -0,0 +1,192 @@
+//! Point primitive (particles) for ray tracing.
+//!
+//! A point cloud is traced as one sphere per point. Centers and radii are
+//! kept in flat arrays (a single radius when all points share it) under a
+//! `PrimitiveBvh`, so millions of particles cost a few dozen bytes each
+//! instead of a boxed `Sphere` apiece.
+
+use crate::{
+    hittable::{HitRecord, Hittable},
+    primitive_bvh::PrimitiveBvh,
+    ray::sphere_point,
+    Material, Ray,
+};
+use bif_core::Points;
+use bif_math::{Aabb, Interval, Mat4, Vec3};
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+/// A point cloud in world space with its own BVH and material.
+///
+/// Shared between the pure-Rust worlds (as a top-level leaf) and
+/// `EmbreeScene` (which intersects the spheres itself and uses
+/// [`PointGeometry::fill_record`] for shading).
+pub struct PointGeometry {
+    centers: Vec<Vec3>,
+    /// Radius per point, or a single shared radius
+    radii: Vec<f32>,
+    bvh: PrimitiveBvh,
+    material: Arc<dyn Material>,
+}
+
+impl PointGeometry {
+    /// Place `points` in the world with `transform`.
+    ///
+    /// Radii are scaled by the transform's average scale factor.
+    pub fn new(points: &Points, transform: Mat4, material: Arc<dyn Material>) -> Self {
+        let radius_scale = transform.determinant().abs().cbrt();
+        let centers: Vec<Vec3> = points
+            .positions
+            .iter()
+            .map(|&p| transform.transform_point3(p))
+            .collect();
+        let radii: Vec<f32> = points.radii.iter().map(|r| r * radius_scale).collect();
+
+        let mut geometry = Self {
+            centers,
+            radii,
+            bvh: PrimitiveBvh::default(),
+            material,
+        };
+        let bounds: Vec<Aabb> = (0..geometry.len()).map(|i| geometry.bounds(i)).collect();
+        geometry.bvh = PrimitiveBvh::new(&bounds);
+        log::debug!(
+            "Points: {} points, {} nodes",
+            geometry.len(),
+            geometry.bvh.node_count()
+        );
+        geometry
+    }
+
+    /// Number of points.
+    pub fn len(&self) -> usize {
+        self.centers.len()
+    }
+
+    /// True if there are no points.
+    pub fn is_empty(&self) -> bool {
+        self.centers.is_empty()
+    }
+
+    /// World-space center of point `index`.
+    pub fn center(&self, index: usize) -> Vec3 {
+        self.centers[index]
+    }
+
+    /// World-space radius of point `index`.
+    #[inline]
+    pub fn radius(&self, index: usize) -> f32 {
+        match self.radii.as_slice() {
+            [radius] => *radius,
+            radii => radii[index],
+        }
+    }
+
+    fn bounds(&self, index: usize) -> Aabb {
+        let r = Vec3::splat(self.radius(index));
+        Aabb::from_points(self.centers[index] - r, self.centers[index] + r)
+    }
+
+    /// Closest hit of `ray` with point `index` within `ray_t`.
+    fn intersect_point(&self, ray: &Ray, index: usize, ray_t: Interval) -> Option<f32> {
+        let radius = self.radius(index);
+        let oc = self.centers[index] - ray.origin();
+        let a = ray.direction().length_squared();
+        let h = ray.direction().dot(oc);
+        let c = oc.length_squared() - radius * radius;
+
+        let discriminant = h * h - a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let sqrtd = discriminant.sqrt();
+        [(h - sqrtd) / a, (h + sqrtd) / a]
+            .into_iter()
+            .find(|&root| ray_t.surrounds(root))
+    }
+
+    /// Fill `rec` for a hit on point `index` at ray `t`.
+    ///
+    /// UVs are spherical coordinates, as for `Sphere`.
+    pub fn fill_record<'a>(&'a self, ray: &Ray, index: usize, t: f32, rec: &mut HitRecord<'a>) {
+        let (p, p_error) = sphere_point(ray.at(t), self.centers[index], self.radius(index));
+        let normal = (p - self.centers[index]).try_normalize().unwrap_or(Vec3::Y);
+
+        rec.t = t;
+        rec.p = p;
+        rec.p_error = p_error;
+        rec.set_face_normal(ray, normal);
+        rec.u = ((-normal.z).atan2(normal.x) + PI) / (2.0 * PI);
+        rec.v = (-normal.y).clamp(-1.0, 1.0).acos() / PI;
+        rec.tangent = Vec3::ZERO;
+        rec.instance = None;
+        rec.material = self.material.as_ref();
+    }
+}
+
+impl Hittable for PointGeometry {
+    fn hit<'a>(&'a self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord<'a>) -> bool {
+        let hit = self.bvh.intersect(ray, ray_t, |index, interval| {
+            self.intersect_point(ray, index as usize, interval)
+                .map(|t| (t, ()))
+        });
+        match hit {
+            Some((t, index, ())) => {
+                self.fill_record(ray, index as usize, t, rec);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bvh.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, Lambertian};
+
+    fn grid(spacing: f32, width: f32) -> PointGeometry {
+        let positions = (0..100)
+            .map(|i| Vec3::new((i % 10) as f32 * spacing, (i / 10) as f32 * spacing, 0.0))
+            .collect();
+        PointGeometry::new(
+            &Points::new(positions, &[width]),
+            Mat4::IDENTITY,
+            Arc::new(Lambertian::new(Color::ONE)),
+        )
+    }
+
+    #[test]
+    fn test_hit_nearest_point() {
+        let points = grid(1.0, 0.5);
+        assert_eq!(points.len(), 100);
+
+        let ray = Ray::new(Vec3::new(3.0, 7.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(points.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.t - 4.75).abs() < 1e-4);
+        assert!((rec.normal - Vec3::Z).length() < 1e-4);
+
+        // Between points
+        let ray = Ray::new(Vec3::new(3.5, 7.5, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert!(!points.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+    }
+
+    #[test]
+    fn test_transform_scales_radii() {
+        let points = Points::new(vec![Vec3::ZERO, Vec3::X], &[1.0, 2.0]);
+        let geometry = PointGeometry::new(
+            &points,
+            Mat4::from_scale(Vec3::splat(2.0)),
+            Arc::new(Lambertian::new(Color::ONE)),
+        );
+        assert_eq!(geometry.center(1), Vec3::new(2.0, 0.0, 0.0));
+        assert_eq!(geometry.radius(1), 2.0);
+        assert_eq!(geometry.bounding_box().x.max, 4.0);
+    }
+}
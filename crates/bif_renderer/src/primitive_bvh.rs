@@ -0,0 +1,185 @@
+//! Linearized BVH over primitives known only by their bounds.
+//!
+//! Same node layout and traversal as `MeshBvh`, but leaves store primitive
+//! indices and the caller intersects them, so non-triangle primitives (curve
+//! segments) get a flat tree without boxing each one as a `dyn Hittable`.
+
+use crate::bvh::{items_bounds, partition, BuildItem, Partition, MAX_DEPTH};
+use crate::mesh_bvh::FlatNode;
+use crate::{BvhBuildConfig, Ray};
+use bif_math::{Aabb, Interval};
+
+/// Traversal stack size (one entry per level of a tree of at most
+/// `MAX_DEPTH` levels).
+const STACK_SIZE: usize = MAX_DEPTH;
+
+/// Intermediate tree, built in parallel and then flattened.
+enum BuildNode {
+    Leaf {
+        bbox: Aabb,
+        primitives: Vec<u32>,
+    },
+    Branch {
+        bbox: Aabb,
+        axis: usize,
+        children: Box<[BuildNode; 2]>,
+    },
+}
+
+/// Linearized BVH whose leaves index into a caller-owned primitive array.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PrimitiveBvh {
+    nodes: Vec<FlatNode>,
+    /// Input index of each primitive, in leaf order
+    primitive_ids: Vec<u32>,
+}
+
+impl PrimitiveBvh {
+    /// Build a BVH over primitives given by their bounds.
+    pub fn new(bounds: &[Aabb]) -> Self {
+        if bounds.is_empty() {
+            return Self::default();
+        }
+
+        // Leaf sizes are stored in 16 bits
+        let defaults = BvhBuildConfig::default();
+        let config = BvhBuildConfig {
+            max_leaf_size: defaults.max_leaf_size.min(u16::MAX as usize),
+            ..defaults
+        };
+
+        let items = bounds
+            .iter()
+            .enumerate()
+            .map(|(i, &bbox)| BuildItem::new(i as u32, bbox))
+            .collect();
+        let root = Self::build(items, &config, 0);
+
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(2 * bounds.len() / config.max_leaf_size.max(1)),
+            primitive_ids: Vec::with_capacity(bounds.len()),
+        };
+        bvh.flatten(root);
+        bvh
+    }
+
+    fn build(items: Vec<BuildItem<u32>>, config: &BvhBuildConfig, depth: usize) -> BuildNode {
+        let bbox = items_bounds(&items);
+        match partition(items, &bbox, config, depth) {
+            Partition::Leaf(items) => BuildNode::Leaf {
+                bbox,
+                primitives: items.into_iter().map(|item| item.object).collect(),
+            },
+            Partition::Split { left, right, axis } => {
+                let build = |items| Self::build(items, config, depth + 1);
+                let children = if left.len() + right.len() >= config.parallel_threshold {
+                    rayon::join(|| build(left), || build(right))
+                } else {
+                    (build(left), build(right))
+                };
+                BuildNode::Branch {
+                    bbox,
+                    axis,
+                    children: Box::new([children.0, children.1]),
+                }
+            }
+        }
+    }
+
+    /// Append `node` and its subtree in depth-first order.
+    fn flatten(&mut self, node: BuildNode) {
+        match node {
+            BuildNode::Leaf { bbox, primitives } => {
+                let first = self.primitive_ids.len() as u32;
+                self.nodes
+                    .push(FlatNode::new(&bbox, first, primitives.len() as u16, 0));
+                self.primitive_ids.extend(primitives);
+            }
+            BuildNode::Branch {
+                bbox,
+                axis,
+                children,
+            } => {
+                let index = self.nodes.len();
+                self.nodes.push(FlatNode::new(&bbox, 0, 0, axis as u16));
+                let [left, right] = *children;
+                self.flatten(left);
+                self.nodes[index].offset = self.nodes.len() as u32;
+                self.flatten(right);
+            }
+        }
+    }
+
+    /// Find the closest primitive hit within `ray_t`.
+    ///
+    /// `intersect(primitive, interval)` tests one primitive against the
+    /// still-open interval and returns the hit distance with any data the
+    /// caller needs to shade it. Returns the closest (t, primitive, data).
+    pub fn intersect<H>(
+        &self,
+        ray: &Ray,
+        ray_t: Interval,
+        mut intersect: impl FnMut(u32, Interval) -> Option<(f32, H)>,
+    ) -> Option<(f32, u32, H)> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let origin = ray.origin();
+        let direction = ray.direction();
+        let inv_dir = direction.recip();
+        let negative = [direction.x < 0.0, direction.y < 0.0, direction.z < 0.0];
+
+        let mut closest = ray_t.max;
+        let mut best = None;
+
+        let mut stack = [0u32; STACK_SIZE];
+        let mut stack_len = 0;
+        let mut index = 0;
+        loop {
+            let node = &self.nodes[index];
+            if node.hit(origin, inv_dir, ray_t.min, closest) {
+                if node.count > 0 {
+                    let first = node.offset as usize;
+                    for &id in &self.primitive_ids[first..first + node.count as usize] {
+                        let interval = Interval::new(ray_t.min, closest);
+                        if let Some((t, data)) = intersect(id, interval) {
+                            closest = t;
+                            best = Some((id, data));
+                        }
+                    }
+                } else {
+                    // Visit the child on the ray's side of the split first
+                    let (near, far) = if negative[node.axis as usize] {
+                        (node.offset as usize, index + 1)
+                    } else {
+                        (index + 1, node.offset as usize)
+                    };
+                    debug_assert!(stack_len < STACK_SIZE, "PrimitiveBvh stack overflow");
+                    stack[stack_len] = far as u32;
+                    stack_len += 1;
+                    index = near;
+                    continue;
+                }
+            }
+
+            if stack_len == 0 {
+                break;
+            }
+            stack_len -= 1;
+            index = stack[stack_len] as usize;
+        }
+
+        best.map(|(id, data)| (closest, id, data))
+    }
+
+    /// Bounds of all primitives.
+    pub fn bounding_box(&self) -> Aabb {
+        self.nodes.first().map_or(Aabb::EMPTY, FlatNode::bounds)
+    }
+
+    /// Number of flattened nodes.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
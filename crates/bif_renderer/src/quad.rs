@@ -0,0 +1,169 @@
+//! Parallelogram primitive for ray tracing.
+
+use crate::{
+    hittable::{alpha_test, HitRecord, Hittable},
+    ray::ray_point_error,
+    AreaLight, Material, Ray, SurfacePoint,
+};
+use bif_math::{Aabb, Interval, Vec3};
+
+/// A parallelogram spanned by two edges from a corner.
+///
+/// Points are `q + u * edge_u + v * edge_v` for (u, v) in [0, 1]², and
+/// (u, v) are the texture coordinates. The front face is on the side of
+/// `edge_u × edge_v`.
+pub struct Quad<M: Material> {
+    q: Vec3,
+    edge_u: Vec3,
+    edge_v: Vec3,
+    /// `n / (n · n)` for the unnormalized normal, used to solve for (u, v)
+    w: Vec3,
+    normal: Vec3,
+    /// Plane constant: `normal · p` for points on the quad
+    d: f32,
+    area: f32,
+    material: M,
+    bbox: Aabb,
+}
+
+impl<M: Material> Quad<M> {
+    /// Create a quad with corner `q` and edges `edge_u`, `edge_v`.
+    pub fn new(q: Vec3, edge_u: Vec3, edge_v: Vec3, material: M) -> Self {
+        let n = edge_u.cross(edge_v);
+        let area = n.length();
+        let normal = n.normalize_or_zero();
+        let w = if area > 0.0 { n / n.dot(n) } else { Vec3::ZERO };
+
+        let bbox = Aabb::surrounding(
+            &Aabb::from_points(q, q + edge_u + edge_v),
+            &Aabb::from_points(q + edge_u, q + edge_v),
+        );
+
+        Self {
+            q,
+            edge_u,
+            edge_v,
+            w,
+            normal,
+            d: normal.dot(q),
+            area,
+            material,
+            bbox,
+        }
+    }
+
+    /// Unit normal on the front side.
+    pub fn normal(&self) -> Vec3 {
+        self.normal
+    }
+}
+
+impl<M: Material + 'static> Hittable for Quad<M> {
+    fn hit<'a>(&'a self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord<'a>) -> bool {
+        let denom = self.normal.dot(ray.direction());
+        if denom.abs() < 1e-8 {
+            return false;
+        }
+
+        let t = (self.d - self.normal.dot(ray.origin())) / denom;
+        if !ray_t.surrounds(t) {
+            return false;
+        }
+
+        // Planar coordinates of the hit point along the two edges
+        let p = ray.at(t);
+        let planar = p - self.q;
+        let u = self.w.dot(planar.cross(self.edge_v));
+        let v = self.w.dot(self.edge_u.cross(planar));
+        if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
+            return false;
+        }
+
+        if !alpha_test(
+            self.material.opacity(u, v),
+            ray.origin(),
+            ray.direction(),
+            t,
+        ) {
+            return false;
+        }
+
+        rec.t = t;
+        rec.p = p;
+        rec.p_error = ray_point_error(ray, t);
+        rec.set_face_normal(ray, self.normal);
+        (rec.u, rec.v) = (u, v);
+        rec.material = &self.material;
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+impl<M: Material + 'static> AreaLight for Quad<M> {
+    fn area(&self) -> f32 {
+        self.area
+    }
+
+    fn sample_area(&self, s: f32, t: f32) -> SurfacePoint {
+        SurfacePoint {
+            p: self.q + s * self.edge_u + t * self.edge_v,
+            normal: self.normal,
+            u: s,
+            v: t,
+        }
+    }
+
+    fn material(&self) -> &dyn Material {
+        &self.material
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    fn unit_quad() -> Quad<Lambertian> {
+        Quad::new(
+            Vec3::new(-1.0, -1.0, -2.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 4.0, 0.0),
+            Lambertian::new(Vec3::splat(0.5)),
+        )
+    }
+
+    #[test]
+    fn test_quad_hit_uv() {
+        let quad = unit_quad();
+        let ray = Ray::new_simple(Vec3::new(0.5, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let mut rec = HitRecord::default();
+
+        assert!(quad.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.t - 2.0).abs() < 1e-5);
+        assert!((rec.u - 0.75).abs() < 1e-5);
+        assert!((rec.v - 0.25).abs() < 1e-5);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn test_quad_miss_outside_edges() {
+        let quad = unit_quad();
+        let ray = Ray::new_simple(Vec3::new(1.5, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let mut rec = HitRecord::default();
+
+        assert!(!quad.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+    }
+
+    #[test]
+    fn test_quad_sampling() {
+        let quad = unit_quad();
+        assert!((quad.area() - 8.0).abs() < 1e-5);
+
+        let point = quad.sample_area(0.75, 0.25);
+        assert!((point.p - Vec3::new(0.5, 0.0, -2.0)).length() < 1e-5);
+        assert_eq!((point.u, point.v), (0.75, 0.25));
+    }
+}
@@ -1,9 +1,14 @@
 //! Ray type for path tracing.
 //!
 //! A ray is defined by an origin point, a direction vector, and a time value
-//! for motion blur support.
+//! for motion blur support. It also carries its ray type, which geometry
+//! checks against its visibility mask.
+//!
+//! Also holds the floating-point error helpers used to start secondary rays
+//! on the correct side of a surface (see [`offset_ray_origin`]).
 
-use bif_math::Vec3;
+use bif_core::Visibility;
+use bif_math::{Mat4, Vec3};
 
 /// A ray with origin, direction, and time.
 #[derive(Debug, Clone, Copy)]
@@ -14,19 +19,30 @@ pub struct Ray {
     direction: Vec3,
     /// Time value for motion blur
     time: f32,
+    /// Ray type: one [`Visibility`] flag (random walks inside a medium use
+    /// `ALL` to find their way out)
+    visibility: Visibility,
 }
 
 impl Ray {
-    /// Create a new ray.
+    /// Create a new camera ray.
     #[inline]
     pub fn new(origin: Vec3, direction: Vec3, time: f32) -> Self {
         Self {
             origin,
             direction,
             time,
+            visibility: Visibility::CAMERA,
         }
     }
 
+    /// Set the ray type (camera, shadow, diffuse, specular or transmission).
+    #[inline]
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
     /// Create a ray at time 0.
     #[inline]
     pub fn new_simple(origin: Vec3, direction: Vec3) -> Self {
@@ -51,6 +67,18 @@ impl Ray {
         self.time
     }
 
+    /// Get the ray's type.
+    #[inline]
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    /// True if geometry visible to `mask` can be hit by this ray.
+    #[inline]
+    pub fn sees(&self, mask: Visibility) -> bool {
+        mask.intersects(self.visibility)
+    }
+
     /// Compute a point along the ray at parameter t.
     /// P(t) = origin + t * direction
     #[inline]
@@ -65,8 +93,109 @@ impl Default for Ray {
             origin: Vec3::ZERO,
             direction: Vec3::Z,
             time: 0.0,
+            visibility: Visibility::CAMERA,
+        }
+    }
+}
+
+/// Half an ULP at 1.0: the relative error of one rounded f32 operation.
+pub const MACHINE_EPSILON: f32 = f32::EPSILON * 0.5;
+
+/// Bound on the relative error accumulated by `n` rounded operations.
+#[inline]
+pub const fn gamma(n: u32) -> f32 {
+    let n = n as f32 * MACHINE_EPSILON;
+    n / (1.0 - n)
+}
+
+/// Smallest f32 greater than `v`.
+#[inline]
+pub fn next_float_up(v: f32) -> f32 {
+    if v.is_infinite() && v > 0.0 {
+        return v;
+    }
+    // Skip -0.0 so that stepping up from zero is positive
+    let v = if v == -0.0 { 0.0 } else { v };
+    let bits = v.to_bits();
+    f32::from_bits(if v >= 0.0 { bits + 1 } else { bits - 1 })
+}
+
+/// Largest f32 less than `v`.
+#[inline]
+pub fn next_float_down(v: f32) -> f32 {
+    if v.is_infinite() && v < 0.0 {
+        return v;
+    }
+    let v = if v == 0.0 { -0.0 } else { v };
+    let bits = v.to_bits();
+    f32::from_bits(if v > 0.0 { bits - 1 } else { bits + 1 })
+}
+
+/// Error bound for a hit point computed as `ray.at(t)`.
+///
+/// For analytic surfaces whose `t` comes from a closed-form solve; meshes
+/// compute tighter bounds from barycentrics instead.
+#[inline]
+pub fn ray_point_error(ray: &Ray, t: f32) -> Vec3 {
+    (ray.origin().abs() + (t * ray.direction()).abs()) * gamma(RAY_POINT_ERROR_OPS)
+}
+
+/// Operations assumed by [`ray_point_error`]: the solve for `t` plus `at()`.
+const RAY_POINT_ERROR_OPS: u32 = 32;
+
+/// Project a ray hit `p` onto a sphere and bound the result's error.
+///
+/// The quadratic's `t` can be far off near silhouettes; reprojecting makes
+/// the point accurate to a few ULPs regardless.
+pub fn sphere_point(p: Vec3, center: Vec3, radius: f32) -> (Vec3, Vec3) {
+    let local = p - center;
+    let length = local.length();
+    if length == 0.0 {
+        return (p, Vec3::splat(radius) * gamma(5));
+    }
+    let local = local * (radius / length);
+    (center + local, (center.abs() + local.abs()) * gamma(6))
+}
+
+/// Transform a point and its error bound by an affine matrix.
+///
+/// The bound grows by the rounding of the matrix-vector product and by the
+/// transformed input error.
+pub fn transform_point_with_error(m: &Mat4, p: Vec3, p_error: Vec3) -> (Vec3, Vec3) {
+    let abs = Mat4::from_cols(
+        m.x_axis.abs(),
+        m.y_axis.abs(),
+        m.z_axis.abs(),
+        m.w_axis.abs(),
+    );
+    let error = gamma(3) * abs.transform_point3(p.abs())
+        + (1.0 + gamma(3)) * abs.transform_vector3(p_error);
+    (m.transform_point3(p), error)
+}
+
+/// Origin for a ray leaving a surface point `p` in direction `w`.
+///
+/// `p` is only known to lie within `p_error` of the true surface, so the
+/// origin is pushed along the geometric normal `n` just past that error box,
+/// on the side `w` points to, and then rounded away from `p`. The spawned
+/// ray can't re-hit the surface it starts on, however large or small the
+/// scene, and needs no `t` epsilon.
+pub fn offset_ray_origin(p: Vec3, p_error: Vec3, n: Vec3, w: Vec3) -> Vec3 {
+    let d = n.abs().dot(p_error);
+    let mut offset = d * n;
+    if w.dot(n) < 0.0 {
+        offset = -offset;
+    }
+
+    let mut po = p + offset;
+    for i in 0..3 {
+        if offset[i] > 0.0 {
+            po[i] = next_float_up(po[i]);
+        } else if offset[i] < 0.0 {
+            po[i] = next_float_down(po[i]);
         }
     }
+    po
 }
 
 #[cfg(test)]
@@ -92,4 +221,30 @@ mod tests {
         assert_eq!(ray.direction(), direction);
         assert_eq!(ray.time(), 0.5);
     }
+
+    #[test]
+    fn test_next_float() {
+        assert!(next_float_up(1.0) > 1.0);
+        assert_eq!(next_float_down(next_float_up(1.0)), 1.0);
+        assert!(next_float_up(0.0) > 0.0);
+        assert!(next_float_up(-0.0) > 0.0);
+        assert!(next_float_down(0.0) < 0.0);
+        assert_eq!(next_float_up(-1.0), -next_float_down(1.0));
+    }
+
+    #[test]
+    fn test_offset_ray_origin_leaves_error_box() {
+        for scale in [1e-3f32, 1.0, 1e4] {
+            let p = Vec3::new(0.3, 0.0, -0.7) * scale;
+            let p_error = Vec3::splat(gamma(7) * scale);
+            let n = Vec3::Y;
+
+            let above = offset_ray_origin(p, p_error, n, Vec3::new(0.2, 1.0, 0.0));
+            assert!(above.y > p_error.y, "scale {}: {:?}", scale, above);
+            assert_eq!(above.x, p.x);
+
+            let below = offset_ray_origin(p, p_error, n, Vec3::new(0.0, -1.0, 0.3));
+            assert!(below.y < -p_error.y, "scale {}: {:?}", scale, below);
+        }
+    }
 }
@@ -2,12 +2,17 @@
 //!
 //! Implements Monte Carlo path tracing with:
 //! - Recursive ray tracing with configurable depth
+//! - Next event estimation for emissive meshes and scene lights (with MIS)
+//! - Environment lighting on escaped rays
 //! - Gamma correction
 //! - Anti-aliasing via multi-sampling
 
-use crate::{Camera, Color, HitRecord, Hittable, Ray};
+use crate::hittable::SHADOW_EPSILON;
+use crate::lights::power_heuristic;
+use crate::{gen_f32, Camera, Color, HitRecord, Hittable, LightList, Ray};
 use bif_math::Interval;
 use rand::RngCore;
+use std::sync::Arc;
 
 /// Render configuration.
 #[derive(Debug, Clone)]
@@ -16,10 +21,18 @@ pub struct RenderConfig {
     pub samples_per_pixel: u32,
     /// Maximum ray bounce depth
     pub max_depth: u32,
-    /// Background color when ray doesn't hit anything
+    /// Background color when ray doesn't hit anything (and there is no
+    /// environment light)
     pub background: Color,
     /// Whether to use sky gradient instead of solid background
     pub use_sky_gradient: bool,
+    /// Emissive triangles sampled directly at each bounce (None = no NEE)
+    ///
+    /// Must contain every emissive surface that is meant to be sampled, since
+    /// emission found by BSDF sampling is MIS-weighted against it.
+    pub lights: Option<Arc<LightList>>,
+    /// Trace primary rays of pixel tiles as packets (see `Hittable::hit_packet`)
+    pub packet_primary_rays: bool,
 }
 
 impl Default for RenderConfig {
@@ -29,6 +42,8 @@ impl Default for RenderConfig {
             max_depth: 50,
             background: Color::ZERO,
             use_sky_gradient: false,
+            lights: None,
+            packet_primary_rays: false,
         }
     }
 }
@@ -43,32 +58,142 @@ pub fn ray_color(
     depth: u32,
     config: &RenderConfig,
     rng: &mut dyn RngCore,
+) -> Color {
+    trace(ray, world, depth, config, None, rng)
+}
+
+/// Compute the color seen by a camera ray whose closest hit is already known.
+///
+/// Used after tracing primary rays as a packet; equivalent to `ray_color`
+/// with `config.max_depth`.
+pub fn ray_color_from_hit(
+    ray: &Ray,
+    hit: Option<&HitRecord>,
+    world: &dyn Hittable,
+    config: &RenderConfig,
+    rng: &mut dyn RngCore,
+) -> Color {
+    if config.max_depth == 0 {
+        return Color::ZERO;
+    }
+    shade(ray, hit, world, config.max_depth, config, None, rng)
+}
+
+/// Recursive part of `ray_color`.
+///
+/// `bsdf_pdf` is the solid-angle density with which the previous bounce
+/// sampled `ray` if it also sampled the lights, so that emission found here
+/// can be MIS-weighted. None means emission is counted in full.
+fn trace(
+    ray: &Ray,
+    world: &dyn Hittable,
+    depth: u32,
+    config: &RenderConfig,
+    bsdf_pdf: Option<f32>,
+    rng: &mut dyn RngCore,
 ) -> Color {
     // If we've exceeded max depth, return black (no light)
     if depth == 0 {
         return Color::ZERO;
     }
 
+    // Secondary rays start offset off their surface, so no t epsilon is needed
     let mut rec = HitRecord::default();
+    let hit = world.hit(ray, Interval::new(0.0, f32::INFINITY), &mut rec);
+    shade(
+        ray,
+        hit.then_some(&rec),
+        world,
+        depth,
+        config,
+        bsdf_pdf,
+        rng,
+    )
+}
 
-    // Check if ray hits anything
-    if !world.hit(ray, Interval::new(0.001, f32::INFINITY), &mut rec) {
-        // Ray didn't hit anything - return background
+/// Color seen along `ray` given its closest hit (None if it escaped).
+fn shade(
+    ray: &Ray,
+    hit: Option<&HitRecord>,
+    world: &dyn Hittable,
+    depth: u32,
+    config: &RenderConfig,
+    bsdf_pdf: Option<f32>,
+    rng: &mut dyn RngCore,
+) -> Color {
+    let Some(rec) = hit else {
+        // Ray didn't hit anything - return the environment light, weighted
+        // against sampling it, or the background
+        let environment = config.lights.as_ref().and_then(|lights| {
+            let (radiance, light_pdf) = lights.environment(ray.direction())?;
+            Some(match bsdf_pdf {
+                Some(pdf) => radiance * power_heuristic(pdf, light_pdf),
+                None => radiance,
+            })
+        });
+        if let Some(radiance) = environment {
+            return radiance;
+        }
         if config.use_sky_gradient {
             return sky_gradient(ray);
         }
         return config.background;
-    }
+    };
 
     // Get emission from material (for lights)
-    let emission = rec.material.emitted(rec.u, rec.v, rec.p);
+    let mut emission = rec.material.emitted(rec.u, rec.v, rec.p);
+    if let (Some(pdf), Some(lights)) = (bsdf_pdf, &config.lights) {
+        if emission != Color::ZERO {
+            let light_pdf = lights.mis_pdf_for_hit(ray, rec, emission);
+            emission *= power_heuristic(pdf, light_pdf);
+        }
+    }
+
+    // Random-walk subsurface replaces part of the diffuse lobe, which the
+    // material's BSDF leaves out. Either the walk or the surface lobes are
+    // followed, each weighted by its inverse selection probability.
+    let mut surface_weight = 1.0;
+    if let Some(sss) = rec.material.subsurface(rec.u, rec.v) {
+        if rec.front_face && sss.weight > 0.0 {
+            let walk_probability = sss.weight / (1.0 + sss.weight);
+            if gen_f32(rng) < walk_probability {
+                let walked = match sss.random_walk(rec, world, ray.time(), rng) {
+                    Some(exit) => {
+                        let exit_color = trace(&exit.ray, world, depth - 1, config, None, rng);
+                        exit.throughput * exit_color
+                    }
+                    None => Color::ZERO,
+                };
+                return emission + walked * (sss.weight / walk_probability);
+            }
+            surface_weight = 1.0 / (1.0 - walk_probability);
+        }
+    }
 
     // Try to scatter the ray
-    match rec.material.scatter(ray, &rec, rng) {
+    match rec.material.scatter(ray, rec, rng) {
         Some(result) => {
-            // Ray scattered - continue tracing
-            let scattered_color = ray_color(&result.scattered, world, depth - 1, config, rng);
-            emission + result.attenuation * scattered_color
+            let lights = config
+                .lights
+                .as_ref()
+                .filter(|lights| !lights.is_empty() && rec.material.properties().can_use_nee);
+
+            // Sample the lights directly, then continue the BSDF-sampled path
+            let (direct, bsdf_pdf) = match lights {
+                Some(lights) => {
+                    let direct = sample_direct(ray, rec, world, lights, rng);
+                    let pdf = rec.material.pdf(ray, rec, &result.scattered);
+                    (direct, Some(pdf))
+                }
+                None => (Color::ZERO, None),
+            };
+
+            // Keep the ray type of the sampled lobe for visibility tests
+            let scattered = rec
+                .spawn_ray(result.scattered.direction(), ray.time())
+                .with_visibility(result.scattered.visibility());
+            let scattered_color = trace(&scattered, world, depth - 1, config, bsdf_pdf, rng);
+            emission + (direct + result.attenuation * scattered_color) * surface_weight
         }
         None => {
             // Ray was absorbed - just return emission
@@ -77,6 +202,43 @@ pub fn ray_color(
     }
 }
 
+/// Estimate direct lighting from one light sample, MIS-weighted against
+/// BSDF sampling.
+fn sample_direct(
+    ray: &Ray,
+    rec: &HitRecord,
+    world: &dyn Hittable,
+    lights: &LightList,
+    rng: &mut dyn RngCore,
+) -> Color {
+    let Some(sample) = lights.sample(rec.p, rng) else {
+        return Color::ZERO;
+    };
+    if sample.pdf <= 0.0 || sample.emission == Color::ZERO {
+        return Color::ZERO;
+    }
+
+    let shadow_ray = rec.spawn_ray_to(sample.p, ray.time());
+    let f = rec.material.bsdf(ray, rec, &shadow_ray);
+    if f == Color::ZERO {
+        return Color::ZERO;
+    }
+
+    // Shadow ray direction is unnormalized, so the light sits at t = 1
+    let mut blocker = HitRecord::default();
+    if world.hit(
+        &shadow_ray,
+        Interval::new(0.0, 1.0 - SHADOW_EPSILON),
+        &mut blocker,
+    ) {
+        return Color::ZERO;
+    }
+
+    let bsdf_pdf = rec.material.pdf(ray, rec, &shadow_ray);
+    let weight = power_heuristic(sample.mis_pdf, bsdf_pdf);
+    sample.emission * f * weight / sample.pdf
+}
+
 /// Compute sky gradient background.
 fn sky_gradient(ray: &Ray) -> Color {
     let unit_direction = ray.direction().normalize();
@@ -194,7 +356,9 @@ pub fn render(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{BvhNode, Lambertian, Sphere, Vec3};
+    use crate::{
+        BvhNode, DiffuseLight, HittableList, Lambertian, Sphere, Triangle, TriangleLight, Vec3,
+    };
     use rand::rngs::StdRng;
     use rand::SeedableRng;
 
@@ -246,6 +410,8 @@ mod tests {
             max_depth: 5,
             background: Color::new(0.5, 0.7, 1.0),
             use_sky_gradient: false,
+            lights: None,
+            packet_primary_rays: false,
         };
 
         let mut rng = StdRng::seed_from_u64(42);
@@ -257,4 +423,343 @@ mod tests {
         // Can't test exact color due to random sampling
         assert!(color.length() > 0.0);
     }
+
+    /// Radiance reaching a point above a floor of `floor_material` lit by a
+    /// small quad light, by brute-force path tracing and with NEE.
+    fn brute_force_and_nee<M: crate::Material + Clone + 'static>(floor_material: M) -> (f32, f32) {
+        let emit = Color::splat(4.0);
+        let floor = [
+            Vec3::new(-10.0, 0.0, -10.0),
+            Vec3::new(10.0, 0.0, -10.0),
+            Vec3::new(10.0, 0.0, 10.0),
+            Vec3::new(-10.0, 0.0, 10.0),
+        ];
+        let light = [
+            Vec3::new(-0.5, 2.0, -0.5),
+            Vec3::new(0.5, 2.0, -0.5),
+            Vec3::new(0.5, 2.0, 0.5),
+            Vec3::new(-0.5, 2.0, 0.5),
+        ];
+        let quad = |q: [Vec3; 4]| [[q[0], q[1], q[2]], [q[0], q[2], q[3]]];
+
+        let mut world = HittableList::new();
+        for [a, b, c] in quad(floor) {
+            world.add(Box::new(Triangle::new(a, b, c, floor_material.clone())));
+        }
+        for [a, b, c] in quad(light) {
+            world.add(Box::new(Triangle::new(a, b, c, DiffuseLight::new(emit))));
+        }
+
+        let material: Arc<dyn crate::Material> = Arc::new(DiffuseLight::new(emit));
+        let lights = LightList::from_triangles(
+            quad(light).map(|tri| TriangleLight::new(tri, material.clone())),
+        );
+
+        let mut config = RenderConfig {
+            samples_per_pixel: 1,
+            max_depth: 2,
+            ..Default::default()
+        };
+        let ray = Ray::new(Vec3::new(0.3, 1.0, 0.0), Vec3::new(-0.3, -1.0, 0.0), 0.0);
+        let estimate = |config: &RenderConfig, seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let n = 20_000;
+            let sum: Color = (0..n)
+                .map(|_| ray_color(&ray, &world, config.max_depth, config, &mut rng))
+                .sum();
+            sum.x / n as f32
+        };
+
+        let brute_force = estimate(&config, 1);
+        config.lights = Some(Arc::new(lights));
+        (brute_force, estimate(&config, 2))
+    }
+
+    #[test]
+    fn test_light_sampling_matches_brute_force() {
+        let (brute_force, with_nee) = brute_force_and_nee(Lambertian::new(Color::ONE));
+
+        assert!(brute_force > 0.0);
+        let relative = (with_nee - brute_force).abs() / brute_force;
+        assert!(
+            relative < 0.1,
+            "brute force {brute_force} vs NEE {with_nee}"
+        );
+    }
+
+    #[test]
+    fn test_disney_light_sampling_matches_brute_force() {
+        let (brute_force, with_nee) =
+            brute_force_and_nee(crate::DisneyBSDF::plastic(Color::splat(0.8), 0.5));
+
+        assert!(brute_force > 0.0);
+        let relative = (with_nee - brute_force).abs() / brute_force;
+        assert!(
+            relative < 0.1,
+            "brute force {brute_force} vs NEE {with_nee}"
+        );
+    }
+
+    #[test]
+    fn test_hidden_light_lights_the_scene() {
+        // A floor under a wide light that isn't part of the world
+        let mut world = HittableList::new();
+        let (a, b, c, d) = (
+            Vec3::new(-10.0, 0.0, -10.0),
+            Vec3::new(10.0, 0.0, -10.0),
+            Vec3::new(10.0, 0.0, 10.0),
+            Vec3::new(-10.0, 0.0, 10.0),
+        );
+        let floor = Lambertian::new(Color::splat(0.5));
+        world.add(Box::new(Triangle::new(a, b, c, floor.clone())));
+        world.add(Box::new(Triangle::new(a, c, d, floor)));
+        let light = crate::Quad::new(
+            Vec3::new(-10.0, 1.0, -10.0),
+            Vec3::X * 20.0,
+            Vec3::Z * 20.0,
+            DiffuseLight::new(Color::ONE),
+        );
+        let lights = crate::InstancedLights::default()
+            .with_hidden_light(Arc::new(light))
+            .light_list();
+
+        let config = RenderConfig {
+            samples_per_pixel: 1,
+            max_depth: 2,
+            lights: Some(Arc::new(lights)),
+            ..Default::default()
+        };
+        let ray = Ray::new(Vec3::new(0.3, 1.0, 0.0), Vec3::new(-0.3, -1.0, 0.0), 0.0);
+        let mut rng = StdRng::seed_from_u64(4);
+        let n = 20_000;
+        let sum: Color = (0..n)
+            .map(|_| ray_color(&ray, &world, config.max_depth, &config, &mut rng))
+            .sum();
+        let estimate = sum.x / n as f32;
+
+        // Albedo times the light's form factor from the origin
+        let expected = 0.5 * 0.9919;
+        assert!(
+            (estimate - expected).abs() < 0.05 * expected,
+            "expected {expected}, got {estimate}"
+        );
+    }
+
+    #[test]
+    fn test_environment_light_lights_the_scene() {
+        // A floor under a constant dome, which escaped rays see
+        let mut world = HittableList::new();
+        let (a, b, c, d) = (
+            Vec3::new(-10.0, 0.0, -10.0),
+            Vec3::new(10.0, 0.0, -10.0),
+            Vec3::new(10.0, 0.0, 10.0),
+            Vec3::new(-10.0, 0.0, 10.0),
+        );
+        let floor = Lambertian::new(Color::splat(0.5));
+        world.add(Box::new(Triangle::new(a, b, c, floor.clone())));
+        world.add(Box::new(Triangle::new(a, c, d, floor)));
+        let dome = crate::EnvironmentLight::new(bif_math::Mat4::IDENTITY, Color::ONE, 10.0);
+        let lights = crate::InstancedLights::default()
+            .with_environment_light(dome)
+            .light_list();
+
+        let config = RenderConfig {
+            samples_per_pixel: 1,
+            max_depth: 2,
+            background: Color::new(1.0, 0.0, 0.0),
+            lights: Some(Arc::new(lights)),
+            ..Default::default()
+        };
+        let mut rng = StdRng::seed_from_u64(6);
+        let sky = Ray::new(Vec3::Y, Vec3::Y, 0.0);
+        assert_eq!(
+            ray_color(&sky, &world, config.max_depth, &config, &mut rng),
+            Color::ONE
+        );
+
+        // Light samples and escaped bounces combine to the albedo
+        let ray = Ray::new(Vec3::new(0.3, 1.0, 0.0), Vec3::new(-0.3, -1.0, 0.0), 0.0);
+        let n = 20_000;
+        let sum: Color = (0..n)
+            .map(|_| ray_color(&ray, &world, config.max_depth, &config, &mut rng))
+            .sum();
+        let estimate = sum.x / n as f32;
+        assert!(
+            (estimate - 0.5).abs() < 0.025,
+            "expected 0.5, got {estimate}"
+        );
+    }
+
+    #[test]
+    fn test_light_sampling_respects_visibility() {
+        use crate::{InstancedGeometryBVH, InstancedPrototype, MaterialTable};
+        use bif_core::Visibility;
+        use bif_math::Mat4;
+
+        // A light hidden from diffuse rays can't light a diffuse floor
+        let floor = [
+            [
+                Vec3::new(-10.0, 0.0, -10.0),
+                Vec3::new(10.0, 0.0, 10.0),
+                Vec3::new(10.0, 0.0, -10.0),
+            ],
+            [
+                Vec3::new(-10.0, 0.0, -10.0),
+                Vec3::new(-10.0, 0.0, 10.0),
+                Vec3::new(10.0, 0.0, 10.0),
+            ],
+        ];
+        let light = floor.map(|tri| tri.map(|v| v * 0.05 + Vec3::new(0.0, 2.0, 0.0)));
+        let mut hidden = Visibility::ALL;
+        hidden.set(Visibility::DIFFUSE, false);
+        let material = |m: Arc<dyn crate::Material>| MaterialTable::new(m);
+        let prototypes = vec![
+            InstancedPrototype::new(
+                &floor,
+                vec![Mat4::IDENTITY],
+                material(Arc::new(Lambertian::new(Color::ONE))),
+            ),
+            InstancedPrototype::new(
+                &light,
+                vec![Mat4::IDENTITY],
+                material(Arc::new(DiffuseLight::new(Color::splat(4.0)))),
+            )
+            .with_visibility(vec![hidden]),
+        ];
+        let lights = LightList::from_prototypes(&prototypes);
+        let world = InstancedGeometryBVH::from_prototypes(prototypes);
+
+        let config = RenderConfig {
+            samples_per_pixel: 1,
+            max_depth: 2,
+            lights: Some(Arc::new(lights)),
+            ..Default::default()
+        };
+        let ray = Ray::new(Vec3::new(0.3, 1.0, 0.0), Vec3::new(-0.3, -1.0, 0.0), 0.0);
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..1000 {
+            let color = ray_color(&ray, &world, config.max_depth, &config, &mut rng);
+            assert_eq!(color, Color::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_no_self_intersection_at_scale() {
+        use crate::material::random_unit_vector;
+        use bif_math::Quat;
+
+        let mut rng = StdRng::seed_from_u64(3);
+        for scale in [1e-3f32, 1e4] {
+            // Tilted ground square away from the origin, with an occluder
+            // hovering over its right half
+            let frame = Quat::from_axis_angle(Vec3::new(1.0, 2.0, 0.5).normalize(), 0.6);
+            let origin = Vec3::new(2.3, -1.1, 0.7) * scale;
+            let to_world = |x: f32, y: f32, z: f32| origin + frame * (Vec3::new(x, y, z) * scale);
+            let height = 0.01 * scale;
+            let quad = |z: f32, x0: f32| {
+                let [a, b, c, d] = [
+                    to_world(x0, 0.0, z),
+                    to_world(1.0, 0.0, z),
+                    to_world(1.0, 1.0, z),
+                    to_world(x0, 1.0, z),
+                ];
+                [[a, b, c], [a, c, d]]
+            };
+            let mut world = HittableList::new();
+            for [a, b, c] in quad(0.0, 0.0) {
+                world.add(Box::new(Triangle::new(
+                    a,
+                    c,
+                    b,
+                    Lambertian::new(Color::ONE),
+                )));
+            }
+            for [a, b, c] in quad(0.01, 0.5) {
+                world.add(Box::new(Triangle::new(
+                    a,
+                    c,
+                    b,
+                    Lambertian::new(Color::ONE),
+                )));
+            }
+            let normal = frame * Vec3::Z;
+
+            for _ in 0..500 {
+                let uv = Vec3::new(gen_f32(&mut rng), gen_f32(&mut rng), 0.0);
+                let target = to_world(uv.x, uv.y, 0.0);
+                let eye = target + normal * scale;
+                let ray = Ray::new(eye, target - eye, 0.0);
+                let mut rec = HitRecord::default();
+                assert!(world.hit(&ray, Interval::new(0.0, f32::INFINITY), &mut rec));
+                if uv.x >= 0.5 {
+                    // Hit the occluder from above
+                    continue;
+                }
+
+                // Leaving the ground in any direction never re-hits it
+                for _ in 0..8 {
+                    let direction = random_unit_vector(&mut rng);
+                    let spawned = rec.spawn_ray(direction, 0.0);
+                    let mut next = HitRecord::default();
+                    if world.hit(&spawned, Interval::new(0.0, f32::INFINITY), &mut next) {
+                        assert!(
+                            next.t > 0.5 * height,
+                            "scale {scale}: self-hit at t = {}",
+                            next.t
+                        );
+                    }
+                }
+
+                // Unoccluded shadow ray to a point above the left half
+                let light = to_world(0.25, 0.5, 0.5);
+                let shadow = rec.spawn_ray_to(light, 0.0);
+                let mut blocker = HitRecord::default();
+                assert!(!world.hit(
+                    &shadow,
+                    Interval::new(0.0, 1.0 - SHADOW_EPSILON),
+                    &mut blocker
+                ));
+            }
+
+            // Rays leaving a sphere only re-hit it across a chord
+            let center = to_world(0.5, 0.5, -5.0);
+            let radius = 0.3 * scale;
+            let sphere = Sphere::new(center, radius, Lambertian::new(Color::ONE));
+            for _ in 0..200 {
+                let eye = center + random_unit_vector(&mut rng) * 2.0 * radius;
+                let target = center + random_unit_vector(&mut rng) * 0.5 * radius;
+                let ray = Ray::new(eye, target - eye, 0.0);
+                let mut rec = HitRecord::default();
+                assert!(sphere.hit(&ray, Interval::new(0.0, f32::INFINITY), &mut rec));
+
+                let direction = random_unit_vector(&mut rng);
+                let spawned = rec.spawn_ray(direction, 0.0);
+                let mut next = HitRecord::default();
+                let hit = sphere.hit(&spawned, Interval::new(0.0, f32::INFINITY), &mut next);
+                let cos = direction.dot(rec.normal);
+                if cos > 0.0 {
+                    assert!(!hit, "scale {scale}: self-hit at t = {}", next.t);
+                } else {
+                    assert!(hit && next.t > radius * cos.abs(), "scale {scale}");
+                }
+            }
+
+            // Points under the occluder see it, however close it is
+            for _ in 0..100 {
+                let target = to_world(0.5 + 0.5 * gen_f32(&mut rng), gen_f32(&mut rng), 0.0);
+                let eye = target - normal * scale;
+                let ray = Ray::new(eye, target - eye, 0.0);
+                let mut rec = HitRecord::default();
+                assert!(world.hit(&ray, Interval::new(0.0, f32::INFINITY), &mut rec));
+                let spawned = rec.spawn_ray(normal, 0.0);
+                let mut next = HitRecord::default();
+                assert!(world.hit(&spawned, Interval::new(0.0, f32::INFINITY), &mut next));
+                assert!(
+                    (next.t - height).abs() < 1e-3 * height,
+                    "scale {scale}: occluder at t = {}",
+                    next.t
+                );
+            }
+        }
+    }
 }
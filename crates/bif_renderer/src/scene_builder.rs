@@ -0,0 +1,894 @@
+//! Conversion of a `bif_core::Scene` into a traceable Ivar world.
+//!
+//! Shared by the viewer, batch rendering and tests so they all see the same
+//! geometry, material bindings and lights for a scene.
+
+use crate::{
+    build_scene_world, AreaLight, Camera, Color, CurveGeometry, Cylinder, DiffuseLight, Disk,
+    DistantLight, EnvironmentLight, HairBSDF, InstancedGeometryBVH, InstancedLights,
+    InstancedPrototype, InstancedWorld, LightList, MaterialTable, PointGeometry, Quad,
+    ShadingGraphMaterial, Shaping, Sphere,
+};
+use bif_core::light::DomeTextureFormat;
+use bif_core::mesh::{DisplacementMap, TessellationSettings};
+use bif_core::{
+    Light, LightKind, MaterialId, Mesh, Prototype, Scene, TextureCache, Visibility, NO_MATERIAL,
+};
+use bif_math::{Aabb, Mat4, Vec3};
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// A scene ready for Ivar: geometry, lights and camera.
+pub struct IvarScene {
+    /// Two-level instanced world (Embree or the pure-Rust fallback).
+    ///
+    /// Edits and animated frames can update it through `Arc::get_mut` and
+    /// the [`InstancedWorld`] methods instead of building a new scene.
+    pub world: Arc<dyn InstancedWorld>,
+
+    /// Emissive triangles and scene lights, for next event estimation
+    pub lights: Arc<LightList>,
+
+    /// Emissive triangles in local space and the scene lights, to rebuild
+    /// `lights` after `world` edits
+    pub instanced_lights: InstancedLights,
+
+    /// World prototype index of each scene prototype (`None` for
+    /// prototypes without instances, which are left out of the world)
+    pub prototype_indices: Vec<Option<usize>>,
+
+    /// Initialized camera
+    pub camera: Camera,
+}
+
+/// Builds an [`IvarScene`] from a `bif_core::Scene`.
+///
+/// Every prototype becomes its own bottom-level BVH, instanced once per
+/// scene instance. Prototypes whose material has a displacement texture are
+/// tessellated and displaced first (see [`Mesh::displaced`]). Materials
+/// follow `Scene::resolve_material`: face bindings win over instance
+/// bindings, which win over the prototype's material. Curve sets are traced
+/// as hair with a [`HairBSDF`] colored by their material; point sets are
+/// traced as spheres with their material. UsdLux lights are sampled for
+/// next event estimation, but paths don't hit them; escaped paths see the
+/// dome light.
+/// Instance motion keys and mesh deformation keys are kept for motion blur
+/// (displaced prototypes don't deform).
+///
+/// # Example
+/// ```ignore
+/// let ivar = SceneBuilder::new(&scene)
+///     .with_camera(Camera::new().with_resolution(1920, 1080))
+///     .build();
+/// let image = render(&ivar.camera, ivar.world.as_ref(), &config);
+/// ```
+pub struct SceneBuilder<'a> {
+    scene: &'a Scene,
+    camera: Option<Camera>,
+    camera_path: Option<String>,
+    use_embree: bool,
+    tessellation: TessellationSettings,
+}
+
+impl<'a> SceneBuilder<'a> {
+    /// Create a builder for `scene`.
+    pub fn new(scene: &'a Scene) -> Self {
+        Self {
+            scene,
+            camera: None,
+            camera_path: None,
+            use_embree: true,
+            tessellation: TessellationSettings::default(),
+        }
+    }
+
+    /// Use this camera instead of one framing the scene bounds.
+    pub fn with_camera(mut self, camera: Camera) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+
+    /// Look through the scene camera at this prim path (see
+    /// [`Camera::with_scene_camera`]).
+    ///
+    /// Resolution, quality and shutter still come from [`Self::with_camera`]
+    /// if set. If the scene has no such camera, the scene is framed instead.
+    pub fn with_camera_path(mut self, path: impl Into<String>) -> Self {
+        self.camera_path = Some(path.into());
+        self
+    }
+
+    /// Allow Embree (the default) or always use the pure-Rust two-level BVH.
+    pub fn with_embree(mut self, enabled: bool) -> Self {
+        self.use_embree = enabled;
+        self
+    }
+
+    /// Tessellate displaced prototypes with these settings.
+    pub fn with_tessellation(mut self, settings: TessellationSettings) -> Self {
+        self.tessellation = settings;
+        self
+    }
+
+    /// Build the acceleration structures, material tables and lights.
+    pub fn build(self) -> IvarScene {
+        let start_time = Instant::now();
+        let scene = self.scene;
+
+        // Scene materials share one texture cache and one palette between
+        // all prototypes
+        let mut textures = TextureCache::new();
+        let materials: Arc<[ShadingGraphMaterial]> = scene
+            .materials
+            .iter()
+            .map(|m| ShadingGraphMaterial::new(m, &mut textures))
+            .collect();
+
+        // Group instances by prototype (local instance indices follow scene order)
+        let mut groups: Vec<InstanceGroup> = vec![Default::default(); scene.prototypes.len()];
+        for (index, instance) in scene.instances.iter().enumerate() {
+            let Some(group) = groups.get_mut(instance.prototype_id) else {
+                log::warn!(
+                    "Skipping instance of missing prototype {}",
+                    instance.prototype_id
+                );
+                continue;
+            };
+            group.transforms.push(instance.model_matrix());
+            group
+                .motion
+                .push(instance.motion.iter().map(|key| key.to_matrix()).collect());
+            group
+                .instance_ids
+                .push(instance.material_id.unwrap_or(NO_MATERIAL));
+            group.visibility.push(scene.resolve_visibility(index));
+        }
+
+        // Uninstanced prototypes are skipped entirely; displaced prototypes
+        // are tessellated and their face bindings follow the refined triangles
+        let geometry: Vec<PrototypeGeometry> = scene
+            .prototypes
+            .iter()
+            .zip(&groups)
+            .map(|(proto, group)| {
+                if group.transforms.is_empty() {
+                    return PrototypeGeometry::default();
+                }
+                match self.displace(proto, &mut textures) {
+                    Some((mesh, origins)) => {
+                        let face_ids = proto.face_material_ids.as_ref().map(|ids| {
+                            origins
+                                .iter()
+                                .map(|&tri| ids.get(tri as usize).copied().unwrap_or(NO_MATERIAL))
+                                .collect()
+                        });
+                        PrototypeGeometry {
+                            vertices: mesh.extract_triangle_vertices(),
+                            deformation: Vec::new(),
+                            uvs: mesh.extract_triangle_uvs(),
+                            face_ids,
+                        }
+                    }
+                    None => PrototypeGeometry {
+                        vertices: proto.mesh.extract_triangle_vertices(),
+                        deformation: proto.mesh.extract_motion_triangle_vertices(),
+                        uvs: proto.mesh.extract_triangle_uvs(),
+                        face_ids: proto.face_material_ids.clone(),
+                    },
+                }
+            })
+            .collect();
+
+        let mut prototypes = Vec::new();
+        let mut prototype_indices = Vec::with_capacity(scene.prototypes.len());
+        for ((proto, geometry), group) in scene.prototypes.iter().zip(&geometry).zip(groups) {
+            if group.transforms.is_empty() {
+                prototype_indices.push(None);
+                continue;
+            }
+            prototype_indices.push(Some(prototypes.len()));
+
+            let default = proto.material.as_deref().cloned().unwrap_or_default();
+            let mut table = MaterialTable::new(ShadingGraphMaterial::new(&default, &mut textures))
+                .with_materials(materials.clone())
+                .with_instance_ids(group.instance_ids);
+            if let Some(face_ids) = &geometry.face_ids {
+                table = table.with_face_ids(face_ids.clone());
+            }
+            let mut prototype =
+                InstancedPrototype::new(&geometry.vertices, group.transforms, table)
+                    .with_uvs(&geometry.uvs)
+                    .with_visibility(group.visibility)
+                    .with_deformation(&geometry.deformation);
+            if group.motion.iter().any(|keys| !keys.is_empty()) {
+                prototype = prototype.with_motion(group.motion);
+            }
+            prototypes.push(prototype);
+        }
+
+        // Emissive triangles and scene lights become lights for next event
+        // estimation
+        let bounds = scene.world_bounds();
+        let (min, max) = (bounds.min_point(), bounds.max_point());
+        let scene_radius = if min.cmple(max).all() && min.is_finite() && max.is_finite() {
+            0.5 * (max - min).length()
+        } else {
+            0.0
+        };
+        let instanced_lights = scene.lights.iter().fold(
+            InstancedLights::from_prototypes(&prototypes),
+            |lights, light| add_scene_light(lights, light, &mut textures, scene_radius),
+        );
+        let lights = instanced_lights.light_list();
+        if !lights.is_empty() {
+            log::info!("Sampling {} lights", lights.len());
+        }
+
+        let curves: Vec<Arc<CurveGeometry>> = scene
+            .curves
+            .iter()
+            .map(|set| {
+                let material = set.material.as_deref().cloned().unwrap_or_default();
+                Arc::new(CurveGeometry::new(
+                    &set.curves,
+                    set.transform,
+                    Arc::new(HairBSDF::from_material(&material)),
+                ))
+            })
+            .collect();
+
+        let points: Vec<Arc<PointGeometry>> = scene
+            .points
+            .iter()
+            .map(|set| {
+                let material = set.material.as_deref().cloned().unwrap_or_default();
+                Arc::new(PointGeometry::new(
+                    &set.points,
+                    set.transform,
+                    Arc::new(ShadingGraphMaterial::new(&material, &mut textures)),
+                ))
+            })
+            .collect();
+
+        let world: Arc<dyn InstancedWorld> = if self.use_embree {
+            build_scene_world(prototypes, curves, points).into()
+        } else {
+            Arc::new(
+                InstancedGeometryBVH::from_prototypes(prototypes)
+                    .with_curves(curves)
+                    .with_points(points),
+            )
+        };
+
+        let scene_camera = self.camera_path.as_deref().and_then(|path| {
+            let camera = scene.find_camera(path);
+            if camera.is_none() {
+                log::warn!("Scene has no camera {}, framing the scene", path);
+            }
+            camera
+        });
+        let mut camera = match (self.camera, scene_camera) {
+            (camera, Some(scene_camera)) => {
+                camera.unwrap_or_default().with_scene_camera(scene_camera)
+            }
+            (Some(camera), None) => camera,
+            (None, None) => framing_camera(&scene.world_bounds()),
+        };
+        camera.initialize();
+
+        log::info!(
+            "Ivar scene built in {:.2}ms: {} prototypes, {} instances, {} curves, {} points",
+            start_time.elapsed().as_secs_f64() * 1000.0,
+            scene.prototype_count(),
+            scene.instance_count(),
+            scene.total_curve_count(),
+            scene.total_point_count()
+        );
+
+        IvarScene {
+            world,
+            lights: Arc::new(lights),
+            instanced_lights,
+            prototype_indices,
+            camera,
+        }
+    }
+
+    /// Tessellate and displace a prototype's mesh if its material has a
+    /// displacement texture.
+    ///
+    /// Instance and face material bindings don't displace; only the
+    /// prototype's own material does, since instances share one mesh.
+    ///
+    /// Returns the displaced mesh and the source triangle of each of its
+    /// triangles, or `None` to use the mesh as-is.
+    fn displace(&self, proto: &Prototype, textures: &mut TextureCache) -> Option<(Mesh, Vec<u32>)> {
+        let material = proto.material.as_deref()?;
+        if !material.has_displacement() {
+            return None;
+        }
+        let path = material.displacement_texture.as_deref()?;
+        let texture = match textures.load_linear(path) {
+            Ok(texture) => texture,
+            Err(e) => {
+                log::warn!("Failed to load displacement texture {}: {}", path, e);
+                return None;
+            }
+        };
+
+        let map = DisplacementMap {
+            texture: &texture,
+            channel: material.displacement_texture_channel,
+            scale: material.displacement_scale,
+            bias: material.displacement_bias,
+            vector: material.vector_displacement,
+            bound: proto.displacement_bound,
+        };
+        let (mesh, origins) = proto.mesh.displaced(&map, &self.tessellation);
+        log::info!(
+            "Displaced {}: {} -> {} triangles",
+            proto.name,
+            proto.mesh.triangle_count(),
+            mesh.triangle_count()
+        );
+        Some((mesh, origins))
+    }
+}
+
+/// Triangles of one prototype as handed to the world.
+#[derive(Default)]
+struct PrototypeGeometry {
+    vertices: Vec<[Vec3; 3]>,
+    /// Further triangle keys for deformation blur
+    deformation: Vec<Vec<[Vec3; 3]>>,
+    /// UVs of each triangle's corners (empty without UVs)
+    uvs: Vec<[[f32; 2]; 3]>,
+    face_ids: Option<Arc<[MaterialId]>>,
+}
+
+/// Instances of one prototype, in scene order.
+#[derive(Clone, Default)]
+struct InstanceGroup {
+    transforms: Vec<Mat4>,
+    /// Further transform keys of each instance
+    motion: Vec<Vec<Mat4>>,
+    instance_ids: Vec<MaterialId>,
+    visibility: Vec<Visibility>,
+}
+
+/// Add a UsdLux light to `lights`.
+///
+/// Rect, disk, sphere and cylinder lights emit from their surface, shaped
+/// by their `ShapingAPI` cone and focus, and distant lights from a cone of
+/// directions. Paths don't hit them, so they only light the scene through
+/// next event estimation. The first dome light becomes the environment
+/// light, with a latlong texture if it has one; IES profiles aren't
+/// supported.
+fn add_scene_light(
+    lights: InstancedLights,
+    light: &Light,
+    textures: &mut TextureCache,
+    scene_radius: f32,
+) -> InstancedLights {
+    let transform = light.transform;
+    let center = light.position();
+    let axis = |v: Vec3| transform.transform_vector3(v);
+    let radiance = light.radiance();
+
+    // Normalized lights keep their power when resized
+    let emission = |area: f32| -> Color {
+        if light.normalize && area > 0.0 {
+            radiance / area
+        } else {
+            radiance
+        }
+    };
+
+    let surface: Arc<dyn AreaLight> = match light.kind {
+        LightKind::Rect { width, height } => {
+            // Edges ordered so the normal faces -Z, where the light shines
+            let edge_u = axis(Vec3::X * width);
+            let edge_v = axis(Vec3::NEG_Y * height);
+            let corner = transform.transform_point3(Vec3::new(-0.5 * width, 0.5 * height, 0.0));
+            let emit = emission(edge_u.cross(edge_v).length());
+            let texture = light.texture.as_deref().and_then(|path| {
+                textures
+                    .load(path)
+                    .map_err(|e| log::warn!("Failed to load light texture {}: {}", path, e))
+                    .ok()
+            });
+            let material = match texture {
+                Some(texture) => DiffuseLight::textured(texture, emit),
+                None => DiffuseLight::new(emit),
+            };
+            Arc::new(Quad::new(corner, edge_u, edge_v, material))
+        }
+        LightKind::Disk { radius } => {
+            let radius = radius * axis(Vec3::X).length();
+            let material = DiffuseLight::new(emission(PI * radius * radius));
+            Arc::new(Disk::new(center, light.direction(), radius, material))
+        }
+        LightKind::Sphere { radius, .. } => {
+            let radius = radius * axis(Vec3::X).length();
+            let material = DiffuseLight::new(emission(4.0 * PI * radius * radius));
+            Arc::new(Sphere::new(center, radius, material))
+        }
+        LightKind::Cylinder { length, radius, .. } => {
+            let (length, radius) = (
+                length * axis(Vec3::X).length(),
+                radius * axis(Vec3::Y).length(),
+            );
+            let material = DiffuseLight::new(emission(2.0 * PI * radius * length));
+            Arc::new(Cylinder::new(
+                center,
+                axis(Vec3::X),
+                radius,
+                length,
+                material,
+            ))
+        }
+        LightKind::Distant { angle } => {
+            // Radiance over the cone; normalized lights give the irradiance
+            let angle = angle.to_radians();
+            let solid_angle = DistantLight::solid_angle(angle);
+            let irradiance = if light.normalize || solid_angle <= 0.0 {
+                radiance
+            } else {
+                radiance * solid_angle
+            };
+            let distant = DistantLight::new(light.direction(), angle, irradiance, scene_radius);
+            return lights.with_distant_light(distant);
+        }
+        LightKind::Dome { texture_format } => {
+            if lights.has_environment_light() {
+                log::warn!("Only one dome light is supported, skipping {}", light.name);
+                return lights;
+            }
+            let mut dome = EnvironmentLight::new(transform, radiance, scene_radius);
+            if let Some(path) = light.texture.as_deref() {
+                match texture_format {
+                    DomeTextureFormat::Automatic | DomeTextureFormat::Latlong => {
+                        match textures.load(path) {
+                            Ok(texture) => dome = dome.with_texture(texture),
+                            Err(e) => log::warn!("Failed to load light texture {}: {}", path, e),
+                        }
+                    }
+                    format => log::warn!(
+                        "Dome light {} has a {:?} texture, only latlong maps are supported",
+                        light.name,
+                        format
+                    ),
+                }
+            }
+            return lights.with_environment_light(dome);
+        }
+    };
+    match &light.shaping {
+        Some(shaping) => {
+            if shaping.ies_file.is_some() {
+                log::warn!("IES profile of light {} isn't supported", light.name);
+            }
+            lights.with_shaped_light(surface, Shaping::new(light.direction(), shaping))
+        }
+        None => lights.with_hidden_light(surface),
+    }
+}
+
+/// Camera looking down -Z at the center of `bounds`, like the viewport's
+/// initial framing.
+fn framing_camera(bounds: &Aabb) -> Camera {
+    let (min, max) = (bounds.min_point(), bounds.max_point());
+    if !(min.cmple(max).all() && min.is_finite() && max.is_finite()) {
+        return Camera::new();
+    }
+
+    let center = (min + max) * 0.5;
+    let distance = ((max - min).length() * 1.5).max(1e-3);
+
+    Camera::new()
+        .with_position(center + Vec3::new(0.0, 0.0, distance), center, Vec3::Y)
+        .with_lens(45.0, 0.0, distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HitRecord, Ray};
+    use bif_core::{
+        CurveBasis, CurveSet, CurveShape, Curves, Instance, Material, Mesh, PointSet, Points,
+        Transform,
+    };
+    use bif_math::Interval;
+    use rand::SeedableRng;
+
+    fn quad_mesh() -> Arc<Mesh> {
+        let positions = vec![Vec3::ZERO, Vec3::X, Vec3::new(1.0, 1.0, 0.0), Vec3::Y];
+        Arc::new(Mesh::new(positions, vec![0, 1, 2, 0, 2, 3], None))
+    }
+
+    fn triangle_mesh() -> Arc<Mesh> {
+        Arc::new(Mesh::new(
+            vec![Vec3::ZERO, Vec3::X, Vec3::Y],
+            vec![0, 1, 2],
+            None,
+        ))
+    }
+
+    #[test]
+    fn test_builds_all_prototypes() {
+        let mut scene = Scene::new("test");
+        let light = scene.add_material(Material {
+            emissive_color: Vec3::ONE,
+            ..Default::default()
+        });
+        let tri = scene.add_prototype(triangle_mesh(), "tri".into());
+        let quad = scene.add_prototype(quad_mesh(), "quad".into());
+        scene.add_prototype(quad_mesh(), "uninstanced".into());
+        scene.add_instance(tri, Transform::from_translation(Vec3::ZERO));
+        scene.add_instance(quad, Transform::from_translation(Vec3::new(5.0, 0.0, 0.0)));
+        scene.instances.push(
+            Instance::with_translation(quad, Vec3::new(10.0, 0.0, 0.0))
+                .with_material(light as MaterialId),
+        );
+
+        let ivar = SceneBuilder::new(&scene).with_embree(false).build();
+
+        // Only the light-bound quad instance is emissive
+        assert_eq!(ivar.lights.len(), 2);
+
+        let hit = |x: f32, y: f32| {
+            let ray = Ray::new(Vec3::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+            let mut rec = HitRecord::default();
+            ivar.world
+                .hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec)
+        };
+        assert!(hit(0.25, 0.25));
+        assert!(!hit(0.75, 0.75));
+        assert!(hit(5.75, 0.75));
+        assert!(hit(10.75, 0.75));
+        assert_eq!(ivar.prototype_indices, [Some(0), Some(1), None]);
+    }
+
+    /// A unit quad in the XY plane whose UVs are rotated a quarter turn
+    /// (u = y, v = 1 - x), bound to `material` given a gray texture with
+    /// `texels` along u.
+    fn rotated_uv_quad(
+        name: &str,
+        texels: &[u8],
+        material: impl FnOnce(String) -> Material,
+    ) -> Scene {
+        let path = std::env::temp_dir().join(format!("bif_scene_builder_{name}.png"));
+        image::RgbImage::from_fn(texels.len() as u32, 1, |x, _| {
+            image::Rgb([texels[x as usize]; 3])
+        })
+        .save(&path)
+        .unwrap();
+
+        let mut scene = Scene::new("test");
+        let positions = vec![Vec3::ZERO, Vec3::X, Vec3::new(1.0, 1.0, 0.0), Vec3::Y];
+        let uvs = vec![[0.0, 1.0], [0.0, 0.0], [1.0, 0.0], [1.0, 1.0]];
+        let mesh = Mesh::new_with_uvs(positions, vec![0, 1, 2, 0, 2, 3], None, Some(uvs));
+        let proto = scene.add_prototype(Arc::new(mesh), "quad".into());
+        let material = Arc::new(material(path.to_string_lossy().into_owned()));
+        scene.prototypes[proto] =
+            Arc::new((*scene.prototypes[proto]).clone().with_material(material));
+        scene.add_instance(proto, Transform::from_translation(Vec3::ZERO));
+        scene
+    }
+
+    #[test]
+    fn test_hits_report_mesh_uvs() {
+        // Black at u = 0 and white at u = 1, blended in between
+        let scene = rotated_uv_quad("uvs", &[0, 255], |path| Material {
+            emissive_color: Vec3::ONE,
+            emissive_texture: Some(path),
+            ..Default::default()
+        });
+        let ivar = SceneBuilder::new(&scene).with_embree(false).build();
+
+        // One point on each triangle
+        for (x, y) in [(0.8, 0.2), (0.3, 0.7)] {
+            let ray = Ray::new(Vec3::new(x, y, 1.0), Vec3::NEG_Z, 0.0);
+            let mut rec = HitRecord::default();
+            assert!(ivar
+                .world
+                .hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+            assert!((rec.u - y).abs() < 1e-5 && (rec.v - (1.0 - x)).abs() < 1e-5);
+            let texel = rec.material.emitted(rec.u, rec.v, rec.p);
+            assert!(
+                (texel - Vec3::splat(y)).length() < 1e-3,
+                "{texel} at u = {y}"
+            );
+
+            // Tangent-space normal maps follow dP/du
+            assert!((rec.tangent - Vec3::Y).length() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_alpha_cutout_follows_mesh_uvs() {
+        // Opaque only where u > 0.5 (nearest texel), above y = 0.5 on the quad
+        let scene = rotated_uv_quad("cutout", &[0, 0, 255, 255], |path| Material {
+            opacity_texture: Some(path),
+            opacity_threshold: 0.5,
+            ..Default::default()
+        });
+        let ivar = SceneBuilder::new(&scene).with_embree(false).build();
+
+        // Barycentric u would be 0.3 on the second, cut out
+        for (x, y, opaque) in [(0.8, 0.2, false), (0.3, 0.7, true)] {
+            let ray = Ray::new(Vec3::new(x, y, 1.0), Vec3::NEG_Z, 0.0);
+            let mut rec = HitRecord::default();
+            let hit = ivar
+                .world
+                .hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec);
+            assert_eq!(hit, opaque, "at ({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn test_scene_lights_are_sampled() {
+        let mut scene = Scene::new("test");
+        let quad = scene.add_prototype(quad_mesh(), "quad".into());
+        scene.add_instance(quad, Transform::from_translation(Vec3::ZERO));
+
+        // A 2x2 rect light normalized to unit radiance, facing the quad
+        let mut rect = Light::new(
+            "/Rect",
+            LightKind::Rect {
+                width: 2.0,
+                height: 2.0,
+            },
+        )
+        .with_transform(Mat4::from_translation(Vec3::new(0.5, 0.5, 2.0)));
+        rect.intensity = 4.0;
+        rect.normalize = true;
+        scene.add_light(rect);
+
+        // A sphere light scaled to radius 1
+        let sphere = LightKind::from_type_name("SphereLight").unwrap();
+        scene.add_light(Light::new("/Sphere", sphere).with_transform(
+            Mat4::from_translation(Vec3::new(0.0, 0.0, 5.0)) * Mat4::from_scale(Vec3::splat(2.0)),
+        ));
+
+        // A distant light delivering unit irradiance, and a dome
+        let mut sun = Light::new("/Sun", LightKind::from_type_name("DistantLight").unwrap());
+        sun.normalize = true;
+        scene.add_light(sun);
+        scene.add_light(Light::new(
+            "/Dome",
+            LightKind::from_type_name("DomeLight").unwrap(),
+        ));
+
+        let ivar = SceneBuilder::new(&scene).with_embree(false).build();
+        assert_eq!(ivar.lights.len(), 4);
+
+        // The distant and dome lights' power falls on the quad's bounding
+        // sphere
+        let scene_radius_squared = 0.5;
+        let expected =
+            4.0 + 4.0 * PI + PI * scene_radius_squared + 4.0 * PI * PI * scene_radius_squared;
+        assert!((ivar.lights.total_power() - expected).abs() < 1e-3);
+        assert_eq!(ivar.lights.environment(Vec3::Y).unwrap().0, Color::ONE);
+
+        // They survive rebuilding the lights after edits
+        let rebuilt = ivar.instanced_lights.light_list();
+        assert_eq!(rebuilt.len(), 4);
+        assert!((rebuilt.total_power() - expected).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_displacement_moves_surface() {
+        // The surface moves up to the displacement bound
+        let t = displaced_hit(255);
+        assert!((t - 0.75).abs() < 1e-4, "hit at t = {t}");
+
+        // Displacement maps are data: 64 is 0.25, not sRGB-decoded
+        let t = displaced_hit(64);
+        let expected = 1.0 - 0.5 * 64.0 / 255.0;
+        assert!((t - expected).abs() < 1e-4, "hit at t = {t}");
+    }
+
+    /// Distance to a quad displaced by 0.5 times a constant texture `value`,
+    /// bounded at 0.25, along a ray starting 1 above it.
+    fn displaced_hit(value: u8) -> f32 {
+        let path = std::env::temp_dir().join(format!("bif_scene_builder_displacement_{value}.png"));
+        image::RgbImage::from_pixel(1, 1, image::Rgb([value; 3]))
+            .save(&path)
+            .unwrap();
+
+        let mut scene = Scene::new("test");
+        let material = Arc::new(Material {
+            displacement_texture: Some(path.to_string_lossy().into_owned()),
+            displacement_scale: 0.5,
+            ..Default::default()
+        });
+        let positions = vec![Vec3::ZERO, Vec3::X, Vec3::new(1.0, 1.0, 0.0), Vec3::Y];
+        let uvs = vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        let mesh = Mesh::new_with_uvs(
+            positions,
+            vec![0, 1, 2, 0, 2, 3],
+            Some(vec![Vec3::Z; 4]),
+            Some(uvs),
+        );
+        let proto = scene.add_prototype(Arc::new(mesh), "displaced".into());
+        let bounded = (*scene.prototypes[proto])
+            .clone()
+            .with_material(material)
+            .with_displacement_bound(0.25);
+        scene.prototypes[proto] = Arc::new(bounded);
+        scene.add_instance(proto, Transform::from_translation(Vec3::ZERO));
+
+        let ivar = SceneBuilder::new(&scene)
+            .with_embree(false)
+            .with_tessellation(TessellationSettings {
+                max_edge_length: Some(0.5),
+                max_level: 4,
+            })
+            .build();
+
+        let ray = Ray::new(Vec3::new(0.3, 0.6, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(ivar
+            .world
+            .hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+
+        std::fs::remove_file(path).ok();
+        rec.t
+    }
+
+    #[test]
+    fn test_curves_are_traced_as_hair() {
+        let mut scene = Scene::new("test");
+        let quad = scene.add_prototype(quad_mesh(), "quad".into());
+        scene.add_instance(quad, Transform::from_translation(Vec3::ZERO));
+
+        // A strand standing in front of the quad
+        let positions = (0..4)
+            .map(|i| Vec3::new(0.0, i as f32 / 3.0, 0.0))
+            .collect();
+        let curves = Curves::new(
+            positions,
+            vec![4],
+            &[0.1],
+            CurveBasis::Linear,
+            CurveShape::Round,
+        );
+        scene.add_curves(CurveSet::new(
+            "hair",
+            Arc::new(curves),
+            Mat4::from_translation(Vec3::new(0.5, 0.0, 0.5)),
+        ));
+
+        let ivar = SceneBuilder::new(&scene).with_embree(false).build();
+
+        let ray = Ray::new(Vec3::new(0.5, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(ivar
+            .world
+            .hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.t - 0.5).abs() < 1e-4, "hit at t = {}", rec.t);
+        assert!((rec.tangent - Vec3::Y).length() < 1e-4);
+
+        // Beside the strand the quad is hit, with no tangent
+        let ray = Ray::new(Vec3::new(0.25, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(ivar
+            .world
+            .hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.t - 1.0).abs() < 1e-4);
+        assert_eq!(rec.tangent, Vec3::ZERO);
+    }
+
+    #[test]
+    fn test_points_only_scene() {
+        let mut scene = Scene::new("test");
+        let positions = vec![Vec3::ZERO, Vec3::new(2.0, 0.0, 0.0)];
+        scene.add_points(PointSet::new(
+            "particles",
+            Arc::new(Points::new(positions, &[0.5])),
+            Mat4::from_translation(Vec3::new(0.0, 1.0, 0.0)),
+        ));
+
+        let ivar = SceneBuilder::new(&scene).with_embree(false).build();
+
+        let ray = Ray::new(Vec3::new(2.0, 1.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(ivar
+            .world
+            .hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert!((rec.t - 4.75).abs() < 1e-4, "hit at t = {}", rec.t);
+        assert!((rec.p - Vec3::new(2.0, 1.0, 0.25)).length() < 1e-4);
+
+        let ray = Ray::new(Vec3::new(1.0, 1.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert!(!ivar
+            .world
+            .hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+    }
+
+    #[test]
+    fn test_motion_keys() {
+        // A triangle that rises by 1 while its instance slides 4 along X
+        let mesh =
+            Mesh::new(vec![Vec3::ZERO, Vec3::X, Vec3::Y], vec![0, 1, 2], None).with_motion(vec![
+                vec![Vec3::Z, Vec3::new(1.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 1.0)],
+            ]);
+        let mut scene = Scene::new("test");
+        let tri = scene.add_prototype(Arc::new(mesh), "tri".into());
+        scene.instances.push(
+            Instance::new(tri, Transform::default())
+                .with_motion(vec![Transform::from_translation(Vec3::new(4.0, 0.0, 0.0))]),
+        );
+
+        let ivar = SceneBuilder::new(&scene).with_embree(false).build();
+        let hit_t = |x: f32, time: f32| {
+            let ray = Ray::new(Vec3::new(x, 0.25, 2.0), Vec3::new(0.0, 0.0, -1.0), time);
+            let mut rec = HitRecord::default();
+            ivar.world
+                .hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec)
+                .then_some(rec.t)
+        };
+
+        assert_eq!(hit_t(0.25, 0.0), Some(2.0));
+        assert_eq!(hit_t(4.25, 0.0), None);
+        assert!((hit_t(4.25, 1.0).unwrap() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_framing_camera_sees_scene() {
+        let mut scene = Scene::new("test");
+        let quad = scene.add_prototype(quad_mesh(), "quad".into());
+        scene.add_instance(
+            quad,
+            Transform::from_translation(Vec3::new(-0.5, -0.5, 0.0)),
+        );
+
+        let ivar = SceneBuilder::new(&scene).with_embree(false).build();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let (w, h) = (ivar.camera.image_width, ivar.camera.image_height);
+        let ray = ivar.camera.get_ray(w / 2, h / 2, &mut rng);
+        let mut rec = HitRecord::default();
+        assert!(ivar
+            .world
+            .hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+    }
+
+    #[test]
+    fn test_camera_path_looks_through_scene_camera() {
+        let mut scene = Scene::new("test");
+        let quad = scene.add_prototype(quad_mesh(), "quad".into());
+        scene.add_instance(
+            quad,
+            Transform::from_translation(Vec3::new(-0.5, -0.5, 0.0)),
+        );
+        // One camera facing the quad, one facing away from it
+        scene.add_camera(
+            bif_core::Camera::new("/Front")
+                .with_transform(Mat4::from_translation(Vec3::new(0.0, 0.0, 5.0))),
+        );
+        scene.add_camera(bif_core::Camera::new("/Back").with_transform(
+            Mat4::from_translation(Vec3::new(0.0, 0.0, 5.0))
+                * Mat4::from_rotation_y(std::f32::consts::PI),
+        ));
+
+        let center_hit = |path: &str| {
+            let ivar = SceneBuilder::new(&scene)
+                .with_camera(Camera::new().with_resolution(64, 32))
+                .with_camera_path(path)
+                .with_embree(false)
+                .build();
+            assert_eq!(ivar.camera.image_width, 64);
+            let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+            let ray = ivar.camera.get_ray(32, 16, &mut rng);
+            let mut rec = HitRecord::default();
+            ivar.world
+                .hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec)
+        };
+
+        assert!(center_hit("/Front"));
+        assert!(!center_hit("/Back"));
+        // Unknown cameras fall back to the given camera
+        assert!(!center_hit("/Missing"));
+    }
+}
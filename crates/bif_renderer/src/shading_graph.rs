@@ -0,0 +1,773 @@
+//! MaterialX shading graph evaluation.
+//!
+//! Evaluates a `bif_core::ShadingGraph` at each hit point and shades with
+//! the resulting Disney BSDF. Supports the common MaterialX stdlib nodes:
+//! `image`, `multiply`, `add`, `mix`, `ramplr`/`ramptb`/`ramp4`,
+//! `normalmap`, `constant`, `texcoord`, `dot` and `standard_surface`.
+//!
+//! Unsupported nodes evaluate to nothing and the input falls back to its
+//! MaterialX default, so partially supported graphs still render. Graphs
+//! with cycles are rejected when the material is built; the others are
+//! evaluated node by node in dependency order, each node once per hit.
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use bif_core::{ShadingGraph, ShadingInput, ShadingNode, Texture, TextureCache};
+use bif_math::Vec3;
+use rand::RngCore;
+
+use crate::material::{build_tangent_basis, Color, MaterialProperties, ScatterResult};
+use crate::{hittable::HitRecord, DisneyBSDF, Material, Ray, Subsurface};
+
+/// Source of [`ShadingGraphMaterial`] identities for the shade cache.
+static NEXT_MATERIAL_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// Last surface shaded on this thread, keyed by material and shading
+    /// point, so `scatter`, `bsdf` and `pdf` at one hit share an evaluation
+    static LAST_SHADE: RefCell<Option<(ShadeKey, ShadedSurface)>> = const { RefCell::new(None) };
+}
+
+/// Material identity, UV and normal bits of a shading point.
+type ShadeKey = (u64, [u32; 8]);
+
+/// Surface properties produced by evaluating a graph at a shading point.
+#[derive(Clone)]
+pub struct ShadedSurface {
+    /// BSDF parameters at this point
+    pub bsdf: DisneyBSDF,
+    /// Shading normal (after normal mapping)
+    pub normal: Vec3,
+    /// Emitted radiance
+    pub emission: Color,
+    /// Opacity (0=transparent, 1=opaque)
+    pub opacity: f32,
+}
+
+/// Geometric inputs available to graph nodes.
+#[derive(Clone, Copy)]
+struct ShadingContext {
+    u: f32,
+    v: f32,
+    normal: Vec3,
+    /// dP/du of the surface (zero if unknown)
+    tangent: Vec3,
+}
+
+/// Material that evaluates a MaterialX node graph per hit.
+///
+/// Materials without a graph behave exactly like their `DisneyBSDF`
+/// conversion, so this can be used for every material in a scene.
+#[derive(Clone)]
+pub struct ShadingGraphMaterial {
+    /// Identity for the shade cache (clones shade alike and share it)
+    id: u64,
+    /// Node network (None = constant material)
+    graph: Option<Arc<ShadingGraph>>,
+    /// Graph nodes in dependency order, without the surface shader
+    order: Arc<[usize]>,
+    /// Loaded textures, indexed by node
+    textures: Vec<Option<Arc<Texture>>>,
+    /// Constant BSDF used when no graph is present
+    fallback: DisneyBSDF,
+    /// Constant emission used when no graph is present
+    fallback_emission: Color,
+    /// Constant opacity used when no graph is present
+    fallback_opacity: f32,
+    /// Emission texture used when no graph is present (replaces the color)
+    emission_texture: Option<Arc<Texture>>,
+    /// True if the material can emit light anywhere
+    emissive: bool,
+    /// Opacity mask texture and the channel it is read from
+    opacity_texture: Option<(Arc<Texture>, usize)>,
+    /// Cutout threshold (0 = stochastic partial opacity)
+    opacity_threshold: f32,
+    /// True if the graph's surface drives opacity (needs per-hit evaluation)
+    graph_opacity: bool,
+}
+
+impl ShadingGraphMaterial {
+    /// Create a material from a scene material, loading any graph textures.
+    ///
+    /// Textures that fail to load are logged and treated as missing.
+    pub fn new(material: &bif_core::Material, textures: &mut TextureCache) -> Self {
+        let graph = valid_graph(material.shading_graph.clone(), &material.name);
+
+        let node_textures = graph
+            .as_ref()
+            .map(|g| {
+                g.nodes
+                    .iter()
+                    .map(|node| load_node_texture(node, textures))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Masks are non-color data: no sRGB decode
+        let opacity_texture = material.opacity_texture.as_deref().and_then(|path| {
+            textures
+                .load_linear(path)
+                .map_err(|e| {
+                    log::warn!(
+                        "Failed to load opacity texture for {}: {}",
+                        material.name,
+                        e
+                    )
+                })
+                .ok()
+                .map(|texture| (texture, material.opacity_texture_channel))
+        });
+        let emission_texture = material.emissive_texture.as_deref().and_then(|path| {
+            textures
+                .load(path)
+                .map_err(|e| {
+                    log::warn!(
+                        "Failed to load emissive texture for {}: {}",
+                        material.name,
+                        e
+                    )
+                })
+                .ok()
+        });
+        let graph_opacity = graph.as_ref().is_some_and(|g| has_opacity_input(g));
+        let emissive = match &graph {
+            Some(g) => has_emission(g),
+            None => material.is_emissive(),
+        };
+
+        Self {
+            id: NEXT_MATERIAL_ID.fetch_add(1, Ordering::Relaxed),
+            order: evaluation_order(graph.as_deref()),
+            graph,
+            textures: node_textures,
+            fallback: DisneyBSDF::from(material),
+            fallback_emission: material.emissive_color,
+            emission_texture,
+            emissive,
+            fallback_opacity: material.opacity,
+            opacity_texture,
+            opacity_threshold: material.opacity_threshold,
+            graph_opacity,
+        }
+    }
+
+    /// Create a material from a graph with pre-loaded textures (by node index).
+    ///
+    /// An invalid graph (dangling connections or cycles) is logged and
+    /// replaced by the default material.
+    pub fn from_graph(graph: Arc<ShadingGraph>, textures: Vec<Option<Arc<Texture>>>) -> Self {
+        let graph = valid_graph(Some(graph), "graph material");
+        let graph_opacity = graph.as_ref().is_some_and(|g| has_opacity_input(g));
+        let emissive = graph.as_ref().is_some_and(|g| has_emission(g));
+        Self {
+            id: NEXT_MATERIAL_ID.fetch_add(1, Ordering::Relaxed),
+            order: evaluation_order(graph.as_deref()),
+            graph,
+            textures,
+            fallback: DisneyBSDF::default(),
+            fallback_emission: Color::ZERO,
+            emission_texture: None,
+            emissive,
+            fallback_opacity: 1.0,
+            opacity_texture: None,
+            opacity_threshold: 0.0,
+            graph_opacity,
+        }
+    }
+
+    /// Check if this material is driven by a node graph.
+    pub fn has_graph(&self) -> bool {
+        self.graph.is_some()
+    }
+
+    /// Evaluate the surface at texture coordinates (u, v) with geometric normal `normal`.
+    pub fn shade(&self, u: f32, v: f32, normal: Vec3) -> ShadedSurface {
+        self.shade_with_tangent(u, v, normal, Vec3::ZERO)
+    }
+
+    /// Like [`Self::shade`], with the surface tangent (dP/du) that
+    /// tangent-space normal maps are authored in. A zero tangent falls back
+    /// to an arbitrary frame around the normal.
+    pub fn shade_with_tangent(&self, u: f32, v: f32, normal: Vec3, tangent: Vec3) -> ShadedSurface {
+        let ctx = ShadingContext {
+            u,
+            v,
+            normal,
+            tangent,
+        };
+
+        let Some(graph) = &self.graph else {
+            return ShadedSurface {
+                bsdf: self.fallback.clone(),
+                normal,
+                emission: self.fallback_emission_at(u, v),
+                opacity: 1.0,
+            };
+        };
+
+        let key = (
+            self.id,
+            [
+                u, v, normal.x, normal.y, normal.z, tangent.x, tangent.y, tangent.z,
+            ]
+            .map(f32::to_bits),
+        );
+        let cached = LAST_SHADE.with(|last| {
+            last.borrow()
+                .as_ref()
+                .filter(|(last_key, _)| *last_key == key)
+                .map(|(_, surface)| surface.clone())
+        });
+        if let Some(surface) = cached {
+            return surface;
+        }
+
+        let surface =
+            GraphEval::new(graph, &self.textures, &self.order, ctx).standard_surface(graph.surface);
+        LAST_SHADE.with(|last| *last.borrow_mut() = Some((key, surface.clone())));
+        surface
+    }
+
+    /// Emission of the fallback material at (u, v).
+    fn fallback_emission_at(&self, u: f32, v: f32) -> Color {
+        match &self.emission_texture {
+            Some(texture) => texture.sample(u, v),
+            None => self.fallback_emission,
+        }
+    }
+
+    /// Build a hit record whose normal is the graph's shading normal.
+    fn shaded_record<'a>(&self, rec: &HitRecord<'a>, surface: &ShadedSurface) -> HitRecord<'a> {
+        let mut shaded = rec.clone();
+        shaded.normal = surface.normal;
+        shaded
+    }
+}
+
+impl Material for ShadingGraphMaterial {
+    fn scatter(
+        &self,
+        ray_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<ScatterResult> {
+        let surface = self.shade_with_tangent(rec.u, rec.v, rec.normal, rec.tangent);
+        surface
+            .bsdf
+            .scatter(ray_in, &self.shaded_record(rec, &surface), rng)
+    }
+
+    fn bsdf(&self, ray_in: &Ray, rec: &HitRecord, scattered: &Ray) -> Color {
+        let surface = self.shade_with_tangent(rec.u, rec.v, rec.normal, rec.tangent);
+        surface
+            .bsdf
+            .bsdf(ray_in, &self.shaded_record(rec, &surface), scattered)
+    }
+
+    fn pdf(&self, ray_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f32 {
+        let surface = self.shade_with_tangent(rec.u, rec.v, rec.normal, rec.tangent);
+        surface
+            .bsdf
+            .pdf(ray_in, &self.shaded_record(rec, &surface), scattered)
+    }
+
+    fn emitted(&self, u: f32, v: f32, _p: Vec3) -> Color {
+        if self.graph.is_none() {
+            return self.fallback_emission_at(u, v);
+        }
+        self.shade(u, v, Vec3::Z).emission
+    }
+
+    fn properties(&self) -> MaterialProperties {
+        MaterialProperties {
+            is_emissive: self.emissive,
+            ..self.fallback.properties()
+        }
+    }
+
+    fn subsurface(&self, u: f32, v: f32) -> Option<Subsurface> {
+        self.graph.as_ref()?;
+        self.shade(u, v, Vec3::Z).bsdf.subsurface(u, v)
+    }
+
+    fn opacity(&self, u: f32, v: f32) -> f32 {
+        let opacity = if self.graph_opacity {
+            self.shade(u, v, Vec3::Z).opacity
+        } else {
+            let mask = self
+                .opacity_texture
+                .as_ref()
+                .map_or(1.0, |(texture, channel)| {
+                    texture.sample_channel(u, v, *channel)
+                });
+            self.fallback_opacity * mask
+        };
+
+        if self.opacity_threshold > 0.0 {
+            if opacity >= self.opacity_threshold {
+                1.0
+            } else {
+                0.0
+            }
+        } else {
+            opacity
+        }
+    }
+}
+
+/// Keep a graph only if it is valid (see [`ShadingGraph::is_valid`]).
+fn valid_graph(graph: Option<Arc<ShadingGraph>>, name: &str) -> Option<Arc<ShadingGraph>> {
+    graph.filter(|g| {
+        let valid = g.is_valid();
+        if !valid {
+            log::warn!("Ignoring invalid shading graph on {}", name);
+        }
+        valid
+    })
+}
+
+/// Nodes to evaluate before a graph's surface shader, in dependency order.
+fn evaluation_order(graph: Option<&ShadingGraph>) -> Arc<[usize]> {
+    let Some(graph) = graph else {
+        return Arc::from([]);
+    };
+    graph
+        .evaluation_order()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|&node| node != graph.surface)
+        .collect()
+}
+
+/// Check if a graph's surface shader has an `opacity` input.
+fn has_opacity_input(graph: &ShadingGraph) -> bool {
+    graph
+        .nodes
+        .get(graph.surface)
+        .is_some_and(|surface| surface.input("opacity").is_some())
+}
+
+/// Check if a graph's surface shader can emit light.
+///
+/// A connected `emission` input counts as emissive; a constant one only if
+/// it is non-zero.
+fn has_emission(graph: &ShadingGraph) -> bool {
+    let Some(surface) = graph.nodes.get(graph.surface) else {
+        return false;
+    };
+    match surface.input("emission") {
+        Some(ShadingInput::Value(value)) => value.as_float().is_some_and(|e| e > 0.0),
+        Some(ShadingInput::Connection { .. }) => true,
+        None => false,
+    }
+}
+
+/// Load the texture referenced by an image node, if any.
+///
+/// Only color images are sRGB decoded; float and vector images (roughness,
+/// metalness, normal maps) hold linear data.
+fn load_node_texture(node: &ShadingNode, textures: &mut TextureCache) -> Option<Arc<Texture>> {
+    if !node.category().contains("image") {
+        return None;
+    }
+    let path = match node.input("file") {
+        Some(ShadingInput::Value(value)) => value.as_str()?,
+        _ => return None,
+    };
+    if path.is_empty() {
+        return None;
+    }
+
+    let texture = if node.shader_id.contains("color") {
+        textures.load(path)
+    } else {
+        textures.load_linear(path)
+    };
+    match texture {
+        Ok(texture) => Some(texture),
+        Err(e) => {
+            log::warn!("Failed to load texture for {}: {}", node.name, e);
+            None
+        }
+    }
+}
+
+/// Per-hit graph evaluator.
+struct GraphEval<'g> {
+    graph: &'g ShadingGraph,
+    textures: &'g [Option<Arc<Texture>>],
+    ctx: ShadingContext,
+    /// Value of each node evaluated so far (by node index)
+    values: Vec<Option<Vec3>>,
+}
+
+impl<'g> GraphEval<'g> {
+    /// Evaluate every node in `order` once, inputs before the nodes they feed.
+    fn new(
+        graph: &'g ShadingGraph,
+        textures: &'g [Option<Arc<Texture>>],
+        order: &[usize],
+        ctx: ShadingContext,
+    ) -> Self {
+        let mut eval = Self {
+            graph,
+            textures,
+            ctx,
+            values: vec![None; graph.nodes.len()],
+        };
+        for &node in order {
+            eval.values[node] = eval.eval_node(node);
+        }
+        eval
+    }
+
+    /// Evaluate a standard_surface node (or any surface node with the same inputs).
+    fn standard_surface(&self, node: usize) -> ShadedSurface {
+        let scalar = |name: &str, default: f32| self.input_float(node, name).unwrap_or(default);
+        let color = |name: &str, default: Vec3| self.input(node, name).unwrap_or(default);
+
+        let base = scalar("base", 0.8);
+        let base_color = color("base_color", Vec3::ONE);
+        let metalness = scalar("metalness", 0.0);
+        let specular = scalar("specular", 1.0);
+        let specular_roughness = scalar("specular_roughness", 0.2);
+        let specular_ior = scalar("specular_IOR", 1.5);
+        let emission = scalar("emission", 0.0);
+        let emission_color = color("emission_color", Vec3::ONE);
+        let opacity = self.input(node, "opacity").unwrap_or(Vec3::ONE);
+
+        // Disney specular 0.5 == IOR 1.5 (F0 = 0.04), so specular = F0 / 0.08
+        let f0 = ((specular_ior - 1.0) / (specular_ior + 1.0)).powi(2);
+        let mut bsdf = DisneyBSDF::new()
+            .with_base_color(base_color * base)
+            .with_metallic(metalness)
+            .with_roughness(specular_roughness)
+            .with_specular(specular * f0 / 0.08);
+        bsdf.sheen = scalar("sheen", 0.0);
+        let subsurface = scalar("subsurface", 0.0);
+        if subsurface > 0.0 {
+            let radius = color("subsurface_radius", Vec3::ONE) * scalar("subsurface_scale", 1.0);
+            let subsurface_color = color("subsurface_color", Vec3::ONE);
+            bsdf = bsdf.with_random_walk_subsurface(subsurface, subsurface_color, radius);
+        }
+        bsdf.clearcoat = scalar("coat", 0.0);
+        bsdf.clearcoat_gloss = 1.0 - scalar("coat_roughness", 0.1);
+
+        let normal = self
+            .input(node, "normal")
+            .filter(|n| n.length_squared() > 1e-8)
+            .map(|n| n.normalize())
+            .unwrap_or(self.ctx.normal);
+
+        ShadedSurface {
+            bsdf,
+            normal,
+            emission: emission_color * emission,
+            opacity: (opacity.x + opacity.y + opacity.z) / 3.0,
+        }
+    }
+
+    /// Evaluate a named input of a node as a vector (scalars are splatted).
+    fn input(&self, node: usize, name: &str) -> Option<Vec3> {
+        match self.graph.nodes.get(node)?.input(name)? {
+            ShadingInput::Value(value) => value.as_vec3(),
+            ShadingInput::Connection { node, output } => self.output(*node, output),
+        }
+    }
+
+    /// Evaluate a named input of a node as a scalar.
+    fn input_float(&self, node: usize, name: &str) -> Option<f32> {
+        match self.graph.nodes.get(node)?.input(name)? {
+            ShadingInput::Value(value) => value.as_float(),
+            ShadingInput::Connection { node, output } => self.output(*node, output).map(|v| v.x),
+        }
+    }
+
+    /// Read an output of an evaluated node (`outr`/`outg`/`outb` select a
+    /// channel; float nodes splat their first one).
+    fn output(&self, node: usize, output: &str) -> Option<Vec3> {
+        let value = (*self.values.get(node)?)?;
+        Some(match output {
+            "outr" | "r" => Vec3::splat(value.x),
+            "outg" | "g" => Vec3::splat(value.y),
+            "outb" | "b" => Vec3::splat(value.z),
+            _ if self.graph.nodes[node].shader_id.ends_with("_float") => Vec3::splat(value.x),
+            _ => value,
+        })
+    }
+
+    /// Texture coordinate for a node (its `texcoord` input or the hit UV).
+    fn texcoord(&self, node: usize) -> (f32, f32) {
+        self.input(node, "texcoord")
+            .map(|t| (t.x, t.y))
+            .unwrap_or((self.ctx.u, self.ctx.v))
+    }
+
+    /// Evaluate a node from the values of the nodes feeding it.
+    fn eval_node(&self, index: usize) -> Option<Vec3> {
+        let node = self.graph.nodes.get(index)?;
+        let input = |name: &str| self.input(index, name);
+        let scalar = |name: &str| self.input_float(index, name);
+
+        match node.category() {
+            "image" | "tiledimage" => {
+                let (u, v) = self.texcoord(index);
+                let Some(texture) = self.textures.get(index).and_then(|t| t.as_ref()) else {
+                    return Some(input("default").unwrap_or(Vec3::ZERO));
+                };
+                Some(texture.sample(u, v))
+            }
+            "multiply" => {
+                Some(input("in1").unwrap_or(Vec3::ZERO) * input("in2").unwrap_or(Vec3::ONE))
+            }
+            "add" => Some(input("in1").unwrap_or(Vec3::ZERO) + input("in2").unwrap_or(Vec3::ZERO)),
+            "mix" => {
+                let fg = input("fg").unwrap_or(Vec3::ZERO);
+                let bg = input("bg").unwrap_or(Vec3::ZERO);
+                let t = scalar("mix").unwrap_or(0.0);
+                Some(bg.lerp(fg, t))
+            }
+            "ramplr" => {
+                let (s, _) = self.texcoord(index);
+                let l = input("valuel").unwrap_or(Vec3::ZERO);
+                let r = input("valuer").unwrap_or(Vec3::ZERO);
+                Some(l.lerp(r, s.clamp(0.0, 1.0)))
+            }
+            "ramptb" => {
+                let (_, t) = self.texcoord(index);
+                let top = input("valuet").unwrap_or(Vec3::ZERO);
+                let bottom = input("valueb").unwrap_or(Vec3::ZERO);
+                Some(top.lerp(bottom, t.clamp(0.0, 1.0)))
+            }
+            "ramp4" => {
+                let (s, t) = self.texcoord(index);
+                let (s, t) = (s.clamp(0.0, 1.0), t.clamp(0.0, 1.0));
+                let top = input("valuetl")
+                    .unwrap_or(Vec3::ZERO)
+                    .lerp(input("valuetr").unwrap_or(Vec3::ZERO), s);
+                let bottom = input("valuebl")
+                    .unwrap_or(Vec3::ZERO)
+                    .lerp(input("valuebr").unwrap_or(Vec3::ZERO), s);
+                Some(top.lerp(bottom, t))
+            }
+            "normalmap" => {
+                let encoded = input("in").unwrap_or(Vec3::new(0.5, 0.5, 1.0));
+                let scale = scalar("scale").unwrap_or(1.0);
+                let n = input("normal")
+                    .map(|n| n.normalize_or_zero())
+                    .filter(|n| *n != Vec3::ZERO)
+                    .unwrap_or(self.ctx.normal);
+                // Frame from the authored or surface tangent, made
+                // orthogonal to the normal
+                let tangent = input("tangent")
+                    .filter(|t| *t != Vec3::ZERO)
+                    .unwrap_or(self.ctx.tangent);
+                let tangent = (tangent - n * n.dot(tangent)).normalize_or_zero();
+                let tangent = if tangent == Vec3::ZERO {
+                    build_tangent_basis(n).0
+                } else {
+                    tangent
+                };
+                let bitangent = n.cross(tangent);
+
+                let mut local = encoded * 2.0 - Vec3::ONE;
+                local.x *= scale;
+                local.y *= scale;
+                let world = tangent * local.x + bitangent * local.y + n * local.z;
+                Some(world.normalize_or_zero())
+            }
+            "constant" => input("value"),
+            "dot" => input("in"),
+            "texcoord" => Some(Vec3::new(self.ctx.u, self.ctx.v, 0.0)),
+            other => {
+                log::debug!("Unsupported MaterialX node '{}' ({})", other, node.name);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bif_core::{ShadingNode, ShadingValue};
+
+    fn surface_with(graph: &mut ShadingGraph, input: &str, node: usize) {
+        graph.surface = graph.add_node(
+            ShadingNode::new("surf", "ND_standard_surface_surfaceshader")
+                .with_value("base", ShadingValue::Float(1.0))
+                .with_connection(input, node, "out"),
+        );
+    }
+
+    #[test]
+    fn test_constant_material_matches_disney() {
+        let mat = bif_core::Material {
+            metallic: 0.7,
+            roughness: 0.3,
+            ..Default::default()
+        };
+        let graph_mat = ShadingGraphMaterial::new(&mat, &mut TextureCache::new());
+        assert!(!graph_mat.has_graph());
+
+        let surface = graph_mat.shade(0.5, 0.5, Vec3::Y);
+        assert!((surface.bsdf.metallic - 0.7).abs() < 1e-6);
+        assert!((surface.bsdf.roughness - 0.3).abs() < 1e-6);
+        assert_eq!(surface.normal, Vec3::Y);
+    }
+
+    #[test]
+    fn test_image_multiply_mix() {
+        let mut graph = ShadingGraph::new();
+        let image = graph.add_node(ShadingNode::new("img", "ND_image_color3"));
+        let tint = graph.add_node(
+            ShadingNode::new("tint", "ND_multiply_color3")
+                .with_connection("in1", image, "out")
+                .with_value("in2", ShadingValue::Vector3(Vec3::new(1.0, 0.5, 0.0))),
+        );
+        let mix = graph.add_node(
+            ShadingNode::new("mix", "ND_mix_color3")
+                .with_connection("fg", tint, "out")
+                .with_value("bg", ShadingValue::Vector3(Vec3::ZERO))
+                .with_value("mix", ShadingValue::Float(0.5)),
+        );
+        surface_with(&mut graph, "base_color", mix);
+
+        let white = Arc::new(Texture::solid_color(Vec3::ONE));
+        let textures = vec![Some(white), None, None, None];
+        let mat = ShadingGraphMaterial::from_graph(Arc::new(graph), textures);
+
+        let color = mat.shade(0.25, 0.25, Vec3::Y).bsdf.base_color;
+        assert!((color - Vec3::new(0.5, 0.25, 0.0)).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_ramplr_follows_texcoord() {
+        let mut graph = ShadingGraph::new();
+        let ramp = graph.add_node(
+            ShadingNode::new("ramp", "ND_ramplr_color3")
+                .with_value("valuel", ShadingValue::Vector3(Vec3::ZERO))
+                .with_value("valuer", ShadingValue::Vector3(Vec3::ONE)),
+        );
+        surface_with(&mut graph, "base_color", ramp);
+        let mat = ShadingGraphMaterial::from_graph(Arc::new(graph), vec![None, None]);
+
+        let left = mat.shade(0.0, 0.5, Vec3::Y).bsdf.base_color;
+        let mid = mat.shade(0.5, 0.5, Vec3::Y).bsdf.base_color;
+        assert!(left.length() < 1e-6);
+        assert!((mid - Vec3::splat(0.5)).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_normalmap_flat_and_tilted() {
+        let mut graph = ShadingGraph::new();
+        let nmap = graph.add_node(
+            ShadingNode::new("nmap", "ND_normalmap")
+                .with_value("in", ShadingValue::Vector3(Vec3::new(0.5, 0.5, 1.0))),
+        );
+        surface_with(&mut graph, "normal", nmap);
+        let mat = ShadingGraphMaterial::from_graph(Arc::new(graph.clone()), vec![None, None]);
+        let n = mat.shade(0.0, 0.0, Vec3::Y).normal;
+        assert!((n - Vec3::Y).length() < 1e-4);
+
+        graph.nodes[nmap] = ShadingNode::new("nmap", "ND_normalmap")
+            .with_value("in", ShadingValue::Vector3(Vec3::new(1.0, 0.5, 0.5)));
+        let mat = ShadingGraphMaterial::from_graph(Arc::new(graph), vec![None, None]);
+        let n = mat.shade(0.0, 0.0, Vec3::Y).normal;
+        assert!((n.length() - 1.0).abs() < 1e-4);
+        assert!(n.dot(Vec3::Y) < 0.99);
+
+        // Tilted towards +u, along the surface tangent
+        for tangent in [Vec3::X, Vec3::Z, Vec3::new(0.2, 0.0, -2.0)] {
+            let n = mat.shade_with_tangent(0.0, 0.0, Vec3::Y, tangent).normal;
+            assert!(
+                (n - tangent.normalize()).length() < 1e-4,
+                "{n} for {tangent}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_opacity_texture_and_threshold() {
+        // Loaded through the cache: 102/255 must read as 0.4, not sRGB decoded
+        let path = std::env::temp_dir().join("bif_shading_graph_opacity.png");
+        image::GrayImage::from_fn(4, 1, |x, _| image::Luma([[0, 102, 255, 255][x as usize]]))
+            .save(&path)
+            .unwrap();
+        let mut textures = TextureCache::new();
+
+        let mut mat = bif_core::Material {
+            opacity_texture: Some(path.to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        let partial = ShadingGraphMaterial::new(&mat, &mut textures);
+        assert_eq!(partial.opacity(0.0, 0.5), 0.0);
+        assert!((partial.opacity(0.4, 0.5) - 0.4).abs() < 1e-6);
+        assert_eq!(partial.opacity(0.9, 0.5), 1.0);
+
+        mat.opacity_threshold = 0.5;
+        let cutout = ShadingGraphMaterial::new(&mat, &mut textures);
+        assert_eq!(cutout.opacity(0.4, 0.5), 0.0);
+        assert_eq!(cutout.opacity(0.9, 0.5), 1.0);
+    }
+
+    #[test]
+    fn test_emission_and_cycle_rejection() {
+        let mut graph = ShadingGraph::new();
+        graph.add_node(
+            ShadingNode::new("tint", "ND_multiply_color3")
+                .with_value("in1", ShadingValue::Vector3(Vec3::ONE)),
+        );
+        graph.surface = graph.add_node(
+            ShadingNode::new("surf", "ND_standard_surface_surfaceshader")
+                .with_value("emission", ShadingValue::Float(2.0))
+                .with_value(
+                    "emission_color",
+                    ShadingValue::Vector3(Vec3::new(1.0, 0.5, 0.25)),
+                )
+                .with_connection("base_color", 0, "out"),
+        );
+        let mat = ShadingGraphMaterial::from_graph(Arc::new(graph.clone()), vec![None, None]);
+
+        let emitted = mat.emitted(0.0, 0.0, Vec3::ZERO);
+        assert!((emitted - Vec3::new(2.0, 1.0, 0.5)).length() < 1e-5);
+        assert!(mat.properties().is_emissive);
+
+        // Node 0 multiplying itself is rejected up front
+        graph.nodes[0] = graph.nodes[0].clone().with_connection("in2", 0, "out");
+        let mat = ShadingGraphMaterial::from_graph(Arc::new(graph), vec![None, None]);
+        assert!(!mat.has_graph());
+        assert_eq!(mat.emitted(0.0, 0.0, Vec3::ZERO), Color::ZERO);
+    }
+
+    #[test]
+    fn test_non_color_images_are_linear() {
+        let path = std::env::temp_dir().join("bif_shading_graph_roughness.png");
+        image::GrayImage::from_pixel(1, 1, image::Luma([102]))
+            .save(&path)
+            .unwrap();
+        let file = ShadingValue::String(path.to_string_lossy().into_owned());
+
+        let mut graph = ShadingGraph::new();
+        let roughness = graph
+            .add_node(ShadingNode::new("rough", "ND_image_float").with_value("file", file.clone()));
+        let albedo =
+            graph.add_node(ShadingNode::new("albedo", "ND_image_color3").with_value("file", file));
+        graph.surface = graph.add_node(
+            ShadingNode::new("surf", "ND_standard_surface_surfaceshader")
+                .with_connection("specular_roughness", roughness, "out")
+                .with_connection("base_color", albedo, "out"),
+        );
+        let material = bif_core::Material {
+            shading_graph: Some(Arc::new(graph)),
+            ..Default::default()
+        };
+        let mat = ShadingGraphMaterial::new(&material, &mut TextureCache::new());
+
+        let bsdf = mat.shade(0.5, 0.5, Vec3::Y).bsdf;
+        assert!((bsdf.roughness - 0.4).abs() < 1e-6);
+        assert!(bsdf.base_color.x < 0.2);
+    }
+}
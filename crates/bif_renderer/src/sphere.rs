@@ -1,8 +1,9 @@
 //! Sphere primitive for ray tracing.
 
 use crate::{
-    hittable::{HitRecord, Hittable},
-    Material, Ray,
+    hittable::{alpha_test, HitRecord, Hittable},
+    ray::sphere_point,
+    AreaLight, Material, Ray, SurfacePoint,
 };
 use bif_math::{Aabb, Interval, Vec3};
 use std::f32::consts::PI;
@@ -58,23 +59,34 @@ impl<M: Material + 'static> Hittable for Sphere<M> {
 
         let sqrtd = discriminant.sqrt();
 
-        // Find the nearest root in the acceptable range
-        let mut root = (h - sqrtd) / a;
-        if !ray_t.surrounds(root) {
-            root = (h + sqrtd) / a;
+        // Find the nearest root in the acceptable range that isn't masked out
+        for root in [(h - sqrtd) / a, (h + sqrtd) / a] {
             if !ray_t.surrounds(root) {
-                return false;
+                continue;
+            }
+
+            let (p, p_error) = sphere_point(ray.at(root), self.center, self.radius);
+            let outward_normal = (p - self.center) / self.radius;
+            let (u, v) = Self::get_sphere_uv(outward_normal);
+            if !alpha_test(
+                self.material.opacity(u, v),
+                ray.origin(),
+                ray.direction(),
+                root,
+            ) {
+                continue;
             }
-        }
 
-        rec.t = root;
-        rec.p = ray.at(rec.t);
-        let outward_normal = (rec.p - self.center) / self.radius;
-        rec.set_face_normal(ray, outward_normal);
-        (rec.u, rec.v) = Self::get_sphere_uv(outward_normal);
-        rec.material = &self.material;
+            rec.t = root;
+            rec.p = p;
+            rec.p_error = p_error;
+            rec.set_face_normal(ray, outward_normal);
+            (rec.u, rec.v) = (u, v);
+            rec.material = &self.material;
+            return true;
+        }
 
-        true
+        false
     }
 
     fn bounding_box(&self) -> Aabb {
@@ -82,6 +94,31 @@ impl<M: Material + 'static> Hittable for Sphere<M> {
     }
 }
 
+impl<M: Material + 'static> AreaLight for Sphere<M> {
+    fn area(&self) -> f32 {
+        4.0 * PI * self.radius * self.radius
+    }
+
+    fn sample_area(&self, s: f32, t: f32) -> SurfacePoint {
+        let z = 1.0 - 2.0 * s;
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        let phi = 2.0 * PI * t;
+        let normal = Vec3::new(r * phi.cos(), r * phi.sin(), z);
+        let (u, v) = Self::get_sphere_uv(normal);
+
+        SurfacePoint {
+            p: self.center + self.radius * normal,
+            normal,
+            u,
+            v,
+        }
+    }
+
+    fn material(&self) -> &dyn Material {
+        &self.material
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -102,12 +139,15 @@ mod tests {
         let dummy_mat = Lambertian::new(Vec3::ONE);
         let mut rec = HitRecord {
             p: Vec3::ZERO,
+            p_error: Vec3::ZERO,
             normal: Vec3::ZERO,
             material: &dummy_mat,
             u: 0.0,
             v: 0.0,
             t: 0.0,
             front_face: false,
+            tangent: Vec3::ZERO,
+            instance: None,
         };
 
         assert!(sphere.hit(&ray, interval, &mut rec));
@@ -129,14 +169,29 @@ mod tests {
         let dummy_mat = Lambertian::new(Vec3::ONE);
         let mut rec = HitRecord {
             p: Vec3::ZERO,
+            p_error: Vec3::ZERO,
             normal: Vec3::ZERO,
             material: &dummy_mat,
             u: 0.0,
             v: 0.0,
             t: 0.0,
             front_face: false,
+            tangent: Vec3::ZERO,
+            instance: None,
         };
 
         assert!(!sphere.hit(&ray, interval, &mut rec));
     }
+
+    #[test]
+    fn test_sphere_samples_lie_on_surface() {
+        let sphere = Sphere::new(Vec3::new(1.0, 2.0, 3.0), 2.0, Lambertian::new(Vec3::ONE));
+        assert!((sphere.area() - 16.0 * PI).abs() < 1e-4);
+
+        for &(s, t) in &[(0.0, 0.0), (0.5, 0.25), (0.9, 0.7)] {
+            let point = sphere.sample_area(s, t);
+            assert!(((point.p - Vec3::new(1.0, 2.0, 3.0)).length() - 2.0).abs() < 1e-4);
+            assert!((point.normal.length() - 1.0).abs() < 1e-4);
+        }
+    }
 }
@@ -0,0 +1,295 @@
+//! Random-walk subsurface scattering.
+//!
+//! Light entering a subsurface material is traced as a volumetric random
+//! walk inside the (closed) mesh using ordinary `Hittable` queries, until it
+//! leaves through the surface again. Based on "Practical and Controllable
+//! Subsurface Scattering for Production Path Tracing" (Chiang et al. 2016).
+
+use crate::material::{cosine_weighted_hemisphere, gen_f32, random_unit_vector, Color};
+use crate::{HitRecord, Hittable, Ray};
+use bif_core::Visibility;
+use bif_math::{Interval, Vec3};
+use rand::RngCore;
+
+/// Maximum number of scattering events before the walk is terminated.
+const MAX_STEPS: u32 = 256;
+
+/// Steps taken before Russian roulette starts.
+const MIN_STEPS: u32 = 8;
+
+/// Subsurface parameters of a material at a shading point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Subsurface {
+    /// Weight of the random walk in the material's response. The material's
+    /// BSDF leaves this share of its diffuse lobe out.
+    pub weight: f32,
+
+    /// Multiple-scattering albedo (the color the material appears)
+    pub color: Color,
+
+    /// Mean free path per channel, in scene units
+    pub radius: Color,
+}
+
+/// Result of a random walk that left the medium.
+#[derive(Debug, Clone)]
+pub struct SubsurfaceExit {
+    /// Ray leaving the surface at the exit point
+    pub ray: Ray,
+
+    /// Path throughput accumulated inside the medium
+    pub throughput: Color,
+}
+
+impl Subsurface {
+    /// Create subsurface parameters.
+    pub fn new(weight: f32, color: Color, radius: Color) -> Self {
+        Self {
+            weight: weight.clamp(0.0, 1.0),
+            color,
+            radius,
+        }
+    }
+
+    /// Extinction coefficient per channel (inverse mean free path).
+    pub fn extinction(&self) -> Color {
+        Color::ONE / self.radius.max(Color::splat(1e-6))
+    }
+
+    /// Single-scattering albedo that produces `color` after multiple scattering.
+    ///
+    /// Uses the fitted inversion from Chiang et al. 2016.
+    pub fn single_scattering_albedo(&self) -> Color {
+        let invert = |a: f32| {
+            let a = a.clamp(0.0, 1.0);
+            let s = 4.09712 + 4.20863 * a - (9.59217 + 41.6808 * a + 17.7126 * a * a).sqrt();
+            (1.0 - s * s).clamp(0.0, 1.0)
+        };
+        Color::new(
+            invert(self.color.x),
+            invert(self.color.y),
+            invert(self.color.z),
+        )
+    }
+
+    /// Trace a random walk starting at the entry hit `rec`.
+    ///
+    /// The walk enters with a diffuse transmission through the surface,
+    /// scatters isotropically inside and exits through the first surface of
+    /// the entered instance it reaches (`rec.instance`; other geometry is
+    /// passed through). Returns None if the path was absorbed.
+    pub fn random_walk(
+        &self,
+        rec: &HitRecord,
+        world: &dyn Hittable,
+        time: f32,
+        rng: &mut dyn RngCore,
+    ) -> Option<SubsurfaceExit> {
+        let sigma_t = self.extinction();
+        let sigma_s = self.single_scattering_albedo() * sigma_t;
+
+        // The walk has to find the object's own surface, so it sees anything
+        // that isn't hidden from every ray type
+        let direction = cosine_weighted_hemisphere(-rec.normal, rng);
+        let mut ray = rec
+            .spawn_ray(direction, time)
+            .with_visibility(Visibility::ALL);
+        let mut throughput = Color::ONE;
+
+        for step in 0..MAX_STEPS {
+            // Pick a channel uniformly and sample a free-flight distance for it
+            let channel = ((gen_f32(rng) * 3.0) as usize).min(2);
+            let distance = -(1.0 - gen_f32(rng)).ln() / sigma_t[channel];
+
+            if let Some((t, hit)) = hit_instance(world, &ray, distance, rec.instance) {
+                // Reached the surface: weight by the probability of no collision
+                let transmittance = exp(-sigma_t * t);
+                let pdf = mean(transmittance);
+                if pdf <= 0.0 {
+                    return None;
+                }
+                throughput *= transmittance / pdf;
+
+                // The hit normal faces back into the medium
+                let outward = -hit.normal;
+                let direction = cosine_weighted_hemisphere(outward, rng);
+                return Some(SubsurfaceExit {
+                    ray: hit
+                        .spawn_ray(direction, time)
+                        .with_visibility(Visibility::DIFFUSE),
+                    throughput,
+                });
+            }
+
+            // Scattering event inside the medium
+            let transmittance = exp(-sigma_t * distance);
+            let pdf = mean(sigma_t * transmittance);
+            if pdf <= 0.0 {
+                return None;
+            }
+            throughput *= sigma_s * transmittance / pdf;
+
+            if step >= MIN_STEPS {
+                let survive = throughput.max_element().min(1.0);
+                if gen_f32(rng) >= survive {
+                    return None;
+                }
+                throughput /= survive;
+            }
+
+            let origin = ray.at(distance);
+            ray = Ray::new(origin, random_unit_vector(rng), time).with_visibility(Visibility::ALL);
+        }
+
+        None
+    }
+}
+
+/// Closest hit on `instance` along `ray` within `distance`, and its t.
+///
+/// Surfaces of other instances (e.g. a neighbouring object intersecting the
+/// medium) are stepped over.
+fn hit_instance<'w>(
+    world: &'w dyn Hittable,
+    ray: &Ray,
+    distance: f32,
+    instance: Option<u32>,
+) -> Option<(f32, HitRecord<'w>)> {
+    let mut segment = *ray;
+    let mut start = 0.0;
+    for _ in 0..MAX_STEPS {
+        let mut hit = HitRecord::default();
+        if !world.hit(&segment, Interval::new(0.0, distance - start), &mut hit) {
+            return None;
+        }
+        if hit.instance == instance {
+            return Some((start + hit.t, hit));
+        }
+        start += hit.t;
+        segment = hit
+            .spawn_ray(segment.direction(), segment.time())
+            .with_visibility(Visibility::ALL);
+    }
+    None
+}
+
+#[inline]
+fn exp(v: Vec3) -> Vec3 {
+    Vec3::new(v.x.exp(), v.y.exp(), v.z.exp())
+}
+
+#[inline]
+fn mean(v: Vec3) -> f32 {
+    (v.x + v.y + v.z) / 3.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Lambertian, Sphere};
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_albedo_inversion() {
+        let sss = Subsurface::new(1.0, Color::new(0.0, 0.5, 1.0), Color::ONE);
+        let alpha = sss.single_scattering_albedo();
+        assert!(alpha.x.abs() < 1e-3);
+        assert!(alpha.y > 0.5 && alpha.y < 1.0);
+        assert!((alpha.z - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_walk_exits_sphere() {
+        let sphere = Sphere::new(Vec3::ZERO, 1.0, Lambertian::new(Color::ONE));
+        let sss = Subsurface::new(1.0, Color::ONE, Color::splat(0.2));
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(sphere.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+
+        let mut exits = 0;
+        for _ in 0..200 {
+            if let Some(exit) = sss.random_walk(&rec, &sphere, 0.0, &mut rng) {
+                exits += 1;
+                let origin = exit.ray.origin();
+                assert!(origin.length() >= 1.0 - 1e-3);
+                assert!(exit.ray.direction().dot(origin) > 0.0);
+                assert!(exit.throughput.min_element() > 0.0);
+            }
+        }
+        // Non-absorbing medium: almost every walk leaves the sphere
+        assert!(exits > 180, "only {exits} walks exited");
+    }
+
+    #[test]
+    fn test_walk_stays_in_entered_instance() {
+        use crate::{InstancedGeometryBVH, InstancedPrototype, MaterialTable};
+        use bif_math::Mat4;
+
+        // Cube [-1, 1]^3 with a quad of another instance cutting through it
+        let corner = |i: usize| {
+            let bit = |b: usize| if i & (1 << b) != 0 { 1.0 } else { -1.0 };
+            Vec3::new(bit(0), bit(1), bit(2))
+        };
+        let faces = [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ];
+        let cube: Vec<[Vec3; 3]> = faces
+            .iter()
+            .flat_map(|&[a, b, c, d]| {
+                [
+                    [corner(a), corner(b), corner(c)],
+                    [corner(a), corner(c), corner(d)],
+                ]
+            })
+            .collect();
+        let [a, b, c, d] =
+            [(-2.0, -2.0), (2.0, -2.0), (2.0, 2.0), (-2.0, 2.0)].map(|(x, y)| Vec3::new(x, y, 0.0));
+        let quad = [[a, b, c], [a, c, d]];
+        let material = || MaterialTable::new(Lambertian::new(Color::ONE));
+        let world = InstancedGeometryBVH::from_prototypes(vec![
+            InstancedPrototype::new(&cube, vec![Mat4::IDENTITY], material()),
+            InstancedPrototype::new(&quad, vec![Mat4::IDENTITY], material()),
+        ]);
+
+        let ray = Ray::new(Vec3::new(0.3, 0.2, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(world.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+        assert_eq!(rec.instance, Some(0));
+
+        // Long mean free path: walks regularly cross the quad
+        let sss = Subsurface::new(1.0, Color::ONE, Color::splat(2.0));
+        let mut rng = rand::rngs::StdRng::seed_from_u64(13);
+        for _ in 0..200 {
+            if let Some(exit) = sss.random_walk(&rec, &world, 0.0, &mut rng) {
+                let origin = exit.ray.origin();
+                assert!((origin.abs().max_element() - 1.0).abs() < 1e-3, "{origin}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_absorbing_walk_darkens() {
+        let sphere = Sphere::new(Vec3::ZERO, 1.0, Lambertian::new(Color::ONE));
+        let sss = Subsurface::new(1.0, Color::splat(0.2), Color::splat(0.1));
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rec = HitRecord::default();
+        assert!(sphere.hit(&ray, Interval::new(0.001, f32::INFINITY), &mut rec));
+
+        let n = 2000;
+        let total: f32 = (0..n)
+            .filter_map(|_| sss.random_walk(&rec, &sphere, 0.0, &mut rng))
+            .map(|exit| exit.throughput.x)
+            .sum();
+        let average = total / n as f32;
+        assert!(average < 0.6, "average throughput {average}");
+    }
+}
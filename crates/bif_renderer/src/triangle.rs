@@ -1,13 +1,121 @@
 //! Triangle primitive for ray tracing.
 //!
-//! Uses the Möller-Trumbore algorithm for ray-triangle intersection.
+//! Uses the watertight ray-triangle algorithm of Woop, Benthin and Wald
+//! (JCGT 2013), shared with the mesh BVHs: rays through an edge or vertex
+//! shared by two triangles always hit one of them, whatever the scene scale.
 
 use crate::{
-    hittable::{HitRecord, Hittable},
+    hittable::{alpha_test, HitRecord, Hittable},
+    ray::gamma,
     Material, Ray,
 };
 use bif_math::{Aabb, Interval, Vec3};
 
+/// Watertight ray-triangle intersection, returning `(t, u, v)`.
+///
+/// `u` and `v` are the barycentric weights of `v1` and `v2`. The ray is
+/// transformed so it runs along +z from the origin; the edge functions of
+/// the projected triangle are then evaluated with exact products, so their
+/// signs agree between neighbouring triangles. Hits are only reported when
+/// `t` is provably positive after rounding, so no `t` epsilon is needed.
+#[inline]
+pub(crate) fn intersect_triangle(
+    origin: Vec3,
+    direction: Vec3,
+    vertices: [Vec3; 3],
+    ray_t: Interval,
+) -> Option<(f32, f32, f32)> {
+    // Permute axes so that z is the dominant direction component
+    let abs_dir = direction.abs();
+    let kz = if abs_dir.x > abs_dir.y {
+        if abs_dir.x > abs_dir.z {
+            0
+        } else {
+            2
+        }
+    } else if abs_dir.y > abs_dir.z {
+        1
+    } else {
+        2
+    };
+    if abs_dir[kz] == 0.0 {
+        return None;
+    }
+    let kx = (kz + 1) % 3;
+    let ky = (kx + 1) % 3;
+    let permute = |v: Vec3| Vec3::new(v[kx], v[ky], v[kz]);
+    let d = permute(direction);
+
+    // Translate to the ray origin and shear the ray onto +z
+    let shear_x = -d.x / d.z;
+    let shear_y = -d.y / d.z;
+    let shear_z = 1.0 / d.z;
+    let [p0, p1, p2] = vertices.map(|v| {
+        let p = permute(v - origin);
+        Vec3::new(p.x + shear_x * p.z, p.y + shear_y * p.z, p.z)
+    });
+
+    // Edge functions; f32 products are exact in f64, so the signs are too
+    let edge = |a: Vec3, b: Vec3| (a.x as f64 * b.y as f64 - a.y as f64 * b.x as f64) as f32;
+    let e0 = edge(p1, p2);
+    let e1 = edge(p2, p0);
+    let e2 = edge(p0, p1);
+    if (e0 < 0.0 || e1 < 0.0 || e2 < 0.0) && (e0 > 0.0 || e1 > 0.0 || e2 > 0.0) {
+        return None;
+    }
+    let det = e0 + e1 + e2;
+    if det == 0.0 {
+        return None;
+    }
+
+    // Scaled distance, compared against the range before dividing
+    let z = Vec3::new(p0.z, p1.z, p2.z) * shear_z;
+    let t_scaled = e0 * z.x + e1 * z.y + e2 * z.z;
+    let t_max_scaled = if ray_t.max.is_finite() {
+        ray_t.max * det
+    } else {
+        f32::INFINITY.copysign(det)
+    };
+    if det < 0.0 && (t_scaled >= 0.0 || t_scaled < t_max_scaled) {
+        return None;
+    }
+    if det > 0.0 && (t_scaled <= 0.0 || t_scaled > t_max_scaled) {
+        return None;
+    }
+
+    let inv_det = 1.0 / det;
+    let t = t_scaled * inv_det;
+
+    // Reject t values that rounding could have pushed above zero
+    let max_x = Vec3::new(p0.x, p1.x, p2.x).abs().max_element();
+    let max_y = Vec3::new(p0.y, p1.y, p2.y).abs().max_element();
+    let max_z = z.abs().max_element();
+    let delta_x = gamma(5) * (max_x + max_z);
+    let delta_y = gamma(5) * (max_y + max_z);
+    let delta_z = gamma(3) * max_z;
+    let delta_e = 2.0 * (gamma(2) * max_x * max_y + delta_y * max_x + delta_x * max_y);
+    let max_e = Vec3::new(e0, e1, e2).abs().max_element();
+    let delta_t =
+        3.0 * (gamma(3) * max_e * max_z + delta_e * max_z + delta_z * max_e) * inv_det.abs();
+    if t <= delta_t || !ray_t.contains(t) {
+        return None;
+    }
+
+    Some((t, e1 * inv_det, e2 * inv_det))
+}
+
+/// Hit point from barycentrics and a bound on its floating-point error.
+///
+/// Interpolating the vertices keeps `p` on the triangle to within a few
+/// ULPs of its coordinates, unlike `ray.at(t)` whose error grows with `t`.
+#[inline]
+pub(crate) fn triangle_point([v0, v1, v2]: [Vec3; 3], u: f32, v: f32) -> (Vec3, Vec3) {
+    let w = 1.0 - u - v;
+    let (a, b, c) = (w * v0, u * v1, v * v2);
+    let error = (a.abs() + b.abs() + c.abs()) * gamma(7);
+    (a + b + c, error)
+}
+
 /// A triangle primitive.
 pub struct Triangle<M: Material> {
     /// Vertices
@@ -86,46 +194,26 @@ impl<M: Material> Triangle<M> {
 }
 
 impl<M: Material + 'static> Hittable for Triangle<M> {
-    /// Möller-Trumbore ray-triangle intersection algorithm.
     fn hit<'a>(&'a self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord<'a>) -> bool {
-        let edge1 = self.v1 - self.v0;
-        let edge2 = self.v2 - self.v0;
-
-        let h = ray.direction().cross(edge2);
-        let a = edge1.dot(h);
-
-        // Ray is parallel to triangle
-        if a.abs() < 1e-8 {
+        let vertices = [self.v0, self.v1, self.v2];
+        let Some((t, u, v)) = intersect_triangle(ray.origin(), ray.direction(), vertices, ray_t)
+        else {
             return false;
-        }
-
-        let f = 1.0 / a;
-        let s = ray.origin() - self.v0;
-        let u = f * s.dot(h);
-
-        // Check if intersection is outside triangle (u parameter)
-        if !(0.0..=1.0).contains(&u) {
-            return false;
-        }
-
-        let q = s.cross(edge1);
-        let v = f * ray.direction().dot(q);
-
-        // Check if intersection is outside triangle (v parameter)
-        if v < 0.0 || u + v > 1.0 {
-            return false;
-        }
-
-        // Calculate t parameter
-        let t = f * edge2.dot(q);
+        };
 
-        if !ray_t.contains(t) {
+        // Masked (cutout / partially transparent) regions let the ray through
+        if !alpha_test(
+            self.material.opacity(u, v),
+            ray.origin(),
+            ray.direction(),
+            t,
+        ) {
             return false;
         }
 
         // Valid intersection found
         rec.t = t;
-        rec.p = ray.at(t);
+        (rec.p, rec.p_error) = triangle_point(vertices, u, v);
         rec.set_face_normal(ray, self.normal);
         rec.u = u;
         rec.v = v;
@@ -161,12 +249,15 @@ mod tests {
         let dummy_mat = Lambertian::new(Vec3::ONE);
         let mut rec = HitRecord {
             p: Vec3::ZERO,
+            p_error: Vec3::ZERO,
             normal: Vec3::ZERO,
             material: &dummy_mat,
             u: 0.0,
             v: 0.0,
             t: 0.0,
             front_face: false,
+            tangent: Vec3::ZERO,
+            instance: None,
         };
 
         assert!(tri.hit(&ray, interval, &mut rec));
@@ -189,14 +280,53 @@ mod tests {
         let dummy_mat = Lambertian::new(Vec3::ONE);
         let mut rec = HitRecord {
             p: Vec3::ZERO,
+            p_error: Vec3::ZERO,
             normal: Vec3::ZERO,
             material: &dummy_mat,
             u: 0.0,
             v: 0.0,
             t: 0.0,
             front_face: false,
+            tangent: Vec3::ZERO,
+            instance: None,
         };
 
         assert!(!tri.hit(&ray, interval, &mut rec));
     }
+
+    #[test]
+    fn test_triangle_alpha_cutout() {
+        use crate::ShadingGraphMaterial;
+        use bif_core::TextureCache;
+
+        let cutout = |opacity: f32| {
+            let mat = bif_core::Material {
+                opacity,
+                ..Default::default()
+            };
+            Triangle::new(
+                Vec3::new(-1.0, -1.0, -1.0),
+                Vec3::new(1.0, -1.0, -1.0),
+                Vec3::new(0.0, 1.0, -1.0),
+                ShadingGraphMaterial::new(&mat, &mut TextureCache::new()),
+            )
+        };
+        let interval = Interval::new(0.001, f32::INFINITY);
+
+        // Fully transparent: every ray passes through
+        let hidden = cutout(0.0);
+        let ray = Ray::new_simple(Vec3::ZERO, Vec3::new(0.0, 0.0, -1.0));
+        assert!(!hidden.hit(&ray, interval, &mut HitRecord::default()));
+
+        // Half transparent: roughly half of jittered rays hit
+        let half = cutout(0.5);
+        let hits = (0..1000)
+            .filter(|i| {
+                let x = (*i as f32 / 1000.0 - 0.5) * 0.2;
+                let ray = Ray::new_simple(Vec3::new(x, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+                half.hit(&ray, interval, &mut HitRecord::default())
+            })
+            .count();
+        assert!((400..600).contains(&hits), "hits = {}", hits);
+    }
 }
@@ -0,0 +1,118 @@
+//! Texture coordinates of prototype triangles.
+//!
+//! Two-level worlds keep a `TriangleUvs` per prototype so that hits report
+//! the mesh's UVs rather than triangle barycentrics, along with the surface
+//! tangent (dP/du) that tangent-space normal maps are authored in.
+
+use bif_math::Vec3;
+
+/// UVs of each corner of a prototype's triangles, and the tangent they
+/// imply. Empty for meshes without UVs, whose hits keep the barycentrics.
+#[derive(Clone, Debug, Default)]
+pub struct TriangleUvs {
+    uvs: Vec<[[f32; 2]; 3]>,
+    /// dP/du of each triangle in local space (zero for degenerate UVs)
+    tangents: Vec<Vec3>,
+}
+
+impl TriangleUvs {
+    /// Pair `uvs` with the triangles they belong to.
+    ///
+    /// There must be one entry per triangle; anything else leaves the
+    /// prototype without UVs.
+    pub fn new(vertices: &[[Vec3; 3]], uvs: &[[[f32; 2]; 3]]) -> Self {
+        if uvs.len() != vertices.len() {
+            if !uvs.is_empty() {
+                log::warn!(
+                    "{} triangle UVs for {} triangles, using barycentrics",
+                    uvs.len(),
+                    vertices.len()
+                );
+            }
+            return Self::default();
+        }
+        let mut triangle_uvs = Self {
+            uvs: uvs.to_vec(),
+            tangents: Vec::new(),
+        };
+        triangle_uvs.refit(vertices);
+        triangle_uvs
+    }
+
+    /// Check if the triangles have no UVs.
+    pub fn is_empty(&self) -> bool {
+        self.uvs.is_empty()
+    }
+
+    /// Texture coordinates at barycentrics (u, v) of triangle `face`.
+    ///
+    /// Returns the barycentrics themselves when there are no UVs.
+    #[inline]
+    pub fn uv(&self, face: u32, u: f32, v: f32) -> (f32, f32) {
+        match self.uvs.get(face as usize) {
+            Some([a, b, c]) => {
+                let w = 1.0 - u - v;
+                (
+                    w * a[0] + u * b[0] + v * c[0],
+                    w * a[1] + u * b[1] + v * c[1],
+                )
+            }
+            None => (u, v),
+        }
+    }
+
+    /// Local-space dP/du of triangle `face` (zero without UVs).
+    #[inline]
+    pub fn tangent(&self, face: u32) -> Vec3 {
+        self.tangents
+            .get(face as usize)
+            .copied()
+            .unwrap_or(Vec3::ZERO)
+    }
+
+    /// Recompute the tangents for moved triangles (same triangles in the
+    /// same order).
+    pub fn refit(&mut self, vertices: &[[Vec3; 3]]) {
+        if self.uvs.is_empty() {
+            return;
+        }
+        self.tangents = vertices
+            .iter()
+            .zip(&self.uvs)
+            .map(|(vertices, uvs)| triangle_tangent(vertices, uvs))
+            .collect();
+    }
+}
+
+/// dP/du of a triangle from its corner positions and UVs.
+fn triangle_tangent([p0, p1, p2]: &[Vec3; 3], [uv0, uv1, uv2]: &[[f32; 2]; 3]) -> Vec3 {
+    let (e1, e2) = (*p1 - *p0, *p2 - *p0);
+    let (du1, dv1) = (uv1[0] - uv0[0], uv1[1] - uv0[1]);
+    let (du2, dv2) = (uv2[0] - uv0[0], uv2[1] - uv0[1]);
+    let det = du1 * dv2 - dv1 * du2;
+    if det.abs() < 1e-12 {
+        return Vec3::ZERO;
+    }
+    (dv2 * e1 - dv1 * e2) / det
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolates_uvs_and_tangent() {
+        // UVs rotated a quarter turn: u runs along +Y
+        let vertices = [[Vec3::ZERO, Vec3::X, Vec3::Y]];
+        let uvs = TriangleUvs::new(&vertices, &[[[0.0, 1.0], [0.0, 0.0], [1.0, 1.0]]]);
+        assert_eq!(uvs.uv(0, 0.0, 0.0), (0.0, 1.0));
+        assert_eq!(uvs.uv(0, 0.5, 0.5), (0.5, 0.5));
+        assert!((uvs.tangent(0) - Vec3::Y).length() < 1e-6);
+
+        // Without UVs, hits keep their barycentrics
+        let none = TriangleUvs::new(&vertices, &[]);
+        assert!(none.is_empty());
+        assert_eq!(none.uv(0, 0.25, 0.5), (0.25, 0.5));
+        assert_eq!(none.tangent(0), Vec3::ZERO);
+    }
+}
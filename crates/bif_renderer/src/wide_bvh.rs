@@ -0,0 +1,677 @@
+//! Wide (4- or 8-ary) BVH with SIMD box tests and packet traversal.
+//!
+//! Collapsed from a binary `MeshBvh`: every wide node holds the bounds of up
+//! to `W` children in structure-of-arrays form, so one node visit tests all
+//! of them with a few vector operations. Fixed-length lane loops compile to
+//! SSE/AVX code. Coherent rays (e.g. primary rays of a pixel tile) can be
+//! traced together with `intersect_packet`, sharing node fetches and box
+//! tests across the packet. Deforming meshes are supported as in `MeshBvh`.
+
+use crate::bvh::MAX_DEPTH;
+use crate::mesh_bvh::{
+    refit_triangles, triangle_at, triangles_bounds, MeshBvh, MeshHit, PackedTriangle,
+};
+use crate::{BvhBuildConfig, Ray};
+use bif_math::{Aabb, Interval, Vec3, SLAB_EXIT_SCALE};
+
+/// Traversal stack size: a visit pops one entry and pushes at most `W`, so
+/// the stack grows by `W - 1` per level (W <= 8) over at most `MAX_DEPTH`
+/// levels.
+const STACK_SIZE: usize = 7 * MAX_DEPTH + 1;
+
+/// Largest packet traced by `intersect_packet` in one pass.
+pub const MAX_PACKET_SIZE: usize = 64;
+
+/// 4-wide BVH (one SSE vector per bound).
+pub type Bvh4 = WideBvh<4>;
+
+/// 8-wide BVH (one AVX vector per bound).
+pub type Bvh8 = WideBvh<8>;
+
+/// Wide node with children bounds in structure-of-arrays layout.
+#[derive(Debug, Clone)]
+struct WideNode<const W: usize> {
+    min_x: [f32; W],
+    min_y: [f32; W],
+    min_z: [f32; W],
+    max_x: [f32; W],
+    max_y: [f32; W],
+    max_z: [f32; W],
+    /// Child node index (interior) or first triangle (leaf)
+    children: [u32; W],
+    /// Triangle count per lane; 0 for interior children
+    counts: [u32; W],
+    /// Number of used lanes
+    len: u32,
+}
+
+impl<const W: usize> WideNode<W> {
+    fn empty() -> Self {
+        Self {
+            min_x: [f32::INFINITY; W],
+            min_y: [f32::INFINITY; W],
+            min_z: [f32::INFINITY; W],
+            max_x: [f32::NEG_INFINITY; W],
+            max_y: [f32::NEG_INFINITY; W],
+            max_z: [f32::NEG_INFINITY; W],
+            children: [0; W],
+            counts: [0; W],
+            len: 0,
+        }
+    }
+
+    fn set_lane_bounds(&mut self, lane: usize, bbox: &Aabb) {
+        self.min_x[lane] = bbox.x.min;
+        self.min_y[lane] = bbox.y.min;
+        self.min_z[lane] = bbox.z.min;
+        self.max_x[lane] = bbox.x.max;
+        self.max_y[lane] = bbox.y.max;
+        self.max_z[lane] = bbox.z.max;
+    }
+
+    /// Union of the used lanes' bounds.
+    fn bounds(&self) -> Aabb {
+        (0..self.len as usize).fold(Aabb::EMPTY, |bbox, lane| {
+            let min = Vec3::new(self.min_x[lane], self.min_y[lane], self.min_z[lane]);
+            let max = Vec3::new(self.max_x[lane], self.max_y[lane], self.max_z[lane]);
+            Aabb::surrounding(&bbox, &Aabb::from_points(min, max))
+        })
+    }
+
+    /// Slab test of all lanes; returns the entry distance, or infinity on a miss.
+    #[inline]
+    fn hit(&self, ray: &RayData, t_min: f32, t_max: f32) -> [f32; W] {
+        // Unused lanes hold inverted boxes, which the slab swap would turn
+        // into infinite ones, so they are masked by `len`
+        let mut near = [f32::INFINITY; W];
+        for (lane, t) in near.iter_mut().enumerate() {
+            let tx0 = (self.min_x[lane] - ray.origin.x) * ray.inv_dir.x;
+            let tx1 = (self.max_x[lane] - ray.origin.x) * ray.inv_dir.x;
+            let ty0 = (self.min_y[lane] - ray.origin.y) * ray.inv_dir.y;
+            let ty1 = (self.max_y[lane] - ray.origin.y) * ray.inv_dir.y;
+            let tz0 = (self.min_z[lane] - ray.origin.z) * ray.inv_dir.z;
+            let tz1 = (self.max_z[lane] - ray.origin.z) * ray.inv_dir.z;
+            let entry = tx0.min(tx1).max(ty0.min(ty1)).max(tz0.min(tz1)).max(t_min);
+            let exit = tx0.max(tx1).min(ty0.max(ty1)).min(tz0.max(tz1)) * SLAB_EXIT_SCALE;
+            let exit = exit.min(t_max);
+            let used = (lane as u32) < self.len;
+            *t = if used && entry <= exit {
+                entry
+            } else {
+                f32::INFINITY
+            };
+        }
+        near
+    }
+}
+
+/// Ray with its reciprocal direction precomputed.
+#[derive(Debug, Clone, Copy)]
+struct RayData {
+    origin: Vec3,
+    direction: Vec3,
+    inv_dir: Vec3,
+    time: f32,
+}
+
+impl RayData {
+    fn new(ray: &Ray) -> Self {
+        Self {
+            origin: ray.origin(),
+            direction: ray.direction(),
+            inv_dir: ray.direction().recip(),
+            time: ray.time(),
+        }
+    }
+}
+
+/// BVH with `W` children per node over a triangle mesh.
+#[derive(Debug, Clone)]
+pub struct WideBvh<const W: usize> {
+    nodes: Vec<WideNode<W>>,
+    triangles: Vec<PackedTriangle>,
+    primitive_ids: Vec<u32>,
+    /// Further deformation keys, in the same order as `triangles`
+    motion: Vec<Vec<PackedTriangle>>,
+    bbox: Aabb,
+}
+
+impl<const W: usize> WideBvh<W> {
+    /// Build a wide BVH over triangles given as vertex triples.
+    pub fn new(vertices: &[[Vec3; 3]]) -> Self {
+        Self::with_config(vertices, &BvhBuildConfig::default())
+    }
+
+    /// Build with explicit construction parameters.
+    pub fn with_config(vertices: &[[Vec3; 3]], config: &BvhBuildConfig) -> Self {
+        Self::from_binary(MeshBvh::with_config(vertices, config))
+    }
+
+    /// Build a wide BVH over a deforming mesh (see `MeshBvh::with_motion`).
+    pub fn with_motion(vertices: &[[Vec3; 3]], motion: &[Vec<[Vec3; 3]>]) -> Self {
+        Self::from_binary(MeshBvh::with_motion(vertices, motion))
+    }
+
+    /// Collapse a binary BVH into a `W`-wide one.
+    ///
+    /// Each wide node repeatedly replaces its largest interior child by that
+    /// child's two children until all `W` lanes are used.
+    pub fn from_binary(binary: MeshBvh) -> Self {
+        assert!(W >= 2, "WideBvh needs at least two lanes");
+
+        let bbox = binary.bounding_box();
+        let mut wide = Self {
+            nodes: Vec::with_capacity(binary.nodes.len() / (W - 1) + 1),
+            triangles: Vec::new(),
+            primitive_ids: Vec::new(),
+            motion: Vec::new(),
+            bbox,
+        };
+        if !binary.nodes.is_empty() {
+            wide.collapse(&binary, &[0]);
+        }
+        wide.triangles = binary.triangles;
+        wide.primitive_ids = binary.primitive_ids;
+        wide.motion = binary.motion;
+        wide
+    }
+
+    /// Append a wide node for the binary nodes `roots` (expanded as needed).
+    fn collapse(&mut self, binary: &MeshBvh, roots: &[usize]) -> u32 {
+        let area = |index: usize| binary.nodes[index].bounds().surface_area();
+
+        let mut slots = roots.to_vec();
+        while slots.len() < W {
+            let largest = slots
+                .iter()
+                .enumerate()
+                .filter(|(_, &index)| binary.nodes[index].count == 0)
+                .max_by(|(_, &a), (_, &b)| area(a).total_cmp(&area(b)))
+                .map(|(slot, _)| slot);
+            let Some(slot) = largest else {
+                break;
+            };
+            let index = slots.swap_remove(slot);
+            slots.push(index + 1);
+            slots.push(binary.nodes[index].offset as usize);
+        }
+
+        let node_index = self.nodes.len();
+        self.nodes.push(WideNode::empty());
+
+        let mut node = WideNode::empty();
+        node.len = slots.len() as u32;
+        for (lane, &index) in slots.iter().enumerate() {
+            let child = &binary.nodes[index];
+            node.set_lane_bounds(lane, &child.bounds());
+            if child.count > 0 {
+                node.children[lane] = child.offset;
+                node.counts[lane] = child.count as u32;
+            } else {
+                let children = [index + 1, child.offset as usize];
+                node.children[lane] = self.collapse(binary, &children);
+            }
+        }
+        self.nodes[node_index] = node;
+        node_index as u32
+    }
+
+    /// Move the triangles to new vertex positions, keeping the tree.
+    ///
+    /// Same contract as `MeshBvh::refit`.
+    pub fn refit(&mut self, vertices: &[[Vec3; 3]]) {
+        refit_triangles(&mut self.triangles, &self.primitive_ids, vertices);
+        self.motion.clear();
+
+        // Children are appended after their parent, so refit in reverse
+        for index in (0..self.nodes.len()).rev() {
+            for lane in 0..self.nodes[index].len as usize {
+                let node = &self.nodes[index];
+                let (child, count) = (node.children[lane] as usize, node.counts[lane] as usize);
+                let bbox = if count > 0 {
+                    triangles_bounds(&self.triangles[child..child + count])
+                } else {
+                    self.nodes[child].bounds()
+                };
+                self.nodes[index].set_lane_bounds(lane, &bbox);
+            }
+        }
+        self.bbox = self.nodes.first().map_or(Aabb::EMPTY, WideNode::bounds);
+    }
+
+    /// Find the closest triangle hit within `ray_t`.
+    ///
+    /// `accept(primitive, u, v, t)` can reject candidate hits, as in
+    /// `MeshBvh::intersect`.
+    pub fn intersect(
+        &self,
+        ray: &Ray,
+        ray_t: Interval,
+        mut accept: impl FnMut(u32, f32, f32, f32) -> bool,
+    ) -> Option<MeshHit> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let data = RayData::new(ray);
+        let mut closest = ray_t.max;
+        let mut best = None;
+
+        let mut stack = [0u32; STACK_SIZE];
+        let mut stack_len = 1;
+        while stack_len > 0 {
+            stack_len -= 1;
+            let node = &self.nodes[stack[stack_len] as usize];
+            let near = node.hit(&data, ray_t.min, closest);
+
+            // Leaves near-to-far, so closer hits cull the later ones
+            let order = sort_lanes(&near);
+            for &lane in &order {
+                if node.counts[lane] == 0 || !entered(near[lane], closest) {
+                    continue;
+                }
+                let first = node.children[lane] as usize;
+                for i in first..first + node.counts[lane] as usize {
+                    let interval = Interval::new(ray_t.min, closest);
+                    let triangle = self.triangle(i, data.time);
+                    let Some((t, u, v)) = triangle.intersect(data.origin, data.direction, interval)
+                    else {
+                        continue;
+                    };
+                    if accept(self.primitive_ids[i], u, v, t) {
+                        closest = t;
+                        best = Some((i, u, v));
+                    }
+                }
+            }
+
+            // Interior children far-to-near, so the nearest is popped first
+            for &lane in order.iter().rev() {
+                if node.counts[lane] == 0 && entered(near[lane], closest) {
+                    debug_assert!(stack_len < STACK_SIZE, "WideBvh stack overflow");
+                    stack[stack_len] = node.children[lane];
+                    stack_len += 1;
+                }
+            }
+        }
+
+        best.map(|(i, u, v)| self.make_hit(i, closest, u, v, data.time))
+    }
+
+    /// Trace a packet of rays together.
+    ///
+    /// `t_max[i]` bounds ray `i` and is lowered to the distance of every hit
+    /// found, which is stored in `hits[i]`. Children are visited if any ray
+    /// of the packet enters them, so packets should be coherent.
+    /// `accept(ray, primitive, u, v, t)` can reject candidate hits.
+    pub fn intersect_packet(
+        &self,
+        rays: &[Ray],
+        t_min: f32,
+        t_max: &mut [f32],
+        hits: &mut [Option<MeshHit>],
+        mut accept: impl FnMut(usize, u32, f32, f32, f32) -> bool,
+    ) {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        for (chunk, start) in rays
+            .chunks(MAX_PACKET_SIZE)
+            .zip((0..).step_by(MAX_PACKET_SIZE))
+        {
+            let end = start + chunk.len();
+            self.intersect_chunk(
+                chunk,
+                t_min,
+                &mut t_max[start..end],
+                &mut hits[start..end],
+                |ray, primitive, u, v, t| accept(start + ray, primitive, u, v, t),
+            );
+        }
+    }
+
+    fn intersect_chunk(
+        &self,
+        rays: &[Ray],
+        t_min: f32,
+        t_max: &mut [f32],
+        hits: &mut [Option<MeshHit>],
+        mut accept: impl FnMut(usize, u32, f32, f32, f32) -> bool,
+    ) {
+        let mut data = [RayData::new(&rays[0]); MAX_PACKET_SIZE];
+        for (d, ray) in data.iter_mut().zip(rays) {
+            *d = RayData::new(ray);
+        }
+        let data = &data[..rays.len()];
+
+        // Closest hit so far per ray (leaf-order triangle and barycentrics)
+        let mut best = [None; MAX_PACKET_SIZE];
+        let mut ray_near = [[f32::INFINITY; W]; MAX_PACKET_SIZE];
+
+        let mut stack = [0u32; STACK_SIZE];
+        let mut stack_len = 1;
+        while stack_len > 0 {
+            stack_len -= 1;
+            let node = &self.nodes[stack[stack_len] as usize];
+
+            // Entry per ray and lane, and the nearest over the packet
+            let mut near = [f32::INFINITY; W];
+            for (r, ray) in data.iter().enumerate() {
+                ray_near[r] = node.hit(ray, t_min, t_max[r]);
+                for lane in 0..W {
+                    near[lane] = near[lane].min(ray_near[r][lane]);
+                }
+            }
+
+            let order = sort_lanes(&near);
+            for &lane in &order {
+                if node.counts[lane] == 0 || !near[lane].is_finite() {
+                    continue;
+                }
+                let first = node.children[lane] as usize;
+                for (r, ray) in data.iter().enumerate() {
+                    // Only rays whose box test hit this leaf
+                    if !entered(ray_near[r][lane], t_max[r]) {
+                        continue;
+                    }
+                    for i in first..first + node.counts[lane] as usize {
+                        let triangle = self.triangle(i, ray.time);
+                        let interval = Interval::new(t_min, t_max[r]);
+                        let Some((t, u, v)) =
+                            triangle.intersect(ray.origin, ray.direction, interval)
+                        else {
+                            continue;
+                        };
+                        if accept(r, self.primitive_ids[i], u, v, t) {
+                            t_max[r] = t;
+                            best[r] = Some((i, u, v));
+                        }
+                    }
+                }
+            }
+
+            for &lane in order.iter().rev() {
+                if node.counts[lane] == 0 && near[lane].is_finite() {
+                    debug_assert!(stack_len < STACK_SIZE, "WideBvh stack overflow");
+                    stack[stack_len] = node.children[lane];
+                    stack_len += 1;
+                }
+            }
+        }
+
+        for (r, ray) in data.iter().enumerate() {
+            if let Some((i, u, v)) = best[r] {
+                hits[r] = Some(self.make_hit(i, t_max[r], u, v, ray.time));
+            }
+        }
+    }
+
+    /// Leaf-order triangle `index` at ray `time`.
+    #[inline]
+    fn triangle(&self, index: usize, time: f32) -> PackedTriangle {
+        triangle_at(&self.triangles, &self.motion, index, time)
+    }
+
+    fn make_hit(&self, index: usize, t: f32, u: f32, v: f32, time: f32) -> MeshHit {
+        let triangle = self.triangle(index, time);
+        let (p, p_error) = triangle.point(u, v);
+        MeshHit {
+            t,
+            u,
+            v,
+            primitive: self.primitive_ids[index],
+            normal: triangle.normal(),
+            p,
+            p_error,
+        }
+    }
+
+    /// Bounds of the whole mesh.
+    pub fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    /// Number of triangles.
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Number of wide nodes.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Approximate heap memory used by the BVH, in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.nodes.len() * std::mem::size_of::<WideNode<W>>()
+            + (self.motion.len() + 1) * self.triangles.len() * std::mem::size_of::<PackedTriangle>()
+            + self.primitive_ids.len() * std::mem::size_of::<u32>()
+    }
+}
+
+/// Whether a lane entry distance from `WideNode::hit` is a hit before `closest`.
+#[inline]
+fn entered(near: f32, closest: f32) -> bool {
+    near.is_finite() && near <= closest
+}
+
+/// Lane indices sorted by ascending entry distance.
+#[inline]
+fn sort_lanes<const W: usize>(near: &[f32; W]) -> [usize; W] {
+    let mut order: [usize; W] = std::array::from_fn(|lane| lane);
+    // Insertion sort: at most 8 lanes
+    for i in 1..W {
+        let mut j = i;
+        while j > 0 && near[order[j - 1]] > near[order[j]] {
+            order.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+
+    fn random_triangles(count: usize, seed: u64) -> Vec<[Vec3; 3]> {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let mut point = || Vec3::new(rng.gen(), rng.gen(), rng.gen());
+        (0..count)
+            .map(|_| {
+                let center = point() * 10.0;
+                [center, center + point(), center + point()]
+            })
+            .collect()
+    }
+
+    fn random_rays(count: usize, seed: u64) -> Vec<Ray> {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        (0..count)
+            .map(|_| {
+                let origin = Vec3::new(rng.gen(), rng.gen(), rng.gen()) * 10.0 - Vec3::splat(5.0);
+                let target = Vec3::new(rng.gen(), rng.gen(), rng.gen()) * 10.0;
+                Ray::new(origin, target - origin, 0.0)
+            })
+            .collect()
+    }
+
+    fn check_matches_binary<const W: usize>() {
+        let triangles = random_triangles(3000, 9);
+        let binary = MeshBvh::new(&triangles);
+        let wide = WideBvh::<W>::from_binary(binary.clone());
+        assert_eq!(wide.triangle_count(), 3000);
+        assert!(wide.node_count() < binary.node_count() / 2);
+
+        let range = Interval::new(0.001, f32::INFINITY);
+        let mut hits = 0;
+        for ray in random_rays(500, 4) {
+            let expected = binary.intersect(&ray, range, |_, _, _, _| true);
+            let actual = wide.intersect(&ray, range, |_, _, _, _| true);
+            match (expected, actual) {
+                (Some(a), Some(b)) => {
+                    hits += 1;
+                    assert_eq!(a.primitive, b.primitive);
+                    assert!((a.t - b.t).abs() < 1e-5);
+                }
+                (None, None) => {}
+                _ => panic!("mismatch: {expected:?} vs {actual:?}"),
+            }
+        }
+        assert!(hits > 100);
+    }
+
+    #[test]
+    fn test_refit_matches_rebuild() {
+        let triangles = random_triangles(2000, 21);
+        let mut binary = MeshBvh::new(&triangles);
+        let mut wide = Bvh4::from_binary(binary.clone());
+
+        // Deform: swirl every vertex around the Y axis
+        let moved: Vec<[Vec3; 3]> = triangles
+            .iter()
+            .map(|triangle| {
+                triangle.map(|p| {
+                    let angle = 0.05 * p.y;
+                    let (sin, cos) = angle.sin_cos();
+                    Vec3::new(p.x * cos - p.z * sin, p.y * 1.2, p.x * sin + p.z * cos)
+                })
+            })
+            .collect();
+        binary.refit(&moved);
+        wide.refit(&moved);
+        let rebuilt = MeshBvh::new(&moved);
+        assert_eq!(wide.bounding_box().y.max, binary.bounding_box().y.max);
+
+        let range = Interval::new(0.001, f32::INFINITY);
+        let mut hits = 0;
+        for ray in random_rays(500, 22) {
+            let expected = rebuilt.intersect(&ray, range, |_, _, _, _| true);
+            for actual in [
+                binary.intersect(&ray, range, |_, _, _, _| true),
+                wide.intersect(&ray, range, |_, _, _, _| true),
+            ] {
+                match (expected, actual) {
+                    (Some(a), Some(b)) => {
+                        hits += 1;
+                        assert_eq!(a.primitive, b.primitive);
+                        assert!((a.t - b.t).abs() < 1e-5);
+                    }
+                    (None, None) => {}
+                    _ => panic!("mismatch: {expected:?} vs {actual:?}"),
+                }
+            }
+        }
+        assert!(hits > 100);
+    }
+
+    #[test]
+    fn test_deformation_matches_static_frames() {
+        // Three keys: rest, shifted along X, then lifted along Z
+        let keys: Vec<Vec<[Vec3; 3]>> = [
+            Vec3::ZERO,
+            Vec3::new(3.0, 0.0, 0.0),
+            Vec3::new(3.0, 0.0, 4.0),
+        ]
+        .iter()
+        .map(|&offset| {
+            random_triangles(500, 31)
+                .iter()
+                .map(|triangle| triangle.map(|p| p + offset))
+                .collect()
+        })
+        .collect();
+        let wide = Bvh4::with_motion(&keys[0], &keys[1..]);
+        assert_eq!(
+            wide.bounding_box().z.max,
+            MeshBvh::new(&keys[2]).bounding_box().z.max
+        );
+
+        let range = Interval::new(0.001, f32::INFINITY);
+        let mut hits = 0;
+        for time in [0.0, 0.3, 0.5, 0.8, 1.0] {
+            // The mesh frozen at `time`
+            let (segment, t) = bif_math::motion_segment(time, keys.len());
+            let frame: Vec<[Vec3; 3]> = keys[segment]
+                .iter()
+                .zip(&keys[segment + 1])
+                .map(|(a, b)| std::array::from_fn(|i| a[i].lerp(b[i], t)))
+                .collect();
+            let frozen = MeshBvh::new(&frame);
+
+            let rays: Vec<Ray> = random_rays(200, 32)
+                .into_iter()
+                .map(|ray| Ray::new(ray.origin(), ray.direction(), time))
+                .collect();
+            let mut t_max = vec![f32::INFINITY; rays.len()];
+            let mut packet = vec![None; rays.len()];
+            wide.intersect_packet(
+                &rays,
+                range.min,
+                &mut t_max,
+                &mut packet,
+                |_, _, _, _, _| true,
+            );
+
+            for (ray, packet_hit) in rays.iter().zip(&packet) {
+                let expected = frozen.intersect(ray, range, |_, _, _, _| true);
+                let actual = wide.intersect(ray, range, |_, _, _, _| true);
+                assert_eq!(actual.map(|h| h.primitive), packet_hit.map(|h| h.primitive));
+                match (expected, actual) {
+                    (Some(a), Some(b)) => {
+                        hits += 1;
+                        assert_eq!(a.primitive, b.primitive);
+                        assert!((a.t - b.t).abs() < 1e-5);
+                        assert!((a.p - b.p).length() < 1e-4);
+                    }
+                    (None, None) => {}
+                    _ => panic!("mismatch at {time}: {expected:?} vs {actual:?}"),
+                }
+            }
+        }
+        assert!(hits > 100);
+    }
+
+    #[test]
+    fn test_bvh4_matches_binary() {
+        check_matches_binary::<4>();
+    }
+
+    #[test]
+    fn test_bvh8_matches_binary() {
+        check_matches_binary::<8>();
+    }
+
+    #[test]
+    fn test_packet_matches_single_rays() {
+        let triangles = random_triangles(1000, 2);
+        let bvh = Bvh4::new(&triangles);
+
+        // More rays than one packet pass, with every third candidate rejected
+        let rays = random_rays(100, 8);
+        let accept = |primitive: u32| !primitive.is_multiple_of(3);
+
+        let mut t_max = vec![f32::INFINITY; rays.len()];
+        let mut hits = vec![None; rays.len()];
+        bvh.intersect_packet(&rays, 0.001, &mut t_max, &mut hits, |_, p, _, _, _| {
+            accept(p)
+        });
+
+        for (ray, hit) in rays.iter().zip(&hits) {
+            let range = Interval::new(0.001, f32::INFINITY);
+            let expected = bvh.intersect(ray, range, |p, _, _, _| accept(p));
+            assert_eq!(expected.map(|h| h.primitive), hit.map(|h| h.primitive));
+        }
+    }
+
+    #[test]
+    fn test_single_leaf() {
+        let bvh = Bvh8::new(&[[Vec3::ZERO, Vec3::X, Vec3::Y]]);
+        assert_eq!(bvh.node_count(), 1);
+        let ray = Ray::new(Vec3::new(0.2, 0.2, 1.0), Vec3::NEG_Z, 0.0);
+        let hit = bvh.intersect(&ray, Interval::new(0.0, f32::INFINITY), |_, _, _, _| true);
+        assert!((hit.unwrap().t - 1.0).abs() < 1e-6);
+    }
+}
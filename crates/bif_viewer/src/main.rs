@@ -1,4 +1,5 @@
 use anyhow::Result;
+use bif_core::usd::LoadOptions;
 use bif_viewport::Renderer;
 use std::time::Instant;
 use winit::{
@@ -14,6 +15,9 @@ use winit::{
 struct CliOptions {
     usda_path: Option<String>,
     usd_path: Option<String>, // Uses C++ bridge (USDC, references)
+    load_options: LoadOptions,
+    shutter: (f32, f32),    // Ivar motion blur shutter (open, close)
+    camera: Option<String>, // Scene camera prim path to look through
 }
 
 fn parse_args() -> CliOptions {
@@ -29,6 +33,32 @@ fn parse_args() -> CliOptions {
                     i += 1;
                 }
             }
+            "--subdiv" => {
+                if let Some(level) = args.get(i + 1).and_then(|s| s.parse().ok()) {
+                    opts.load_options.subdivision_level = level;
+                    i += 1;
+                }
+            }
+            "--frame" => {
+                if let Some(time) = args.get(i + 1).and_then(|s| s.parse().ok()) {
+                    opts.load_options.time = Some(time);
+                    i += 1;
+                }
+            }
+            "--shutter" => {
+                let time = |offset: usize| args.get(i + offset).and_then(|s| s.parse().ok());
+                if let (Some(open), Some(close)) = (time(1), time(2)) {
+                    opts.shutter = (open, close);
+                    opts.load_options.motion_blur = close > open;
+                    i += 2;
+                }
+            }
+            "--camera" => {
+                if let Some(path) = args.get(i + 1) {
+                    opts.camera = Some(path.clone());
+                    i += 1;
+                }
+            }
             "--usd" => {
                 if i + 1 < args.len() {
                     opts.usd_path = Some(args[i + 1].clone());
@@ -51,6 +81,10 @@ fn parse_args() -> CliOptions {
                 println!("Options:");
                 println!("  --usda, -u <FILE>  Load a USDA scene file (pure Rust parser)");
                 println!("  --usd <FILE>       Load USD/USDA/USDC file (C++ bridge, supports references)");
+                println!("  --subdiv <LEVEL>   Catmull-Clark refinement level (default 2)");
+                println!("  --frame <TIME>     Time code to load (default: unanimated values)");
+                println!("  --shutter <OPEN> <CLOSE>  Ivar motion blur over the --frame, 0-1 (default 0 0)");
+                println!("  --camera <PATH>    Look through the scene camera at this prim path");
                 println!("  --help, -h         Show this help message");
                 println!();
                 println!("Note: --usd requires PXR_PLUGINPATH_NAME environment variable.");
@@ -61,6 +95,7 @@ fn parse_args() -> CliOptions {
                 println!("  Middle Mouse Drag  Pan camera");
                 println!("  Scroll Wheel       Zoom in/out");
                 println!("  WASD               Move camera");
+                println!("  Left/Right Arrow   Previous/next frame");
                 println!("  Tab                Toggle UI");
                 std::process::exit(0);
             }
@@ -78,6 +113,12 @@ struct App {
     renderer: Option<Renderer>,
     usda_path: Option<String>,
     usd_path: Option<String>, // C++ bridge path
+    load_options: LoadOptions,
+    shutter: (f32, f32),
+    camera: Option<String>,
+
+    // Time code shown (None until a scene is loaded)
+    frame: Option<f64>,
 
     // Input state
     left_mouse_pressed: bool,
@@ -88,12 +129,22 @@ struct App {
 }
 
 impl App {
-    fn new(usda_path: Option<String>, usd_path: Option<String>) -> Self {
+    fn new(
+        usda_path: Option<String>,
+        usd_path: Option<String>,
+        load_options: LoadOptions,
+        shutter: (f32, f32),
+        camera: Option<String>,
+    ) -> Self {
         Self {
             window: None,
             renderer: None,
             usda_path,
             usd_path,
+            load_options,
+            shutter,
+            camera,
+            frame: None,
             left_mouse_pressed: false,
             middle_mouse_pressed: false,
             last_mouse_pos: None,
@@ -117,10 +168,10 @@ impl ApplicationHandler for App {
             );
 
             // Load scene based on CLI options
-            let renderer = if let Some(usd_path) = &self.usd_path {
+            let mut renderer = if let Some(usd_path) = &self.usd_path {
                 // Use C++ bridge for --usd flag (supports USDC and references)
                 log::info!("Loading USD scene via C++ bridge: {}", usd_path);
-                match bif_core::usd::load_usd_with_stage(usd_path) {
+                match bif_core::usd::load_usd_with_options(usd_path, &self.load_options) {
                     Ok((scene, stage)) => {
                         log::info!(
                             "Scene loaded: {} prototypes, {} instances, {} prims",
@@ -149,7 +200,7 @@ impl ApplicationHandler for App {
                 if let Some(usda_path) = self.usda_path.clone() {
                     log::info!("Loading USDA scene: {}", usda_path);
                     // Try C++ bridge first to get scene browser support
-                    match bif_core::usd::load_usd_with_stage(&usda_path) {
+                    match bif_core::usd::load_usd_with_options(&usda_path, &self.load_options) {
                         Ok((scene, stage)) => {
                             log::info!("Scene loaded via C++ bridge: {} prototypes, {} instances, {} prims", 
                                        scene.prototype_count(), scene.instance_count(), stage.prim_count().unwrap_or(0));
@@ -166,7 +217,10 @@ impl ApplicationHandler for App {
                                 "C++ bridge failed ({}), using pure Rust parser (no scene browser)",
                                 e
                             );
-                            match bif_core::load_usda(&usda_path) {
+                            match bif_core::usd::load_usda_with_options(
+                                &usda_path,
+                                &self.load_options,
+                            ) {
                                 Ok(scene) => {
                                     log::info!(
                                         "Scene loaded: {} prototypes, {} instances",
@@ -196,6 +250,15 @@ impl ApplicationHandler for App {
                 }
             };
 
+            renderer.ivar_state.shutter = self.shutter;
+            self.frame = Some(
+                self.load_options
+                    .time
+                    .unwrap_or(renderer.time_codes().start),
+            );
+            if let Some(path) = &self.camera {
+                renderer.set_active_camera(Some(path));
+            }
             self.window = Some(window);
             self.renderer = Some(renderer);
 
@@ -312,6 +375,20 @@ impl ApplicationHandler for App {
                                 renderer.frame_mesh();
                             }
                         }
+
+                        // Step through the animation
+                        let step = match keycode {
+                            KeyCode::ArrowLeft => -1.0,
+                            KeyCode::ArrowRight => 1.0,
+                            _ => 0.0,
+                        };
+                        if let (true, Some(renderer), Some(frame)) =
+                            (step != 0.0, &mut self.renderer, &mut self.frame)
+                        {
+                            let range = renderer.time_codes();
+                            *frame = (*frame + step).clamp(range.start, range.end.max(range.start));
+                            renderer.set_frame(*frame);
+                        }
                     }
                     ElementState::Released => {
                         self.keys_pressed.remove(&keycode);
@@ -434,7 +511,13 @@ fn main() -> Result<()> {
     let event_loop = EventLoop::new()?;
     event_loop.set_control_flow(ControlFlow::Poll);
 
-    let mut app = App::new(opts.usda_path, opts.usd_path);
+    let mut app = App::new(
+        opts.usda_path,
+        opts.usd_path,
+        opts.load_options,
+        opts.shutter,
+        opts.camera,
+    );
 
     log::info!("Running event loop");
     event_loop.run_app(&mut app)?;
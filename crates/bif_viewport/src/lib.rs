@@ -5,6 +5,7 @@ use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
+use std::thread::JoinHandle;
 use std::time::Instant;
 
 use wgpu::{util::DeviceExt, Device, Instance, Queue, Surface, SurfaceConfiguration};
@@ -16,8 +17,8 @@ use bif_core::usd::UsdStage;
 
 // Re-export bif_renderer types for Ivar integration
 use bif_renderer::{
-    generate_buckets, render_bucket, Bucket, BucketResult, BvhNode, Color, DisneyBSDF, EmbreeScene,
-    Hittable, ImageBuffer, RenderConfig, DEFAULT_BUCKET_SIZE,
+    generate_buckets, render_bucket, Bucket, BucketResult, Color, ImageBuffer, InstancedLights,
+    InstancedWorld, IvarScene, LightList, RenderConfig, SceneBuilder, DEFAULT_BUCKET_SIZE,
 };
 
 // Scene browser and property inspector modules
@@ -115,6 +116,8 @@ pub struct IvarState {
     pub render_complete: bool,
     /// Cancel flag for background thread
     pub cancel_flag: Arc<AtomicBool>,
+    /// Background render thread (holds a clone of `world` while running)
+    pub render_thread: Option<JoinHandle<()>>,
     /// Receiver for bucket completion messages
     pub receiver: Option<mpsc::Receiver<IvarMessage>>,
     /// Last camera snapshot for dirty detection
@@ -124,16 +127,24 @@ pub struct IvarState {
     /// Cached world geometry (BVH of triangles)
     /// TODO: Invalidate world cache when scene is reloaded or modified
     /// TODO: Add "Rebuild Scene" button to manually invalidate cached BVH
-    pub world: Option<Arc<BvhNode>>,
+    pub world: Option<Arc<dyn InstancedWorld>>,
+    /// Emissive triangles of the cached world, for light sampling
+    pub lights: Option<Arc<LightList>>,
+    /// Local-space emissive triangles, to rebuild `lights` after world edits
+    pub instanced_lights: Option<InstancedLights>,
+    /// World prototype index of each scene prototype (see `IvarScene`)
+    pub prototype_indices: Vec<Option<usize>>,
     /// Scene build status for async construction
     pub build_status: BuildStatus,
     /// Receiver for scene build completion
-    pub build_receiver: Option<mpsc::Receiver<Arc<BvhNode>>>,
+    pub build_receiver: Option<mpsc::Receiver<IvarScene>>,
     /// Samples per pixel for rendering
     /// TODO: Expose SPP in UI
     pub samples_per_pixel: u32,
     /// Max bounce depth
     pub max_depth: u32,
+    /// Shutter (open, close) for motion blur, in normalized motion time
+    pub shutter: (f32, f32),
 }
 
 impl Default for IvarState {
@@ -145,14 +156,19 @@ impl Default for IvarState {
             buckets_completed: 0,
             render_complete: false,
             cancel_flag: Arc::new(AtomicBool::new(false)),
+            render_thread: None,
             receiver: None,
             last_camera_snapshot: None,
             render_start_time: None,
             world: None,
+            lights: None,
+            instanced_lights: None,
+            prototype_indices: Vec::new(),
             build_status: BuildStatus::NotStarted,
             build_receiver: None,
             samples_per_pixel: 16, // Lower for interactive preview
             max_depth: 8,
+            shutter: (0.0, 0.0),
         }
     }
 }
@@ -175,6 +191,18 @@ impl IvarState {
         self.render_start_time = Some(Instant::now());
     }
 
+    /// Cancel the current render and wait for its thread to exit, so it no
+    /// longer holds the world.
+    pub fn stop_render(&mut self) {
+        self.cancel_flag.store(true, Ordering::Relaxed);
+        self.cancel_flag = Arc::new(AtomicBool::new(false));
+        if let Some(thread) = self.render_thread.take() {
+            // Rayon finishes the buckets in flight, then the thread exits
+            let _ = thread.join();
+        }
+        self.render_complete = false;
+    }
+
     /// Check if camera has moved and render needs restart
     pub fn check_camera_dirty(&mut self, camera: &Camera) -> bool {
         let current = CameraSnapshot::from_camera(camera);
@@ -774,6 +802,56 @@ impl InstanceData {
     }
 }
 
+/// Per-point data for drawing particles as camera-facing sprites
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PointSprite {
+    /// World-space center (xyz) and radius (w)
+    pub center_radius: [f32; 4],
+    /// RGB + padding
+    pub color: [f32; 4],
+}
+
+impl PointSprite {
+    const ATTRIBS: [wgpu::VertexAttribute; 2] =
+        wgpu::vertex_attr_array![0 => Float32x4, 1 => Float32x4];
+
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<PointSprite>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+
+    /// Collect world-space sprites for every point set in the scene,
+    /// colored by the set's material.
+    pub fn from_scene(scene: &bif_core::Scene) -> Vec<Self> {
+        let mut sprites = Vec::with_capacity(scene.total_point_count());
+        for set in &scene.points {
+            let color = set
+                .material
+                .as_ref()
+                .map(|m| m.diffuse_color)
+                .unwrap_or_else(|| bif_core::Material::default().diffuse_color);
+            let radius_scale = set.transform.determinant().abs().cbrt();
+            for i in 0..set.points.len() {
+                let center = set.transform.transform_point3(set.points.positions[i]);
+                sprites.push(PointSprite {
+                    center_radius: [
+                        center.x,
+                        center.y,
+                        center.z,
+                        set.points.radius(i) * radius_scale,
+                    ],
+                    color: [color.x, color.y, color.z, 1.0],
+                });
+            }
+        }
+        sprites
+    }
+}
+
 /// Scratch buffers for frustum culling to avoid per-frame allocations
 struct CullingScratch {
     visible_with_distance: Vec<(f32, usize)>,
@@ -810,6 +888,12 @@ pub struct Renderer {
     num_indices: u32,
     instance_buffer: wgpu::Buffer,
     num_instances: u32,
+
+    // Point sprites (particles), drawn after the meshes
+    points_pipeline: wgpu::RenderPipeline,
+    point_buffer: wgpu::Buffer,
+    num_points: u32,
+
     pub camera: Camera,
     camera_uniform: CameraUniform,
     camera_buffer: wgpu::Buffer,
@@ -857,15 +941,12 @@ pub struct Renderer {
     ivar_bind_group: wgpu::BindGroup,
     ivar_pipeline: wgpu::RenderPipeline,
 
-    // Cached mesh data for Ivar scene building
-    mesh_data: MeshData,
+    // Loaded scene, converted to an Ivar world on demand
+    scene: Arc<bif_core::Scene>,
 
     // Instance transforms for Ivar (stored as Mat4 arrays)
     instance_transforms: Vec<Mat4>,
 
-    // Material for Ivar rendering (from loaded USD scene)
-    scene_material: bif_core::Material,
-
     // Frustum culling for GPU instancing optimization
     /// Maximum instances the buffer can hold (preallocated)
     #[allow(dead_code)]
@@ -900,6 +981,10 @@ pub struct Renderer {
     /// Camera snapshot for frustum cache invalidation
     frustum_camera_snapshot: CameraSnapshot,
 
+    // Scene camera the viewport looks through and the view it set (moving
+    // the view leaves the scene camera)
+    active_camera: Option<(String, CameraSnapshot)>,
+
     // Scene browser state
     pub scene_browser_state: SceneBrowserState,
 
@@ -1068,6 +1153,103 @@ impl Renderer {
         (pipeline, bind_group, bind_group_layout)
     }
 
+    /// Create the point sprite pipeline (shares the camera bind group)
+    fn create_points_pipeline(
+        device: &Device,
+        surface_format: wgpu::TextureFormat,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Points Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/points.wgsl").into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Points Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Points Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[PointSprite::desc()], // Quad corners come from vertex_index
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None, // Sprites always face the camera
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth24Plus,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// Mesh data and material of the first prototype.
+    ///
+    /// Points-only scenes get a box over the world bounds, which is never
+    /// drawn since they have no instances.
+    fn first_prototype_mesh(scene: &bif_core::Scene) -> (MeshData, bif_core::Material) {
+        match scene.prototypes.first() {
+            Some(proto) => (
+                MeshData::from_core_mesh(&proto.mesh),
+                proto
+                    .material
+                    .as_ref()
+                    .map(|m| (**m).clone())
+                    .unwrap_or_default(),
+            ),
+            None => (
+                MeshData::from_aabb(&scene.world_bounds()),
+                bif_core::Material::default(),
+            ),
+        }
+    }
+
+    /// Create a point sprite buffer (never empty, so it can always be bound)
+    fn create_point_buffer(device: &Device, sprites: &[PointSprite]) -> wgpu::Buffer {
+        let placeholder = [<PointSprite as bytemuck::Zeroable>::zeroed()];
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Point Sprite Buffer"),
+            contents: bytemuck::cast_slice(if sprites.is_empty() {
+                &placeholder
+            } else {
+                sprites
+            }),
+            usage: wgpu::BufferUsages::VERTEX,
+        })
+    }
+
     /// Upload Ivar image buffer to GPU texture
     fn upload_ivar_pixels(&self, image: &ImageBuffer) {
         let rgba = image.to_rgba();
@@ -1510,6 +1692,10 @@ impl Renderer {
 
         log::info!("Ivar resources initialized");
 
+        let points_pipeline =
+            Self::create_points_pipeline(&device, config.format, &camera_bind_group_layout);
+        let point_buffer = Self::create_point_buffer(&device, &[]);
+
         Ok(Self {
             surface,
             device,
@@ -1522,6 +1708,9 @@ impl Renderer {
             num_indices: 0, // Empty scene - no indices
             instance_buffer,
             num_instances: 0, // Empty scene - no instances
+            points_pipeline,
+            point_buffer,
+            num_points: 0,
             camera,
             camera_uniform,
             camera_buffer,
@@ -1556,9 +1745,8 @@ impl Renderer {
             ivar_sampler,
             ivar_bind_group,
             ivar_pipeline,
-            mesh_data,
+            scene: Arc::new(bif_core::Scene::default()),
             instance_transforms: vec![], // Empty scene - no instances
-            scene_material: bif_core::Material::default(),
             max_instances: MAX_INSTANCES,
             instance_aabbs: vec![],
             prototype_aabb: Aabb::empty(),
@@ -1575,6 +1763,7 @@ impl Renderer {
                 camera.projection_matrix() * camera.view_matrix(),
             ),
             frustum_camera_snapshot: CameraSnapshot::from_camera(&camera),
+            active_camera: None,
             scene_browser_state: SceneBrowserState::new(),
             selected_prim_path: None,
             selected_prim_properties: None,
@@ -1645,19 +1834,11 @@ impl Renderer {
         surface.configure(&device, &config);
 
         // Convert first prototype to MeshData (for now, use first prototype)
-        if scene.prototypes.is_empty() {
-            anyhow::bail!("Scene has no prototypes");
+        if scene.prototypes.is_empty() && scene.points.is_empty() {
+            anyhow::bail!("Scene has no geometry");
         }
 
-        let proto = &scene.prototypes[0];
-        let mesh_data = MeshData::from_core_mesh(&proto.mesh);
-
-        // Get material from prototype (or use default)
-        let scene_material = proto
-            .material
-            .as_ref()
-            .map(|m| (**m).clone())
-            .unwrap_or_default();
+        let (mesh_data, scene_material) = Self::first_prototype_mesh(scene);
         log::info!(
             "Material: {} (metallic={:.2}, roughness={:.2})",
             scene_material.name,
@@ -2073,6 +2254,11 @@ impl Renderer {
 
         log::info!("Ivar resources initialized");
 
+        let points_pipeline =
+            Self::create_points_pipeline(&device, config.format, &camera_bind_group_layout);
+        let point_sprites = PointSprite::from_scene(scene);
+        let point_buffer = Self::create_point_buffer(&device, &point_sprites);
+
         Ok(Self {
             surface,
             device,
@@ -2085,6 +2271,9 @@ impl Renderer {
             num_indices: mesh_data.indices.len() as u32,
             instance_buffer,
             num_instances: instances.len() as u32,
+            points_pipeline,
+            point_buffer,
+            num_points: point_sprites.len() as u32,
             camera,
             camera_uniform,
             camera_buffer,
@@ -2119,9 +2308,8 @@ impl Renderer {
             ivar_sampler,
             ivar_bind_group,
             ivar_pipeline,
-            mesh_data,
+            scene: Arc::new(scene.clone()),
             instance_transforms,
-            scene_material,
             max_instances: MAX_INSTANCES,
             instance_aabbs,
             prototype_aabb,
@@ -2138,6 +2326,7 @@ impl Renderer {
                 camera.projection_matrix() * camera.view_matrix(),
             ),
             frustum_camera_snapshot: CameraSnapshot::from_camera(&camera),
+            active_camera: None,
             scene_browser_state: SceneBrowserState::new(),
             selected_prim_path: None,
             selected_prim_properties: None,
@@ -2353,6 +2542,57 @@ impl Renderer {
         );
     }
 
+    /// Cameras of the loaded scene.
+    pub fn scene_cameras(&self) -> &[bif_core::Camera] {
+        &self.scene.cameras
+    }
+
+    /// Prim path of the scene camera the viewport looks through, if the view
+    /// hasn't been moved since it was selected.
+    pub fn active_camera(&self) -> Option<&str> {
+        let (path, snapshot) = self.active_camera.as_ref()?;
+        let current = CameraSnapshot::from_camera(&self.camera);
+        (!snapshot.has_changed(&current)).then_some(path.as_str())
+    }
+
+    /// Look through the scene camera at `path`, or go back to the free
+    /// orbit camera with `None`.
+    ///
+    /// The viewport takes the camera's position, orientation, field of view
+    /// and clipping range and can be orbited from there. Until the view is
+    /// moved, Ivar renders through the scene camera itself (with its depth
+    /// of field). Returns false if the scene has no camera at `path`.
+    pub fn set_active_camera(&mut self, path: Option<&str>) -> bool {
+        let Some(path) = path else {
+            self.active_camera = None;
+            self.camera.up = Vec3::Y;
+            // Clamps the pitch of cameras looking straight up or down
+            self.camera.orbit(0.0, 0.0);
+            self.update_camera();
+            return true;
+        };
+        let Some(scene_camera) = self.scene.find_camera(path) else {
+            log::warn!("Scene has no camera {}", path);
+            return false;
+        };
+
+        let mut camera = Camera::new(
+            scene_camera.position(),
+            scene_camera.target(),
+            self.camera.aspect,
+        );
+        camera.up = scene_camera.up();
+        camera.fov_y = scene_camera.vertical_fov().to_radians();
+        (camera.near, camera.far) = scene_camera.clipping_range;
+        camera.move_speed = self.camera.move_speed;
+        self.camera = camera;
+        self.update_camera();
+
+        self.active_camera = Some((path.to_string(), CameraSnapshot::from_camera(&self.camera)));
+        log::info!("Looking through camera {}", path);
+        true
+    }
+
     /// Load a USD scene file and update the viewport
     ///
     /// This method reloads the viewport with a new USD file:
@@ -2371,20 +2611,12 @@ impl Renderer {
         let (scene, stage) =
             load_usd_with_stage(path).map_err(|e| anyhow::anyhow!("Failed to load USD: {}", e))?;
 
-        if scene.prototypes.is_empty() {
+        if scene.prototypes.is_empty() && scene.points.is_empty() {
             return Err(anyhow::anyhow!("Scene has no geometry"));
         }
 
         // Convert first prototype to MeshData
-        let proto = &scene.prototypes[0];
-        let mesh_data = MeshData::from_core_mesh(&proto.mesh);
-
-        // Get material from prototype (or use default)
-        let scene_material = proto
-            .material
-            .as_ref()
-            .map(|m| (**m).clone())
-            .unwrap_or_default();
+        let (mesh_data, scene_material) = Self::first_prototype_mesh(&scene);
         log::info!(
             "Material: {} (metallic={:.2}, roughness={:.2})",
             scene_material.name,
@@ -2452,6 +2684,10 @@ impl Renderer {
 
         log::info!("Created {} instances from USD scene", instances.len());
 
+        let point_sprites = PointSprite::from_scene(&scene);
+        self.point_buffer = Self::create_point_buffer(&self.device, &point_sprites);
+        self.num_points = point_sprites.len() as u32;
+
         // Calculate world bounds for camera framing
         let world_bounds = scene.world_bounds();
         let mesh_center = Vec3::new(
@@ -2476,9 +2712,8 @@ impl Renderer {
         self.visible_instance_count = instances.len() as u32;
         self.mesh_bounds_min = mesh_data.bounds_min;
         self.mesh_bounds_max = mesh_data.bounds_max;
-        self.mesh_data = mesh_data;
         self.instance_transforms = instance_transforms;
-        self.scene_material = scene_material.clone();
+        self.scene = Arc::new(scene);
 
         // Update material uniform buffer for viewport PBR
         self.material_uniform = MaterialUniform::from_material(&scene_material);
@@ -2525,6 +2760,8 @@ impl Renderer {
         self.selected_prim_properties = None;
 
         // Update camera to frame the scene
+        self.active_camera = None;
+        self.camera.up = Vec3::Y;
         self.camera.target = mesh_center;
         self.camera.distance = camera_distance;
         self.camera.near = camera_distance * 0.01;
@@ -2534,6 +2771,8 @@ impl Renderer {
 
         // Invalidate Ivar scene cache
         self.ivar_state.world = None;
+        self.ivar_state.lights = None;
+        self.ivar_state.instanced_lights = None;
         self.ivar_state.build_status = BuildStatus::NotStarted;
         self.ivar_state.cancel_flag.store(true, Ordering::Relaxed);
         self.ivar_state.render_complete = false;
@@ -2570,11 +2809,11 @@ impl Renderer {
         }
     }
 
-    /// Build Ivar scene from viewport mesh data using instancing (async in background thread).
+    /// Build Ivar scene from the loaded scene using instancing (async in background thread).
     ///
-    /// NEW: Uses InstancedGeometry to build ONE BVH for the prototype mesh
-    /// instead of duplicating 28M triangles. This reduces build time from
-    /// ~4 seconds to ~40ms (100x faster) and memory from ~5GB to ~50MB.
+    /// Builds ONE BVH per prototype mesh (Embree, or the pure-Rust
+    /// `InstancedGeometryBVH` when Embree is missing) instead of duplicating 28M triangles.
+    /// See `bif_renderer::SceneBuilder`.
     ///
     /// ASYNC: Runs on background thread to keep UI responsive during build.
     fn build_ivar_scene(&mut self) {
@@ -2592,18 +2831,16 @@ impl Renderer {
         }
 
         log::info!(
-            "Starting background Ivar scene build: {} instances, {} tris/instance",
-            self.instance_transforms.len(),
-            self.mesh_data.indices.len() / 3
+            "Starting background Ivar scene build: {} prototypes, {} instances",
+            self.scene.prototype_count(),
+            self.scene.instance_count()
         );
 
         // Mark as building
         self.ivar_state.build_status = BuildStatus::Building;
 
-        // Clone data needed for background thread
-        let mesh_data = self.mesh_data.clone();
-        let transforms = self.instance_transforms.clone();
-        let material = self.scene_material.clone();
+        // Share the scene with the background thread
+        let scene = self.scene.clone();
 
         // Create channel for build completion
         let (tx, rx) = mpsc::channel();
@@ -2611,66 +2848,11 @@ impl Renderer {
 
         // Spawn background thread to build scene
         std::thread::spawn(move || {
-            let start_time = Instant::now();
-
-            log::info!(
-                "Background thread: Building Embree scene ({} triangles, {} instances)...",
-                mesh_data.indices.len() / 3,
-                transforms.len()
-            );
-
-            // Extract triangle vertices for Embree
-            let mut triangle_vertices = Vec::with_capacity(mesh_data.indices.len() / 3);
-            for i in (0..mesh_data.indices.len()).step_by(3) {
-                let i0 = mesh_data.indices[i] as usize;
-                let i1 = mesh_data.indices[i + 1] as usize;
-                let i2 = mesh_data.indices[i + 2] as usize;
-
-                // Get vertices in LOCAL space (no transformation)
-                let v0 = Vec3::from_array(mesh_data.vertices[i0].position);
-                let v1 = Vec3::from_array(mesh_data.vertices[i1].position);
-                let v2 = Vec3::from_array(mesh_data.vertices[i2].position);
-
-                triangle_vertices.push([v0, v1, v2]);
-            }
-
-            log::info!("Background thread: Extracted {} triangles, creating acceleration structure with {} instances...",
-                triangle_vertices.len(), transforms.len());
-
-            // Convert material to Disney BSDF
-            let disney_mat = DisneyBSDF::from(&material);
-            log::info!(
-                "Using Disney BSDF: base_color=({:.2}, {:.2}, {:.2}), metallic={:.2}, roughness={:.2}",
-                disney_mat.base_color.x, disney_mat.base_color.y, disney_mat.base_color.z,
-                disney_mat.metallic, disney_mat.roughness
-            );
-
-            // Try to create Embree scene first, fall back to CPU BVH if unavailable
-            let world = if let Some(embree_scene) =
-                EmbreeScene::try_new(&triangle_vertices, transforms.clone(), disney_mat)
-            {
-                log::info!("Using Embree for hardware-accelerated ray tracing");
-                // Wrap Embree scene in a BVH node (BVH contains just 1 object)
-                let objects: Vec<Box<dyn Hittable + Send + Sync>> = vec![Box::new(embree_scene)];
-                Arc::new(BvhNode::new(objects))
-            } else {
-                log::warn!("Embree not available - using CPU BVH (slower performance)");
-                log::info!("To enable Embree acceleration, ensure embree4.dll is in PATH");
-                // Fall back to CPU BVH - create instances manually
-                // TODO: Implement CPU-based instancing fallback
-                // For now, just create empty BVH
-                let objects: Vec<Box<dyn Hittable + Send + Sync>> = vec![];
-                Arc::new(BvhNode::new(objects))
-            };
-
-            let elapsed = start_time.elapsed();
-            log::info!(
-                "Background thread: Ivar scene built in {:.2}ms",
-                elapsed.as_secs_f64() * 1000.0
-            );
+            // The camera is recreated from the viewport for every render
+            let ivar_scene = SceneBuilder::new(&scene).build();
 
             // Send completed scene to main thread
-            let _ = tx.send(world);
+            let _ = tx.send(ivar_scene);
         });
     }
 
@@ -2687,6 +2869,8 @@ impl Renderer {
 
         // Clear cached scene
         self.ivar_state.world = None;
+        self.ivar_state.lights = None;
+        self.ivar_state.instanced_lights = None;
         self.ivar_state.build_status = BuildStatus::NotStarted;
         self.ivar_state.build_receiver = None;
 
@@ -2702,6 +2886,172 @@ impl Renderer {
         log::info!("Ivar scene cache cleared - will rebuild on next render");
     }
 
+    /// Move the instances of one prototype without rebuilding the Ivar scene.
+    ///
+    /// `transforms` has one matrix per instance of `prototype`, in scene
+    /// order. See `update_ivar_world` for how the Ivar world follows.
+    pub fn update_ivar_transforms(&mut self, prototype: usize, transforms: &[Mat4]) {
+        let scene = Arc::make_mut(&mut self.scene);
+        let instances = scene
+            .instances
+            .iter_mut()
+            .filter(|instance| instance.prototype_id == prototype);
+        for (instance, transform) in instances.zip(transforms) {
+            instance.transform = bif_core::Transform::from_matrix(*transform);
+        }
+        self.sync_viewport_transforms(&[prototype]);
+        self.update_ivar_world(&[prototype], &[]);
+    }
+
+    /// Playback range of the loaded scene.
+    pub fn time_codes(&self) -> bif_core::TimeCodes {
+        self.scene.time_codes
+    }
+
+    /// Move one instance (a transform edit), updating the viewport and the
+    /// Ivar world.
+    pub fn set_instance_transform(&mut self, instance: usize, transform: Mat4) {
+        let scene = Arc::make_mut(&mut self.scene);
+        let Some(edited) = scene.instances.get_mut(instance) else {
+            log::warn!("No instance {} to move", instance);
+            return;
+        };
+        edited.transform = bif_core::Transform::from_matrix(transform);
+        let prototype = edited.prototype_id;
+        self.sync_viewport_transforms(&[prototype]);
+        self.update_ivar_world(&[prototype], &[]);
+    }
+
+    /// Move the scene to another frame (a time code, see `Scene::set_time`).
+    ///
+    /// Animated instances and deforming prototypes update the Ivar world in
+    /// place. Animated curves and points need a rebuild. With the shutter
+    /// open, motion keys are sampled over the new frame. The viewport only
+    /// follows the instance transforms; its mesh is not re-uploaded.
+    pub fn set_frame(&mut self, time: f64) {
+        let (open, close) = self.ivar_state.shutter;
+        let scene = Arc::make_mut(&mut self.scene);
+        scene.set_time(time);
+        if close > open {
+            scene.sample_motion(time);
+        }
+        let animation = &scene.animation;
+
+        let mut moved: Vec<usize> = animation
+            .instances
+            .keys()
+            .filter_map(|&index| scene.instances.get(index))
+            .map(|instance| instance.prototype_id)
+            .collect();
+        moved.sort_unstable();
+        moved.dedup();
+        let deformed: Vec<usize> = animation.prototypes.keys().copied().collect();
+        let rebuild = !animation.curves.is_empty() || !animation.points.is_empty();
+
+        self.sync_viewport_transforms(&moved);
+        log::info!("Moved scene to frame {}", time);
+        if rebuild {
+            self.invalidate_ivar_scene();
+        } else if !moved.is_empty() || !deformed.is_empty() {
+            self.update_ivar_world(&moved, &deformed);
+        }
+    }
+
+    /// Copy the scene transforms of the instances of `prototypes` to the
+    /// viewport (for drawing and frustum culling).
+    fn sync_viewport_transforms(&mut self, prototypes: &[usize]) {
+        let instances = self.scene.instances.iter().enumerate();
+        for (index, instance) in instances {
+            if !prototypes.contains(&instance.prototype_id) {
+                continue;
+            }
+            let transform = instance.model_matrix();
+            if let Some(slot) = self.instance_transforms.get_mut(index) {
+                *slot = transform;
+            }
+            if let Some(aabb) = self.instance_aabbs.get_mut(index) {
+                *aabb = transform.transform_aabb(&self.prototype_aabb);
+            }
+        }
+    }
+
+    /// Bring the cached Ivar world up to date with the scene after instances
+    /// of the `moved` prototypes or the meshes of the `deformed` prototypes
+    /// changed (scene prototype IDs).
+    ///
+    /// Stops the render and joins its thread so the world can be edited in
+    /// place, which rebuilds only the top-level BVH (or refits a prototype
+    /// BVH). Lights are rebuilt from `instanced_lights`, and the render is
+    /// restarted. Falls back to `invalidate_ivar_scene` when no world is
+    /// built yet, a displaced prototype deforms, or the shutter is open and
+    /// the updated prototypes carry motion keys (in-place updates leave
+    /// them static).
+    fn update_ivar_world(&mut self, moved: &[usize], deformed: &[usize]) {
+        self.ivar_state.stop_render();
+
+        let scene = Arc::clone(&self.scene);
+        let displaced = deformed.iter().any(|&id| {
+            scene.prototypes[id]
+                .material
+                .as_ref()
+                .is_some_and(|m| m.has_displacement())
+        });
+        let (open, close) = self.ivar_state.shutter;
+        let blurred = close > open
+            && moved.iter().chain(deformed).any(|&id| {
+                !scene.prototypes[id].mesh.motion.is_empty()
+                    || scene
+                        .instances
+                        .iter()
+                        .any(|instance| instance.prototype_id == id && instance.is_moving())
+            });
+        let state = &mut self.ivar_state;
+        let (Some(world), Some(lights)) = (
+            state.world.as_mut().and_then(Arc::get_mut),
+            state.instanced_lights.as_mut(),
+        ) else {
+            self.invalidate_ivar_scene();
+            return;
+        };
+        if displaced || blurred {
+            self.invalidate_ivar_scene();
+            return;
+        }
+
+        let world_index = |id: usize| state.prototype_indices.get(id).copied().flatten();
+        for &id in moved {
+            let Some(index) = world_index(id) else {
+                continue;
+            };
+            let transforms: Vec<Mat4> = scene
+                .instances
+                .iter()
+                .filter(|instance| instance.prototype_id == id)
+                .map(|instance| instance.model_matrix())
+                .collect();
+            world.set_transforms(index, &transforms);
+            lights.set_transforms(index, &transforms);
+        }
+        for &id in deformed {
+            let Some(index) = world_index(id) else {
+                continue;
+            };
+            let vertices = scene.prototypes[id].mesh.extract_triangle_vertices();
+            world.refit_prototype(index, &vertices);
+            lights.refit_prototype(index, &vertices);
+        }
+        state.lights = Some(Arc::new(lights.light_list()));
+        log::info!(
+            "Updated Ivar world in place: {} moved, {} deformed prototypes",
+            moved.len(),
+            deformed.len()
+        );
+
+        if state.mode == RenderMode::Ivar {
+            self.start_ivar_render();
+        }
+    }
+
     /// Poll for scene build completion (call each frame).
     ///
     /// Checks if background scene build is complete, and if so:
@@ -2719,11 +3069,14 @@ impl Renderer {
         };
 
         // Non-blocking check for completion
-        if let Ok(world) = receiver.try_recv() {
+        if let Ok(ivar_scene) = receiver.try_recv() {
             log::info!("Scene build completed, received on main thread");
 
             // Store completed scene
-            self.ivar_state.world = Some(world);
+            self.ivar_state.world = Some(ivar_scene.world);
+            self.ivar_state.lights = Some(ivar_scene.lights);
+            self.ivar_state.instanced_lights = Some(ivar_scene.instanced_lights);
+            self.ivar_state.prototype_indices = ivar_scene.prototype_indices;
             self.ivar_state.build_status = BuildStatus::Complete;
 
             // Clear receiver
@@ -2736,16 +3089,28 @@ impl Renderer {
     }
 
     /// Create Ivar camera from viewport camera
+    ///
+    /// Looks through the active scene camera, if any, with its depth of
+    /// field.
     fn create_ivar_camera(&self) -> bif_renderer::Camera {
-        let mut camera = bif_renderer::Camera::new()
+        let camera = bif_renderer::Camera::new()
             .with_resolution(self.size.0, self.size.1)
-            .with_position(self.camera.position, self.camera.target, Vec3::Y)
-            .with_lens(
-                self.camera.fov_y.to_degrees(),
-                0.0, // No DOF for preview
-                (self.camera.target - self.camera.position).length(),
-            )
-            .with_quality(self.ivar_state.samples_per_pixel, self.ivar_state.max_depth);
+            .with_quality(self.ivar_state.samples_per_pixel, self.ivar_state.max_depth)
+            .with_shutter(self.ivar_state.shutter.0, self.ivar_state.shutter.1);
+
+        let scene_camera = self
+            .active_camera()
+            .and_then(|path| self.scene.find_camera(path));
+        let mut camera = match scene_camera {
+            Some(scene_camera) => camera.with_scene_camera(scene_camera),
+            None => camera
+                .with_position(self.camera.position, self.camera.target, self.camera.up)
+                .with_lens(
+                    self.camera.fov_y.to_degrees(),
+                    0.0, // No DOF for preview
+                    (self.camera.target - self.camera.position).length(),
+                ),
+        };
 
         camera.initialize();
         camera
@@ -2779,6 +3144,8 @@ impl Renderer {
             max_depth: self.ivar_state.max_depth,
             background: Color::new(0.1, 0.1, 0.1),
             use_sky_gradient: true,
+            lights: self.ivar_state.lights.clone(),
+            packet_primary_rays: false,
         };
 
         let start_time = Instant::now();
@@ -2792,7 +3159,7 @@ impl Renderer {
         );
 
         // Spawn background render thread
-        std::thread::spawn(move || {
+        self.ivar_state.render_thread = Some(std::thread::spawn(move || {
             use rayon::prelude::*;
 
             // Process buckets in parallel
@@ -2819,7 +3186,7 @@ impl Renderer {
                     elapsed_secs: elapsed,
                 });
             }
-        });
+        }));
     }
 
     /// Poll for Ivar bucket completion messages
@@ -2881,6 +3248,9 @@ impl Renderer {
         let show_ui = self.show_ui;
         let fps = self.fps;
         let camera = &self.camera;
+        let scene_cameras: Vec<&str> = self.scene.cameras.iter().map(|c| c.name.as_str()).collect();
+        let active_camera = self.active_camera().map(str::to_string);
+        let mut selected_camera = active_camera.clone();
         let num_instances = self.num_instances;
         let visible_instances = self.visible_instance_count;
         let lod_box_instances = self.lod_box_instance_count;
@@ -2945,9 +3315,9 @@ impl Renderer {
                                     ui.label("Building scene geometry...");
                                 });
                                 ui.label(format!(
-                                    "{} instances, {} tris/instance",
-                                    self.instance_transforms.len(),
-                                    self.mesh_data.indices.len() / 3
+                                    "{} prototypes, {} instances",
+                                    self.scene.prototype_count(),
+                                    self.scene.instance_count()
                                 ));
                             }
                             BuildStatus::Failed => {
@@ -3046,6 +3416,24 @@ impl Renderer {
 
                     // Camera Stats
                     ui.collapsing("Camera", |ui| {
+                        // Look through a scene camera or orbit freely
+                        if !scene_cameras.is_empty() {
+                            ui.horizontal(|ui| {
+                                ui.label("View:");
+                                egui::ComboBox::from_id_salt("scene_camera")
+                                    .selected_text(selected_camera.as_deref().unwrap_or("Free"))
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(&mut selected_camera, None, "Free");
+                                        for &name in &scene_cameras {
+                                            ui.selectable_value(
+                                                &mut selected_camera,
+                                                Some(name.to_string()),
+                                                name,
+                                            );
+                                        }
+                                    });
+                            });
+                        }
                         ui.label(format!(
                             "Position: ({:.2}, {:.2}, {:.2})",
                             camera.position.x, camera.position.y, camera.position.z
@@ -3172,6 +3560,11 @@ impl Renderer {
         // Update LOD max polys from UI
         self.lod_max_polys = lod_max_polys;
 
+        // Look through the scene camera picked in the UI
+        if selected_camera != active_camera {
+            self.set_active_camera(selected_camera.as_deref());
+        }
+
         // Update render mode from UI - detect mode change
         let mode_changed = self.ivar_state.mode != render_mode;
         self.ivar_state.mode = render_mode;
@@ -3330,6 +3723,14 @@ impl Renderer {
                             0..self.lod_box_instance_count,
                         );
                     }
+
+                    // Draw particles as sphere sprites (6 vertices per point)
+                    if self.num_points > 0 {
+                        render_pass.set_pipeline(&self.points_pipeline);
+                        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+                        render_pass.set_vertex_buffer(0, self.point_buffer.slice(..));
+                        render_pass.draw(0..6, 0..self.num_points);
+                    }
                 }
 
                 // Render gnomon in bottom-right corner
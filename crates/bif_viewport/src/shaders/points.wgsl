@@ -0,0 +1,61 @@
+// Point sprite shader for particles (UsdGeomPoints)
+// Each point is a camera-facing quad shaded as a sphere impostor
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    view: mat4x4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct PointInput {
+    @location(0) center_radius: vec4<f32>,  // World-space center + radius
+    @location(1) color: vec4<f32>,          // RGB + padding
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) corner: vec2<f32>,  // Quad corner in [-1, 1]
+    @location(1) color: vec3<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32, in: PointInput) -> VertexOutput {
+    // Two triangles per quad
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+    let corner = corners[vertex_index];
+
+    // Camera right/up in world space are the first two rows of the view matrix
+    let right = vec3<f32>(camera.view[0][0], camera.view[1][0], camera.view[2][0]);
+    let up = vec3<f32>(camera.view[0][1], camera.view[1][1], camera.view[2][1]);
+    let world_position = in.center_radius.xyz
+        + (corner.x * right + corner.y * up) * in.center_radius.w;
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(world_position, 1.0);
+    out.corner = corner;
+    out.color = in.color.rgb;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Cut the quad to a disc
+    let r2 = dot(in.corner, in.corner);
+    if (r2 > 1.0) {
+        discard;
+    }
+
+    // View-space sphere normal, lit by a camera headlight
+    let n_dot_v = sqrt(1.0 - r2);
+    let ambient = 0.15;
+    return vec4<f32>(in.color * (ambient + (1.0 - ambient) * n_dot_v), 1.0);
+}